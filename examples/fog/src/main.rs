@@ -43,8 +43,11 @@ pub async fn run() {
 
     // Fog
     let mut fog_effect = FogEffect {
-        color: Srgba::new_opaque(200, 200, 200),
-        density: 0.1,
+        fog: Some(Fog {
+            color: Srgba::new_opaque(200, 200, 200),
+            falloff: FogFalloff::ExponentialSquared { density: 0.1 },
+            ..Default::default()
+        }),
         animation: 0.1,
         ..Default::default()
     };
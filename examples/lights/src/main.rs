@@ -107,6 +107,7 @@ pub async fn run() {
                     constant,
                     linear,
                     quadratic,
+                    ..Default::default()
                 },
             );
             light.update(0.00005 * size.magnitude() * frame_input.elapsed_time as f32);
@@ -1,10 +1,11 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use three_d::{renderer::*, WindowedContext};
 
 struct Scene {
     camera: Camera,
-    model: Gm<Mesh, ColorMaterial>,
+    model: Gm<Rc<Mesh>, ColorMaterial>,
 }
 
 pub fn main() {
@@ -55,14 +56,35 @@ pub fn main() {
                 .with_prevent_default(true)
         };
         let window = window_builder.build(&event_loop).unwrap();
-        let context = WindowedContext::from_winit_window(
-            &window,
-            three_d::SurfaceSettings {
-                vsync: false, // Wayland hangs in swap_buffers when one window is minimized or occluded
-                ..three_d::SurfaceSettings::default()
+        let settings = three_d::SurfaceSettings {
+            vsync: false, // Wayland hangs in swap_buffers when one window is minimized or occluded
+            ..three_d::SurfaceSettings::default()
+        };
+        // The following windows share resources with the first window, so the mesh is only uploaded to the GPU once.
+        // Sharing is not supported on web, so there each window creates its own mesh.
+        let shared = windows.values().next().map(
+            |(_, context, _, scene): &(_, WindowedContext, _, Scene)| {
+                (context, scene.model.geometry.clone())
             },
-        )
-        .unwrap();
+        );
+        let new_context = || {
+            let context = WindowedContext::from_winit_window(&window, settings).unwrap();
+            let mesh = Rc::new(Mesh::new(&context, &cpu_mesh));
+            (context, mesh)
+        };
+        let (context, mesh) = match shared {
+            Some((shared_context, mesh)) => {
+                match WindowedContext::from_winit_window_with_shared_context(
+                    &window,
+                    settings,
+                    shared_context,
+                ) {
+                    Ok(context) => (context, mesh),
+                    Err(_) => new_context(),
+                }
+            }
+            None => new_context(),
+        };
 
         let camera = Camera::new_perspective(
             Viewport::new_at_origo(1, 1),
@@ -74,8 +96,7 @@ pub fn main() {
             10.0,
         );
 
-        let mut model = Gm::new(Mesh::new(&context, &cpu_mesh), ColorMaterial::default());
-        model.set_animation(|time| Mat4::from_angle_y(radians(time * 0.005)));
+        let model = Gm::new(mesh, ColorMaterial::default());
 
         let frame_input_generator = three_d::FrameInputGenerator::from_winit_window(&window);
         windows.insert(
@@ -103,7 +124,14 @@ pub fn main() {
                 let frame_input = frame_input_generator.generate(context);
 
                 scene.camera.set_viewport(frame_input.viewport);
-                scene.model.animate(frame_input.accumulated_time as f32);
+                // The mesh is shared between the windows, so the camera is rotated instead of the mesh
+                let angle = frame_input.accumulated_time as f32 * 0.005;
+                let distance = scene.camera.position().magnitude();
+                scene.camera.set_view(
+                    vec3(-angle.sin(), 0.0, angle.cos()) * distance,
+                    vec3(0.0, 0.0, 0.0),
+                    vec3(0.0, 1.0, 0.0),
+                );
                 frame_input
                     .screen()
                     .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
@@ -92,6 +92,7 @@ pub async fn run() {
             NormalDistributionFunction::TrowbridgeReitzGGX,
             GeometryFunction::SmithSchlickGGX,
         ),
+        ..Default::default()
     };
 
     let mut color_texture = Texture2D::new_empty::<[f16; 4]>(
@@ -0,0 +1,20 @@
+// Entry point for non-wasm
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::main]
+async fn main() {
+    run().await;
+}
+
+use three_d::*;
+
+pub async fn run() {
+    let mut viewer = Viewer::new("Viewer!").unwrap();
+
+    let mut loaded = three_d_asset::io::load_async(&["examples/assets/suzanne.obj"])
+        .await
+        .unwrap();
+    let model: CpuModel = loaded.deserialize("suzanne.obj").unwrap();
+    viewer.add_model(&model).unwrap();
+
+    viewer.run();
+}
@@ -0,0 +1,117 @@
+//!
+//! Converts a model, for example a glTF or OBJ file, into the `.3d` format which three-d can load without parsing or decoding anything, see [serialize_model],
+//! and optionally generates levels of detail and an imposter texture for the model as part of the conversion.
+//!
+//! Build and run it with
+//! ```text
+//! cargo run --features asset-cli --bin three-d-assetc -- <input> [options]
+//! ```
+//!
+
+use three_d::*;
+
+const USAGE: &str = "Usage: three-d-assetc <input> [options]
+
+Converts the model in the input file (glTF, GLB or OBJ) into the .3d format, including all textures.
+
+Options:
+  -o, --output <path>          The output file, defaults to the input file with the extension .3d
+      --lod <count>            Generate levels of detail, the number of levels includes the original
+      --lod-reduction <ratio>  The number of triangles in each level relative to the previous level [default: 0.5]
+      --lod-min-triangles <n>  Do not generate levels with fewer triangles than this [default: 32]
+      --imposter <size>        Render an imposter texture with the given maximum size
+  -h, --help                   Print this help
+
+Compressing meshes with Draco is not supported, since three-d cannot load that format.
+Textures are not compressed, but block compressed KTX2 and DDS files created by for example toktx or basisu
+can be loaded separately using CpuCompressedTexture::deserialize.";
+
+struct Options {
+    input: std::path::PathBuf,
+    output: std::path::PathBuf,
+    lod: Option<LodGenerationSettings>,
+    imposter_size: Option<u32>,
+}
+
+fn main() {
+    let options = match parse_options(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = run(&options) {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut input = None;
+    let mut output = None;
+    let mut lod_count = None;
+    let mut lod = LodGenerationSettings::default();
+    let mut imposter_size = None;
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("missing value for {}", name));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => output = Some(value(&arg)?.into()),
+            "--lod" => lod_count = Some(parse(&arg, &value(&arg)?)?),
+            "--lod-reduction" => lod.reduction = parse(&arg, &value(&arg)?)?,
+            "--lod-min-triangles" => lod.min_triangle_count = parse(&arg, &value(&arg)?)?,
+            "--imposter" => imposter_size = Some(parse(&arg, &value(&arg)?)?),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ if input.is_none() => input = Some(std::path::PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+    let input = input.ok_or("missing input file")?;
+    Ok(Some(Options {
+        output: output.unwrap_or_else(|| input.with_extension("3d")),
+        input,
+        lod: lod_count.map(|level_count| LodGenerationSettings { level_count, ..lod }),
+        imposter_size,
+    }))
+}
+
+fn parse<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value {} for {}", value, name))
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn std::error::Error>> {
+    let mut loaded = three_d_asset::io::load(&[&options.input])?;
+    let mut cpu_model: CpuModel = loaded.deserialize(&options.input)?;
+    if let Some(settings) = &options.lod {
+        generate_lod_levels(&mut cpu_model, settings);
+    }
+    if let Some(max_texture_size) = options.imposter_size {
+        let context = HeadlessContext::new()?;
+        let ambient = AmbientLight::new(&context, 0.4, Srgba::WHITE);
+        let directional =
+            DirectionalLight::new(&context, 2.0, Srgba::WHITE, &vec3(-1.0, -1.0, -1.0));
+        generate_imposter_texture(
+            &context,
+            &mut cpu_model,
+            &[&ambient, &directional],
+            max_texture_size,
+        )?;
+    }
+    let bytes = serialize_model(&cpu_model);
+    std::fs::write(&options.output, &bytes)?;
+    println!(
+        "wrote {} primitives and {} materials to {} ({} bytes)",
+        cpu_model.geometries.len(),
+        cpu_model.materials.len(),
+        options.output.display(),
+        bytes.len()
+    );
+    Ok(())
+}
@@ -28,6 +28,17 @@ mod program;
 #[doc(inline)]
 pub use program::*;
 
+mod program_binary;
+#[doc(inline)]
+pub use program_binary::*;
+
+mod shader_compile_thread;
+use shader_compile_thread::*;
+
+mod resources;
+#[doc(inline)]
+pub use resources::*;
+
 mod scissor_box;
 #[doc(inline)]
 pub use scissor_box::*;
@@ -59,6 +70,12 @@ pub enum CoreError {
     ShaderCompilation(String, String, String),
     #[error("failed to link shader program: {0}")]
     ShaderLink(String),
+    #[error("failed creating {0}: {1}")]
+    ResourceCreation(String, String),
+    #[error("invalid program cache: {0}")]
+    InvalidProgramCache(String),
+    #[error("invalid compressed texture: {0}")]
+    InvalidCompressedTexture(String),
 }
 
 pub(crate) fn full_screen_draw(
@@ -66,14 +83,29 @@ pub(crate) fn full_screen_draw(
     program: &Program,
     render_states: RenderStates,
     viewport: Viewport,
+) {
+    draw_without_attributes(context, program, render_states, viewport, 3);
+}
+
+///
+/// Draws `count` vertices, ie. `count / 3` triangles, using a vertex shader that computes the vertex positions from `gl_VertexID` without any vertex attributes.
+///
+pub(crate) fn draw_without_attributes(
+    context: &Context,
+    program: &Program,
+    render_states: RenderStates,
+    viewport: Viewport,
+    count: u32,
 ) {
     unsafe { context.bind_vertex_array(Some(context.vao)) };
-    program.draw_arrays(render_states, viewport, 3);
+    program.draw_arrays(render_states, viewport, count);
 }
 
 pub(crate) fn full_screen_vertex_shader_source() -> &'static str {
     "
         out vec2 uvs;
+        out vec2 uvs1;
+        flat out float texture_layer;
         out vec4 col;
         void main()
         {
@@ -86,6 +118,8 @@ pub(crate) fn full_screen_vertex_shader_source() -> &'static str {
             vec3 position = vertices[gl_VertexID];
 
             uvs = 0.5 * position.xy + 0.5;
+            uvs1 = uvs;
+            texture_layer = 0.0;
             col = vec4(1.0);
             gl_Position = vec4(position, 1.0);
         }
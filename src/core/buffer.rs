@@ -49,10 +49,12 @@ struct Buffer {
     data_type: u32,
     data_size: u32,
     normalized: bool,
+    capacity: usize,
 }
 
 impl Buffer {
     pub fn new(context: &Context) -> Self {
+        context.resources.created(ResourceKind::Buffer);
         Self {
             context: context.clone(),
             id: unsafe { context.create_buffer().expect("Failed creating buffer") },
@@ -60,10 +62,12 @@ impl Buffer {
             data_type: 0,
             data_size: 0,
             normalized: false,
+            capacity: 0,
         }
     }
 
     pub fn new_with_data<T: BufferDataType>(context: &Context, data: &[T]) -> Self {
+        span!(TRACE, "create_buffer", count = data.len());
         let mut buffer = Self::new(context);
         if !data.is_empty() {
             buffer.fill(data);
@@ -72,23 +76,83 @@ impl Buffer {
     }
 
     pub fn fill<T: BufferDataType>(&mut self, data: &[T]) {
+        self.fill_internal(data, T::normalized());
+    }
+
+    pub fn fill_normalized<T: BufferDataType>(&mut self, data: &[T]) {
+        self.fill_internal(data, true);
+    }
+
+    fn fill_internal<T: BufferDataType>(&mut self, data: &[T], normalized: bool) {
+        let bytes = to_byte_slice(data);
         self.bind();
         unsafe {
-            self.context.buffer_data_u8_slice(
-                crate::context::ARRAY_BUFFER,
-                to_byte_slice(data),
-                if self.attribute_count > 0 {
-                    crate::context::DYNAMIC_DRAW
-                } else {
-                    crate::context::STATIC_DRAW
-                },
-            );
+            if self.attribute_count > 0
+                && bytes.len() <= self.capacity
+                && bytes.len() >= self.capacity / 4
+            {
+                // Orphan the current storage, so the driver can hand out new memory of the same size
+                // instead of waiting for the draw calls using the current data to finish
+                self.context.buffer_data_size(
+                    crate::context::ARRAY_BUFFER,
+                    self.capacity as i32,
+                    crate::context::DYNAMIC_DRAW,
+                );
+                self.context
+                    .buffer_sub_data_u8_slice(crate::context::ARRAY_BUFFER, 0, bytes);
+            } else {
+                self.context.buffer_data_u8_slice(
+                    crate::context::ARRAY_BUFFER,
+                    bytes,
+                    if self.attribute_count > 0 {
+                        crate::context::DYNAMIC_DRAW
+                    } else {
+                        crate::context::STATIC_DRAW
+                    },
+                );
+                self.capacity = bytes.len();
+            }
             self.context.bind_buffer(crate::context::ARRAY_BUFFER, None);
         }
         self.attribute_count = data.len() as u32;
         self.data_type = T::data_type();
         self.data_size = T::size();
-        self.normalized = T::normalized();
+        self.normalized = normalized;
+    }
+
+    pub fn update_subset<T: BufferDataType>(&mut self, offset: u32, data: &[T]) {
+        if T::data_type() != self.data_type || T::size() != self.data_size {
+            panic!("the data used to update a subset of a buffer must be of the same type as the data in the buffer");
+        }
+        if offset as usize + data.len() > self.attribute_count as usize {
+            panic!(
+                "the subset with offset {} and length {} is outside the buffer with length {}",
+                offset,
+                data.len(),
+                self.attribute_count
+            );
+        }
+        self.bind();
+        unsafe {
+            self.context.buffer_sub_data_u8_slice(
+                crate::context::ARRAY_BUFFER,
+                (offset as usize * std::mem::size_of::<T>()) as i32,
+                to_byte_slice(data),
+            );
+            self.context.bind_buffer(crate::context::ARRAY_BUFFER, None);
+        }
+    }
+
+    pub fn read<T: BufferDataType>(&self) -> Vec<T> {
+        let count = (self.attribute_count * self.data_size / T::size()) as usize;
+        let mut data = vec![0u8; count * std::mem::size_of::<T>()];
+        self.bind();
+        unsafe {
+            self.context
+                .get_buffer_sub_data(crate::context::ARRAY_BUFFER, 0, &mut data);
+            self.context.bind_buffer(crate::context::ARRAY_BUFFER, None);
+        }
+        from_byte_slice(&data).to_vec()
     }
 
     pub fn attribute_count(&self) -> u32 {
@@ -107,6 +171,7 @@ impl Drop for Buffer {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_buffer(self.id);
+            self.context.resources.released(ResourceKind::Buffer);
         }
     }
 }
@@ -41,6 +41,7 @@ impl ElementBuffer {
     ///
     pub fn new(context: &Context) -> Self {
         let id = unsafe { context.create_buffer().expect("Failed creating buffer") };
+        context.resources.created(ResourceKind::Buffer);
         Self {
             context: context.clone(),
             id,
@@ -53,6 +54,7 @@ impl ElementBuffer {
     /// Creates a new element buffer and fills it with the given indices which must be divisable by 3.
     ///
     pub fn new_with_data<T: ElementBufferDataType>(context: &Context, data: &[T]) -> Self {
+        span!(TRACE, "create_element_buffer", count = data.len());
         let mut buffer = Self::new(context);
         if !data.is_empty() {
             buffer.fill(data);
@@ -108,6 +110,7 @@ impl Drop for ElementBuffer {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_buffer(self.id);
+            self.context.resources.released(ResourceKind::Buffer);
         }
     }
 }
@@ -33,10 +33,24 @@ impl InstanceBuffer {
     /// Fills the instance buffer with the given data. The data should be in the same format as specified in the shader.
     /// As an example, if specified as `vec3` in the shader it needs to be specified as an array of `Vector3<T>` where `T` is a primitive type that implements [BufferDataType], for example can be f16 or f32.
     ///
+    /// Refilling a buffer with data of about the same size reuses the memory on the GPU and does not wait for the draw calls using the previous data to finish,
+    /// so it is efficient to refill the buffer every frame, for example with the positions of animated geometry. Use [InstanceBuffer::update_subset] to only update some of the values.
+    ///
     pub fn fill<T: BufferDataType>(&mut self, data: &[T]) {
         self.buffer.fill(data)
     }
 
+    ///
+    /// Updates the values starting at the given offset, ie. the index of the first instance to update, with the given data, without changing the rest of the buffer.
+    /// This is more efficient than refilling the whole buffer when only a part of it changes each frame, for example the newest points of a trail or the particles which are respawned.
+    ///
+    /// # Panic
+    /// Will panic if the data is not of the same type as the data in the buffer or if the values to update are outside the buffer.
+    ///
+    pub fn update_subset<T: BufferDataType>(&mut self, offset: u32, data: &[T]) {
+        self.buffer.update_subset(offset, data);
+    }
+
     ///
     /// The number of values in the buffer.
     ///
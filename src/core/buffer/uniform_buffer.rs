@@ -7,7 +7,7 @@ use crate::core::*;
 pub struct UniformBuffer {
     context: Context,
     id: crate::context::Buffer,
-    offsets: Vec<usize>,
+    variables: Vec<(usize, usize)>,
     data: Vec<f32>,
 }
 
@@ -18,20 +18,32 @@ impl UniformBuffer {
     /// The first with 3 elements (a [Vec3]), the second with 1 element (a `f32`), the third with four elements (a [Vec4]) and the last with 16 elements (a [Mat4]).
     /// The variables are initialized to 0.
     ///
+    /// The variables are laid out according to the `std140` layout, so the buffer matches a uniform block declared with `layout (std140)` in the shader
+    /// containing the variables in the same order. That is, a variable with 2 elements is aligned to 8 bytes and a variable with 3 or more elements is aligned to 16 bytes,
+    /// which means that variables with more than 4 elements must be a [Mat4] or an array of [Vec4]s.
+    ///
     pub fn new(context: &Context, sizes: &[u32]) -> UniformBuffer {
         let id = unsafe { context.create_buffer().expect("Failed creating buffer") };
+        context.resources.created(ResourceKind::Buffer);
 
-        let mut offsets = Vec::new();
-        let mut length = 0;
+        let mut variables = Vec::new();
+        let mut length: usize = 0;
         for size in sizes {
-            offsets.push(length);
+            let alignment = match size {
+                1 => 1,
+                2 => 2,
+                _ => 4,
+            };
+            length = length.div_ceil(alignment) * alignment;
+            variables.push((length, *size as usize));
             length += *size as usize;
         }
         let buffer = UniformBuffer {
             context: context.clone(),
             id,
-            offsets,
-            data: vec![0.0; length],
+            variables,
+            // The size of a uniform block is rounded up to a multiple of 16 bytes
+            data: vec![0.0; length.div_ceil(4) * 4],
         };
         buffer.send();
         buffer
@@ -46,6 +58,7 @@ impl UniformBuffer {
 
     ///
     /// Update the values of the variable at the given index with the given data.
+    /// Only the values of that variable are sent to the GPU and nothing is sent if the values are unchanged.
     ///
     /// # Panic
     /// Will panic if the index is not in the range `[0-max]` where `max` is the length of the `sizes` argument given at construction.
@@ -61,17 +74,27 @@ impl UniformBuffer {
                     length,
                 );
             }
-            self.data
-                .splice(offset..offset + length, data.iter().cloned());
-            self.send();
+            if self.data[offset..offset + length] != *data {
+                self.data[offset..offset + length].copy_from_slice(data);
+                unsafe {
+                    self.context
+                        .bind_buffer(crate::context::UNIFORM_BUFFER, Some(self.id));
+                    self.context.buffer_sub_data_u8_slice(
+                        crate::context::UNIFORM_BUFFER,
+                        offset as i32 * 4,
+                        to_byte_slice(data),
+                    );
+                    self.context
+                        .bind_buffer(crate::context::UNIFORM_BUFFER, None);
+                }
+            }
         } else {
             panic!(
                 "the index {} is outside the expected range [0, {}]",
                 index,
-                self.offsets.len() - 1
+                self.variables.len() - 1
             );
         }
-        //TODO: Send to GPU (contextBufferSubData)
     }
 
     ///
@@ -83,17 +106,7 @@ impl UniformBuffer {
     }
 
     fn offset_length(&self, index: usize) -> Option<(usize, usize)> {
-        if index >= self.offsets.len() {
-            None
-        } else {
-            let offset = self.offsets[index];
-            let length = if index + 1 == self.offsets.len() {
-                self.data.len()
-            } else {
-                self.offsets[index + 1]
-            } - offset;
-            Some((offset, length))
-        }
+        self.variables.get(index).copied()
     }
 
     fn send(&self) {
@@ -115,6 +128,7 @@ impl Drop for UniformBuffer {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_buffer(self.id);
+            self.context.resources.released(ResourceKind::Buffer);
         }
     }
 }
@@ -29,14 +29,48 @@ impl VertexBuffer {
         }
     }
 
+    ///
+    /// Creates a new vertex buffer and fills it with the given integer data which is normalized when read in the shader,
+    /// ie. unsigned integers are mapped to the range `[0..1]` and signed integers to the range `[-1..1]`.
+    /// As an example, a `vec3` in the shader can be specified as an array of `Vector3<i16>`, which for example is useful for storing normals using less memory.
+    ///
+    pub fn new_with_normalized_data<T: BufferDataType>(context: &Context, data: &[T]) -> Self {
+        let mut buffer = Buffer::new(context);
+        if !data.is_empty() {
+            buffer.fill_normalized(data);
+        }
+        Self { buffer }
+    }
+
+    ///
+    /// Fills the vertex buffer with the given integer data which is normalized when read in the shader, see [VertexBuffer::new_with_normalized_data].
+    ///
+    pub fn fill_normalized<T: BufferDataType>(&mut self, data: &[T]) {
+        self.buffer.fill_normalized(data);
+    }
+
     ///
     /// Fills the vertex buffer with the given data. The data should be in the same format as specified in the shader.
     /// As an example, if specified as `vec3` in the shader it needs to be specified as an array of `Vector3<T>` where `T` is a primitive type that implements [BufferDataType], for example can be f16 or f32.
     ///
+    /// Refilling a buffer with data of about the same size reuses the memory on the GPU and does not wait for the draw calls using the previous data to finish,
+    /// so it is efficient to refill the buffer every frame, for example with the positions of animated geometry. Use [VertexBuffer::update_subset] to only update some of the values.
+    ///
     pub fn fill<T: BufferDataType>(&mut self, data: &[T]) {
         self.buffer.fill(data);
     }
 
+    ///
+    /// Updates the values starting at the given offset, ie. the index of the first vertex to update, with the given data, without changing the rest of the buffer.
+    /// This is more efficient than refilling the whole buffer when only a part of it changes each frame, for example the newest points of a trail or the particles which are respawned.
+    ///
+    /// # Panic
+    /// Will panic if the data is not of the same type as the data in the buffer or if the values to update are outside the buffer.
+    ///
+    pub fn update_subset<T: BufferDataType>(&mut self, offset: u32, data: &[T]) {
+        self.buffer.update_subset(offset, data);
+    }
+
     ///
     /// The number of values in the buffer.
     ///
@@ -51,6 +85,20 @@ impl VertexBuffer {
         self.buffer.attribute_count()
     }
 
+    ///
+    /// Returns the data in the buffer, for example the result of a simulation using [Program::transform_feedback].
+    /// The data type must have the same size as the data in the buffer, for example `Vec3` if the buffer contains `vec3` values.
+    ///
+    /// **Note:** This waits for the GPU to finish writing to the buffer, so it should only be used when necessary.
+    ///
+    pub fn read<T: BufferDataType>(&self) -> Vec<T> {
+        self.buffer.read()
+    }
+
+    pub(in crate::core) fn id(&self) -> crate::context::Buffer {
+        self.buffer.id
+    }
+
     pub(in crate::core) fn bind(&self) {
         self.buffer.bind();
     }
@@ -17,8 +17,21 @@ pub struct Context {
     pub(super) vao: crate::context::VertexArray,
     /// A cache of programs to avoid recompiling a [Program] every frame.
     pub programs: Arc<RwLock<HashMap<Vec<u8>, Program>>>,
+    pub(super) program_binary_functions: Option<Arc<ProgramBinaryFunctions>>,
+    /// The program binaries loaded by [Context::load_program_cache] identified by the hash of their shader sources, see [Program::from_source].
+    pub(super) program_binaries: Arc<RwLock<HashMap<u64, ProgramBinary>>>,
+    /// The uniform buffers which are shared between all programs, see [Program::use_shared_uniform_block].
+    pub(super) uniform_buffers: Arc<RwLock<HashMap<String, UniformBuffer>>>,
+    pub(crate) resources: Arc<ResourceRegistry>,
+    pub(super) shader_compile_thread: Option<Arc<ShaderCompileThread>>,
+    #[cfg(target_arch = "wasm32")]
+    screen_framebuffer: std::rc::Rc<std::cell::RefCell<Option<ScreenFramebuffer>>>,
 }
 
+/// Binds an external framebuffer, which is used instead of the default framebuffer as the screen, to the given target.
+#[cfg(target_arch = "wasm32")]
+pub(crate) type ScreenFramebuffer = Box<dyn Fn(&crate::context::Context, u32)>;
+
 impl Context {
     ///
     /// Creates a new mid-level context, used in this [core](crate::core) module, from a low-level OpenGL/WebGL context from the [context](crate::context) module.
@@ -44,11 +57,99 @@ impl Context {
                 context,
                 vao,
                 programs: Arc::new(RwLock::new(HashMap::new())),
+                program_binary_functions: None,
+                program_binaries: Arc::new(RwLock::new(HashMap::new())),
+                uniform_buffers: Arc::new(RwLock::new(HashMap::new())),
+                resources: Arc::new(ResourceRegistry::default()),
+                shader_compile_thread: None,
+                #[cfg(target_arch = "wasm32")]
+                screen_framebuffer: std::rc::Rc::new(std::cell::RefCell::new(None)),
             }
         };
         Ok(c)
     }
 
+    ///
+    /// Returns the maximum number of samples supported for multisampled render targets (see [RenderTargetMultisample]).
+    /// A value of 0 or 1 means that multisample anti-aliasing is not available, in which case a post-processing anti-aliasing, for example the `FxaaEffect`, can be used instead.
+    ///
+    pub fn max_samples(&self) -> u32 {
+        unsafe { self.get_parameter_i32(crate::context::MAX_SAMPLES) as u32 }
+    }
+
+    ///
+    /// Returns whether the graphics driver supports compiling shader programs in parallel with rendering
+    /// through the `KHR_parallel_shader_compile` extension (see [PendingProgram::is_ready]).
+    ///
+    pub fn supports_parallel_shader_compile(&self) -> bool {
+        let extensions = self.supported_extensions();
+        extensions.contains("GL_KHR_parallel_shader_compile")
+            || extensions.contains("KHR_parallel_shader_compile")
+            || extensions.contains("GL_ARB_parallel_shader_compile")
+    }
+
+    ///
+    /// Returns whether or not anisotropic filtering is supported, see [SamplerSettings::anisotropy].
+    ///
+    pub fn supports_anisotropic_filtering(&self) -> bool {
+        let extensions = self.supported_extensions();
+        extensions.contains("GL_EXT_texture_filter_anisotropic")
+            || extensions.contains("EXT_texture_filter_anisotropic")
+            || extensions.contains("GL_ARB_texture_filter_anisotropic")
+    }
+
+    ///
+    /// Returns whether or not textures compressed with the given [CompressedFormat] can be uploaded to the GPU without decompression,
+    /// see [Texture2D::new_compressed]. If `srgb` is true, it also checks support for the sRGB variant of the format.
+    ///
+    pub fn supports_compressed_format(&self, format: CompressedFormat, srgb: bool) -> bool {
+        let extensions = self.supported_extensions();
+        let has =
+            |name: &str| extensions.contains(&format!("GL_{}", name)) || extensions.contains(name);
+        match format {
+            CompressedFormat::Bc1 | CompressedFormat::Bc2 | CompressedFormat::Bc3 => {
+                (has("EXT_texture_compression_s3tc") || has("WEBGL_compressed_texture_s3tc"))
+                    && (!srgb
+                        || has("EXT_texture_sRGB")
+                        || has("EXT_texture_compression_s3tc_srgb")
+                        || has("WEBGL_compressed_texture_s3tc_srgb"))
+            }
+            CompressedFormat::Bc4 | CompressedFormat::Bc5 => {
+                // RGTC is part of core desktop OpenGL since version 3.0
+                !self.version().is_embedded
+                    || has("EXT_texture_compression_rgtc")
+                    || has("ARB_texture_compression_rgtc")
+            }
+        }
+    }
+
+    ///
+    /// Binds the framebuffer with the given id to the given target, or the framebuffer which is used as the screen if the id is `None`, see [RenderTarget::screen].
+    ///
+    pub(super) unsafe fn bind_target_framebuffer(
+        &self,
+        target: u32,
+        id: Option<crate::context::Framebuffer>,
+    ) {
+        #[cfg(target_arch = "wasm32")]
+        if id.is_none() {
+            if let Some(bind) = self.screen_framebuffer.borrow().as_ref() {
+                bind(&self.context, target);
+                return;
+            }
+        }
+        self.bind_framebuffer(target, id);
+    }
+
+    ///
+    /// Replaces the default framebuffer, which is used as the screen, with an external framebuffer, for example the framebuffer of a WebXR layer,
+    /// or use `None` to go back to the default framebuffer.
+    ///
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn set_screen_framebuffer(&self, screen_framebuffer: Option<ScreenFramebuffer>) {
+        *self.screen_framebuffer.borrow_mut() = screen_framebuffer;
+    }
+
     ///
     /// Set the scissor test for this context (see [ScissorBox]).
     ///
@@ -1,5 +1,7 @@
 use crate::core::*;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::sync::mpsc::TryRecvError;
 use std::sync::RwLock;
 
 ///
@@ -9,12 +11,13 @@ use std::sync::RwLock;
 /// and execute the shader program (see the draw functionality).
 ///
 pub struct Program {
-    context: Context,
-    id: crate::context::Program,
+    pub(super) context: Context,
+    pub(super) id: crate::context::Program,
     attributes: HashMap<String, u32>,
     textures: RwLock<HashMap<String, u32>>,
     uniforms: HashMap<String, crate::context::UniformLocation>,
     uniform_blocks: RwLock<HashMap<String, (u32, u32)>>,
+    pub(super) source_hash: Option<u64>,
 }
 
 impl Program {
@@ -26,109 +29,80 @@ impl Program {
         vertex_shader_source: &str,
         fragment_shader_source: &str,
     ) -> Result<Self, CoreError> {
-        unsafe {
-            let vert_shader = context
-                .create_shader(crate::context::VERTEX_SHADER)
-                .expect("Failed creating vertex shader");
-            let frag_shader = context
-                .create_shader(crate::context::FRAGMENT_SHADER)
-                .expect("Failed creating fragment shader");
-
-            let header: &str = if context.version().is_embedded {
-                "#version 300 es
-                    #ifdef GL_FRAGMENT_PRECISION_HIGH
-                        precision highp float;
-                        precision highp int;
-                        precision highp sampler2DArray;
-                        precision highp sampler3D;
-                    #else
-                        precision mediump float;
-                        precision mediump int;
-                        precision mediump sampler2DArray;
-                        precision mediump sampler3D;
-                    #endif\n"
-            } else {
-                "#version 330 core\n"
-            };
-            let vertex_shader_source = format!("{}{}", header, vertex_shader_source);
-            let fragment_shader_source = format!("{}{}", header, fragment_shader_source);
-
-            context.shader_source(vert_shader, &vertex_shader_source);
-            context.shader_source(frag_shader, &fragment_shader_source);
-            context.compile_shader(vert_shader);
-            context.compile_shader(frag_shader);
-
-            let id = context.create_program().expect("Failed creating program");
-            context.attach_shader(id, vert_shader);
-            context.attach_shader(id, frag_shader);
-            context.link_program(id);
-
-            if !context.get_program_link_status(id) {
-                let log = context.get_shader_info_log(vert_shader);
-                if !log.is_empty() {
-                    Err(CoreError::ShaderCompilation(
-                        "vertex".to_string(),
-                        log,
-                        vertex_shader_source,
-                    ))?;
-                }
-                let log = context.get_shader_info_log(frag_shader);
-                if !log.is_empty() {
-                    Err(CoreError::ShaderCompilation(
-                        "fragment".to_string(),
-                        log,
-                        fragment_shader_source,
-                    ))?;
-                }
-                let log = context.get_program_info_log(id);
-                if !log.is_empty() {
-                    Err(CoreError::ShaderLink(log))?;
-                }
-                unreachable!();
-            }
+        PendingProgram::start(
+            context,
+            vertex_shader_source,
+            fragment_shader_source,
+            &[],
+            false,
+        )?
+        .finish()
+    }
 
-            context.detach_shader(id, vert_shader);
-            context.detach_shader(id, frag_shader);
-            context.delete_shader(vert_shader);
-            context.delete_shader(frag_shader);
+    ///
+    /// Creates a new shader program from the given vertex and fragment glsl shader source where the given outputs of the vertex shader
+    /// are captured using transform feedback, see [Program::transform_feedback].
+    /// The outputs are the names of `out` variables of the vertex shader, for example `float`, `vec3` or `flat ivec2`.
+    /// The fragment shader is never executed when capturing the outputs, but is still required, so it can be as simple as `void main() {}`.
+    ///
+    pub fn from_source_with_transform_feedback(
+        context: &Context,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        outputs: &[&str],
+    ) -> Result<Self, CoreError> {
+        PendingProgram::start(
+            context,
+            vertex_shader_source,
+            fragment_shader_source,
+            outputs,
+            false,
+        )?
+        .finish()
+    }
 
-            // Init vertex attributes
-            let num_attribs = context.get_active_attributes(id);
-            let mut attributes = HashMap::new();
-            for i in 0..num_attribs {
-                if let Some(crate::context::ActiveAttribute { name, .. }) = context
-                    .get_active_attribute(id, i)
-                    .filter(|a| !a.name.starts_with("gl_"))
-                {
-                    if let Some(location) = context.get_attrib_location(id, &name) {
-                        attributes.insert(name, location);
-                    }
+    pub(super) unsafe fn from_linked_program(
+        context: &Context,
+        id: crate::context::Program,
+    ) -> Self {
+        context.resources.created(ResourceKind::Program);
+        // Init vertex attributes
+        let num_attribs = context.get_active_attributes(id);
+        let mut attributes = HashMap::new();
+        for i in 0..num_attribs {
+            if let Some(crate::context::ActiveAttribute { name, .. }) = context
+                .get_active_attribute(id, i)
+                .filter(|a| !a.name.starts_with("gl_"))
+            {
+                if let Some(location) = context.get_attrib_location(id, &name) {
+                    attributes.insert(name, location);
                 }
             }
+        }
 
-            // Init uniforms
-            let num_uniforms = context.get_active_uniforms(id);
-            let mut uniforms = HashMap::new();
-            for i in 0..num_uniforms {
-                if let Some(crate::context::ActiveUniform { name, .. }) = context
-                    .get_active_uniform(id, i)
-                    .filter(|a| !a.name.starts_with("gl_"))
-                {
-                    if let Some(location) = context.get_uniform_location(id, &name) {
-                        let name = name.split('[').next().unwrap().to_string();
-                        uniforms.insert(name, location);
-                    }
+        // Init uniforms
+        let num_uniforms = context.get_active_uniforms(id);
+        let mut uniforms = HashMap::new();
+        for i in 0..num_uniforms {
+            if let Some(crate::context::ActiveUniform { name, .. }) = context
+                .get_active_uniform(id, i)
+                .filter(|a| !a.name.starts_with("gl_"))
+            {
+                if let Some(location) = context.get_uniform_location(id, &name) {
+                    let name = name.split('[').next().unwrap().to_string();
+                    uniforms.insert(name, location);
                 }
             }
+        }
 
-            Ok(Program {
-                context: context.clone(),
-                id,
-                attributes,
-                uniforms,
-                uniform_blocks: RwLock::new(HashMap::new()),
-                textures: RwLock::new(HashMap::new()),
-            })
+        Program {
+            context: context.clone(),
+            id,
+            attributes,
+            uniforms,
+            uniform_blocks: RwLock::new(HashMap::new()),
+            textures: RwLock::new(HashMap::new()),
+            source_hash: None,
         }
     }
 
@@ -322,6 +296,39 @@ impl Program {
         }
     }
 
+    ///
+    /// Uses the [UniformBuffer] with the given name which is shared between all programs created with the same context as the uniform block
+    /// with the same name in this program, see [Program::use_uniform_block], after updating it using the given closure.
+    /// The buffer is created with the given sizes the first time it is used, see [UniformBuffer::new], so the sizes must be the same each time.
+    ///
+    /// This avoids sending the same data to each program, for example the data of the camera which is used by almost all programs when rendering a frame,
+    /// since [UniformBuffer::update] only sends the values to the GPU when they change.
+    ///
+    /// # Panic
+    /// Will panic if the uniform block is not defined or not used in the shader code.
+    ///
+    pub fn use_shared_uniform_block(
+        &self,
+        name: &str,
+        sizes: &[u32],
+        update: impl FnOnce(&mut UniformBuffer),
+    ) {
+        let mut buffers = self.context.uniform_buffers.write().unwrap();
+        let buffer = buffers
+            .entry(name.to_owned())
+            .or_insert_with(|| UniformBuffer::new(&self.context, sizes));
+        update(buffer);
+        self.use_uniform_block(name, buffer);
+    }
+
+    ///
+    /// Returns true if this program uses the uniform block with the given name.
+    ///
+    pub fn requires_uniform_block(&self, name: &str) -> bool {
+        self.uniform_blocks.read().unwrap().contains_key(name)
+            || unsafe { self.context.get_uniform_block_index(self.id, name) }.is_some()
+    }
+
     ///
     /// Uses the given [VertexBuffer] data in this shader program and associates it with the given named variable.
     /// Each value in the buffer is used when rendering one vertex using the [Program::draw_arrays] or [Program::draw_elements] methods.
@@ -480,6 +487,63 @@ impl Program {
             .expect("Unexpected rendering error occured")
     }
 
+    ///
+    /// Runs the vertex shader of this program for `count` vertices without rasterizing anything and captures the outputs of the vertex shader,
+    /// which are given when creating the program using [Program::from_source_with_transform_feedback], into the given buffers,
+    /// one buffer for each output in the same order as the outputs.
+    /// Each buffer must already contain at least `count` values of the same type as the output, for example created using [VertexBuffer::new_with_data],
+    /// and the first `count` values are overwritten.
+    /// Requires that all attributes and uniforms have been defined using the use_attribute and use_uniform methods.
+    ///
+    /// This makes it possible to run simulations on the GPU also on web, for example of particles or cloth, by using the outputs of one step
+    /// as the input attributes of the next step, where two sets of buffers are swapped between each step, since a buffer cannot be both an input and an output.
+    /// The resulting buffers can be used as vertex attributes when rendering, for example to render the particles.
+    ///
+    /// # Panic
+    /// Will panic if a buffer contains less than `count` values.
+    ///
+    pub fn transform_feedback(&self, count: u32, outputs: &[&VertexBuffer]) {
+        assert!(
+            outputs.iter().all(|buffer| buffer.vertex_count() >= count),
+            "the buffers must contain at least {} values to capture the transform feedback",
+            count
+        );
+        self.use_program();
+        unsafe {
+            for (i, buffer) in outputs.iter().enumerate() {
+                self.context.bind_buffer_base(
+                    crate::context::TRANSFORM_FEEDBACK_BUFFER,
+                    i as u32,
+                    Some(buffer.id()),
+                );
+            }
+            self.context.enable(crate::context::RASTERIZER_DISCARD);
+            self.context
+                .begin_transform_feedback(crate::context::POINTS);
+            self.context
+                .draw_arrays(crate::context::POINTS, 0, count as i32);
+            self.context.end_transform_feedback();
+            self.context.disable(crate::context::RASTERIZER_DISCARD);
+            for i in 0..outputs.len() {
+                self.context.bind_buffer_base(
+                    crate::context::TRANSFORM_FEEDBACK_BUFFER,
+                    i as u32,
+                    None,
+                );
+            }
+            for location in self.attributes.values() {
+                self.context.disable_vertex_attrib_array(*location);
+            }
+            self.context.bind_vertex_array(None);
+        }
+        self.unuse_program();
+
+        #[cfg(debug_assertions)]
+        self.context
+            .error_check()
+            .expect("Unexpected error when capturing transform feedback")
+    }
+
     ///
     /// Draws the triangles defined by the given [ElementBuffer] with the given render states and viewport using this shader program.
     /// Requires that all attributes and uniforms have been defined using the use_attribute and use_uniform methods.
@@ -643,5 +707,328 @@ impl Drop for Program {
         unsafe {
             self.context.delete_program(self.id);
         }
+        self.context.resources.released(ResourceKind::Program);
+    }
+}
+
+///
+/// A shader program which is being compiled and linked.
+/// Starting the compilation of many programs before using any of them allows the programs to be compiled in parallel with rendering,
+/// either by the graphics driver when the `KHR_parallel_shader_compile` extension is available
+/// or by a separate thread when a shader compile thread is enabled (see [Context::enable_shader_compile_thread]).
+/// Use [PendingProgram::is_ready] to check whether the compilation is done and [PendingProgram::finish] to get the resulting [Program].
+///
+pub struct PendingProgram {
+    context: Context,
+    compilation: RefCell<Compilation>,
+    vertex_shader_source: String,
+    fragment_shader_source: String,
+    source_hash: u64,
+}
+
+/// The OpenGL objects of a program, which are created either by the context or by the shader compile thread.
+#[derive(Clone, Copy)]
+pub(super) struct ProgramObjects {
+    pub id: crate::context::Program,
+    pub vertex_shader: crate::context::Shader,
+    pub fragment_shader: crate::context::Shader,
+}
+
+enum Compilation {
+    Loaded(Box<Program>),
+    Started(ProgramObjects),
+    OnThread(CompileResult),
+    Failed(CoreError),
+    Finished,
+}
+
+impl PendingProgram {
+    ///
+    /// Starts compiling and linking a new shader program from the given vertex and fragment glsl shader source.
+    /// Returns an error if the shaders or the program could not be created, the compilation errors are returned by [PendingProgram::finish].
+    ///
+    pub fn new(
+        context: &Context,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+    ) -> Result<Self, CoreError> {
+        Self::new_with_transform_feedback(
+            context,
+            vertex_shader_source,
+            fragment_shader_source,
+            &[],
+        )
+    }
+
+    ///
+    /// Starts compiling and linking a new shader program from the given vertex and fragment glsl shader source where the given outputs of the vertex shader
+    /// are captured using transform feedback, see [Program::from_source_with_transform_feedback].
+    ///
+    pub fn new_with_transform_feedback(
+        context: &Context,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        transform_feedback_outputs: &[&str],
+    ) -> Result<Self, CoreError> {
+        Self::start(
+            context,
+            vertex_shader_source,
+            fragment_shader_source,
+            transform_feedback_outputs,
+            true,
+        )
+    }
+
+    ///
+    /// Starts the compilation on the shader compile thread if `use_thread` is true and the thread is enabled, otherwise on this thread.
+    /// The thread is not used when the program is needed right away, since waiting for the thread is slower than compiling on this thread.
+    ///
+    pub(super) fn start(
+        context: &Context,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        transform_feedback_outputs: &[&str],
+        use_thread: bool,
+    ) -> Result<Self, CoreError> {
+        span!(DEBUG, "compile_program");
+        let header: &str = if context.version().is_embedded {
+            "#version 300 es
+                #ifdef GL_FRAGMENT_PRECISION_HIGH
+                    precision highp float;
+                    precision highp int;
+                    precision highp sampler2DArray;
+                    precision highp sampler3D;
+                #else
+                    precision mediump float;
+                    precision mediump int;
+                    precision mediump sampler2DArray;
+                    precision mediump sampler3D;
+                #endif\n"
+        } else {
+            "#version 330 core\n"
+        };
+        let vertex_shader_source = format!("{}{}", header, vertex_shader_source);
+        let fragment_shader_source = format!("{}{}", header, fragment_shader_source);
+        let source_hash = program_source_hash(
+            &vertex_shader_source,
+            &fragment_shader_source,
+            transform_feedback_outputs,
+        );
+        // A binary loaded from a program cache is only used if it was created from the exact same sources
+        let binary = context
+            .program_binaries
+            .read()
+            .unwrap()
+            .get(&source_hash)
+            .cloned();
+        let loaded = binary.and_then(|binary| Program::from_binary(context, &binary).ok());
+        let compilation = if let Some(program) = loaded {
+            Compilation::Loaded(Box::new(program))
+        } else {
+            match context
+                .shader_compile_thread
+                .as_ref()
+                .filter(|_| use_thread)
+            {
+                Some(thread) => Compilation::OnThread(thread.compile(
+                    &vertex_shader_source,
+                    &fragment_shader_source,
+                    transform_feedback_outputs,
+                )),
+                None => Compilation::Started(unsafe {
+                    create_program_objects(
+                        context,
+                        context.program_binary_functions.as_deref(),
+                        &vertex_shader_source,
+                        &fragment_shader_source,
+                        transform_feedback_outputs,
+                    )?
+                }),
+            }
+        };
+        Ok(Self {
+            context: context.clone(),
+            compilation: RefCell::new(compilation),
+            vertex_shader_source,
+            fragment_shader_source,
+            source_hash,
+        })
+    }
+
+    ///
+    /// Returns whether the compilation and linking is done, ie. whether [PendingProgram::finish] can be called without blocking.
+    /// Always returns true if the `KHR_parallel_shader_compile` extension is not available and the program is not compiled on a shader compile thread.
+    ///
+    pub fn is_ready(&self) -> bool {
+        let mut compilation = self.compilation.borrow_mut();
+        match &*compilation {
+            Compilation::Started(objects) => {
+                !self.context.supports_parallel_shader_compile()
+                    || unsafe { self.context.get_program_completion_status(objects.id) }
+            }
+            Compilation::OnThread(receiver) => match receiver.try_recv() {
+                Ok(result) => {
+                    *compilation = match result {
+                        Ok(objects) => Compilation::Started(objects),
+                        Err(e) => Compilation::Failed(e),
+                    };
+                    true
+                }
+                Err(TryRecvError::Empty) => false,
+                Err(TryRecvError::Disconnected) => {
+                    *compilation = Compilation::Failed(compile_thread_stopped());
+                    true
+                }
+            },
+            Compilation::Loaded(_) | Compilation::Failed(_) | Compilation::Finished => true,
+        }
     }
+
+    ///
+    /// Waits for the compilation and linking to finish and returns the resulting [Program] or an error if the compilation or linking failed.
+    ///
+    pub fn finish(mut self) -> Result<Program, CoreError> {
+        span!(DEBUG, "link_program");
+        let objects = match std::mem::replace(self.compilation.get_mut(), Compilation::Finished) {
+            Compilation::Loaded(mut program) => {
+                program.source_hash = Some(self.source_hash);
+                return Ok(*program);
+            }
+            Compilation::Started(objects) => objects,
+            Compilation::OnThread(receiver) => receiver
+                .recv()
+                .unwrap_or_else(|_| Err(compile_thread_stopped()))?,
+            Compilation::Failed(e) => Err(e)?,
+            Compilation::Finished => unreachable!(),
+        };
+        // If the compilation or linking failed, the program and shaders are deleted when this pending program is dropped
+        *self.compilation.get_mut() = Compilation::Started(objects);
+        let context = self.context.clone();
+        let ProgramObjects {
+            id,
+            vertex_shader: vert_shader,
+            fragment_shader: frag_shader,
+        } = objects;
+        unsafe {
+            if !context.get_program_link_status(id) {
+                let log = context.get_shader_info_log(vert_shader);
+                if !log.is_empty() {
+                    Err(CoreError::ShaderCompilation(
+                        "vertex".to_string(),
+                        log,
+                        std::mem::take(&mut self.vertex_shader_source),
+                    ))?;
+                }
+                let log = context.get_shader_info_log(frag_shader);
+                if !log.is_empty() {
+                    Err(CoreError::ShaderCompilation(
+                        "fragment".to_string(),
+                        log,
+                        std::mem::take(&mut self.fragment_shader_source),
+                    ))?;
+                }
+                Err(CoreError::ShaderLink(context.get_program_info_log(id)))?;
+            }
+
+            context.detach_shader(id, vert_shader);
+            context.detach_shader(id, frag_shader);
+            context.delete_shader(vert_shader);
+            context.delete_shader(frag_shader);
+            *self.compilation.get_mut() = Compilation::Finished;
+
+            let mut program = Program::from_linked_program(&context, id);
+            program.source_hash = Some(self.source_hash);
+            Ok(program)
+        }
+    }
+}
+
+impl Drop for PendingProgram {
+    fn drop(&mut self) {
+        // A pending program which is dropped without being finished, for example when a shader warm-up is abandoned, must not leak the program and shaders
+        let objects = match std::mem::replace(self.compilation.get_mut(), Compilation::Finished) {
+            Compilation::Started(objects) => objects,
+            // Waiting for the thread would stall this thread, so the thread deletes the program if it is not done yet
+            Compilation::OnThread(receiver) => match self
+                .context
+                .shader_compile_thread
+                .as_ref()
+                .and_then(|thread| thread.abandon(receiver))
+            {
+                Some(objects) => objects,
+                None => return,
+            },
+            Compilation::Loaded(_) | Compilation::Failed(_) | Compilation::Finished => return,
+        };
+        unsafe { delete_program_objects(&self.context, objects) };
+    }
+}
+
+fn compile_thread_stopped() -> CoreError {
+    CoreError::ContextError("the shader compile thread stopped".to_string())
+}
+
+///
+/// Creates the shaders and the program, starts compiling the shaders and links the program without waiting for the result.
+/// The program and shaders are deleted again if one of them could not be created.
+///
+pub(super) unsafe fn create_program_objects(
+    context: &crate::context::Context,
+    program_binary_functions: Option<&ProgramBinaryFunctions>,
+    vertex_shader_source: &str,
+    fragment_shader_source: &str,
+    transform_feedback_outputs: &[&str],
+) -> Result<ProgramObjects, CoreError> {
+    let vertex_shader = context
+        .create_shader(crate::context::VERTEX_SHADER)
+        .map_err(|e| CoreError::ResourceCreation("vertex shader".to_string(), e))?;
+    let fragment_shader = match context.create_shader(crate::context::FRAGMENT_SHADER) {
+        Ok(shader) => shader,
+        Err(e) => {
+            context.delete_shader(vertex_shader);
+            Err(CoreError::ResourceCreation(
+                "fragment shader".to_string(),
+                e,
+            ))?
+        }
+    };
+
+    context.shader_source(vertex_shader, vertex_shader_source);
+    context.shader_source(fragment_shader, fragment_shader_source);
+    context.compile_shader(vertex_shader);
+    context.compile_shader(fragment_shader);
+
+    let id = match context.create_program() {
+        Ok(id) => id,
+        Err(e) => {
+            context.delete_shader(vertex_shader);
+            context.delete_shader(fragment_shader);
+            Err(CoreError::ResourceCreation("program".to_string(), e))?
+        }
+    };
+    context.attach_shader(id, vertex_shader);
+    context.attach_shader(id, fragment_shader);
+    if !transform_feedback_outputs.is_empty() {
+        context.transform_feedback_varyings(
+            id,
+            transform_feedback_outputs,
+            crate::context::SEPARATE_ATTRIBS,
+        );
+    }
+    Program::set_binary_retrievable(program_binary_functions, id);
+    context.link_program(id);
+    Ok(ProgramObjects {
+        id,
+        vertex_shader,
+        fragment_shader,
+    })
+}
+
+pub(super) unsafe fn delete_program_objects(
+    context: &crate::context::Context,
+    objects: ProgramObjects,
+) {
+    context.delete_program(objects.id);
+    context.delete_shader(objects.vertex_shader);
+    context.delete_shader(objects.fragment_shader);
 }
@@ -0,0 +1,302 @@
+use crate::core::*;
+use std::ffi::c_void;
+
+const PROGRAM_BINARY_RETRIEVABLE_HINT: u32 = 0x8257;
+const PROGRAM_BINARY_LENGTH: u32 = 0x8741;
+const NUM_PROGRAM_BINARY_FORMATS: u32 = 0x87FE;
+const PROGRAM_CACHE_MAGIC: &[u8; 4] = b"3dpc";
+const PROGRAM_CACHE_VERSION: u32 = 2;
+
+///
+/// The compiled and linked binary of a [Program], see [Program::binary] and [Program::from_binary].
+/// A program binary can only be loaded by the same graphics driver that created it.
+///
+#[derive(Clone, Debug)]
+pub struct ProgramBinary {
+    /// The driver specific format of the binary.
+    pub format: u32,
+    /// The binary data.
+    pub data: Vec<u8>,
+}
+
+type GetProgramIv = unsafe extern "system" fn(u32, u32, *mut i32);
+type GetProgramBinary = unsafe extern "system" fn(u32, i32, *mut i32, *mut u32, *mut c_void);
+type ProgramBinaryFn = unsafe extern "system" fn(u32, u32, *const c_void, i32);
+type ProgramParameterI = unsafe extern "system" fn(u32, u32, i32);
+
+///
+/// The OpenGL functions needed to get and load program binaries, which are not exposed by the low-level context.
+///
+pub(crate) struct ProgramBinaryFunctions {
+    get_program_iv: GetProgramIv,
+    get_program_binary: GetProgramBinary,
+    program_binary: ProgramBinaryFn,
+    program_parameter_i: ProgramParameterI,
+}
+
+impl ProgramBinaryFunctions {
+    pub(crate) unsafe fn load(
+        mut loader: impl FnMut(&str) -> *const c_void,
+    ) -> Option<ProgramBinaryFunctions> {
+        let mut load = |name: &str| {
+            let f = loader(name);
+            if f.is_null() {
+                None
+            } else {
+                Some(f)
+            }
+        };
+        Some(ProgramBinaryFunctions {
+            get_program_iv: std::mem::transmute::<*const c_void, GetProgramIv>(load(
+                "glGetProgramiv",
+            )?),
+            get_program_binary: std::mem::transmute::<*const c_void, GetProgramBinary>(load(
+                "glGetProgramBinary",
+            )?),
+            program_binary: std::mem::transmute::<*const c_void, ProgramBinaryFn>(load(
+                "glProgramBinary",
+            )?),
+            program_parameter_i: std::mem::transmute::<*const c_void, ProgramParameterI>(load(
+                "glProgramParameteri",
+            )?),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn raw_id(id: crate::context::Program) -> u32 {
+    id.0.get()
+}
+
+impl Context {
+    ///
+    /// Enables support for getting and loading program binaries (see [Program::binary] and [Program::from_binary]) by loading the needed OpenGL functions using the given loader function,
+    /// usually the same function as used for creating the low-level context.
+    /// This is done automatically when using the [window](crate::window) module on desktop. Program binaries are not supported on web.
+    ///
+    /// # Safety
+    /// The loader must return valid function pointers for the graphics context, or null if a function is not available.
+    ///
+    pub unsafe fn enable_program_binaries(&mut self, loader: impl FnMut(&str) -> *const c_void) {
+        self.program_binary_functions = ProgramBinaryFunctions::load(loader)
+            .filter(|_| {
+                cfg!(not(target_arch = "wasm32"))
+                    && self.get_parameter_i32(NUM_PROGRAM_BINARY_FORMATS) > 0
+            })
+            .map(std::sync::Arc::new);
+    }
+
+    ///
+    /// Returns whether program binaries are supported, see [Context::enable_program_binaries].
+    ///
+    pub fn supports_program_binaries(&self) -> bool {
+        self.program_binary_functions.is_some()
+    }
+
+    ///
+    /// Serializes the binaries of all programs in the program cache, ie. all programs used for rendering so far, into bytes which can be written to disk.
+    /// Loading the bytes with [Context::load_program_cache] the next time the application starts avoids compiling the shader programs again.
+    /// The binaries loaded from a previous cache which have not been used yet are also included.
+    /// Returns an empty cache if program binaries are not supported.
+    ///
+    pub fn save_program_cache(&self) -> Vec<u8> {
+        span!(INFO, "save_program_cache");
+        let mut entries = self.program_binaries.read().unwrap().clone();
+        for program in self.programs.read().unwrap().values() {
+            if let Some(source_hash) = program.source_hash {
+                if let Some(binary) = program.binary() {
+                    entries.insert(source_hash, binary);
+                }
+            }
+        }
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(PROGRAM_CACHE_MAGIC);
+        bytes.extend(PROGRAM_CACHE_VERSION.to_le_bytes());
+        write_bytes(&mut bytes, self.driver_identifier().as_bytes());
+        bytes.extend((entries.len() as u32).to_le_bytes());
+        for (source_hash, binary) in entries {
+            bytes.extend(source_hash.to_le_bytes());
+            bytes.extend(binary.format.to_le_bytes());
+            write_bytes(&mut bytes, &binary.data);
+        }
+        bytes
+    }
+
+    ///
+    /// Loads the program binaries serialized using [Context::save_program_cache] and returns the number of loaded binaries.
+    /// Each binary is identified by a hash of the shader sources it was created from, so when a program is created from the same sources,
+    /// for example when rendering with the same material, the binary is used instead of compiling the sources.
+    /// A program whose sources have changed, for example after editing a custom material or upgrading this crate, is compiled from source as usual.
+    /// Nothing is loaded if the cache was saved with a different graphics driver, driver version or version of this crate,
+    /// and a binary which is rejected by the driver is ignored and the program is compiled from source instead.
+    ///
+    /// Returns an error if the bytes are not a program cache.
+    ///
+    pub fn load_program_cache(&self, bytes: &[u8]) -> Result<usize, CoreError> {
+        span!(INFO, "load_program_cache", size = bytes.len());
+        let mut reader = bytes;
+        if read(&mut reader, 4)? != PROGRAM_CACHE_MAGIC
+            || read_u32(&mut reader)? != PROGRAM_CACHE_VERSION
+        {
+            Err(CoreError::InvalidProgramCache(
+                "unknown format or version".to_string(),
+            ))?;
+        }
+        if read_bytes(&mut reader)? != self.driver_identifier().as_bytes()
+            || !self.supports_program_binaries()
+        {
+            return Ok(0);
+        }
+        let count = read_u32(&mut reader)?;
+        let mut binaries = Vec::new();
+        for _ in 0..count {
+            let source_hash = u64::from_le_bytes(read(&mut reader, 8)?.try_into().unwrap());
+            let format = read_u32(&mut reader)?;
+            let data = read_bytes(&mut reader)?.to_vec();
+            binaries.push((source_hash, ProgramBinary { format, data }));
+        }
+        let loaded = binaries.len();
+        self.program_binaries.write().unwrap().extend(binaries);
+        Ok(loaded)
+    }
+
+    ///
+    /// Identifies the graphics driver and the version of this crate, since the binaries can only be loaded by the same driver
+    /// and the shader sources usually change between versions of this crate.
+    ///
+    fn driver_identifier(&self) -> String {
+        unsafe {
+            format!(
+                "{}\n{}\n{}\n{}",
+                self.get_parameter_string(crate::context::VENDOR),
+                self.get_parameter_string(crate::context::RENDERER),
+                self.get_parameter_string(crate::context::VERSION),
+                env!("CARGO_PKG_VERSION")
+            )
+        }
+    }
+}
+
+///
+/// Returns the hash which identifies the binary of a program created from the given complete shader sources and transform feedback outputs in a program cache.
+///
+pub(super) fn program_source_hash(
+    vertex_shader_source: &str,
+    fragment_shader_source: &str,
+    transform_feedback_outputs: &[&str],
+) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    vertex_shader_source.hash(&mut hasher);
+    fragment_shader_source.hash(&mut hasher);
+    transform_feedback_outputs.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn write_bytes(bytes: &mut Vec<u8>, data: &[u8]) {
+    bytes.extend((data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(data);
+}
+
+fn read<'a>(reader: &mut &'a [u8], length: usize) -> Result<&'a [u8], CoreError> {
+    if reader.len() < length {
+        Err(CoreError::InvalidProgramCache(
+            "unexpected end of data".to_string(),
+        ))?;
+    }
+    let (data, rest) = reader.split_at(length);
+    *reader = rest;
+    Ok(data)
+}
+
+fn read_u32(reader: &mut &[u8]) -> Result<u32, CoreError> {
+    Ok(u32::from_le_bytes(read(reader, 4)?.try_into().unwrap()))
+}
+
+fn read_bytes<'a>(reader: &mut &'a [u8]) -> Result<&'a [u8], CoreError> {
+    let length = read_u32(reader)? as usize;
+    read(reader, length)
+}
+
+impl Program {
+    ///
+    /// Returns the compiled and linked binary of this program which can be saved and later loaded with [Program::from_binary] to avoid compiling the program again.
+    /// Returns `None` if program binaries are not supported, see [Context::enable_program_binaries].
+    ///
+    pub fn binary(&self) -> Option<ProgramBinary> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(functions) = &self.context.program_binary_functions {
+            unsafe {
+                let id = raw_id(self.id);
+                let mut length = 0;
+                (functions.get_program_iv)(id, PROGRAM_BINARY_LENGTH, &mut length);
+                if length <= 0 {
+                    return None;
+                }
+                let mut data = vec![0u8; length as usize];
+                let mut format = 0;
+                (functions.get_program_binary)(
+                    id,
+                    length,
+                    &mut length,
+                    &mut format,
+                    data.as_mut_ptr() as *mut c_void,
+                );
+                data.truncate(length.max(0) as usize);
+                return Some(ProgramBinary { format, data });
+            }
+        }
+        None
+    }
+
+    ///
+    /// Creates a new shader program from a binary returned by [Program::binary].
+    /// Returns an error if program binaries are not supported or if the driver rejects the binary, for example because the driver has been updated,
+    /// in which case the program should be compiled from source instead.
+    ///
+    pub fn from_binary(context: &Context, binary: &ProgramBinary) -> Result<Self, CoreError> {
+        span!(DEBUG, "load_program_binary", size = binary.data.len());
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(functions) = &context.program_binary_functions {
+            unsafe {
+                let id = context
+                    .create_program()
+                    .map_err(|e| CoreError::ResourceCreation("program".to_string(), e))?;
+                (functions.program_binary)(
+                    raw_id(id),
+                    binary.format,
+                    binary.data.as_ptr() as *const c_void,
+                    binary.data.len() as i32,
+                );
+                if !context.get_program_link_status(id) {
+                    let log = context.get_program_info_log(id);
+                    context.delete_program(id);
+                    Err(CoreError::ShaderLink(if log.is_empty() {
+                        "the program binary was rejected".to_string()
+                    } else {
+                        log
+                    }))?;
+                }
+                return Ok(Self::from_linked_program(context, id));
+            }
+        }
+        Err(CoreError::ShaderLink(
+            "program binaries are not supported".to_string(),
+        ))
+    }
+
+    pub(super) fn set_binary_retrievable(
+        functions: Option<&ProgramBinaryFunctions>,
+        id: crate::context::Program,
+    ) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(functions) = functions {
+            unsafe {
+                (functions.program_parameter_i)(raw_id(id), PROGRAM_BINARY_RETRIEVABLE_HINT, 1)
+            };
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = (functions, id);
+    }
+}
@@ -26,6 +26,14 @@ mod depth_target_multisample;
 #[doc(inline)]
 pub use depth_target_multisample::*;
 
+mod pixel_readback;
+#[doc(inline)]
+pub use pixel_readback::*;
+
+mod render_target_pool;
+#[doc(inline)]
+pub use render_target_pool::*;
+
 use crate::core::*;
 
 use crate::context::Framebuffer;
@@ -159,22 +167,7 @@ impl<'a> RenderTarget<'a> {
     /// - 32-bit float RGBA (Specify `T` as either `Vec4<f32>` or `[f32; 4]`) which works with any render target using `f16` or `f32` as its base type.
     ///
     pub fn read_color_partially<T: TextureDataType>(&self, scissor_box: ScissorBox) -> Vec<T> {
-        if self.id.is_some() && self.color.is_none() {
-            panic!("Cannot read color from a render target without a color target");
-        }
-        let format = format_from_data_type::<T>();
-        let data_type = T::data_type();
-
-        // On web, the read format needs to be RGBA and f16 is not supported (see https://webglfundamentals.org/webgl/lessons/webgl-readpixels.html).
-        #[cfg(target_arch = "wasm32")]
-        if format != crate::context::RGBA
-            || !(data_type == crate::context::UNSIGNED_BYTE || data_type == crate::context::FLOAT)
-        {
-            panic!("Only the texture data types `Vec4<T>` and `[T; 4]` where `T` is either `u8` or `f32` are supported when reading color from a render target on web.");
-        }
-
-        self.bind(crate::context::DRAW_FRAMEBUFFER);
-        self.bind(crate::context::READ_FRAMEBUFFER);
+        let (format, data_type) = self.bind_for_color_read::<T>();
         let data_size = std::mem::size_of::<T>();
         let mut bytes =
             vec![0u8; scissor_box.width as usize * scissor_box.height as usize * data_size];
@@ -198,6 +191,28 @@ impl<'a> RenderTarget<'a> {
         pixels
     }
 
+    ///
+    /// Starts reading the colors of the pixels in this render target asynchronously, which does not wait for the GPU to finish rendering
+    /// and therefore does not stall the pipeline like [RenderTarget::read_color] does.
+    /// The colors are available when the returned [PixelReadback] is ready, usually one or two frames later.
+    /// See [RenderTarget::read_color] for the supported data types.
+    ///
+    pub fn read_color_async<T: TextureDataType>(&self) -> PixelReadback<T> {
+        self.read_color_partially_async(self.scissor_box())
+    }
+
+    ///
+    /// Starts reading the colors of the pixels in this render target inside the given scissor box asynchronously,
+    /// see [RenderTarget::read_color_async].
+    ///
+    pub fn read_color_partially_async<T: TextureDataType>(
+        &self,
+        scissor_box: ScissorBox,
+    ) -> PixelReadback<T> {
+        let (format, data_type) = self.bind_for_color_read::<T>();
+        PixelReadback::new(&self.context, scissor_box, format, data_type, true)
+    }
+
     ///
     /// Returns the depth values in this render target.
     ///
@@ -211,11 +226,7 @@ impl<'a> RenderTarget<'a> {
     ///
     #[cfg(not(target_arch = "wasm32"))]
     pub fn read_depth_partially(&self, scissor_box: ScissorBox) -> Vec<f32> {
-        if self.id.is_some() && self.depth.is_none() {
-            panic!("cannot read depth from a render target without a depth target");
-        }
-        self.bind(crate::context::DRAW_FRAMEBUFFER);
-        self.bind(crate::context::READ_FRAMEBUFFER);
+        self.bind_for_depth_read();
         let mut pixels = vec![0u8; scissor_box.width as usize * scissor_box.height as usize * 4];
         unsafe {
             self.context.read_pixels(
@@ -231,6 +242,61 @@ impl<'a> RenderTarget<'a> {
         from_byte_slice(&pixels).to_vec()
     }
 
+    ///
+    /// Starts reading the depth values in this render target asynchronously, which does not wait for the GPU to finish rendering
+    /// and therefore does not stall the pipeline like [RenderTarget::read_depth] does.
+    /// The depth values are available when the returned [PixelReadback] is ready, usually one or two frames later.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_depth_async(&self) -> PixelReadback<f32> {
+        self.read_depth_partially_async(self.scissor_box())
+    }
+
+    ///
+    /// Starts reading the depth values in this render target inside the given scissor box asynchronously,
+    /// see [RenderTarget::read_depth_async].
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_depth_partially_async(&self, scissor_box: ScissorBox) -> PixelReadback<f32> {
+        self.bind_for_depth_read();
+        PixelReadback::new(
+            &self.context,
+            scissor_box,
+            crate::context::DEPTH_COMPONENT,
+            crate::context::FLOAT,
+            false,
+        )
+    }
+
+    fn bind_for_color_read<T: TextureDataType>(&self) -> (u32, u32) {
+        if self.id.is_some() && self.color.is_none() {
+            panic!("Cannot read color from a render target without a color target");
+        }
+        let format = format_from_data_type::<T>();
+        let data_type = T::data_type();
+
+        // On web, the read format needs to be RGBA and f16 is not supported (see https://webglfundamentals.org/webgl/lessons/webgl-readpixels.html).
+        #[cfg(target_arch = "wasm32")]
+        if format != crate::context::RGBA
+            || !(data_type == crate::context::UNSIGNED_BYTE || data_type == crate::context::FLOAT)
+        {
+            panic!("Only the texture data types `Vec4<T>` and `[T; 4]` where `T` is either `u8` or `f32` are supported when reading color from a render target on web.");
+        }
+
+        self.bind(crate::context::DRAW_FRAMEBUFFER);
+        self.bind(crate::context::READ_FRAMEBUFFER);
+        (format, data_type)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn bind_for_depth_read(&self) {
+        if self.id.is_some() && self.depth.is_none() {
+            panic!("cannot read depth from a render target without a depth target");
+        }
+        self.bind(crate::context::DRAW_FRAMEBUFFER);
+        self.bind(crate::context::READ_FRAMEBUFFER);
+    }
+
     ///
     /// Creates a [RenderTarget] with the given low-level [Framebuffer]. Should only be used if the [Framebuffer] is used for something else, ie. to be able
     /// to combine this crate with functionality of another crate. Also see [Self::into_framebuffer].
@@ -278,7 +344,7 @@ impl<'a> RenderTarget<'a> {
             .set_scissor(ScissorBox::new_at_origo(target.width, target.height));
         unsafe {
             self.context
-                .bind_framebuffer(crate::context::READ_FRAMEBUFFER, self.id);
+                .bind_target_framebuffer(crate::context::READ_FRAMEBUFFER, self.id);
 
             self.context.blit_framebuffer(
                 0,
@@ -323,7 +389,16 @@ impl<'a> RenderTarget<'a> {
 
     fn bind(&self, target: u32) {
         unsafe {
-            self.context.bind_framebuffer(target, self.id);
+            self.context.bind_target_framebuffer(target, self.id);
+            if !self.context.version().is_embedded {
+                // Convert to sRGB when writing to sRGB textures, which is always enabled on OpenGL ES and WebGL,
+                // but not when writing to the screen to avoid converting twice if the screen is sRGB capable
+                if self.id.is_some() {
+                    self.context.enable(crate::context::FRAMEBUFFER_SRGB);
+                } else {
+                    self.context.disable(crate::context::FRAMEBUFFER_SRGB);
+                }
+            }
         }
         if let Some(ref color) = self.color {
             color.bind(&self.context);
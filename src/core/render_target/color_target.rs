@@ -14,6 +14,7 @@ pub struct ColorTarget<'a> {
     mip_level: Option<u32>,
     target: Option<ColorTexture<'a>>,
     multisample_target: Option<&'a Texture2DMultisample>,
+    volume_target: Option<(&'a Texture3D, &'a [u32])>,
 }
 
 impl<'a> ColorTarget<'a> {
@@ -27,6 +28,7 @@ impl<'a> ColorTarget<'a> {
             mip_level,
             target: Some(ColorTexture::Single(texture)),
             multisample_target: None,
+            volume_target: None,
         }
     }
 
@@ -41,6 +43,7 @@ impl<'a> ColorTarget<'a> {
             mip_level,
             target: Some(ColorTexture::CubeMap { texture, sides }),
             multisample_target: None,
+            volume_target: None,
         }
     }
 
@@ -55,6 +58,7 @@ impl<'a> ColorTarget<'a> {
             mip_level,
             target: Some(ColorTexture::Array { texture, layers }),
             multisample_target: None,
+            volume_target: None,
         }
     }
 
@@ -67,6 +71,22 @@ impl<'a> ColorTarget<'a> {
             mip_level: None,
             target: None,
             multisample_target: Some(texture),
+            volume_target: None,
+        }
+    }
+
+    pub(in crate::core) fn new_texture_3d(
+        context: &Context,
+        texture: &'a Texture3D,
+        layers: &'a [u32],
+        mip_level: Option<u32>,
+    ) -> Self {
+        ColorTarget {
+            context: context.clone(),
+            mip_level,
+            target: None,
+            multisample_target: None,
+            volume_target: Some((texture, layers)),
         }
     }
 
@@ -146,6 +166,24 @@ impl<'a> ColorTarget<'a> {
         self.as_render_target().read_color_partially(scissor_box)
     }
 
+    ///
+    /// Starts reading the colors of the pixels in this color target asynchronously, see [RenderTarget::read_color_async].
+    ///
+    pub fn read_async<T: TextureDataType>(&self) -> PixelReadback<T> {
+        self.read_partially_async(self.scissor_box())
+    }
+
+    ///
+    /// Starts reading the colors of the pixels in this color target inside the given scissor box asynchronously, see [RenderTarget::read_color_async].
+    ///
+    pub fn read_partially_async<T: TextureDataType>(
+        &self,
+        scissor_box: ScissorBox,
+    ) -> PixelReadback<T> {
+        self.as_render_target()
+            .read_color_partially_async(scissor_box)
+    }
+
     ///
     /// Returns the width of the color target in texels.
     /// If using the zero mip level of the underlying texture, then this is simply the width of that texture, otherwise it is the width of the given mip level.
@@ -161,6 +199,8 @@ impl<'a> ColorTarget<'a> {
                     size_with_mip(texture.width(), self.mip_level)
                 }
             }
+        } else if let Some((texture, _)) = self.volume_target {
+            size_with_mip(texture.width(), self.mip_level)
         } else {
             self.multisample_target.as_ref().unwrap().width()
         }
@@ -181,6 +221,8 @@ impl<'a> ColorTarget<'a> {
                     size_with_mip(texture.height(), self.mip_level)
                 }
             }
+        } else if let Some((texture, _)) = self.volume_target {
+            size_with_mip(texture.height(), self.mip_level)
         } else {
             self.multisample_target.as_ref().unwrap().height()
         }
@@ -209,6 +251,10 @@ impl<'a> ColorTarget<'a> {
                     }
                 }
             }
+        } else if let Some((texture, _)) = self.volume_target {
+            if self.mip_level.is_none() {
+                texture.generate_mip_maps()
+            }
         }
     }
 
@@ -248,6 +294,21 @@ impl<'a> ColorTarget<'a> {
                     });
                 },
             }
+        } else if let Some((texture, layers)) = self.volume_target {
+            unsafe {
+                context.draw_buffers(
+                    &(0..layers.len())
+                        .map(|i| crate::context::COLOR_ATTACHMENT0 + i as u32)
+                        .collect::<Vec<u32>>(),
+                );
+                (0..layers.len()).for_each(|channel| {
+                    texture.bind_as_color_target(
+                        layers[channel],
+                        channel as u32,
+                        self.mip_level.unwrap_or(0),
+                    );
+                });
+            }
         } else {
             unsafe {
                 context.draw_buffers(&[crate::context::COLOR_ATTACHMENT0]);
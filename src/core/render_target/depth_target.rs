@@ -117,6 +117,23 @@ impl<'a> DepthTarget<'a> {
         self.as_render_target().read_depth_partially(scissor_box)
     }
 
+    ///
+    /// Starts reading the depth values in this depth target asynchronously, see [RenderTarget::read_depth_async].
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_async(&self) -> PixelReadback<f32> {
+        self.read_partially_async(self.scissor_box())
+    }
+
+    ///
+    /// Starts reading the depth values in this depth target inside the given scissor box asynchronously, see [RenderTarget::read_depth_async].
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_partially_async(&self, scissor_box: ScissorBox) -> PixelReadback<f32> {
+        self.as_render_target()
+            .read_depth_partially_async(scissor_box)
+    }
+
     pub(super) fn as_render_target(&self) -> RenderTarget<'a> {
         RenderTarget::new_depth(self.clone())
     }
@@ -0,0 +1,142 @@
+use crate::core::*;
+
+///
+/// Pixels which are being copied from a render target into a pixel buffer on the GPU, see for example [RenderTarget::read_color_async] and [RenderTarget::read_depth_async].
+/// The copy is performed asynchronously by the GPU, so starting the read does not wait for the rendering to finish.
+/// Poll [PixelReadback::is_ready] or call [PixelReadback::try_read] each frame until the pixels are available, usually one or two frames later,
+/// or call [PixelReadback::read] to wait for the pixels.
+///
+pub struct PixelReadback<T: TextureDataType> {
+    context: Context,
+    buffer: crate::context::Buffer,
+    fence: crate::context::Fence,
+    width: u32,
+    height: u32,
+    flip_y: bool,
+    _data: std::marker::PhantomData<T>,
+}
+
+impl<T: TextureDataType> PixelReadback<T> {
+    ///
+    /// Starts copying the pixels inside the scissor box of the currently bound read framebuffer into a new pixel buffer.
+    ///
+    pub(super) fn new(
+        context: &Context,
+        scissor_box: ScissorBox,
+        format: u32,
+        data_type: u32,
+        flip_y: bool,
+    ) -> Self {
+        let size =
+            scissor_box.width as usize * scissor_box.height as usize * std::mem::size_of::<T>();
+        unsafe {
+            let buffer = context
+                .create_buffer()
+                .expect("Failed creating pixel buffer");
+            context.resources.created(ResourceKind::Buffer);
+            context.bind_buffer(crate::context::PIXEL_PACK_BUFFER, Some(buffer));
+            context.buffer_data_size(
+                crate::context::PIXEL_PACK_BUFFER,
+                size as i32,
+                crate::context::STREAM_READ,
+            );
+            context.read_pixels(
+                scissor_box.x,
+                scissor_box.y,
+                scissor_box.width as i32,
+                scissor_box.height as i32,
+                format,
+                data_type,
+                crate::context::PixelPackData::BufferOffset(0),
+            );
+            context.bind_buffer(crate::context::PIXEL_PACK_BUFFER, None);
+            let fence = context
+                .fence_sync(crate::context::SYNC_GPU_COMMANDS_COMPLETE, 0)
+                .expect("Failed creating fence");
+            // Make sure the commands are submitted, otherwise the fence might never be signaled
+            context.flush();
+            Self {
+                context: context.clone(),
+                buffer,
+                fence,
+                width: scissor_box.width,
+                height: scissor_box.height,
+                flip_y,
+                _data: std::marker::PhantomData,
+            }
+        }
+    }
+
+    /// The width of the area that is read.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the area that is read.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    ///
+    /// Returns whether the GPU has finished copying the pixels, in which case [PixelReadback::read] returns the pixels without waiting.
+    ///
+    pub fn is_ready(&self) -> bool {
+        unsafe { self.context.get_sync_status(self.fence) == crate::context::SIGNALED }
+    }
+
+    ///
+    /// Returns the pixels if the GPU has finished copying them, otherwise this is returned so that it can be tried again later, for example in the next frame.
+    ///
+    pub fn try_read(self) -> Result<Vec<T>, Self> {
+        if self.is_ready() {
+            Ok(self.read())
+        } else {
+            Err(self)
+        }
+    }
+
+    ///
+    /// Returns the pixels, waiting for the GPU to finish copying them if they are not ready yet (see [PixelReadback::is_ready]).
+    ///
+    pub fn read(self) -> Vec<T> {
+        let size = self.width as usize * self.height as usize * std::mem::size_of::<T>();
+        let mut bytes = vec![0u8; size];
+        unsafe {
+            self.context
+                .bind_buffer(crate::context::PIXEL_PACK_BUFFER, Some(self.buffer));
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let data = self.context.map_buffer_range(
+                    crate::context::PIXEL_PACK_BUFFER,
+                    0,
+                    size as i32,
+                    crate::context::MAP_READ_BIT,
+                );
+                if !data.is_null() {
+                    std::ptr::copy_nonoverlapping(data, bytes.as_mut_ptr(), size);
+                }
+                self.context.unmap_buffer(crate::context::PIXEL_PACK_BUFFER);
+            }
+            #[cfg(target_arch = "wasm32")]
+            self.context
+                .get_buffer_sub_data(crate::context::PIXEL_PACK_BUFFER, 0, &mut bytes);
+            self.context
+                .bind_buffer(crate::context::PIXEL_PACK_BUFFER, None);
+        }
+        let mut pixels = from_byte_slice(&bytes).to_vec();
+        if self.flip_y {
+            flip_y(&mut pixels, self.width as usize, self.height as usize);
+        }
+        pixels
+    }
+}
+
+impl<T: TextureDataType> Drop for PixelReadback<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.context.delete_buffer(self.buffer);
+            self.context.resources.released(ResourceKind::Buffer);
+            self.context.delete_sync(self.fence);
+        }
+    }
+}
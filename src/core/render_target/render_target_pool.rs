@@ -0,0 +1,285 @@
+use crate::core::*;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ColorTextureKey {
+    internal_format: u32,
+    width: u32,
+    height: u32,
+    min_filter: Interpolation,
+    mag_filter: Interpolation,
+    mip_map_filter: Option<Interpolation>,
+    wrap_s: Wrapping,
+    wrap_t: Wrapping,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct DepthTextureKey {
+    internal_format: u32,
+    width: u32,
+    height: u32,
+    wrap_s: Wrapping,
+    wrap_t: Wrapping,
+}
+
+struct PoolEntry<K, T> {
+    key: K,
+    texture: T,
+    last_used: u64,
+}
+
+struct PoolStorage {
+    frame: u64,
+    max_unused_frames: u64,
+    allocated: usize,
+    color_textures: Vec<PoolEntry<ColorTextureKey, Texture2D>>,
+    depth_textures: Vec<PoolEntry<DepthTextureKey, DepthTexture2D>>,
+}
+
+impl PoolStorage {
+    fn take<K: PartialEq, T>(entries: &mut Vec<PoolEntry<K, T>>, key: &K) -> Option<T> {
+        // The most recently returned texture is reused first to keep the others unused so they can be released
+        entries
+            .iter()
+            .rposition(|e| e.key == *key)
+            .map(|i| entries.swap_remove(i).texture)
+    }
+}
+
+///
+/// A pool of transient textures which can be reused across render passes and frames, for example for the intermediate results of a chain of effects
+/// or for the render targets used when rendering a probe.
+/// Instead of allocating new textures each frame, request them from the pool using [RenderTargetPool::color_texture] and [RenderTargetPool::depth_texture].
+/// When the returned [PooledTexture2D] or [PooledDepthTexture2D] is dropped, the texture is returned to the pool and is handed out again
+/// the next time a texture with the same size, format and parameters is requested.
+///
+/// Call [RenderTargetPool::end_frame] once each frame to release the textures which have not been used for a number of frames,
+/// for example after the window has been resized.
+/// The pool can be cloned cheaply, all clones share the same textures.
+///
+#[derive(Clone)]
+pub struct RenderTargetPool {
+    context: Context,
+    storage: Rc<RefCell<PoolStorage>>,
+}
+
+impl RenderTargetPool {
+    ///
+    /// Creates a new empty pool which releases the textures that have not been used in the last two frames.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self::new_with_max_unused_frames(context, 2)
+    }
+
+    ///
+    /// Creates a new empty pool which releases the textures that have not been used in the given number of frames.
+    ///
+    pub fn new_with_max_unused_frames(context: &Context, max_unused_frames: u32) -> Self {
+        Self {
+            context: context.clone(),
+            storage: Rc::new(RefCell::new(PoolStorage {
+                frame: 0,
+                max_unused_frames: max_unused_frames as u64,
+                allocated: 0,
+                color_textures: Vec::new(),
+                depth_textures: Vec::new(),
+            })),
+        }
+    }
+
+    ///
+    /// Returns a color texture with the given size and parameters where the format is determined by the generic [TextureDataType] parameter,
+    /// see [Texture2D::new_empty]. The texture is taken from the pool if one is available, otherwise a new texture is allocated.
+    ///
+    /// **Note:** The content of the returned texture is undefined, since it might contain the result of an earlier pass, so it should be cleared or fully overwritten.
+    ///
+    #[allow(clippy::too_many_arguments)]
+    pub fn color_texture<T: TextureDataType>(
+        &self,
+        width: u32,
+        height: u32,
+        min_filter: Interpolation,
+        mag_filter: Interpolation,
+        mip_map_filter: Option<Interpolation>,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+    ) -> PooledTexture2D {
+        let key = ColorTextureKey {
+            internal_format: T::internal_format(),
+            width,
+            height,
+            min_filter,
+            mag_filter,
+            mip_map_filter,
+            wrap_s,
+            wrap_t,
+        };
+        let mut storage = self.storage.borrow_mut();
+        let texture = PoolStorage::take(&mut storage.color_textures, &key).unwrap_or_else(|| {
+            storage.allocated += 1;
+            Texture2D::new_empty::<T>(
+                &self.context,
+                width,
+                height,
+                min_filter,
+                mag_filter,
+                mip_map_filter,
+                wrap_s,
+                wrap_t,
+            )
+        });
+        PooledTexture2D {
+            texture: Some(texture),
+            key,
+            storage: Rc::downgrade(&self.storage),
+        }
+    }
+
+    ///
+    /// Returns a depth texture with the given size and parameters where the format is determined by the generic [DepthTextureDataType] parameter,
+    /// see [DepthTexture2D::new]. The texture is taken from the pool if one is available, otherwise a new texture is allocated.
+    ///
+    /// **Note:** The content of the returned texture is undefined, since it might contain the result of an earlier pass, so it should be cleared or fully overwritten.
+    ///
+    pub fn depth_texture<T: DepthTextureDataType>(
+        &self,
+        width: u32,
+        height: u32,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+    ) -> PooledDepthTexture2D {
+        let key = DepthTextureKey {
+            internal_format: T::internal_format(),
+            width,
+            height,
+            wrap_s,
+            wrap_t,
+        };
+        let mut storage = self.storage.borrow_mut();
+        let texture = PoolStorage::take(&mut storage.depth_textures, &key).unwrap_or_else(|| {
+            storage.allocated += 1;
+            DepthTexture2D::new::<T>(&self.context, width, height, wrap_s, wrap_t)
+        });
+        PooledDepthTexture2D {
+            texture: Some(texture),
+            key,
+            storage: Rc::downgrade(&self.storage),
+        }
+    }
+
+    ///
+    /// Ends the current frame and releases the textures in the pool which have not been used in the last number of frames
+    /// given when constructing the pool. Textures which are currently in use are never released.
+    ///
+    pub fn end_frame(&self) {
+        let mut storage = self.storage.borrow_mut();
+        storage.frame += 1;
+        let oldest = storage.frame.saturating_sub(storage.max_unused_frames);
+        let before = storage.color_textures.len() + storage.depth_textures.len();
+        storage.color_textures.retain(|e| e.last_used >= oldest);
+        storage.depth_textures.retain(|e| e.last_used >= oldest);
+        let after = storage.color_textures.len() + storage.depth_textures.len();
+        storage.allocated -= before - after;
+    }
+
+    ///
+    /// Releases all textures in the pool which are not currently in use.
+    ///
+    pub fn clear(&self) {
+        let mut storage = self.storage.borrow_mut();
+        let count = storage.color_textures.len() + storage.depth_textures.len();
+        storage.color_textures.clear();
+        storage.depth_textures.clear();
+        storage.allocated -= count;
+    }
+
+    ///
+    /// Returns the number of textures allocated by this pool, both the textures in use and the textures available in the pool.
+    ///
+    pub fn allocated_count(&self) -> usize {
+        self.storage.borrow().allocated
+    }
+
+    ///
+    /// Returns the number of textures available in the pool, ie. the textures which are allocated but not in use.
+    ///
+    pub fn available_count(&self) -> usize {
+        let storage = self.storage.borrow();
+        storage.color_textures.len() + storage.depth_textures.len()
+    }
+}
+
+///
+/// A color texture borrowed from a [RenderTargetPool] which is returned to the pool when dropped.
+/// Dereferences to a [Texture2D], so it can be used in the same way, for example to create a [ColorTarget] using [Texture2D::as_color_target].
+///
+pub struct PooledTexture2D {
+    texture: Option<Texture2D>,
+    key: ColorTextureKey,
+    storage: Weak<RefCell<PoolStorage>>,
+}
+
+impl std::ops::Deref for PooledTexture2D {
+    type Target = Texture2D;
+    fn deref(&self) -> &Self::Target {
+        self.texture.as_ref().unwrap()
+    }
+}
+
+impl std::ops::DerefMut for PooledTexture2D {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.texture.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledTexture2D {
+    fn drop(&mut self) {
+        if let (Some(texture), Some(storage)) = (self.texture.take(), self.storage.upgrade()) {
+            let mut storage = storage.borrow_mut();
+            let last_used = storage.frame;
+            storage.color_textures.push(PoolEntry {
+                key: self.key,
+                texture,
+                last_used,
+            });
+        }
+    }
+}
+
+///
+/// A depth texture borrowed from a [RenderTargetPool] which is returned to the pool when dropped.
+/// Dereferences to a [DepthTexture2D], so it can be used in the same way, for example to create a [DepthTarget] using [DepthTexture2D::as_depth_target].
+///
+pub struct PooledDepthTexture2D {
+    texture: Option<DepthTexture2D>,
+    key: DepthTextureKey,
+    storage: Weak<RefCell<PoolStorage>>,
+}
+
+impl std::ops::Deref for PooledDepthTexture2D {
+    type Target = DepthTexture2D;
+    fn deref(&self) -> &Self::Target {
+        self.texture.as_ref().unwrap()
+    }
+}
+
+impl std::ops::DerefMut for PooledDepthTexture2D {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.texture.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledDepthTexture2D {
+    fn drop(&mut self) {
+        if let (Some(texture), Some(storage)) = (self.texture.take(), self.storage.upgrade()) {
+            let mut storage = storage.borrow_mut();
+            let last_used = storage.frame;
+            storage.depth_textures.push(PoolEntry {
+                key: self.key,
+                texture,
+                last_used,
+            });
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::core::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+///
+/// The different kinds of resources allocated on the GPU.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    /// A buffer, for example a [VertexBuffer], [InstanceBuffer], [ElementBuffer] or [UniformBuffer].
+    Buffer,
+    /// A texture, for example a [Texture2D] or a [DepthTexture2D].
+    Texture,
+    /// A render buffer, used by the multisample render targets, for example [RenderTargetMultisample].
+    Renderbuffer,
+    /// A shader [Program].
+    Program,
+}
+
+///
+/// The number of resources of each [ResourceKind] that are currently allocated on the GPU, see [Context::resource_statistics].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceStatistics {
+    /// The number of buffers.
+    pub buffers: usize,
+    /// The number of textures.
+    pub textures: usize,
+    /// The number of render buffers.
+    pub renderbuffers: usize,
+    /// The number of shader programs, including the programs in the program cache.
+    pub programs: usize,
+}
+
+impl ResourceStatistics {
+    ///
+    /// Returns the total number of resources.
+    ///
+    pub fn total(&self) -> usize {
+        self.buffers + self.textures + self.renderbuffers + self.programs
+    }
+
+    ///
+    /// Returns the number of resources of each kind that are allocated in these statistics but not in the given earlier statistics.
+    ///
+    pub fn allocated_since(&self, earlier: &ResourceStatistics) -> ResourceStatistics {
+        ResourceStatistics {
+            buffers: self.buffers.saturating_sub(earlier.buffers),
+            textures: self.textures.saturating_sub(earlier.textures),
+            renderbuffers: self.renderbuffers.saturating_sub(earlier.renderbuffers),
+            programs: self.programs.saturating_sub(earlier.programs),
+        }
+    }
+}
+
+impl std::fmt::Display for ResourceStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} buffers, {} textures, {} render buffers and {} programs",
+            self.buffers, self.textures, self.renderbuffers, self.programs
+        )
+    }
+}
+
+///
+/// Keeps track of the number of GPU resources which are alive, shared by all clones of a [Context].
+///
+#[derive(Default)]
+pub(crate) struct ResourceRegistry {
+    counts: [AtomicUsize; 4],
+}
+
+impl ResourceRegistry {
+    pub(crate) fn created(&self, kind: ResourceKind) {
+        self.counts[kind as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn released(&self, kind: ResourceKind) {
+        self.counts[kind as usize].fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn statistics(&self) -> ResourceStatistics {
+        let count = |kind: ResourceKind| self.counts[kind as usize].load(Ordering::Relaxed);
+        ResourceStatistics {
+            buffers: count(ResourceKind::Buffer),
+            textures: count(ResourceKind::Texture),
+            renderbuffers: count(ResourceKind::Renderbuffer),
+            programs: count(ResourceKind::Program),
+        }
+    }
+}
+
+impl Context {
+    ///
+    /// Returns the number of GPU resources of each kind that are currently allocated using this context or any of its clones.
+    /// Comparing the statistics at different points in time, for example each time a document is closed in an editor, makes it possible to detect resources that are never released.
+    ///
+    pub fn resource_statistics(&self) -> ResourceStatistics {
+        self.resources.statistics()
+    }
+
+    ///
+    /// Starts tracking the GPU resources allocated from now on, see [ResourceScope].
+    ///
+    pub fn resource_scope(&self) -> ResourceScope {
+        ResourceScope {
+            context: self.clone(),
+            start: self.resource_statistics(),
+        }
+    }
+
+    ///
+    /// Deletes all programs in the program cache which are not in use.
+    /// The programs are compiled again when they are needed, so this is only useful to release the programs for materials, effects or lights that are no longer used.
+    ///
+    pub fn clear_program_cache(&self) {
+        self.programs.write().unwrap().clear();
+    }
+}
+
+///
+/// Tracks the GPU resources allocated after the scope was created with [Context::resource_scope].
+/// In debug builds, a warning with the number of resources which are still allocated is printed when the scope is dropped.
+/// This is useful to detect leaked resources from, for example, loading and unloading a scene.
+/// Note that programs added to the program cache in the meantime are also reported, use [Context::clear_program_cache] to avoid that.
+///
+pub struct ResourceScope {
+    context: Context,
+    start: ResourceStatistics,
+}
+
+impl ResourceScope {
+    ///
+    /// Returns the number of resources of each kind that have been allocated since this scope was created and which are still allocated.
+    ///
+    pub fn leaked(&self) -> ResourceStatistics {
+        self.context
+            .resource_statistics()
+            .allocated_since(&self.start)
+    }
+}
+
+impl Drop for ResourceScope {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        {
+            let leaked = self.leaked();
+            if leaked.total() > 0 {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    "{} are still allocated at the end of the resource scope",
+                    leaked
+                );
+                #[cfg(not(feature = "tracing"))]
+                eprintln!(
+                    "three-d: {} are still allocated at the end of the resource scope",
+                    leaked
+                );
+            }
+        }
+    }
+}
+
+///
+/// A resource allocated on the GPU.
+/// The resource is deleted when it is dropped, but it can also be deleted explicitly using [GpuResource::dispose] to make the intent clear.
+/// A resource which is shared using an [Arc](std::sync::Arc) is deleted when the last strong reference is dropped,
+/// so use a weak reference, for example a `WeakTexture2DRef`, to refer to a resource without keeping it alive.
+///
+pub trait GpuResource {
+    ///
+    /// The kind of this resource.
+    ///
+    fn kind(&self) -> ResourceKind;
+
+    ///
+    /// Deletes this resource on the GPU.
+    ///
+    fn dispose(self)
+    where
+        Self: Sized,
+    {
+        drop(self)
+    }
+}
+
+macro_rules! impl_gpu_resource {
+    ($kind:ident, $($type:ty),+) => {
+        $(
+            impl GpuResource for $type {
+                fn kind(&self) -> ResourceKind {
+                    ResourceKind::$kind
+                }
+            }
+        )+
+    };
+}
+
+impl_gpu_resource!(
+    Buffer,
+    VertexBuffer,
+    InstanceBuffer,
+    ElementBuffer,
+    UniformBuffer
+);
+impl_gpu_resource!(
+    Texture,
+    Texture2D,
+    Texture2DArray,
+    Texture3D,
+    TextureCubeMap,
+    DepthTexture2D,
+    DepthTexture2DArray,
+    DepthTextureCubeMap
+);
+impl_gpu_resource!(Program, Program);
@@ -0,0 +1,157 @@
+use crate::core::*;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+
+/// Receives the program objects created by the shader compile thread or the error if they could not be created.
+pub(super) type CompileResult = Receiver<Result<ProgramObjects, CoreError>>;
+
+struct CompileJob {
+    vertex_shader_source: String,
+    fragment_shader_source: String,
+    transform_feedback_outputs: Vec<String>,
+    result: Sender<Result<ProgramObjects, CoreError>>,
+}
+
+///
+/// A thread with its own graphics context, which shares resources with the context that started the thread, that compiles and links shader programs.
+///
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+pub(super) struct ShaderCompileThread {
+    jobs: Mutex<Sender<CompileJob>>,
+    abandoned: Arc<Mutex<Vec<CompileResult>>>,
+}
+
+impl ShaderCompileThread {
+    ///
+    /// Starts compiling and linking the program on the thread and returns a receiver of the created program objects.
+    /// The receiver returns an error if the thread has stopped.
+    ///
+    pub fn compile(
+        &self,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        transform_feedback_outputs: &[&str],
+    ) -> CompileResult {
+        let (result, receiver) = channel();
+        let _ = self.jobs.lock().unwrap().send(CompileJob {
+            vertex_shader_source: vertex_shader_source.to_string(),
+            fragment_shader_source: fragment_shader_source.to_string(),
+            transform_feedback_outputs: transform_feedback_outputs
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            result,
+        });
+        receiver
+    }
+
+    ///
+    /// Abandons a compilation started by [ShaderCompileThread::compile] without waiting for it to finish.
+    /// Returns the program objects if they are already created, otherwise the thread deletes them when they are.
+    ///
+    pub fn abandon(&self, receiver: CompileResult) -> Option<ProgramObjects> {
+        // The lock is held while checking the receiver, so the thread cannot send the result in between without seeing the abandoned receiver afterwards
+        let mut abandoned = self.abandoned.lock().unwrap();
+        match receiver.try_recv() {
+            Ok(result) => result.ok(),
+            Err(TryRecvError::Empty) => {
+                abandoned.push(receiver);
+                None
+            }
+            Err(TryRecvError::Disconnected) => None,
+        }
+    }
+}
+
+impl Context {
+    ///
+    /// Starts a thread which compiles and links the shader programs of a [PendingProgram], for example when using a `ShaderWarmUp`,
+    /// so the programs are compiled in parallel with rendering even if the `KHR_parallel_shader_compile` extension is not available.
+    /// Programs which are needed right away, for example the programs compiled when rendering, are still compiled on the calling thread.
+    ///
+    /// The `create_context` closure is called on the new thread. It must make a graphics context current on that thread which shares resources,
+    /// ie. shaders and programs, with this context, and return the low-level context for it together with anything that must be kept alive while the thread is running,
+    /// for example the context from the windowing library. If it returns `None`, the thread is stopped and programs are compiled as if this was not called.
+    /// The thread stops when this context and all its clones are dropped.
+    ///
+    /// This is done automatically when using the [window](crate::window) module on desktop, if the platform supports a graphics context without a surface.
+    /// Returns whether the thread was started.
+    ///
+    /// # Safety
+    /// The context made current by `create_context` must share resources with this context.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub unsafe fn enable_shader_compile_thread(
+        &mut self,
+        create_context: impl FnOnce() -> Option<(crate::context::Context, Box<dyn std::any::Any>)>
+            + Send
+            + 'static,
+    ) -> bool {
+        let (jobs, receiver) = channel::<CompileJob>();
+        let (started, is_started) = channel();
+        let program_binary_functions = self.program_binary_functions.clone();
+        let abandoned = Arc::new(Mutex::new(Vec::new()));
+        let abandoned_on_thread = abandoned.clone();
+        let thread = std::thread::Builder::new()
+            .name("three-d shader compile".to_string())
+            .spawn(move || {
+                let Some((context, _keep_alive)) = create_context() else {
+                    let _ = started.send(false);
+                    return;
+                };
+                let _ = started.send(true);
+                for job in receiver {
+                    let outputs = job
+                        .transform_feedback_outputs
+                        .iter()
+                        .map(|s| s.as_str())
+                        .collect::<Vec<_>>();
+                    let result = unsafe {
+                        let result = create_program_objects(
+                            &context,
+                            program_binary_functions.as_deref(),
+                            &job.vertex_shader_source,
+                            &job.fragment_shader_source,
+                            &outputs,
+                        );
+                        // The compilation and linking must be done before the program is used by the other context
+                        context.finish();
+                        result
+                    };
+                    if let Err(e) = job.result.send(result) {
+                        if let Ok(objects) = e.0 {
+                            unsafe { delete_program_objects(&context, objects) };
+                        }
+                    }
+                    // Delete the programs of the pending programs that were dropped before they were done compiling
+                    abandoned_on_thread
+                        .lock()
+                        .unwrap()
+                        .retain(|receiver: &CompileResult| match receiver.try_recv() {
+                            Ok(Ok(objects)) => {
+                                unsafe { delete_program_objects(&context, objects) };
+                                false
+                            }
+                            Err(TryRecvError::Empty) => true,
+                            _ => false,
+                        });
+                }
+            });
+        if thread.is_ok() && is_started.recv().unwrap_or(false) {
+            self.shader_compile_thread = Some(Arc::new(ShaderCompileThread {
+                jobs: Mutex::new(jobs),
+                abandoned,
+            }));
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Returns whether the shader programs of a [PendingProgram] are compiled on a separate thread, see [Context::enable_shader_compile_thread].
+    ///
+    pub fn has_shader_compile_thread(&self) -> bool {
+        self.shader_compile_thread.is_some()
+    }
+}
@@ -37,11 +37,75 @@ mod depth_texture2d_multisample;
 #[doc(inline)]
 pub(in crate::core) use depth_texture2d_multisample::*;
 
+mod compressed_texture;
+#[doc(inline)]
+pub use compressed_texture::*;
+
 use data_type::*;
 pub use three_d_asset::texture::{
     Interpolation, Texture2D as CpuTexture, Texture3D as CpuTexture3D, TextureData, Wrapping,
 };
 
+///
+/// Settings for how a texture is sampled, in addition to the interpolation and wrapping given when the texture is constructed.
+/// Use for example [Texture2D::set_sampler_settings] to apply them to a texture.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SamplerSettings {
+    /// The maximum amount of anisotropic filtering, where 1.0 means no anisotropic filtering and higher values, typically up to 16.0,
+    /// makes textures seen at a grazing angle sharper without shimmering. Only used if supported, see [Context::supports_anisotropic_filtering],
+    /// and clamped to the maximum supported value.
+    pub anisotropy: f32,
+    /// An offset added to the mip level chosen when sampling, where positive values gives a more blurry result and negative values a sharper result with more aliasing.
+    /// Only supported on desktop OpenGL.
+    pub lod_bias: f32,
+    /// The lowest mip level that is used when sampling, where 0.0 is the full resolution mip level.
+    pub min_lod: f32,
+    /// The highest mip level that is used when sampling.
+    pub max_lod: f32,
+}
+
+impl Default for SamplerSettings {
+    fn default() -> Self {
+        Self {
+            anisotropy: 1.0,
+            lod_bias: 0.0,
+            min_lod: -1000.0,
+            max_lod: 1000.0,
+        }
+    }
+}
+
+///
+/// Determines how the mip maps of a texture are created, if the texture has mip maps, ie. if a mip map filter is specified.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MipMapGeneration {
+    /// The mip maps are generated from the full resolution mip level each time the texture is filled or written to.
+    #[default]
+    Automatic,
+    /// The mip maps are never generated, instead the data of each mip level is provided, for example using [Texture2D::new_with_mip_chain] or [Texture2D::fill_mip_level].
+    Manual,
+}
+
+///
+/// The color space of the color data in a texture.
+///
+/// Color textures like albedo and emissive textures are usually authored in sRGB color space while data textures,
+/// for example normal, metallic and roughness textures, and HDR textures are linear.
+/// Shading must happen in linear space, so sRGB data must be converted to linear when it is sampled and converted back to sRGB
+/// when it is written to an 8-bit target which is presented or saved as an image.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// The data is linear and used unchanged, which is the case for data textures and HDR textures and for textures which have already been converted to linear sRGB.
+    #[default]
+    Linear,
+    /// The data is 8-bit sRGB encoded. The hardware converts it to linear sRGB when the texture is sampled,
+    /// and from linear sRGB when the texture is rendered into, so the shader output written to such a texture must be linear (see [crate::renderer::ColorMapping::None]).
+    Srgb,
+}
+
 /// The basic data type used for each channel of each pixel in a texture.
 pub trait TextureDataType: DataType {}
 impl TextureDataType for u8 {}
@@ -276,6 +340,7 @@ use crate::core::*;
 // COMMON TEXTURE FUNCTIONS
 
 fn generate(context: &Context) -> crate::context::Texture {
+    context.resources.created(ResourceKind::Texture);
     unsafe { context.create_texture().expect("Failed creating texture") }
 }
 
@@ -349,6 +414,25 @@ fn set_parameters(
     }
 }
 
+fn set_sampler_settings(context: &Context, target: u32, settings: SamplerSettings) {
+    unsafe {
+        if context.supports_anisotropic_filtering() {
+            let max_anisotropy =
+                context.get_parameter_f32(crate::context::MAX_TEXTURE_MAX_ANISOTROPY_EXT);
+            context.tex_parameter_f32(
+                target,
+                crate::context::TEXTURE_MAX_ANISOTROPY_EXT,
+                settings.anisotropy.clamp(1.0, max_anisotropy.max(1.0)),
+            );
+        }
+        if !context.version().is_embedded {
+            context.tex_parameter_f32(target, crate::context::TEXTURE_LOD_BIAS, settings.lod_bias);
+        }
+        context.tex_parameter_f32(target, crate::context::TEXTURE_MIN_LOD, settings.min_lod);
+        context.tex_parameter_f32(target, crate::context::TEXTURE_MAX_LOD, settings.max_lod);
+    }
+}
+
 fn calculate_number_of_mip_maps<T: TextureDataType>(
     mip_map_filter: Option<Interpolation>,
     width: u32,
@@ -0,0 +1,646 @@
+use crate::core::texture::*;
+
+///
+/// The block compressed formats supported by [CpuCompressedTexture].
+/// All of the formats compress blocks of 4x4 pixels.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompressedFormat {
+    /// RGBA with 1-bit alpha, also known as DXT1 (8 bytes per block).
+    Bc1,
+    /// RGBA with explicit 4-bit alpha, also known as DXT3 (16 bytes per block).
+    Bc2,
+    /// RGBA with interpolated alpha, also known as DXT5 (16 bytes per block).
+    Bc3,
+    /// A single channel (red), also known as RGTC1 or ATI1 (8 bytes per block).
+    Bc4,
+    /// Two channels (red and green), also known as RGTC2 or ATI2, for example used for normal maps (16 bytes per block).
+    Bc5,
+}
+
+impl CompressedFormat {
+    ///
+    /// The number of bytes used to store a block of 4x4 pixels.
+    ///
+    pub fn block_byte_size(&self) -> usize {
+        match self {
+            Self::Bc1 | Self::Bc4 => 8,
+            Self::Bc2 | Self::Bc3 | Self::Bc5 => 16,
+        }
+    }
+
+    ///
+    /// The number of bytes used to store an image of the given size or `None` if the number of bytes is too large to be represented by a `usize`.
+    ///
+    pub fn byte_size(&self, width: u32, height: u32) -> Option<usize> {
+        (width.div_ceil(4) as usize)
+            .checked_mul(height.div_ceil(4) as usize)?
+            .checked_mul(self.block_byte_size())
+    }
+
+    pub(in crate::core) fn internal_format(&self, srgb: bool) -> u32 {
+        use crate::context::*;
+        match (self, srgb) {
+            (Self::Bc1, false) => COMPRESSED_RGBA_S3TC_DXT1_EXT,
+            (Self::Bc1, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT,
+            (Self::Bc2, false) => COMPRESSED_RGBA_S3TC_DXT3_EXT,
+            (Self::Bc2, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT,
+            (Self::Bc3, false) => COMPRESSED_RGBA_S3TC_DXT5_EXT,
+            (Self::Bc3, true) => COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT,
+            (Self::Bc4, _) => COMPRESSED_RED_RGTC1,
+            (Self::Bc5, _) => COMPRESSED_RG_RGTC2,
+        }
+    }
+}
+
+///
+/// A block compressed 2D texture on the CPU side, for example loaded from a DDS or KTX2 file using [CpuCompressedTexture::deserialize].
+/// Compressed textures use a fraction of the memory and bandwidth of uncompressed textures and can be uploaded to the GPU without
+/// decompression using [Texture2D::new_compressed] if the format is supported, see [Context::supports_compressed_format].
+///
+#[derive(Clone, Debug)]
+pub struct CpuCompressedTexture {
+    /// Name of this texture.
+    pub name: String,
+    /// The compressed format of the data.
+    pub format: CompressedFormat,
+    /// Whether the color channels are in sRGB color space, in which case they are converted to linear sRGB when sampled.
+    /// Only used for the [CompressedFormat::Bc1], [CompressedFormat::Bc2] and [CompressedFormat::Bc3] formats.
+    pub srgb: bool,
+    /// The width of the texture.
+    pub width: u32,
+    /// The height of the texture.
+    pub height: u32,
+    /// The compressed data of each mip level, starting with the full size image.
+    /// The blocks are stored row by row starting with the top row of the image.
+    pub mip_levels: Vec<Vec<u8>>,
+    /// The way the pixel data is interpolated when the texture is far away.
+    pub min_filter: Interpolation,
+    /// The way the pixel data is interpolated when the texture is close.
+    pub mag_filter: Interpolation,
+    /// Specifies whether mipmaps are used and how they are interpolated. Only used if there is more than one mip level
+    /// or if the texture is decompressed before it is uploaded to the GPU, in which case the mip maps are generated.
+    pub mip_map_filter: Option<Interpolation>,
+    /// Determines how the texture is sampled outside the [0..1] s coordinate range (the first value of the uv coordinates).
+    pub wrap_s: Wrapping,
+    /// Determines how the texture is sampled outside the [0..1] t coordinate range (the second value of the uv coordinates).
+    pub wrap_t: Wrapping,
+}
+
+const DDS_MAGIC: &[u8] = b"DDS ";
+const KTX2_MAGIC: &[u8] = &[
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+impl CpuCompressedTexture {
+    ///
+    /// Deserialize a compressed texture from the bytes of a DDS or a KTX2 file. The file format is determined from the content.
+    ///
+    /// Only 2D textures compressed with one of the [CompressedFormat]s are supported. KTX2 files with supercompression,
+    /// including Basis Universal textures, have to be transcoded to one of these formats before they can be loaded,
+    /// for example using the `toktx` or `basisu` tools.
+    ///
+    pub fn deserialize(name: impl Into<String>, bytes: &[u8]) -> Result<Self, CoreError> {
+        let (format, srgb, width, height, mip_levels) = if bytes.starts_with(DDS_MAGIC) {
+            parse_dds(bytes)?
+        } else if bytes.starts_with(KTX2_MAGIC) {
+            parse_ktx2(bytes)?
+        } else {
+            Err(invalid("not a DDS or KTX2 file"))?
+        };
+        Ok(Self {
+            name: name.into(),
+            format,
+            srgb,
+            width,
+            height,
+            mip_levels,
+            min_filter: Interpolation::Linear,
+            mag_filter: Interpolation::Linear,
+            mip_map_filter: Some(Interpolation::Linear),
+            wrap_s: Wrapping::Repeat,
+            wrap_t: Wrapping::Repeat,
+        })
+    }
+
+    ///
+    /// Decompresses the full size image of this texture into a [CpuTexture],
+    /// which is used as a fallback when the compressed format is not supported by the GPU.
+    /// The texture data is `RgbaU8` for [CompressedFormat::Bc1], [CompressedFormat::Bc2] and [CompressedFormat::Bc3],
+    /// `RU8` for [CompressedFormat::Bc4] and `RgU8` for [CompressedFormat::Bc5]. The colors are not converted from sRGB.
+    /// Returns an error if the texture does not have any mip levels or is too large to be decompressed.
+    ///
+    pub fn decompress(&self) -> Result<CpuTexture, CoreError> {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let blocks_x = width.div_ceil(4);
+        let data = self
+            .mip_levels
+            .first()
+            .ok_or_else(|| invalid("the texture does not have any mip levels"))?;
+        let pixel_count = width
+            .checked_mul(height)
+            .ok_or_else(|| invalid("the texture is too large to be decompressed"))?;
+        let mut pixels = vec![[0u8; 4]; pixel_count];
+        for (i, block) in data.chunks_exact(self.format.block_byte_size()).enumerate() {
+            let decoded = match self.format {
+                CompressedFormat::Bc1 => decode_color_block(block, true),
+                CompressedFormat::Bc2 => {
+                    let mut decoded = decode_color_block(&block[8..], false);
+                    for (p, pixel) in decoded.iter_mut().enumerate() {
+                        let row = u16::from_le_bytes([block[2 * (p / 4)], block[2 * (p / 4) + 1]]);
+                        pixel[3] = ((row >> (4 * (p % 4))) & 0xF) as u8 * 17;
+                    }
+                    decoded
+                }
+                CompressedFormat::Bc3 => {
+                    let mut decoded = decode_color_block(&block[8..], false);
+                    for (pixel, alpha) in decoded.iter_mut().zip(decode_channel_block(block)) {
+                        pixel[3] = alpha;
+                    }
+                    decoded
+                }
+                CompressedFormat::Bc4 => decode_channel_block(block).map(|r| [r, 0, 0, 255]),
+                CompressedFormat::Bc5 => {
+                    let green = decode_channel_block(&block[8..]);
+                    let mut decoded = decode_channel_block(block).map(|r| [r, 0, 0, 255]);
+                    for (pixel, g) in decoded.iter_mut().zip(green) {
+                        pixel[1] = g;
+                    }
+                    decoded
+                }
+            };
+            for (p, pixel) in decoded.into_iter().enumerate() {
+                let x = (i % blocks_x) * 4 + p % 4;
+                let y = (i / blocks_x) * 4 + p / 4;
+                if x < width && y < height {
+                    pixels[y * width + x] = pixel;
+                }
+            }
+        }
+        let data = match self.format {
+            CompressedFormat::Bc1 | CompressedFormat::Bc2 | CompressedFormat::Bc3 => {
+                TextureData::RgbaU8(pixels)
+            }
+            CompressedFormat::Bc4 => TextureData::RU8(pixels.iter().map(|p| p[0]).collect()),
+            CompressedFormat::Bc5 => {
+                TextureData::RgU8(pixels.iter().map(|p| [p[0], p[1]]).collect())
+            }
+        };
+        Ok(CpuTexture {
+            name: self.name.clone(),
+            data,
+            width: self.width,
+            height: self.height,
+            min_filter: self.min_filter,
+            mag_filter: self.mag_filter,
+            mip_map_filter: self.mip_map_filter,
+            wrap_s: self.wrap_s,
+            wrap_t: self.wrap_t,
+        })
+    }
+
+    ///
+    /// Returns the mip levels with the block rows in reverse order and the pixel rows inside each block reversed,
+    /// which is the order the rows are uploaded to the GPU in, or `None` if that is not possible
+    /// because the height of a mip level is not a multiple of the block size.
+    ///
+    pub(in crate::core) fn flipped_mip_levels(&self) -> Option<Vec<Vec<u8>>> {
+        let block_byte_size = self.format.block_byte_size();
+        let mut levels = Vec::with_capacity(self.mip_levels.len());
+        for (level, data) in self.mip_levels.iter().enumerate() {
+            let width = (self.width >> level).max(1);
+            let height = (self.height >> level).max(1);
+            if height > 4 && !height.is_multiple_of(4) {
+                return None;
+            }
+            let rows = height.min(4) as usize;
+            let row_byte_size = self.format.byte_size(width, 1)?;
+            let mut flipped = Vec::with_capacity(data.len());
+            for block_row in data.chunks_exact(row_byte_size).rev() {
+                for block in block_row.chunks_exact(block_byte_size) {
+                    let mut block = block.to_vec();
+                    match self.format {
+                        CompressedFormat::Bc1 => flip_color_block(&mut block, rows),
+                        CompressedFormat::Bc2 => {
+                            block[..2 * rows].reverse();
+                            for row in block[..2 * rows].chunks_exact_mut(2) {
+                                row.swap(0, 1);
+                            }
+                            flip_color_block(&mut block[8..], rows);
+                        }
+                        CompressedFormat::Bc3 => {
+                            flip_channel_block(&mut block, rows);
+                            flip_color_block(&mut block[8..], rows);
+                        }
+                        CompressedFormat::Bc4 => flip_channel_block(&mut block, rows),
+                        CompressedFormat::Bc5 => {
+                            flip_channel_block(&mut block, rows);
+                            flip_channel_block(&mut block[8..], rows);
+                        }
+                    }
+                    flipped.extend_from_slice(&block);
+                }
+            }
+            levels.push(flipped);
+        }
+        Some(levels)
+    }
+}
+
+type ParsedTexture = (CompressedFormat, bool, u32, u32, Vec<Vec<u8>>);
+
+fn parse_dds(bytes: &[u8]) -> Result<ParsedTexture, CoreError> {
+    const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+    const DDPF_FOURCC: u32 = 0x4;
+    const DDSCAPS2_CUBEMAP: u32 = 0x200;
+    const DDSCAPS2_VOLUME: u32 = 0x200000;
+
+    let flags = read_u32(bytes, 8)?;
+    let height = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 16)?;
+    let mip_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        read_u32(bytes, 28)?.max(1)
+    } else {
+        1
+    };
+    if read_u32(bytes, 80)? & DDPF_FOURCC == 0 {
+        Err(invalid("only block compressed DDS files are supported"))?;
+    }
+    if read_u32(bytes, 112)? & (DDSCAPS2_CUBEMAP | DDSCAPS2_VOLUME) != 0 {
+        Err(invalid("only 2D textures are supported"))?;
+    }
+    let (format, srgb, offset) = match read_bytes(bytes, 84, 4)? {
+        b"DXT1" => (CompressedFormat::Bc1, false, 128),
+        b"DXT2" | b"DXT3" => (CompressedFormat::Bc2, false, 128),
+        b"DXT4" | b"DXT5" => (CompressedFormat::Bc3, false, 128),
+        b"ATI1" | b"BC4U" => (CompressedFormat::Bc4, false, 128),
+        b"ATI2" | b"BC5U" => (CompressedFormat::Bc5, false, 128),
+        b"DX10" => {
+            const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+            if read_u32(bytes, 128 + 8)? & DDS_RESOURCE_MISC_TEXTURECUBE != 0
+                || read_u32(bytes, 128 + 12)? > 1
+            {
+                Err(invalid("only 2D textures are supported"))?;
+            }
+            let (format, srgb) = match read_u32(bytes, 128)? {
+                70 | 71 => (CompressedFormat::Bc1, false),
+                72 => (CompressedFormat::Bc1, true),
+                73 | 74 => (CompressedFormat::Bc2, false),
+                75 => (CompressedFormat::Bc2, true),
+                76 | 77 => (CompressedFormat::Bc3, false),
+                78 => (CompressedFormat::Bc3, true),
+                79 | 80 => (CompressedFormat::Bc4, false),
+                82 | 83 => (CompressedFormat::Bc5, false),
+                dxgi_format => Err(invalid(format!(
+                    "the DXGI format {} is not supported",
+                    dxgi_format
+                )))?,
+            };
+            (format, srgb, 148)
+        }
+        four_cc => Err(invalid(format!(
+            "the format {} is not supported",
+            String::from_utf8_lossy(four_cc)
+        )))?,
+    };
+
+    let mut offset = offset;
+    let mut mip_levels = Vec::new();
+    for level in 0..mip_count {
+        let size = mip_level_byte_size(format, width, height, level)?;
+        mip_levels.push(read_bytes(bytes, offset, size)?.to_vec());
+        offset += size;
+    }
+    Ok((format, srgb, width, height, mip_levels))
+}
+
+fn parse_ktx2(bytes: &[u8]) -> Result<ParsedTexture, CoreError> {
+    let vk_format = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 20)?;
+    let height = read_u32(bytes, 24)?;
+    if read_u32(bytes, 28)? > 0 || read_u32(bytes, 32)? > 0 || read_u32(bytes, 36)? > 1 {
+        Err(invalid("only 2D textures are supported"))?;
+    }
+    let level_count = read_u32(bytes, 40)?.max(1);
+    match read_u32(bytes, 44)? {
+        0 => {}
+        1 => Err(invalid(
+            "Basis Universal textures have to be transcoded to a block compressed format",
+        ))?,
+        scheme => Err(invalid(format!(
+            "the supercompression scheme {} is not supported",
+            scheme
+        )))?,
+    }
+    let (format, srgb) = match vk_format {
+        131 | 133 => (CompressedFormat::Bc1, false),
+        132 | 134 => (CompressedFormat::Bc1, true),
+        135 => (CompressedFormat::Bc2, false),
+        136 => (CompressedFormat::Bc2, true),
+        137 => (CompressedFormat::Bc3, false),
+        138 => (CompressedFormat::Bc3, true),
+        139 => (CompressedFormat::Bc4, false),
+        141 => (CompressedFormat::Bc5, false),
+        0 => Err(invalid(
+            "Basis Universal (UASTC) textures have to be transcoded to a block compressed format",
+        ))?,
+        vk_format => Err(invalid(format!(
+            "the Vulkan format {} is not supported",
+            vk_format
+        )))?,
+    };
+
+    let mut mip_levels = Vec::new();
+    for level in 0..level_count {
+        let index = 80 + 24 * level as usize;
+        let offset = to_usize(read_u64(bytes, index)?)?;
+        let length = to_usize(read_u64(bytes, index + 8)?)?;
+        let size = mip_level_byte_size(format, width, height, level)?;
+        if length != size {
+            Err(invalid(format!(
+                "mip level {} has {} bytes but expected {} bytes",
+                level, length, size
+            )))?;
+        }
+        mip_levels.push(read_bytes(bytes, offset, size)?.to_vec());
+    }
+    Ok((format, srgb, width, height, mip_levels))
+}
+
+fn invalid(message: impl Into<String>) -> CoreError {
+    CoreError::InvalidCompressedTexture(message.into())
+}
+
+///
+/// Returns the number of bytes of the given mip level of a texture with the given size or an error if a malformed header gives an impossible size.
+///
+fn mip_level_byte_size(
+    format: CompressedFormat,
+    width: u32,
+    height: u32,
+    level: u32,
+) -> Result<usize, CoreError> {
+    if level >= u32::BITS {
+        Err(invalid(format!("too many mip levels ({})", level + 1)))?;
+    }
+    format
+        .byte_size((width >> level).max(1), (height >> level).max(1))
+        .ok_or_else(|| invalid(format!("the size {}x{} is too large", width, height)))
+}
+
+fn to_usize(value: u64) -> Result<usize, CoreError> {
+    usize::try_from(value).map_err(|_| invalid("unexpected end of file"))
+}
+
+fn read_bytes(bytes: &[u8], offset: usize, length: usize) -> Result<&[u8], CoreError> {
+    offset
+        .checked_add(length)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or_else(|| invalid("unexpected end of file"))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, CoreError> {
+    Ok(u32::from_le_bytes(
+        read_bytes(bytes, offset, 4)?.try_into().unwrap(),
+    ))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, CoreError> {
+    Ok(u64::from_le_bytes(
+        read_bytes(bytes, offset, 8)?.try_into().unwrap(),
+    ))
+}
+
+///
+/// Decodes a BC1 color block into 16 pixels in row-major order.
+/// The 1-bit alpha mode is only available for BC1, the color part of BC2 and BC3 blocks always use four colors.
+///
+fn decode_color_block(block: &[u8], allow_alpha: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let expand = |c: u16| {
+        let r = ((c >> 11) & 0x1F) as u32;
+        let g = ((c >> 5) & 0x3F) as u32;
+        let b = (c & 0x1F) as u32;
+        [
+            (r << 3) | (r >> 2),
+            (g << 2) | (g >> 4),
+            (b << 3) | (b >> 2),
+        ]
+    };
+    let (e0, e1) = (expand(c0), expand(c1));
+    let mix = |w0: u32, w1: u32| {
+        let [r, g, b] = [0, 1, 2].map(|i| ((w0 * e0[i] + w1 * e1[i]) / (w0 + w1)) as u8);
+        [r, g, b, 255]
+    };
+    let palette = if c0 > c1 || !allow_alpha {
+        [mix(1, 0), mix(0, 1), mix(2, 1), mix(1, 2)]
+    } else {
+        [mix(1, 0), mix(0, 1), mix(1, 1), [0, 0, 0, 0]]
+    };
+    let mut pixels = [[0u8; 4]; 16];
+    for (p, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = palette[((block[4 + p / 4] >> (2 * (p % 4))) & 0x3) as usize];
+    }
+    pixels
+}
+
+///
+/// Decodes a BC4 block, which is also the alpha part of a BC3 block, into 16 values in row-major order.
+///
+fn decode_channel_block(block: &[u8]) -> [u8; 16] {
+    let a0 = block[0] as u32;
+    let a1 = block[1] as u32;
+    let mut palette = [0u8; 8];
+    palette[0] = a0 as u8;
+    palette[1] = a1 as u8;
+    if a0 > a1 {
+        for k in 1..7 {
+            palette[k as usize + 1] = (((7 - k) * a0 + k * a1) / 7) as u8;
+        }
+    } else {
+        for k in 1..5 {
+            palette[k as usize + 1] = (((5 - k) * a0 + k * a1) / 5) as u8;
+        }
+        palette[6] = 0;
+        palette[7] = 255;
+    }
+    let indices = channel_indices(block);
+    let mut values = [0u8; 16];
+    for (p, value) in values.iter_mut().enumerate() {
+        *value = palette[((indices >> (3 * p)) & 0x7) as usize];
+    }
+    values
+}
+
+fn channel_indices(block: &[u8]) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes[..6].copy_from_slice(&block[2..8]);
+    u64::from_le_bytes(bytes)
+}
+
+fn flip_color_block(block: &mut [u8], rows: usize) {
+    block[4..4 + rows].reverse();
+}
+
+fn flip_channel_block(block: &mut [u8], rows: usize) {
+    let indices = channel_indices(block);
+    let mut flipped = indices;
+    for row in 0..rows {
+        let mask = 0xFFFu64 << (12 * row);
+        flipped =
+            (flipped & !mask) | (((indices >> (12 * (rows - 1 - row))) & 0xFFF) << (12 * row));
+    }
+    block[2..8].copy_from_slice(&flipped.to_le_bytes()[..6]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dds_header(four_cc: &[u8; 4], width: u32, height: u32, mip_count: u32) -> Vec<u8> {
+        let mut bytes = vec![0u8; 128];
+        bytes[..4].copy_from_slice(DDS_MAGIC);
+        bytes[8..12].copy_from_slice(&0x20000u32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&height.to_le_bytes());
+        bytes[16..20].copy_from_slice(&width.to_le_bytes());
+        bytes[28..32].copy_from_slice(&mip_count.to_le_bytes());
+        bytes[80..84].copy_from_slice(&0x4u32.to_le_bytes());
+        bytes[84..88].copy_from_slice(four_cc);
+        bytes
+    }
+
+    fn ktx2_header(vk_format: u32, width: u32, height: u32, levels: &[(u64, u64)]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 80];
+        bytes[..12].copy_from_slice(KTX2_MAGIC);
+        bytes[12..16].copy_from_slice(&vk_format.to_le_bytes());
+        bytes[20..24].copy_from_slice(&width.to_le_bytes());
+        bytes[24..28].copy_from_slice(&height.to_le_bytes());
+        bytes[40..44].copy_from_slice(&(levels.len() as u32).to_le_bytes());
+        for (offset, length) in levels {
+            bytes.extend(offset.to_le_bytes());
+            bytes.extend(length.to_le_bytes());
+            bytes.extend(length.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn dds_mip_levels_follow_the_header() {
+        let mut bytes = dds_header(b"DXT1", 8, 4, 2);
+        let data = (0..24).collect::<Vec<u8>>();
+        bytes.extend(&data);
+        let texture = CpuCompressedTexture::deserialize("dds", &bytes).unwrap();
+        assert_eq!(texture.format, CompressedFormat::Bc1);
+        assert!(!texture.srgb);
+        assert_eq!((texture.width, texture.height), (8, 4));
+        assert_eq!(
+            texture.mip_levels,
+            vec![data[..16].to_vec(), data[16..].to_vec()]
+        );
+
+        bytes.pop();
+        assert!(CpuCompressedTexture::deserialize("dds", &bytes).is_err());
+    }
+
+    #[test]
+    fn dds_dx10_header_is_skipped() {
+        let mut bytes = dds_header(b"DX10", 4, 4, 1);
+        let mut dx10 = [0u8; 20];
+        dx10[..4].copy_from_slice(&72u32.to_le_bytes());
+        dx10[12..16].copy_from_slice(&1u32.to_le_bytes());
+        bytes.extend(dx10);
+        bytes.extend(0..8);
+        let texture = CpuCompressedTexture::deserialize("dds", &bytes).unwrap();
+        assert_eq!(texture.format, CompressedFormat::Bc1);
+        assert!(texture.srgb);
+        assert_eq!(texture.mip_levels, vec![(0..8).collect::<Vec<u8>>()]);
+    }
+
+    #[test]
+    fn ktx2_mip_levels_are_read_at_their_offsets() {
+        // The smallest mip level is stored first, as written by most tools
+        let mut bytes = ktx2_header(137, 8, 4, &[(144, 32), (128, 16)]);
+        bytes.resize(128, 0);
+        bytes.extend(100..116);
+        bytes.extend(0..32);
+        let texture = CpuCompressedTexture::deserialize("ktx2", &bytes).unwrap();
+        assert_eq!(texture.format, CompressedFormat::Bc3);
+        assert_eq!(
+            texture.mip_levels,
+            vec![
+                (0..32).collect::<Vec<u8>>(),
+                (100..116).collect::<Vec<u8>>()
+            ]
+        );
+
+        let bytes = ktx2_header(137, 8, 4, &[(144, 16), (128, 16)]);
+        assert!(CpuCompressedTexture::deserialize("ktx2", &bytes).is_err());
+    }
+
+    #[test]
+    fn malformed_sizes_and_offsets_are_errors() {
+        let bytes = dds_header(b"DXT5", u32::MAX, u32::MAX, 1);
+        assert!(CpuCompressedTexture::deserialize("dds", &bytes).is_err());
+        let bytes = dds_header(b"DXT1", 4, 4, 40);
+        assert!(CpuCompressedTexture::deserialize("dds", &bytes).is_err());
+        let bytes = ktx2_header(131, 4, 4, &[(u64::MAX, 8)]);
+        assert!(CpuCompressedTexture::deserialize("ktx2", &bytes).is_err());
+        assert!(CpuCompressedTexture::deserialize("none", b"DDS").is_err());
+    }
+
+    fn bc1_texture(width: u32, height: u32, data: Vec<u8>) -> CpuCompressedTexture {
+        CpuCompressedTexture {
+            name: String::new(),
+            format: CompressedFormat::Bc1,
+            srgb: false,
+            width,
+            height,
+            mip_levels: vec![data],
+            min_filter: Interpolation::Linear,
+            mag_filter: Interpolation::Linear,
+            mip_map_filter: None,
+            wrap_s: Wrapping::Repeat,
+            wrap_t: Wrapping::Repeat,
+        }
+    }
+
+    #[test]
+    fn flip_reverses_block_rows_and_pixel_rows() {
+        let top = [0, 1, 2, 3, 10, 11, 12, 13];
+        let bottom = [4, 5, 6, 7, 20, 21, 22, 23];
+        let texture = bc1_texture(4, 8, [top, bottom].concat());
+        assert_eq!(
+            texture.flipped_mip_levels().unwrap(),
+            vec![vec![4, 5, 6, 7, 23, 22, 21, 20, 0, 1, 2, 3, 13, 12, 11, 10]]
+        );
+
+        // Only the rows inside the image are flipped for a mip level which is smaller than a block
+        let texture = bc1_texture(4, 2, top.to_vec());
+        assert_eq!(
+            texture.flipped_mip_levels().unwrap(),
+            vec![vec![0, 1, 2, 3, 11, 10, 12, 13]]
+        );
+
+        assert!(bc1_texture(4, 6, [top, bottom].concat())
+            .flipped_mip_levels()
+            .is_none());
+    }
+
+    #[test]
+    fn decompress_bc1() {
+        // The first color is white and all pixels use the first color except the last one which uses the second color, which is black
+        let texture = bc1_texture(4, 4, vec![0xFF, 0xFF, 0, 0, 0, 0, 0, 0b01000000]);
+        let TextureData::RgbaU8(pixels) = texture.decompress().unwrap().data else {
+            panic!("expected RGBA data");
+        };
+        assert_eq!(pixels.len(), 16);
+        assert!(pixels[..15].iter().all(|p| *p == [255, 255, 255, 255]));
+        assert_eq!(pixels[15], [0, 0, 0, 255]);
+
+        let mut texture = texture;
+        texture.mip_levels.clear();
+        assert!(texture.decompress().is_err());
+    }
+}
@@ -21,6 +21,7 @@ impl DepthTexture2D {
         wrap_s: Wrapping,
         wrap_t: Wrapping,
     ) -> Self {
+        span!(DEBUG, "create_depth_texture_2d", width, height);
         let id = generate(context);
         let texture = Self {
             context: context.clone(),
@@ -93,6 +94,7 @@ impl Drop for DepthTexture2D {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_texture(self.id);
+            self.context.resources.released(ResourceKind::Texture);
         }
     }
 }
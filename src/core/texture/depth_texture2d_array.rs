@@ -23,6 +23,7 @@ impl DepthTexture2DArray {
         wrap_s: Wrapping,
         wrap_t: Wrapping,
     ) -> Self {
+        span!(DEBUG, "create_depth_texture_2d_array", width, height, depth);
         let id = generate(context);
         let texture = Self {
             context: context.clone(),
@@ -102,6 +103,7 @@ impl Drop for DepthTexture2DArray {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_texture(self.id);
+            self.context.resources.released(ResourceKind::Texture);
         }
     }
 }
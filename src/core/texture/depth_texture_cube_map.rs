@@ -22,6 +22,7 @@ impl DepthTextureCubeMap {
         wrap_t: Wrapping,
         wrap_r: Wrapping,
     ) -> Self {
+        span!(DEBUG, "create_depth_texture_cube_map", width, height);
         let id = generate(context);
         let texture = Self {
             context: context.clone(),
@@ -94,6 +95,7 @@ impl Drop for DepthTextureCubeMap {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_texture(self.id);
+            self.context.resources.released(ResourceKind::Texture);
         }
     }
 }
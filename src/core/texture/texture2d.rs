@@ -9,6 +9,8 @@ pub struct Texture2D {
     width: u32,
     height: u32,
     number_of_mip_maps: u32,
+    mip_map_generation: MipMapGeneration,
+    color_space: ColorSpace,
     data_byte_size: usize,
 }
 
@@ -35,6 +37,40 @@ impl Texture2D {
         }
     }
 
+    ///
+    /// Constructs a new texture with the given data in the given color space.
+    /// If the color space is [ColorSpace::Srgb] and the data is `RgbU8` or `RgbaU8`, the texture is stored in an sRGB format
+    /// which the hardware converts to linear sRGB when the texture is sampled, which is more precise than converting the data to linear sRGB before upload.
+    /// `RgbU8` data is expanded to `RgbaU8` in that case, since not all platforms are able to generate mip maps for an RGB sRGB texture.
+    /// For all other formats, the color space is ignored and the data is assumed to be linear.
+    ///
+    pub fn new_with_color_space(
+        context: &Context,
+        cpu_texture: &CpuTexture,
+        color_space: ColorSpace,
+    ) -> Self {
+        let data = match cpu_texture.data {
+            TextureData::RgbU8(ref data) if color_space == ColorSpace::Srgb => data
+                .iter()
+                .map(|c| [c[0], c[1], c[2], 255])
+                .collect::<Vec<_>>(),
+            TextureData::RgbaU8(ref data) if color_space == ColorSpace::Srgb => data.clone(),
+            _ => return Self::new(context, cpu_texture),
+        };
+        let mut texture = Self::new_empty_srgb(
+            context,
+            cpu_texture.width,
+            cpu_texture.height,
+            cpu_texture.min_filter,
+            cpu_texture.mag_filter,
+            cpu_texture.mip_map_filter,
+            cpu_texture.wrap_s,
+            cpu_texture.wrap_t,
+        );
+        texture.fill(&data);
+        texture
+    }
+
     fn new_with_data<T: TextureDataType>(
         context: &Context,
         cpu_texture: &CpuTexture,
@@ -54,6 +90,199 @@ impl Texture2D {
         texture
     }
 
+    ///
+    /// Constructs a new texture with the given mip chain, where the first texture is the full resolution mip level
+    /// and each of the following textures is half the width and height of the previous one, rounded down but at least one pixel.
+    /// The mip maps are not generated automatically (see [MipMapGeneration::Manual]), which makes it possible to use mip maps
+    /// that are prefiltered offline, for example with a better filter or sharpening to avoid shimmering of distant surfaces.
+    /// The interpolation, wrapping and, if more than one texture is given, the mip map filter of the first texture is used.
+    ///
+    /// # Panic
+    /// Will panic if no textures are given, if the textures do not have the same [TextureDataType] or if the size of a texture is wrong.
+    ///
+    pub fn new_with_mip_chain(context: &Context, mip_chain: &[CpuTexture]) -> Self {
+        let cpu_texture = mip_chain
+            .first()
+            .expect("Expect at least one texture in a mip chain");
+        match cpu_texture.data {
+            TextureData::RU8(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(ru8_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RgU8(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rgu8_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RgbU8(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rgbu8_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RgbaU8(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rgbau8_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RF16(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rf16_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RgF16(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rgf16_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RgbF16(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rgbf16_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RgbaF16(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rgbaf16_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RF32(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rf32_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RgF32(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rgf32_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RgbF32(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rgbf32_data).collect::<Vec<_>>(),
+            ),
+            TextureData::RgbaF32(_) => Self::new_with_mip_chain_data(
+                context,
+                cpu_texture,
+                &mip_chain.iter().map(rgbaf32_data).collect::<Vec<_>>(),
+            ),
+        }
+    }
+
+    fn new_with_mip_chain_data<T: TextureDataType>(
+        context: &Context,
+        cpu_texture: &CpuTexture,
+        data: &[&[T]],
+    ) -> Self {
+        let mut texture = Self::new_empty_with_mip_maps::<T>(
+            context,
+            cpu_texture.width,
+            cpu_texture.height,
+            cpu_texture.min_filter,
+            cpu_texture.mag_filter,
+            cpu_texture.mip_map_filter,
+            cpu_texture.wrap_s,
+            cpu_texture.wrap_t,
+            data.len() as u32,
+        );
+        texture.mip_map_generation = MipMapGeneration::Manual;
+        for (mip_level, data) in data.iter().enumerate() {
+            texture.fill_mip_level(mip_level as u32, data);
+        }
+        texture
+    }
+
+    ///
+    /// Constructs a new texture from compressed data, for example loaded from a DDS or KTX2 file using [CpuCompressedTexture::deserialize].
+    /// The data is uploaded to the GPU without decompression if the format is supported (see [Context::supports_compressed_format]),
+    /// otherwise the texture is decompressed on the CPU (see [CpuCompressedTexture::decompress]) and uploaded as an uncompressed texture.
+    /// The same happens if the height of a mip level is larger than 4 and not a multiple of 4, since the rows cannot be flipped without decompression.
+    /// If [CpuCompressedTexture::srgb] is true, the texture is in [ColorSpace::Srgb] in both cases, so the hardware converts the colors to linear sRGB when the texture is sampled.
+    ///
+    /// **Note:** A compressed texture cannot be filled with new data, written to or have mip maps generated, so the [MipMapGeneration] is [MipMapGeneration::Manual].
+    ///
+    /// Returns an error if the texture has to be decompressed and cannot be, see [CpuCompressedTexture::decompress].
+    ///
+    pub fn new_compressed(
+        context: &Context,
+        cpu_texture: &CpuCompressedTexture,
+    ) -> Result<Self, CoreError> {
+        let mip_levels = cpu_texture
+            .flipped_mip_levels()
+            .filter(|_| context.supports_compressed_format(cpu_texture.format, cpu_texture.srgb));
+        let Some(mip_levels) = mip_levels else {
+            let color_space = if cpu_texture.srgb {
+                ColorSpace::Srgb
+            } else {
+                ColorSpace::Linear
+            };
+            return Ok(Self::new_with_color_space(
+                context,
+                &cpu_texture.decompress()?,
+                color_space,
+            ));
+        };
+        span!(
+            DEBUG,
+            "create_compressed_texture_2d",
+            cpu_texture.width,
+            cpu_texture.height
+        );
+        let id = generate(context);
+        let number_of_mip_maps = mip_levels.len() as u32;
+        let texture = Self {
+            context: context.clone(),
+            id,
+            width: cpu_texture.width,
+            height: cpu_texture.height,
+            number_of_mip_maps,
+            mip_map_generation: MipMapGeneration::Manual,
+            color_space: if cpu_texture.srgb {
+                ColorSpace::Srgb
+            } else {
+                ColorSpace::Linear
+            },
+            data_byte_size: 0,
+        };
+        texture.bind();
+        set_parameters(
+            context,
+            crate::context::TEXTURE_2D,
+            cpu_texture.min_filter,
+            cpu_texture.mag_filter,
+            if number_of_mip_maps == 1 {
+                None
+            } else {
+                cpu_texture.mip_map_filter
+            },
+            cpu_texture.wrap_s,
+            cpu_texture.wrap_t,
+            None,
+        );
+        let internal_format = cpu_texture.format.internal_format(cpu_texture.srgb);
+        unsafe {
+            context.tex_storage_2d(
+                crate::context::TEXTURE_2D,
+                number_of_mip_maps as i32,
+                internal_format,
+                cpu_texture.width as i32,
+                cpu_texture.height as i32,
+            );
+            for (level, data) in mip_levels.iter().enumerate() {
+                context.compressed_tex_sub_image_2d(
+                    crate::context::TEXTURE_2D,
+                    level as i32,
+                    0,
+                    0,
+                    (cpu_texture.width >> level).max(1) as i32,
+                    (cpu_texture.height >> level).max(1) as i32,
+                    internal_format,
+                    crate::context::CompressedPixelUnpackData::Slice(data),
+                );
+            }
+        }
+        Ok(texture)
+    }
+
     ///
     /// Constructs a new empty 2D texture with the given parameters.
     /// The format is determined by the generic [TextureDataType] parameter
@@ -71,15 +300,100 @@ impl Texture2D {
         wrap_s: Wrapping,
         wrap_t: Wrapping,
     ) -> Self {
-        let id = generate(context);
         let number_of_mip_maps =
             calculate_number_of_mip_maps::<T>(mip_map_filter, width, height, None);
+        Self::new_empty_with_mip_maps::<T>(
+            context,
+            width,
+            height,
+            min_filter,
+            mag_filter,
+            mip_map_filter,
+            wrap_s,
+            wrap_t,
+            number_of_mip_maps,
+        )
+    }
+
+    ///
+    /// Constructs a new empty 2D texture in sRGB color space (see [ColorSpace::Srgb]) with 8-bit RGBA data, ie. the data type is `[u8; 4]`.
+    /// When rendering into this texture, the linear output of the shader is converted to sRGB by the hardware,
+    /// so, in contrast to a linear 8-bit texture, the precision is distributed like in the final image and no banding is introduced in dark areas.
+    /// Use [crate::renderer::ColorMapping::None] when rendering into this texture and note that the clear color is also assumed to be linear.
+    ///
+    pub fn new_empty_srgb(
+        context: &Context,
+        width: u32,
+        height: u32,
+        min_filter: Interpolation,
+        mag_filter: Interpolation,
+        mip_map_filter: Option<Interpolation>,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+    ) -> Self {
+        let number_of_mip_maps =
+            calculate_number_of_mip_maps::<[u8; 4]>(mip_map_filter, width, height, None);
+        Self::new_empty_with_format::<[u8; 4]>(
+            context,
+            width,
+            height,
+            min_filter,
+            mag_filter,
+            mip_map_filter,
+            wrap_s,
+            wrap_t,
+            number_of_mip_maps,
+            ColorSpace::Srgb,
+        )
+    }
+
+    fn new_empty_with_mip_maps<T: TextureDataType>(
+        context: &Context,
+        width: u32,
+        height: u32,
+        min_filter: Interpolation,
+        mag_filter: Interpolation,
+        mip_map_filter: Option<Interpolation>,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+        number_of_mip_maps: u32,
+    ) -> Self {
+        Self::new_empty_with_format::<T>(
+            context,
+            width,
+            height,
+            min_filter,
+            mag_filter,
+            mip_map_filter,
+            wrap_s,
+            wrap_t,
+            number_of_mip_maps,
+            ColorSpace::Linear,
+        )
+    }
+
+    fn new_empty_with_format<T: TextureDataType>(
+        context: &Context,
+        width: u32,
+        height: u32,
+        min_filter: Interpolation,
+        mag_filter: Interpolation,
+        mip_map_filter: Option<Interpolation>,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+        number_of_mip_maps: u32,
+        color_space: ColorSpace,
+    ) -> Self {
+        span!(DEBUG, "create_texture_2d", width, height);
+        let id = generate(context);
         let texture = Self {
             context: context.clone(),
             id,
             width,
             height,
             number_of_mip_maps,
+            mip_map_generation: MipMapGeneration::Automatic,
+            color_space,
             data_byte_size: std::mem::size_of::<T>(),
         };
         texture.bind();
@@ -101,7 +415,10 @@ impl Texture2D {
             context.tex_storage_2d(
                 crate::context::TEXTURE_2D,
                 number_of_mip_maps as i32,
-                T::internal_format(),
+                match color_space {
+                    ColorSpace::Linear => T::internal_format(),
+                    ColorSpace::Srgb => crate::context::SRGB8_ALPHA8,
+                },
                 width as i32,
                 height as i32,
             );
@@ -112,6 +429,7 @@ impl Texture2D {
 
     ///
     /// Fills this texture with the given data.
+    /// Afterwards, the mip maps are generated if the mip map generation is [MipMapGeneration::Automatic].
     ///
     /// # Panic
     /// Will panic if the length of the data does not correspond to the width, height and format specified at construction.
@@ -138,6 +456,82 @@ impl Texture2D {
         self.generate_mip_maps();
     }
 
+    ///
+    /// Fills the given mip level of this texture with the given data, for example to provide the mip maps of a texture
+    /// with [MipMapGeneration::Manual] mip map generation. The size of a mip level is half the size of the previous mip level,
+    /// rounded down but at least one pixel.
+    ///
+    /// # Panic
+    /// Will panic if the mip level does not exist or if the length of the data does not correspond to the size of the mip level and the format specified at construction.
+    ///
+    pub fn fill_mip_level<T: TextureDataType>(&mut self, mip_level: u32, data: &[T]) {
+        if mip_level >= self.number_of_mip_maps {
+            panic!(
+                "the mip level {} does not exist, the texture has {} mip levels",
+                mip_level, self.number_of_mip_maps
+            );
+        }
+        let width = (self.width >> mip_level).max(1);
+        let height = (self.height >> mip_level).max(1);
+        check_data_length::<T>(width, height, 1, self.data_byte_size, data.len());
+        self.bind();
+        let mut data = data.to_owned();
+        flip_y(&mut data, width as usize, height as usize);
+        unsafe {
+            self.context.tex_sub_image_2d(
+                crate::context::TEXTURE_2D,
+                mip_level as i32,
+                0,
+                0,
+                width as i32,
+                height as i32,
+                format_from_data_type::<T>(),
+                T::data_type(),
+                crate::context::PixelUnpackData::Slice(to_byte_slice(&data)),
+            );
+        }
+    }
+
+    ///
+    /// Applies the given [SamplerSettings] to this texture, for example to enable anisotropic filtering.
+    /// This can also be done for a texture which is shared, for example a texture referenced by a material.
+    ///
+    pub fn set_sampler_settings(&self, settings: SamplerSettings) {
+        self.bind();
+        set_sampler_settings(&self.context, crate::context::TEXTURE_2D, settings);
+    }
+
+    ///
+    /// Sets how the mip maps of this texture are created, see [MipMapGeneration].
+    /// A compressed texture, see [Texture2D::new_compressed], cannot have mip maps generated, so this has no effect for a compressed texture.
+    ///
+    pub fn set_mip_map_generation(&mut self, mip_map_generation: MipMapGeneration) {
+        if self.data_byte_size > 0 {
+            self.mip_map_generation = mip_map_generation;
+        }
+    }
+
+    ///
+    /// Returns how the mip maps of this texture are created, see [MipMapGeneration].
+    ///
+    pub fn mip_map_generation(&self) -> MipMapGeneration {
+        self.mip_map_generation
+    }
+
+    ///
+    /// Returns the color space of the data in this texture, see [ColorSpace].
+    ///
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    ///
+    /// The number of mip levels of this texture, including the full resolution mip level.
+    ///
+    pub fn number_of_mip_maps(&self) -> u32 {
+        self.number_of_mip_maps
+    }
+
     ///
     /// Returns a [ColorTarget] which can be used to clear, write to and read from the given mip level of this texture.
     /// Combine this together with a [DepthTarget] with [RenderTarget::new] to be able to write to both a depth and color target at the same time.
@@ -150,6 +544,13 @@ impl Texture2D {
         ColorTarget::new_texture2d(&self.context, self, mip_level)
     }
 
+    ///
+    /// Same as [Texture2D::as_color_target] but for a texture which is shared, for example a texture that is painted on while it is referenced by a material.
+    ///
+    pub(crate) fn as_shared_color_target(&self, mip_level: Option<u32>) -> ColorTarget<'_> {
+        ColorTarget::new_texture2d(&self.context, self, mip_level)
+    }
+
     /// The width of this texture.
     pub fn width(&self) -> u32 {
         self.width
@@ -161,7 +562,7 @@ impl Texture2D {
     }
 
     pub(crate) fn generate_mip_maps(&self) {
-        if self.number_of_mip_maps > 1 {
+        if self.number_of_mip_maps > 1 && self.mip_map_generation == MipMapGeneration::Automatic {
             self.bind();
             unsafe {
                 self.context.generate_mipmap(crate::context::TEXTURE_2D);
@@ -192,6 +593,7 @@ impl Drop for Texture2D {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_texture(self.id);
+            self.context.resources.released(ResourceKind::Texture);
         }
     }
 }
@@ -160,6 +160,7 @@ impl Texture2DArray {
         wrap_s: Wrapping,
         wrap_t: Wrapping,
     ) -> Self {
+        span!(DEBUG, "create_texture_2d_array", width, height, depth);
         let id = generate(context);
         let number_of_mip_maps =
             calculate_number_of_mip_maps::<T>(mip_map_filter, width, height, None);
@@ -315,6 +316,7 @@ impl Drop for Texture2DArray {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_texture(self.id);
+            self.context.resources.released(ResourceKind::Texture);
         }
     }
 }
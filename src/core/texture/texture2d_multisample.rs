@@ -15,11 +15,19 @@ impl Texture2DMultisample {
         height: u32,
         number_of_samples: u32,
     ) -> Self {
+        span!(
+            DEBUG,
+            "create_texture_2d_multisample",
+            width,
+            height,
+            number_of_samples
+        );
         let id = unsafe {
             context
                 .create_renderbuffer()
                 .expect("Failed creating render buffer")
         };
+        context.resources.created(ResourceKind::Renderbuffer);
         let texture = Self {
             context: context.clone(),
             id,
@@ -77,6 +85,7 @@ impl Drop for Texture2DMultisample {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_renderbuffer(self.id);
+            self.context.resources.released(ResourceKind::Renderbuffer);
         }
     }
 }
@@ -73,6 +73,7 @@ impl Texture3D {
         wrap_t: Wrapping,
         wrap_r: Wrapping,
     ) -> Self {
+        span!(DEBUG, "create_texture_3d", width, height, depth);
         let id = generate(context);
         let number_of_mip_maps =
             calculate_number_of_mip_maps::<T>(mip_map_filter, width, height, Some(depth));
@@ -148,6 +149,22 @@ impl Texture3D {
         self.generate_mip_maps();
     }
 
+    ///
+    /// Returns a [ColorTarget] which can be used to clear, write to and read from the given layers, ie. slices along the depth axis, and mip level of this texture.
+    /// Combine this together with a [DepthTarget] with [RenderTarget::new] to be able to write to both a depth and color target at the same time.
+    /// If `None` is specified as the mip level, the 0 level mip level is used and mip maps are generated after a write operation if a mip map filter is specified.
+    /// Otherwise, the given mip level is used and no mip maps are generated.
+    ///
+    /// **Note:** [DepthTest] is disabled if not also writing to a depth texture.
+    ///
+    pub fn as_color_target<'a>(
+        &'a mut self,
+        layers: &'a [u32],
+        mip_level: Option<u32>,
+    ) -> ColorTarget<'a> {
+        ColorTarget::new_texture_3d(&self.context, self, layers, mip_level)
+    }
+
     /// The width of this texture.
     pub fn width(&self) -> u32 {
         self.width
@@ -163,7 +180,7 @@ impl Texture3D {
         self.depth
     }
 
-    fn generate_mip_maps(&self) {
+    pub(in crate::core) fn generate_mip_maps(&self) {
         if self.number_of_mip_maps > 1 {
             self.bind();
             unsafe {
@@ -171,6 +188,19 @@ impl Texture3D {
             }
         }
     }
+
+    pub(in crate::core) fn bind_as_color_target(&self, layer: u32, channel: u32, mip_level: u32) {
+        unsafe {
+            self.context.framebuffer_texture_layer(
+                crate::context::DRAW_FRAMEBUFFER,
+                crate::context::COLOR_ATTACHMENT0 + channel,
+                Some(self.id),
+                mip_level as i32,
+                layer as i32,
+            );
+        }
+    }
+
     pub(in crate::core) fn bind(&self) {
         unsafe {
             self.context
@@ -183,6 +213,7 @@ impl Drop for Texture3D {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_texture(self.id);
+            self.context.resources.released(ResourceKind::Texture);
         }
     }
 }
@@ -110,6 +110,7 @@ pub struct TextureCubeMap {
     width: u32,
     height: u32,
     number_of_mip_maps: u32,
+    mip_map_generation: MipMapGeneration,
     data_byte_size: usize,
 }
 
@@ -314,6 +315,7 @@ impl TextureCubeMap {
         wrap_t: Wrapping,
         wrap_r: Wrapping,
     ) -> Self {
+        span!(DEBUG, "create_texture_cube_map", width, height);
         let id = generate(context);
         let number_of_mip_maps =
             calculate_number_of_mip_maps::<T>(mip_map_filter, width, height, None);
@@ -323,6 +325,7 @@ impl TextureCubeMap {
             width,
             height,
             number_of_mip_maps,
+            mip_map_generation: MipMapGeneration::Automatic,
             data_byte_size: std::mem::size_of::<T>(),
         };
         texture.bind();
@@ -355,6 +358,7 @@ impl TextureCubeMap {
 
     ///
     /// Fills the cube map texture with the given pixel data for the 6 images.
+    /// Afterwards, the mip maps are generated if the mip map generation is [MipMapGeneration::Automatic].
     ///
     /// # Panic
     /// Will panic if the length of the data for all 6 images does not correspond to the width, height and format specified at construction.
@@ -369,48 +373,54 @@ impl TextureCubeMap {
         front_data: &[T],
         back_data: &[T],
     ) {
-        check_data_length::<T>(
-            self.width,
-            self.height,
-            1,
-            self.data_byte_size,
-            right_data.len(),
-        );
-        check_data_length::<T>(
-            self.width,
-            self.height,
-            1,
-            self.data_byte_size,
-            left_data.len(),
-        );
-        check_data_length::<T>(
-            self.width,
-            self.height,
-            1,
-            self.data_byte_size,
-            top_data.len(),
-        );
-        check_data_length::<T>(
-            self.width,
-            self.height,
-            1,
-            self.data_byte_size,
-            bottom_data.len(),
-        );
-        check_data_length::<T>(
-            self.width,
-            self.height,
-            1,
-            self.data_byte_size,
-            front_data.len(),
-        );
-        check_data_length::<T>(
-            self.width,
-            self.height,
-            1,
-            self.data_byte_size,
-            back_data.len(),
+        self.fill_mip_level(
+            0,
+            right_data,
+            left_data,
+            top_data,
+            bottom_data,
+            front_data,
+            back_data,
         );
+        self.generate_mip_maps();
+    }
+
+    ///
+    /// Fills the given mip level of the cube map texture with the given pixel data for the 6 images,
+    /// for example to provide prefiltered mip maps of a cube map with [MipMapGeneration::Manual] mip map generation.
+    /// The size of a mip level is half the size of the previous mip level, rounded down but at least one pixel.
+    ///
+    /// # Panic
+    /// Will panic if the mip level does not exist or if the length of the data for all 6 images does not correspond to the size of the mip level and the format specified at construction.
+    ///
+    pub fn fill_mip_level<T: TextureDataType>(
+        &mut self,
+        mip_level: u32,
+        right_data: &[T],
+        left_data: &[T],
+        top_data: &[T],
+        bottom_data: &[T],
+        front_data: &[T],
+        back_data: &[T],
+    ) {
+        if mip_level >= self.number_of_mip_maps {
+            panic!(
+                "the mip level {} does not exist, the texture has {} mip levels",
+                mip_level, self.number_of_mip_maps
+            );
+        }
+        let width = (self.width >> mip_level).max(1);
+        let height = (self.height >> mip_level).max(1);
+        for data in [
+            right_data,
+            left_data,
+            top_data,
+            bottom_data,
+            front_data,
+            back_data,
+        ] {
+            check_data_length::<T>(width, height, 1, self.data_byte_size, data.len());
+        }
         self.bind();
         for i in 0..6 {
             let data = match i {
@@ -425,18 +435,17 @@ impl TextureCubeMap {
             unsafe {
                 self.context.tex_sub_image_2d(
                     crate::context::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32,
+                    mip_level as i32,
                     0,
                     0,
-                    0,
-                    self.width as i32,
-                    self.height as i32,
+                    width as i32,
+                    height as i32,
                     format_from_data_type::<T>(),
                     T::data_type(),
                     crate::context::PixelUnpackData::Slice(to_byte_slice(data)),
                 );
             }
         }
-        self.generate_mip_maps();
     }
 
     ///
@@ -446,6 +455,12 @@ impl TextureCubeMap {
         context: &Context,
         cpu_texture: &CpuTexture,
     ) -> Self {
+        span!(
+            DEBUG,
+            "texture_cube_map_from_equirectangular",
+            width = cpu_texture.width,
+            height = cpu_texture.height
+        );
         let texture_size = cpu_texture.width / 4;
         let mut texture = Self::new_empty::<[T; 4]>(
             context,
@@ -529,8 +544,38 @@ impl TextureCubeMap {
         self.height
     }
 
+    ///
+    /// Applies the given [SamplerSettings] to this texture, for example to enable anisotropic filtering.
+    /// This can also be done for a texture which is shared, for example a texture referenced by a material.
+    ///
+    pub fn set_sampler_settings(&self, settings: SamplerSettings) {
+        self.bind();
+        set_sampler_settings(&self.context, crate::context::TEXTURE_CUBE_MAP, settings);
+    }
+
+    ///
+    /// Sets how the mip maps of this texture are created, see [MipMapGeneration].
+    ///
+    pub fn set_mip_map_generation(&mut self, mip_map_generation: MipMapGeneration) {
+        self.mip_map_generation = mip_map_generation;
+    }
+
+    ///
+    /// Returns how the mip maps of this texture are created, see [MipMapGeneration].
+    ///
+    pub fn mip_map_generation(&self) -> MipMapGeneration {
+        self.mip_map_generation
+    }
+
+    ///
+    /// The number of mip levels of this texture, including the full resolution mip level.
+    ///
+    pub fn number_of_mip_maps(&self) -> u32 {
+        self.number_of_mip_maps
+    }
+
     pub(in crate::core) fn generate_mip_maps(&self) {
-        if self.number_of_mip_maps > 1 {
+        if self.number_of_mip_maps > 1 && self.mip_map_generation == MipMapGeneration::Automatic {
             self.bind();
             unsafe {
                 self.context
@@ -568,6 +613,7 @@ impl Drop for TextureCubeMap {
     fn drop(&mut self) {
         unsafe {
             self.context.delete_texture(self.id);
+            self.context.resources.released(ResourceKind::Texture);
         }
     }
 }
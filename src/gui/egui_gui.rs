@@ -6,6 +6,16 @@ use std::cell::RefCell;
 #[doc(hidden)]
 pub use egui;
 
+///
+/// Error associated with the [GUI].
+///
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum GUIError {
+    #[error("failed creating the egui painter: {0}")]
+    PainterCreation(String),
+}
+
 ///
 /// Integration of [egui](https://crates.io/crates/egui), an immediate mode GUI.
 ///
@@ -13,6 +23,7 @@ pub struct GUI {
     painter: RefCell<Painter>,
     egui_context: egui::Context,
     output: RefCell<Option<egui::FullOutput>>,
+    platform_output: egui::PlatformOutput,
     viewport: Viewport,
     modifiers: Modifiers,
 }
@@ -21,22 +32,47 @@ impl GUI {
     ///
     /// Creates a new GUI from a mid-level [Context].
     ///
+    /// # Panic
+    /// Will panic if the GUI could not be created, see [GUI::try_new] for a non-panicking version.
+    ///
     pub fn new(context: &Context) -> Self {
+        Self::try_new(context).unwrap()
+    }
+
+    ///
+    /// Creates a new GUI from a mid-level [Context] or returns an error if the GUI could not be created.
+    ///
+    pub fn try_new(context: &Context) -> Result<Self, GUIError> {
         use std::ops::Deref;
-        Self::from_gl_context(context.deref().clone())
+        Self::try_from_gl_context(context.deref().clone())
     }
 
     ///
     /// Creates a new GUI from a low-level graphics [Context](crate::context::Context).
     ///
+    /// # Panic
+    /// Will panic if the GUI could not be created, see [GUI::try_from_gl_context] for a non-panicking version.
+    ///
     pub fn from_gl_context(context: std::sync::Arc<crate::context::Context>) -> Self {
-        GUI {
+        Self::try_from_gl_context(context).unwrap()
+    }
+
+    ///
+    /// Creates a new GUI from a low-level graphics [Context](crate::context::Context) or returns an error if the GUI could not be created.
+    ///
+    pub fn try_from_gl_context(
+        context: std::sync::Arc<crate::context::Context>,
+    ) -> Result<Self, GUIError> {
+        let painter = Painter::new(context, "", None)
+            .map_err(|e| GUIError::PainterCreation(format!("{:?}", e)))?;
+        Ok(GUI {
             egui_context: egui::Context::default(),
-            painter: RefCell::new(Painter::new(context, "", None).unwrap()),
+            painter: RefCell::new(painter),
             output: RefCell::new(None),
+            platform_output: egui::PlatformOutput::default(),
             viewport: Viewport::new_at_origo(1, 1),
             modifiers: Modifiers::default(),
-        }
+        })
     }
 
     ///
@@ -189,13 +225,29 @@ impl GUI {
                     }
                     _ => None,
                 })
+                .chain(events.iter().filter_map(|event| match event {
+                    // The clipboard shortcuts are separate events in egui
+                    Event::KeyPress {
+                        kind: Key::C,
+                        modifiers,
+                        handled: false,
+                    } if modifiers.command => Some(egui::Event::Copy),
+                    Event::KeyPress {
+                        kind: Key::X,
+                        modifiers,
+                        handled: false,
+                    } if modifiers.command => Some(egui::Event::Cut),
+                    _ => None,
+                }))
                 .collect::<Vec<_>>(),
             ..Default::default()
         };
 
         self.egui_context.begin_frame(egui_input);
         callback(&self.egui_context);
-        *self.output.borrow_mut() = Some(self.egui_context.end_frame());
+        let mut output = self.egui_context.end_frame();
+        self.platform_output = std::mem::take(&mut output.platform_output);
+        *self.output.borrow_mut() = Some(output);
 
         for event in events.iter_mut() {
             if let Event::ModifiersChange { modifiers } = event {
@@ -246,6 +298,15 @@ impl GUI {
         self.egui_context.wants_pointer_input() || self.egui_context.wants_keyboard_input()
     }
 
+    ///
+    /// Returns the output of the last [update](Self::update) which has to be handled by the application, since it depends on the windowing system,
+    /// for example the text to copy to the clipboard, the cursor icon to show or a URL to open.
+    /// Text pasted from the clipboard can be given to the GUI as an [Event::Text].
+    ///
+    pub fn platform_output(&self) -> &egui::PlatformOutput {
+        &self.platform_output
+    }
+
     ///
     /// Render the GUI defined in the [update](Self::update) function.
     /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
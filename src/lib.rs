@@ -9,6 +9,22 @@
 //! the [examples](https://github.com/asny/three-d/tree/master/examples) for how to use it.
 //!
 
+///
+/// Enters a [tracing](https://docs.rs/tracing) span with the given level, name and fields which lasts until the end of the current scope.
+/// Does nothing if the `tracing` feature is not enabled.
+///
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($level:ident, $($args:tt)*) => {
+        let _span = tracing::span!(tracing::Level::$level, $($args)*).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($level:ident, $($args:tt)*) => {};
+}
+
 pub mod context;
 
 pub mod core;
@@ -23,3 +39,30 @@ pub use window::*;
 mod gui;
 #[allow(unused_imports)]
 pub use gui::*;
+
+///
+/// An error from any of the modules in this crate, which makes it possible to handle all errors using one type, for example with the `?` operator.
+///
+#[derive(thiserror::Error, Debug)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Core(#[from] CoreError),
+    #[error(transparent)]
+    Renderer(#[from] RendererError),
+    #[cfg(feature = "window")]
+    #[error(transparent)]
+    Window(#[from] WindowError),
+    #[cfg(all(feature = "window", feature = "image", not(target_arch = "wasm32")))]
+    #[error(transparent)]
+    Capture(#[from] CaptureError),
+    #[cfg(feature = "headless")]
+    #[error(transparent)]
+    Headless(#[from] HeadlessError),
+    #[cfg(all(feature = "golden-image", not(target_arch = "wasm32")))]
+    #[error(transparent)]
+    GoldenImage(#[from] GoldenImageError),
+    #[cfg(feature = "egui-gui")]
+    #[error(transparent)]
+    GUI(#[from] GUIError),
+}
@@ -29,6 +29,50 @@ pub enum RendererError {
     InvalidBufferLength(String, usize, usize),
     #[error("the material {0} is required by the geometry {1} but could not be found")]
     MissingMaterial(String, String),
+    #[error("the model does not contain a part with the name {0}")]
+    MissingModelPart(String),
+    #[error("the model does not contain a material variant with the name {0}")]
+    MissingMaterialVariant(String),
+    #[error("the model {0} does not contain an imposter texture")]
+    MissingImposterTexture(String),
+    #[error("the imposter texture {0} is invalid: {1}")]
+    InvalidImposterTexture(String, String),
+    #[error("the mesh must have uv coordinates to bake a lightmap")]
+    MissingLightmapUvs,
+    #[error("invalid .cube color lookup table: {0}")]
+    InvalidCubeLut(String),
+    #[error("the shader snippet {0} could not be found")]
+    MissingShaderSnippet(String),
+    #[error("invalid .3d model: {0}")]
+    InvalidModelBundle(String),
+    #[error("invalid scene patches: {0}")]
+    InvalidScenePatch(String),
+    #[error("invalid baked lighting: {0}")]
+    InvalidBakedLighting(String),
+    #[error("invalid frame dump: {0}")]
+    InvalidFrameDump(String),
+    #[error("the scene does not contain a model with the id {0}")]
+    MissingSceneModel(u32),
+    #[error("not possible to use the given {0} to render full screen, the full screen geometry only provides uv coordinates and color")]
+    InvalidFullScreenAttributes(String),
+    #[error("the number of secondary uv coordinates does not match the number of vertices of the model part {0}")]
+    InvalidSecondaryUvs(String),
+    #[cfg(feature = "gltf-extensions")]
+    #[error("the glTF buffer {0} could not be found")]
+    MissingGltfBuffer(String),
+    #[error("failed loading an asset")]
+    Asset(#[from] three_d_asset::Error),
+    #[error("error in three-d: {0}")]
+    ThreeDError(#[from] CoreError),
+    #[cfg(feature = "text")]
+    #[error("invalid font: {0}")]
+    InvalidFont(#[from] ab_glyph::InvalidFont),
+    #[cfg(feature = "gltf-extensions")]
+    #[error("failed parsing the material variants of a glTF file")]
+    Gltf(#[from] gltf::Error),
+    #[cfg(feature = "hot-reload")]
+    #[error("failed reading the shader source file {0}: {1}")]
+    ShaderSourceFile(String, std::io::Error),
 }
 
 mod camera;
@@ -52,6 +96,45 @@ pub use object::*;
 pub mod control;
 pub use control::*;
 
+mod shader_warm_up;
+pub use shader_warm_up::*;
+
+mod shader_preprocessor;
+pub use shader_preprocessor::*;
+
+mod model_viewer;
+pub use model_viewer::*;
+
+mod split_view;
+pub use split_view::*;
+
+mod stereo_camera;
+pub use stereo_camera::*;
+
+mod stereo_panorama;
+pub use stereo_panorama::*;
+
+mod model_bundle;
+pub use model_bundle::*;
+
+mod scene_patch;
+pub use scene_patch::*;
+
+mod baked_lighting;
+pub use baked_lighting::*;
+
+mod frame_dump;
+pub use frame_dump::*;
+
+mod motion_vectors;
+pub use motion_vectors::*;
+
+#[cfg(feature = "software-rasterizer")]
+#[cfg_attr(docsrs, doc(cfg(feature = "software-rasterizer")))]
+mod software_rasterizer;
+#[cfg(feature = "software-rasterizer")]
+pub use software_rasterizer::*;
+
 macro_rules! impl_render_target_extensions_body {
     () => {
         ///
@@ -79,11 +162,59 @@ macro_rules! impl_render_target_extensions_body {
             camera: &Camera,
             objects: impl IntoIterator<Item = impl Object>,
             lights: &[&dyn Light],
+        ) -> &Self {
+            self.render_partially_internal(scissor_box, camera, objects, lights, false)
+        }
+
+        ///
+        /// Render the objects using the given camera and lights into this render target like [Self::render], but with a depth pre-pass.
+        /// First, only the depth of the opaque objects is rendered, see [Object::render_depth_pre_pass], and then the objects are rendered
+        /// where the depth is equal to the depth from the pre-pass, so each pixel is only shaded once regardless of the amount of overdraw.
+        /// This is faster when the fragment shaders are expensive, for example with many lights, but adds the cost of rendering the geometry twice.
+        /// The materials of the objects provide the depth-only variants used in the pre-pass, see [Material::depth_pre_pass_material],
+        /// and objects that do not support a depth pre-pass are rendered as usual.
+        ///
+        pub fn render_with_depth_pre_pass(
+            &self,
+            camera: &Camera,
+            objects: impl IntoIterator<Item = impl Object>,
+            lights: &[&dyn Light],
+        ) -> &Self {
+            self.render_partially_with_depth_pre_pass(self.scissor_box(), camera, objects, lights)
+        }
+
+        ///
+        /// Render the objects using the given camera and lights into the part of this render target defined by the scissor box with a depth pre-pass,
+        /// see [Self::render_with_depth_pre_pass].
+        ///
+        pub fn render_partially_with_depth_pre_pass(
+            &self,
+            scissor_box: ScissorBox,
+            camera: &Camera,
+            objects: impl IntoIterator<Item = impl Object>,
+            lights: &[&dyn Light],
+        ) -> &Self {
+            self.render_partially_internal(scissor_box, camera, objects, lights, true)
+        }
+
+        fn render_partially_internal(
+            &self,
+            scissor_box: ScissorBox,
+            camera: &Camera,
+            objects: impl IntoIterator<Item = impl Object>,
+            lights: &[&dyn Light],
+            depth_pre_pass: bool,
         ) -> &Self {
             let (mut deferred_objects, mut forward_objects): (Vec<_>, Vec<_>) = objects
                 .into_iter()
                 .filter(|o| camera.in_frustum(&o.aabb()))
                 .partition(|o| o.material_type() == MaterialType::Deferred);
+            span!(
+                INFO,
+                "render",
+                deferred = deferred_objects.len(),
+                forward = forward_objects.len()
+            );
 
             // Deferred
             if deferred_objects.len() > 0 {
@@ -118,9 +249,13 @@ macro_rules! impl_render_target_extensions_body {
                 )
                 .clear(ClearState::default())
                 .write::<RendererError>(|| {
-                    for object in deferred_objects {
-                        object.render(&geometry_pass_camera, lights);
-                    }
+                    span!(INFO, "geometry_pass");
+                    render_objects(
+                        &geometry_pass_camera,
+                        &deferred_objects,
+                        lights,
+                        depth_pre_pass,
+                    );
                     Ok(())
                 })
                 .unwrap();
@@ -142,9 +277,8 @@ macro_rules! impl_render_target_extensions_body {
             // Forward
             forward_objects.sort_by(|a, b| cmp_render_order(camera, a, b));
             self.write_partially::<RendererError>(scissor_box, || {
-                for object in forward_objects {
-                    object.render(camera, lights);
-                }
+                span!(INFO, "forward_pass");
+                render_objects(camera, &forward_objects, lights, depth_pre_pass);
                 Ok(())
             })
             .unwrap();
@@ -184,6 +318,7 @@ macro_rules! impl_render_target_extensions_body {
             lights: &[&dyn Light],
         ) -> &Self {
             self.write_partially::<RendererError>(scissor_box, || {
+                span!(INFO, "render_with_material", material = material.id());
                 for geometry in geometries
                     .into_iter()
                     .filter(|o| camera.in_frustum(&o.aabb()))
@@ -235,6 +370,11 @@ macro_rules! impl_render_target_extensions_body {
             depth_texture: Option<DepthTexture>,
         ) -> &Self {
             self.write_partially::<RendererError>(scissor_box, || {
+                span!(
+                    INFO,
+                    "render_with_effect",
+                    effect = effect.id(color_texture, depth_texture)
+                );
                 for geometry in geometries
                     .into_iter()
                     .filter(|o| camera.in_frustum(&o.aabb()))
@@ -280,6 +420,7 @@ macro_rules! impl_render_target_extensions_body {
             lights: &[&dyn Light],
         ) -> &Self {
             self.write_partially::<RendererError>(scissor_box, || {
+                span!(INFO, "apply_screen_material", material = material.id());
                 apply_screen_material(&self.context, material, camera, lights);
                 Ok(())
             })
@@ -323,6 +464,11 @@ macro_rules! impl_render_target_extensions_body {
             depth_texture: Option<DepthTexture>,
         ) -> &Self {
             self.write_partially::<RendererError>(scissor_box, || {
+                span!(
+                    INFO,
+                    "apply_screen_effect",
+                    effect = effect.id(color_texture, depth_texture)
+                );
                 apply_screen_effect(
                     &self.context,
                     effect,
@@ -375,11 +521,42 @@ impl_render_target_extensions!(
 impl_render_target_extensions!(ColorTargetMultisample<C: TextureDataType>);
 impl_render_target_extensions!(DepthTargetMultisample<D: DepthTextureDataType>);
 
+///
+/// Renders the objects in the given order, optionally with a depth pre-pass.
+///
+fn render_objects(
+    camera: &Camera,
+    objects: &[impl Object],
+    lights: &[&dyn Light],
+    depth_pre_pass: bool,
+) {
+    if depth_pre_pass {
+        let pre_passed = objects
+            .iter()
+            .map(|object| object.render_depth_pre_pass(camera))
+            .collect::<Vec<_>>();
+        for (object, pre_passed) in objects.iter().zip(pre_passed) {
+            if pre_passed {
+                object.render_after_depth_pre_pass(camera, lights);
+            } else {
+                object.render(camera, lights);
+            }
+        }
+    } else {
+        for object in objects {
+            object.render(camera, lights);
+        }
+    }
+}
+
 ///
 /// Render the given [Geometry] with the given [Material].
 /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
 /// Use an empty array for the `lights` argument, if the material does not require lights to be rendered.
 ///
+/// # Panic
+/// Will panic if the shader program could not be compiled, see [try_render_with_material] for a non-panicking version.
+///
 pub fn render_with_material(
     context: &Context,
     camera: &Camera,
@@ -387,20 +564,34 @@ pub fn render_with_material(
     material: impl Material,
     lights: &[&dyn Light],
 ) {
+    try_render_with_material(context, camera, geometry, material, lights)
+        .expect("Failed compiling shader")
+}
+
+///
+/// Render the given [Geometry] with the given [Material], see [render_with_material].
+/// Returns an error instead of panicking if the shader program could not be compiled.
+///
+pub fn try_render_with_material(
+    context: &Context,
+    camera: &Camera,
+    geometry: impl Geometry,
+    material: impl Material,
+    lights: &[&dyn Light],
+) -> Result<(), RendererError> {
     let fragment_attributes = material.fragment_attributes();
-    let mut id = geometry.id(fragment_attributes).to_le_bytes().to_vec();
-    id.extend(material.id().to_le_bytes());
-    id.extend(lights.iter().map(|l| l.id()));
+    let id = material_program_id(&geometry, &material, lights);
 
     let mut programs = context.programs.write().unwrap();
-    let program = programs.entry(id).or_insert_with(|| {
-        Program::from_source(
+    if !programs.contains_key(&id) {
+        let program = Program::from_source(
             context,
-            &geometry.vertex_shader_source(fragment_attributes),
+            &material_vertex_shader_source(&geometry, &material),
             &material.fragment_shader_source(lights),
-        )
-        .expect("Failed compiling shader")
-    });
+        )?;
+        programs.insert(id.clone(), program);
+    }
+    let program = &programs[&id];
     material.use_uniforms(program, camera, lights);
     geometry.draw(
         camera,
@@ -408,6 +599,82 @@ pub fn render_with_material(
         material.render_states(),
         fragment_attributes,
     );
+    Ok(())
+}
+
+///
+/// Returns the id of the program used for rendering the given [Geometry] with the given [Material] and lights.
+///
+pub(crate) fn material_program_id(
+    geometry: &impl Geometry,
+    material: &impl Material,
+    lights: &[&dyn Light],
+) -> Vec<u8> {
+    let mut id = geometry
+        .id(material.fragment_attributes())
+        .to_le_bytes()
+        .to_vec();
+    id.extend(material.id().to_le_bytes());
+    extend_with_optional_id(&mut id, material.extended_id());
+    // The parts of the id before the lights have a fixed length, so different combinations cannot result in the same id
+    extend_with_optional_id(
+        &mut id,
+        material.vertex_displacement_source().map(|source| {
+            use std::hash::{Hash, Hasher};
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            source.hash(&mut hasher);
+            hasher.finish()
+        }),
+    );
+    id.extend(lights.iter().map(|l| l.id()));
+    id
+}
+
+///
+/// Returns the id of the program used to render the given geometry, or the full screen geometry if `None`, with the given effect, see [material_program_id].
+///
+pub(crate) fn effect_program_id(
+    geometry: Option<&dyn Geometry>,
+    effect: &impl Effect,
+    lights: &[&dyn Light],
+    color_texture: Option<ColorTexture>,
+    depth_texture: Option<DepthTexture>,
+) -> Vec<u8> {
+    let geometry_id = geometry.map_or(0b1u16 << 15, |geometry| {
+        geometry.id(effect.fragment_attributes())
+    });
+    let mut id = geometry_id.to_le_bytes().to_vec();
+    id.extend(effect.id(color_texture, depth_texture).to_le_bytes());
+    id.extend(lights.iter().map(|l| l.id()));
+    id
+}
+
+fn extend_with_optional_id(id: &mut Vec<u8>, extended_id: Option<u64>) {
+    match extended_id {
+        Some(extended_id) => {
+            id.push(1);
+            id.extend(extended_id.to_le_bytes());
+        }
+        None => id.extend([0; 9]),
+    }
+}
+
+///
+/// Returns the vertex shader source of the given [Geometry] including the vertex displacement of the given [Material], see [Material::vertex_displacement_source].
+///
+pub(crate) fn material_vertex_shader_source(
+    geometry: &impl Geometry,
+    material: &impl Material,
+) -> String {
+    let source = geometry.vertex_shader_source(material.fragment_attributes());
+    if let Some(displacement_source) = material.vertex_displacement_source() {
+        format!(
+            "#define USE_VERTEX_DISPLACEMENT\n{}\n{}",
+            displacement_source, source
+        )
+    } else {
+        source
+    }
 }
 
 ///
@@ -415,6 +682,9 @@ pub fn render_with_material(
 /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
 /// Use an empty array for the `lights` argument, if the effect does not require lights to be rendered.
 ///
+/// # Panic
+/// Will panic if the shader program could not be compiled, see [try_render_with_effect] for a non-panicking version.
+///
 pub fn render_with_effect(
     context: &Context,
     camera: &Camera,
@@ -424,22 +694,53 @@ pub fn render_with_effect(
     color_texture: Option<ColorTexture>,
     depth_texture: Option<DepthTexture>,
 ) {
+    try_render_with_effect(
+        context,
+        camera,
+        geometry,
+        effect,
+        lights,
+        color_texture,
+        depth_texture,
+    )
+    .expect("Failed compiling shader")
+}
+
+///
+/// Render the given [Geometry] with the given [Effect], see [render_with_effect].
+/// Returns an error instead of panicking if the shader program could not be compiled.
+///
+pub fn try_render_with_effect(
+    context: &Context,
+    camera: &Camera,
+    geometry: impl Geometry,
+    effect: impl Effect,
+    lights: &[&dyn Light],
+    color_texture: Option<ColorTexture>,
+    depth_texture: Option<DepthTexture>,
+) -> Result<(), RendererError> {
     let fragment_attributes = effect.fragment_attributes();
-    let mut id = geometry.id(fragment_attributes).to_le_bytes().to_vec();
-    id.extend(effect.id(color_texture, depth_texture).to_le_bytes());
-    id.extend(lights.iter().map(|l| l.id()));
+    let id = effect_program_id(
+        Some(&geometry),
+        &effect,
+        lights,
+        color_texture,
+        depth_texture,
+    );
 
     let mut programs = context.programs.write().unwrap();
-    let program = programs.entry(id).or_insert_with(|| {
-        Program::from_source(
+    if !programs.contains_key(&id) {
+        let program = Program::from_source(
             context,
             &geometry.vertex_shader_source(fragment_attributes),
             &effect.fragment_shader_source(lights, color_texture, depth_texture),
-        )
-        .expect("Failed compiling shader")
-    });
+        )?;
+        programs.insert(id.clone(), program);
+    }
+    let program = &programs[&id];
     effect.use_uniforms(program, camera, lights, color_texture, depth_texture);
     geometry.draw(camera, program, effect.render_states(), fragment_attributes);
+    Ok(())
 }
 
 ///
@@ -447,29 +748,55 @@ pub fn render_with_effect(
 /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
 /// Use an empty array for the `lights` argument, if the material does not require lights to be rendered.
 ///
+/// # Panic
+/// Will panic if the material requires other attributes than uv coordinates and color or if the shader program could not be compiled,
+/// see [try_apply_screen_material] for a non-panicking version.
+///
 pub fn apply_screen_material(
     context: &Context,
     material: impl Material,
     camera: &Camera,
     lights: &[&dyn Light],
 ) {
+    if let Err(e) = try_apply_screen_material(context, material, camera, lights) {
+        match e {
+            RendererError::InvalidFullScreenAttributes(_) => panic!("Not possible to use the given material to render full screen, the full screen geometry only provides uv coordinates and color"),
+            _ => panic!("Failed compiling shader: {:?}", e),
+        }
+    }
+}
+
+///
+/// Apply the given [Material] to the entire sceen, see [apply_screen_material].
+/// Returns an error instead of panicking if the material requires other attributes than uv coordinates and color or if the shader program could not be compiled.
+///
+pub fn try_apply_screen_material(
+    context: &Context,
+    material: impl Material,
+    camera: &Camera,
+    lights: &[&dyn Light],
+) -> Result<(), RendererError> {
     let fragment_attributes = material.fragment_attributes();
     if fragment_attributes.normal || fragment_attributes.position || fragment_attributes.tangents {
-        panic!("Not possible to use the given material to render full screen, the full screen geometry only provides uv coordinates and color");
+        Err(RendererError::InvalidFullScreenAttributes(
+            "material".to_string(),
+        ))?;
     }
     let mut id = (0b1u16 << 15).to_le_bytes().to_vec();
     id.extend(material.id().to_le_bytes());
+    extend_with_optional_id(&mut id, material.extended_id());
     id.extend(lights.iter().map(|l| l.id()));
 
     let mut programs = context.programs.write().unwrap();
-    let program = programs.entry(id).or_insert_with(|| {
-        Program::from_source(
+    if !programs.contains_key(&id) {
+        let program = Program::from_source(
             context,
             full_screen_vertex_shader_source(),
             &material.fragment_shader_source(lights),
-        )
-        .expect("Failed compiling shader")
-    });
+        )?;
+        programs.insert(id.clone(), program);
+    }
+    let program = &programs[&id];
     material.use_uniforms(program, camera, lights);
     full_screen_draw(
         context,
@@ -477,6 +804,7 @@ pub fn apply_screen_material(
         material.render_states(),
         camera.viewport(),
     );
+    Ok(())
 }
 
 ///
@@ -484,6 +812,10 @@ pub fn apply_screen_material(
 /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
 /// Use an empty array for the `lights` argument, if the effect does not require lights to be rendered.
 ///
+/// # Panic
+/// Will panic if the effect requires other attributes than uv coordinates and color or if the shader program could not be compiled,
+/// see [try_apply_screen_effect] for a non-panicking version.
+///
 pub fn apply_screen_effect(
     context: &Context,
     effect: impl Effect,
@@ -492,25 +824,54 @@ pub fn apply_screen_effect(
     color_texture: Option<ColorTexture>,
     depth_texture: Option<DepthTexture>,
 ) {
+    if let Err(e) = try_apply_screen_effect(
+        context,
+        effect,
+        camera,
+        lights,
+        color_texture,
+        depth_texture,
+    ) {
+        match e {
+            RendererError::InvalidFullScreenAttributes(_) => panic!("Not possible to use the given effect to render full screen, the full screen geometry only provides uv coordinates and color"),
+            _ => panic!("Failed compiling shader: {:?}", e),
+        }
+    }
+}
+
+///
+/// Apply the given [Effect] to the entire sceen, see [apply_screen_effect].
+/// Returns an error instead of panicking if the effect requires other attributes than uv coordinates and color or if the shader program could not be compiled.
+///
+pub fn try_apply_screen_effect(
+    context: &Context,
+    effect: impl Effect,
+    camera: &Camera,
+    lights: &[&dyn Light],
+    color_texture: Option<ColorTexture>,
+    depth_texture: Option<DepthTexture>,
+) -> Result<(), RendererError> {
     let fragment_attributes = effect.fragment_attributes();
     if fragment_attributes.normal || fragment_attributes.position || fragment_attributes.tangents {
-        panic!("Not possible to use the given effect to render full screen, the full screen geometry only provides uv coordinates and color");
+        Err(RendererError::InvalidFullScreenAttributes(
+            "effect".to_string(),
+        ))?;
     }
-    let mut id = (0b1u16 << 15).to_le_bytes().to_vec();
-    id.extend(effect.id(color_texture, depth_texture).to_le_bytes());
-    id.extend(lights.iter().map(|l| l.id()));
+    let id = effect_program_id(None, &effect, lights, color_texture, depth_texture);
 
     let mut programs = context.programs.write().unwrap();
-    let program = programs.entry(id).or_insert_with(|| {
-        Program::from_source(
+    if !programs.contains_key(&id) {
+        let program = Program::from_source(
             context,
             full_screen_vertex_shader_source(),
             &effect.fragment_shader_source(lights, color_texture, depth_texture),
-        )
-        .expect("Failed compiling shader")
-    });
+        )?;
+        programs.insert(id.clone(), program);
+    }
+    let program = &programs[&id];
     effect.use_uniforms(program, camera, lights, color_texture, depth_texture);
     full_screen_draw(context, program, effect.render_states(), camera.viewport());
+    Ok(())
 }
 
 ///
@@ -567,6 +928,27 @@ pub fn pick(
     )
 }
 
+///
+/// Starts finding the closest intersection between a ray from the given camera in the given pixel coordinate and the given geometries, see [pick].
+/// The result is read back asynchronously so this does not wait for the GPU to finish rendering, see [PendingIntersection].
+///
+pub fn pick_async(
+    context: &Context,
+    camera: &Camera,
+    pixel: impl Into<PhysicalPoint> + Copy,
+    geometries: impl IntoIterator<Item = impl Geometry>,
+) -> PendingIntersection {
+    let pos = camera.position_at_pixel(pixel);
+    let dir = camera.view_direction_at_pixel(pixel);
+    ray_intersect_async(
+        context,
+        pos + dir * camera.z_near(),
+        dir,
+        camera.z_far() - camera.z_near(),
+        geometries,
+    )
+}
+
 ///
 /// Finds the closest intersection between a ray starting at the given position in the given direction and the given geometries.
 /// Returns ```None``` if no geometry was hit before the given maximum depth.
@@ -578,6 +960,54 @@ pub fn ray_intersect(
     max_depth: f32,
     geometries: impl IntoIterator<Item = impl Geometry>,
 ) -> Option<Vec3> {
+    ray_intersect_async(context, position, direction, max_depth, geometries).read()
+}
+
+///
+/// Starts finding the closest intersection between a ray starting at the given position in the given direction and the given geometries, see [ray_intersect].
+/// The result is read back asynchronously so this does not wait for the GPU to finish rendering, see [PendingIntersection].
+///
+pub fn ray_intersect_async(
+    context: &Context,
+    position: Vec3,
+    direction: Vec3,
+    max_depth: f32,
+    geometries: impl IntoIterator<Item = impl Geometry>,
+) -> PendingIntersection {
+    let depth_material = DepthMaterial {
+        render_states: RenderStates {
+            write_mask: WriteMask {
+                red: true,
+                ..WriteMask::DEPTH
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let readback = render_ray(context, position, direction, max_depth, |camera| {
+        for geometry in geometries {
+            render_with_material(context, camera, &geometry, &depth_material, &[]);
+        }
+    });
+    PendingIntersection {
+        readback,
+        position,
+        direction,
+        max_depth,
+    }
+}
+
+///
+/// Renders along the ray starting at the given position in the given direction into a single pixel using the given callback
+/// and starts reading back the result.
+///
+fn render_ray(
+    context: &Context,
+    position: Vec3,
+    direction: Vec3,
+    max_depth: f32,
+    render: impl FnOnce(&Camera),
+) -> PixelReadback<[f32; 4]> {
     use crate::core::*;
     let viewport = Viewport::new_at_origo(1, 1);
     let up = if direction.dot(vec3(1.0, 0.0, 0.0)).abs() > 0.99 {
@@ -594,7 +1024,7 @@ pub fn ray_intersect(
         0.0,
         max_depth,
     );
-    let mut texture = Texture2D::new_empty::<f32>(
+    let mut texture = Texture2D::new_empty::<[f32; 4]>(
         context,
         viewport.width,
         viewport.height,
@@ -611,32 +1041,225 @@ pub fn ray_intersect(
         Wrapping::ClampToEdge,
         Wrapping::ClampToEdge,
     );
-    let depth_material = DepthMaterial {
-        render_states: RenderStates {
-            write_mask: WriteMask {
-                red: true,
-                ..WriteMask::DEPTH
-            },
-            ..Default::default()
-        },
-        ..Default::default()
-    };
-    let depth = RenderTarget::new(
+    let readback = RenderTarget::new(
         texture.as_color_target(None),
         depth_texture.as_depth_target(),
     )
     .clear(ClearState::color_and_depth(1.0, 1.0, 1.0, 1.0, 1.0))
     .write::<RendererError>(|| {
-        for geometry in geometries {
-            render_with_material(context, &camera, &geometry, &depth_material, &[]);
-        }
+        render(&camera);
         Ok(())
     })
     .unwrap()
-    .read_color::<[f32; 4]>()[0][0];
-    if depth < 1.0 {
-        Some(position + direction * depth * max_depth)
-    } else {
-        None
+    .read_color_async::<[f32; 4]>();
+    readback
+}
+
+///
+/// An intersection test which is being performed on the GPU, see [pick_async] and [ray_intersect_async].
+/// Poll [PendingIntersection::is_ready] or call [PendingIntersection::try_read] each frame until the result is available,
+/// or call [PendingIntersection::read] to wait for the result.
+///
+pub struct PendingIntersection {
+    readback: PixelReadback<[f32; 4]>,
+    position: Vec3,
+    direction: Vec3,
+    max_depth: f32,
+}
+
+impl PendingIntersection {
+    ///
+    /// Returns whether the result is available, in which case [PendingIntersection::read] returns without waiting.
+    ///
+    pub fn is_ready(&self) -> bool {
+        self.readback.is_ready()
+    }
+
+    ///
+    /// Returns the result if it is available, otherwise this is returned so that it can be tried again later, for example in the next frame.
+    /// The result is ```None``` if no geometry was hit before the maximum depth.
+    ///
+    pub fn try_read(self) -> Result<Option<Vec3>, Self> {
+        if self.is_ready() {
+            Ok(self.read())
+        } else {
+            Err(self)
+        }
+    }
+
+    ///
+    /// Returns the closest intersection, waiting for the GPU to finish if the result is not available yet.
+    /// Returns ```None``` if no geometry was hit before the maximum depth.
+    ///
+    pub fn read(self) -> Option<Vec3> {
+        let depth = self.readback.read()[0][0];
+        if depth < 1.0 {
+            Some(self.position + self.direction * depth * self.max_depth)
+        } else {
+            None
+        }
+    }
+}
+
+///
+/// Finds the closest intersection between a ray from the given camera in the given pixel coordinate and the given objects,
+/// each given as a [Geometry] and the [Material] it is rendered with, see [ray_intersect_with_materials].
+/// The pixel coordinate must be in physical pixels, where (viewport.x, viewport.y) indicate the bottom left corner of the viewport
+/// and (viewport.x + viewport.width, viewport.y + viewport.height) indicate the top right corner.
+/// Returns ```None``` if no object was hit between the near (`z_near`) and far (`z_far`) plane for this camera.
+///
+pub fn pick_with_materials<G: Geometry, M: Material>(
+    context: &Context,
+    camera: &Camera,
+    pixel: impl Into<PhysicalPoint> + Copy,
+    objects: impl IntoIterator<Item = (G, M)>,
+) -> Option<RayHit> {
+    pick_with_materials_async(context, camera, pixel, objects).read()
+}
+
+///
+/// Starts finding the closest intersection between a ray from the given camera in the given pixel coordinate and the given objects, see [pick_with_materials].
+/// The result is read back asynchronously so this does not wait for the GPU to finish rendering, see [PendingRayHit].
+///
+pub fn pick_with_materials_async<G: Geometry, M: Material>(
+    context: &Context,
+    camera: &Camera,
+    pixel: impl Into<PhysicalPoint> + Copy,
+    objects: impl IntoIterator<Item = (G, M)>,
+) -> PendingRayHit {
+    let pos = camera.position_at_pixel(pixel);
+    let dir = camera.view_direction_at_pixel(pixel);
+    ray_intersect_with_materials_async(
+        context,
+        pos + dir * camera.z_near(),
+        dir,
+        camera.z_far() - camera.z_near(),
+        objects,
+    )
+}
+
+///
+/// Finds the closest intersection between a ray starting at the given position in the given direction and the given objects,
+/// each given as a [Geometry] and the [Material] it is rendered with.
+/// Returns ```None``` if no object was hit before the given maximum depth.
+///
+/// As opposed to [ray_intersect], the objects are intersected as they are rendered on the GPU, ie. including the displacement of the vertices
+/// by the material, see [Material::vertex_displacement_source], and the faces culled by the material are not hit.
+/// Like [ray_intersect], the current state of the geometries is used, including the instances of an [InstancedMesh], the particles of a [ParticleSystem]
+/// at the current time and the deformation of a [MorphMesh] or [VertexCacheMesh], so the result is accurate in an animated scene where
+/// the data on the CPU is out of date. The result also contains the index of the object that was hit and, if the material of that object requires normals,
+/// the normal at the intersection, see [RayHit].
+///
+pub fn ray_intersect_with_materials<G: Geometry, M: Material>(
+    context: &Context,
+    position: Vec3,
+    direction: Vec3,
+    max_depth: f32,
+    objects: impl IntoIterator<Item = (G, M)>,
+) -> Option<RayHit> {
+    ray_intersect_with_materials_async(context, position, direction, max_depth, objects).read()
+}
+
+///
+/// Starts finding the closest intersection between a ray starting at the given position in the given direction and the given objects, see [ray_intersect_with_materials].
+/// The result is read back asynchronously so this does not wait for the GPU to finish rendering, see [PendingRayHit].
+///
+pub fn ray_intersect_with_materials_async<G: Geometry, M: Material>(
+    context: &Context,
+    position: Vec3,
+    direction: Vec3,
+    max_depth: f32,
+    objects: impl IntoIterator<Item = (G, M)>,
+) -> PendingRayHit {
+    let readback = render_ray(context, position, direction, max_depth, |camera| {
+        for (index, (geometry, material)) in objects.into_iter().enumerate() {
+            let ray_hit_material = RayHitMaterial {
+                material: &material,
+                index,
+                max_distance: max_depth,
+            };
+            render_with_material(context, camera, &geometry, &ray_hit_material, &[]);
+        }
+    });
+    PendingRayHit {
+        readback,
+        position,
+        direction,
+        max_depth,
+    }
+}
+
+///
+/// The closest intersection between a ray and a set of objects, see [ray_intersect_with_materials].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// The position of the intersection in world space.
+    pub position: Vec3,
+    /// The index of the object that was hit in the given objects.
+    pub index: usize,
+    /// The normal at the intersection in world space, which is only available if the material of the object that was hit requires normals,
+    /// see [FragmentAttributes::normal].
+    pub normal: Option<Vec3>,
+}
+
+///
+/// An intersection test against a set of objects which is being performed on the GPU, see [pick_with_materials_async] and [ray_intersect_with_materials_async].
+/// Poll [PendingRayHit::is_ready] or call [PendingRayHit::try_read] each frame until the result is available,
+/// or call [PendingRayHit::read] to wait for the result.
+///
+pub struct PendingRayHit {
+    readback: PixelReadback<[f32; 4]>,
+    position: Vec3,
+    direction: Vec3,
+    max_depth: f32,
+}
+
+impl PendingRayHit {
+    ///
+    /// Returns whether the result is available, in which case [PendingRayHit::read] returns without waiting.
+    ///
+    pub fn is_ready(&self) -> bool {
+        self.readback.is_ready()
+    }
+
+    ///
+    /// Returns the result if it is available, otherwise this is returned so that it can be tried again later, for example in the next frame.
+    /// The result is ```None``` if no object was hit before the maximum depth.
+    ///
+    pub fn try_read(self) -> Result<Option<RayHit>, Self> {
+        if self.is_ready() {
+            Ok(self.read())
+        } else {
+            Err(self)
+        }
+    }
+
+    ///
+    /// Returns the closest intersection, waiting for the GPU to finish if the result is not available yet.
+    /// Returns ```None``` if no object was hit before the maximum depth.
+    ///
+    pub fn read(self) -> Option<RayHit> {
+        let [depth, index, x, y] = self.readback.read()[0];
+        if depth >= 1.0 {
+            return None;
+        }
+        let normal = if x.abs() <= 1.0 && y.abs() <= 1.0 {
+            // Decode the octahedral encoding of the normal
+            let z = 1.0 - x.abs() - y.abs();
+            let (x, y) = if z < 0.0 {
+                ((1.0 - y.abs()) * x.signum(), (1.0 - x.abs()) * y.signum())
+            } else {
+                (x, y)
+            };
+            Some(vec3(x, y, z).normalize())
+        } else {
+            None
+        };
+        Some(RayHit {
+            position: self.position + self.direction * depth * self.max_depth,
+            index: index.round() as usize,
+            normal,
+        })
     }
 }
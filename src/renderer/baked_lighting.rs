@@ -0,0 +1,119 @@
+use super::model_bundle::{Encode, Reader};
+use crate::renderer::*;
+
+/// The bytes at the start of serialized baked lighting.
+const MAGIC: &[u8; 8] = b"3DLIGHT\0";
+/// The version of the baked lighting format, which is increased each time the format changes.
+const VERSION: u32 = 1;
+
+///
+/// The result of baking the lighting of a scene, ie. lightmaps and ambient occlusion maps for the static objects (see [LightmapBaker] and [AmbientOcclusionBaker])
+/// and volumetric lightmaps for the dynamic objects (see [LightmapBaker::bake_volumetric]).
+/// Since baking is expensive, the lighting would usually be baked offline and stored next to the scene using [serialize_baked_lighting],
+/// for example next to a model in the `.3d` format (see [serialize_model]), so the application only has to load it using [deserialize_baked_lighting].
+///
+#[derive(Clone, Debug, Default)]
+pub struct BakedLighting {
+    /// The lightmaps and ambient occlusion maps, identified by their [CpuTexture::name], for example the name of the mesh they belong to.
+    pub lightmaps: Vec<CpuTexture>,
+    /// The volumetric lightmaps.
+    pub volumetric_lightmaps: Vec<CpuVolumetricLightmap>,
+}
+
+impl BakedLighting {
+    ///
+    /// Returns the first lightmap with the given name, if any.
+    ///
+    pub fn lightmap(&self, name: &str) -> Option<&CpuTexture> {
+        self.lightmaps.iter().find(|lightmap| lightmap.name == name)
+    }
+}
+
+///
+/// Serializes the given baked lighting into bytes in the same way as the textures in the `.3d` format (see [serialize_model]),
+/// so the lightmaps are stored without compression and loading them is fast. Use [deserialize_baked_lighting] to get the lighting back.
+///
+/// ```no_run
+/// # use three_d::*;
+/// # let baked_lighting: BakedLighting = unimplemented!();
+/// std::fs::write("scene.3dlight", serialize_baked_lighting(&baked_lighting)).unwrap();
+/// ```
+///
+pub fn serialize_baked_lighting(baked_lighting: &BakedLighting) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    VERSION.encode(&mut bytes);
+    baked_lighting.lightmaps.encode(&mut bytes);
+    baked_lighting.volumetric_lightmaps.encode(&mut bytes);
+    bytes
+}
+
+///
+/// Deserializes baked lighting from bytes produced by [serialize_baked_lighting].
+/// Returns an error if the bytes are not baked lighting or if they were serialized by a different version of three-d.
+///
+/// ```no_run
+/// # use three_d::*;
+/// # async fn load() -> Result<BakedLighting, RendererError> {
+/// let loaded = three_d_asset::io::load_async(&["scene.3dlight"]).await?;
+/// deserialize_baked_lighting(loaded.get("scene.3dlight")?)
+/// # }
+/// ```
+///
+pub fn deserialize_baked_lighting(bytes: &[u8]) -> Result<BakedLighting, RendererError> {
+    let invalid = |e| match e {
+        RendererError::InvalidModelBundle(message) => RendererError::InvalidBakedLighting(message),
+        e => e,
+    };
+    let mut reader = Reader(bytes);
+    if reader.take(MAGIC.len()).map_err(invalid)? != MAGIC {
+        return Err(RendererError::InvalidBakedLighting(
+            "not baked lighting".to_string(),
+        ));
+    }
+    let version = u32::decode(&mut reader).map_err(invalid)?;
+    if version != VERSION {
+        return Err(RendererError::InvalidBakedLighting(format!(
+            "unsupported version {}, expected version {}",
+            version, VERSION
+        )));
+    }
+    let baked_lighting = BakedLighting {
+        lightmaps: Vec::decode(&mut reader).map_err(invalid)?,
+        volumetric_lightmaps: Vec::decode(&mut reader).map_err(invalid)?,
+    };
+    if !reader.0.is_empty() {
+        return Err(RendererError::InvalidBakedLighting(
+            "unexpected data after the baked lighting".to_string(),
+        ));
+    }
+    Ok(baked_lighting)
+}
+
+impl Encode for CpuVolumetricLightmap {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.min.encode(bytes);
+        self.max.encode(bytes);
+        self.resolution.encode(bytes);
+        self.probes.encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        let lightmap = Self {
+            min: Vec3::decode(reader)?,
+            max: Vec3::decode(reader)?,
+            resolution: <[u32; 3]>::decode(reader)?,
+            probes: Vec::decode(reader)?,
+        };
+        let probe_count = lightmap
+            .resolution
+            .iter()
+            .map(|r| r.max(&1))
+            .try_fold(1u32, |count, r| count.checked_mul(*r));
+        if probe_count != Some(lightmap.probes.len() as u32) {
+            return Err(RendererError::InvalidModelBundle(
+                "the number of probes does not match the resolution of the volumetric lightmap"
+                    .to_string(),
+            ));
+        }
+        Ok(lightmap)
+    }
+}
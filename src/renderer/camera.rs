@@ -4,8 +4,25 @@ pub use tone_mapping::*;
 mod color_space;
 pub use color_space::*;
 
+mod fog;
+pub use fog::*;
+
+mod camera_animator;
+pub use camera_animator::*;
+
 use crate::core::*;
 
+/// The declaration of the `Camera` uniform block, see [Camera::use_uniform_block].
+pub(crate) const CAMERA_UNIFORM_BLOCK_SOURCE: &str = "
+layout (std140) uniform Camera {
+    mat4 viewProjection;
+    mat4 view;
+    mat4 projection;
+    vec3 position;
+    vec4 viewport;
+} camera;
+";
+
 ///
 /// Represents a camera used for viewing 2D and 3D objects.
 ///
@@ -16,6 +33,20 @@ pub struct Camera {
     pub tone_mapping: ToneMapping,
     /// This color mapping is applied to the final color of renders using this camera.
     pub color_mapping: ColorMapping,
+    /// The exposure in stops, ie. the color is multiplied by `2^exposure` before the tone mapping is applied.
+    /// It is only applied together with the tone mapping, so it has no effect when the tone mapping is [ToneMapping::None]. The default is 0.0.
+    pub exposure: f32,
+    /// The gamma of the power-law transfer function applied by [ColorMapping::ComputeToGamma].
+    /// It has no effect with any other color mapping. The default is 2.2.
+    pub gamma: f32,
+    /// Whether an [EffectChain] applied with this camera runs its effects. Disable this for example for a minimap or UI camera
+    /// which should not be affected by the post effects of the main view. The default is true.
+    pub post_effects: bool,
+    /// The fog applied to the materials rendered with this camera, unless the material opts out of it. The default is no fog.
+    pub fog: Fog,
+    jitter: Vec2,
+    oblique_near_plane: Option<Vec4>,
+    projection_matrix: Option<Mat4>,
 }
 
 impl Camera {
@@ -37,6 +68,13 @@ impl Camera {
             ),
             tone_mapping: ToneMapping::default(),
             color_mapping: ColorMapping::default(),
+            exposure: 0.0,
+            gamma: 2.2,
+            post_effects: true,
+            fog: Fog::default(),
+            jitter: vec2(0.0, 0.0),
+            oblique_near_plane: None,
+            projection_matrix: None,
         }
     }
 
@@ -64,6 +102,13 @@ impl Camera {
             ),
             tone_mapping: ToneMapping::default(),
             color_mapping: ColorMapping::default(),
+            exposure: 0.0,
+            gamma: 2.2,
+            post_effects: true,
+            fog: Fog::default(),
+            jitter: vec2(0.0, 0.0),
+            oblique_near_plane: None,
+            projection_matrix: None,
         }
     }
 
@@ -93,6 +138,206 @@ impl Camera {
         )
     }
 
+    ///
+    /// Returns the projection matrix of this camera including the sub-pixel jitter (see [Camera::set_jitter]).
+    /// Use [Camera::projection_without_jitter] to get the projection matrix without the jitter.
+    ///
+    pub fn projection(&self) -> Mat4 {
+        let viewport = self.camera.viewport();
+        let mut projection = self.projection_without_jitter();
+        if let Some(plane) = self.oblique_near_plane {
+            // Eric Lengyel, "Oblique View Frustum Depth Projection and Clipping", Journal of Game Development, 2005
+            let clip_plane = self.camera.view().invert().unwrap().transpose() * plane;
+            if let Some(inverse) = projection.invert() {
+                let q = inverse * vec4(clip_plane.x.signum(), clip_plane.y.signum(), 1.0, 1.0);
+                let c = clip_plane * (2.0 / clip_plane.dot(q));
+                projection.x.z = c.x - projection.x.w;
+                projection.y.z = c.y - projection.y.w;
+                projection.z.z = c.z - projection.z.w;
+                projection.w.z = c.w - projection.w.w;
+            }
+        }
+        Mat4::from_translation(vec3(
+            2.0 * self.jitter.x / viewport.width as f32,
+            2.0 * self.jitter.y / viewport.height as f32,
+            0.0,
+        )) * projection
+    }
+
+    ///
+    /// Returns the projection matrix of this camera without the sub-pixel jitter and the oblique near plane, ie. the projection matrix set by
+    /// [Camera::set_projection_matrix] or otherwise the projection matrix given by the projection type.
+    ///
+    pub fn projection_without_jitter(&self) -> Mat4 {
+        self.projection_matrix
+            .unwrap_or_else(|| *self.camera.projection())
+    }
+
+    ///
+    /// Replaces the projection of this camera with the given perspective or orthographic projection matrix, for example the asymmetric projection
+    /// of an eye of a VR headset, which cannot be described by a field of view, or use `None` to go back to the projection given by the projection type.
+    /// The given matrix is used when rendering and when culling objects outside the frustum, see [Camera::in_frustum].
+    /// The projection type and near and far planes of the camera are set to values which match the matrix as closely as possible,
+    /// and they are used by the functionality that only takes the projection type into account, for example [Camera::position_at_pixel].
+    ///
+    pub fn set_projection_matrix(&mut self, projection: Option<Mat4>) {
+        self.projection_matrix = projection;
+        if let Some(m) = projection {
+            if m.w.w == 0.0 {
+                let z_near = m.w.z / (m.z.z - 1.0);
+                let z_far = m.w.z / (m.z.z + 1.0);
+                // An infinite far plane is replaced by a far plane which is far away
+                let z_far = if z_far.is_finite() && z_far > z_near {
+                    z_far
+                } else {
+                    z_near * 1.0e6
+                };
+                self.camera.set_perspective_projection(
+                    radians(2.0 * (1.0 / m.y.y).atan()),
+                    z_near,
+                    z_far,
+                );
+            } else {
+                self.camera.set_orthographic_projection(
+                    2.0 / m.y.y,
+                    (m.w.z + 1.0) / m.z.z,
+                    (m.w.z - 1.0) / m.z.z,
+                );
+            }
+        }
+    }
+
+    ///
+    /// Returns the projection matrix set by [Camera::set_projection_matrix], if any.
+    ///
+    pub fn projection_matrix(&self) -> Option<Mat4> {
+        self.projection_matrix
+    }
+
+    ///
+    /// Returns whether or not the given bounding box is within the camera frustum.
+    /// It returns false if it is fully outside and true if it is inside or intersects.
+    /// The frustum is given by the projection set by [Camera::set_projection_matrix], if any.
+    ///
+    pub fn in_frustum(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        let Some(projection) = self.projection_matrix else {
+            return self.camera.in_frustum(aabb);
+        };
+        if aabb.is_infinite() {
+            return true;
+        }
+        let m = projection * self.view();
+        let corners = [
+            vec4(aabb.min().x, aabb.min().y, aabb.min().z, 1.0),
+            vec4(aabb.max().x, aabb.min().y, aabb.min().z, 1.0),
+            vec4(aabb.min().x, aabb.max().y, aabb.min().z, 1.0),
+            vec4(aabb.max().x, aabb.max().y, aabb.min().z, 1.0),
+            vec4(aabb.min().x, aabb.min().y, aabb.max().z, 1.0),
+            vec4(aabb.max().x, aabb.min().y, aabb.max().z, 1.0),
+            vec4(aabb.min().x, aabb.max().y, aabb.max().z, 1.0),
+            vec4(aabb.max().x, aabb.max().y, aabb.max().z, 1.0),
+        ];
+        // The box is outside if all the corners are outside one of the six planes of the frustum
+        let row = m.transpose();
+        [
+            row.w + row.x,
+            row.w - row.x,
+            row.w + row.y,
+            row.w - row.y,
+            row.w + row.z,
+            row.w - row.z,
+        ]
+        .iter()
+        .all(|plane| corners.iter().any(|corner| plane.dot(*corner) >= 0.0))
+    }
+
+    ///
+    /// Replaces the near plane of the projection of this camera with the given plane in world space, so everything behind the plane is clipped (an oblique near plane).
+    /// The plane is given as `(a, b, c, d)` where the points `p` in front of the plane, which are not clipped, satisfy `a * p.x + b * p.y + c * p.z + d > 0`
+    /// and the camera must be behind the plane.
+    /// This is used for example by [ReflectionPlane](crate::renderer::ReflectionPlane) to clip away everything below a mirror.
+    /// Only the depth is changed, so the camera sees the same as without the oblique near plane except for the clipped objects.
+    ///
+    pub fn set_oblique_near_plane(&mut self, plane: Option<Vec4>) {
+        self.oblique_near_plane = plane;
+    }
+
+    ///
+    /// Returns the oblique near plane of this camera, see [Camera::set_oblique_near_plane].
+    ///
+    pub fn oblique_near_plane(&self) -> Option<Vec4> {
+        self.oblique_near_plane
+    }
+
+    ///
+    /// Sets a sub-pixel offset, in pixels, which is applied to the projection of this camera.
+    /// Changing the jitter each frame and accumulating the result over time is the basis of temporal techniques, for example [TemporalUpscaler](crate::renderer::TemporalUpscaler).
+    ///
+    pub fn set_jitter(&mut self, jitter: Vec2) {
+        self.jitter = jitter;
+    }
+
+    ///
+    /// Returns the sub-pixel offset, in pixels, applied to the projection of this camera (see [Camera::set_jitter]).
+    ///
+    pub fn jitter(&self) -> Vec2 {
+        self.jitter
+    }
+
+    ///
+    /// Uses the data of this camera in the `Camera` uniform block of the given program, which is declared by including the `"camera"` shader snippet
+    /// (see [shader_snippet](crate::renderer::shader_snippet)) and has the following `std140` layout:
+    ///
+    /// ```glsl
+    /// layout (std140) uniform Camera {
+    ///     mat4 viewProjection; // offset 0, the projection matrix multiplied by the view matrix
+    ///     mat4 view;           // offset 64, the view matrix
+    ///     mat4 projection;     // offset 128, the projection matrix including the jitter, see Camera::projection
+    ///     vec3 position;       // offset 192, the position of the camera
+    ///     vec4 viewport;       // offset 208, the x, y, width and height of the viewport in pixels
+    /// } camera;
+    /// ```
+    ///
+    /// The block is stored in a uniform buffer which is shared between all programs, see [Program::use_shared_uniform_block],
+    /// so the data is only sent to the GPU when the camera changes instead of once for each program, for example at most once per frame.
+    /// The built-in geometries and materials use this block, so using the camera in a custom shader this way does not send any additional data.
+    ///
+    /// # Panic
+    /// Will panic if the program does not use the `Camera` uniform block.
+    ///
+    pub fn use_uniform_block(&self, program: &Program) {
+        let viewport = self.viewport();
+        program.use_shared_uniform_block("Camera", &[16, 16, 16, 3, 4], |buffer| {
+            let view = self.view();
+            let projection = self.projection();
+            let view_projection = projection * view;
+            let matrices: [&[f32; 16]; 3] =
+                [view_projection.as_ref(), view.as_ref(), projection.as_ref()];
+            for (i, matrix) in matrices.into_iter().enumerate() {
+                buffer.update(i as u32, matrix);
+            }
+            buffer.update(3, AsRef::<[f32; 3]>::as_ref(&self.position()));
+            buffer.update(
+                4,
+                &[
+                    viewport.x as f32,
+                    viewport.y as f32,
+                    viewport.width as f32,
+                    viewport.height as f32,
+                ],
+            );
+        });
+    }
+
+    ///
+    /// Calls [Camera::use_uniform_block] if the program uses the `Camera` uniform block.
+    ///
+    pub fn use_uniform_block_if_required(&self, program: &Program) {
+        if program.requires_uniform_block("Camera") {
+            self.use_uniform_block(program);
+        }
+    }
+
     ///
     /// Disables the tone and color mapping so as to be ready for rendering into an intermediate render target with this camera.
     ///
@@ -108,6 +353,77 @@ impl Camera {
         self.tone_mapping = ToneMapping::default();
         self.color_mapping = ColorMapping::default();
     }
+
+    ///
+    /// Returns a camera in between the two given cameras, where a factor `t` of 0 returns the `from` camera and 1 returns the `to` camera.
+    /// The position is rotated around the target, so that the camera moves in an arc between the two views, while the target, distance to the target
+    /// and projection parameters are linearly interpolated.
+    ///
+    /// If one camera uses a perspective projection and the other an orthographic projection, the field of view is gradually narrowed while the camera
+    /// moves away from the target such that the size of the view at the target is kept (a dolly zoom), until the projection is switched to orthographic.
+    /// The viewport, tone mapping and color mapping are taken from the closest camera.
+    ///
+    pub fn interpolate(from: &Camera, to: &Camera, t: f32) -> Camera {
+        let t = t.clamp(0.0, 1.0);
+        let mut camera = if t < 0.5 { from.clone() } else { to.clone() };
+        camera.jitter = vec2(0.0, 0.0);
+        camera.projection_matrix = None;
+
+        let target = from.target().lerp(*to.target(), t);
+        let offset = |c: &Camera| {
+            let offset = c.position() - c.target();
+            let distance = offset.magnitude();
+            if distance > 0.0 {
+                (offset / distance, distance)
+            } else {
+                (-c.view_direction(), 0.0)
+            }
+        };
+        let (from_direction, from_distance) = offset(from);
+        let (to_direction, to_distance) = offset(to);
+        let rotation = Quat::from_arc(from_direction, to_direction, Some(*from.up()));
+        let direction = Quat::one().slerp(rotation, t).rotate_vector(from_direction);
+        let distance = from_distance + (to_distance - from_distance) * t;
+        let up = from.up().lerp(*to.up(), t);
+        let up = if up.magnitude2() > 0.0 {
+            up.normalize()
+        } else {
+            *to.up()
+        };
+        let z_near = from.z_near() + (to.z_near() - from.z_near()) * t;
+        let z_far = from.z_far() + (to.z_far() - from.z_far()) * t;
+
+        // The half height of the view at the target relative to the distance and the height of the view at the target
+        let frustum = |c: &Camera, distance: f32| match c.projection_type() {
+            three_d_asset::ProjectionType::Perspective { field_of_view_y } => {
+                let tan_half = (0.5f32 * field_of_view_y.0).tan();
+                (tan_half, 2.0 * distance * tan_half)
+            }
+            three_d_asset::ProjectionType::Orthographic { height } => (0.0, *height),
+        };
+        let (from_tan, from_height) = frustum(from, from_distance);
+        let (to_tan, to_height) = frustum(to, to_distance);
+        let tan_half = from_tan + (to_tan - from_tan) * t;
+        let height = from_height + (to_height - from_height) * t;
+
+        if tan_half < 0.0001 {
+            camera.set_view(target + direction * distance, target, up);
+            camera.set_orthographic_projection(height, z_near, z_far);
+        } else if from_tan > 0.0 && to_tan > 0.0 {
+            camera.set_view(target + direction * distance, target, up);
+            camera.set_perspective_projection(radians(2.0 * tan_half.atan()), z_near, z_far);
+        } else {
+            let dolly_distance = 0.5 * height / tan_half;
+            let shift = dolly_distance - distance;
+            camera.set_view(target + direction * dolly_distance, target, up);
+            camera.set_perspective_projection(
+                radians(2.0 * tan_half.atan()),
+                (z_near + shift).max(z_near),
+                (z_far + shift).max(z_near + shift + 0.001),
+            );
+        }
+        camera
+    }
 }
 
 use std::ops::Deref;
@@ -0,0 +1,111 @@
+use super::Camera;
+
+///
+/// Specifies how an animation progresses over time, see [CameraAnimator].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Starts slowly and accelerates until the end.
+    EaseIn,
+    /// Starts fast and decelerates until the end.
+    EaseOut,
+    /// Starts slowly, accelerates and then decelerates until the end.
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    ///
+    /// Returns the progress of the animation, in the range `[0..1]`, given the fraction of the duration that has elapsed.
+    ///
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - 0.5 * (2.0 - 2.0 * t).powi(3)
+                }
+            }
+        }
+    }
+}
+
+///
+/// Smoothly animates a camera from one view to another, for example when navigating between stored views in a viewer application.
+/// The cameras are interpolated using [Camera::interpolate], so the animation can also change between a perspective and an orthographic projection.
+///
+/// Start an animation with [CameraAnimator::animate_to] and call [CameraAnimator::update] each frame.
+/// Any camera control should not handle events while the animation is running, see [CameraAnimator::is_animating].
+///
+#[derive(Clone, Debug, Default)]
+pub struct CameraAnimator {
+    cameras: Option<(Camera, Camera)>,
+    duration: f64,
+    time: f64,
+    easing: Easing,
+}
+
+impl CameraAnimator {
+    ///
+    /// Creates a new camera animator which is not animating.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Starts an animation from the current view of the given camera to the view of the target camera.
+    /// The duration is in milliseconds. Any running animation is replaced, starting from the current view.
+    ///
+    pub fn animate_to(&mut self, camera: &Camera, target: &Camera, duration: f64, easing: Easing) {
+        self.cameras = Some((camera.clone(), target.clone()));
+        self.duration = duration.max(0.0);
+        self.time = 0.0;
+        self.easing = easing;
+    }
+
+    ///
+    /// Returns whether an animation is running.
+    ///
+    pub fn is_animating(&self) -> bool {
+        self.cameras.is_some()
+    }
+
+    ///
+    /// Stops the running animation, leaving the camera at the current view.
+    ///
+    pub fn stop(&mut self) {
+        self.cameras = None;
+    }
+
+    ///
+    /// Advances the animation by the given elapsed time in milliseconds, for example [FrameInput::elapsed_time](crate::FrameInput::elapsed_time),
+    /// and updates the view and projection of the given camera. The viewport of the camera is kept.
+    /// Returns whether the camera was changed.
+    ///
+    pub fn update(&mut self, camera: &mut Camera, elapsed_time: f64) -> bool {
+        let Some((from, to)) = &self.cameras else {
+            return false;
+        };
+        self.time += elapsed_time;
+        let t = if self.duration > 0.0 {
+            (self.time / self.duration).min(1.0) as f32
+        } else {
+            1.0
+        };
+        let viewport = camera.viewport();
+        *camera = Camera::interpolate(from, to, self.easing.apply(t));
+        camera.set_viewport(viewport);
+        if t >= 1.0 {
+            self.cameras = None;
+        }
+        true
+    }
+}
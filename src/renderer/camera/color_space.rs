@@ -2,14 +2,26 @@ use crate::core::*;
 
 ///
 /// Color space mapping used for mapping to/from color spaces when rendering.
+/// This is the transfer function applied to the output of the final render pass and since it is specified per [Camera], it can be chosen per viewport and render target,
+/// for example [ColorMapping::None] for frames that are passed to a video encoder or compositor which applies the transfer function itself.
 ///
 #[derive(Clone, Debug, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub enum ColorMapping {
-    /// No color mapping. Use this if you are rendering into an intermediate render target, ie. this is not the final render pass that renders into the screen.
+    /// No color mapping. Use this if you are rendering into an intermediate render target, ie. this is not the final render pass that renders into the screen,
+    /// or if you are rendering into a texture in [ColorSpace::Srgb] where the hardware converts to sRGB.
     None = 0,
     /// Maps from compute color space (HDR or linear sRGB) to sRGB color space. Use this if this is the final render pass, ie. you write to the screen or want to save it as an image.
     #[default]
     ComputeToSrgb = 1,
+    /// Same as [ColorMapping::ComputeToSrgb] but also adds a small amount of noise before the color is quantized to 8 bits per channel.
+    /// This removes visible banding in smooth gradients, for example in skies and fog, at the cost of a barely visible grain.
+    ComputeToSrgbDithered = 2,
+    /// Maps from compute color space (HDR or linear sRGB) to the Rec. 709 (BT.709) transfer function used by most video formats.
+    /// Use this if the output is passed to a video encoder which expects Rec. 709 encoded colors.
+    ComputeToRec709 = 3,
+    /// Maps from compute color space (HDR or linear sRGB) using a pure power-law transfer function, ie. `color^(1/gamma)` where the gamma is given by [Camera::gamma](crate::Camera::gamma).
+    /// Use this if the output is passed to a display or compositor which expects for example gamma 2.2 encoded colors.
+    ComputeToGamma = 4,
 }
 
 impl ColorMapping {
@@ -19,9 +31,15 @@ impl ColorMapping {
     pub fn fragment_shader_source() -> &'static str {
         "
         uniform uint ColorMappingType;
+        uniform float ColorMappingGamma;
+
+        // Interleaved gradient noise, see http://www.iryoku.com/next-generation-post-processing-in-call-of-duty-advanced-warfare
+        float dither_noise(vec2 pixel) {
+            return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+        }
 
         vec3 color_mapping(vec3 color) {
-            if (ColorMappingType == 1u) {
+            if (ColorMappingType == 1u || ColorMappingType == 2u) {
                 vec3 a = vec3(0.055, 0.055, 0.055);
                 vec3 ap1 = vec3(1.0, 1.0, 1.0) + a;
                 vec3 g = vec3(2.4, 2.4, 2.4);
@@ -30,7 +48,20 @@ impl ColorMapping {
                 vec3 lo = color * 12.92;
                 vec3 hi = ap1 * pow(color, ginv) - a;
                 color = mix(lo, hi, select);
-            } 
+            } else if (ColorMappingType == 3u) {
+                vec3 select = step(vec3(0.018, 0.018, 0.018), color);
+                vec3 lo = color * 4.5;
+                vec3 hi = 1.099 * pow(color, vec3(0.45, 0.45, 0.45)) - 0.099;
+                color = mix(lo, hi, select);
+            } else if (ColorMappingType == 4u) {
+                color = pow(max(color, vec3(0.0, 0.0, 0.0)), vec3(1.0 / ColorMappingGamma));
+            }
+            if (ColorMappingType == 2u) {
+                // Triangular distributed noise in the range [-1, 1] of the least significant bit
+                float n0 = dither_noise(gl_FragCoord.xy);
+                float n1 = dither_noise(gl_FragCoord.xy + vec2(113.0, 71.0));
+                color += (n0 + n1 - 1.0) / 255.0;
+            }
 
             return color;
         }
@@ -38,9 +69,21 @@ impl ColorMapping {
     }
 
     ///
-    /// Sends the uniform data needed to apply this color space mapping to the fragment shader.
+    /// Sends the uniform data needed to apply this color space mapping to the fragment shader, where [ColorMapping::ComputeToGamma] uses a gamma of 2.2.
     ///
+    #[deprecated(
+        note = "use `use_uniforms_with_gamma` with the `Camera::gamma` of the camera instead, otherwise the gamma of the camera is ignored"
+    )]
     pub fn use_uniforms(&self, program: &Program) {
+        self.use_uniforms_with_gamma(program, 2.2);
+    }
+
+    ///
+    /// Sends the uniform data needed to apply this color space mapping to the fragment shader,
+    /// where the given gamma is used by [ColorMapping::ComputeToGamma] (see [Camera::gamma](crate::Camera::gamma)).
+    ///
+    pub fn use_uniforms_with_gamma(&self, program: &Program, gamma: f32) {
         program.use_uniform("ColorMappingType", *self as u32);
+        program.use_uniform_if_required("ColorMappingGamma", gamma.max(0.001));
     }
 }
@@ -0,0 +1,169 @@
+use crate::core::*;
+
+///
+/// How the opacity of [Fog] increases with the distance to the camera.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum FogFalloff {
+    /// No distance fog, only the [Fog::height_fog] is applied if specified.
+    #[default]
+    None,
+    /// The fog is fully transparent closer to the camera than `start` and fully opaque further away than `end` with a linear increase in between.
+    Linear {
+        /// The distance at which the fog starts.
+        start: f32,
+        /// The distance at which the fog is fully opaque.
+        end: f32,
+    },
+    /// The transparency of the fog is `e^-(density * distance)`, which is physically correct for a fog with a constant density.
+    Exponential {
+        /// The density of the fog.
+        density: f32,
+    },
+    /// The transparency of the fog is `e^-(density * distance)^2`, which keeps the area close to the camera clear for longer than [FogFalloff::Exponential].
+    ExponentialSquared {
+        /// The density of the fog.
+        density: f32,
+    },
+}
+
+///
+/// Fog which is densest close to the ground and thins out with the height, for example mist in a valley or low clouds.
+/// The density at a position is `density * e^-(falloff * (y - height))` where `y` is the height of the position,
+/// and the fog is integrated along the line of sight from the camera.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeightFog {
+    /// The height at which the density is [HeightFog::density].
+    pub height: f32,
+    /// The density of the fog at [HeightFog::height].
+    pub density: f32,
+    /// How fast the density decreases with the height, where 0.0 gives a constant density everywhere.
+    pub falloff: f32,
+}
+
+impl Default for HeightFog {
+    fn default() -> Self {
+        Self {
+            height: 0.0,
+            density: 0.1,
+            falloff: 0.5,
+        }
+    }
+}
+
+///
+/// Fog which blends the color of surfaces towards the fog color depending on the distance to the camera ([Fog::falloff])
+/// and the height above the ground ([Fog::height_fog]).
+///
+/// The fog of a [Camera] (see [Camera::fog]) is applied when rendering [PhysicalMaterial](crate::renderer::PhysicalMaterial),
+/// [UnlitMaterial](crate::renderer::UnlitMaterial) and [DeferredPhysicalMaterial](crate::renderer::DeferredPhysicalMaterial) with the camera,
+/// and each [PhysicalMaterial](crate::renderer::PhysicalMaterial) and [UnlitMaterial](crate::renderer::UnlitMaterial) can opt out of the fog,
+/// for example for user interface elements or emissive objects like the sun that should shine through the fog.
+/// To instead apply fog to an already rendered scene, use a [FogEffect](crate::renderer::FogEffect).
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fog {
+    /// The color of the fog.
+    pub color: Srgba,
+    /// How the opacity of the fog increases with the distance to the camera.
+    pub falloff: FogFalloff,
+    /// Fog which depends on the height, which is added to the distance fog.
+    pub height_fog: Option<HeightFog>,
+    /// The maximum opacity of the fog in the range `[0..1]`, for example to keep distant mountains slightly visible.
+    pub max_opacity: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            color: Srgba::WHITE,
+            falloff: FogFalloff::None,
+            height_fog: None,
+            max_opacity: 1.0,
+        }
+    }
+}
+
+impl Fog {
+    ///
+    /// Returns whether or not any fog is applied, ie. whether the [Fog::falloff] is not [FogFalloff::None] or there is a [Fog::height_fog].
+    ///
+    pub fn is_enabled(&self) -> bool {
+        self.falloff != FogFalloff::None || self.height_fog.is_some()
+    }
+
+    ///
+    /// Returns the fragment shader source for applying fog in a shader, which defines the functions
+    /// `float fog_factor(vec3 position)`, returning the opacity of the fog in front of the given world space position,
+    /// and `vec3 apply_fog(vec3 color, vec3 position)`, returning the color blended with the fog color.
+    ///
+    pub fn fragment_shader_source() -> &'static str {
+        "
+        uniform vec3 fogColor;
+        uniform int fogFalloff;
+        uniform vec2 fogParameters;
+        uniform int fogUseHeight;
+        uniform vec3 fogHeightParameters;
+        uniform float fogMaxOpacity;
+        uniform vec3 fogEyePosition;
+
+        float fog_factor(vec3 position) {
+            float dist = distance(fogEyePosition, position);
+            float transmittance = 1.0;
+            if (fogFalloff == 1) {
+                transmittance = clamp((fogParameters.y - dist) / max(fogParameters.y - fogParameters.x, 0.0001), 0.0, 1.0);
+            } else if (fogFalloff == 2) {
+                transmittance = exp(-fogParameters.x * dist);
+            } else if (fogFalloff == 3) {
+                float x = fogParameters.x * dist;
+                transmittance = exp(-x * x);
+            }
+            if (fogUseHeight == 1) {
+                float falloff = fogHeightParameters.z;
+                float k = falloff * (position.y - fogEyePosition.y);
+                float integral = abs(k) > 0.0001 ? (1.0 - exp(-k)) / k : 1.0;
+                float amount = fogHeightParameters.y * dist * exp(-falloff * (fogEyePosition.y - fogHeightParameters.x)) * integral;
+                transmittance *= exp(-max(amount, 0.0));
+            }
+            return min(1.0 - transmittance, fogMaxOpacity);
+        }
+
+        vec3 apply_fog(vec3 color, vec3 position) {
+            return mix(color, fogColor, fog_factor(position));
+        }
+        "
+    }
+
+    ///
+    /// Sends the uniform data needed to apply this fog to the fragment shader, where the distance is measured from the given eye position,
+    /// usually the position of the camera.
+    ///
+    pub fn use_uniforms(&self, program: &Program, eye_position: Vec3) {
+        let (falloff, parameters) = match self.falloff {
+            FogFalloff::None => (0, vec2(0.0, 0.0)),
+            FogFalloff::Linear { start, end } => (1, vec2(start, end)),
+            FogFalloff::Exponential { density } => (2, vec2(density, 0.0)),
+            FogFalloff::ExponentialSquared { density } => (3, vec2(density, 0.0)),
+        };
+        program.use_uniform_if_required("fogColor", self.color.to_linear_srgb().truncate());
+        program.use_uniform_if_required("fogFalloff", falloff);
+        program.use_uniform_if_required("fogParameters", parameters);
+        program.use_uniform_if_required("fogUseHeight", self.height_fog.is_some() as i32);
+        let height_fog = self.height_fog.unwrap_or(HeightFog {
+            height: 0.0,
+            density: 0.0,
+            falloff: 0.0,
+        });
+        program.use_uniform_if_required(
+            "fogHeightParameters",
+            vec3(
+                height_fog.height,
+                height_fog.density.max(0.0),
+                height_fog.falloff,
+            ),
+        );
+        program.use_uniform_if_required("fogMaxOpacity", self.max_opacity.clamp(0.0, 1.0));
+        program.use_uniform_if_required("fogEyePosition", eye_position);
+    }
+}
@@ -24,8 +24,12 @@ impl ToneMapping {
     pub fn fragment_shader_source() -> &'static str {
         "
         uniform uint toneMappingType;
+        uniform float toneMappingExposure;
 
         vec3 tone_mapping(vec3 color) {
+            if (toneMappingType != 0u) {
+                color *= exp2(toneMappingExposure);
+            }
             if (toneMappingType == 1u) {
                 color = color / (color + vec3(1.0));
                 color = clamp(color, 0.0, 1.0);
@@ -55,6 +59,16 @@ impl ToneMapping {
     /// Sends the uniform data needed to apply this tone mapping to the fragment shader.
     ///
     pub fn use_uniforms(&self, program: &Program) {
+        self.use_uniforms_with_exposure(program, 0.0);
+    }
+
+    ///
+    /// Sends the uniform data needed to apply this tone mapping to the fragment shader,
+    /// where the color is multiplied by `2^exposure` before the tone mapping is applied (see [Camera::exposure](crate::Camera::exposure)).
+    /// The exposure is not applied if the tone mapping is [ToneMapping::None].
+    ///
+    pub fn use_uniforms_with_exposure(&self, program: &Program, exposure: f32) {
         program.use_uniform("toneMappingType", *self as u32);
+        program.use_uniform("toneMappingExposure", exposure);
     }
 }
@@ -22,6 +22,10 @@ mod fly_control;
 #[doc(inline)]
 pub use fly_control::*;
 
+mod constrained_drag;
+#[doc(inline)]
+pub use constrained_drag::*;
+
 pub use three_d_asset::PixelPoint as PhysicalPoint;
 
 /// Type of mouse button.
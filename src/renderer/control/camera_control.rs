@@ -84,11 +84,92 @@ impl std::default::Default for CameraAction {
     }
 }
 
+///
+/// An input which can be bound to a [CameraAction], see [CameraBindings::bind].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CameraInput {
+    /// Dragging horizontally with the given mouse button.
+    DragHorizontal(MouseButton),
+    /// Dragging vertically with the given mouse button.
+    DragVertical(MouseButton),
+    /// Scrolling horizontally.
+    ScrollHorizontal,
+    /// Scrolling vertically.
+    ScrollVertical,
+    /// Holding down the given key. The action is applied with an amount of 1 each time [CameraControl::handle_events] is called
+    /// while the key is down, ie. each frame, so the speed of the action is the change per frame.
+    Key(Key),
+}
+
+///
+/// A map from inputs combined with a state of the modifiers to camera actions, which is used by a [CameraControl] in addition to the fixed input slots.
+/// For example, Maya-style navigation is achieved by binding [CameraInput::DragHorizontal] and [CameraInput::DragVertical] with the left mouse button
+/// and the alt modifier to orbit actions.
+///
+#[derive(Clone, Debug, Default)]
+pub struct CameraBindings {
+    bindings: Vec<(CameraInput, Modifiers, CameraAction)>,
+    pressed_keys: Vec<(Key, Modifiers)>,
+}
+
+impl CameraBindings {
+    ///
+    /// Creates a new empty set of bindings.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Binds the given action to the given input when exactly the given modifiers are down, replacing any existing binding for the same input and modifiers.
+    ///
+    pub fn bind(&mut self, input: CameraInput, modifiers: Modifiers, action: CameraAction) {
+        self.unbind(input, modifiers);
+        self.bindings.push((input, modifiers, action));
+    }
+
+    ///
+    /// Removes the binding for the given input and modifiers.
+    ///
+    pub fn unbind(&mut self, input: CameraInput, modifiers: Modifiers) {
+        self.bindings
+            .retain(|(i, m, _)| *i != input || *m != modifiers);
+    }
+
+    ///
+    /// Removes all bindings.
+    ///
+    pub fn clear(&mut self) {
+        self.bindings.clear();
+        self.pressed_keys.clear();
+    }
+
+    ///
+    /// Returns the action bound to the given input and modifiers, if any.
+    ///
+    pub fn action(&self, input: CameraInput, modifiers: Modifiers) -> Option<CameraAction> {
+        self.bindings
+            .iter()
+            .find(|(i, m, _)| *i == input && *m == modifiers)
+            .map(|(_, _, action)| *action)
+    }
+
+    ///
+    /// Returns mutable references to all bound actions, for example to update the target of the actions.
+    ///
+    pub fn actions_mut(&mut self) -> impl Iterator<Item = &mut CameraAction> {
+        self.bindings.iter_mut().map(|(_, _, action)| action)
+    }
+}
+
 ///
 /// A customizable controller for the camera.
 /// It is possible to specify a [CameraAction] for each of the input events.
+/// Actions can also be bound to combinations of mouse buttons, modifiers and keys using the [CameraControl::bindings],
+/// which take precedence over the fixed input slots when the modifiers match.
 ///
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct CameraControl {
     /// Specifies what happens when dragging horizontally with the left mouse button.
     pub left_drag_horizontal: CameraAction,
@@ -106,6 +187,8 @@ pub struct CameraControl {
     pub scroll_horizontal: CameraAction,
     /// Specifies what happens when scrolling vertically.
     pub scroll_vertical: CameraAction,
+    /// Additional bindings of inputs combined with modifiers to actions.
+    pub bindings: CameraBindings,
 }
 
 impl CameraControl {
@@ -122,38 +205,91 @@ impl CameraControl {
                 Event::MouseMotion {
                     delta,
                     button,
+                    modifiers,
                     handled,
                     ..
                 } => {
                     if !*handled && button.is_some() {
                         if let Some(b) = button {
-                            let (control_horizontal, control_vertical) = match b {
-                                MouseButton::Left => {
-                                    (self.left_drag_horizontal, self.left_drag_vertical)
-                                }
-                                MouseButton::Middle => {
-                                    (self.middle_drag_horizontal, self.middle_drag_vertical)
-                                }
-                                MouseButton::Right => {
-                                    (self.right_drag_horizontal, self.right_drag_vertical)
-                                }
-                            };
+                            let horizontal = self
+                                .bindings
+                                .action(CameraInput::DragHorizontal(*b), *modifiers);
+                            let vertical = self
+                                .bindings
+                                .action(CameraInput::DragVertical(*b), *modifiers);
+                            let (control_horizontal, control_vertical) =
+                                if horizontal.is_some() || vertical.is_some() {
+                                    (horizontal.unwrap_or_default(), vertical.unwrap_or_default())
+                                } else {
+                                    match b {
+                                        MouseButton::Left => {
+                                            (self.left_drag_horizontal, self.left_drag_vertical)
+                                        }
+                                        MouseButton::Middle => {
+                                            (self.middle_drag_horizontal, self.middle_drag_vertical)
+                                        }
+                                        MouseButton::Right => {
+                                            (self.right_drag_horizontal, self.right_drag_vertical)
+                                        }
+                                    }
+                                };
                             *handled = self.handle_action(camera, control_horizontal, delta.0);
                             *handled |= self.handle_action(camera, control_vertical, delta.1);
                             change |= *handled;
                         }
                     }
                 }
-                Event::MouseWheel { delta, handled, .. } => {
+                Event::MouseWheel {
+                    delta,
+                    modifiers,
+                    handled,
+                    ..
+                } => {
                     if !*handled {
-                        *handled = self.handle_action(camera, self.scroll_horizontal, delta.0);
-                        *handled |= self.handle_action(camera, self.scroll_vertical, delta.1);
+                        let horizontal = self
+                            .bindings
+                            .action(CameraInput::ScrollHorizontal, *modifiers);
+                        let vertical = self
+                            .bindings
+                            .action(CameraInput::ScrollVertical, *modifiers);
+                        let (control_horizontal, control_vertical) =
+                            if horizontal.is_some() || vertical.is_some() {
+                                (horizontal.unwrap_or_default(), vertical.unwrap_or_default())
+                            } else {
+                                (self.scroll_horizontal, self.scroll_vertical)
+                            };
+                        *handled = self.handle_action(camera, control_horizontal, delta.0);
+                        *handled |= self.handle_action(camera, control_vertical, delta.1);
                         change |= *handled;
                     }
                 }
+                Event::KeyPress {
+                    kind,
+                    modifiers,
+                    handled,
+                } if !*handled
+                    && self
+                        .bindings
+                        .action(CameraInput::Key(*kind), *modifiers)
+                        .is_some() =>
+                {
+                    if !self.bindings.pressed_keys.iter().any(|(k, _)| k == kind) {
+                        self.bindings.pressed_keys.push((*kind, *modifiers));
+                    }
+                    *handled = true;
+                }
+                Event::KeyRelease { kind, .. } => {
+                    self.bindings.pressed_keys.retain(|(k, _)| k != kind);
+                }
                 _ => {}
             }
         }
+        for i in 0..self.bindings.pressed_keys.len() {
+            let (key, modifiers) = self.bindings.pressed_keys[i];
+            if let Some(action) = self.bindings.action(CameraInput::Key(key), modifiers) {
+                change |= self.handle_action(camera, action, 1.0);
+            }
+        }
         change
     }
 
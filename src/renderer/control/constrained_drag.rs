@@ -0,0 +1,230 @@
+use crate::renderer::*;
+
+///
+/// An infinite plane in world space, for example the ground plane that objects are placed on by an object placement tool, see [pick_on_plane].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    /// A point in the plane.
+    pub point: Vec3,
+    /// The normal of the plane, which does not need to be normalized.
+    pub normal: Vec3,
+}
+
+impl Plane {
+    ///
+    /// Creates a new plane through the given point with the given normal.
+    ///
+    pub fn new(point: Vec3, normal: Vec3) -> Self {
+        Self { point, normal }
+    }
+
+    ///
+    /// Creates a new plane through the given point which faces the given camera,
+    /// ie. moving along the plane corresponds to moving parallel to the screen.
+    ///
+    pub fn facing_camera(camera: &Camera, point: Vec3) -> Self {
+        Self::new(point, -camera.view_direction())
+    }
+
+    ///
+    /// Returns the given position projected onto the plane, ie. the closest point in the plane.
+    ///
+    pub fn project(&self, position: Vec3) -> Vec3 {
+        let normal = self.normal.normalize();
+        position - normal * (position - self.point).dot(normal)
+    }
+
+    ///
+    /// Returns the distance along the ray with the given origin and direction to the intersection with the plane,
+    /// in units of the length of the direction, or `None` if the ray is parallel to the plane or intersects it behind the origin.
+    ///
+    pub fn ray_intersection(&self, origin: Vec3, direction: Vec3) -> Option<f32> {
+        let denominator = self.normal.dot(direction);
+        if denominator.abs() < 1.0e-6 * self.normal.magnitude() * direction.magnitude() {
+            return None;
+        }
+        let distance = self.normal.dot(self.point - origin) / denominator;
+        (distance >= 0.0).then_some(distance)
+    }
+}
+
+///
+/// Finds the intersection between a ray from the given camera in the given pixel coordinate and the given plane,
+/// for example to convert the position of the mouse into a position on the ground.
+/// The pixel coordinate must be in physical pixels, where (viewport.x, viewport.y) indicate the bottom left corner of the viewport
+/// and (viewport.x + viewport.width, viewport.y + viewport.height) indicate the top right corner.
+/// Returns ```None``` if the plane is not hit before the far plane (`z_far`) of the camera, for example if the ray is parallel to the plane.
+///
+pub fn pick_on_plane(
+    camera: &Camera,
+    pixel: impl Into<PhysicalPoint> + Copy,
+    plane: &Plane,
+) -> Option<Vec3> {
+    let position = camera.position_at_pixel(pixel);
+    let direction = camera.view_direction_at_pixel(pixel);
+    plane
+        .ray_intersection(position, direction)
+        .filter(|distance| *distance <= camera.z_far())
+        .map(|distance| position + direction * distance)
+}
+
+///
+/// Same as [pick_on_plane], except that the intersection is snapped to the closest point on a grid with the given spacing,
+/// which has a grid point in [Plane::point].
+/// The grid is aligned with the world axes and then projected onto the plane, so it is regular for planes whose normal is along one of the axes,
+/// for example the ground plane.
+///
+pub fn pick_on_grid(
+    camera: &Camera,
+    pixel: impl Into<PhysicalPoint> + Copy,
+    plane: &Plane,
+    spacing: f32,
+) -> Option<Vec3> {
+    pick_on_plane(camera, pixel, plane)
+        .map(|position| plane.project(plane.point + snap(position - plane.point, spacing)))
+}
+
+///
+/// Finds the point on the line through the given origin along the given direction which is closest to the ray from the given camera
+/// in the given pixel coordinate, for example to move an object along one of its axes with the mouse.
+/// See [pick_on_plane] for a description of the pixel coordinate.
+/// Returns ```None``` if the line is parallel to the ray.
+///
+pub fn pick_on_axis(
+    camera: &Camera,
+    pixel: impl Into<PhysicalPoint> + Copy,
+    origin: Vec3,
+    direction: Vec3,
+) -> Option<Vec3> {
+    axis_distance(camera, pixel, origin, direction.normalize())
+        .map(|distance| origin + direction.normalize() * distance)
+}
+
+// The signed distance from the origin along the normalized axis direction to the point closest to the ray at the pixel
+fn axis_distance(
+    camera: &Camera,
+    pixel: impl Into<PhysicalPoint> + Copy,
+    origin: Vec3,
+    direction: Vec3,
+) -> Option<f32> {
+    let ray_origin = camera.position_at_pixel(pixel);
+    let ray_direction = camera.view_direction_at_pixel(pixel);
+    let cos_angle = direction.dot(ray_direction);
+    let denominator = 1.0 - cos_angle * cos_angle;
+    if denominator < 1.0e-6 {
+        return None;
+    }
+    let offset = ray_origin - origin;
+    Some((offset.dot(direction) - offset.dot(ray_direction) * cos_angle) / denominator)
+}
+
+fn snap(value: Vec3, spacing: f32) -> Vec3 {
+    if spacing > 0.0 {
+        (value / spacing).map(|v| v.round()) * spacing
+    } else {
+        value
+    }
+}
+
+///
+/// The constraint of the movement of a [ConstrainedDrag].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DragConstraint {
+    /// The movement is restricted to the given plane, see [pick_on_plane].
+    Plane(Plane),
+    /// The movement is restricted to the line through the given origin along the given direction, see [pick_on_axis].
+    Axis {
+        /// A point on the line.
+        origin: Vec3,
+        /// The direction of the line, which does not need to be normalized.
+        direction: Vec3,
+    },
+}
+
+///
+/// Converts the movement of the mouse into a movement in 3D which is constrained to a plane or an axis,
+/// for example when moving an object with an object placement tool or a translation gizmo.
+///
+/// Create a new drag when a mouse button is pressed, using the position of the mouse and a constraint through the position of the object,
+/// and then call [ConstrainedDrag::translation] with the position of the mouse each time it moves
+/// and add the result to the position of the object when the drag started.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConstrainedDrag {
+    /// The constraint of the movement.
+    pub constraint: DragConstraint,
+    /// If specified, the translation is snapped to multiples of this distance.
+    /// For an axis, the distance along the axis is snapped, otherwise the translation is snapped to a grid aligned with the world axes.
+    pub grid_spacing: Option<f32>,
+    start: Vec3,
+}
+
+impl ConstrainedDrag {
+    ///
+    /// Starts a new drag at the given pixel coordinate as seen from the given camera.
+    /// Returns ```None``` if the pixel does not correspond to a point on the constraint, see [pick_on_plane] and [pick_on_axis].
+    ///
+    pub fn new(
+        camera: &Camera,
+        pixel: impl Into<PhysicalPoint> + Copy,
+        constraint: DragConstraint,
+    ) -> Option<Self> {
+        let mut drag = Self {
+            constraint,
+            grid_spacing: None,
+            start: Vec3::zero(),
+        };
+        drag.start = drag.position(camera, pixel)?;
+        Some(drag)
+    }
+
+    ///
+    /// Returns the point on the constraint where the drag started.
+    ///
+    pub fn start_position(&self) -> Vec3 {
+        self.start
+    }
+
+    ///
+    /// Returns the translation from the point where the drag started to the point on the constraint at the given pixel coordinate
+    /// as seen from the given camera, snapped to the [ConstrainedDrag::grid_spacing] if specified.
+    /// Returns ```None``` if the pixel does not correspond to a point on the constraint,
+    /// in which case the previous translation can be used until the mouse is moved to a valid position.
+    ///
+    pub fn translation(
+        &self,
+        camera: &Camera,
+        pixel: impl Into<PhysicalPoint> + Copy,
+    ) -> Option<Vec3> {
+        let translation = self.position(camera, pixel)? - self.start;
+        let spacing = self.grid_spacing.unwrap_or(0.0);
+        Some(match self.constraint {
+            DragConstraint::Plane(plane) => {
+                let snapped = snap(translation, spacing);
+                let normal = plane.normal.normalize();
+                snapped - normal * snapped.dot(normal)
+            }
+            DragConstraint::Axis { direction, .. } => {
+                let direction = direction.normalize();
+                let distance = translation.dot(direction);
+                direction
+                    * if spacing > 0.0 {
+                        (distance / spacing).round() * spacing
+                    } else {
+                        distance
+                    }
+            }
+        })
+    }
+
+    fn position(&self, camera: &Camera, pixel: impl Into<PhysicalPoint> + Copy) -> Option<Vec3> {
+        match self.constraint {
+            DragConstraint::Plane(plane) => pick_on_plane(camera, pixel, &plane),
+            DragConstraint::Axis { origin, direction } => {
+                pick_on_axis(camera, pixel, origin, direction)
+            }
+        }
+    }
+}
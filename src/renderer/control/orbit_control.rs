@@ -1,19 +1,55 @@
 use crate::renderer::*;
 
+///
+/// Specifies how the camera is rotated around the target when dragging with an [OrbitControl].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OrbitMode {
+    /// The camera orbits around the target while keeping the up direction of the camera fixed.
+    /// The rotation stops when the camera gets close to looking straight along the up direction.
+    #[default]
+    Orbit,
+    /// Horizontal dragging rotates the camera around the up direction and vertical dragging changes the elevation above or below the target,
+    /// which is clamped just before reaching the poles so that the camera never flips over.
+    /// This is the mode of most model viewers and CAD applications.
+    Turntable,
+    /// The camera is rotated around the axis perpendicular to the drag direction as if rolling a ball under the mouse, with no fixed up direction,
+    /// so the camera can be rotated freely into any orientation.
+    Trackball,
+}
+
 ///
 /// A control that makes the camera orbit around a target.
+/// Dragging with the left mouse button orbits around the target as specified by the [OrbitMode], dragging with the middle mouse button pans the camera and the target
+/// and scrolling zooms towards the target.
 ///
 pub struct OrbitControl {
     control: CameraControl,
+    target: Vec3,
+    mode: OrbitMode,
+    /// The fraction of the orbit and pan velocity which is kept each frame after the mouse button is released, in the range `[0..1)`.
+    /// A value of 0, which is the default, stops the camera as soon as the mouse button is released,
+    /// while a value close to 1 makes the camera continue moving for a long time.
+    pub inertia: f32,
+    velocity: Option<(MouseButton, Vec2)>,
+    dragging: Option<MouseButton>,
 }
 
 impl OrbitControl {
     /// Creates a new orbit control with the given target and minimum and maximum distance to the target.
     pub fn new(target: Vec3, min_distance: f32, max_distance: f32) -> Self {
+        Self::new_with_mode(target, min_distance, max_distance, OrbitMode::default())
+    }
+
+    /// Creates a new orbit control with the given target, minimum and maximum distance to the target and mode of rotation.
+    pub fn new_with_mode(
+        target: Vec3,
+        min_distance: f32,
+        max_distance: f32,
+        mode: OrbitMode,
+    ) -> Self {
         Self {
             control: CameraControl {
-                left_drag_horizontal: CameraAction::OrbitLeft { target, speed: 0.1 },
-                left_drag_vertical: CameraAction::OrbitUp { target, speed: 0.1 },
                 scroll_vertical: CameraAction::Zoom {
                     min: min_distance,
                     max: max_distance,
@@ -22,23 +58,224 @@ impl OrbitControl {
                 },
                 ..Default::default()
             },
+            target,
+            mode,
+            inertia: 0.0,
+            velocity: None,
+            dragging: None,
         }
     }
 
+    ///
+    /// Returns the mode of rotation.
+    ///
+    pub fn mode(&self) -> OrbitMode {
+        self.mode
+    }
+
+    ///
+    /// Sets the mode of rotation.
+    ///
+    pub fn set_mode(&mut self, mode: OrbitMode) {
+        self.mode = mode;
+        self.velocity = None;
+    }
+
+    ///
+    /// Returns the target which the camera orbits around.
+    ///
+    pub fn target(&self) -> Vec3 {
+        self.target
+    }
+
+    ///
+    /// Sets the target which the camera orbits around. The camera is not changed.
+    ///
+    pub fn set_target(&mut self, target: Vec3) {
+        self.target = target;
+        self.velocity = None;
+    }
+
+    ///
+    /// Moves the target to the center of the given bounding box and the camera such that the whole bounding box is visible,
+    /// keeping the current view direction. For an orthographic camera, the height of the view is changed instead of the distance to the target.
+    /// Returns false and does nothing if the bounding box is empty.
+    ///
+    pub fn focus(&mut self, camera: &mut Camera, aabb: &AxisAlignedBoundingBox) -> bool {
+        if aabb.is_empty() {
+            return false;
+        }
+        let target = aabb.center();
+        let radius = (0.5 * aabb.size().magnitude()).max(0.001);
+        let direction = -camera.view_direction();
+        let up = *camera.up();
+        match *camera.projection_type() {
+            three_d_asset::ProjectionType::Perspective { field_of_view_y } => {
+                let tan_half =
+                    (0.5 * field_of_view_y.0).tan() * camera.viewport().aspect().min(1.0);
+                let distance = radius / tan_half.atan().sin();
+                camera.set_view(target + direction * distance, target, up);
+            }
+            three_d_asset::ProjectionType::Orthographic { .. } => {
+                let distance = target.distance(*camera.position()).max(2.0 * radius);
+                let height = 2.0 * radius / camera.viewport().aspect().min(1.0);
+                let (z_near, z_far) = (camera.z_near(), camera.z_far());
+                camera.set_view(target + direction * distance, target, up);
+                camera.set_orthographic_projection(height, z_near, z_far);
+            }
+        }
+        self.set_target(target);
+        true
+    }
+
     /// Handles the events. Must be called each frame.
     pub fn handle_events(&mut self, camera: &mut Camera, events: &mut [Event]) -> bool {
-        if let CameraAction::Zoom { speed, target, .. } = &mut self.control.scroll_vertical {
-            let x = target.distance(*camera.position());
-            *speed = 0.01 * x + 0.001;
+        let mut change = false;
+        let mut delta = None;
+        for event in events.iter_mut() {
+            match event {
+                Event::MousePress {
+                    button,
+                    handled: false,
+                    ..
+                } => {
+                    self.dragging = Some(*button);
+                    self.velocity = None;
+                }
+                Event::MouseRelease { button, .. } if self.dragging == Some(*button) => {
+                    self.dragging = None;
+                }
+                Event::MouseMotion {
+                    delta: (x, y),
+                    button: Some(button),
+                    handled,
+                    ..
+                } if !*handled
+                    && (*button == MouseButton::Left || *button == MouseButton::Middle) =>
+                {
+                    let d = match delta {
+                        Some((b, d)) if b == *button => d,
+                        _ => vec2(0.0, 0.0),
+                    };
+                    delta = Some((*button, d + vec2(*x, *y)));
+                    if *button == MouseButton::Middle {
+                        self.pan(camera, vec2(*x, *y));
+                    } else {
+                        self.orbit(camera, vec2(*x, *y));
+                    }
+                    *handled = true;
+                    change = true;
+                }
+                _ => {}
+            }
+        }
+        self.update_actions(camera);
+        change |= self.control.handle_events(camera, events);
+
+        if delta.is_some() {
+            self.velocity = delta;
+        } else if self.dragging.is_some() {
+            self.velocity = None;
+        } else if let Some((button, velocity)) = self.velocity {
+            let velocity = velocity * self.inertia.clamp(0.0, 0.999);
+            if velocity.magnitude() < 0.01 {
+                self.velocity = None;
+            } else {
+                match button {
+                    MouseButton::Middle => self.pan(camera, velocity),
+                    _ => self.orbit(camera, velocity),
+                }
+                self.velocity = Some((button, velocity));
+                change = true;
+            }
         }
-        if let CameraAction::OrbitLeft { speed, target } = &mut self.control.left_drag_horizontal {
-            let x = target.distance(*camera.position());
-            *speed = 0.01 * x + 0.001;
+        change
+    }
+
+    fn orbit(&self, camera: &mut Camera, delta: Vec2) {
+        let target = self.target;
+        match self.mode {
+            OrbitMode::Orbit => {
+                let speed = self.speed(camera);
+                camera.rotate_around_with_fixed_up(&target, speed * delta.x, 0.0);
+                camera.rotate_around_with_fixed_up(&target, 0.0, speed * delta.y);
+            }
+            OrbitMode::Turntable => {
+                let up = camera.up().normalize();
+                let offset = camera.position() - target;
+                if offset.magnitude2() == 0.0 {
+                    return;
+                }
+                let elevation = offset.normalize().dot(up).clamp(-1.0, 1.0).asin();
+                let max_elevation = 0.5 * std::f32::consts::PI - 0.01;
+                let pitch = (elevation + ROTATION_SPEED * delta.y)
+                    .clamp(-max_elevation, max_elevation)
+                    - elevation;
+                let axis = offset.cross(up);
+                let axis = if axis.magnitude2() > 0.0 {
+                    axis.normalize()
+                } else {
+                    -camera.right_direction()
+                };
+                let rotation = Quat::from_axis_angle(up, radians(-ROTATION_SPEED * delta.x))
+                    * Quat::from_axis_angle(axis, radians(pitch));
+                rotate_camera(camera, target, rotation, up);
+            }
+            OrbitMode::Trackball => {
+                let right = camera.right_direction().normalize();
+                let up = right.cross(camera.view_direction()).normalize();
+                let axis = up * delta.x + right * delta.y;
+                if axis.magnitude2() == 0.0 {
+                    return;
+                }
+                let rotation = Quat::from_axis_angle(
+                    axis.normalize(),
+                    radians(-ROTATION_SPEED * delta.magnitude()),
+                );
+                rotate_camera(camera, target, rotation, rotation.rotate_vector(up));
+            }
         }
-        if let CameraAction::OrbitUp { speed, target } = &mut self.control.left_drag_vertical {
-            let x = target.distance(*camera.position());
-            *speed = 0.01 * x + 0.001;
+    }
+
+    fn pan(&mut self, camera: &mut Camera, delta: Vec2) {
+        let height = match *camera.projection_type() {
+            three_d_asset::ProjectionType::Perspective { field_of_view_y } => {
+                2.0 * self.target.distance(*camera.position()) * (0.5 * field_of_view_y.0).tan()
+            }
+            three_d_asset::ProjectionType::Orthographic { height } => height,
+        };
+        let scale = height / camera.viewport().height.max(1) as f32;
+        let right = camera.right_direction();
+        let up = right.cross(camera.view_direction());
+        let change = (up * delta.y - right * delta.x) * scale;
+        camera.translate(&change);
+        self.target += change;
+        self.update_actions(camera);
+    }
+
+    fn speed(&self, camera: &Camera) -> f32 {
+        0.01 * self.target.distance(*camera.position()) + 0.001
+    }
+
+    fn update_actions(&mut self, camera: &Camera) {
+        let speed = self.speed(camera);
+        if let CameraAction::Zoom {
+            speed: s, target, ..
+        } = &mut self.control.scroll_vertical
+        {
+            *s = speed;
+            *target = self.target;
         }
-        self.control.handle_events(camera, events)
     }
 }
+
+///
+/// The rotation in radians for each logical pixel the mouse is dragged in the [OrbitMode::Turntable] and [OrbitMode::Trackball] modes.
+///
+const ROTATION_SPEED: f32 = 0.01;
+
+fn rotate_camera(camera: &mut Camera, point: Vec3, rotation: Quat, up: Vec3) {
+    let position = point + rotation.rotate_vector(camera.position() - point);
+    let target = point + rotation.rotate_vector(camera.target() - point);
+    camera.set_view(position, target, up);
+}
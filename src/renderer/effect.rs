@@ -44,8 +44,11 @@ macro_rules! impl_effect_body {
     };
 }
 
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
 mod fog;
 #[doc(inline)]
+#[cfg(feature = "effects")]
 pub use fog::*;
 
 mod copy;
@@ -56,14 +59,94 @@ mod full_screen;
 #[doc(inline)]
 pub use full_screen::*;
 
+mod effect_chain;
+#[doc(inline)]
+pub use effect_chain::*;
+
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
 mod fxaa;
 #[doc(inline)]
+#[cfg(feature = "effects")]
 pub use fxaa::*;
 
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
 mod water;
 #[doc(inline)]
+#[cfg(feature = "effects")]
 pub use water::*;
 
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
+mod color_grading;
+#[doc(inline)]
+#[cfg(feature = "effects")]
+pub use color_grading::*;
+
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
+mod ssao;
+#[doc(inline)]
+#[cfg(feature = "effects")]
+pub use ssao::*;
+
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
+mod ssr;
+#[doc(inline)]
+#[cfg(feature = "effects")]
+pub use ssr::*;
+
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
+mod depth_of_field;
+#[doc(inline)]
+#[cfg(feature = "effects")]
+pub use depth_of_field::*;
+
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
+mod motion_blur;
+#[doc(inline)]
+#[cfg(feature = "effects")]
+pub use motion_blur::*;
+
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
+mod temporal_upscaler;
+#[doc(inline)]
+#[cfg(feature = "effects")]
+pub use temporal_upscaler::*;
+
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
+mod temporal_anti_aliasing;
+#[doc(inline)]
+#[cfg(feature = "effects")]
+pub use temporal_anti_aliasing::*;
+
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
+mod half_resolution;
+#[doc(inline)]
+#[cfg(feature = "effects")]
+pub use half_resolution::*;
+
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
+mod outline;
+#[doc(inline)]
+#[cfg(feature = "effects")]
+pub use outline::*;
+
+#[cfg(feature = "effects")]
+#[cfg_attr(docsrs, doc(cfg(feature = "effects")))]
+mod luminance_meter;
+#[doc(inline)]
+#[cfg(feature = "effects")]
+pub use luminance_meter::*;
+
 pub(crate) mod lighting_pass;
 
 use crate::renderer::*;
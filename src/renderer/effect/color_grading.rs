@@ -0,0 +1,262 @@
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// A 3D color lookup table (LUT) used for color grading, see [ColorGradingEffect].
+/// The lookup table maps an input color to an output color and can be created in most image editing and color grading tools.
+///
+#[derive(Clone)]
+pub struct ColorLut {
+    texture: Arc<Texture3D>,
+    domain_min: Vec3,
+    domain_max: Vec3,
+}
+
+impl ColorLut {
+    ///
+    /// Creates a new color lookup table from the source of a `.cube` file (the Adobe/Resolve cube LUT format).
+    /// Only 3D lookup tables are supported, ie. the source must contain a `LUT_3D_SIZE` entry.
+    ///
+    pub fn from_cube(context: &Context, source: &str) -> Result<Self, RendererError> {
+        let mut size = None;
+        let mut domain_min = vec3(0.0, 0.0, 0.0);
+        let mut domain_max = vec3(1.0, 1.0, 1.0);
+        let mut data = Vec::new();
+        for line in source.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("TITLE") {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap();
+            match keyword {
+                "LUT_3D_SIZE" => {
+                    size = Some(
+                        words
+                            .next()
+                            .and_then(|w| w.parse::<u32>().ok())
+                            .ok_or_else(|| RendererError::InvalidCubeLut(line.to_string()))?,
+                    );
+                }
+                "DOMAIN_MIN" => domain_min = parse_cube_vector(line, words)?,
+                "DOMAIN_MAX" => domain_max = parse_cube_vector(line, words)?,
+                "LUT_1D_SIZE" | "LUT_1D_INPUT_RANGE" => {
+                    return Err(RendererError::InvalidCubeLut(
+                        "1D lookup tables are not supported".to_string(),
+                    ));
+                }
+                "LUT_3D_INPUT_RANGE" => {
+                    let min = words.next().and_then(|w| w.parse::<f32>().ok());
+                    let max = words.next().and_then(|w| w.parse::<f32>().ok());
+                    if let (Some(min), Some(max)) = (min, max) {
+                        domain_min = vec3(min, min, min);
+                        domain_max = vec3(max, max, max);
+                    } else {
+                        return Err(RendererError::InvalidCubeLut(line.to_string()));
+                    }
+                }
+                _ => {
+                    let value = parse_cube_vector(line, line.split_whitespace())?;
+                    data.push([
+                        f16::from_f32(value.x),
+                        f16::from_f32(value.y),
+                        f16::from_f32(value.z),
+                    ]);
+                }
+            }
+        }
+        let size = size.ok_or_else(|| {
+            RendererError::InvalidCubeLut("missing LUT_3D_SIZE entry".to_string())
+        })?;
+        let expected = (size * size * size) as usize;
+        if size < 2 || data.len() != expected {
+            return Err(RendererError::InvalidCubeLut(format!(
+                "expected {} entries for a lookup table of size {}, found {}",
+                expected,
+                size,
+                data.len()
+            )));
+        }
+        Ok(Self {
+            texture: Arc::new(Texture3D::new(
+                context,
+                &CpuTexture3D {
+                    name: "color lut".to_string(),
+                    data: TextureData::RgbF16(data),
+                    width: size,
+                    height: size,
+                    depth: size,
+                    min_filter: Interpolation::Linear,
+                    mag_filter: Interpolation::Linear,
+                    mip_map_filter: None,
+                    wrap_s: Wrapping::ClampToEdge,
+                    wrap_t: Wrapping::ClampToEdge,
+                    wrap_r: Wrapping::ClampToEdge,
+                },
+            )),
+            domain_min,
+            domain_max,
+        })
+    }
+
+    ///
+    /// Returns the size of the lookup table in each dimension.
+    ///
+    pub fn size(&self) -> u32 {
+        self.texture.width()
+    }
+}
+
+fn parse_cube_vector<'a>(
+    line: &str,
+    mut words: impl Iterator<Item = &'a str>,
+) -> Result<Vec3, RendererError> {
+    let mut value = [0.0; 3];
+    for v in value.iter_mut() {
+        *v = words
+            .next()
+            .and_then(|w| w.parse::<f32>().ok())
+            .ok_or_else(|| RendererError::InvalidCubeLut(line.to_string()))?;
+    }
+    Ok(vec3(value[0], value[1], value[2]))
+}
+
+///
+/// A configurable tone mapping and color grading stage which is applied to a HDR color texture, usually when resolving an intermediate render target to the screen.
+/// The stages are applied in the following order: exposure, tone mapping, gamma, color lookup table and lastly the color mapping defined in the [Camera].
+///
+/// Render the scene into the intermediate render target with a camera where the tone and color mapping is disabled (see [Camera::disable_tone_and_color_mapping])
+/// and then apply this effect with a camera with the color mapping enabled.
+///
+#[derive(Clone)]
+pub struct ColorGradingEffect {
+    /// The tone mapping applied to the HDR color.
+    pub tone_mapping: ToneMapping,
+    /// The exposure in stops, ie. the color is multiplied by `2^exposure` before tone mapping. The default is 0.0.
+    /// This is added to the exposure of the [Camera] used when applying this effect.
+    pub exposure: f32,
+    /// A gamma value applied to the tone mapped color as `color^(1/gamma)`. The default is 1.0 which leaves the color unchanged.
+    pub gamma: f32,
+    /// An optional 3D color lookup table applied to the tone mapped color.
+    pub lut: Option<ColorLut>,
+    /// The amount that the color lookup table is applied, 0.0 means no change and 1.0 means that the lookup table is applied fully.
+    pub lut_strength: f32,
+}
+
+impl Default for ColorGradingEffect {
+    fn default() -> Self {
+        Self {
+            tone_mapping: ToneMapping::default(),
+            exposure: 0.0,
+            gamma: 1.0,
+            lut: None,
+            lut_strength: 1.0,
+        }
+    }
+}
+
+impl Effect for ColorGradingEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn crate::Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply a color grading effect");
+        format!(
+            "{}{}{}{}
+            uniform float exposure;
+            uniform float gamma;
+
+            in vec2 uvs;
+            layout (location = 0) out vec4 outColor;
+
+            void main()
+            {{
+                outColor = sample_color(uvs);
+                outColor.rgb *= exp2(exposure);
+                outColor.rgb = tone_mapping(outColor.rgb);
+                outColor.rgb = pow(max(outColor.rgb, vec3(0.0)), vec3(1.0 / gamma));
+                {}
+                outColor.rgb = color_mapping(outColor.rgb);
+            }}
+            ",
+            color_texture.fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            if self.lut.is_some() {
+                "
+                uniform sampler3D lut;
+                uniform float lutSize;
+                uniform float lutStrength;
+                uniform vec3 lutDomainMin;
+                uniform vec3 lutDomainMax;
+                "
+            } else {
+                ""
+            },
+            if self.lut.is_some() {
+                "
+                vec3 uvw = clamp((outColor.rgb - lutDomainMin) / (lutDomainMax - lutDomainMin), 0.0, 1.0);
+                uvw = uvw * ((lutSize - 1.0) / lutSize) + 0.5 / lutSize;
+                outColor.rgb = mix(outColor.rgb, texture(lut, uvw).rgb, lutStrength);
+                "
+            } else {
+                ""
+            },
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 11
+            | if self.lut.is_some() { 0b1u16 << 7 } else { 0 }
+            | color_texture
+                .expect("Must supply a color texture to apply a color grading effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn crate::Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        self.tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        program.use_uniform("exposure", self.exposure);
+        program.use_uniform("gamma", self.gamma);
+        if let Some(lut) = &self.lut {
+            program.use_texture_3d("lut", &lut.texture);
+            program.use_uniform("lutSize", lut.size() as f32);
+            program.use_uniform("lutStrength", self.lut_strength);
+            program.use_uniform("lutDomainMin", lut.domain_min);
+            program.use_uniform("lutDomainMax", lut.domain_max);
+        }
+        color_texture
+            .expect("Must supply a color texture to apply a color grading effect")
+            .use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            write_mask: WriteMask::COLOR,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::renderer::*;
+
+///
+/// An effect that simulates the depth of field of a camera lens, ie. the parts of the scene closer to or further away from the camera than the focus distance are blurred.
+///
+/// The blur is computed for a thin lens with the given [DepthOfFieldEffect::aperture] from the depth texture and the field of view of the camera,
+/// by gathering samples from a disc with the size of the circle of confusion, which gives round bokeh around bright spots.
+/// Blurry objects in the foreground blur over sharp objects behind them, while sharp objects in the foreground are not affected by the blurry background.
+///
+#[derive(Clone, Debug)]
+pub struct DepthOfFieldEffect {
+    /// The distance from the camera, in world space units along the view direction, which is in focus. The default is 10.0.
+    pub focus_distance: f32,
+    /// The diameter of the lens aperture in world space units, where a larger aperture gives a more shallow depth of field, ie. more blur.
+    /// The default is 0.1.
+    pub aperture: f32,
+    /// The maximum radius of the blur in pixels. The default is 16.0.
+    pub max_blur_radius: f32,
+    /// The number of samples used for the largest blur, where more samples give smoother bokeh at a higher cost. The default is 64.
+    pub samples: u32,
+}
+
+impl Default for DepthOfFieldEffect {
+    fn default() -> Self {
+        Self {
+            focus_distance: 10.0,
+            aperture: 0.1,
+            max_blur_radius: 16.0,
+            samples: 64,
+        }
+    }
+}
+
+impl DepthOfFieldEffect {
+    ///
+    /// Sets the [DepthOfFieldEffect::focus_distance] so the given position is in focus when seen from the given camera,
+    /// for example the position of an object picked in the center of the screen.
+    ///
+    pub fn focus_at(&mut self, camera: &Camera, position: Vec3) {
+        self.focus_distance = (position - camera.position())
+            .dot(camera.view_direction())
+            .max(camera.z_near());
+    }
+
+    ///
+    /// Returns the radius in pixels of the circle of confusion of an object at the given distance from the camera along the view direction,
+    /// ie. the radius of the blur of the object, before it is limited by [DepthOfFieldEffect::max_blur_radius].
+    ///
+    pub fn blur_radius(&self, camera: &Camera, distance: f32) -> f32 {
+        self.aperture.max(0.0) * (distance - self.focus_distance).abs() / distance.max(0.0001)
+            * self.pixels_per_unit(camera)
+    }
+
+    // The radius of the circle of confusion in pixels is the aperture multiplied by this scale and by `|distance - focus| / distance`.
+    fn pixels_per_unit(&self, camera: &Camera) -> f32 {
+        let height = camera.viewport().height as f32;
+        let focus_distance = self.focus_distance.max(0.0001);
+        0.5 * height
+            / match camera.projection_type() {
+                three_d_asset::ProjectionType::Perspective { field_of_view_y } => {
+                    2.0 * focus_distance * (field_of_view_y.0 * 0.5).tan()
+                }
+                three_d_asset::ProjectionType::Orthographic { height } => *height,
+            }
+    }
+}
+
+impl Effect for DepthOfFieldEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            include_str!("../../core/shared.frag"),
+            color_texture
+                .expect("Must supply a color texture to apply a depth of field effect")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a depth of field effect")
+                .fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/depth_of_field_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 12
+            | 0b1u16 << 10
+            | color_texture
+                .expect("Must supply a color texture to apply a depth of field effect")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a depth of field effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply a depth of field effect");
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        color_texture.use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a depth of field effect")
+            .use_uniforms(program);
+        program.use_uniform(
+            "viewProjectionInverse",
+            (camera.projection() * camera.view()).invert().unwrap(),
+        );
+        program.use_uniform("eyePosition", camera.position());
+        program.use_uniform("viewDirection", camera.view_direction());
+        program.use_uniform(
+            "resolution",
+            vec2(color_texture.width() as f32, color_texture.height() as f32),
+        );
+        program.use_uniform("focusDistance", self.focus_distance);
+        program.use_uniform(
+            "cocScale",
+            self.aperture.max(0.0) * self.pixels_per_unit(camera),
+        );
+        let max_blur_radius = self.max_blur_radius.max(0.0);
+        let samples = self.samples.max(1);
+        program.use_uniform("maxBlurRadius", max_blur_radius);
+        program.use_uniform("sampleCount", samples as i32);
+        // The radius increases by radiusStep / radius for each sample, so the squared radius increases by approximately 2 * radiusStep
+        program.use_uniform(
+            "radiusStep",
+            (max_blur_radius * max_blur_radius / (2.0 * samples as f32)).max(0.5),
+        );
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
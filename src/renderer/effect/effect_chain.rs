@@ -0,0 +1,131 @@
+use crate::renderer::*;
+
+///
+/// A sequence of [Effect]s which are applied one after the other, where the output of each effect is the color input of the next,
+/// for example an ambient occlusion, fog and color grading effect applied to a scene rendered into an intermediate render target.
+/// The intermediate results are stored in textures taken from a [RenderTargetPool], so applying the chain each frame does not allocate new textures.
+///
+/// Each camera decides whether the effects are applied (see [Camera::post_effects]), so the same chain can be used when rendering
+/// several views where for example a minimap or UI camera should not be affected by the post effects of the main view.
+/// The tone mapping, exposure and color mapping of the camera are only applied by the last effect.
+///
+pub struct EffectChain {
+    effects: Vec<Box<dyn Effect>>,
+    pool: RenderTargetPool,
+}
+
+impl EffectChain {
+    ///
+    /// Creates a new empty effect chain.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            effects: Vec::new(),
+            pool: RenderTargetPool::new(context),
+        }
+    }
+
+    ///
+    /// Adds an effect to the end of the chain.
+    ///
+    pub fn push(&mut self, effect: impl Effect + 'static) {
+        self.effects.push(Box::new(effect));
+    }
+
+    ///
+    /// Removes and returns the last effect in the chain.
+    ///
+    pub fn pop(&mut self) -> Option<Box<dyn Effect>> {
+        self.effects.pop()
+    }
+
+    ///
+    /// Removes all effects from the chain.
+    ///
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    ///
+    /// Returns the number of effects in the chain.
+    ///
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    ///
+    /// Returns true if the chain contains no effects.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    ///
+    /// Applies the effects in the chain to the given color and depth texture, which must contain the scene rendered with the given camera
+    /// with the tone and color mapping disabled (see [Camera::disable_tone_and_color_mapping]), and writes the result into the viewport of the camera in the given target.
+    /// If the chain is empty or [Camera::post_effects] is disabled, the color texture is copied to the target with the tone and color mapping of the camera
+    /// applied, see [ScreenEffect].
+    ///
+    pub fn apply(
+        &self,
+        target: &RenderTarget,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: ColorTexture,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        if self.effects.is_empty() || !camera.post_effects {
+            target.apply_screen_effect(
+                &ScreenEffect::default(),
+                camera,
+                lights,
+                Some(color_texture),
+                depth_texture,
+            );
+            return;
+        }
+        let viewport = camera.viewport();
+        let mut intermediate_camera = camera.clone();
+        intermediate_camera.disable_tone_and_color_mapping();
+        intermediate_camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        let new_texture = || {
+            self.pool.color_texture::<[f16; 4]>(
+                viewport.width,
+                viewport.height,
+                Interpolation::Linear,
+                Interpolation::Linear,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+
+        let (last, rest) = self.effects.split_last().unwrap();
+        let mut input: Option<PooledTexture2D> = None;
+        for effect in rest {
+            let mut output = new_texture();
+            let color = input
+                .as_deref()
+                .map(ColorTexture::Single)
+                .unwrap_or(color_texture);
+            output
+                .as_color_target(None)
+                .clear(ClearState::color(0.0, 0.0, 0.0, 1.0))
+                .apply_screen_effect(
+                    effect.as_ref(),
+                    &intermediate_camera,
+                    lights,
+                    Some(color),
+                    depth_texture,
+                );
+            input = Some(output);
+        }
+        let color = input
+            .as_deref()
+            .map(ColorTexture::Single)
+            .unwrap_or(color_texture);
+        target.apply_screen_effect(last.as_ref(), camera, lights, Some(color), depth_texture);
+        drop(input);
+        self.pool.end_frame();
+    }
+}
@@ -2,12 +2,20 @@ use crate::renderer::*;
 
 ///
 /// An effect that simulates fog, ie. the area where it is applied gets hazy when objects are far away.
+/// Contrary to the [Camera::fog], which is applied by the materials, this effect applies the fog to an already rendered scene using the depth texture,
+/// so it is applied to everything in the scene and can be animated with noise.
+/// The background, where nothing is rendered, is fogged as if it was 100 units away.
 ///
 #[derive(Clone, Debug)]
 pub struct FogEffect {
+    /// The fog, where the distance fog falloff and the height fog are applied in the same way as when applied by the materials.
+    /// If specified, [FogEffect::color] and [FogEffect::density] are ignored.
+    pub fog: Option<Fog>,
     /// The color of the fog.
+    #[deprecated(note = "use `fog` with `Fog::color` instead")]
     pub color: Srgba,
-    /// The density of the fog.
+    /// The density of the fog, which is applied as a [FogFalloff::ExponentialSquared] falloff.
+    #[deprecated(note = "use `fog` with `FogFalloff::ExponentialSquared` instead")]
     pub density: f32,
     /// Determines the variation on the density as a function of time.
     pub animation: f32,
@@ -16,8 +24,10 @@ pub struct FogEffect {
 }
 
 impl Default for FogEffect {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
+            fog: None,
             color: Srgba::WHITE,
             density: 0.2,
             animation: 1.0,
@@ -26,6 +36,19 @@ impl Default for FogEffect {
     }
 }
 
+impl FogEffect {
+    #[allow(deprecated)]
+    fn fog(&self) -> Fog {
+        self.fog.unwrap_or(Fog {
+            color: self.color,
+            falloff: FogFalloff::ExponentialSquared {
+                density: self.density,
+            },
+            ..Default::default()
+        })
+    }
+}
+
 impl Effect for FogEffect {
     fn fragment_shader_source(
         &self,
@@ -34,7 +57,7 @@ impl Effect for FogEffect {
         depth_texture: Option<DepthTexture>,
     ) -> String {
         format!(
-            "{}\n{}\n{}\n{}\n{}\n{}",
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
             include_str!("../../core/shared.frag"),
             color_texture
                 .expect("Must supply a depth texture to apply a fog effect")
@@ -44,6 +67,7 @@ impl Effect for FogEffect {
                 .fragment_shader_source(),
             ToneMapping::fragment_shader_source(),
             ColorMapping::fragment_shader_source(),
+            Fog::fragment_shader_source(),
             include_str!("shaders/fog_effect.frag")
         )
     }
@@ -75,8 +99,12 @@ impl Effect for FogEffect {
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) {
-        camera.tone_mapping.use_uniforms(program);
-        camera.color_mapping.use_uniforms(program);
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
         color_texture
             .expect("Must supply a color texture to apply a fog effect")
             .use_uniforms(program);
@@ -87,11 +115,12 @@ impl Effect for FogEffect {
             "viewProjectionInverse",
             (camera.projection() * camera.view()).invert().unwrap(),
         );
-        program.use_uniform("fogColor", Vec4::from(self.color));
-        program.use_uniform("fogDensity", self.density);
+        let fog = self.fog();
+        fog.use_uniforms(program, *camera.position());
+        program.use_uniform("fogEffectColor", Vec4::from(fog.color));
+        program.use_uniform("eyePosition", camera.position());
         program.use_uniform("animation", self.animation);
         program.use_uniform("time", 0.001 * self.time);
-        program.use_uniform("eyePosition", camera.position());
     }
 
     fn render_states(&self) -> RenderStates {
@@ -3,6 +3,7 @@ use crate::renderer::*;
 ///
 /// Renders a full screen quad with the content of the color and/or depth textures.
 /// The difference from [CopyEffect] is that this effect also applies any mapping set in the [Camera].
+/// This is therefore the way to present a linear HDR texture or a texture in [ColorSpace::Srgb], which are both sampled as linear colors, on the screen.
 ///
 #[derive(Clone, Debug, Default)]
 pub struct ScreenEffect {
@@ -77,8 +78,12 @@ impl Effect for ScreenEffect {
         depth_texture: Option<DepthTexture>,
     ) {
         if let Some(color_texture) = color_texture {
-            camera.tone_mapping.use_uniforms(program);
-            camera.color_mapping.use_uniforms(program);
+            camera
+                .tone_mapping
+                .use_uniforms_with_exposure(program, camera.exposure);
+            camera
+                .color_mapping
+                .use_uniforms_with_gamma(program, camera.gamma);
             color_texture.use_uniforms(program);
         }
         if let Some(depth_texture) = depth_texture {
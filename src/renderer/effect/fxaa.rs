@@ -3,9 +3,28 @@ use crate::renderer::*;
 ///
 /// A simple anti-aliasing approach which smooths otherwise jagged edges (for example lines) but also
 /// smooths the rest of the image.
+/// Use this instead of a [RenderTargetMultisample] when multisampling is not available (see [Context::max_samples]),
+/// for example when rendering into a floating point render target on web.
 ///
-#[derive(Clone, Default, Debug)]
-pub struct FxaaEffect {}
+#[derive(Clone, Debug)]
+pub struct FxaaEffect {
+    /// The maximum length, in pixels, of the blur along an edge. Higher values gives smoother long edges at the cost of blurrier details.
+    pub span_max: f32,
+    /// The amount that the edge direction is reduced relative to the luminance, higher values gives less blur.
+    pub reduce_multiplier: f32,
+    /// The minimum reduction of the edge direction which avoids blurring areas with very low contrast.
+    pub reduce_min: f32,
+}
+
+impl Default for FxaaEffect {
+    fn default() -> Self {
+        Self {
+            span_max: 8.0,
+            reduce_multiplier: 1.0 / 8.0,
+            reduce_min: 1.0 / 128.0,
+        }
+    }
+}
 
 impl Effect for FxaaEffect {
     fn fragment_shader_source(
@@ -50,6 +69,9 @@ impl Effect for FxaaEffect {
         let h = color_texture.height();
         color_texture.use_uniforms(program);
         program.use_uniform("resolution", vec2(w as f32, h as f32));
+        program.use_uniform("spanMax", self.span_max);
+        program.use_uniform("reduceMul", self.reduce_multiplier);
+        program.use_uniform("reduceMin", self.reduce_min);
     }
 
     fn render_states(&self) -> RenderStates {
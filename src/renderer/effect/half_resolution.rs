@@ -0,0 +1,157 @@
+use crate::renderer::*;
+
+///
+/// Renders expensive transparent objects, for example smoke or other particle effects, at half the resolution
+/// and composites the result on top of the full resolution scene using a depth aware upsampling that keeps the edges of opaque objects sharp.
+///
+/// First render the opaque part of the scene into a color and depth texture, then call [HalfResolutionPass::render] with the transparent objects and
+/// finally apply this as an [Effect] with the full resolution color and depth textures, for example using [RenderTarget::apply_screen_effect].
+///
+/// **Note:** The objects are rendered twice, each time at a quarter of the number of pixels, to find both the color and the transmittance
+/// regardless of the blend mode of the objects.
+///
+pub struct HalfResolutionPass {
+    color_over_black: Texture2D,
+    color_over_white: Texture2D,
+    depth: DepthTexture2D,
+}
+
+impl HalfResolutionPass {
+    ///
+    /// Creates a new half resolution pass for a scene rendered with the given full resolution width and height.
+    ///
+    pub fn new(context: &Context, width: u32, height: u32) -> Self {
+        let width = width.div_ceil(2).max(1);
+        let height = height.div_ceil(2).max(1);
+        let new_color_texture = || {
+            Texture2D::new_empty::<[f16; 4]>(
+                context,
+                width,
+                height,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        Self {
+            color_over_black: new_color_texture(),
+            color_over_white: new_color_texture(),
+            depth: DepthTexture2D::new::<f32>(
+                context,
+                width,
+                height,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+        }
+    }
+
+    ///
+    /// Renders the objects at half resolution. The given depth texture must contain the depth of the opaque part of the scene at full resolution
+    /// and is used to hide the parts of the objects that are behind the opaque objects.
+    ///
+    pub fn render<T: Object>(
+        &mut self,
+        camera: &Camera,
+        objects: impl IntoIterator<Item = T> + Clone,
+        lights: &[&dyn Light],
+        depth_texture: DepthTexture,
+    ) {
+        let mut half_camera = camera.clone();
+        half_camera.set_viewport(Viewport::new_at_origo(
+            self.depth.width(),
+            self.depth.height(),
+        ));
+        for (texture, background) in [
+            (&mut self.color_over_black, 0.0),
+            (&mut self.color_over_white, 1.0),
+        ] {
+            // The depth is copied before each pass since the objects might write to the depth
+            self.depth
+                .as_depth_target()
+                .clear(ClearState::depth(1.0))
+                .apply_screen_effect(
+                    &CopyEffect {
+                        write_mask: WriteMask::DEPTH,
+                        ..Default::default()
+                    },
+                    &half_camera,
+                    &[],
+                    None,
+                    Some(depth_texture),
+                );
+            RenderTarget::new(texture.as_color_target(None), self.depth.as_depth_target())
+                .clear(ClearState::color(background, background, background, 1.0))
+                .render(&half_camera, objects.clone(), lights);
+        }
+    }
+}
+
+impl Effect for HalfResolutionPass {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}",
+            color_texture
+                .expect("Must supply a color texture to apply a half resolution pass")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a half resolution pass")
+                .fragment_shader_source(),
+            include_str!("shaders/half_resolution_composite.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 11
+            | 0b100u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to apply a half resolution pass")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a half resolution pass")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        color_texture
+            .expect("Must supply a color texture to apply a half resolution pass")
+            .use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a half resolution pass")
+            .use_uniforms(program);
+        program.use_texture("colorOverBlack", &self.color_over_black);
+        program.use_texture("colorOverWhite", &self.color_over_white);
+        program.use_depth_texture("halfResolutionDepth", &self.depth);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -20,6 +20,7 @@ impl Effect for LightingPassEffect {
         fragment_shader.push_str(&depth_texture.unwrap().fragment_shader_source());
         fragment_shader.push_str(ToneMapping::fragment_shader_source());
         fragment_shader.push_str(ColorMapping::fragment_shader_source());
+        fragment_shader.push_str(Fog::fragment_shader_source());
         fragment_shader.push_str(include_str!("shaders/deferred_lighting.frag"));
         fragment_shader
     }
@@ -43,8 +44,13 @@ impl Effect for LightingPassEffect {
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) {
-        camera.tone_mapping.use_uniforms(program);
-        camera.color_mapping.use_uniforms(program);
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        camera.fog.use_uniforms(program, *camera.position());
         color_texture.unwrap().use_uniforms(program);
         depth_texture.unwrap().use_uniforms(program);
         program.use_uniform_if_required("cameraPosition", camera.position());
@@ -0,0 +1,316 @@
+use crate::core::*;
+use crate::renderer::*;
+
+/// The number of bins in the luminance histogram computed by a [LuminanceMeter].
+pub const LUMINANCE_HISTOGRAM_BINS: usize = 64;
+
+/// The maximum number of pixels in each direction which are sampled when computing the luminance histogram.
+const MAX_SAMPLES_PER_SIDE: u32 = 128;
+
+///
+/// Measures the luminance of a rendered scene, for example to automatically adjust the exposure to the brightness of the scene (auto-exposure).
+///
+/// The luminance histogram and the resulting exposure are computed and stored entirely on the GPU, so measuring each frame does not stall the rendering
+/// while waiting for the result to be read back to the CPU.
+/// The exposure is applied by using the meter as an [Effect], for example as the last effect of an [EffectChain], which multiplies the color by `2^exposure`
+/// before applying the tone and color mapping of the camera. Use [LuminanceMeter::read_exposure] or [LuminanceMeter::read_histogram] only for debugging,
+/// since these read the result back to the CPU.
+///
+/// Each frame, render the scene into a color texture with the tone and color mapping disabled, call [LuminanceMeter::measure] with the texture
+/// and then apply the meter as an effect to the same texture.
+///
+/// **Note:** Computing the histogram requires blending into a 32 bit float texture, which on web requires the `EXT_float_blend` extension.
+///
+pub struct LuminanceMeter {
+    context: Context,
+    /// The base 2 logarithm of the lowest luminance in the histogram, all lower luminances are counted in the first bin. The default is -10.0.
+    pub min_log_luminance: f32,
+    /// The base 2 logarithm of the highest luminance in the histogram, all higher luminances are counted in the last bin. The default is 10.0.
+    pub max_log_luminance: f32,
+    /// The fraction of the darkest pixels which are ignored when computing the average luminance. The default is 0.1.
+    pub low_percentile: f32,
+    /// The fraction of the pixels, starting from the darkest, which are included when computing the average luminance, ie. the brightest pixels above this fraction are ignored.
+    /// The default is 0.9.
+    pub high_percentile: f32,
+    /// The exposure in stops which is added to the exposure computed from the average luminance, so a positive value gives a brighter image. The default is 0.0.
+    pub exposure_compensation: f32,
+    /// The lowest exposure in stops. The default is -10.0.
+    pub min_exposure: f32,
+    /// The highest exposure in stops. The default is 10.0.
+    pub max_exposure: f32,
+    /// How fast the exposure adapts to a change in luminance, where a higher value is faster.
+    /// The difference between the current and the target exposure is reduced by a factor `e^-(speed * seconds)`. The default is 1.5.
+    pub adaptation_speed: f32,
+    histogram: Texture2D,
+    exposure: [Texture2D; 2],
+    current: usize,
+    measured: bool,
+    histogram_program: Option<(u16, Program)>,
+    exposure_program: Program,
+}
+
+impl LuminanceMeter {
+    ///
+    /// Creates a new luminance meter.
+    ///
+    pub fn new(context: &Context) -> Self {
+        let exposure_texture = || {
+            Texture2D::new_empty::<[f32; 2]>(
+                context,
+                1,
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        Self {
+            context: context.clone(),
+            min_log_luminance: -10.0,
+            max_log_luminance: 10.0,
+            low_percentile: 0.1,
+            high_percentile: 0.9,
+            exposure_compensation: 0.0,
+            min_exposure: -10.0,
+            max_exposure: 10.0,
+            adaptation_speed: 1.5,
+            histogram: Texture2D::new_empty::<f32>(
+                context,
+                LUMINANCE_HISTOGRAM_BINS as u32,
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+            exposure: [exposure_texture(), exposure_texture()],
+            current: 0,
+            measured: false,
+            histogram_program: None,
+            exposure_program: Program::from_source(
+                context,
+                full_screen_vertex_shader_source(),
+                include_str!("shaders/luminance_exposure.frag"),
+            )
+            .expect("Failed compiling shader"),
+        }
+    }
+
+    ///
+    /// Computes the luminance histogram of the given color texture, which should contain the scene rendered with the tone and color mapping disabled,
+    /// and adapts the exposure towards the exposure which maps the average luminance to middle grey.
+    /// The elapsed time in milliseconds since the last measurement, for example [FrameInput::elapsed_time](crate::FrameInput::elapsed_time),
+    /// determines how much the exposure adapts, see [LuminanceMeter::adaptation_speed].
+    /// The first measurement, and the first after calling [LuminanceMeter::reset], sets the exposure without adaptation.
+    ///
+    pub fn measure(&mut self, color_texture: ColorTexture, elapsed_time: f64) {
+        let id = color_texture.id();
+        if self
+            .histogram_program
+            .as_ref()
+            .map(|(program_id, _)| *program_id != id)
+            .unwrap_or(true)
+        {
+            let program = Program::from_source(
+                &self.context,
+                &format!(
+                    "{}{}",
+                    color_texture.fragment_shader_source(),
+                    include_str!("shaders/luminance_histogram.vert")
+                ),
+                include_str!("shaders/luminance_histogram.frag"),
+            )
+            .expect("Failed compiling shader");
+            self.histogram_program = Some((id, program));
+        }
+
+        let min_log_luminance = self.min_log_luminance;
+        let log_luminance_range = (self.max_log_luminance - min_log_luminance).max(0.001);
+        let samples_x = color_texture.width().clamp(1, MAX_SAMPLES_PER_SIDE);
+        let samples_y = color_texture.height().clamp(1, MAX_SAMPLES_PER_SIDE);
+        let sample_count = samples_x * samples_y;
+        let histogram_program = &self.histogram_program.as_ref().unwrap().1;
+        self.histogram
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .write::<RendererError>(|| {
+                color_texture.use_uniforms(histogram_program);
+                histogram_program
+                    .use_uniform("sampleCount", vec2(samples_x as i32, samples_y as i32));
+                histogram_program.use_uniform("minLogLuminance", min_log_luminance);
+                histogram_program.use_uniform("logLuminanceRange", log_luminance_range);
+                histogram_program.use_uniform("binCount", LUMINANCE_HISTOGRAM_BINS as f32);
+                histogram_program.use_uniform("sampleWeight", 1.0 / sample_count as f32);
+                draw_without_attributes(
+                    &self.context,
+                    histogram_program,
+                    RenderStates {
+                        write_mask: WriteMask::COLOR,
+                        depth_test: DepthTest::Always,
+                        blend: Blend::ADD,
+                        cull: Cull::None,
+                    },
+                    Viewport::new_at_origo(LUMINANCE_HISTOGRAM_BINS as u32, 1),
+                    3 * sample_count,
+                );
+                Ok(())
+            })
+            .unwrap();
+
+        let adaptation = if self.measured {
+            (-self.adaptation_speed.max(0.0) * elapsed_time as f32 * 0.001).exp()
+        } else {
+            0.0
+        };
+        let (first, second) = self.exposure.split_at_mut(1);
+        let (previous, target) = if self.current == 0 {
+            (&first[0], &mut second[0])
+        } else {
+            (&second[0], &mut first[0])
+        };
+        let program = &self.exposure_program;
+        target
+            .as_color_target(None)
+            .write::<RendererError>(|| {
+                program.use_texture("histogram", &self.histogram);
+                program.use_texture("previousExposure", previous);
+                program.use_uniform("binCount", LUMINANCE_HISTOGRAM_BINS as i32);
+                program.use_uniform("minLogLuminance", min_log_luminance);
+                program.use_uniform("logLuminanceRange", log_luminance_range);
+                program.use_uniform("lowPercentile", self.low_percentile.clamp(0.0, 1.0));
+                program.use_uniform(
+                    "highPercentile",
+                    self.high_percentile
+                        .clamp(self.low_percentile.clamp(0.0, 1.0), 1.0),
+                );
+                program.use_uniform("exposureCompensation", self.exposure_compensation);
+                program.use_uniform("minExposure", self.min_exposure);
+                program.use_uniform("maxExposure", self.max_exposure.max(self.min_exposure));
+                program.use_uniform("adaptation", adaptation);
+                full_screen_draw(
+                    &self.context,
+                    program,
+                    RenderStates {
+                        write_mask: WriteMask::COLOR,
+                        depth_test: DepthTest::Always,
+                        blend: Blend::Disabled,
+                        cull: Cull::None,
+                    },
+                    Viewport::new_at_origo(1, 1),
+                );
+                Ok(())
+            })
+            .unwrap();
+        self.current = 1 - self.current;
+        self.measured = true;
+    }
+
+    ///
+    /// Discards the adapted exposure, so the next measurement sets the exposure without adaptation, for example after a change of scene.
+    ///
+    pub fn reset(&mut self) {
+        self.measured = false;
+    }
+
+    ///
+    /// Returns the luminance histogram computed by the last measurement, where each of the [LUMINANCE_HISTOGRAM_BINS] texels contains the fraction of the
+    /// sampled pixels with a base 2 logarithm of the luminance in the range of the bin, the bins being evenly distributed between
+    /// [LuminanceMeter::min_log_luminance] and [LuminanceMeter::max_log_luminance].
+    ///
+    pub fn histogram_texture(&self) -> &Texture2D {
+        &self.histogram
+    }
+
+    ///
+    /// Returns a 1x1 texture where the red channel contains the exposure in stops and the green channel contains the base 2 logarithm of the average luminance
+    /// computed by the last measurement.
+    ///
+    pub fn exposure_texture(&self) -> &Texture2D {
+        &self.exposure[self.current]
+    }
+
+    ///
+    /// Reads the luminance histogram back to the CPU, see [LuminanceMeter::histogram_texture].
+    /// This waits for the GPU to finish the measurement, so it should only be used for debugging or tools.
+    ///
+    pub fn read_histogram(&self) -> Vec<f32> {
+        self.histogram.as_shared_color_target(None).read()
+    }
+
+    ///
+    /// Reads the exposure in stops back to the CPU, see [LuminanceMeter::exposure_texture].
+    /// This waits for the GPU to finish the measurement, so it should only be used for debugging or tools.
+    ///
+    pub fn read_exposure(&self) -> f32 {
+        self.exposure_texture()
+            .as_shared_color_target(None)
+            .read::<[f32; 2]>()[0][0]
+    }
+}
+
+impl Effect for LuminanceMeter {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}\n{}",
+            color_texture
+                .expect("Must supply a color texture to apply a luminance meter")
+                .fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/auto_exposure_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, _depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 11
+            | 0b111u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to apply a luminance meter")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        color_texture
+            .expect("Must supply a color texture to apply a luminance meter")
+            .use_uniforms(program);
+        program.use_texture("exposureMap", self.exposure_texture());
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
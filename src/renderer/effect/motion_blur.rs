@@ -0,0 +1,157 @@
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// An effect that blurs the rendered scene along the motion of each pixel since the previous frame, which makes motion look smoother,
+/// especially at lower frame rates.
+///
+/// The motion caused by the camera is calculated from the depth texture and the [MotionBlurEffect::previous_view_projection] of the camera,
+/// so call [MotionBlurEffect::update_previous_camera] each frame after applying the effect.
+/// To also blur moving objects, set the [MotionBlurEffect::velocity_texture] to the motion vectors of the scene,
+/// for example rendered using [MotionVectors::render_meshes] and shared using [MotionVectors::texture].
+/// Pixels that are not covered by any geometry in the velocity texture, for example the background, fall back to the camera motion.
+///
+#[derive(Clone)]
+pub struct MotionBlurEffect {
+    /// A texture with the same size as the color texture containing the motion in pixels since the previous frame in the red and green channels
+    /// and an alpha value of zero where there is no geometry, as rendered by [MotionVectors].
+    pub velocity_texture: Option<Arc<Texture2D>>,
+    /// The view projection matrix of the camera in the previous frame, see [MotionBlurEffect::update_previous_camera].
+    /// If this is not specified, there is no camera motion.
+    pub previous_view_projection: Option<Mat4>,
+    /// The fraction of the time between two frames that the shutter of the camera is open, where 1.0 blurs along the entire motion since the previous frame.
+    /// The default is 0.5, which corresponds to a 180° shutter.
+    pub shutter: f32,
+    /// The maximum length of the blur in pixels. The default is 32.0.
+    pub max_blur_length: f32,
+    /// The number of samples along the motion of each pixel. The default is 16.
+    pub samples: u32,
+}
+
+impl Default for MotionBlurEffect {
+    fn default() -> Self {
+        Self {
+            velocity_texture: None,
+            previous_view_projection: None,
+            shutter: 0.5,
+            max_blur_length: 32.0,
+            samples: 16,
+        }
+    }
+}
+
+impl MotionBlurEffect {
+    ///
+    /// Stores the view projection of the given camera as the [MotionBlurEffect::previous_view_projection],
+    /// which should be called each frame after the effect is applied.
+    ///
+    pub fn update_previous_camera(&mut self, camera: &Camera) {
+        self.previous_view_projection = Some(camera.projection_without_jitter() * camera.view());
+    }
+
+    ///
+    /// Discards the previous camera, for example when the camera is moved to a completely different location, so there is no camera motion in the next frame.
+    ///
+    pub fn reset(&mut self) {
+        self.previous_view_projection = None;
+    }
+}
+
+impl Effect for MotionBlurEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}{}\n{}\n{}\n{}\n{}\n{}",
+            if self.velocity_texture.is_some() {
+                "#define USE_VELOCITY_TEXTURE\n"
+            } else {
+                ""
+            },
+            include_str!("../../core/shared.frag"),
+            color_texture
+                .expect("Must supply a color texture to apply a motion blur effect")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a motion blur effect")
+                .fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/motion_blur_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        let mut id = 0b1u16 << 14
+            | 0b1u16 << 13
+            | 0b1u16 << 10
+            | color_texture
+                .expect("Must supply a color texture to apply a motion blur effect")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a motion blur effect")
+                .id();
+        if self.velocity_texture.is_some() {
+            id |= 0b1u16 << 7;
+        }
+        id
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply a motion blur effect");
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        color_texture.use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a motion blur effect")
+            .use_uniforms(program);
+        let view_projection = camera.projection_without_jitter() * camera.view();
+        program.use_uniform("viewProjectionInverse", view_projection.invert().unwrap());
+        program.use_uniform(
+            "previousViewProjection",
+            self.previous_view_projection.unwrap_or(view_projection),
+        );
+        program.use_uniform("eyePosition", camera.position());
+        program.use_uniform("viewDirection", camera.view_direction());
+        program.use_uniform(
+            "resolution",
+            vec2(color_texture.width() as f32, color_texture.height() as f32),
+        );
+        program.use_uniform("shutter", self.shutter.max(0.0));
+        program.use_uniform("maxBlurLength", self.max_blur_length.max(0.0));
+        program.use_uniform("sampleCount", self.samples.max(1) as i32);
+        if let Some(ref texture) = self.velocity_texture {
+            program.use_texture("velocityTexture", texture);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
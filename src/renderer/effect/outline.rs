@@ -0,0 +1,148 @@
+use crate::renderer::*;
+
+///
+/// Draws a colored outline around the silhouette of a set of objects, for example to highlight the selected objects in an editor.
+///
+/// The silhouette of the objects are rendered into a mask which is then grown by the thickness of the outline, so the outline is visible
+/// even if the objects are hidden behind other objects.
+/// Render the scene as usual and then call [OutlineEffect::render] with the selected objects and the same render target and camera.
+///
+pub struct OutlineEffect {
+    context: Context,
+    mask: Option<Texture2D>,
+}
+
+impl OutlineEffect {
+    ///
+    /// Creates a new outline effect.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            mask: None,
+        }
+    }
+
+    ///
+    /// Draws an outline with the given color and thickness, in physical pixels, around the silhouette of the given objects on top of the content of the render target.
+    ///
+    pub fn render(
+        &mut self,
+        target: &RenderTarget,
+        camera: &Camera,
+        objects: impl IntoIterator<Item = impl Geometry>,
+        color: Srgba,
+        thickness: f32,
+    ) {
+        let viewport = camera.viewport();
+        if self
+            .mask
+            .as_ref()
+            .map(|m| m.width() != viewport.width || m.height() != viewport.height)
+            .unwrap_or(true)
+        {
+            self.mask = Some(Texture2D::new_empty::<u8>(
+                &self.context,
+                viewport.width,
+                viewport.height,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ));
+        }
+        let mask = self.mask.as_mut().unwrap();
+        let mut mask_camera = camera.clone();
+        mask_camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        mask.as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .render_with_material(
+                &ColorMaterial {
+                    color: Srgba::WHITE,
+                    render_states: RenderStates {
+                        depth_test: DepthTest::Always,
+                        cull: Cull::None,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                &mask_camera,
+                objects,
+                &[],
+            );
+        target.apply_screen_effect(
+            &OutlineCompositeEffect {
+                mask,
+                color,
+                thickness,
+            },
+            camera,
+            &[],
+            None,
+            None,
+        );
+    }
+}
+
+struct OutlineCompositeEffect<'a> {
+    mask: &'a Texture2D,
+    color: Srgba,
+    thickness: f32,
+}
+
+impl Effect for OutlineCompositeEffect<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}",
+            include_str!("../../core/shared.frag"),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/outline_effect.frag")
+        )
+    }
+
+    fn id(
+        &self,
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> u16 {
+        0b1u16 << 14 | 0b1u16 << 11 | 0b101u16 << 7
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        program.use_texture("mask", self.mask);
+        program.use_uniform("outlineColor", self.color.to_linear_srgb());
+        program.use_uniform("thickness", self.thickness.max(0.0));
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::Back,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+
+uniform sampler2D exposureMap;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 color = sample_color(uvs);
+    color.rgb *= exp2(texelFetch(exposureMap, ivec2(0, 0), 0).r);
+    outColor = vec4(color_mapping(tone_mapping(color.rgb)), color.a);
+}
@@ -62,6 +62,7 @@ void main()
     }
     else { // None
         outColor.rgb = total_emissive + calculate_lighting(cameraPosition, surface_color.rgb, position, normal, metallic_factor, roughness_factor, occlusion);
+        outColor.rgb = apply_fog(outColor.rgb, position);
         outColor.rgb = tone_mapping(outColor.rgb);
         outColor.rgb = color_mapping(outColor.rgb);
         outColor.a = surface_color.a;
@@ -0,0 +1,59 @@
+
+uniform mat4 viewProjectionInverse;
+uniform vec3 eyePosition;
+uniform vec3 viewDirection;
+uniform vec2 resolution;
+uniform float focusDistance;
+uniform float cocScale;
+uniform float maxBlurRadius;
+uniform float radiusStep;
+uniform int sampleCount;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+const float GOLDEN_ANGLE = 2.39996323;
+
+float view_depth(vec2 uv) {
+    vec3 position = world_pos_from_depth(viewProjectionInverse, sample_depth(uv), uv);
+    return max(dot(position - eyePosition, viewDirection), 0.0001);
+}
+
+float blur_radius(float depth) {
+    return min(cocScale * abs(depth - focusDistance) / depth, maxBlurRadius);
+}
+
+// Bokeh gather based on "Bokeh depth of field in a single pass" by Dennis Gustafsson
+void main()
+{
+    vec4 center = sample_color(uvs);
+    float depth = sample_depth(uvs);
+    float center_depth = view_depth(uvs);
+    float center_radius = blur_radius(center_depth);
+
+    vec3 color = center.rgb;
+    float total = 1.0;
+    float radius = radiusStep;
+    float angle = 0.0;
+    for (int i = 0; i < sampleCount && radius < maxBlurRadius; i++) {
+        vec2 uv = uvs + vec2(cos(angle), sin(angle)) * radius / resolution;
+        vec3 c = sample_color(uv).rgb;
+        float d = view_depth(uv);
+        float r = blur_radius(d);
+        if (d > center_depth) {
+            // Blurry background should not blur over sharp foreground
+            r = clamp(r, 0.0, center_radius * 2.0);
+        }
+        float m = smoothstep(radius - 0.5, radius + 0.5, r);
+        color += mix(color / total, c, m);
+        total += 1.0;
+        radius += radiusStep / radius;
+        angle += GOLDEN_ANGLE;
+    }
+
+    outColor = vec4(color / total, center.a);
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    gl_FragDepth = depth;
+}
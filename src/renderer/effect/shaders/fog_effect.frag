@@ -2,8 +2,7 @@
 uniform mat4 viewProjectionInverse;
 
 uniform float time;
-uniform float fogDensity;
-uniform vec4 fogColor;
+uniform vec4 fogEffectColor;
 uniform float animation;
 uniform vec3 eyePosition;
 
@@ -132,10 +131,10 @@ void main()
     vec3 pos = world_pos_from_depth(viewProjectionInverse, depth, uvs);
 
     // Distance
-    float dist = depth < 0.999f ? distance(pos, eyePosition) : 100.f;
-
-    float x = dist * fogDensity;
-    float factor = 1. - 1. / exp(x * x);
+    if (depth >= 0.999f) {
+        pos = eyePosition + 100.f * normalize(pos - eyePosition);
+    }
+    float factor = fog_factor(pos);
 
     // Noise
     float n = snoise(pos);
@@ -143,7 +142,7 @@ void main()
     factor = clamp(factor, 0., 1.);
 
     // Output
-    outColor = mix(color, fogColor, factor);
+    outColor = mix(color, fogEffectColor, factor);
     outColor.rgb = tone_mapping(outColor.rgb);
     outColor.rgb = color_mapping(outColor.rgb);
     gl_FragDepth = depth;
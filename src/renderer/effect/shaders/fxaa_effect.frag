@@ -1,5 +1,8 @@
 
 uniform vec2 resolution;
+uniform float spanMax;
+uniform float reduceMul;
+uniform float reduceMin;
 
 in vec2 uvs;
 
@@ -41,15 +44,9 @@ THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT
 OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 */
 
-#ifndef FXAA_REDUCE_MIN
-    #define FXAA_REDUCE_MIN   (1.0/ 128.0)
-#endif
-#ifndef FXAA_REDUCE_MUL
-    #define FXAA_REDUCE_MUL   (1.0 / 8.0)
-#endif
-#ifndef FXAA_SPAN_MAX
-    #define FXAA_SPAN_MAX     8.0
-#endif
+#define FXAA_REDUCE_MIN reduceMin
+#define FXAA_REDUCE_MUL reduceMul
+#define FXAA_SPAN_MAX spanMax
 
 //optimized version for mobile, where dependent
 //texture reads can be a bottleneck
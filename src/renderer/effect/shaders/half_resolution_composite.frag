@@ -0,0 +1,39 @@
+
+uniform sampler2D colorOverBlack;
+uniform sampler2D colorOverWhite;
+uniform sampler2D halfResolutionDepth;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 sceneColor = sample_color(uvs);
+    float depth = sample_depth(uvs);
+
+    // Depth aware upsampling: weigh the four closest half resolution texels by how similar their depth is to the full resolution depth
+    vec2 size = vec2(textureSize(halfResolutionDepth, 0));
+    vec2 position = uvs * size - 0.5;
+    vec2 base = floor(position);
+    vec2 f = position - base;
+    vec3 color = vec3(0.0);
+    vec3 transmittance = vec3(0.0);
+    float weightSum = 0.0;
+    for (int i = 0; i < 4; i++) {
+        vec2 offset = vec2(float(i % 2), float(i / 2));
+        ivec2 texel = ivec2(clamp(base + offset, vec2(0.0), size - 1.0));
+        vec2 bilinear = mix(1.0 - f, f, offset);
+        float halfDepth = texelFetch(halfResolutionDepth, texel, 0).x;
+        float weight = bilinear.x * bilinear.y / (0.0001 + abs(halfDepth - depth));
+        vec3 black = texelFetch(colorOverBlack, texel, 0).rgb;
+        vec3 white = texelFetch(colorOverWhite, texel, 0).rgb;
+        color += weight * black;
+        transmittance += weight * (white - black);
+        weightSum += weight;
+    }
+    color /= weightSum;
+    transmittance = clamp(transmittance / weightSum, 0.0, 1.0);
+
+    outColor = vec4(color + transmittance * sceneColor.rgb, sceneColor.a);
+}
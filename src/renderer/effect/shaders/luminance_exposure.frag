@@ -0,0 +1,37 @@
+
+uniform sampler2D histogram;
+uniform sampler2D previousExposure;
+uniform int binCount;
+uniform float minLogLuminance;
+uniform float logLuminanceRange;
+uniform float lowPercentile;
+uniform float highPercentile;
+uniform float exposureCompensation;
+uniform float minExposure;
+uniform float maxExposure;
+uniform float adaptation;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // The average of the bins between the low and high percentile, where a bin which is partially included is weighted accordingly
+    float accumulated = 0.0;
+    float weight_sum = 0.0;
+    float log_luminance_sum = 0.0;
+    for (int i = 0; i < binCount; i++) {
+        float value = texelFetch(histogram, ivec2(i, 0), 0).r;
+        float weight = max(0.0, min(accumulated + value, highPercentile) - max(accumulated, lowPercentile));
+        float log_luminance = minLogLuminance + (float(i) + 0.5) / float(binCount) * logLuminanceRange;
+        log_luminance_sum += weight * log_luminance;
+        weight_sum += weight;
+        accumulated += value;
+    }
+    float average_log_luminance = weight_sum > 0.0 ? log_luminance_sum / weight_sum : minLogLuminance;
+
+    // The exposure which maps the average luminance to middle grey
+    float target = clamp(log2(0.18) - average_log_luminance + exposureCompensation, minExposure, maxExposure);
+    float previous = texelFetch(previousExposure, ivec2(0, 0), 0).r;
+    float exposure = mix(target, previous, adaptation);
+    outColor = vec4(exposure, average_log_luminance, 0.0, 1.0);
+}
@@ -0,0 +1,9 @@
+
+uniform float sampleWeight;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    outColor = vec4(sampleWeight, 0.0, 0.0, 1.0);
+}
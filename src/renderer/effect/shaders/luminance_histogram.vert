@@ -0,0 +1,22 @@
+
+uniform ivec2 sampleCount;
+uniform float minLogLuminance;
+uniform float logLuminanceRange;
+uniform float binCount;
+
+void main()
+{
+    int sample_index = gl_VertexID / 3;
+    int corner = gl_VertexID - 3 * sample_index;
+    vec2 uv = (vec2(float(sample_index % sampleCount.x), float(sample_index / sampleCount.x)) + 0.5) / vec2(sampleCount);
+    float luminance = dot(sample_color(uv).rgb, vec3(0.2126, 0.7152, 0.0722));
+
+    // Black pixels end up in the first bin
+    float t = clamp((log2(max(luminance, 1e-9)) - minLogLuminance) / logLuminanceRange, 0.0, 1.0);
+    float bin = min(floor(t * binCount), binCount - 1.0);
+
+    // A small triangle which only covers the center of the pixel of the bin
+    vec2 corners[3] = vec2[3](vec2(0.0, 0.0), vec2(1.0, 0.0), vec2(0.5, 2.0));
+    vec2 pixel = vec2(bin, 0.0) + corners[corner];
+    gl_Position = vec4(2.0 * pixel.x / binCount - 1.0, 2.0 * pixel.y - 1.0, 0.0, 1.0);
+}
@@ -0,0 +1,70 @@
+
+uniform mat4 viewProjectionInverse;
+uniform mat4 previousViewProjection;
+uniform vec3 eyePosition;
+uniform vec3 viewDirection;
+uniform vec2 resolution;
+uniform float shutter;
+uniform float maxBlurLength;
+uniform int sampleCount;
+#ifdef USE_VELOCITY_TEXTURE
+uniform sampler2D velocityTexture;
+#endif
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+// The motion in pixels during the time the shutter is open
+vec2 velocity(vec2 uv, vec3 position) {
+    vec2 v = vec2(0.0);
+#ifdef USE_VELOCITY_TEXTURE
+    vec4 motion = texture(velocityTexture, uv);
+    if (motion.a > 0.0) {
+        v = motion.xy;
+    } else
+#endif
+    {
+        vec4 previous = previousViewProjection * vec4(position, 1.0);
+        if (previous.w > 0.0) {
+            v = 0.5 * (uv * 2.0 - 1.0 - previous.xy / previous.w) * resolution;
+        }
+    }
+    v *= shutter;
+    float l = length(v);
+    return l > maxBlurLength ? v * maxBlurLength / l : v;
+}
+
+void main()
+{
+    vec4 center = sample_color(uvs);
+    float depth = sample_depth(uvs);
+    vec3 center_position = world_pos_from_depth(viewProjectionInverse, depth, uvs);
+    float center_depth = dot(center_position - eyePosition, viewDirection);
+    vec2 v = velocity(uvs, center_position);
+    float l = length(v);
+
+    vec3 color = center.rgb;
+    float total = 1.0;
+    if (l > 0.5) {
+        for (int i = 0; i < sampleCount; i++) {
+            float t = (float(i) + 0.5) / float(sampleCount) - 0.5;
+            vec2 uv = uvs + v * t / resolution;
+            vec3 position = world_pos_from_depth(viewProjectionInverse, sample_depth(uv), uv);
+            float weight = 1.0;
+            if (dot(position - eyePosition, viewDirection) < 0.99 * center_depth) {
+                // A sample in front of the center pixel only blurs over it if the sample itself moves far enough
+                float reach = 0.5 * length(velocity(uv, position));
+                float dist = abs(t) * l;
+                weight = smoothstep(dist - 1.0, dist + 1.0, reach);
+            }
+            color += weight * sample_color(uv).rgb;
+            total += weight;
+        }
+    }
+
+    outColor = vec4(color / total, center.a);
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    gl_FragDepth = depth;
+}
@@ -0,0 +1,39 @@
+
+uniform sampler2D mask;
+uniform vec4 outlineColor;
+uniform float thickness;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    ivec2 size = textureSize(mask, 0);
+    ivec2 center = ivec2(uvs * vec2(size));
+    if (texelFetch(mask, center, 0).r > 0.5) {
+        discard;
+    }
+
+    // Find the distance to the closest pixel inside the silhouette
+    int radius = int(ceil(thickness));
+    float closest = thickness + 1.0;
+    for (int y = -radius; y <= radius; y++) {
+        for (int x = -radius; x <= radius; x++) {
+            ivec2 p = center + ivec2(x, y);
+            if (p.x < 0 || p.y < 0 || p.x >= size.x || p.y >= size.y) {
+                continue;
+            }
+            if (texelFetch(mask, p, 0).r > 0.5) {
+                closest = min(closest, length(vec2(x, y)));
+            }
+        }
+    }
+
+    float alpha = clamp(thickness + 0.5 - closest, 0.0, 1.0);
+    if (alpha <= 0.0) {
+        discard;
+    }
+    outColor.rgb = color_mapping(outlineColor.rgb);
+    outColor.a = outlineColor.a * alpha;
+}
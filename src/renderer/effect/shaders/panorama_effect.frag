@@ -0,0 +1,16 @@
+uniform samplerCube cubeMap;
+uniform mat3 toCubeMap;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float longitude = (2.0 * uvs.x - 1.0) * 3.14159265;
+    float latitude = (uvs.y - 0.5) * 3.14159265;
+    vec3 direction = vec3(cos(latitude) * sin(longitude), sin(latitude), cos(latitude) * cos(longitude));
+    vec4 color = texture(cubeMap, toCubeMap * direction);
+    outColor.rgb = color_mapping(tone_mapping(color.rgb));
+    outColor.a = color.a;
+}
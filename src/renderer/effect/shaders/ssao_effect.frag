@@ -0,0 +1,102 @@
+
+uniform mat4 projection;
+uniform mat4 projectionInverse;
+uniform vec2 resolution;
+uniform float radius;
+uniform float bias;
+uniform float intensity;
+uniform int samples;
+uniform int blur;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+vec3 view_position(vec2 uv) {
+    vec4 position = projectionInverse * vec4(uv * 2.0 - 1.0, sample_depth(uv) * 2.0 - 1.0, 1.0);
+    return position.xyz / position.w;
+}
+
+// Interleaved gradient noise used to rotate the sample kernel per pixel
+float rotation_noise(vec2 pixel) {
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+vec3 view_normal(vec2 uv, vec3 position) {
+    vec2 texel = 1.0 / resolution;
+    vec3 right = view_position(uv + vec2(texel.x, 0.0)) - position;
+    vec3 left = position - view_position(uv - vec2(texel.x, 0.0));
+    vec3 up = view_position(uv + vec2(0.0, texel.y)) - position;
+    vec3 down = position - view_position(uv - vec2(0.0, texel.y));
+    // Use the smallest difference to avoid artifacts at depth discontinuities
+    vec3 dx = abs(right.z) < abs(left.z) ? right : left;
+    vec3 dy = abs(up.z) < abs(down.z) ? up : down;
+    return normalize(cross(dx, dy));
+}
+
+float ambient_occlusion(vec2 pixel) {
+    vec2 uv = pixel / resolution;
+    if (sample_depth(uv) >= 1.0) {
+        return 1.0;
+    }
+    vec3 position = view_position(uv);
+    vec3 normal = view_normal(uv, position);
+
+    float angle = 2.0 * PI * rotation_noise(pixel);
+    vec3 random_direction = vec3(cos(angle), sin(angle), 0.0);
+    vec3 tangent = random_direction - normal * dot(random_direction, normal);
+    if (dot(tangent, tangent) < 0.0001) {
+        tangent = vec3(0.0, 0.0, 1.0) - normal * normal.z;
+    }
+    tangent = normalize(tangent);
+    vec3 bitangent = cross(normal, tangent);
+
+    float occlusion = 0.0;
+    for (int i = 0; i < samples; i++) {
+        vec2 h = Hammersley(uint(i), uint(samples));
+        float phi = 2.0 * PI * h.y;
+        float cos_theta = sqrt(1.0 - h.x);
+        float sin_theta = sqrt(h.x);
+        float scale = float(i + 1) / float(samples);
+        scale = mix(0.1, 1.0, scale * scale) * radius;
+        vec3 offset = vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta) * scale;
+        vec3 sample_position = position + tangent * offset.x + bitangent * offset.y + normal * offset.z;
+
+        vec4 projected = projection * vec4(sample_position, 1.0);
+        vec2 sample_uv = projected.xy / projected.w * 0.5 + 0.5;
+        if (sample_uv.x < 0.0 || sample_uv.x > 1.0 || sample_uv.y < 0.0 || sample_uv.y > 1.0) {
+            continue;
+        }
+        float scene_z = view_position(sample_uv).z;
+        float range_check = smoothstep(0.0, 1.0, radius / max(abs(position.z - scene_z), 0.0001));
+        occlusion += (scene_z >= sample_position.z + bias ? 1.0 : 0.0) * range_check;
+    }
+    return 1.0 - occlusion / float(max(samples, 1));
+}
+
+void main()
+{
+    vec4 color = sample_color(uvs);
+    float depth = sample_depth(uvs);
+    vec2 pixel = floor(gl_FragCoord.xy) + 0.5;
+    float center_z = view_position(uvs).z;
+
+    // Depth aware blur of the noisy ambient occlusion
+    float ao = 0.0;
+    float weight_sum = 0.0;
+    for (int x = -blur; x <= blur; x++) {
+        for (int y = -blur; y <= blur; y++) {
+            vec2 p = pixel + vec2(float(x), float(y));
+            float z = view_position(p / resolution).z;
+            float weight = 1.0 / (0.0001 + abs(center_z - z) / radius);
+            ao += weight * ambient_occlusion(p);
+            weight_sum += weight;
+        }
+    }
+    ao = pow(clamp(ao / weight_sum, 0.0, 1.0), intensity);
+
+    outColor = vec4(color.rgb * ao, color.a);
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    gl_FragDepth = depth;
+}
@@ -0,0 +1,165 @@
+
+uniform mat4 projection;
+uniform mat4 projectionInverse;
+uniform mat4 view;
+uniform mat3 viewInverse;
+uniform vec2 resolution;
+uniform float metallic;
+uniform float roughness;
+uniform float maxRoughness;
+uniform float maxDistance;
+uniform int steps;
+uniform float thickness;
+uniform float intensity;
+
+#ifdef USE_NORMAL_TEXTURE
+uniform sampler2D normalTexture;
+#endif
+#ifdef USE_ORM_TEXTURE
+uniform sampler2D ormTexture;
+#endif
+#ifdef USE_ENVIRONMENT
+uniform samplerCube environmentMap;
+uniform float environmentMaxLod;
+#endif
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+vec3 view_position(vec2 uv) {
+    vec4 position = projectionInverse * vec4(uv * 2.0 - 1.0, sample_depth(uv) * 2.0 - 1.0, 1.0);
+    return position.xyz / position.w;
+}
+
+vec3 view_normal(vec2 uv, vec3 position) {
+#ifdef USE_NORMAL_TEXTURE
+    return normalize(mat3(view) * (2.0 * texture(normalTexture, uv).xyz - 1.0));
+#else
+    vec2 texel = 1.0 / resolution;
+    vec3 right = view_position(uv + vec2(texel.x, 0.0)) - position;
+    vec3 left = position - view_position(uv - vec2(texel.x, 0.0));
+    vec3 up = view_position(uv + vec2(0.0, texel.y)) - position;
+    vec3 down = position - view_position(uv - vec2(0.0, texel.y));
+    // Use the smallest difference to avoid artifacts at depth discontinuities
+    vec3 dx = abs(right.z) < abs(left.z) ? right : left;
+    vec3 dy = abs(up.z) < abs(down.z) ? up : down;
+    return normalize(cross(dx, dy));
+#endif
+}
+
+vec2 project(vec3 position) {
+    vec4 projected = projection * vec4(position, 1.0);
+    return projected.xy / projected.w * 0.5 + 0.5;
+}
+
+bool outside(vec2 uv) {
+    return uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0;
+}
+
+// Interleaved gradient noise used to offset the ray march per pixel
+float ray_noise(vec2 pixel) {
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+// Marches the ray through the depth buffer and returns the uv coordinate of the hit in xy, the distance to the hit in z and whether it was a hit in w
+vec4 ray_march(vec3 origin, vec3 direction) {
+    float step_length = maxDistance / float(max(steps, 1));
+    float offset = ray_noise(gl_FragCoord.xy);
+    float previous = 0.0;
+    for (int i = 0; i < steps; i++) {
+        float t = (float(i) + offset) * step_length;
+        vec3 position = origin + direction * t;
+        vec2 uv = project(position);
+        if (outside(uv) || position.z > 0.0) {
+            break;
+        }
+        float difference = view_position(uv).z - position.z;
+        if (difference > 0.0 && difference < thickness + step_length) {
+            // Refine the hit with a binary search between the previous and the current position
+            float near = previous;
+            float far = t;
+            for (int j = 0; j < 5; j++) {
+                float middle = 0.5 * (near + far);
+                vec3 p = origin + direction * middle;
+                if (view_position(project(p)).z - p.z > 0.0) {
+                    far = middle;
+                } else {
+                    near = middle;
+                }
+            }
+            vec3 hit = origin + direction * far;
+            vec2 hit_uv = project(hit);
+            if (abs(view_position(hit_uv).z - hit.z) < thickness) {
+                return vec4(hit_uv, far, 1.0);
+            }
+            return vec4(0.0);
+        }
+        previous = t;
+    }
+    return vec4(0.0);
+}
+
+// Samples the color around the hit where the radius grows with the roughness and the distance travelled by the ray
+vec3 blurred_color(vec2 uv, float radius) {
+    const vec2 taps[8] = vec2[8](
+        vec2(1.0, 0.0), vec2(0.707, 0.707), vec2(0.0, 1.0), vec2(-0.707, 0.707),
+        vec2(-1.0, 0.0), vec2(-0.707, -0.707), vec2(0.0, -1.0), vec2(0.707, -0.707)
+    );
+    vec3 color = sample_color(uv).rgb;
+    if (radius < 0.5) {
+        return color;
+    }
+    for (int i = 0; i < 8; i++) {
+        float scale = i % 2 == 0 ? 1.0 : 0.5;
+        color += sample_color(uv + taps[i] * scale * radius / resolution).rgb;
+    }
+    return color / 9.0;
+}
+
+void main()
+{
+    vec4 color = sample_color(uvs);
+    float depth = sample_depth(uvs);
+    outColor = color;
+    if (depth < 1.0) {
+        float surface_roughness = roughness;
+        float surface_metallic = metallic;
+#ifdef USE_ORM_TEXTURE
+        vec4 orm = texture(ormTexture, uvs);
+        surface_roughness = orm.g;
+        surface_metallic = orm.b;
+#endif
+        float roughness_fade = 1.0 - smoothstep(0.5 * maxRoughness, maxRoughness, surface_roughness);
+        if (roughness_fade > 0.0) {
+            vec3 position = view_position(uvs);
+            vec3 normal = view_normal(uvs, position);
+            vec3 view_direction = normalize(position);
+            vec3 direction = reflect(view_direction, normal);
+
+            vec4 hit = ray_march(position, direction);
+            vec2 edge = smoothstep(0.0, 0.1, hit.xy) * (1.0 - smoothstep(0.9, 1.0, hit.xy));
+            float visibility = hit.w * edge.x * edge.y * (1.0 - smoothstep(0.5 * maxDistance, maxDistance, hit.z));
+
+            vec3 reflection = vec3(0.0);
+            if (visibility > 0.0) {
+                float radius = surface_roughness * hit.z / max(-position.z, 0.0001) * 0.25 * resolution.y;
+                reflection = blurred_color(hit.xy, min(radius, 16.0));
+            }
+            float coverage = visibility;
+#ifdef USE_ENVIRONMENT
+            vec3 environment = textureLod(environmentMap, viewInverse * direction, surface_roughness * environmentMaxLod).rgb;
+            reflection = mix(environment, reflection, visibility);
+            coverage = 1.0;
+#endif
+            float NdV = max(dot(normal, -view_direction), 0.0);
+            float F0 = mix(0.04, 1.0, surface_metallic);
+            float fresnel = F0 + (max(1.0 - surface_roughness, F0) - F0) * pow(1.0 - NdV, 5.0);
+            float weight = clamp(fresnel * roughness_fade * coverage * intensity, 0.0, 1.0);
+            outColor.rgb = mix(color.rgb, reflection, weight);
+        }
+    }
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    gl_FragDepth = depth;
+}
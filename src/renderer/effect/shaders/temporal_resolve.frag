@@ -0,0 +1,51 @@
+
+uniform sampler2D historyMap;
+uniform int historyValid;
+uniform float blendFactor;
+uniform vec2 jitter;
+uniform mat4 reprojection;
+uniform vec2 sourceResolution;
+#ifdef USE_VELOCITY_TEXTURE
+uniform sampler2D velocityTexture;
+#endif
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // The scene is rendered with a jitter, so sample where the unjittered position is in the source
+    vec2 uv = uvs + jitter;
+    vec4 current = sample_color(uv);
+
+    // Neighbourhood clamping of the history to avoid ghosting
+    vec2 texel = 1.0 / sourceResolution;
+    vec3 minColor = current.rgb;
+    vec3 maxColor = current.rgb;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec3 c = sample_color(uv + vec2(float(x), float(y)) * texel).rgb;
+            minColor = min(minColor, c);
+            maxColor = max(maxColor, c);
+        }
+    }
+
+    float depth = sample_depth(uv);
+    vec4 previous = reprojection * vec4(uvs * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    vec2 previousUv = previous.xy / previous.w * 0.5 + 0.5;
+#ifdef USE_VELOCITY_TEXTURE
+    vec4 velocity = texture(velocityTexture, uv);
+    if (velocity.a > 0.0) {
+        // Moving objects are reprojected using their own motion instead of only the motion of the camera
+        previousUv = uvs - velocity.xy / sourceResolution;
+    }
+#endif
+
+    if (historyValid == 1 && all(greaterThanEqual(previousUv, vec2(0.0))) && all(lessThanEqual(previousUv, vec2(1.0)))) {
+        vec3 history = clamp(texture(historyMap, previousUv).rgb, minColor, maxColor);
+        outColor = vec4(mix(history, current.rgb, blendFactor), current.a);
+    } else {
+        outColor = current;
+    }
+}
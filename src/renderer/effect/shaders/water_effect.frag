@@ -11,6 +11,17 @@ uniform vec4 environmentColor;
 
 uniform float metallic;
 uniform float roughness;
+uniform vec3 waterColor;
+uniform vec3 transmittance;
+uniform float refractionStrength;
+
+#ifdef USE_NORMAL_TEXTURE
+uniform sampler2D normalTexture;
+uniform float normalScale;
+uniform float normalTiling;
+uniform float normalSpeed;
+uniform float time;
+#endif
 
 in vec3 pos;
 in vec3 nor;
@@ -51,14 +62,9 @@ vec3 reflect_color(vec3 incidentDir, vec3 normal)
 
 vec3 water(vec3 col, vec3 p1, vec3 p2)
 {
-    const vec3 scattering = vec3(0.2, 0.4, 0.2); // Scattering coefficient (due to particles in the water)
-    const vec3 absorption = vec3(0.4, 0.955, 0.99); // Absorption coefficient
-    const vec3 c = scattering * absorption;
-    const vec3 equilibriumColorAtInfinity = vec3(0., 0.1, 0.14); // Water color at "infinity"
-    
     float dist = min(distance(p1, p2), 100.);
-    vec3 colorChange = vec3(clamp( pow(c.r, dist), 0., 1.), clamp( pow(c.g, dist), 0., 1.), clamp( pow(c.b, dist), 0., 1.));
-    return colorChange * col + (1. - colorChange) * equilibriumColorAtInfinity;
+    vec3 colorChange = clamp(pow(transmittance, vec3(dist)), 0., 1.);
+    return colorChange * col + (1. - colorChange) * waterColor;
 }
 
 void main()
@@ -66,8 +72,15 @@ void main()
     vec2 screen_uv = gl_FragCoord.xy/screenSize;
     
     vec3 normal = normalize(nor);
+#ifdef USE_NORMAL_TEXTURE
+    // Two normal map samples scrolling in different directions
+    vec2 offset = time * normalSpeed * vec2(1.0, 0.6);
+    vec2 ripples = texture(normalTexture, uvs * normalTiling + offset).xy
+        + texture(normalTexture, uvs * normalTiling * 1.3 - offset.yx).xy - 1.0;
+    normal = normalize(normal + normalScale * vec3(ripples.x, 0.0, ripples.y));
+#endif
     vec3 incidentDir = normalize(pos - cameraPosition);
-    screen_uv -= 0.05 * normal.xz; // Shift the water bottom/sky.
+    screen_uv -= refractionStrength * normal.xz; // Shift the water bottom/sky.
     float depth = sample_depth(screen_uv);
     vec3 backgroundPos = world_pos_from_depth(viewProjectionInverse, depth, screen_uv);
     outColor.rgb = sample_color(screen_uv).rgb;
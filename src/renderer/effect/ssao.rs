@@ -0,0 +1,117 @@
+use crate::renderer::*;
+
+///
+/// Screen space ambient occlusion (SSAO) which darkens creases, corners and areas where objects are in contact,
+/// based on the depth texture of the rendered scene.
+/// The normals are reconstructed from the depth texture and the occlusion is estimated by sampling the depth in a hemisphere around each pixel.
+///
+/// **Note:** The total number of evaluated samples per pixel is `samples * (2 * blur + 1)^2`, so keep the number of samples low if blurring.
+///
+#[derive(Clone, Debug)]
+pub struct SsaoEffect {
+    /// The radius, in world space units, of the hemisphere around each pixel in which occluders are found.
+    pub radius: f32,
+    /// A small depth offset which avoids that a surface occludes itself.
+    pub bias: f32,
+    /// The strength of the darkening, 1.0 is the physically motivated value and higher values gives more pronounced occlusion.
+    pub intensity: f32,
+    /// The number of samples used to estimate the occlusion at each pixel.
+    pub samples: u32,
+    /// The radius, in pixels, of the depth aware blur which removes the noise from the occlusion estimate. 0 means no blur.
+    pub blur: u32,
+}
+
+impl Default for SsaoEffect {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            bias: 0.025,
+            intensity: 1.0,
+            samples: 8,
+            blur: 1,
+        }
+    }
+}
+
+impl Effect for SsaoEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            include_str!("../../core/shared.frag"),
+            color_texture
+                .expect("Must supply a color texture to apply a ssao effect")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a ssao effect")
+                .fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/ssao_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        0b1u16 << 14
+            | 0b1u16 << 11
+            | 0b10u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to apply a ssao effect")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a ssao effect")
+                .id()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply a ssao effect");
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        color_texture.use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a ssao effect")
+            .use_uniforms(program);
+        program.use_uniform("projection", camera.projection());
+        program.use_uniform("projectionInverse", camera.projection().invert().unwrap());
+        program.use_uniform(
+            "resolution",
+            vec2(color_texture.width() as f32, color_texture.height() as f32),
+        );
+        program.use_uniform("radius", self.radius);
+        program.use_uniform("bias", self.bias);
+        program.use_uniform("intensity", self.intensity);
+        program.use_uniform("samples", self.samples as i32);
+        program.use_uniform("blur", self.blur as i32);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// Screen space reflections (SSR) which adds glossy reflections of the visible part of the scene, based on the color and depth texture of the rendered scene.
+/// The reflection of each pixel is found by marching a ray through the depth texture and the reflected color is blurred depending on the roughness of the surface
+/// and the distance to the reflected surface.
+/// Where the ray leaves the screen or does not hit anything, the [SsrEffect::environment] is reflected instead if specified.
+///
+/// Since only what is visible on the screen can be reflected, this effect complements a [ReflectionProbe] or the environment of an [AmbientLight],
+/// which handles reflections of everything else, with reflections of moving objects and the surroundings close to the reflecting surface.
+///
+/// The normals are reconstructed from the depth texture unless a [SsrEffect::normal_texture] is specified, and the roughness and metallic
+/// values are the same everywhere unless an [SsrEffect::orm_texture] is specified.
+///
+#[derive(Clone)]
+pub struct SsrEffect {
+    /// A texture with the same size as the color texture containing the world space normals encoded as `0.5 + 0.5 * normal`, for example rendered using a [NormalMaterial].
+    pub normal_texture: Option<Arc<Texture2D>>,
+    /// A texture with the same size as the color texture containing the roughness in the green channel and the metallic value in the blue channel,
+    /// for example rendered using an [ORMMaterial].
+    pub orm_texture: Option<Arc<Texture2D>>,
+    /// The environment which is reflected where the rays miss, for example the texture of a [Skybox]. Mip maps are used for the rough surfaces if available.
+    pub environment: Option<Arc<TextureCubeMap>>,
+    /// The metallic value used if there is no [SsrEffect::orm_texture].
+    pub metallic: f32,
+    /// The roughness used if there is no [SsrEffect::orm_texture].
+    pub roughness: f32,
+    /// The reflections fade out on surfaces with a roughness approaching this value and surfaces with a higher roughness do not get reflections.
+    pub max_roughness: f32,
+    /// The maximum distance, in world space units, that the rays travel.
+    pub max_distance: f32,
+    /// The number of steps along each ray.
+    pub steps: u32,
+    /// How thick, in world space units, the surfaces in the depth texture are assumed to be, ie. how far behind a surface a ray can be and still hit it.
+    pub thickness: f32,
+    /// The strength of the reflections, 1.0 is the physically motivated value.
+    pub intensity: f32,
+}
+
+impl Default for SsrEffect {
+    fn default() -> Self {
+        Self {
+            normal_texture: None,
+            orm_texture: None,
+            environment: None,
+            metallic: 0.0,
+            roughness: 0.1,
+            max_roughness: 0.6,
+            max_distance: 10.0,
+            steps: 48,
+            thickness: 0.2,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl Effect for SsrEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}{}{}\n{}\n{}\n{}\n{}\n{}",
+            if self.normal_texture.is_some() {
+                "#define USE_NORMAL_TEXTURE\n"
+            } else {
+                ""
+            },
+            if self.orm_texture.is_some() {
+                "#define USE_ORM_TEXTURE\n"
+            } else {
+                ""
+            },
+            if self.environment.is_some() {
+                "#define USE_ENVIRONMENT\n"
+            } else {
+                ""
+            },
+            color_texture
+                .expect("Must supply a color texture to apply a ssr effect")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a ssr effect")
+                .fragment_shader_source(),
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/ssr_effect.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        let mut id = 0b1u16 << 14
+            | 0b1u16 << 10
+            | color_texture
+                .expect("Must supply a color texture to apply a ssr effect")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a ssr effect")
+                .id();
+        if self.normal_texture.is_some() {
+            id |= 0b1u16 << 7;
+        }
+        if self.orm_texture.is_some() {
+            id |= 0b1u16 << 8;
+        }
+        if self.environment.is_some() {
+            id |= 0b1u16 << 9;
+        }
+        id
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply a ssr effect");
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        color_texture.use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a ssr effect")
+            .use_uniforms(program);
+        let view = camera.view();
+        program.use_uniform("projection", camera.projection());
+        program.use_uniform("projectionInverse", camera.projection().invert().unwrap());
+        program.use_uniform_if_required("view", *view);
+        program.use_uniform_if_required(
+            "viewInverse",
+            Mat3::from_cols(view.x.truncate(), view.y.truncate(), view.z.truncate()).transpose(),
+        );
+        program.use_uniform(
+            "resolution",
+            vec2(color_texture.width() as f32, color_texture.height() as f32),
+        );
+        program.use_uniform_if_required("metallic", self.metallic);
+        program.use_uniform_if_required("roughness", self.roughness);
+        program.use_uniform("maxRoughness", self.max_roughness.max(0.001));
+        program.use_uniform("maxDistance", self.max_distance.max(0.001));
+        program.use_uniform("steps", self.steps as i32);
+        program.use_uniform("thickness", self.thickness);
+        program.use_uniform("intensity", self.intensity);
+        if let Some(ref texture) = self.normal_texture {
+            program.use_texture("normalTexture", texture);
+        }
+        if let Some(ref texture) = self.orm_texture {
+            program.use_texture("ormTexture", texture);
+        }
+        if let Some(ref environment) = self.environment {
+            program.use_texture_cube("environmentMap", environment);
+            program.use_uniform(
+                "environmentMaxLod",
+                environment.number_of_mip_maps().saturating_sub(1) as f32,
+            );
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
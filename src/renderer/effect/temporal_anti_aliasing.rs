@@ -0,0 +1,148 @@
+use super::temporal_upscaler::{halton, TemporalResolveEffect};
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// Temporal anti-aliasing (TAA) which removes aliasing on edges and thin geometry by rendering each frame with a different sub-pixel jitter
+/// and accumulating the frames over time in a history color buffer.
+/// This works well together with a chain of post-processing effects where multisampling is unavailable or costly,
+/// and unlike [FxaaEffect] it also recovers geometry thinner than a pixel.
+///
+/// The previous frames are reprojected using the depth of the current frame, so moving the camera is supported.
+/// To also reproject moving objects, set the [TemporalAntiAliasing::velocity_texture] to the motion vectors of the scene,
+/// for example rendered using [MotionVectors::render_meshes] and shared using [MotionVectors::texture].
+/// The reprojected history is clamped to the colors of the neighbourhood of each pixel in the current frame to reduce ghosting.
+///
+/// Each frame, call [TemporalAntiAliasing::prepare] on a copy of the camera, render the scene into a color and depth texture
+/// with the size of the viewport of that camera and then call [TemporalAntiAliasing::resolve] to get the anti-aliased result.
+///
+pub struct TemporalAntiAliasing {
+    context: Context,
+    /// How much the current frame contributes to the result compared to the accumulated history.
+    /// Lower values gives a smoother result but more ghosting. The default is 0.1.
+    pub blend_factor: f32,
+    /// The number of different sub-pixel jitters in the sequence that is repeated, where a longer sequence gives smoother edges
+    /// but takes longer to converge. The default is 8.
+    pub jitter_sequence_length: u32,
+    /// A texture with the same size as the color texture containing the motion in pixels since the previous frame in the red and green channels
+    /// and an alpha value of zero where there is no geometry, as rendered by [MotionVectors].
+    /// If this is not specified, the history is only reprojected using the camera motion, so moving objects will leave a short trail.
+    pub velocity_texture: Option<Arc<Texture2D>>,
+    frame: u32,
+    previous_view_projection: Mat4,
+    history: Option<[Texture2D; 2]>,
+    history_valid: bool,
+}
+
+impl TemporalAntiAliasing {
+    ///
+    /// Creates a new temporal anti-aliasing without any history.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            blend_factor: 0.1,
+            jitter_sequence_length: 8,
+            velocity_texture: None,
+            frame: 0,
+            previous_view_projection: Mat4::identity(),
+            history: None,
+            history_valid: false,
+        }
+    }
+
+    ///
+    /// Returns the sub-pixel jitter, in pixels, that is applied to the camera in [TemporalAntiAliasing::prepare] this frame.
+    ///
+    pub fn jitter(&self) -> Vec2 {
+        let index = self.frame % self.jitter_sequence_length.max(1) + 1;
+        vec2(halton(index, 2) - 0.5, halton(index, 3) - 0.5)
+    }
+
+    ///
+    /// Prepares the camera for rendering the scene this frame, ie. applies the next sub-pixel jitter in the sequence.
+    ///
+    pub fn prepare(&self, camera: &mut Camera) {
+        camera.set_jitter(self.jitter());
+    }
+
+    ///
+    /// Combines the color and depth textures, rendered with the camera given to [TemporalAntiAliasing::prepare], with the previous frames
+    /// and returns the anti-aliased result with the same size as the color texture.
+    /// The result is in the same color space as the input, so use for example a [CopyEffect] or [ScreenEffect] to write it to the screen.
+    ///
+    pub fn resolve(
+        &mut self,
+        camera: &Camera,
+        color_texture: ColorTexture,
+        depth_texture: DepthTexture,
+    ) -> &Texture2D {
+        let width = color_texture.width();
+        let height = color_texture.height();
+        if self
+            .history
+            .as_ref()
+            .map(|h| h[0].width() != width || h[0].height() != height)
+            .unwrap_or(true)
+        {
+            let new_texture = || {
+                Texture2D::new_empty::<[f16; 4]>(
+                    &self.context,
+                    width,
+                    height,
+                    Interpolation::Linear,
+                    Interpolation::Linear,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                )
+            };
+            self.history = Some([new_texture(), new_texture()]);
+            self.history_valid = false;
+        }
+
+        let view_projection = camera.projection_without_jitter() * camera.view();
+        let mut output_camera = camera.clone();
+        output_camera.set_viewport(Viewport::new_at_origo(width, height));
+        output_camera.set_jitter(vec2(0.0, 0.0));
+
+        let current = (self.frame % 2) as usize;
+        let history = self.history.as_mut().unwrap();
+        let (first, second) = history.split_at_mut(1);
+        let (source, target) = if current == 0 {
+            (&first[0], &mut second[0])
+        } else {
+            (&second[0], &mut first[0])
+        };
+        target.as_color_target(None).apply_screen_effect(
+            &TemporalResolveEffect {
+                history: source,
+                history_valid: self.history_valid,
+                blend_factor: self.blend_factor,
+                // The current frame is not shifted back, since that would blur it, instead the jitter is accumulated into sub-pixel detail
+                jitter: vec2(0.0, 0.0),
+                reprojection: self.previous_view_projection
+                    * view_projection.invert().unwrap_or(Mat4::identity()),
+                velocity_texture: self.velocity_texture.as_deref(),
+            },
+            &output_camera,
+            &[],
+            Some(color_texture),
+            Some(depth_texture),
+        );
+
+        self.previous_view_projection = view_projection;
+        self.history_valid = true;
+        self.frame = self.frame.wrapping_add(1);
+        &self.history.as_ref().unwrap()[1 - current]
+    }
+
+    ///
+    /// Discards the accumulated history, for example when the camera is moved to a completely different location,
+    /// and restarts the sequence of sub-pixel jitters, so the frames after a reset are the same each time the same frames are rendered.
+    ///
+    pub fn reset(&mut self) {
+        self.history_valid = false;
+        self.frame = 0;
+    }
+}
@@ -0,0 +1,247 @@
+use crate::renderer::*;
+
+///
+/// Temporal upscaling which makes it possible to render the scene at a lower resolution and reconstruct an image at the full resolution
+/// by accumulating jittered low resolution frames over time.
+/// The previous frames are reprojected using the depth of the current frame, so moving the camera is supported,
+/// whereas moving objects will leave a short trail.
+///
+/// Each frame, call [TemporalUpscaler::prepare] on a copy of the camera, render the scene into a color and depth texture
+/// with the size of the viewport of that camera and then call [TemporalUpscaler::resolve] to get the full resolution result.
+///
+pub struct TemporalUpscaler {
+    context: Context,
+    /// The resolution of the rendered scene relative to the output resolution, ie. a value of 0.5 means that half the width and height is rendered.
+    pub render_scale: f32,
+    /// How much the current frame contributes to the result compared to the accumulated history.
+    /// Lower values gives a smoother result but more ghosting.
+    pub blend_factor: f32,
+    frame: u32,
+    output_viewport: Viewport,
+    previous_view_projection: Mat4,
+    history: Option<[Texture2D; 2]>,
+    history_valid: bool,
+}
+
+impl TemporalUpscaler {
+    ///
+    /// Creates a new temporal upscaler which renders the scene at the given scale of the output resolution.
+    ///
+    pub fn new(context: &Context, render_scale: f32) -> Self {
+        Self {
+            context: context.clone(),
+            render_scale,
+            blend_factor: 0.1,
+            frame: 0,
+            output_viewport: Viewport::new_at_origo(1, 1),
+            previous_view_projection: Mat4::identity(),
+            history: None,
+            history_valid: false,
+        }
+    }
+
+    ///
+    /// Prepares the camera for rendering the scene this frame, ie. sets the viewport to the render resolution and applies a sub-pixel jitter.
+    /// The viewport of the camera before calling this method is used as the output resolution.
+    ///
+    pub fn prepare(&mut self, camera: &mut Camera) {
+        self.output_viewport = camera.viewport();
+        let scale = self.render_scale.clamp(0.01, 1.0);
+        camera.set_viewport(Viewport::new_at_origo(
+            ((self.output_viewport.width as f32 * scale).round() as u32).max(1),
+            ((self.output_viewport.height as f32 * scale).round() as u32).max(1),
+        ));
+        let index = self.frame % 8 + 1;
+        camera.set_jitter(vec2(halton(index, 2) - 0.5, halton(index, 3) - 0.5));
+    }
+
+    ///
+    /// Combines the color and depth textures, rendered with the camera given to [TemporalUpscaler::prepare], with the previous frames
+    /// and returns the result at the output resolution.
+    /// The result is in the same color space as the input, so use for example a [CopyEffect] or [ScreenEffect] to write it to the screen.
+    ///
+    pub fn resolve(
+        &mut self,
+        camera: &Camera,
+        color_texture: ColorTexture,
+        depth_texture: DepthTexture,
+    ) -> &Texture2D {
+        let width = self.output_viewport.width;
+        let height = self.output_viewport.height;
+        if self
+            .history
+            .as_ref()
+            .map(|h| h[0].width() != width || h[0].height() != height)
+            .unwrap_or(true)
+        {
+            let new_texture = || {
+                Texture2D::new_empty::<[f16; 4]>(
+                    &self.context,
+                    width,
+                    height,
+                    Interpolation::Linear,
+                    Interpolation::Linear,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                )
+            };
+            self.history = Some([new_texture(), new_texture()]);
+            self.history_valid = false;
+        }
+
+        let view_projection = camera.projection_without_jitter() * camera.view();
+        let mut output_camera = camera.clone();
+        output_camera.set_viewport(Viewport::new_at_origo(width, height));
+        output_camera.set_jitter(vec2(0.0, 0.0));
+
+        let current = (self.frame % 2) as usize;
+        let history = self.history.as_mut().unwrap();
+        let (first, second) = history.split_at_mut(1);
+        let (source, target) = if current == 0 {
+            (&first[0], &mut second[0])
+        } else {
+            (&second[0], &mut first[0])
+        };
+        let jitter = camera.jitter();
+        let viewport = camera.viewport();
+        target.as_color_target(None).apply_screen_effect(
+            &TemporalResolveEffect {
+                history: source,
+                history_valid: self.history_valid,
+                blend_factor: self.blend_factor,
+                jitter: vec2(
+                    jitter.x / viewport.width as f32,
+                    jitter.y / viewport.height as f32,
+                ),
+                reprojection: self.previous_view_projection
+                    * view_projection.invert().unwrap_or(Mat4::identity()),
+                velocity_texture: None,
+            },
+            &output_camera,
+            &[],
+            Some(color_texture),
+            Some(depth_texture),
+        );
+
+        self.previous_view_projection = view_projection;
+        self.history_valid = true;
+        self.frame = self.frame.wrapping_add(1);
+        &self.history.as_ref().unwrap()[1 - current]
+    }
+
+    ///
+    /// Discards the accumulated history, for example when the camera is moved to a completely different location,
+    /// and restarts the sequence of sub-pixel jitters, so the frames after a reset are the same each time the same frames are rendered.
+    ///
+    pub fn reset(&mut self) {
+        self.history_valid = false;
+        self.frame = 0;
+    }
+}
+
+pub(super) fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut f = 1.0;
+    while index > 0 {
+        f /= base as f32;
+        result += f * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+pub(super) struct TemporalResolveEffect<'a> {
+    pub history: &'a Texture2D,
+    pub history_valid: bool,
+    pub blend_factor: f32,
+    pub jitter: Vec2,
+    pub reprojection: Mat4,
+    // The motion in pixels since the previous frame, which is used instead of the reprojection where the alpha value is above zero
+    pub velocity_texture: Option<&'a Texture2D>,
+}
+
+impl<'a> Effect for TemporalResolveEffect<'a> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}{}\n{}\n{}",
+            if self.velocity_texture.is_some() {
+                "#define USE_VELOCITY_TEXTURE\n"
+            } else {
+                ""
+            },
+            color_texture
+                .expect("Must supply a color texture to apply a temporal resolve")
+                .fragment_shader_source(),
+            depth_texture
+                .expect("Must supply a depth texture to apply a temporal resolve")
+                .fragment_shader_source(),
+            include_str!("shaders/temporal_resolve.frag")
+        )
+    }
+
+    fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        let mut id = 0b1u16 << 14
+            | 0b1u16 << 11
+            | 0b11u16 << 7
+            | color_texture
+                .expect("Must supply a color texture to apply a temporal resolve")
+                .id()
+            | depth_texture
+                .expect("Must supply a depth texture to apply a temporal resolve")
+                .id();
+        if self.velocity_texture.is_some() {
+            id |= 0b1u16 << 10;
+        }
+        id
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _camera: &Camera,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let color_texture =
+            color_texture.expect("Must supply a color texture to apply a temporal resolve");
+        color_texture.use_uniforms(program);
+        depth_texture
+            .expect("Must supply a depth texture to apply a temporal resolve")
+            .use_uniforms(program);
+        program.use_texture("historyMap", self.history);
+        program.use_uniform("historyValid", if self.history_valid { 1 } else { 0 });
+        program.use_uniform("blendFactor", self.blend_factor);
+        program.use_uniform("jitter", self.jitter);
+        program.use_uniform("reprojection", self.reprojection);
+        if let Some(velocity_texture) = self.velocity_texture {
+            program.use_texture("velocityTexture", velocity_texture);
+        }
+        program.use_uniform(
+            "sourceResolution",
+            vec2(color_texture.width() as f32, color_texture.height() as f32),
+        );
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
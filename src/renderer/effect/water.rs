@@ -29,6 +29,21 @@ pub struct WaterEffect {
     pub metallic: f32,
     /// A value in the range `[0..1]` specifying how rough the surface is.
     pub roughness: f32,
+    /// The color of the water when looking through a lot of water.
+    pub water_color: Srgba,
+    /// The fraction of the red, green and blue light that is transmitted through one unit of water.
+    /// Lower values makes the water less clear.
+    pub transmittance: Vec3,
+    /// Specifies how much the water surface distorts the refracted scene below the surface.
+    pub refraction_strength: f32,
+    /// An optional normal map which is scrolled across the surface in two directions to add small ripples on top of the waves.
+    pub normal_texture: Option<Texture2DRef>,
+    /// The strength of the ripples from the [WaterEffect::normal_texture].
+    pub normal_scale: f32,
+    /// The size in world space of one repetition of the [WaterEffect::normal_texture].
+    pub normal_tiling: f32,
+    /// The speed in world space units per second that the [WaterEffect::normal_texture] is scrolled across the surface.
+    pub normal_speed: f32,
     /// The lighting model used when rendering this effect
     pub lighting_model: LightingModel,
 }
@@ -41,11 +56,16 @@ impl Effect for WaterEffect {
         depth_texture: Option<DepthTexture>,
     ) -> String {
         format!(
-            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
             match &self.background {
                 Background::Color(_) => "",
                 Background::Texture(_) => "#define USE_BACKGROUND_TEXTURE",
             },
+            if self.normal_texture.is_some() {
+                "#define USE_NORMAL_TEXTURE"
+            } else {
+                ""
+            },
             color_texture
                 .expect("Must supply a color texture to apply a water effect")
                 .fragment_shader_source(),
@@ -60,7 +80,7 @@ impl Effect for WaterEffect {
     }
 
     fn id(&self, color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
-        0b1u16 << 14
+        let mut id = 0b1u16 << 14
             | 0b1u16 << 12
             | 0b1u16 << 11
             | color_texture
@@ -68,7 +88,14 @@ impl Effect for WaterEffect {
                 .id()
             | depth_texture
                 .expect("Must supply a depth texture to apply a water effect")
-                .id()
+                .id();
+        if let Background::Texture(_) = self.background {
+            id |= 0b1u16 << 7;
+        }
+        if self.normal_texture.is_some() {
+            id |= 0b1u16 << 8;
+        }
+        id
     }
 
     fn fragment_attributes(&self) -> FragmentAttributes {
@@ -95,8 +122,12 @@ impl Effect for WaterEffect {
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) {
-        camera.tone_mapping.use_uniforms(program);
-        camera.color_mapping.use_uniforms(program);
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
         color_texture
             .expect("Must supply a color texture to apply a water effect")
             .use_uniforms(program);
@@ -121,6 +152,15 @@ impl Effect for WaterEffect {
         );
         program.use_uniform("metallic", self.metallic);
         program.use_uniform("roughness", self.roughness);
+        program.use_uniform("waterColor", self.water_color.to_linear_srgb().truncate());
+        program.use_uniform("transmittance", self.transmittance);
+        program.use_uniform("refractionStrength", self.refraction_strength);
+        if let Some(ref texture) = self.normal_texture {
+            program.use_uniform("normalScale", self.normal_scale);
+            program.use_uniform("normalTiling", 1.0 / self.normal_tiling.max(f32::EPSILON));
+            program.use_uniform("normalSpeed", self.normal_speed);
+            program.use_texture("normalTexture", texture);
+        }
         match &self.background {
             Background::Color(color) => {
                 program.use_uniform("environmentColor", color.to_linear_srgb())
@@ -136,6 +176,13 @@ impl Default for WaterEffect {
             background: Background::default(),
             metallic: 0.0,
             roughness: 1.0,
+            water_color: Srgba::new(0, 89, 105, 255),
+            transmittance: vec3(0.08, 0.382, 0.198),
+            refraction_strength: 0.05,
+            normal_texture: None,
+            normal_scale: 0.3,
+            normal_tiling: 4.0,
+            normal_speed: 0.2,
             lighting_model: LightingModel::Blinn,
         }
     }
@@ -0,0 +1,611 @@
+use super::model_bundle::{Encode, Reader};
+use crate::renderer::*;
+use three_d_asset::ProjectionType;
+
+/// The bytes at the start of a serialized frame dump.
+const MAGIC: &[u8; 8] = b"3DFRAME\0";
+/// The version of the frame dump format, which is increased each time the format changes.
+const VERSION: u32 = 2;
+
+///
+/// A light in a [FrameDump].
+///
+#[derive(Clone, Debug)]
+#[allow(missing_docs)]
+pub enum FrameDumpLight {
+    Ambient {
+        intensity: f32,
+        color: Srgba,
+    },
+    Directional {
+        intensity: f32,
+        color: Srgba,
+        direction: Vec3,
+    },
+    Point {
+        intensity: f32,
+        color: Srgba,
+        position: Vec3,
+        attenuation: Attenuation,
+    },
+    Spot {
+        intensity: f32,
+        color: Srgba,
+        position: Vec3,
+        direction: Vec3,
+        cutoff: Radians,
+        attenuation: Attenuation,
+    },
+}
+
+impl From<&AmbientLight> for FrameDumpLight {
+    fn from(light: &AmbientLight) -> Self {
+        Self::Ambient {
+            intensity: light.intensity,
+            color: light.color,
+        }
+    }
+}
+
+impl From<&DirectionalLight> for FrameDumpLight {
+    fn from(light: &DirectionalLight) -> Self {
+        Self::Directional {
+            intensity: light.intensity,
+            color: light.color,
+            direction: light.direction,
+        }
+    }
+}
+
+impl From<&PointLight> for FrameDumpLight {
+    fn from(light: &PointLight) -> Self {
+        Self::Point {
+            intensity: light.intensity,
+            color: light.color,
+            position: light.position,
+            attenuation: light.attenuation,
+        }
+    }
+}
+
+impl From<&SpotLight> for FrameDumpLight {
+    fn from(light: &SpotLight) -> Self {
+        Self::Spot {
+            intensity: light.intensity,
+            color: light.color,
+            position: light.position,
+            direction: light.direction,
+            cutoff: light.cutoff,
+            attenuation: light.attenuation,
+        }
+    }
+}
+
+///
+/// The input of a single frame, ie. the camera, the models with their materials and textures and the lights, which can be saved to a single file
+/// and replayed later, for example to attach a reproducible rendering problem to a bug report.
+/// The textures are reduced to a maximum size when the models are added to keep the file small.
+/// Only the parts of the scene which can be described by a [CpuModel] and the lights which can be described by a [FrameDumpLight] are captured,
+/// so custom geometries, materials, effects and shadow maps are not part of the dump and the models are replayed with a [PhysicalMaterial].
+///
+/// ```no_run
+/// # use three_d::*;
+/// # let context: Context = unimplemented!();
+/// # let camera: Camera = unimplemented!();
+/// # let cpu_model: CpuModel = unimplemented!();
+/// # let light: DirectionalLight = unimplemented!();
+/// let mut dump = FrameDump::new(&camera, Srgba::BLACK, 256);
+/// dump.add_model(&cpu_model, Mat4::identity());
+/// dump.add_light(&light);
+/// std::fs::write("frame.3dframe", dump.serialize()).unwrap();
+///
+/// // Later, for example by a maintainer
+/// let dump = FrameDump::deserialize(&std::fs::read("frame.3dframe").unwrap()).unwrap();
+/// let replay = dump.replay(&context).unwrap();
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct FrameDump {
+    /// The camera used to render the frame.
+    pub camera: Camera,
+    /// The color the frame is cleared to before rendering.
+    pub background: Srgba,
+    /// The models in the frame, where the transformation of the models is applied to the transformation of each primitive.
+    pub models: Vec<CpuModel>,
+    /// The lights in the frame.
+    pub lights: Vec<FrameDumpLight>,
+    /// The maximum width and height of the textures of the models added with [FrameDump::add_model].
+    pub max_texture_size: u32,
+}
+
+impl FrameDump {
+    ///
+    /// Creates a new frame dump of a frame rendered with the given camera and cleared to the given background color.
+    /// The textures of the models added are reduced so that their width and height is at most the given maximum texture size.
+    ///
+    pub fn new(camera: &Camera, background: Srgba, max_texture_size: u32) -> Self {
+        Self {
+            camera: camera.clone(),
+            background,
+            models: Vec::new(),
+            lights: Vec::new(),
+            max_texture_size,
+        }
+    }
+
+    ///
+    /// Adds the given model rendered with the given transformation, for example the transformation set on the [Model] constructed from the [CpuModel].
+    /// The textures of the materials are reduced to [FrameDump::max_texture_size].
+    ///
+    pub fn add_model(&mut self, cpu_model: &CpuModel, transformation: Mat4) {
+        let mut cpu_model = cpu_model.clone();
+        for primitive in cpu_model.geometries.iter_mut() {
+            primitive.transformation = transformation * primitive.transformation;
+        }
+        for material in cpu_model.materials.iter_mut() {
+            for texture in [
+                &mut material.albedo_texture,
+                &mut material.metallic_roughness_texture,
+                &mut material.occlusion_metallic_roughness_texture,
+                &mut material.occlusion_texture,
+                &mut material.normal_texture,
+                &mut material.emissive_texture,
+                &mut material.transmission_texture,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                reduce_texture(texture, self.max_texture_size.max(1));
+            }
+        }
+        self.models.push(cpu_model);
+    }
+
+    ///
+    /// Adds the given light, for example an [AmbientLight], [DirectionalLight], [PointLight] or [SpotLight].
+    /// The environment of an ambient light and the shadow maps are not captured.
+    ///
+    pub fn add_light(&mut self, light: impl Into<FrameDumpLight>) {
+        self.lights.push(light.into());
+    }
+
+    ///
+    /// Serializes the frame dump into a single binary blob, where the models are encoded in the same way as in the `.3d` format (see [serialize_model]).
+    /// Use [FrameDump::deserialize] to get the frame dump back.
+    ///
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        VERSION.encode(&mut bytes);
+        self.camera.encode(&mut bytes);
+        self.background.encode(&mut bytes);
+        self.models.encode(&mut bytes);
+        self.lights.encode(&mut bytes);
+        self.max_texture_size.encode(&mut bytes);
+        bytes
+    }
+
+    ///
+    /// Deserializes a frame dump from bytes produced by [FrameDump::serialize].
+    /// Returns an error if the bytes are not a frame dump or if they were serialized by a different version of three-d.
+    ///
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, RendererError> {
+        let invalid = |e| match e {
+            RendererError::InvalidModelBundle(message) => RendererError::InvalidFrameDump(message),
+            e => e,
+        };
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len()).map_err(invalid)? != MAGIC {
+            return Err(RendererError::InvalidFrameDump(
+                "not a frame dump".to_string(),
+            ));
+        }
+        let version = u32::decode(&mut reader).map_err(invalid)?;
+        if version != VERSION {
+            return Err(RendererError::InvalidFrameDump(format!(
+                "unsupported version {}, expected version {}",
+                version, VERSION
+            )));
+        }
+        let decode = |reader: &mut Reader| -> Result<Self, RendererError> {
+            Ok(Self {
+                camera: Camera::decode(reader)?,
+                background: Srgba::decode(reader)?,
+                models: Vec::decode(reader)?,
+                lights: Vec::decode(reader)?,
+                max_texture_size: u32::decode(reader)?,
+            })
+        };
+        let frame_dump = decode(&mut reader).map_err(invalid)?;
+        if !reader.0.is_empty() {
+            return Err(RendererError::InvalidFrameDump(
+                "unexpected data after the frame".to_string(),
+            ));
+        }
+        Ok(frame_dump)
+    }
+
+    ///
+    /// Constructs the models and lights of this frame dump, so that the frame can be rendered again using [FrameReplay::render].
+    ///
+    pub fn replay(&self, context: &Context) -> Result<FrameReplay, RendererError> {
+        let models = self
+            .models
+            .iter()
+            .map(|cpu_model| Model::new(context, cpu_model))
+            .collect::<Result<Vec<_>, _>>()?;
+        let lights = self
+            .lights
+            .iter()
+            .map(|light| -> Box<dyn Light> {
+                match light {
+                    FrameDumpLight::Ambient { intensity, color } => {
+                        Box::new(AmbientLight::new(context, *intensity, *color))
+                    }
+                    FrameDumpLight::Directional {
+                        intensity,
+                        color,
+                        direction,
+                    } => Box::new(DirectionalLight::new(
+                        context, *intensity, *color, direction,
+                    )),
+                    FrameDumpLight::Point {
+                        intensity,
+                        color,
+                        position,
+                        attenuation,
+                    } => Box::new(PointLight::new(
+                        context,
+                        *intensity,
+                        *color,
+                        position,
+                        *attenuation,
+                    )),
+                    FrameDumpLight::Spot {
+                        intensity,
+                        color,
+                        position,
+                        direction,
+                        cutoff,
+                        attenuation,
+                    } => Box::new(SpotLight::new(
+                        context,
+                        *intensity,
+                        *color,
+                        position,
+                        direction,
+                        *cutoff,
+                        *attenuation,
+                    )),
+                }
+            })
+            .collect();
+        Ok(FrameReplay {
+            camera: self.camera.clone(),
+            background: self.background,
+            models,
+            lights,
+        })
+    }
+}
+
+///
+/// The models and lights of a [FrameDump] constructed by [FrameDump::replay], which renders the captured frame again.
+///
+pub struct FrameReplay {
+    /// The camera used to render the frame.
+    pub camera: Camera,
+    /// The color the frame is cleared to before rendering.
+    pub background: Srgba,
+    /// The models in the frame.
+    pub models: Vec<Model<PhysicalMaterial>>,
+    /// The lights in the frame.
+    pub lights: Vec<Box<dyn Light>>,
+}
+
+impl FrameReplay {
+    ///
+    /// Clears the given render target to the background color and renders the frame with the captured camera.
+    /// The viewport of the captured camera is used, so the render target should have the same size as the captured frame.
+    ///
+    pub fn render<'a>(&self, target: &'a RenderTarget<'a>) -> &'a RenderTarget<'a> {
+        let background = self.background.to_linear_srgb();
+        target
+            .clear(ClearState::color_and_depth(
+                background.x,
+                background.y,
+                background.z,
+                background.w,
+                1.0,
+            ))
+            .render(&self.camera, self.objects(), &self.light_refs())
+    }
+
+    ///
+    /// Returns the parts of all the models in the frame.
+    ///
+    pub fn objects(&self) -> impl Iterator<Item = &dyn Object> + Clone {
+        self.models
+            .iter()
+            .flat_map(|model| model.iter().map(|part| part as &dyn Object))
+    }
+
+    ///
+    /// Returns references to the lights in the frame, which can be given to the render calls.
+    ///
+    pub fn light_refs(&self) -> Vec<&dyn Light> {
+        self.lights.iter().map(|light| light.as_ref()).collect()
+    }
+}
+
+///
+/// Reduces the size of the given texture, using nearest sampling, so that both the width and height is at most the given maximum size.
+///
+fn reduce_texture(texture: &mut CpuTexture, max_size: u32) {
+    if texture.width <= max_size && texture.height <= max_size {
+        return;
+    }
+    let scale = max_size as f32 / texture.width.max(texture.height) as f32;
+    let width = ((texture.width as f32 * scale) as u32).clamp(1, max_size);
+    let height = ((texture.height as f32 * scale) as u32).clamp(1, max_size);
+    fn resample<T: Copy>(data: &[T], from: (u32, u32), to: (u32, u32)) -> Vec<T> {
+        (0..to.1)
+            .flat_map(|y| {
+                let source_y = (y as u64 * from.1 as u64 / to.1 as u64) as usize;
+                (0..to.0).map(move |x| {
+                    let source_x = (x as u64 * from.0 as u64 / to.0 as u64) as usize;
+                    data[source_y * from.0 as usize + source_x]
+                })
+            })
+            .collect()
+    }
+    let from = (texture.width, texture.height);
+    let to = (width, height);
+    texture.data = match &texture.data {
+        TextureData::RU8(data) => TextureData::RU8(resample(data, from, to)),
+        TextureData::RgU8(data) => TextureData::RgU8(resample(data, from, to)),
+        TextureData::RgbU8(data) => TextureData::RgbU8(resample(data, from, to)),
+        TextureData::RgbaU8(data) => TextureData::RgbaU8(resample(data, from, to)),
+        TextureData::RF16(data) => TextureData::RF16(resample(data, from, to)),
+        TextureData::RgF16(data) => TextureData::RgF16(resample(data, from, to)),
+        TextureData::RgbF16(data) => TextureData::RgbF16(resample(data, from, to)),
+        TextureData::RgbaF16(data) => TextureData::RgbaF16(resample(data, from, to)),
+        TextureData::RF32(data) => TextureData::RF32(resample(data, from, to)),
+        TextureData::RgF32(data) => TextureData::RgF32(resample(data, from, to)),
+        TextureData::RgbF32(data) => TextureData::RgbF32(resample(data, from, to)),
+        TextureData::RgbaF32(data) => TextureData::RgbaF32(resample(data, from, to)),
+    };
+    texture.width = width;
+    texture.height = height;
+}
+
+impl Encode for Camera {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        let viewport = self.viewport();
+        viewport.x.encode(bytes);
+        viewport.y.encode(bytes);
+        viewport.width.encode(bytes);
+        viewport.height.encode(bytes);
+        match self.projection_type() {
+            ProjectionType::Orthographic { height } => {
+                0u8.encode(bytes);
+                height.encode(bytes);
+            }
+            ProjectionType::Perspective { field_of_view_y } => {
+                1u8.encode(bytes);
+                field_of_view_y.0.encode(bytes);
+            }
+        }
+        self.z_near().encode(bytes);
+        self.z_far().encode(bytes);
+        self.position().encode(bytes);
+        self.target().encode(bytes);
+        self.up().encode(bytes);
+        (self.tone_mapping as u8).encode(bytes);
+        (self.color_mapping as u8).encode(bytes);
+        if self.color_mapping == ColorMapping::ComputeToGamma {
+            self.gamma.encode(bytes);
+        }
+        self.exposure.encode(bytes);
+        self.fog.encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        let viewport = Viewport {
+            x: i32::decode(reader)?,
+            y: i32::decode(reader)?,
+            width: u32::decode(reader)?,
+            height: u32::decode(reader)?,
+        };
+        let orthographic = reader.tag("projection type", 1)? == 0;
+        let parameter = f32::decode(reader)?;
+        let z_near = f32::decode(reader)?;
+        let z_far = f32::decode(reader)?;
+        let position = Vec3::decode(reader)?;
+        let target = Vec3::decode(reader)?;
+        let up = Vec3::decode(reader)?;
+        let mut camera = if orthographic {
+            Camera::new_orthographic(viewport, position, target, up, parameter, z_near, z_far)
+        } else {
+            Camera::new_perspective(
+                viewport,
+                position,
+                target,
+                up,
+                radians(parameter),
+                z_near,
+                z_far,
+            )
+        };
+        camera.tone_mapping = match reader.tag("tone mapping", 3)? {
+            0 => ToneMapping::None,
+            1 => ToneMapping::Reinhard,
+            2 => ToneMapping::Aces,
+            _ => ToneMapping::Filmic,
+        };
+        camera.color_mapping = match reader.tag("color mapping", 4)? {
+            0 => ColorMapping::None,
+            1 => ColorMapping::ComputeToSrgb,
+            2 => ColorMapping::ComputeToSrgbDithered,
+            3 => ColorMapping::ComputeToRec709,
+            _ => {
+                camera.gamma = f32::decode(reader)?;
+                ColorMapping::ComputeToGamma
+            }
+        };
+        camera.exposure = f32::decode(reader)?;
+        camera.fog = Fog::decode(reader)?;
+        Ok(camera)
+    }
+}
+
+impl Encode for Fog {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.color.encode(bytes);
+        match self.falloff {
+            FogFalloff::None => 0u8.encode(bytes),
+            FogFalloff::Linear { start, end } => {
+                1u8.encode(bytes);
+                start.encode(bytes);
+                end.encode(bytes);
+            }
+            FogFalloff::Exponential { density } => {
+                2u8.encode(bytes);
+                density.encode(bytes);
+            }
+            FogFalloff::ExponentialSquared { density } => {
+                3u8.encode(bytes);
+                density.encode(bytes);
+            }
+        }
+        self.height_fog.encode(bytes);
+        self.max_opacity.encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(Self {
+            color: Srgba::decode(reader)?,
+            falloff: match reader.tag("fog falloff", 3)? {
+                0 => FogFalloff::None,
+                1 => FogFalloff::Linear {
+                    start: f32::decode(reader)?,
+                    end: f32::decode(reader)?,
+                },
+                2 => FogFalloff::Exponential {
+                    density: f32::decode(reader)?,
+                },
+                _ => FogFalloff::ExponentialSquared {
+                    density: f32::decode(reader)?,
+                },
+            },
+            height_fog: Option::decode(reader)?,
+            max_opacity: f32::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for HeightFog {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.height.encode(bytes);
+        self.density.encode(bytes);
+        self.falloff.encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(Self {
+            height: f32::decode(reader)?,
+            density: f32::decode(reader)?,
+            falloff: f32::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for Attenuation {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.constant.encode(bytes);
+        self.linear.encode(bytes);
+        self.quadratic.encode(bytes);
+        self.range.encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(Self {
+            constant: f32::decode(reader)?,
+            linear: f32::decode(reader)?,
+            quadratic: f32::decode(reader)?,
+            range: Option::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for FrameDumpLight {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Self::Ambient { intensity, color } => {
+                0u8.encode(bytes);
+                intensity.encode(bytes);
+                color.encode(bytes);
+            }
+            Self::Directional {
+                intensity,
+                color,
+                direction,
+            } => {
+                1u8.encode(bytes);
+                intensity.encode(bytes);
+                color.encode(bytes);
+                direction.encode(bytes);
+            }
+            Self::Point {
+                intensity,
+                color,
+                position,
+                attenuation,
+            } => {
+                2u8.encode(bytes);
+                intensity.encode(bytes);
+                color.encode(bytes);
+                position.encode(bytes);
+                attenuation.encode(bytes);
+            }
+            Self::Spot {
+                intensity,
+                color,
+                position,
+                direction,
+                cutoff,
+                attenuation,
+            } => {
+                3u8.encode(bytes);
+                intensity.encode(bytes);
+                color.encode(bytes);
+                position.encode(bytes);
+                direction.encode(bytes);
+                cutoff.0.encode(bytes);
+                attenuation.encode(bytes);
+            }
+        }
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(match reader.tag("light", 3)? {
+            0 => Self::Ambient {
+                intensity: f32::decode(reader)?,
+                color: Srgba::decode(reader)?,
+            },
+            1 => Self::Directional {
+                intensity: f32::decode(reader)?,
+                color: Srgba::decode(reader)?,
+                direction: Vec3::decode(reader)?,
+            },
+            2 => Self::Point {
+                intensity: f32::decode(reader)?,
+                color: Srgba::decode(reader)?,
+                position: Vec3::decode(reader)?,
+                attenuation: Attenuation::decode(reader)?,
+            },
+            _ => Self::Spot {
+                intensity: f32::decode(reader)?,
+                color: Srgba::decode(reader)?,
+                position: Vec3::decode(reader)?,
+                direction: Vec3::decode(reader)?,
+                cutoff: radians(f32::decode(reader)?),
+                attenuation: Attenuation::decode(reader)?,
+            },
+        })
+    }
+}
@@ -57,6 +57,14 @@ mod mesh;
 #[doc(inline)]
 pub use mesh::*;
 
+mod morph_mesh;
+#[doc(inline)]
+pub use morph_mesh::*;
+
+mod vertex_cache_mesh;
+#[doc(inline)]
+pub use vertex_cache_mesh::*;
+
 mod instanced_mesh;
 #[doc(inline)]
 pub use instanced_mesh::*;
@@ -85,6 +93,34 @@ mod circle;
 #[doc(inline)]
 pub use circle::*;
 
+mod lod;
+#[doc(inline)]
+pub use lod::*;
+
+mod mesh_processing;
+#[doc(inline)]
+pub use mesh_processing::*;
+
+mod primitives;
+#[doc(inline)]
+pub use primitives::*;
+
+mod voxelizer;
+#[doc(inline)]
+pub use voxelizer::*;
+
+mod triangle_bvh;
+#[doc(inline)]
+pub use triangle_bvh::*;
+
+mod vertex_format;
+#[doc(inline)]
+pub use vertex_format::*;
+
+mod meshlet;
+#[doc(inline)]
+pub use meshlet::*;
+
 use crate::core::*;
 use crate::renderer::*;
 
@@ -104,6 +140,8 @@ pub use three_d_asset::{
 /// - tangent: `out vec3 tang;`
 /// - bitangent: `out vec3 bitang;`
 /// - uv coordinates: `out vec2 uvs;` (must be flipped in v compared to standard uv coordinates, ie. do `uvs = vec2(uvs.x, 1.0 - uvs.y);` in the vertex shader or do the flip before constructing the uv coordinates vertex buffer)
+/// - secondary uv coordinates: `out vec2 uvs1;` (flipped in the same way as the uv coordinates and equal to the uv coordinates if the geometry does not have secondary uv coordinates)
+/// - texture layer: `flat out float texture_layer;` (the layer of a texture array to sample, see [Instances::texture_layers], and zero if the geometry does not have texture layers)
 /// - color: `out vec4 col;`
 ///
 pub trait Geometry {
@@ -263,46 +301,77 @@ struct BaseMesh {
     normals: Option<VertexBuffer>,
     tangents: Option<VertexBuffer>,
     uvs: Option<VertexBuffer>,
+    uvs1: Option<VertexBuffer>,
     colors: Option<VertexBuffer>,
 }
 
 impl BaseMesh {
     pub fn new(context: &Context, cpu_mesh: &CpuMesh) -> Self {
+        Self::new_with_format(context, cpu_mesh, VertexFormat::FULL).0
+    }
+
+    ///
+    /// Returns the mesh with the attributes stored in the given format together with the transformation which decodes the stored positions.
+    ///
+    pub fn new_with_format(
+        context: &Context,
+        cpu_mesh: &CpuMesh,
+        format: VertexFormat,
+    ) -> (Self, Mat4) {
         #[cfg(debug_assertions)]
         cpu_mesh.validate().expect("invalid cpu mesh");
 
-        Self {
+        let (positions, position_decoding) =
+            pack_positions(context, &cpu_mesh.positions.to_f32(), format.positions);
+        let base_mesh = Self {
             indices: match &cpu_mesh.indices {
                 Indices::U8(ind) => Some(ElementBuffer::new_with_data(context, ind)),
                 Indices::U16(ind) => Some(ElementBuffer::new_with_data(context, ind)),
                 Indices::U32(ind) => Some(ElementBuffer::new_with_data(context, ind)),
                 Indices::None => None,
             },
-            positions: VertexBuffer::new_with_data(context, &cpu_mesh.positions.to_f32()),
-            normals: cpu_mesh
-                .normals
-                .as_ref()
-                .map(|data| VertexBuffer::new_with_data(context, data)),
-            tangents: cpu_mesh
-                .tangents
-                .as_ref()
-                .map(|data| VertexBuffer::new_with_data(context, data)),
-            uvs: cpu_mesh.uvs.as_ref().map(|data| {
-                VertexBuffer::new_with_data(
+            positions,
+            normals: cpu_mesh.normals.as_ref().map(|data| {
+                pack(
+                    context,
+                    &data.iter().map(|n| [n.x, n.y, n.z]).collect::<Vec<_>>(),
+                    format.normals,
+                    true,
+                )
+            }),
+            tangents: cpu_mesh.tangents.as_ref().map(|data| {
+                pack(
                     context,
                     &data
                         .iter()
-                        .map(|uv| vec2(uv.x, 1.0 - uv.y))
+                        .map(|t| [t.x, t.y, t.z, t.w])
                         .collect::<Vec<_>>(),
+                    format.tangents,
+                    true,
                 )
             }),
+            uvs: cpu_mesh.uvs.as_ref().map(|data| {
+                pack(
+                    context,
+                    &data.iter().map(|uv| [uv.x, 1.0 - uv.y]).collect::<Vec<_>>(),
+                    format.uvs,
+                    false,
+                )
+            }),
+            uvs1: None,
             colors: cpu_mesh.colors.as_ref().map(|data| {
-                VertexBuffer::new_with_data(
+                pack(
                     context,
-                    &data.iter().map(|c| c.to_linear_srgb()).collect::<Vec<_>>(),
+                    &data
+                        .iter()
+                        .map(|c| c.to_linear_srgb().into())
+                        .collect::<Vec<_>>(),
+                    format.colors,
+                    false,
                 )
             }),
-        }
+        };
+        (base_mesh, position_decoding)
     }
 
     pub fn draw(
@@ -385,6 +454,11 @@ impl BaseMesh {
                     )
                 }),
             );
+            if let Some(uvs1) = &self.uvs1 {
+                if program.requires_attribute("uv_coordinates1") {
+                    program.use_vertex_attribute("uv_coordinates1", uvs1);
+                }
+            }
         }
 
         if attributes.color {
@@ -14,6 +14,7 @@ pub struct InstancedMesh {
     instance_buffers: RwLock<(HashMap<String, InstanceBuffer>, Vec3)>,
     aabb: AxisAlignedBoundingBox,
     aabb_local: AxisAlignedBoundingBox,
+    aabb_margin: f32,
     transformation: Mat4,
     current_transformation: Mat4,
     animation: Option<Box<dyn Fn(f32) -> Mat4 + Send + Sync>>,
@@ -34,6 +35,7 @@ impl InstancedMesh {
             instance_buffers: RwLock::new((Default::default(), vec3(0.0, 0.0, 0.0))),
             aabb,
             aabb_local: aabb,
+            aabb_margin: 0.0,
             transformation: Mat4::identity(),
             current_transformation: Mat4::identity(),
             animation: None,
@@ -85,10 +87,32 @@ impl InstancedMesh {
         self.update_instance_buffers(None);
     }
 
+    ///
+    /// Returns the margin which the axis aligned bounding box of each instance is expanded with in all directions, see [Self::set_aabb_margin].
+    ///
+    pub fn aabb_margin(&self) -> f32 {
+        self.aabb_margin
+    }
+
+    ///
+    /// Sets a margin, in the local space of the mesh, which the axis aligned bounding box of each instance is conservatively expanded with in all directions.
+    /// Use this if the vertices are moved in the vertex shader, since such a deformation is not known on the CPU,
+    /// and the instances would otherwise be culled or not picked when they are deformed outside of their original bounds.
+    ///
+    pub fn set_aabb_margin(&mut self, margin: f32) {
+        self.aabb_margin = margin.max(0.0);
+        self.update_aabb();
+    }
+
     fn update_aabb(&mut self) {
+        let mut aabb_local = self.aabb_local;
+        if self.aabb_margin > 0.0 && !aabb_local.is_empty() {
+            let margin = vec3(self.aabb_margin, self.aabb_margin, self.aabb_margin);
+            aabb_local.expand(&[aabb_local.min() - margin, aabb_local.max() + margin]);
+        }
         let mut aabb = AxisAlignedBoundingBox::EMPTY;
         for transformation in self.instances.transformations.iter() {
-            let mut aabb2 = self.aabb_local;
+            let mut aabb2 = aabb_local;
             aabb2.transform(&(transformation * self.transformation));
             aabb.expand_with_aabb(&aabb2);
         }
@@ -200,6 +224,16 @@ impl InstancedMesh {
                 InstanceBuffer::new_with_data(&self.context, &ordered_instance_colors),
             );
         }
+        if let Some(texture_layers) = &self.instances.texture_layers {
+            let ordered_texture_layers = indices
+                .iter()
+                .map(|i| texture_layers[*i] as f32)
+                .collect::<Vec<_>>();
+            instance_buffers.insert(
+                "instance_texture_layer".to_string(),
+                InstanceBuffer::new_with_data(&self.context, &ordered_texture_layers),
+            );
+        }
     }
 }
 
@@ -236,7 +270,7 @@ impl Geometry for InstancedMesh {
                 return;
             }
         }
-        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        camera.use_uniform_block(program);
         program.use_uniform("modelMatrix", self.current_transformation);
 
         for attribute_name in [
@@ -247,6 +281,7 @@ impl Geometry for InstancedMesh {
             "tex_transform_row1",
             "tex_transform_row2",
             "instance_color",
+            "instance_texture_layer",
         ] {
             if program.requires_attribute(attribute_name) {
                 program.use_instance_attribute(
@@ -268,7 +303,7 @@ impl Geometry for InstancedMesh {
     fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
         let instance_buffers = &self.instance_buffers.read().unwrap().0;
         format!(
-            "{}{}{}{}{}{}{}{}{}",
+            "{}{}{}{}{}{}{}{}{}{}{}",
             if required_attributes.normal {
                 "#define USE_NORMALS\n"
             } else {
@@ -304,7 +339,13 @@ impl Geometry for InstancedMesh {
             } else {
                 ""
             },
+            if required_attributes.uv && instance_buffers.contains_key("instance_texture_layer") {
+                "#define USE_INSTANCE_TEXTURE_LAYERS\n"
+            } else {
+                ""
+            },
             include_str!("../../core/shared.frag"),
+            CAMERA_UNIFORM_BLOCK_SOURCE,
             include_str!("shaders/mesh.vert"),
         )
     }
@@ -337,6 +378,9 @@ impl Geometry for InstancedMesh {
         if required_attributes.uv && instance_buffers.contains_key("tex_transform_row1") {
             id |= 0b1u16 << 6;
         }
+        if required_attributes.uv && instance_buffers.contains_key("instance_texture_layer") {
+            id |= 0b1u16 << 9;
+        }
         id
     }
 
@@ -396,6 +440,9 @@ pub struct Instances {
     pub texture_transformations: Option<Vec<Mat3>>,
     /// Colors multiplied onto the base color of each instance.
     pub colors: Option<Vec<Srgba>>,
+    /// The layer of a texture array which is sampled for each instance, for example by a [TextureArrayMaterial],
+    /// so that many differently textured instances can be rendered in one draw call.
+    pub texture_layers: Option<Vec<u32>>,
 }
 
 impl Instances {
@@ -423,6 +470,10 @@ impl Instances {
         )?;
         buffer_check(Some(self.transformations.len()), "transformations")?;
         buffer_check(self.colors.as_ref().map(|b| b.len()), "colors")?;
+        buffer_check(
+            self.texture_layers.as_ref().map(|b| b.len()),
+            "texture layers",
+        )?;
 
         Ok(())
     }
@@ -0,0 +1,137 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A level-of-detail (LOD) geometry which consists of several versions of the same geometry with decreasing level of detail,
+/// and which renders the version that is appropriate for the distance from the camera to the center of the geometry.
+///
+/// Each level has a maximum distance and the first level with a maximum distance larger than the current distance is rendered.
+/// If the distance is larger than the maximum distance of all levels, nothing is rendered.
+/// All levels should require the same vertex attributes, since [Geometry::id] and [Geometry::vertex_shader_source] always use the first level.
+///
+pub struct LodGeometry<G: Geometry> {
+    levels: Vec<(G, f32)>,
+}
+
+impl<G: Geometry> LodGeometry<G> {
+    ///
+    /// Creates a new level-of-detail geometry from a list of geometries and the maximum distance from the camera at which each of them is rendered.
+    /// The levels are sorted by the maximum distance, so the first level should be the one with the highest detail.
+    /// Use [f32::INFINITY] as the maximum distance of the last level to always render something.
+    ///
+    /// # Panic
+    /// Will panic if no levels are given.
+    ///
+    pub fn new(levels: Vec<(G, f32)>) -> Self {
+        assert!(
+            !levels.is_empty(),
+            "a level-of-detail geometry must have at least one level"
+        );
+        let mut levels = levels;
+        levels.sort_by(|a, b| a.1.total_cmp(&b.1));
+        Self { levels }
+    }
+
+    ///
+    /// Returns the levels together with the maximum distance from the camera at which they are rendered.
+    ///
+    pub fn levels(&self) -> &[(G, f32)] {
+        &self.levels
+    }
+
+    ///
+    /// Returns the index of the level which is rendered when viewed from the given camera or `None` if the geometry is too far away to be rendered.
+    ///
+    pub fn level_index(&self, camera: &Camera) -> Option<usize> {
+        let aabb = self.aabb();
+        if aabb.is_empty() {
+            return None;
+        }
+        let distance = aabb.center().distance(*camera.position());
+        self.levels.iter().position(|(_, max)| distance < *max)
+    }
+
+    fn level(&self, camera: &Camera) -> Option<&G> {
+        self.level_index(camera).map(|i| &self.levels[i].0)
+    }
+}
+
+impl LodGeometry<Mesh> {
+    ///
+    /// Creates a new level-of-detail mesh from a [CpuMesh] where the lower levels are generated using [CpuMeshProcessing::simplify].
+    /// The first level is the given mesh and each of the following levels has half the number of triangles of the previous level.
+    /// The number of levels is given by the number of maximum distances.
+    ///
+    /// # Panic
+    /// Will panic if no maximum distances are given.
+    ///
+    pub fn from_cpu_mesh(context: &Context, cpu_mesh: &CpuMesh, max_distances: &[f32]) -> Self {
+        let mut levels = Vec::new();
+        let mut cpu_mesh = cpu_mesh.clone();
+        for (i, max_distance) in max_distances.iter().enumerate() {
+            if i > 0 {
+                cpu_mesh = cpu_mesh.simplify(cpu_mesh.triangle_count() / 2);
+            }
+            levels.push((Mesh::new(context, &cpu_mesh), *max_distance));
+        }
+        Self::new(levels)
+    }
+}
+
+impl<G: Geometry> Geometry for LodGeometry<G> {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        if let Some(geometry) = self.level(camera) {
+            geometry.draw(camera, program, render_states, attributes)
+        }
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        self.levels[0].0.vertex_shader_source(required_attributes)
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        self.levels[0].0.id(required_attributes)
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        if let Some(geometry) = self.level(camera) {
+            geometry.render_with_material(material, camera, lights)
+        }
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        if let Some(geometry) = self.level(camera) {
+            geometry.render_with_effect(material, camera, lights, color_texture, depth_texture)
+        }
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        self.levels
+            .iter()
+            .for_each(|(g, _)| aabb.expand_with_aabb(&g.aabb()));
+        aabb
+    }
+
+    fn animate(&mut self, time: f32) {
+        self.levels.iter_mut().for_each(|(g, _)| g.animate(time));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::core::*;
 use crate::renderer::*;
 
-use super::BaseMesh;
+use super::{fill_packed, fill_packed_positions, pack, BaseMesh};
 
 ///
 /// A triangle mesh [Geometry].
@@ -9,9 +9,13 @@ use super::BaseMesh;
 pub struct Mesh {
     base_mesh: BaseMesh,
     context: Context,
+    format: VertexFormat,
+    position_decoding: Mat4,
     aabb: AxisAlignedBoundingBox,
+    aabb_margin: f32,
     transformation: Mat4,
     current_transformation: Mat4,
+    previous_transformation: Option<Mat4>,
     animation: Option<Box<dyn Fn(f32) -> Mat4 + Send + Sync>>,
 }
 
@@ -21,13 +25,26 @@ impl Mesh {
     /// All data in the [CpuMesh] is transfered to the GPU, so make sure to remove all unnecessary data from the [CpuMesh] before calling this method.
     ///
     pub fn new(context: &Context, cpu_mesh: &CpuMesh) -> Self {
+        Self::new_with_format(context, cpu_mesh, VertexFormat::default())
+    }
+
+    ///
+    /// Creates a new triangle mesh from the given [CpuMesh] where the vertex attributes are packed into the given format,
+    /// for example [VertexFormat::COMPACT] to use less than half the memory of the default format.
+    ///
+    pub fn new_with_format(context: &Context, cpu_mesh: &CpuMesh, format: VertexFormat) -> Self {
         let aabb = cpu_mesh.compute_aabb();
+        let (base_mesh, position_decoding) = BaseMesh::new_with_format(context, cpu_mesh, format);
         Self {
             context: context.clone(),
-            base_mesh: BaseMesh::new(context, cpu_mesh),
+            base_mesh,
+            format,
+            position_decoding,
             aabb,
+            aabb_margin: 0.0,
             transformation: Mat4::identity(),
             current_transformation: Mat4::identity(),
+            previous_transformation: None,
             animation: None,
         }
     }
@@ -69,6 +86,36 @@ impl Mesh {
         self.current_transformation = transformation;
     }
 
+    ///
+    /// Returns the local to world transformation, including the transformation from the animation (see [Self::set_animation]),
+    /// at the time of the last call to [Self::update_previous_transformation], usually the transformation in the previous frame.
+    /// Before the first call, this is the current transformation.
+    ///
+    pub fn previous_transformation(&self) -> Mat4 {
+        self.previous_transformation
+            .unwrap_or(self.current_transformation)
+    }
+
+    ///
+    /// Stores the current local to world transformation, including the transformation from the animation, as the [Self::previous_transformation].
+    /// Call this once each frame after the velocity of the mesh has been rendered, which [MotionVectors::render_meshes] does automatically.
+    ///
+    pub fn update_previous_transformation(&mut self) {
+        self.previous_transformation = Some(self.current_transformation);
+    }
+
+    ///
+    /// Returns the transformation from a world space position on the mesh in this frame to the world space position of the same point
+    /// in the previous frame, as given by [Self::previous_transformation], which is the [VelocityMaterial::motion] of this mesh.
+    ///
+    pub fn motion(&self) -> Mat4 {
+        self.previous_transformation()
+            * self
+                .current_transformation
+                .invert()
+                .unwrap_or(Mat4::identity())
+    }
+
     ///
     /// Specifies a function which takes a time parameter as input and returns a transformation that should be applied to this mesh at the given time.
     /// To actually animate this mesh, call [Geometry::animate] at each frame which in turn evaluates the animation function defined by this method.
@@ -85,7 +132,8 @@ impl Mesh {
         self.base_mesh.positions.vertex_count()
     }
 
-    /// Updates the vertex positions of the mesh.
+    /// Updates the vertex positions of the mesh, for example after deforming the mesh on the CPU.
+    /// The axis aligned bounding box is updated to contain the new positions, so the mesh is still culled and picked correctly.
     ///
     /// # Panics
     ///
@@ -94,7 +142,29 @@ impl Mesh {
         if positions.len() as u32 != self.vertex_count() {
             panic!("Failed updating positions: The number of positions {} does not match the number of vertices {} in the mesh.", positions.len(), self.vertex_count())
         }
-        self.base_mesh.positions.fill(positions);
+        // The positions are stored in the same format as before, so the existing buffer is refilled instead of allocating a new one every update
+        self.position_decoding = fill_packed_positions(
+            &mut self.base_mesh.positions,
+            positions,
+            self.format.positions,
+        );
+        self.aabb = AxisAlignedBoundingBox::new_with_positions(positions);
+    }
+
+    ///
+    /// Returns the margin which the axis aligned bounding box is expanded with in all directions, see [Self::set_aabb_margin].
+    ///
+    pub fn aabb_margin(&self) -> f32 {
+        self.aabb_margin
+    }
+
+    ///
+    /// Sets a margin, in the local space of the mesh, which the axis aligned bounding box is conservatively expanded with in all directions.
+    /// Use this if the vertices are moved in the vertex shader, since such a deformation is not known on the CPU,
+    /// and the mesh would otherwise be culled or not picked when it is deformed outside of its original bounds.
+    ///
+    pub fn set_aabb_margin(&mut self, margin: f32) {
+        self.aabb_margin = margin.max(0.0);
     }
 
     ///
@@ -107,12 +177,32 @@ impl Mesh {
         if normals.len() as u32 != self.vertex_count() {
             panic!("Failed updating normals: The number of normals {} does not match the number of vertices {} in the mesh.", normals.len(), self.vertex_count())
         }
-
-        if let Some(normal_buffer) = &mut self.base_mesh.normals {
-            normal_buffer.fill(normals);
+        let normals = normals.iter().map(|n| [n.x, n.y, n.z]).collect::<Vec<_>>();
+        if let Some(buffer) = &mut self.base_mesh.normals {
+            fill_packed(buffer, &normals, self.format.normals, true);
         } else {
-            self.base_mesh.normals = Some(VertexBuffer::new_with_data(&self.context, normals));
+            self.base_mesh.normals = Some(pack(&self.context, &normals, self.format.normals, true));
+        }
+    }
+
+    ///
+    /// Sets the secondary uv coordinates of the mesh, for example the uv coordinates of a lightmap or a detail texture,
+    /// or the uv coordinates from the `TEXCOORD_1` attribute of a glTF file, see [Model::set_secondary_uvs].
+    /// The secondary uv coordinates are used when sampling a texture with a [Texture2DRef::uv_channel] of 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of uv coordinates does not match the number of vertices in the mesh.
+    pub fn set_secondary_uvs(&mut self, uvs: &[Vec2]) {
+        if uvs.len() as u32 != self.vertex_count() {
+            panic!("Failed setting secondary uv coordinates: The number of uv coordinates {} does not match the number of vertices {} in the mesh.", uvs.len(), self.vertex_count())
         }
+        self.base_mesh.uvs1 = Some(pack(
+            &self.context,
+            &uvs.iter().map(|uv| [uv.x, 1.0 - uv.y]).collect::<Vec<_>>(),
+            self.format.uvs,
+            false,
+        ));
     }
 }
 
@@ -128,6 +218,10 @@ impl<'a> IntoIterator for &'a Mesh {
 impl Geometry for Mesh {
     fn aabb(&self) -> AxisAlignedBoundingBox {
         let mut aabb = self.aabb;
+        if self.aabb_margin > 0.0 && !aabb.is_empty() {
+            let margin = vec3(self.aabb_margin, self.aabb_margin, self.aabb_margin);
+            aabb.expand(&[aabb.min() - margin, aabb.max() + margin]);
+        }
         aabb.transform(&self.current_transformation);
         aabb
     }
@@ -154,8 +248,9 @@ impl Geometry for Mesh {
             }
         }
 
-        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        camera.use_uniform_block(program);
         program.use_uniform("modelMatrix", self.current_transformation);
+        program.use_uniform("positionDecoding", self.position_decoding);
 
         self.base_mesh
             .draw(program, render_states, camera, attributes);
@@ -163,7 +258,7 @@ impl Geometry for Mesh {
 
     fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
         format!(
-            "{}{}{}{}{}{}",
+            "#define USE_POSITION_DECODING\n{}{}{}{}{}{}{}",
             if required_attributes.normal {
                 "#define USE_NORMALS\n"
             } else {
@@ -175,7 +270,11 @@ impl Geometry for Mesh {
                 ""
             },
             if required_attributes.uv {
-                "#define USE_UVS\n"
+                if self.base_mesh.uvs1.is_some() {
+                    "#define USE_UVS\n#define USE_SECONDARY_UVS\n"
+                } else {
+                    "#define USE_UVS\n"
+                }
             } else {
                 ""
             },
@@ -185,11 +284,14 @@ impl Geometry for Mesh {
                 ""
             },
             include_str!("../../core/shared.frag"),
+            CAMERA_UNIFORM_BLOCK_SOURCE,
             include_str!("shaders/mesh.vert"),
         )
     }
 
     fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        // Bit 15 and 4 identify a mesh, bit 0-3 are the normals, tangents, uv coordinates and colors
+        // and bit 13, which is not used by any other geometry, is the secondary uv coordinates
         let mut id = 0b1u16 << 15 | 0b1u16 << 4;
         if required_attributes.normal {
             id |= 0b1u16;
@@ -203,6 +305,9 @@ impl Geometry for Mesh {
         if required_attributes.color && self.base_mesh.colors.is_some() {
             id |= 0b1u16 << 3;
         }
+        if required_attributes.uv && self.base_mesh.uvs1.is_some() {
+            id |= 0b1u16 << 13;
+        }
         id
     }
 
@@ -0,0 +1,1022 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::collections::HashMap;
+
+///
+/// Geometry processing functionality for a [CpuMesh].
+///
+pub trait CpuMeshProcessing {
+    ///
+    /// Returns a simplified version of this mesh with approximately the given number of triangles,
+    /// for example to generate the lower levels of a [LodGeometry].
+    ///
+    /// The mesh is simplified by repeatedly collapsing the edge which changes the shape the least, measured using quadric error metrics.
+    /// The vertices on the boundary of the mesh and on seams, ie. where vertices at the same position have different normals, uv coordinates or colors, are not moved,
+    /// so the result can contain more triangles than requested if the mesh contains many of those.
+    /// The normals, tangents, uv coordinates and colors of the remaining vertices are kept as they are.
+    ///
+    fn simplify(&self, target_triangle_count: usize) -> CpuMesh;
+
+    ///
+    /// Returns a smoother version of this mesh with four times as many triangles using one step of Loop subdivision.
+    /// Each triangle is split into four triangles and the vertices are moved to a weighted average of the neighbouring vertices,
+    /// where the boundary of the mesh is smoothed as a curve. Vertices at the same position are treated as one vertex, so seams do not open up.
+    ///
+    /// The uv coordinates and colors of the new vertices are interpolated, while the normals and tangents, if present, are computed again.
+    ///
+    fn subdivide(&self) -> CpuMesh;
+
+    ///
+    /// Computes smooth per vertex normals where the normal of each triangle is weighted by the angle of the triangle at the vertex,
+    /// which, unlike weighting by area, does not depend on how the surface is triangulated.
+    /// Vertices at the same position get the same normal, so use [CpuMeshProcessing::to_flat_shaded] first to keep the edges between the triangles sharp.
+    /// It will override the current normals if they already exist.
+    ///
+    fn compute_angle_weighted_normals(&mut self);
+
+    ///
+    /// Computes per vertex tangents for normal mapping following the conventions of [MikkTSpace](http://www.mikktspace.com/), which is used by most tools that bake normal maps,
+    /// ie. the tangents are weighted by the angle of each triangle, orthogonalized against the normals and the handedness of the tangent space is stored in the `w` component.
+    /// The normals are computed using [CpuMeshProcessing::compute_angle_weighted_normals] if the mesh does not have normals.
+    /// It will override the current tangents if they already exist.
+    ///
+    /// # Panic
+    /// Will panic if the mesh does not have uv coordinates.
+    ///
+    fn compute_mikktspace_tangents(&mut self);
+
+    ///
+    /// Returns a copy of this mesh where the triangles do not share any vertices and all vertices of a triangle have the normal of the triangle,
+    /// so the mesh looks faceted when rendered. The uv coordinates and colors are kept and the tangents are computed again if present.
+    ///
+    fn to_flat_shaded(&self) -> CpuMesh;
+
+    ///
+    /// Returns a copy of this mesh where the vertices closer to each other than the given tolerance are snapped to the same position and
+    /// vertices which then have the same attributes are merged, which for example closes the cracks in tessellations of CAD models where each face is tessellated on its own.
+    /// Triangles which become degenerate are removed and T-junctions, ie. vertices lying on an edge of a neighbouring triangle without being a vertex of that triangle,
+    /// are fixed by splitting the triangle at the vertex.
+    ///
+    /// Vertices at the same position but with different attributes, for example on uv seams, are kept as separate vertices.
+    /// The computations are done in double precision and the positions are kept in double precision if they are given in double precision,
+    /// so that meshes with large coordinates, for example in geographic coordinates, can be welded with a small tolerance.
+    ///
+    fn weld(&self, tolerance: f32) -> CpuMesh;
+}
+
+impl CpuMeshProcessing for CpuMesh {
+    fn simplify(&self, target_triangle_count: usize) -> CpuMesh {
+        let (mesh, mut triangles) = weld_identical_vertices(self);
+        let positions = mesh.positions.to_f32();
+        let vertex_count = positions.len();
+
+        let (groups, group_count) = position_groups(&positions);
+        let mut group_sizes = vec![0; group_count];
+        groups.iter().for_each(|g| group_sizes[*g] += 1);
+
+        // Vertices on a seam or on the boundary are locked
+        let mut locked = groups
+            .iter()
+            .map(|g| group_sizes[*g] > 1)
+            .collect::<Vec<_>>();
+        let mut edge_count: HashMap<(usize, usize), u32> = HashMap::new();
+        for t in triangles.iter() {
+            for i in 0..3 {
+                let (a, b) = (groups[t[i]], groups[t[(i + 1) % 3]]);
+                *edge_count.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+            }
+        }
+        let mut locked_groups = vec![false; group_sizes.len()];
+        for ((a, b), count) in edge_count {
+            if count == 1 {
+                locked_groups[a] = true;
+                locked_groups[b] = true;
+            }
+        }
+        for v in 0..vertex_count {
+            locked[v] |= locked_groups[groups[v]];
+        }
+
+        let mut quadrics = vec![Quadric::default(); group_sizes.len()];
+        let mut adjacent = vec![Vec::new(); vertex_count];
+        for (i, t) in triangles.iter().enumerate() {
+            let q = Quadric::from_triangle(positions[t[0]], positions[t[1]], positions[t[2]]);
+            for v in t {
+                adjacent[*v].push(i);
+            }
+            for g in t.iter().map(|v| groups[*v]) {
+                quadrics[g].add(&q);
+            }
+        }
+
+        let mut alive = vec![true; triangles.len()];
+        let mut triangle_count = triangles.len();
+        while triangle_count > target_triangle_count {
+            let mut candidates = Vec::new();
+            for (i, t) in triangles.iter().enumerate() {
+                if !alive[i] {
+                    continue;
+                }
+                for j in 0..3 {
+                    for (from, to) in [(t[j], t[(j + 1) % 3]), (t[(j + 1) % 3], t[j])] {
+                        if !locked[from] {
+                            let mut q = quadrics[groups[from]];
+                            q.add(&quadrics[groups[to]]);
+                            candidates.push((q.error(positions[to]), from, to));
+                        }
+                    }
+                }
+            }
+            candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut touched = vec![false; vertex_count];
+            let mut collapsed = false;
+            for (_, from, to) in candidates {
+                if triangle_count <= target_triangle_count {
+                    break;
+                }
+                if touched[from] || touched[to] {
+                    continue;
+                }
+                if flips_triangles(&positions, &triangles, &alive, &adjacent[from], from, to) {
+                    continue;
+                }
+                for t in std::mem::take(&mut adjacent[from]) {
+                    if !alive[t] {
+                        continue;
+                    }
+                    triangles[t].iter().for_each(|v| touched[*v] = true);
+                    if triangles[t].contains(&to) {
+                        alive[t] = false;
+                        triangle_count -= 1;
+                    } else {
+                        triangles[t].iter_mut().for_each(|v| {
+                            if *v == from {
+                                *v = to
+                            }
+                        });
+                        adjacent[to].push(t);
+                    }
+                }
+                let q = quadrics[groups[from]];
+                quadrics[groups[to]].add(&q);
+                touched[from] = true;
+                touched[to] = true;
+                collapsed = true;
+            }
+            if !collapsed {
+                break;
+            }
+        }
+
+        let triangles = triangles
+            .into_iter()
+            .zip(alive)
+            .filter_map(|(t, alive)| alive.then_some(t))
+            .collect::<Vec<_>>();
+        compact(&mesh, &triangles)
+    }
+
+    fn subdivide(&self) -> CpuMesh {
+        let (mesh, triangles) = weld_identical_vertices(self);
+        let positions = mesh.positions.to_f32();
+        let (groups, group_count) = position_groups(&positions);
+
+        // The opposite vertices of each edge between two position groups
+        let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for t in triangles.iter() {
+            for i in 0..3 {
+                let (a, b) = (groups[t[i]], groups[t[(i + 1) % 3]]);
+                edges
+                    .entry((a.min(b), a.max(b)))
+                    .or_default()
+                    .push(t[(i + 2) % 3]);
+            }
+        }
+
+        // The new positions of the existing vertices
+        let mut group_positions = vec![Vec3::zero(); group_count];
+        positions
+            .iter()
+            .zip(groups.iter())
+            .for_each(|(p, g)| group_positions[*g] = *p);
+        let mut neighbours = vec![Vec::new(); group_count];
+        let mut boundary_neighbours = vec![Vec::new(); group_count];
+        for ((a, b), opposite) in edges.iter() {
+            neighbours[*a].push(*b);
+            neighbours[*b].push(*a);
+            if opposite.len() == 1 {
+                boundary_neighbours[*a].push(*b);
+                boundary_neighbours[*b].push(*a);
+            }
+        }
+        let smoothed_positions = (0..group_count)
+            .map(|g| {
+                let p = group_positions[g];
+                let sum = |n: &[usize]| n.iter().fold(Vec3::zero(), |s, i| s + group_positions[*i]);
+                if !boundary_neighbours[g].is_empty() {
+                    if boundary_neighbours[g].len() == 2 {
+                        p * 0.75 + sum(&boundary_neighbours[g]) * 0.125
+                    } else {
+                        p
+                    }
+                } else {
+                    let n = neighbours[g].len() as f32;
+                    let beta = if neighbours[g].len() == 3 {
+                        3.0 / 16.0
+                    } else {
+                        3.0 / (8.0 * n)
+                    };
+                    p * (1.0 - n * beta) + sum(&neighbours[g]) * beta
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // The existing vertices followed by a new vertex on each edge
+        let mut new_positions = groups
+            .iter()
+            .map(|g| smoothed_positions[*g])
+            .collect::<Vec<_>>();
+        let mut new_uvs = mesh.uvs.clone();
+        let mut new_colors = mesh.colors.clone();
+        let mut edge_vertices = HashMap::new();
+        let mut edge_vertex = |a: usize, b: usize| {
+            *edge_vertices
+                .entry((a.min(b), a.max(b)))
+                .or_insert_with(|| {
+                    let (ga, gb) = (groups[a], groups[b]);
+                    let opposite = &edges[&(ga.min(gb), ga.max(gb))];
+                    let (pa, pb) = (group_positions[ga], group_positions[gb]);
+                    new_positions.push(if opposite.len() == 2 {
+                        (pa + pb) * 0.375
+                            + (positions[opposite[0]] + positions[opposite[1]]) * 0.125
+                    } else {
+                        (pa + pb) * 0.5
+                    });
+                    if let Some(uvs) = &mut new_uvs {
+                        uvs.push((uvs[a] + uvs[b]) * 0.5);
+                    }
+                    if let Some(colors) = &mut new_colors {
+                        let (ca, cb) = (colors[a], colors[b]);
+                        let mix = |x: u8, y: u8| (x as u16 + y as u16).div_ceil(2) as u8;
+                        colors.push(Srgba::new(
+                            mix(ca.r, cb.r),
+                            mix(ca.g, cb.g),
+                            mix(ca.b, cb.b),
+                            mix(ca.a, cb.a),
+                        ));
+                    }
+                    new_positions.len() as u32 - 1
+                })
+        };
+        let mut indices = Vec::with_capacity(triangles.len() * 12);
+        for t in triangles.iter() {
+            let [a, b, c] = t.map(|v| v as u32);
+            let ab = edge_vertex(t[0], t[1]);
+            let bc = edge_vertex(t[1], t[2]);
+            let ca = edge_vertex(t[2], t[0]);
+            indices.extend([a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+        }
+
+        let mut result = CpuMesh {
+            positions: Positions::F32(new_positions),
+            indices: Indices::U32(indices),
+            uvs: new_uvs,
+            colors: new_colors,
+            ..Default::default()
+        };
+        if mesh.normals.is_some() {
+            result.compute_normals();
+        }
+        if mesh.tangents.is_some() && result.uvs.is_some() {
+            result.compute_tangents();
+        }
+        result
+    }
+
+    fn compute_angle_weighted_normals(&mut self) {
+        let positions = self.positions.to_f32();
+        let (groups, group_count) = position_groups(&positions);
+        let mut normals = vec![Vec3::zero(); group_count];
+        self.for_each_triangle(|i0, i1, i2| {
+            let t = [i0, i1, i2];
+            let normal = (positions[i1] - positions[i0]).cross(positions[i2] - positions[i0]);
+            if normal.magnitude2() > 0.0 {
+                let normal = normal.normalize();
+                for i in 0..3 {
+                    let angle = corner_angle(&positions, t[i], t[(i + 1) % 3], t[(i + 2) % 3]);
+                    normals[groups[t[i]]] += normal * angle;
+                }
+            }
+        });
+        self.normals = Some(
+            groups
+                .iter()
+                .map(|g| {
+                    let n = normals[*g];
+                    if n.magnitude2() > 0.0 {
+                        n.normalize()
+                    } else {
+                        vec3(0.0, 1.0, 0.0)
+                    }
+                })
+                .collect(),
+        );
+    }
+
+    fn compute_mikktspace_tangents(&mut self) {
+        if self.normals.is_none() {
+            self.compute_angle_weighted_normals();
+        }
+        let uvs = self
+            .uvs
+            .as_ref()
+            .expect("mesh must have uv coordinates to be able to compute tangents");
+        let positions = self.positions.to_f32();
+        let mut tangents = vec![Vec3::zero(); positions.len()];
+        let mut bitangents = vec![Vec3::zero(); positions.len()];
+        self.for_each_triangle(|i0, i1, i2| {
+            let t = [i0, i1, i2];
+            let (e1, e2) = (positions[i1] - positions[i0], positions[i2] - positions[i0]);
+            let (d1, d2) = (uvs[i1] - uvs[i0], uvs[i2] - uvs[i0]);
+            let d = d1.x * d2.y - d2.x * d1.y;
+            if d.abs() <= f32::EPSILON {
+                return;
+            }
+            let tangent = (e1 * d2.y - e2 * d1.y) / d;
+            let bitangent = (e2 * d1.x - e1 * d2.x) / d;
+            for i in 0..3 {
+                let angle = corner_angle(&positions, t[i], t[(i + 1) % 3], t[(i + 2) % 3]);
+                tangents[t[i]] += tangent * angle;
+                bitangents[t[i]] += bitangent * angle;
+            }
+        });
+        let normals = self.normals.as_ref().unwrap();
+        self.tangents = Some(
+            (0..positions.len())
+                .map(|i| {
+                    let n = normals[i];
+                    let mut t = tangents[i] - n * n.dot(tangents[i]);
+                    if t.magnitude2() <= f32::EPSILON * f32::EPSILON {
+                        // No valid uv coordinates, so any direction perpendicular to the normal will do
+                        t = n.cross(if n.x.abs() < 0.9 {
+                            vec3(1.0, 0.0, 0.0)
+                        } else {
+                            vec3(0.0, 1.0, 0.0)
+                        });
+                    }
+                    let t = t.normalize();
+                    // The same handedness as CpuMesh::compute_tangents, since texture rows are flipped when uploaded
+                    let w = if n.cross(t).dot(bitangents[i]) < 0.0 {
+                        1.0
+                    } else {
+                        -1.0
+                    };
+                    t.extend(w)
+                })
+                .collect(),
+        );
+    }
+
+    fn to_flat_shaded(&self) -> CpuMesh {
+        let positions = self.positions.to_f32();
+        let mut vertices = Vec::with_capacity(3 * self.triangle_count());
+        self.for_each_triangle(|i0, i1, i2| vertices.extend([i0, i1, i2]));
+        let normals = vertices
+            .chunks_exact(3)
+            .flat_map(|t| {
+                let n =
+                    (positions[t[1]] - positions[t[0]]).cross(positions[t[2]] - positions[t[0]]);
+                let n = if n.magnitude2() > 0.0 {
+                    n.normalize()
+                } else {
+                    vec3(0.0, 1.0, 0.0)
+                };
+                [n; 3]
+            })
+            .collect();
+        let mut mesh = CpuMesh {
+            positions: Positions::F32(vertices.iter().map(|v| positions[*v]).collect()),
+            indices: Indices::None,
+            normals: Some(normals),
+            tangents: None,
+            uvs: self
+                .uvs
+                .as_ref()
+                .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+            colors: self
+                .colors
+                .as_ref()
+                .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+        };
+        if self.tangents.is_some() && mesh.uvs.is_some() {
+            mesh.compute_mikktspace_tangents();
+        }
+        mesh
+    }
+
+    fn weld(&self, tolerance: f32) -> CpuMesh {
+        let positions = match &self.positions {
+            Positions::F32(p) => p
+                .iter()
+                .map(|p| p.cast::<f64>().unwrap())
+                .collect::<Vec<_>>(),
+            Positions::F64(p) => p.clone(),
+        };
+        let tolerance = tolerance.max(0.0) as f64;
+        let (mut clusters, cluster_positions) = snap_positions(&positions, tolerance);
+
+        let mut triangles = Vec::with_capacity(self.triangle_count());
+        self.for_each_triangle(|i0, i1, i2| {
+            let t = [i0, i1, i2];
+            if (0..3).all(|i| clusters[t[i]] != clusters[t[(i + 1) % 3]]) {
+                triangles.push(t);
+            }
+        });
+
+        // Find the boundary edges and vertices which are candidates for T-junctions
+        let mut edge_count: HashMap<(usize, usize), u32> = HashMap::new();
+        for t in triangles.iter() {
+            for i in 0..3 {
+                let (a, b) = (clusters[t[i]], clusters[t[(i + 1) % 3]]);
+                *edge_count.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+            }
+        }
+        let mut boundary = edge_count
+            .iter()
+            .filter(|(_, count)| **count == 1)
+            .flat_map(|((a, b), _)| [*a, *b])
+            .collect::<Vec<_>>();
+        boundary.sort_by(|a, b| {
+            cluster_positions[*a]
+                .x
+                .total_cmp(&cluster_positions[*b].x)
+                .then(a.cmp(b))
+        });
+        boundary.dedup();
+        let boundary_x = boundary
+            .iter()
+            .map(|c| cluster_positions[*c].x)
+            .collect::<Vec<_>>();
+
+        // Split triangles at the boundary vertices which lie on a boundary edge until there are no T-junctions left
+        let mut normals = self.normals.clone();
+        let mut tangents = self.tangents.clone();
+        let mut uvs = self.uvs.clone();
+        let mut colors = self.colors.clone();
+        let mut result = Vec::with_capacity(triangles.len());
+        while let Some(t) = triangles.pop() {
+            let split = (0..3).find_map(|i| {
+                let (a, b) = (clusters[t[i]], clusters[t[(i + 1) % 3]]);
+                if edge_count.get(&(a.min(b), a.max(b))) != Some(&1) {
+                    return None;
+                }
+                let (pa, pb) = (cluster_positions[a], cluster_positions[b]);
+                let edge = pb - pa;
+                let length2 = edge.magnitude2();
+                let start = boundary_x.partition_point(|x| *x < pa.x.min(pb.x) - tolerance);
+                let end = boundary_x.partition_point(|x| *x <= pa.x.max(pb.x) + tolerance);
+                boundary[start..end]
+                    .iter()
+                    .filter(|c| **c != a && **c != b)
+                    .filter_map(|c| {
+                        let p = cluster_positions[*c];
+                        let s = (p - pa).dot(edge) / length2;
+                        (s > 0.0 && s < 1.0 && (pa + edge * s - p).magnitude() <= tolerance)
+                            .then_some((s, *c))
+                    })
+                    .min_by(|x, y| x.0.total_cmp(&y.0))
+                    .map(|(s, c)| (i, s, c))
+            });
+            if let Some((i, s, c)) = split {
+                let (v0, v1, v2) = (t[i], t[(i + 1) % 3], t[(i + 2) % 3]);
+                let v = clusters.len();
+                let s32 = s as f32;
+                clusters.push(c);
+                if let Some(d) = normals.as_mut() {
+                    let n = d[v0].lerp(d[v1], s32);
+                    d.push(if n.magnitude2() > 0.0 {
+                        n.normalize()
+                    } else {
+                        d[v0]
+                    });
+                }
+                if let Some(d) = tangents.as_mut() {
+                    let t = d[v0].truncate().lerp(d[v1].truncate(), s32);
+                    let t = if t.magnitude2() > 0.0 {
+                        t.normalize()
+                    } else {
+                        d[v0].truncate()
+                    };
+                    d.push(t.extend(d[v0].w));
+                }
+                if let Some(d) = uvs.as_mut() {
+                    d.push(d[v0].lerp(d[v1], s32));
+                }
+                if let Some(d) = colors.as_mut() {
+                    let (c0, c1) = (d[v0], d[v1]);
+                    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * s32).round() as u8;
+                    d.push(Srgba::new(
+                        mix(c0.r, c1.r),
+                        mix(c0.g, c1.g),
+                        mix(c0.b, c1.b),
+                        mix(c0.a, c1.a),
+                    ));
+                }
+                // The halves of the split edge are boundary edges which can contain more T-junctions
+                let (a, b) = (clusters[v0], clusters[v1]);
+                edge_count.insert((a.min(c), a.max(c)), 1);
+                edge_count.insert((c.min(b), c.max(b)), 1);
+                triangles.push([v0, v, v2]);
+                triangles.push([v, v1, v2]);
+            } else {
+                result.push(t);
+            }
+        }
+        result.reverse();
+
+        // Merge the vertices which have the same position and attributes
+        let mut vertex_ids = HashMap::new();
+        let mut vertices = Vec::new();
+        let indices = result
+            .iter()
+            .flatten()
+            .map(|v| {
+                let mut key = vec![[clusters[*v] as u32, 0, 0]];
+                if let Some(d) = &normals {
+                    key.push(position_key(&d[*v]));
+                }
+                if let Some(d) = &tangents {
+                    key.push(position_key(&d[*v].truncate()));
+                    key.push([d[*v].w.to_bits(), 0, 0]);
+                }
+                if let Some(d) = &uvs {
+                    key.push([d[*v].x.to_bits(), d[*v].y.to_bits(), 0]);
+                }
+                if let Some(d) = &colors {
+                    let c = d[*v];
+                    key.push([u32::from_le_bytes([c.r, c.g, c.b, c.a]), 0, 0]);
+                }
+                *vertex_ids.entry(key).or_insert_with(|| {
+                    vertices.push(*v);
+                    vertices.len() as u32 - 1
+                })
+            })
+            .collect::<Vec<_>>();
+        let welded_positions = vertices
+            .iter()
+            .map(|v| cluster_positions[clusters[*v]])
+            .collect::<Vec<_>>();
+        CpuMesh {
+            positions: match self.positions {
+                Positions::F32(_) => Positions::F32(
+                    welded_positions
+                        .iter()
+                        .map(|p| p.cast::<f32>().unwrap())
+                        .collect(),
+                ),
+                Positions::F64(_) => Positions::F64(welded_positions),
+            },
+            indices: Indices::U32(indices),
+            normals: normals.map(|d| vertices.iter().map(|v| d[*v]).collect()),
+            tangents: tangents.map(|d| vertices.iter().map(|v| d[*v]).collect()),
+            uvs: uvs.map(|d| vertices.iter().map(|v| d[*v]).collect()),
+            colors: colors.map(|d| vertices.iter().map(|v| d[*v]).collect()),
+        }
+    }
+}
+
+///
+/// Snaps the positions closer to each other than the given tolerance to the same position and returns the cluster of each position
+/// together with the position of each cluster, which is the first position that was added to the cluster.
+///
+fn snap_positions(positions: &[Vector3<f64>], tolerance: f64) -> (Vec<usize>, Vec<Vector3<f64>>) {
+    let mut cluster_positions = Vec::new();
+    if tolerance <= 0.0 {
+        let mut ids = HashMap::new();
+        let clusters = positions
+            .iter()
+            .map(|p| {
+                // Adding zero turns -0.0 into 0.0
+                let key = [
+                    (p.x + 0.0).to_bits(),
+                    (p.y + 0.0).to_bits(),
+                    (p.z + 0.0).to_bits(),
+                ];
+                *ids.entry(key).or_insert_with(|| {
+                    cluster_positions.push(*p);
+                    cluster_positions.len() - 1
+                })
+            })
+            .collect();
+        return (clusters, cluster_positions);
+    }
+
+    // The cells are relative to the first position to keep the cell indices small for large coordinates
+    let origin = positions.first().copied().unwrap_or(Vector3::zero());
+    let cell = |p: &Vector3<f64>| {
+        let c = (p - origin) / tolerance;
+        [c.x.floor() as i64, c.y.floor() as i64, c.z.floor() as i64]
+    };
+    let mut cells: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+    let clusters = positions
+        .iter()
+        .map(|p| {
+            let [x, y, z] = cell(p);
+            let existing = (-1..=1)
+                .flat_map(|dx| {
+                    (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| [x + dx, y + dy, z + dz]))
+                })
+                .filter_map(|c| cells.get(&c))
+                .flatten()
+                .filter(|c| (cluster_positions[**c] - p).magnitude() <= tolerance)
+                .min()
+                .copied();
+            existing.unwrap_or_else(|| {
+                cluster_positions.push(*p);
+                cells
+                    .entry([x, y, z])
+                    .or_default()
+                    .push(cluster_positions.len() - 1);
+                cluster_positions.len() - 1
+            })
+        })
+        .collect();
+    (clusters, cluster_positions)
+}
+
+///
+/// Returns the angle of the triangle at the vertex `v` where `a` and `b` are the other two vertices.
+///
+fn corner_angle(positions: &[Vec3], v: usize, a: usize, b: usize) -> f32 {
+    let (e1, e2) = (positions[a] - positions[v], positions[b] - positions[v]);
+    e1.cross(e2).magnitude().atan2(e1.dot(e2))
+}
+
+///
+/// Returns the mesh with the normals, and the tangents if the material has a normal map, computed if they are missing,
+/// so that models loaded from, for example, obj files without normals can be rendered with any material.
+///
+pub(crate) fn with_missing_attributes<'a>(
+    cpu_mesh: &'a CpuMesh,
+    cpu_material: Option<&CpuMaterial>,
+) -> std::borrow::Cow<'a, CpuMesh> {
+    let missing_tangents = cpu_material.is_some_and(|m| m.normal_texture.is_some())
+        && cpu_mesh.tangents.is_none()
+        && cpu_mesh.uvs.is_some();
+    if cpu_mesh.normals.is_none() || missing_tangents {
+        let mut cpu_mesh = cpu_mesh.clone();
+        if cpu_mesh.normals.is_none() {
+            cpu_mesh.compute_angle_weighted_normals();
+        }
+        if missing_tangents {
+            cpu_mesh.compute_mikktspace_tangents();
+        }
+        std::borrow::Cow::Owned(cpu_mesh)
+    } else {
+        std::borrow::Cow::Borrowed(cpu_mesh)
+    }
+}
+
+///
+/// Returns whether collapsing the vertex `from` into the vertex `to` flips or degenerates any of the triangles around `from`.
+///
+fn flips_triangles(
+    positions: &[Vec3],
+    triangles: &[[usize; 3]],
+    alive: &[bool],
+    adjacent: &[usize],
+    from: usize,
+    to: usize,
+) -> bool {
+    adjacent
+        .iter()
+        .filter(|t| alive[**t] && !triangles[**t].contains(&to))
+        .any(|t| {
+            let p = triangles[*t].map(|v| positions[v]);
+            let q = triangles[*t].map(|v| {
+                if v == from {
+                    positions[to]
+                } else {
+                    positions[v]
+                }
+            });
+            let before = (p[1] - p[0]).cross(p[2] - p[0]);
+            let after = (q[1] - q[0]).cross(q[2] - q[0]);
+            after.magnitude2() <= 1e-6 * before.magnitude2() || before.dot(after) <= 0.0
+        })
+}
+
+///
+/// Merges vertices which have exactly the same attributes and returns the merged mesh together with the triangles.
+///
+pub(super) fn weld_identical_vertices(cpu_mesh: &CpuMesh) -> (CpuMesh, Vec<[usize; 3]>) {
+    let positions = cpu_mesh.positions.to_f32();
+    let indices = cpu_mesh
+        .indices
+        .to_u32()
+        .unwrap_or_else(|| (0..positions.len() as u32).collect());
+    let mut vertex_ids = HashMap::new();
+    let mut vertices = Vec::new();
+    let remap = (0..positions.len())
+        .map(|v| {
+            let mut key = vec![position_key(&positions[v])];
+            if let Some(normals) = &cpu_mesh.normals {
+                key.push(position_key(&normals[v]));
+            }
+            if let Some(tangents) = &cpu_mesh.tangents {
+                key.push(position_key(&tangents[v].truncate()));
+                key.push([tangents[v].w.to_bits(), 0, 0]);
+            }
+            if let Some(uvs) = &cpu_mesh.uvs {
+                key.push([uvs[v].x.to_bits(), uvs[v].y.to_bits(), 0]);
+            }
+            if let Some(colors) = &cpu_mesh.colors {
+                let c = colors[v];
+                key.push([u32::from_le_bytes([c.r, c.g, c.b, c.a]), 0, 0]);
+            }
+            *vertex_ids.entry(key).or_insert_with(|| {
+                vertices.push(v);
+                vertices.len() - 1
+            })
+        })
+        .collect::<Vec<_>>();
+    let triangles = indices
+        .chunks_exact(3)
+        .map(|t| [0, 1, 2].map(|i| remap[t[i] as usize]))
+        .filter(|t| t[0] != t[1] && t[1] != t[2] && t[0] != t[2])
+        .collect();
+    let mesh = CpuMesh {
+        positions: Positions::F32(vertices.iter().map(|i| positions[*i]).collect::<Vec<_>>()),
+        indices: Indices::None,
+        normals: cpu_mesh
+            .normals
+            .as_ref()
+            .map(|d| vertices.iter().map(|i| d[*i]).collect()),
+        tangents: cpu_mesh
+            .tangents
+            .as_ref()
+            .map(|d| vertices.iter().map(|i| d[*i]).collect()),
+        uvs: cpu_mesh
+            .uvs
+            .as_ref()
+            .map(|d| vertices.iter().map(|i| d[*i]).collect()),
+        colors: cpu_mesh
+            .colors
+            .as_ref()
+            .map(|d| vertices.iter().map(|i| d[*i]).collect()),
+    };
+    (mesh, triangles)
+}
+
+///
+/// Returns a mesh with the given triangles and only the vertices used by the triangles.
+///
+pub(super) fn compact(cpu_mesh: &CpuMesh, triangles: &[[usize; 3]]) -> CpuMesh {
+    let positions = cpu_mesh.positions.to_f32();
+    let mut remap = vec![u32::MAX; positions.len()];
+    let mut vertices = Vec::new();
+    let indices = triangles
+        .iter()
+        .flatten()
+        .map(|v| {
+            if remap[*v] == u32::MAX {
+                remap[*v] = vertices.len() as u32;
+                vertices.push(*v);
+            }
+            remap[*v]
+        })
+        .collect::<Vec<_>>();
+    CpuMesh {
+        positions: Positions::F32(vertices.iter().map(|v| positions[*v]).collect()),
+        indices: Indices::U32(indices),
+        normals: cpu_mesh
+            .normals
+            .as_ref()
+            .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+        tangents: cpu_mesh
+            .tangents
+            .as_ref()
+            .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+        uvs: cpu_mesh
+            .uvs
+            .as_ref()
+            .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+        colors: cpu_mesh
+            .colors
+            .as_ref()
+            .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+    }
+}
+
+///
+/// Returns the position group of each vertex, where vertices at the same position belong to the same group, and the number of groups.
+///
+pub(super) fn position_groups(positions: &[Vec3]) -> (Vec<usize>, usize) {
+    let mut group_ids = HashMap::new();
+    let groups = positions
+        .iter()
+        .map(|p| {
+            let id = group_ids.len();
+            *group_ids.entry(position_key(p)).or_insert(id)
+        })
+        .collect();
+    (groups, group_ids.len())
+}
+
+fn position_key(p: &Vec3) -> [u32; 3] {
+    // Adding zero turns -0.0 into 0.0
+    [
+        (p.x + 0.0).to_bits(),
+        (p.y + 0.0).to_bits(),
+        (p.z + 0.0).to_bits(),
+    ]
+}
+
+///
+/// A symmetric 4x4 matrix which measures the sum of the squared distances from a point to a set of planes.
+///
+#[derive(Clone, Copy, Default)]
+struct Quadric {
+    a: [f64; 10],
+}
+
+impl Quadric {
+    fn from_triangle(p0: Vec3, p1: Vec3, p2: Vec3) -> Self {
+        let n = (p1 - p0).cross(p2 - p0);
+        let area = n.magnitude();
+        if area <= f32::EPSILON {
+            return Self::default();
+        }
+        let n = n / area;
+        let (x, y, z) = (n.x as f64, n.y as f64, n.z as f64);
+        let d = -(n.dot(p0) as f64);
+        let w = area as f64;
+        Self {
+            a: [
+                w * x * x,
+                w * x * y,
+                w * x * z,
+                w * x * d,
+                w * y * y,
+                w * y * z,
+                w * y * d,
+                w * z * z,
+                w * z * d,
+                w * d * d,
+            ],
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        for i in 0..10 {
+            self.a[i] += other.a[i];
+        }
+    }
+
+    fn error(&self, p: Vec3) -> f64 {
+        let (x, y, z) = (p.x as f64, p.y as f64, p.z as f64);
+        let a = &self.a;
+        a[0] * x * x
+            + 2.0 * a[1] * x * y
+            + 2.0 * a[2] * x * z
+            + 2.0 * a[3] * x
+            + a[4] * y * y
+            + 2.0 * a[5] * y * z
+            + 2.0 * a[6] * y
+            + a[7] * z * z
+            + 2.0 * a[8] * z
+            + a[9]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangles(mesh: &CpuMesh) -> Vec<[Vec3; 3]> {
+        let positions = mesh.positions.to_f32();
+        let mut triangles = Vec::new();
+        mesh.for_each_triangle(|i0, i1, i2| {
+            triangles.push([positions[i0], positions[i1], positions[i2]]);
+        });
+        triangles
+    }
+
+    #[test]
+    fn weld_splits_all_t_junctions_on_an_edge() {
+        // A large quad above two small quads, which have three vertices on the bottom edge of the large quad
+        let quad = |x0: f32, y0: f32, x1: f32, y1: f32| {
+            [
+                vec3(x0, y0, 0.0),
+                vec3(x1, y0, 0.0),
+                vec3(x1, y1, 0.0),
+                vec3(x0, y0, 0.0),
+                vec3(x1, y1, 0.0),
+                vec3(x0, y1, 0.0),
+            ]
+        };
+        let mut positions = quad(0.0, 0.0, 3.0, 1.0).to_vec();
+        positions.extend(quad(0.5, -1.0, 1.5, 0.0));
+        positions.extend(quad(1.5, -1.0, 2.5, 0.0));
+        let mesh = CpuMesh {
+            positions: Positions::F32(positions),
+            ..Default::default()
+        };
+
+        let welded = mesh.weld(0.0001);
+        let triangles = triangles(&welded);
+        for x in [0.5, 1.5, 2.5] {
+            let p = vec3(x, 0.0, 0.0);
+            assert!(
+                triangles
+                    .iter()
+                    .filter(|t| t.iter().all(|v| v.y >= 0.0))
+                    .any(|t| t.contains(&p)),
+                "the vertex {:?} is not a vertex of the large quad",
+                p
+            );
+        }
+        for t in triangles.iter() {
+            assert!((t[1] - t[0]).cross(t[2] - t[0]).magnitude() > 0.0);
+        }
+    }
+
+    fn assert_valid(mesh: &CpuMesh) {
+        let vertex_count = mesh.vertex_count();
+        mesh.for_each_triangle(|i0, i1, i2| {
+            assert!(i0 < vertex_count && i1 < vertex_count && i2 < vertex_count);
+        });
+        for t in triangles(mesh) {
+            assert!(
+                (t[1] - t[0]).cross(t[2] - t[0]).magnitude() > 0.0,
+                "degenerate triangle {:?}",
+                t
+            );
+        }
+    }
+
+    fn positions_only(mesh: CpuMesh) -> CpuMesh {
+        CpuMesh {
+            positions: mesh.positions,
+            indices: mesh.indices,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn simplify_reduces_the_triangle_count() {
+        let mesh = positions_only(CpuMesh::sphere(16)).weld(0.0001);
+        let triangle_count = triangles(&mesh).len();
+        let simplified = mesh.simplify(triangle_count / 4);
+        assert_valid(&simplified);
+        let simplified_count = triangles(&simplified).len();
+        assert!(simplified_count <= triangle_count / 4 + 2);
+        assert!(simplified_count >= triangle_count / 8);
+        assert!(simplified.vertex_count() < mesh.vertex_count());
+
+        assert_eq!(
+            triangles(&mesh.simplify(triangle_count)).len(),
+            triangle_count
+        );
+    }
+
+    #[test]
+    fn subdivide_quadruples_the_triangle_count() {
+        let mesh = CpuMesh::cube();
+        let subdivided = mesh.subdivide();
+        assert_valid(&subdivided);
+        assert_eq!(triangles(&subdivided).len(), 4 * triangles(&mesh).len());
+        assert!(subdivided.normals.is_some());
+        assert_eq!(
+            subdivided.uvs.as_ref().map(|uvs| uvs.len()),
+            Some(subdivided.vertex_count())
+        );
+    }
+
+    #[test]
+    fn weld_merges_vertices_at_the_same_position() {
+        let cube = positions_only(CpuMesh::cube());
+        let welded = cube.weld(0.0001);
+        assert_valid(&welded);
+        assert_eq!(welded.vertex_count(), 8);
+        assert_eq!(triangles(&welded).len(), 12);
+
+        // Vertices with different normals are kept apart
+        let welded = CpuMesh::cube().weld(0.0001);
+        assert_valid(&welded);
+        assert_eq!(welded.vertex_count(), 24);
+        assert_eq!(triangles(&welded).len(), 12);
+
+        // Vertices within the tolerance are snapped together and the collapsed triangles are removed
+        let mut positions = cube.positions.to_f32();
+        positions[0] += vec3(0.00001, 0.0, 0.0);
+        let moved = CpuMesh {
+            positions: Positions::F32(positions),
+            indices: cube.indices.clone(),
+            ..Default::default()
+        };
+        assert_eq!(moved.weld(0.0001).vertex_count(), 8);
+        assert_eq!(moved.weld(10.0).vertex_count(), 0);
+    }
+}
@@ -0,0 +1,367 @@
+use super::mesh_processing::position_groups;
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// The default maximum number of vertices in a [Meshlet], which matches the recommended limits of most mesh shader implementations.
+///
+pub const MAX_MESHLET_VERTICES: usize = 64;
+
+///
+/// The default maximum number of triangles in a [Meshlet], which matches the recommended limits of most mesh shader implementations.
+///
+pub const MAX_MESHLET_TRIANGLES: usize = 124;
+
+///
+/// A small cluster of connected triangles of a mesh together with the data needed to cull the whole cluster at once,
+/// which is the building block of GPU-driven rendering pipelines and mesh shaders. Use [Meshlet::build] to split a [CpuMesh] into meshlets.
+///
+/// All positions and directions are in the local space of the mesh.
+///
+#[derive(Clone, Debug)]
+pub struct Meshlet {
+    /// The indices of the vertices of the mesh which are used by this meshlet.
+    pub vertices: Vec<u32>,
+    /// The triangles of this meshlet, where each index refers to an element of [Meshlet::vertices].
+    pub triangles: Vec<[u8; 3]>,
+    /// The center of a sphere which contains all the triangles of this meshlet.
+    pub center: Vec3,
+    /// The radius of a sphere which contains all the triangles of this meshlet.
+    pub radius: f32,
+    /// The apex of the normal cone, see [Meshlet::is_backfacing].
+    pub cone_apex: Vec3,
+    /// The axis of the normal cone, which is the average facing direction of the triangles.
+    pub cone_axis: Vec3,
+    /// The cosine of the angle used when testing whether the meshlet is backfacing, see [Meshlet::is_backfacing].
+    /// If the triangles face in too different directions, the value is larger than 1 so that the meshlet is never backfacing.
+    pub cone_cutoff: f32,
+}
+
+impl Meshlet {
+    ///
+    /// Splits the given mesh into meshlets of at most [MAX_MESHLET_VERTICES] vertices and [MAX_MESHLET_TRIANGLES] triangles.
+    ///
+    pub fn build(cpu_mesh: &CpuMesh) -> Vec<Meshlet> {
+        Self::build_with_limits(cpu_mesh, MAX_MESHLET_VERTICES, MAX_MESHLET_TRIANGLES)
+    }
+
+    ///
+    /// Splits the given mesh into meshlets of at most the given number of vertices and triangles.
+    ///
+    /// Each meshlet is grown from a starting triangle by repeatedly adding the neighbouring triangle which adds the fewest new vertices,
+    /// so that the meshlets are compact and the vertices are shared by as many triangles as possible.
+    /// Vertices at the same position are treated as neighbours, so meshes where the triangles do not share vertices, for example flat shaded meshes, are also supported.
+    /// When a meshlet has no more neighbouring triangles, it is continued with the next triangle in a spatial order of the triangles.
+    ///
+    /// # Panic
+    /// Will panic if the maximum number of vertices is not in the range `[3..256]` or the maximum number of triangles is zero.
+    ///
+    pub fn build_with_limits(
+        cpu_mesh: &CpuMesh,
+        max_vertices: usize,
+        max_triangles: usize,
+    ) -> Vec<Meshlet> {
+        assert!(
+            (3..=256).contains(&max_vertices),
+            "the maximum number of vertices in a meshlet must be between 3 and 256"
+        );
+        assert!(
+            max_triangles > 0,
+            "the maximum number of triangles in a meshlet must be larger than zero"
+        );
+        let positions = cpu_mesh.positions.to_f32();
+        let mut triangles = Vec::with_capacity(cpu_mesh.triangle_count());
+        cpu_mesh.for_each_triangle(|i0, i1, i2| triangles.push([i0, i1, i2]));
+
+        // The triangles which are neighbours of each position
+        let (groups, group_count) = position_groups(&positions);
+        let mut neighbours = vec![Vec::new(); group_count];
+        for (t, triangle) in triangles.iter().enumerate() {
+            for v in triangle {
+                neighbours[groups[*v]].push(t);
+            }
+        }
+
+        // The triangles sorted along a z-order curve of their centers, which is used when a meshlet has no more neighbouring triangles
+        let centroids = triangles
+            .iter()
+            .map(|t| (positions[t[0]] + positions[t[1]] + positions[t[2]]) / 3.0)
+            .collect::<Vec<_>>();
+        let aabb = AxisAlignedBoundingBox::new_with_positions(&centroids);
+        let mut order = (0..triangles.len()).collect::<Vec<_>>();
+        if !aabb.is_empty() {
+            let size = aabb.size();
+            let size = size.x.max(size.y).max(size.z).max(f32::EPSILON);
+            let keys = centroids
+                .iter()
+                .map(|c| morton_code((c - aabb.min()) / size))
+                .collect::<Vec<_>>();
+            order.sort_by_key(|t| keys[*t]);
+        }
+
+        let mut meshlets = Vec::new();
+        let mut assigned = vec![false; triangles.len()];
+        let mut local = vec![u8::MAX as u32 + 1; positions.len()];
+        let mut builder = MeshletBuilder::new();
+        let mut candidates: Vec<usize> = Vec::new();
+        let mut is_candidate = vec![false; triangles.len()];
+        let mut next = 0;
+        loop {
+            // Find the neighbouring triangle which adds the fewest new vertices, preferring triangles close to the meshlet
+            candidates.retain(|t| !assigned[*t]);
+            let new_vertex_count = |t: usize| {
+                triangles[t]
+                    .iter()
+                    .filter(|v| local[**v] > u8::MAX as u32)
+                    .count()
+            };
+            let center = builder.centroid / builder.triangles.len().max(1) as f32;
+            let best = candidates
+                .iter()
+                .copied()
+                .filter(|t| builder.vertices.len() + new_vertex_count(*t) <= max_vertices)
+                .min_by(|a, b| {
+                    new_vertex_count(*a).cmp(&new_vertex_count(*b)).then(
+                        centroids[*a]
+                            .distance2(center)
+                            .total_cmp(&centroids[*b].distance2(center)),
+                    )
+                });
+            let triangle = match best {
+                Some(t) => t,
+                None if !candidates.is_empty() => {
+                    // There are neighbouring triangles, but none of them fit
+                    builder.finish(&mut meshlets, &mut local, &positions, &triangles);
+                    candidates.drain(..).for_each(|t| is_candidate[t] = false);
+                    continue;
+                }
+                None => {
+                    while next < order.len() && assigned[order[next]] {
+                        next += 1;
+                    }
+                    if next == order.len() {
+                        break;
+                    }
+                    let t = order[next];
+                    if builder.vertices.len() + new_vertex_count(t) > max_vertices {
+                        builder.finish(&mut meshlets, &mut local, &positions, &triangles);
+                    }
+                    t
+                }
+            };
+
+            assigned[triangle] = true;
+            builder.centroid += centroids[triangle];
+            let mut local_triangle = [0; 3];
+            for (i, v) in triangles[triangle].iter().enumerate() {
+                if local[*v] > u8::MAX as u32 {
+                    local[*v] = builder.vertices.len() as u32;
+                    builder.vertices.push(*v as u32);
+                }
+                local_triangle[i] = local[*v] as u8;
+                for t in neighbours[groups[*v]].iter() {
+                    if !assigned[*t] && !is_candidate[*t] {
+                        is_candidate[*t] = true;
+                        candidates.push(*t);
+                    }
+                }
+            }
+            builder.triangles.push(local_triangle);
+            builder.mesh_triangles.push(triangle);
+            if builder.triangles.len() == max_triangles {
+                builder.finish(&mut meshlets, &mut local, &positions, &triangles);
+                candidates.drain(..).for_each(|t| is_candidate[t] = false);
+            }
+        }
+        builder.finish(&mut meshlets, &mut local, &positions, &triangles);
+        meshlets
+    }
+
+    ///
+    /// Returns whether all the triangles of this meshlet are facing away from the given camera position, in the local space of the mesh,
+    /// in which case the whole meshlet can be culled.
+    ///
+    pub fn is_backfacing(&self, camera_position: Vec3) -> bool {
+        let direction = self.cone_apex - camera_position;
+        let distance = direction.magnitude();
+        distance > 0.0 && direction.dot(self.cone_axis) >= self.cone_cutoff * distance
+    }
+
+    ///
+    /// Returns the axis aligned bounding box of the sphere which contains all the triangles of this meshlet.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        let r = vec3(self.radius, self.radius, self.radius);
+        AxisAlignedBoundingBox::new_with_positions(&[self.center - r, self.center + r])
+    }
+
+    ///
+    /// Returns the triangles of this meshlet as a [CpuMesh], using the vertex attributes of the given mesh which this meshlet was built from.
+    /// This is for example useful for visualizing the meshlets.
+    ///
+    pub fn to_cpu_mesh(&self, cpu_mesh: &CpuMesh) -> CpuMesh {
+        let vertices = self
+            .vertices
+            .iter()
+            .map(|v| *v as usize)
+            .collect::<Vec<_>>();
+        let positions = cpu_mesh.positions.to_f32();
+        CpuMesh {
+            positions: Positions::F32(vertices.iter().map(|v| positions[*v]).collect()),
+            indices: Indices::U8(self.triangles.iter().flatten().copied().collect()),
+            normals: cpu_mesh
+                .normals
+                .as_ref()
+                .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+            tangents: cpu_mesh
+                .tangents
+                .as_ref()
+                .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+            uvs: cpu_mesh
+                .uvs
+                .as_ref()
+                .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+            colors: cpu_mesh
+                .colors
+                .as_ref()
+                .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+        }
+    }
+}
+
+struct MeshletBuilder {
+    vertices: Vec<u32>,
+    triangles: Vec<[u8; 3]>,
+    mesh_triangles: Vec<usize>,
+    centroid: Vec3,
+}
+
+impl MeshletBuilder {
+    fn new() -> Self {
+        Self {
+            vertices: Vec::new(),
+            triangles: Vec::new(),
+            mesh_triangles: Vec::new(),
+            centroid: vec3(0.0, 0.0, 0.0),
+        }
+    }
+
+    fn finish(
+        &mut self,
+        meshlets: &mut Vec<Meshlet>,
+        local: &mut [u32],
+        positions: &[Vec3],
+        triangles: &[[usize; 3]],
+    ) {
+        if self.triangles.is_empty() {
+            return;
+        }
+        for v in self.vertices.iter() {
+            local[*v as usize] = u8::MAX as u32 + 1;
+        }
+
+        let aabb = AxisAlignedBoundingBox::new_with_positions(
+            &self
+                .vertices
+                .iter()
+                .map(|v| positions[*v as usize])
+                .collect::<Vec<_>>(),
+        );
+        let center = aabb.center();
+        let radius = self
+            .vertices
+            .iter()
+            .map(|v| positions[*v as usize].distance(center))
+            .fold(0.0, f32::max);
+
+        // The normal cone, see https://zeux.io/2023/04/28/triangle-backface-culling/
+        let normals = self
+            .mesh_triangles
+            .iter()
+            .filter_map(|t| {
+                let [p0, p1, p2] = triangles[*t].map(|v| positions[v]);
+                let n = (p1 - p0).cross(p2 - p0);
+                (n.magnitude2() > 0.0).then(|| (p0, n.normalize()))
+            })
+            .collect::<Vec<_>>();
+        let axis = normals
+            .iter()
+            .fold(vec3(0.0, 0.0, 0.0), |sum, (_, n)| sum + n);
+        let (cone_apex, cone_axis, cone_cutoff) = if axis.magnitude2() > 0.0 {
+            let axis = axis.normalize();
+            let min_dot = normals.iter().map(|(_, n)| n.dot(axis)).fold(1.0, f32::min);
+            if min_dot <= 0.1 {
+                (center, axis, 2.0)
+            } else {
+                let max_t = normals
+                    .iter()
+                    .map(|(p0, n)| (center - p0).dot(*n) / axis.dot(*n))
+                    .fold(0.0, f32::max);
+                (
+                    center - axis * max_t,
+                    axis,
+                    (1.0 - min_dot * min_dot).sqrt(),
+                )
+            }
+        } else {
+            (center, vec3(0.0, 0.0, 1.0), 2.0)
+        };
+
+        meshlets.push(Meshlet {
+            vertices: std::mem::take(&mut self.vertices),
+            triangles: std::mem::take(&mut self.triangles),
+            center,
+            radius,
+            cone_apex,
+            cone_axis,
+            cone_cutoff,
+        });
+        self.mesh_triangles.clear();
+        self.centroid = vec3(0.0, 0.0, 0.0);
+    }
+}
+
+///
+/// Returns the position along a z-order curve of the given point in the unit cube.
+///
+fn morton_code(p: Vec3) -> u32 {
+    let spread = |x: f32| {
+        let mut x = (x.clamp(0.0, 1.0) * 1023.0) as u32;
+        x = (x | (x << 16)) & 0x030000FF;
+        x = (x | (x << 8)) & 0x0300F00F;
+        x = (x | (x << 4)) & 0x030C30C3;
+        (x | (x << 2)) & 0x09249249
+    };
+    spread(p.x) << 2 | spread(p.y) << 1 | spread(p.z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meshlets_cover_each_triangle_once_within_the_limits() {
+        let mesh = CpuMesh::sphere(16);
+        let positions = mesh.positions.to_f32();
+        let mut expected = Vec::new();
+        mesh.for_each_triangle(|i0, i1, i2| expected.push([i0 as u32, i1 as u32, i2 as u32]));
+
+        let meshlets = Meshlet::build_with_limits(&mesh, 32, 40);
+        let mut triangles = Vec::new();
+        for meshlet in meshlets.iter() {
+            assert!(meshlet.vertices.len() <= 32);
+            assert!(!meshlet.triangles.is_empty() && meshlet.triangles.len() <= 40);
+            for triangle in meshlet.triangles.iter() {
+                let triangle = triangle.map(|i| meshlet.vertices[i as usize]);
+                for v in triangle {
+                    let distance = positions[v as usize].distance(meshlet.center);
+                    assert!(distance <= meshlet.radius * 1.0001);
+                }
+                triangles.push(triangle);
+            }
+        }
+        triangles.sort();
+        expected.sort();
+        assert_eq!(triangles, expected);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::ops::Deref;
+
+///
+/// A morph target, also called a blend shape, which defines an offset from the base shape of a [MorphMesh] for each vertex.
+///
+#[derive(Clone, Debug, Default)]
+pub struct MorphTarget {
+    /// The name of the morph target, for example the name of a viseme used for lip-sync.
+    pub name: String,
+    /// The offset of the position of each vertex when this morph target has a weight of 1.
+    pub position_offsets: Vec<Vec3>,
+    /// The optional offset of the normal of each vertex when this morph target has a weight of 1.
+    pub normal_offsets: Option<Vec<Vec3>>,
+}
+
+///
+/// A triangle [Mesh] which can be deformed by a weighted sum of [MorphTarget]s, for example to animate the face of an avatar.
+///
+/// The weights can be set directly using [MorphMesh::set_weights] or be fed from an external source, for example lip-sync or face tracking,
+/// using [MorphMesh::set_target_weights] in which case the weights are smoothly moved towards the target weights each time [Geometry::animate] is called.
+/// The deformation is calculated on the CPU, the tangents of the mesh are not updated.
+///
+pub struct MorphMesh {
+    mesh: Mesh,
+    positions: Vec<Vec3>,
+    normals: Option<Vec<Vec3>>,
+    targets: Vec<MorphTarget>,
+    weights: Vec<f32>,
+    target_weights: Vec<f32>,
+    smoothing: f32,
+    time: Option<f32>,
+}
+
+impl MorphMesh {
+    ///
+    /// Creates a new morph mesh from the given [CpuMesh], which defines the base shape, and morph targets.
+    /// All weights are initially zero.
+    ///
+    /// # Panics
+    /// Will panic if the number of offsets in a morph target does not match the number of vertices in the mesh.
+    ///
+    pub fn new(context: &Context, cpu_mesh: &CpuMesh, targets: Vec<MorphTarget>) -> Self {
+        let positions = cpu_mesh.positions.to_f32();
+        for target in targets.iter() {
+            if target.position_offsets.len() != positions.len()
+                || target
+                    .normal_offsets
+                    .as_ref()
+                    .is_some_and(|n| n.len() != positions.len())
+            {
+                panic!(
+                    "the number of offsets in the morph target {} does not match the number of vertices {} in the mesh",
+                    target.name,
+                    positions.len()
+                );
+            }
+        }
+        Self {
+            mesh: Mesh::new(context, cpu_mesh),
+            positions,
+            normals: cpu_mesh.normals.clone(),
+            weights: vec![0.0; targets.len()],
+            target_weights: vec![0.0; targets.len()],
+            targets,
+            smoothing: 0.1,
+            time: None,
+        }
+    }
+
+    ///
+    /// Returns the morph targets.
+    ///
+    pub fn targets(&self) -> &[MorphTarget] {
+        &self.targets
+    }
+
+    ///
+    /// Returns the index of the morph target with the given name, which is the index of the weight of that morph target.
+    ///
+    pub fn target_index(&self, name: &str) -> Option<usize> {
+        self.targets.iter().position(|t| t.name == name)
+    }
+
+    ///
+    /// Returns the current weight of each morph target.
+    ///
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    ///
+    /// Sets the weight of each morph target and deforms the mesh immediately.
+    /// Any missing weights are set to zero and any extra weights are ignored.
+    ///
+    pub fn set_weights(&mut self, weights: &[f32]) {
+        self.set_target_weights(weights);
+        self.weights.clone_from(&self.target_weights);
+        self.update();
+    }
+
+    ///
+    /// Sets the weights that the current weights are smoothly moved towards each time [Geometry::animate] is called,
+    /// for example the weights of each frame from a lip-sync or face tracking source.
+    /// Any missing weights are set to zero and any extra weights are ignored.
+    ///
+    pub fn set_target_weights(&mut self, weights: &[f32]) {
+        for (i, weight) in self.target_weights.iter_mut().enumerate() {
+            *weight = weights.get(i).copied().unwrap_or(0.0);
+        }
+    }
+
+    ///
+    /// Returns the smoothing time in seconds, see [Self::set_smoothing].
+    ///
+    pub fn smoothing(&self) -> f32 {
+        self.smoothing
+    }
+
+    ///
+    /// Sets the smoothing time in seconds, ie. the time it takes for the current weights to move about two thirds of the way towards the target weights.
+    /// A larger value removes more jitter from a noisy source at the cost of a slower response. Zero means no smoothing.
+    ///
+    pub fn set_smoothing(&mut self, smoothing: f32) {
+        self.smoothing = smoothing.max(0.0);
+    }
+
+    fn update(&mut self) {
+        let mut positions = self.positions.clone();
+        let mut normals = self.normals.clone();
+        for (target, weight) in self.targets.iter().zip(self.weights.iter()) {
+            if *weight == 0.0 {
+                continue;
+            }
+            positions
+                .iter_mut()
+                .zip(target.position_offsets.iter())
+                .for_each(|(p, o)| *p += o * *weight);
+            if let (Some(normals), Some(offsets)) = (&mut normals, &target.normal_offsets) {
+                normals
+                    .iter_mut()
+                    .zip(offsets.iter())
+                    .for_each(|(n, o)| *n += o * *weight);
+            }
+        }
+        self.mesh.update_positions(&positions);
+        if let Some(mut normals) = normals {
+            normals.iter_mut().for_each(|n| *n = n.normalize());
+            self.mesh.update_normals(&normals);
+        }
+    }
+}
+
+impl Deref for MorphMesh {
+    type Target = Mesh;
+    fn deref(&self) -> &Self::Target {
+        &self.mesh
+    }
+}
+
+impl std::ops::DerefMut for MorphMesh {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.mesh
+    }
+}
+
+impl<'a> IntoIterator for &'a MorphMesh {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for MorphMesh {
+    impl_geometry_body!(deref);
+
+    fn animate(&mut self, time: f32) {
+        self.mesh.animate(time);
+        let elapsed = self.time.map(|t| (time - t).max(0.0)).unwrap_or(0.0);
+        self.time = Some(time);
+        if self.weights == self.target_weights {
+            return;
+        }
+        let factor = if self.smoothing > 0.0 {
+            1.0 - (-elapsed / self.smoothing).exp()
+        } else {
+            1.0
+        };
+        for (weight, target) in self.weights.iter_mut().zip(self.target_weights.iter()) {
+            *weight += (target - *weight) * factor;
+            if (target - *weight).abs() < 0.0001 {
+                *weight = *target;
+            }
+        }
+        self.update();
+    }
+}
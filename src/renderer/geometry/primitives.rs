@@ -0,0 +1,165 @@
+use crate::renderer::*;
+use std::f32::consts::PI;
+
+///
+/// Constructors for [CpuMesh]es of common shapes, in addition to [CpuMesh::square], [CpuMesh::circle], [CpuMesh::sphere], [CpuMesh::cube],
+/// [CpuMesh::cylinder], [CpuMesh::cone] and [CpuMesh::arrow], which are useful for demos and debug visualizations.
+/// All of the meshes have normals, uv coordinates and tangents, so they can be used with any material, including materials with normal maps.
+///
+pub trait CpuMeshPrimitives: Sized {
+    ///
+    /// Returns a square mesh spanning the xy-plane with positions in the range `[-1..1]` in the x and y axes like [CpuMesh::square],
+    /// but divided into the given number of quads along each axis, for example to be displaced afterwards.
+    ///
+    fn plane(subdivisions: u32) -> Self;
+
+    ///
+    /// Returns a sphere mesh with radius 1 and center in `(0, 0, 0)` like [CpuMesh::sphere], but with uv coordinates which wraps a texture around the sphere,
+    /// where the u coordinate follows the angle around the z-axis and the v coordinate goes from the pole at `(0, 0, 1)` to the pole at `(0, 0, -1)`.
+    ///
+    fn uv_sphere(angle_subdivisions: u32) -> Self;
+
+    ///
+    /// Returns a torus mesh around the z-axis with center in `(0, 0, 0)`, where the center of the tube has the distance `major_radius` to the center
+    /// and the tube has the radius `minor_radius`.
+    ///
+    fn torus(
+        major_radius: f32,
+        minor_radius: f32,
+        major_subdivisions: u32,
+        minor_subdivisions: u32,
+    ) -> Self;
+
+    ///
+    /// Returns a capsule mesh around the x-axis with radius 1, ie. a cylinder in the range `[0..length]` with a half sphere at each end,
+    /// so the positions are in the range `[-1..length + 1]` along the x-axis.
+    ///
+    fn capsule(length: f32, angle_subdivisions: u32) -> Self;
+}
+
+impl CpuMeshPrimitives for CpuMesh {
+    fn plane(subdivisions: u32) -> Self {
+        let subdivisions = subdivisions.max(1);
+        grid(subdivisions, subdivisions, |u, v| {
+            (vec3(2.0 * u - 1.0, 1.0 - 2.0 * v, 0.0), vec3(0.0, 0.0, 1.0))
+        })
+    }
+
+    fn uv_sphere(angle_subdivisions: u32) -> Self {
+        let angle_subdivisions = angle_subdivisions.max(2);
+        grid(2 * angle_subdivisions, angle_subdivisions, |u, v| {
+            let (theta, phi) = (PI * v, 2.0 * PI * u);
+            let p = vec3(
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            );
+            (p, p)
+        })
+    }
+
+    fn torus(
+        major_radius: f32,
+        minor_radius: f32,
+        major_subdivisions: u32,
+        minor_subdivisions: u32,
+    ) -> Self {
+        grid(
+            major_subdivisions.max(3),
+            minor_subdivisions.max(3),
+            |u, v| {
+                let (a, b) = (2.0 * PI * u, 2.0 * PI * v);
+                let n = vec3(b.cos() * a.cos(), b.cos() * a.sin(), b.sin());
+                let center = vec3(a.cos(), a.sin(), 0.0) * major_radius;
+                (center + n * minor_radius, n)
+            },
+        )
+    }
+
+    fn capsule(length: f32, angle_subdivisions: u32) -> Self {
+        let angle_subdivisions = angle_subdivisions.max(4);
+        let ring_subdivisions = angle_subdivisions / 4;
+        // The profile from the tip at -1 to the tip at length + 1, with two rings of the same radius at each end of the cylinder
+        let profile = (0..=ring_subdivisions)
+            .map(|i| (0.0, PI * (1.0 - 0.5 * i as f32 / ring_subdivisions as f32)))
+            .chain((0..=ring_subdivisions).map(|i| {
+                (
+                    length,
+                    PI * (0.5 - 0.5 * i as f32 / ring_subdivisions as f32),
+                )
+            }))
+            .collect::<Vec<_>>();
+        let rows = profile.len() as u32 - 1;
+        grid(angle_subdivisions, rows, |u, v| {
+            let (x, theta) = profile[(v * rows as f32).round() as usize];
+            let angle = 2.0 * PI * u;
+            let n = vec3(
+                theta.cos(),
+                theta.sin() * angle.cos(),
+                theta.sin() * angle.sin(),
+            );
+            (vec3(x, 0.0, 0.0) + n, n)
+        })
+    }
+}
+
+///
+/// Returns a mesh with a grid of vertices where the position and normal of each vertex is given by the callback
+/// with the uv coordinates of the vertex in the range `[0..1]`. The triangles are oriented so that they face in the direction of the normals
+/// and triangles without area, for example at the poles of a sphere, are skipped.
+///
+fn grid(
+    u_subdivisions: u32,
+    v_subdivisions: u32,
+    callback: impl Fn(f32, f32) -> (Vec3, Vec3),
+) -> CpuMesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for j in 0..=v_subdivisions {
+        for i in 0..=u_subdivisions {
+            let uv = vec2(
+                i as f32 / u_subdivisions as f32,
+                j as f32 / v_subdivisions as f32,
+            );
+            let (position, normal) = callback(uv.x, uv.y);
+            positions.push(position);
+            normals.push(normal.normalize());
+            uvs.push(uv);
+        }
+    }
+    let index = |i: u32, j: u32| j * (u_subdivisions + 1) + i;
+    let mut indices = Vec::new();
+    for j in 0..v_subdivisions {
+        for i in 0..u_subdivisions {
+            let quad = [
+                index(i, j),
+                index(i + 1, j),
+                index(i + 1, j + 1),
+                index(i, j + 1),
+            ];
+            for [a, b, c] in [[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]] {
+                let p = [a, b, c].map(|v| positions[v as usize]);
+                let n = (p[1] - p[0]).cross(p[2] - p[0]);
+                if n.magnitude2() <= 1e-12 {
+                    continue;
+                }
+                let normal = normals[a as usize] + normals[b as usize] + normals[c as usize];
+                if n.dot(normal) > 0.0 {
+                    indices.extend([a, b, c]);
+                } else {
+                    indices.extend([a, c, b]);
+                }
+            }
+        }
+    }
+    let mut mesh = CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U32(indices),
+        normals: Some(normals),
+        uvs: Some(uvs),
+        ..Default::default()
+    };
+    mesh.compute_tangents();
+    mesh
+}
@@ -1,8 +1,12 @@
 
-uniform mat4 viewProjection;
 uniform mat4 modelMatrix;
 in vec3 position;
 
+#ifdef USE_POSITION_DECODING
+// Transforms the stored positions, which are quantized when using a packed vertex format, to model space
+uniform mat4 positionDecoding;
+#endif
+
 #ifdef PARTICLES
 in vec3 start_position;
 in vec3 start_velocity;
@@ -43,6 +47,14 @@ in vec3 tex_transform_row2;
 #endif
 in vec2 uv_coordinates;
 out vec2 uvs;
+#ifdef USE_SECONDARY_UVS
+in vec2 uv_coordinates1;
+#endif
+out vec2 uvs1;
+#ifdef USE_INSTANCE_TEXTURE_LAYERS
+in float instance_texture_layer;
+#endif
+flat out float texture_layer;
 #endif
 
 #ifdef USE_VERTEX_COLORS 
@@ -68,15 +80,30 @@ void main()
     local2World *= transform;
 #endif
 
-    vec4 worldPosition = local2World * vec4(position, 1.);
+#ifdef USE_INSTANCE_TRANSLATIONS 
+    local2World[3].xyz += instance_translation;
+#endif
+
+#ifdef USE_POSITION_DECODING
+    vec3 localPosition = (positionDecoding * vec4(position, 1.0)).xyz;
+#else
+    vec3 localPosition = position;
+#endif
+
+#ifdef USE_VERTEX_DISPLACEMENT
+#ifdef USE_NORMALS
+    localPosition = displace_position(localPosition, normal, local2World);
+#else
+    localPosition = displace_position(localPosition, vec3(0.0), local2World);
+#endif
+#endif
+
+    vec4 worldPosition = local2World * vec4(localPosition, 1.);
     worldPosition /= worldPosition.w;
 #ifdef PARTICLES
     worldPosition.xyz += start_position + start_velocity * time + 0.5 * acceleration * time * time;
 #endif
-#ifdef USE_INSTANCE_TRANSLATIONS 
-    worldPosition.xyz += instance_translation;
-#endif
-    gl_Position = viewProjection * worldPosition;
+    gl_Position = camera.viewProjection * worldPosition;
 
     pos = worldPosition.xyz;
 
@@ -107,6 +134,16 @@ void main()
 #else
     uvs = uv_coordinates;
 #endif
+#ifdef USE_SECONDARY_UVS
+    uvs1 = uv_coordinates1;
+#else
+    uvs1 = uvs;
+#endif
+#ifdef USE_INSTANCE_TEXTURE_LAYERS
+    texture_layer = instance_texture_layer;
+#else
+    texture_layer = 0.0;
+#endif
 #endif
 
     // *** COLOR ***
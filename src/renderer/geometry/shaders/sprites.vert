@@ -1,22 +1,50 @@
+uniform mat4 transformation;
+
+#ifdef USE_SCREEN_SPACE
+uniform vec2 viewportSize;
+#else
 uniform mat4 viewProjection;
 uniform vec3 eye;
-uniform mat4 transformation;
 uniform vec3 direction;
+#endif
 
 in vec3 center;
 
+#ifdef USE_TEXTURE_REGIONS
+in vec4 texture_region;
+#endif
+
+#ifdef USE_INSTANCE_COLORS
+in vec4 instance_color;
+#endif
+
 in vec3 position;
 in vec2 uv_coordinate;
 
 out vec2 uvs;
+out vec2 uvs1;
+flat out float texture_layer;
 out vec4 col;
 out vec3 pos;
 
 void main()
 {
     uvs = uv_coordinate;
+#ifdef USE_TEXTURE_REGIONS
+    uvs = texture_region.xy + uvs * texture_region.zw;
+#endif
+    uvs1 = uvs;
+    texture_layer = 0.0;
     col = vec4(1.0);
+#ifdef USE_INSTANCE_COLORS
+    col = instance_color;
+#endif
 
+#ifdef USE_SCREEN_SPACE
+    vec4 screen_pos = transformation * vec4(position, 1.);
+    pos = vec3(center.xy + screen_pos.xy / screen_pos.w, 0.0);
+    gl_Position = vec4(2.0 * pos.xy / viewportSize - 1.0, -1.0, 1.0);
+#else
     vec3 z = normalize(eye - center);
     vec3 y = direction;
     vec3 x;
@@ -38,4 +66,5 @@ void main()
     vec4 world_pos = instanced_transform * transformation * vec4(position, 1.);
     pos = world_pos.xyz / world_pos.w;
     gl_Position = viewProjection * world_pos;
+#endif
 }
@@ -8,13 +8,20 @@ use crate::renderer::*;
 /// For example, if the up direction is specified, the sprites will rotate around the up direction trying to face the camera.
 /// Sprites are also known as billboards in the case where no direction is specified.
 ///
+/// Each sprite can show a different region of the texture, for example a cell in a sprite sheet (see [Sprites::set_sprite_sheet_cells]),
+/// and can be tinted with a different color (see [Sprites::set_colors]).
+/// The sprites can also be placed in screen space instead of world space, for example to render icons and simple 2D overlays (see [Sprites::set_screen_space]).
+///
 pub struct Sprites {
     context: Context,
     position_buffer: VertexBuffer,
     uv_buffer: VertexBuffer,
     center_buffer: InstanceBuffer,
+    texture_region_buffer: Option<InstanceBuffer>,
+    color_buffer: Option<InstanceBuffer>,
     transformation: Mat4,
     direction: Option<Vec3>,
+    screen_space: bool,
 }
 
 impl Sprites {
@@ -50,8 +57,11 @@ impl Sprites {
             position_buffer,
             uv_buffer,
             center_buffer: InstanceBuffer::new_with_data(context, centers),
+            texture_region_buffer: None,
+            color_buffer: None,
             transformation: Mat4::identity(),
             direction,
+            screen_space: false,
         }
     }
 
@@ -83,17 +93,110 @@ impl Sprites {
         self.center_buffer.fill(centers);
     }
 
-    fn draw(&self, program: &Program, render_states: RenderStates, camera: &Camera) {
-        program.use_uniform("eye", camera.position());
-        program.use_uniform("viewProjection", camera.projection() * camera.view());
+    ///
+    /// Set whether the sprites are placed in screen space instead of world space.
+    /// In screen space, the x and y coordinates of the centers are given in pixels, measured from the bottom left corner of the viewport,
+    /// the z coordinate is ignored and the transformation scales the quads in pixels.
+    /// The sprites are placed at the near plane, so they are rendered on top of the scene.
+    ///
+    pub fn set_screen_space(&mut self, screen_space: bool) {
+        self.screen_space = screen_space;
+    }
+
+    ///
+    /// Set the region of the texture shown on each sprite, given as the minimum and maximum uv coordinates.
+    /// The number of regions must be the same as the number of sprites. Use an empty slice to show the entire texture on all sprites.
+    ///
+    pub fn set_texture_regions(&mut self, regions: &[(Vec2, Vec2)]) {
+        self.texture_region_buffer = if regions.is_empty() {
+            None
+        } else {
+            Some(InstanceBuffer::new_with_data(
+                &self.context,
+                &regions
+                    .iter()
+                    .map(|(min, max)| vec4(min.x, min.y, max.x - min.x, max.y - min.y))
+                    .collect::<Vec<_>>(),
+            ))
+        };
+    }
+
+    ///
+    /// Set the cell of a sprite sheet with the given number of columns and rows shown on each sprite.
+    /// The cells are numbered row by row, starting with 0 in the top left corner of the sprite sheet.
+    /// The number of cells must be the same as the number of sprites.
+    ///
+    pub fn set_sprite_sheet_cells(&mut self, columns: u32, rows: u32, cells: &[u32]) {
+        let columns = columns.max(1);
+        let rows = rows.max(1);
+        let size = vec2(1.0 / columns as f32, 1.0 / rows as f32);
+        self.set_texture_regions(
+            &cells
+                .iter()
+                .map(|cell| {
+                    let min = vec2(
+                        (cell % columns) as f32 * size.x,
+                        1.0 - ((cell / columns) % rows + 1) as f32 * size.y,
+                    );
+                    (min, min + size)
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    ///
+    /// Set a color for each sprite which is multiplied with the color of the material, if the material uses vertex colors, for example [ColorMaterial].
+    /// The number of colors must be the same as the number of sprites. Use an empty slice to remove the colors.
+    ///
+    pub fn set_colors(&mut self, colors: &[Srgba]) {
+        self.color_buffer = if colors.is_empty() {
+            None
+        } else {
+            Some(InstanceBuffer::new_with_data(
+                &self.context,
+                &colors
+                    .iter()
+                    .map(|c| c.to_linear_srgb())
+                    .collect::<Vec<_>>(),
+            ))
+        };
+    }
+
+    fn draw(
+        &self,
+        program: &Program,
+        render_states: RenderStates,
+        camera: &Camera,
+        attributes: FragmentAttributes,
+    ) {
+        let viewport = camera.viewport();
+        if self.screen_space {
+            program.use_uniform(
+                "viewportSize",
+                vec2(viewport.width as f32, viewport.height as f32),
+            );
+        } else {
+            program.use_uniform("eye", camera.position());
+            program.use_uniform("viewProjection", camera.projection() * camera.view());
+            program.use_uniform("direction", self.direction.unwrap_or(vec3(0.0, 0.0, 0.0)));
+        }
         program.use_uniform("transformation", self.transformation);
         program.use_vertex_attribute("position", &self.position_buffer);
         program.use_vertex_attribute("uv_coordinate", &self.uv_buffer);
         program.use_instance_attribute("center", &self.center_buffer);
-        program.use_uniform("direction", self.direction.unwrap_or(vec3(0.0, 0.0, 0.0)));
+        if attributes.uv {
+            if let Some(texture_region_buffer) = &self.texture_region_buffer {
+                program.use_instance_attribute("texture_region", texture_region_buffer);
+            }
+        }
+        if attributes.color {
+            if let Some(color_buffer) = &self.color_buffer {
+                program.use_instance_attribute("instance_color", color_buffer);
+            }
+        }
         program.draw_arrays_instanced(
             render_states,
-            camera.viewport(),
+            viewport,
             6,
             self.center_buffer.instance_count(),
         )
@@ -123,15 +226,43 @@ impl Geometry for Sprites {
         if attributes.normal || attributes.tangents {
             todo!()
         }
-        self.draw(program, render_states, camera);
+        self.draw(program, render_states, camera, attributes);
     }
 
-    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
-        include_str!("shaders/sprites.vert").to_owned()
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        format!(
+            "{}{}{}{}",
+            if self.screen_space {
+                "#define USE_SCREEN_SPACE\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv && self.texture_region_buffer.is_some() {
+                "#define USE_TEXTURE_REGIONS\n"
+            } else {
+                ""
+            },
+            if required_attributes.color && self.color_buffer.is_some() {
+                "#define USE_INSTANCE_COLORS\n"
+            } else {
+                ""
+            },
+            include_str!("shaders/sprites.vert")
+        )
     }
 
-    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
-        0b1u16 << 15 | 0b100u16
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 8;
+        if self.screen_space {
+            id |= 0b1u16;
+        }
+        if required_attributes.uv && self.texture_region_buffer.is_some() {
+            id |= 0b1u16 << 1;
+        }
+        if required_attributes.color && self.color_buffer.is_some() {
+            id |= 0b1u16 << 2;
+        }
+        id
     }
 
     fn render_with_material(
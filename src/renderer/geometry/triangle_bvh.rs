@@ -0,0 +1,336 @@
+use crate::renderer::*;
+
+/// The maximum number of triangles in a leaf node.
+const MAX_LEAF_SIZE: usize = 4;
+
+///
+/// The result of a ray intersection test against a [TriangleBvh].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriangleIntersection {
+    /// The distance from the ray origin to the intersection point, in units of the length of the ray direction.
+    pub distance: f32,
+    /// The index of the intersected triangle in the order the triangles were given when constructing the [TriangleBvh].
+    pub triangle_index: u32,
+    /// The barycentric coordinates of the intersection point with respect to the second and third vertex of the triangle,
+    /// so the point is `(1 - u - v) * p0 + u * p1 + v * p2`.
+    pub barycentric: Vec2,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    min: Vec3,
+    max: Vec3,
+    // The index of the first triangle for a leaf node and the index of the left child, followed by the right child, for an interior node
+    first: u32,
+    // The number of triangles for a leaf node and zero for an interior node
+    count: u32,
+}
+
+///
+/// A bounding volume hierarchy of triangles on the CPU, which makes it possible to test a ray for intersection with a large number of triangles
+/// by only testing the triangles in the boxes that the ray passes through,
+/// for example when baking ambient occlusion (see [AmbientOcclusionBaker]) or for picking without rendering the scene.
+///
+/// The hierarchy is built by recursively splitting the triangles in two halves along the longest axis of the bounding box of their centers.
+///
+#[derive(Clone, Debug)]
+pub struct TriangleBvh {
+    triangles: Vec<[Vec3; 3]>,
+    indices: Vec<u32>,
+    nodes: Vec<Node>,
+}
+
+impl TriangleBvh {
+    ///
+    /// Builds a bounding volume hierarchy of the given triangles.
+    ///
+    pub fn new(triangles: Vec<[Vec3; 3]>) -> Self {
+        let centers = triangles
+            .iter()
+            .map(|t| (t[0] + t[1] + t[2]) / 3.0)
+            .collect::<Vec<_>>();
+        let mut indices = (0..triangles.len() as u32).collect::<Vec<_>>();
+        let mut nodes = Vec::with_capacity(2 * triangles.len() / MAX_LEAF_SIZE + 1);
+        nodes.push(Node {
+            min: Vec3::zero(),
+            max: Vec3::zero(),
+            first: 0,
+            count: 0,
+        });
+        build(&triangles, &centers, &mut indices, 0, &mut nodes, 0);
+        let triangles = indices.iter().map(|i| triangles[*i as usize]).collect();
+        Self {
+            triangles,
+            indices,
+            nodes,
+        }
+    }
+
+    ///
+    /// Builds a bounding volume hierarchy of the triangles of the given meshes, each placed in the world using the given transformation.
+    /// The triangle indices of the intersections are counted consecutively through the meshes.
+    ///
+    pub fn from_meshes<'a>(meshes: impl IntoIterator<Item = (&'a CpuMesh, Mat4)>) -> Self {
+        let mut triangles = Vec::new();
+        for (cpu_mesh, transformation) in meshes {
+            let positions = cpu_mesh
+                .positions
+                .to_f32()
+                .into_iter()
+                .map(|p| (transformation * p.extend(1.0)).truncate())
+                .collect::<Vec<_>>();
+            cpu_mesh.for_each_triangle(|i0, i1, i2| {
+                triangles.push([positions[i0], positions[i1], positions[i2]])
+            });
+        }
+        Self::new(triangles)
+    }
+
+    ///
+    /// Builds a bounding volume hierarchy of the triangles of all the triangle meshes in the given model, placed using their transformations.
+    ///
+    pub fn from_model(cpu_model: &CpuModel) -> Self {
+        Self::from_meshes(cpu_model.geometries.iter().filter_map(|primitive| {
+            if let three_d_asset::Geometry::Triangles(mesh) = &primitive.geometry {
+                Some((mesh, primitive.transformation))
+            } else {
+                None
+            }
+        }))
+    }
+
+    ///
+    /// Returns the number of triangles in the hierarchy.
+    ///
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    ///
+    /// Returns the bounding box of all the triangles.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        if self.triangles.is_empty() {
+            AxisAlignedBoundingBox::EMPTY
+        } else {
+            AxisAlignedBoundingBox::new_with_positions(&[self.nodes[0].min, self.nodes[0].max])
+        }
+    }
+
+    ///
+    /// Finds the closest intersection between the ray with the given origin and direction and the triangles,
+    /// where intersections further away from the origin than the given maximum distance are ignored.
+    /// Both sides of the triangles are intersected.
+    ///
+    pub fn intersect(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+    ) -> Option<TriangleIntersection> {
+        let mut closest: Option<TriangleIntersection> = None;
+        let mut max_distance = max_distance;
+        self.traverse(origin, direction, max_distance, |index, triangle| {
+            if let Some((distance, barycentric)) =
+                intersect_triangle(origin, direction, triangle, max_distance)
+            {
+                max_distance = distance;
+                closest = Some(TriangleIntersection {
+                    distance,
+                    triangle_index: self.indices[index],
+                    barycentric,
+                });
+            }
+            (false, max_distance)
+        });
+        closest
+    }
+
+    ///
+    /// Returns whether the ray with the given origin and direction intersects any of the triangles closer to the origin than the given maximum distance.
+    /// This is faster than [TriangleBvh::intersect] since the search stops at the first intersection found.
+    ///
+    pub fn is_occluded(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> bool {
+        let mut occluded = false;
+        self.traverse(origin, direction, max_distance, |_, triangle| {
+            occluded = intersect_triangle(origin, direction, triangle, max_distance).is_some();
+            (occluded, max_distance)
+        });
+        occluded
+    }
+
+    // Calls the callback for each triangle in the leaf nodes hit by the ray until it returns true, where the callback also returns the current maximum distance.
+    fn traverse(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        mut callback: impl FnMut(usize, &[Vec3; 3]) -> (bool, f32),
+    ) {
+        if self.triangles.is_empty() {
+            return;
+        }
+        let inverse_direction = vec3(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z);
+        let mut max_distance = max_distance;
+        let mut stack = [0u32; 64];
+        let mut stack_size = 1;
+        while stack_size > 0 {
+            stack_size -= 1;
+            let node = &self.nodes[stack[stack_size] as usize];
+            if intersect_box(origin, inverse_direction, node, max_distance).is_none() {
+                continue;
+            }
+            if node.count > 0 {
+                for index in node.first as usize..(node.first + node.count) as usize {
+                    let (stop, distance) = callback(index, &self.triangles[index]);
+                    if stop {
+                        return;
+                    }
+                    max_distance = distance;
+                }
+            } else {
+                // Visit the closest child first, so the maximum distance is reduced as early as possible
+                let left = node.first;
+                let right = node.first + 1;
+                let left_distance = intersect_box(
+                    origin,
+                    inverse_direction,
+                    &self.nodes[left as usize],
+                    max_distance,
+                );
+                let right_distance = intersect_box(
+                    origin,
+                    inverse_direction,
+                    &self.nodes[right as usize],
+                    max_distance,
+                );
+                let (first, second) = match (left_distance, right_distance) {
+                    (Some(l), Some(r)) if r < l => (Some(right), Some(left)),
+                    (l, r) => (l.map(|_| left), r.map(|_| right)),
+                };
+                for child in [second, first].into_iter().flatten() {
+                    if stack_size < stack.len() {
+                        stack[stack_size] = child;
+                        stack_size += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn build(
+    triangles: &[[Vec3; 3]],
+    centers: &[Vec3],
+    indices: &mut [u32],
+    offset: usize,
+    nodes: &mut Vec<Node>,
+    node_index: usize,
+) {
+    let mut min = vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+    let mut max = vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+    let mut center_min = min;
+    let mut center_max = max;
+    for index in indices.iter() {
+        for p in triangles[*index as usize] {
+            min = vec3(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = vec3(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+        let c = centers[*index as usize];
+        center_min = vec3(
+            center_min.x.min(c.x),
+            center_min.y.min(c.y),
+            center_min.z.min(c.z),
+        );
+        center_max = vec3(
+            center_max.x.max(c.x),
+            center_max.y.max(c.y),
+            center_max.z.max(c.z),
+        );
+    }
+    nodes[node_index].min = min;
+    nodes[node_index].max = max;
+    if indices.len() <= MAX_LEAF_SIZE {
+        nodes[node_index].first = offset as u32;
+        nodes[node_index].count = indices.len() as u32;
+        return;
+    }
+
+    let extent = center_max - center_min;
+    let axis = if extent.x >= extent.y && extent.x >= extent.z {
+        0
+    } else if extent.y >= extent.z {
+        1
+    } else {
+        2
+    };
+    let middle = indices.len() / 2;
+    indices.select_nth_unstable_by(middle, |a, b| {
+        centers[*a as usize][axis]
+            .partial_cmp(&centers[*b as usize][axis])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let left = nodes.len();
+    nodes[node_index].first = left as u32;
+    nodes[node_index].count = 0;
+    nodes.extend([nodes[node_index]; 2]);
+    let (left_indices, right_indices) = indices.split_at_mut(middle);
+    build(triangles, centers, left_indices, offset, nodes, left);
+    build(
+        triangles,
+        centers,
+        right_indices,
+        offset + middle,
+        nodes,
+        left + 1,
+    );
+}
+
+// Returns the distance to the entry point of the ray into the box if the ray hits the box closer than the maximum distance
+fn intersect_box(
+    origin: Vec3,
+    inverse_direction: Vec3,
+    node: &Node,
+    max_distance: f32,
+) -> Option<f32> {
+    let mut t_min = 0.0f32;
+    let mut t_max = max_distance;
+    for axis in 0..3 {
+        let t0 = (node.min[axis] - origin[axis]) * inverse_direction[axis];
+        let t1 = (node.max[axis] - origin[axis]) * inverse_direction[axis];
+        t_min = t_min.max(t0.min(t1));
+        t_max = t_max.min(t0.max(t1));
+    }
+    (t_min <= t_max).then_some(t_min)
+}
+
+// Möller–Trumbore ray-triangle intersection
+fn intersect_triangle(
+    origin: Vec3,
+    direction: Vec3,
+    triangle: &[Vec3; 3],
+    max_distance: f32,
+) -> Option<(f32, Vec2)> {
+    let edge1 = triangle[1] - triangle[0];
+    let edge2 = triangle[2] - triangle[0];
+    let p = direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < 1.0e-12 {
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+    let s = origin - triangle[0];
+    let u = s.dot(p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let v = direction.dot(q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge2.dot(q) * inverse_determinant;
+    (distance > 0.0 && distance < max_distance).then_some((distance, vec2(u, v)))
+}
@@ -0,0 +1,239 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::ops::Deref;
+
+///
+/// A frame of a [VertexCacheMesh], ie. the baked position of each vertex at a point in time.
+///
+#[derive(Clone, Debug, Default)]
+pub struct VertexCacheFrame {
+    /// The time in seconds of this frame.
+    pub time: f32,
+    /// The position of each vertex in this frame.
+    pub positions: Vec<Vec3>,
+    /// The optional normal of each vertex in this frame. If not specified, the normals are computed from the positions if the mesh has normals.
+    pub normals: Option<Vec<Vec3>>,
+}
+
+///
+/// A triangle [Mesh] which is animated by playing back baked per-frame vertex positions, also called a vertex cache or a point cache,
+/// for example the result of a cloth or fluid simulation exported from a content creation tool as an Alembic or point cache.
+/// The topology, ie. the number of vertices and the triangles, must be the same in all frames.
+///
+/// Each time [Geometry::animate] is called, the positions (and normals) are interpolated between the two frames closest to the given time,
+/// weighted by how close the time is to each of them, and uploaded to the mesh.
+/// The frames can be given up front, see [VertexCacheMesh::new] or [VertexCacheMesh::from_cpu_meshes], or be streamed in during playback
+/// using [VertexCacheMesh::push_frame] and [VertexCacheMesh::remove_frames_before], so that a long cache does not have to be kept in memory.
+///
+pub struct VertexCacheMesh {
+    mesh: Mesh,
+    cpu_mesh: CpuMesh,
+    frames: Vec<VertexCacheFrame>,
+    looping: bool,
+    time: Option<f32>,
+}
+
+impl VertexCacheMesh {
+    ///
+    /// Creates a new vertex cache mesh from the given [CpuMesh], which defines the triangles and the other vertex attributes, and frames.
+    /// The frames are sorted by time.
+    ///
+    /// # Panics
+    /// Will panic if the number of positions or normals in a frame does not match the number of vertices in the mesh.
+    ///
+    pub fn new(context: &Context, cpu_mesh: &CpuMesh, frames: Vec<VertexCacheFrame>) -> Self {
+        let mut vertex_cache_mesh = Self {
+            mesh: Mesh::new(context, cpu_mesh),
+            cpu_mesh: cpu_mesh.clone(),
+            frames: Vec::with_capacity(frames.len()),
+            looping: true,
+            time: None,
+        };
+        for frame in frames {
+            vertex_cache_mesh.push_frame(frame);
+        }
+        vertex_cache_mesh
+    }
+
+    ///
+    /// Creates a new vertex cache mesh from a sequence of meshes with the same topology, one for each frame, for example
+    /// a sequence of OBJ files exported from a simulation, where the frames are played back with the given number of frames per second.
+    /// The triangles and the other vertex attributes are taken from the first mesh.
+    ///
+    /// # Panics
+    /// Will panic if there are no meshes or if the number of vertices is not the same in all the meshes.
+    ///
+    pub fn from_cpu_meshes(context: &Context, cpu_meshes: &[CpuMesh], frame_rate: f32) -> Self {
+        let frames = cpu_meshes
+            .iter()
+            .enumerate()
+            .map(|(i, cpu_mesh)| VertexCacheFrame {
+                time: i as f32 / frame_rate,
+                positions: cpu_mesh.positions.to_f32(),
+                normals: cpu_mesh.normals.clone(),
+            })
+            .collect();
+        Self::new(
+            context,
+            cpu_meshes
+                .first()
+                .expect("at least one mesh is required to create a vertex cache mesh"),
+            frames,
+        )
+    }
+
+    ///
+    /// Returns the frames.
+    ///
+    pub fn frames(&self) -> &[VertexCacheFrame] {
+        &self.frames
+    }
+
+    ///
+    /// Adds the given frame, for example when streaming the frames of a long vertex cache from disk.
+    /// The frames are kept sorted by time.
+    ///
+    /// # Panics
+    /// Will panic if the number of positions or normals in the frame does not match the number of vertices in the mesh.
+    ///
+    pub fn push_frame(&mut self, frame: VertexCacheFrame) {
+        let vertex_count = self.mesh.vertex_count() as usize;
+        if frame.positions.len() != vertex_count
+            || frame
+                .normals
+                .as_ref()
+                .is_some_and(|n| n.len() != vertex_count)
+        {
+            panic!(
+                "the number of vertices in the vertex cache frame at time {} does not match the number of vertices {} in the mesh",
+                frame.time, vertex_count
+            );
+        }
+        let index = self.frames.partition_point(|f| f.time <= frame.time);
+        self.frames.insert(index, frame);
+        self.time = None;
+    }
+
+    ///
+    /// Removes the frames that are no longer needed to play back the animation from the given time, ie. all frames before the given time
+    /// except the last of them, which is needed for interpolation. Use this together with [VertexCacheMesh::push_frame] when streaming frames
+    /// and looping is disabled, see [VertexCacheMesh::set_looping].
+    ///
+    pub fn remove_frames_before(&mut self, time: f32) {
+        let count = self.frames.partition_point(|f| f.time <= time);
+        self.frames.drain(..count.saturating_sub(1));
+    }
+
+    ///
+    /// Returns the time from the first to the last frame in seconds.
+    ///
+    pub fn duration(&self) -> f32 {
+        match (self.frames.first(), self.frames.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        }
+    }
+
+    ///
+    /// Returns whether the animation is looping, see [Self::set_looping].
+    ///
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    ///
+    /// Sets whether the animation starts over from the first frame after the last frame, which is the default,
+    /// or holds the last frame.
+    ///
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    ///
+    /// Deforms the mesh to the given time in seconds immediately by interpolating between the two frames closest to the given time.
+    /// The time is wrapped to the interval between the first and the last frame if looping, see [Self::set_looping], otherwise it is clamped to that interval.
+    ///
+    pub fn set_time(&mut self, time: f32) {
+        let (Some(first), Some(last)) = (self.frames.first(), self.frames.last()) else {
+            return;
+        };
+        let duration = last.time - first.time;
+        let time = if self.looping && duration > 0.0 {
+            first.time + (time - first.time).rem_euclid(duration)
+        } else {
+            time.clamp(first.time, last.time)
+        };
+        if self.time == Some(time) {
+            return;
+        }
+        self.time = Some(time);
+
+        let index = self
+            .frames
+            .partition_point(|f| f.time <= time)
+            .clamp(1, self.frames.len());
+        let frame0 = &self.frames[index - 1];
+        let frame1 = self.frames.get(index).unwrap_or(frame0);
+        let weight = if frame1.time > frame0.time {
+            (time - frame0.time) / (frame1.time - frame0.time)
+        } else {
+            0.0
+        };
+        let positions = frame0
+            .positions
+            .iter()
+            .zip(frame1.positions.iter())
+            .map(|(p0, p1)| p0.lerp(*p1, weight))
+            .collect::<Vec<_>>();
+        let normals = match (&frame0.normals, &frame1.normals) {
+            (Some(normals0), Some(normals1)) => Some(
+                normals0
+                    .iter()
+                    .zip(normals1.iter())
+                    .map(|(n0, n1)| n0.lerp(*n1, weight).normalize())
+                    .collect::<Vec<_>>(),
+            ),
+            _ if self.cpu_mesh.normals.is_some() => {
+                self.cpu_mesh.positions = Positions::F32(positions.clone());
+                self.cpu_mesh.compute_normals();
+                self.cpu_mesh.normals.clone()
+            }
+            _ => None,
+        };
+        self.mesh.update_positions(&positions);
+        if let Some(normals) = normals {
+            self.mesh.update_normals(&normals);
+        }
+    }
+}
+
+impl Deref for VertexCacheMesh {
+    type Target = Mesh;
+    fn deref(&self) -> &Self::Target {
+        &self.mesh
+    }
+}
+
+impl std::ops::DerefMut for VertexCacheMesh {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.mesh
+    }
+}
+
+impl<'a> IntoIterator for &'a VertexCacheMesh {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for VertexCacheMesh {
+    impl_geometry_body!(deref);
+
+    fn animate(&mut self, time: f32) {
+        self.mesh.animate(time);
+        self.set_time(time);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::core::*;
+
+///
+/// The format used to store a vertex attribute on the GPU, see [VertexFormat].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttributeFormat {
+    /// 32-bit floating point values, which is the format of the attributes in a [CpuMesh](crate::CpuMesh).
+    #[default]
+    F32,
+    /// 16-bit floating point values, which for positions gives a precision relative to the distance from the origin,
+    /// so [AttributeFormat::Normalized16] is usually a better choice for positions.
+    F16,
+    /// 16-bit integers which are normalized when read in the shader.
+    /// Positions are quantized within the bounding box of the mesh, normals and tangents are signed values in the range `[-1..1]`
+    /// and uv coordinates and colors are unsigned values in the range `[0..1]`, so uv coordinates outside this range are clamped.
+    Normalized16,
+    /// 8-bit integers which are normalized when read in the shader, see [AttributeFormat::Normalized16].
+    Normalized8,
+}
+
+impl AttributeFormat {
+    ///
+    /// Returns the number of bytes used to store each component of an attribute in this format.
+    ///
+    pub fn component_size(&self) -> usize {
+        match self {
+            Self::F32 => 4,
+            Self::F16 | Self::Normalized16 => 2,
+            Self::Normalized8 => 1,
+        }
+    }
+}
+
+///
+/// Specifies the format used to store each vertex attribute of a [Mesh](crate::Mesh) on the GPU, see [Mesh::new_with_format](crate::Mesh::new_with_format).
+/// Using a smaller format reduces the memory usage and the memory bandwidth needed when rendering, which is especially important for large meshes, for example 3D scans,
+/// at the cost of precision. The attributes are packed from the 32-bit floating point values of the [CpuMesh](crate::CpuMesh) when the mesh is constructed.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VertexFormat {
+    /// The format of the positions.
+    pub positions: AttributeFormat,
+    /// The format of the normals.
+    pub normals: AttributeFormat,
+    /// The format of the tangents.
+    pub tangents: AttributeFormat,
+    /// The format of the uv coordinates.
+    pub uvs: AttributeFormat,
+    /// The format of the colors.
+    pub colors: AttributeFormat,
+}
+
+impl VertexFormat {
+    ///
+    /// All attributes are stored as 32-bit floating point values, which is the default.
+    ///
+    pub const FULL: Self = Self {
+        positions: AttributeFormat::F32,
+        normals: AttributeFormat::F32,
+        tangents: AttributeFormat::F32,
+        uvs: AttributeFormat::F32,
+        colors: AttributeFormat::F32,
+    };
+
+    ///
+    /// The positions, normals and tangents are stored as normalized 16-bit integers, the uv coordinates as 16-bit floating point values
+    /// and the colors as normalized 8-bit integers, which uses less than half the memory of [VertexFormat::FULL]
+    /// with a precision of the positions of 1/65535 of the size of the mesh.
+    ///
+    pub const COMPACT: Self = Self {
+        positions: AttributeFormat::Normalized16,
+        normals: AttributeFormat::Normalized16,
+        tangents: AttributeFormat::Normalized16,
+        uvs: AttributeFormat::F16,
+        colors: AttributeFormat::Normalized8,
+    };
+}
+
+impl Default for VertexFormat {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+///
+/// Returns a vertex buffer with the given positions in the given format together with the transformation which decodes the stored positions.
+///
+pub(super) fn pack_positions(
+    context: &Context,
+    positions: &[Vec3],
+    format: AttributeFormat,
+) -> (VertexBuffer, Mat4) {
+    let mut buffer = VertexBuffer::new(context);
+    let position_decoding = fill_packed_positions(&mut buffer, positions, format);
+    (buffer, position_decoding)
+}
+
+///
+/// Fills the given vertex buffer with the given positions in the given format, reusing the memory of the buffer,
+/// and returns the transformation which decodes the stored positions.
+///
+pub(super) fn fill_packed_positions(
+    buffer: &mut VertexBuffer,
+    positions: &[Vec3],
+    format: AttributeFormat,
+) -> Mat4 {
+    match format {
+        AttributeFormat::Normalized16 | AttributeFormat::Normalized8 => {
+            let aabb = AxisAlignedBoundingBox::new_with_positions(positions);
+            let (min, size) = if aabb.is_empty() {
+                (vec3(0.0, 0.0, 0.0), vec3(1.0, 1.0, 1.0))
+            } else {
+                (aabb.min(), aabb.size())
+            };
+            let size = vec3(
+                size.x.max(f32::EPSILON),
+                size.y.max(f32::EPSILON),
+                size.z.max(f32::EPSILON),
+            );
+            let data = positions
+                .iter()
+                .map(|p| {
+                    let p = p - min;
+                    [p.x / size.x, p.y / size.y, p.z / size.z]
+                })
+                .collect::<Vec<_>>();
+            fill_packed(buffer, &data, format, false);
+            Mat4::from_translation(min) * Mat4::from_nonuniform_scale(size.x, size.y, size.z)
+        }
+        _ => {
+            fill_packed(
+                buffer,
+                &positions
+                    .iter()
+                    .map(|p| [p.x, p.y, p.z])
+                    .collect::<Vec<_>>(),
+                format,
+                false,
+            );
+            Mat4::identity()
+        }
+    }
+}
+
+///
+/// Returns a vertex buffer with the given data in the given format, where normalized integers are signed if `signed` is true.
+///
+pub(super) fn pack<const N: usize>(
+    context: &Context,
+    data: &[[f32; N]],
+    format: AttributeFormat,
+    signed: bool,
+) -> VertexBuffer
+where
+    [f32; N]: BufferDataType,
+    [f16; N]: BufferDataType,
+    [u16; N]: BufferDataType,
+    [i16; N]: BufferDataType,
+    [u8; N]: BufferDataType,
+    [i8; N]: BufferDataType,
+{
+    let mut buffer = VertexBuffer::new(context);
+    if !data.is_empty() {
+        fill_packed(&mut buffer, data, format, signed);
+    }
+    buffer
+}
+
+///
+/// Fills the given vertex buffer with the given data in the given format, reusing the memory of the buffer, see [pack].
+///
+pub(super) fn fill_packed<const N: usize>(
+    buffer: &mut VertexBuffer,
+    data: &[[f32; N]],
+    format: AttributeFormat,
+    signed: bool,
+) where
+    [f32; N]: BufferDataType,
+    [f16; N]: BufferDataType,
+    [u16; N]: BufferDataType,
+    [i16; N]: BufferDataType,
+    [u8; N]: BufferDataType,
+    [i8; N]: BufferDataType,
+{
+    let scaled = |max: f32| {
+        data.iter()
+            .map(|v| {
+                v.map(|c| {
+                    let c = if signed {
+                        c.clamp(-1.0, 1.0)
+                    } else {
+                        c.clamp(0.0, 1.0)
+                    };
+                    (c * max).round()
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    match format {
+        AttributeFormat::F32 => buffer.fill(data),
+        AttributeFormat::F16 => buffer.fill(
+            &data
+                .iter()
+                .map(|v| v.map(f16::from_f32))
+                .collect::<Vec<_>>(),
+        ),
+        AttributeFormat::Normalized16 if signed => buffer.fill_normalized(
+            &scaled(i16::MAX as f32)
+                .into_iter()
+                .map(|v| v.map(|c| c as i16))
+                .collect::<Vec<_>>(),
+        ),
+        AttributeFormat::Normalized16 => buffer.fill_normalized(
+            &scaled(u16::MAX as f32)
+                .into_iter()
+                .map(|v| v.map(|c| c as u16))
+                .collect::<Vec<_>>(),
+        ),
+        AttributeFormat::Normalized8 if signed => buffer.fill_normalized(
+            &scaled(i8::MAX as f32)
+                .into_iter()
+                .map(|v| v.map(|c| c as i8))
+                .collect::<Vec<_>>(),
+        ),
+        AttributeFormat::Normalized8 => buffer.fill_normalized(
+            &scaled(u8::MAX as f32)
+                .into_iter()
+                .map(|v| v.map(|c| c as u8))
+                .collect::<Vec<_>>(),
+        ),
+    }
+}
@@ -0,0 +1,422 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Specifies which voxels are filled when voxelizing a mesh, see [Voxels::new].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VoxelizationMode {
+    /// Only the voxels that intersect the surface of the mesh are filled.
+    Surface,
+    /// The voxels that intersect the surface of the mesh and the voxels inside the mesh are filled.
+    /// The mesh should be closed, otherwise the inside is not well defined and some columns of voxels might only contain the surface.
+    #[default]
+    Solid,
+}
+
+///
+/// A regular grid of cubic voxels where each voxel is either filled or empty, for example the result of voxelizing a [CpuMesh].
+/// The voxels can be used for collision detection, volume and thickness analysis before 3D printing or as the input to
+/// lighting techniques that require a volumetric representation of the scene.
+///
+/// The voxel with index `(0, 0, 0)` is placed at the minimum corner of the grid, see [Voxels::aabb].
+///
+#[derive(Clone, Debug)]
+pub struct Voxels {
+    min: Vec3,
+    voxel_size: f32,
+    resolution: (u32, u32, u32),
+    data: Vec<bool>,
+}
+
+impl Voxels {
+    ///
+    /// Voxelizes the given mesh, where the resolution is the number of voxels along the longest side of the bounding box of the mesh.
+    /// The number of voxels along the other sides is chosen such that the voxels are cubes which cover the entire mesh.
+    ///
+    /// The triangles are only tested against the voxels within their own bounds, so the cost scales with the number of triangles
+    /// and the voxelized area of the surface, rather than with the number of triangles times the number of voxels.
+    ///
+    pub fn new(cpu_mesh: &CpuMesh, resolution: u32, mode: VoxelizationMode) -> Self {
+        let positions = cpu_mesh.positions.to_f32();
+        let aabb = AxisAlignedBoundingBox::new_with_positions(&positions);
+        let resolution = resolution.max(1);
+        let (min, size) = if aabb.is_empty() {
+            (vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0))
+        } else {
+            (aabb.min(), aabb.size())
+        };
+        let voxel_size = (size.x.max(size.y).max(size.z) / resolution as f32).max(f32::EPSILON);
+        let count = |extent: f32| ((extent / voxel_size).ceil() as u32).clamp(1, resolution);
+        let mut voxels = Self::new_empty(
+            min,
+            voxel_size,
+            (count(size.x), count(size.y), count(size.z)),
+        );
+
+        let mut triangles = Vec::with_capacity(cpu_mesh.triangle_count());
+        cpu_mesh.for_each_triangle(|i0, i1, i2| {
+            triangles.push([positions[i0], positions[i1], positions[i2]])
+        });
+        for triangle in triangles.iter() {
+            voxels.fill_surface(triangle);
+        }
+        if mode == VoxelizationMode::Solid {
+            voxels.fill_interior(&triangles);
+        }
+        voxels
+    }
+
+    ///
+    /// Creates a grid of empty voxels with the given minimum corner, size of each voxel and number of voxels along each axis.
+    ///
+    pub fn new_empty(min: Vec3, voxel_size: f32, resolution: (u32, u32, u32)) -> Self {
+        Self {
+            min,
+            voxel_size,
+            resolution,
+            data: vec![false; (resolution.0 * resolution.1 * resolution.2) as usize],
+        }
+    }
+
+    ///
+    /// Returns the number of voxels along each axis.
+    ///
+    pub fn resolution(&self) -> (u32, u32, u32) {
+        self.resolution
+    }
+
+    ///
+    /// Returns the length of the sides of each voxel.
+    ///
+    pub fn voxel_size(&self) -> f32 {
+        self.voxel_size
+    }
+
+    ///
+    /// Returns the bounding box of the voxel grid, which contains the voxelized mesh.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        let (x, y, z) = self.resolution;
+        AxisAlignedBoundingBox::new_with_positions(&[
+            self.min,
+            self.min + self.voxel_size * vec3(x as f32, y as f32, z as f32),
+        ])
+    }
+
+    ///
+    /// Returns whether the voxel with the given index is filled. Voxels outside the grid are empty.
+    ///
+    pub fn is_filled(&self, x: u32, y: u32, z: u32) -> bool {
+        self.index(x, y, z).map(|i| self.data[i]).unwrap_or(false)
+    }
+
+    ///
+    /// Sets whether the voxel with the given index is filled.
+    ///
+    /// # Panic
+    /// Will panic if the index is outside the grid.
+    ///
+    pub fn set_filled(&mut self, x: u32, y: u32, z: u32, filled: bool) {
+        let index = self
+            .index(x, y, z)
+            .expect("the voxel index is outside the voxel grid");
+        self.data[index] = filled;
+    }
+
+    ///
+    /// Returns the index of the voxel containing the given position or `None` if the position is outside the grid.
+    ///
+    pub fn voxel_at(&self, position: Vec3) -> Option<(u32, u32, u32)> {
+        let p = (position - self.min) / self.voxel_size;
+        let (x, y, z) = self.resolution;
+        if p.x < 0.0 || p.y < 0.0 || p.z < 0.0 {
+            return None;
+        }
+        let (i, j, k) = (p.x as u32, p.y as u32, p.z as u32);
+        (i < x && j < y && k < z).then_some((i, j, k))
+    }
+
+    ///
+    /// Returns whether the given position is inside a filled voxel.
+    ///
+    pub fn contains(&self, position: Vec3) -> bool {
+        self.voxel_at(position)
+            .map(|(x, y, z)| self.is_filled(x, y, z))
+            .unwrap_or(false)
+    }
+
+    ///
+    /// Returns the position of the center of the voxel with the given index.
+    ///
+    pub fn voxel_center(&self, x: u32, y: u32, z: u32) -> Vec3 {
+        self.min + self.voxel_size * vec3(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5)
+    }
+
+    ///
+    /// Returns an iterator over the indices of the filled voxels.
+    ///
+    pub fn filled_voxels(&self) -> impl Iterator<Item = (u32, u32, u32)> + '_ {
+        let (x, y, _) = self.resolution;
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|(_, filled)| **filled)
+            .map(move |(i, _)| {
+                let i = i as u32;
+                (i % x, (i / x) % y, i / (x * y))
+            })
+    }
+
+    ///
+    /// Returns the number of filled voxels.
+    ///
+    pub fn filled_count(&self) -> usize {
+        self.data.iter().filter(|filled| **filled).count()
+    }
+
+    ///
+    /// Returns the total volume of the filled voxels, which for a solid voxelization approximates the volume of the mesh.
+    ///
+    pub fn volume(&self) -> f32 {
+        self.filled_count() as f32 * self.voxel_size.powi(3)
+    }
+
+    ///
+    /// Returns a mesh which consists of the faces of the filled voxels that are not shared with another filled voxel,
+    /// ie. the outer surface of the voxels, for example to visualize the voxelization.
+    ///
+    pub fn to_cpu_mesh(&self) -> CpuMesh {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut indices = Vec::new();
+        let faces = [
+            (
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(0.0, 0.0, 1.0),
+            ),
+            (
+                vec3(0.0, 1.0, 0.0),
+                vec3(0.0, 0.0, 1.0),
+                vec3(1.0, 0.0, 0.0),
+            ),
+            (
+                vec3(0.0, 0.0, 1.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+            ),
+        ];
+        for (x, y, z) in self.filled_voxels() {
+            let center = self.voxel_center(x, y, z);
+            for (axis, (normal, tangent, bitangent)) in faces.iter().enumerate() {
+                for sign in [-1.0, 1.0] {
+                    let neighbour = match (axis, sign > 0.0) {
+                        (0, true) => self.is_filled(x + 1, y, z),
+                        (0, false) => x > 0 && self.is_filled(x - 1, y, z),
+                        (1, true) => self.is_filled(x, y + 1, z),
+                        (1, false) => y > 0 && self.is_filled(x, y - 1, z),
+                        (2, true) => self.is_filled(x, y, z + 1),
+                        _ => z > 0 && self.is_filled(x, y, z - 1),
+                    };
+                    if neighbour {
+                        continue;
+                    }
+                    let h = 0.5 * self.voxel_size;
+                    let n = sign * normal;
+                    let (t, b) = (h * tangent, sign * h * bitangent);
+                    let start = positions.len() as u32;
+                    positions.extend([
+                        center + h * n - t - b,
+                        center + h * n + t - b,
+                        center + h * n + t + b,
+                        center + h * n - t + b,
+                    ]);
+                    normals.extend([n; 4]);
+                    indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+                }
+            }
+        }
+        CpuMesh {
+            positions: Positions::F32(positions),
+            indices: Indices::U32(indices),
+            normals: Some(normals),
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Returns a [CpuVoxelGrid] where the filled voxels have a value of 255 and the empty voxels a value of 0, which for example can be rendered
+    /// with a [VoxelGrid] using an [IsosurfaceMaterial]. The voxel grid is centered at the origin, so it has to be translated to the center
+    /// of [Voxels::aabb] to be placed at the voxelized mesh.
+    ///
+    #[cfg(feature = "volume")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "volume")))]
+    pub fn to_cpu_voxel_grid(&self) -> CpuVoxelGrid {
+        let (x, y, z) = self.resolution;
+        CpuVoxelGrid {
+            name: "voxels".to_owned(),
+            voxels: CpuTexture3D {
+                data: TextureData::RU8(
+                    self.data
+                        .iter()
+                        .map(|filled| if *filled { 255 } else { 0 })
+                        .collect(),
+                ),
+                width: x,
+                height: y,
+                depth: z,
+                ..Default::default()
+            },
+            size: self.voxel_size * vec3(x as f32, y as f32, z as f32),
+        }
+    }
+
+    fn index(&self, x: u32, y: u32, z: u32) -> Option<usize> {
+        let (rx, ry, rz) = self.resolution;
+        (x < rx && y < ry && z < rz).then(|| (x + rx * (y + ry * z)) as usize)
+    }
+
+    fn voxel_range(&self, min: Vec3, max: Vec3) -> [(u32, u32); 3] {
+        let (rx, ry, rz) = self.resolution;
+        let range = |min: f32, max: f32, origin: f32, count: u32| {
+            let to_index = |v: f32| {
+                (((v - origin) / self.voxel_size).floor().max(0.0) as u32)
+                    .min(count.saturating_sub(1))
+            };
+            (to_index(min), to_index(max))
+        };
+        [
+            range(min.x, max.x, self.min.x, rx),
+            range(min.y, max.y, self.min.y, ry),
+            range(min.z, max.z, self.min.z, rz),
+        ]
+    }
+
+    fn fill_surface(&mut self, triangle: &[Vec3; 3]) {
+        let min = triangle[0]
+            .zip(triangle[1], f32::min)
+            .zip(triangle[2], f32::min);
+        let max = triangle[0]
+            .zip(triangle[1], f32::max)
+            .zip(triangle[2], f32::max);
+        let [(x0, x1), (y0, y1), (z0, z1)] = self.voxel_range(min, max);
+        let half_size = vec3(0.5, 0.5, 0.5) * self.voxel_size;
+        for z in z0..=z1 {
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    let center = self.voxel_center(x, y, z);
+                    if triangle_box_overlap(center, half_size, triangle) {
+                        let index = self.index(x, y, z).unwrap();
+                        self.data[index] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    ///
+    /// Casts a ray along the z-axis through the center of each column of voxels and fills the voxels between each
+    /// pair of intersections with the surface, ie. in between entering and leaving the mesh.
+    ///
+    fn fill_interior(&mut self, triangles: &[[Vec3; 3]]) {
+        let (rx, ry, rz) = self.resolution;
+        let mut intersections = vec![Vec::new(); (rx * ry) as usize];
+        for [a, b, c] in triangles.iter() {
+            let area = edge_function(a.truncate(), b.truncate(), c.truncate());
+            if area == 0.0 {
+                continue;
+            }
+            // Make the triangle counter-clockwise in the xy-plane
+            let (b, c) = if area > 0.0 { (b, c) } else { (c, b) };
+            let min = a.zip(*b, f32::min).zip(*c, f32::min);
+            let max = a.zip(*b, f32::max).zip(*c, f32::max);
+            let [(x0, x1), (y0, y1), _] = self.voxel_range(min, max);
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    let p = self.voxel_center(x, y, 0).truncate();
+                    let weights = [(b, c, a), (c, a, b), (a, b, c)].map(|(e0, e1, _)| {
+                        let w = edge_function(e0.truncate(), e1.truncate(), p);
+                        // Points on an edge shared by two triangles belong to only one of the triangles
+                        let edge = e1.truncate() - e0.truncate();
+                        let inclusive = edge.y > 0.0 || (edge.y == 0.0 && edge.x < 0.0);
+                        (w > 0.0 || (w == 0.0 && inclusive)).then_some(w)
+                    });
+                    if let [Some(wa), Some(wb), Some(wc)] = weights {
+                        let sum = wa + wb + wc;
+                        let z = (wa * a.z + wb * b.z + wc * c.z) / sum;
+                        intersections[(x + rx * y) as usize].push(z);
+                    }
+                }
+            }
+        }
+        for y in 0..ry {
+            for x in 0..rx {
+                let column = &mut intersections[(x + rx * y) as usize];
+                column.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                for pair in column.chunks_exact(2) {
+                    for z in 0..rz {
+                        let center = self.voxel_center(x, y, z).z;
+                        if center >= pair[0] && center <= pair[1] {
+                            let index = self.index(x, y, z).unwrap();
+                            self.data[index] = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn edge_function(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+///
+/// Tests whether the triangle overlaps the axis aligned box with the given center and half size using the separating axis theorem.
+///
+fn triangle_box_overlap(center: Vec3, half_size: Vec3, triangle: &[Vec3; 3]) -> bool {
+    let v = triangle.map(|p| p - center);
+    let separated = |axis: Vec3| {
+        let p = v.map(|p| p.dot(axis));
+        let r =
+            half_size.x * axis.x.abs() + half_size.y * axis.y.abs() + half_size.z * axis.z.abs();
+        p[0].min(p[1]).min(p[2]) > r || p[0].max(p[1]).max(p[2]) < -r
+    };
+    let edges = [v[1] - v[0], v[2] - v[1], v[0] - v[2]];
+    let axes = [
+        vec3(1.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        vec3(0.0, 0.0, 1.0),
+    ];
+    if axes.iter().any(|axis| separated(*axis)) {
+        return false;
+    }
+    if separated(edges[0].cross(edges[1])) {
+        return false;
+    }
+    !edges
+        .iter()
+        .any(|edge| axes.iter().any(|axis| separated(axis.cross(*edge))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn voxelize_cube() {
+        let cube = CpuMesh::cube();
+        let surface = Voxels::new(&cube, 8, VoxelizationMode::Surface);
+        assert_eq!(surface.resolution(), (8, 8, 8));
+        assert_eq!(surface.filled_count(), 8 * 8 * 8 - 6 * 6 * 6);
+        assert!(surface.is_filled(0, 3, 4));
+        assert!(!surface.is_filled(3, 3, 4));
+
+        let solid = Voxels::new(&cube, 8, VoxelizationMode::Solid);
+        assert_eq!(solid.filled_count(), 8 * 8 * 8);
+        assert!((solid.volume() - 8.0).abs() < 0.0001);
+        assert_eq!(solid.voxel_at(vec3(0.1, -0.1, 0.9)), Some((4, 3, 7)));
+        assert!(solid.voxel_at(vec3(1.5, 0.0, 0.0)).is_none());
+    }
+}
@@ -32,6 +32,18 @@ mod point_light;
 #[doc(inline)]
 pub use point_light::*;
 
+mod clustered_lights;
+#[doc(inline)]
+pub use clustered_lights::*;
+
+mod sphere_area_light;
+#[doc(inline)]
+pub use sphere_area_light::*;
+
+mod rectangle_area_light;
+#[doc(inline)]
+pub use rectangle_area_light::*;
+
 mod ambient_light;
 #[doc(inline)]
 pub use ambient_light::*;
@@ -40,12 +52,33 @@ mod environment;
 #[doc(inline)]
 pub use environment::*;
 
+mod reflection_probe;
+#[doc(inline)]
+pub use reflection_probe::*;
+
+mod reflection_plane;
+#[doc(inline)]
+pub use reflection_plane::*;
+
+mod lightmap_baker;
+#[doc(inline)]
+pub use lightmap_baker::*;
+
+mod ambient_occlusion_baker;
+#[doc(inline)]
+pub use ambient_occlusion_baker::*;
+
+mod volumetric_lightmap;
+#[doc(inline)]
+pub use volumetric_lightmap::*;
+
 use crate::core::*;
 use crate::renderer::camera::*;
 
 ///
 /// Specifies how the intensity of a light fades over distance.
 /// The light intensity is scaled by ``` 1 / max(1, constant + distance * linear + distance * distance * quadratic) ```.
+/// If a range is specified, the intensity is furthermore smoothly faded to zero at that distance by ``` (1 - (distance / range)^4)^2 ```.
 ///
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Attenuation {
@@ -55,6 +88,32 @@ pub struct Attenuation {
     pub linear: f32,
     /// Quadratic attenuation factor.
     pub quadratic: f32,
+    /// The distance at which the light intensity reaches zero. If `None`, the light intensity never reaches zero.
+    pub range: Option<f32>,
+}
+
+impl Attenuation {
+    ///
+    /// Physically based attenuation where the light intensity falls off with the inverse square of the distance
+    /// and is smoothly faded to zero at the given range.
+    ///
+    pub fn inverse_square(range: f32) -> Self {
+        Self {
+            constant: 0.0,
+            linear: 0.0,
+            quadratic: 1.0,
+            range: Some(range),
+        }
+    }
+
+    pub(crate) fn uniform(&self) -> Vec4 {
+        vec4(
+            self.constant,
+            self.linear,
+            self.quadratic,
+            self.range.unwrap_or(0.0).max(0.0),
+        )
+    }
 }
 
 impl Default for Attenuation {
@@ -63,6 +122,7 @@ impl Default for Attenuation {
             constant: 1.0,
             linear: 0.0,
             quadratic: 0.0,
+            range: None,
         }
     }
 }
@@ -77,6 +137,8 @@ pub trait Light {
     fn shader_source(&self, i: u32) -> String;
 
     /// Should bind the uniforms that is needed for calculating this lights contribution to the color in [Light::shader_source].
+    /// The built-in lights store their parameters in a `Lights` uniform block which is shared between all programs,
+    /// so the parameters are only sent to the GPU when they change instead of once for each material.
     fn use_uniforms(&self, program: &Program, i: u32);
 
     ///
@@ -157,6 +219,75 @@ pub fn lights_shader_source(lights: &[&dyn Light], lighting_model: LightingModel
     shader_source
 }
 
+/// The maximum number of lights, including lights which do not use the `Lights` uniform block, when using one of the built-in lights.
+const MAX_LIGHT_COUNT: u32 = 64;
+/// The number of [Vec4]s in the `Lights` uniform block reserved for each light.
+const LIGHT_UNIFORM_SLOT_SIZE: u32 = 12;
+
+///
+/// Returns the shader source which declares the `Lights` uniform block, which contains the parameters of all the built-in lights, unless it is already declared,
+/// and defines each of the given uniforms, ordered as the data given to [use_light_uniform_block], as `light` followed by the index of the light, an underscore and the name,
+/// for example `light0_color`.
+/// Each uniform is a `float`, `vec2`, `vec3`, `vec4` or `mat4` and occupies one [Vec4] in the block, except a `mat4` which occupies four.
+///
+pub(crate) fn light_uniform_block_source(i: u32, uniforms: &[(&str, &str)]) -> String {
+    let mut source = format!(
+        "
+#ifndef LIGHTS_UNIFORM_BLOCK
+#define LIGHTS_UNIFORM_BLOCK
+layout (std140) uniform Lights {{
+    vec4 lightData[{}];
+}};
+#endif
+",
+        MAX_LIGHT_COUNT * LIGHT_UNIFORM_SLOT_SIZE
+    );
+    let mut index = i * LIGHT_UNIFORM_SLOT_SIZE;
+    for (uniform_type, name) in uniforms {
+        let value = match *uniform_type {
+            "float" => format!("lightData[{}].x", index),
+            "vec2" => format!("lightData[{}].xy", index),
+            "vec3" => format!("lightData[{}].xyz", index),
+            "vec4" => format!("lightData[{}]", index),
+            "mat4" => format!(
+                "mat4(lightData[{}], lightData[{}], lightData[{}], lightData[{}])",
+                index,
+                index + 1,
+                index + 2,
+                index + 3
+            ),
+            _ => unreachable!(),
+        };
+        index += if *uniform_type == "mat4" { 4 } else { 1 };
+        source.push_str(&format!("#define light{}_{} {}\n", i, name, value));
+    }
+    source
+}
+
+///
+/// Uses the given data, where each uniform declared in [light_uniform_block_source] is one [Vec4] or four for a [Mat4], for the light with the given index
+/// in the `Lights` uniform block, which is shared between all programs, so the data is only sent to the GPU when the light changes.
+///
+/// # Panic
+/// Will panic if the index is not smaller than the maximum number of lights.
+///
+pub(crate) fn use_light_uniform_block(program: &Program, i: u32, data: &[Vec4]) {
+    assert!(
+        i < MAX_LIGHT_COUNT,
+        "at most {} lights are supported when using the built-in lights",
+        MAX_LIGHT_COUNT
+    );
+    let mut slot = [0.0; LIGHT_UNIFORM_SLOT_SIZE as usize * 4];
+    for (values, value) in slot.chunks_mut(4).zip(data) {
+        values.copy_from_slice(AsRef::<[f32; 4]>::as_ref(value));
+    }
+    program.use_shared_uniform_block(
+        "Lights",
+        &[LIGHT_UNIFORM_SLOT_SIZE * 4; MAX_LIGHT_COUNT as usize],
+        |buffer| buffer.update(i, &slot),
+    );
+}
+
 fn shadow_matrix(camera: &Camera) -> Mat4 {
     let bias_matrix = crate::Mat4::new(
         0.5, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.5, 0.5, 0.5, 1.0,
@@ -12,6 +12,14 @@ pub struct AmbientLight {
     pub color: Srgba,
     /// The light shining from the environment. This is calculated based on an environment map.
     pub environment: Option<Environment>,
+    /// The rotation of the environment, which makes it possible to rotate the environment at runtime without computing a new [Environment].
+    /// Use the same rotation for a [Skybox] showing the same environment map (see [Skybox::set_rotation]).
+    pub environment_rotation: Mat3,
+    /// The environment which the light is crossfading to, see [AmbientLight::crossfade_to].
+    pub next_environment: Option<Environment>,
+    /// How far the crossfade from [AmbientLight::environment] to [AmbientLight::next_environment] has progressed,
+    /// where 0 is only the environment and 1 is only the next environment.
+    pub crossfade: f32,
 }
 
 impl AmbientLight {
@@ -21,6 +29,9 @@ impl AmbientLight {
             intensity,
             color,
             environment: None,
+            environment_rotation: Mat3::identity(),
+            next_environment: None,
+            crossfade: 0.0,
         }
     }
 
@@ -35,6 +46,43 @@ impl AmbientLight {
             intensity,
             color,
             environment: Some(Environment::new(context, environment_map)),
+            environment_rotation: Mat3::identity(),
+            next_environment: None,
+            crossfade: 0.0,
+        }
+    }
+
+    ///
+    /// Starts a crossfade from the current environment to the given environment, for example for a smooth time-of-day transition,
+    /// which is advanced using [AmbientLight::advance_crossfade].
+    /// If a crossfade is already in progress, the environment which is crossfaded to is replaced.
+    /// If the light does not have an environment, the given environment is used immediately.
+    ///
+    pub fn crossfade_to(&mut self, environment: Environment) {
+        if self.environment.is_some() {
+            self.next_environment = Some(environment);
+            self.crossfade = 0.0;
+        } else {
+            self.environment = Some(environment);
+        }
+    }
+
+    ///
+    /// Advances the crossfade by the given amount, for example the elapsed time divided by the duration of the crossfade.
+    /// When the crossfade is done, the environment is replaced by the environment which is crossfaded to.
+    /// Returns whether or not a crossfade is still in progress.
+    ///
+    pub fn advance_crossfade(&mut self, amount: f32) -> bool {
+        if self.next_environment.is_none() {
+            return false;
+        }
+        self.crossfade += amount;
+        if self.crossfade >= 1.0 {
+            self.environment = self.next_environment.take();
+            self.crossfade = 0.0;
+            false
+        } else {
+            true
         }
     }
 }
@@ -42,12 +90,15 @@ impl AmbientLight {
 impl Light for AmbientLight {
     fn shader_source(&self, i: u32) -> String {
         if self.environment.is_some() {
+            let crossfade = self.next_environment.is_some();
             format!(
             "
                 uniform samplerCube irradianceMap;
                 uniform samplerCube prefilterMap;
                 uniform sampler2D brdfLUT;
                 uniform vec3 ambientColor;
+                uniform mat3 environmentRotation;
+                {}
     
                 vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
                 {{
@@ -59,23 +110,50 @@ impl Light for AmbientLight {
                     // calculate reflectance at normal incidence; if dia-electric (like plastic) use F0 
                     // of 0.04 and if it's a metal, use the albedo color as F0 (metallic workflow)    
                     vec3 F0 = mix(vec3(0.04), surface_color, metallic);
-                    vec3 specular_fresnel = fresnel_schlick_roughness(F0, NdV, roughness);
+                    vec3 specular_fresnel = iridescence_fresnel(fresnel_schlick_roughness(F0, NdV, roughness), F0, NdV);
                     vec3 diffuse_fresnel = 1.0 - specular_fresnel;
 
                     // Diffuse
-                    vec3 irradiance = texture(irradianceMap, N).rgb;
+                    vec3 irradiance = texture(irradianceMap, environmentRotation * N).rgb;
+                    {}
                     vec3 diffuse = diffuse_fresnel * mix(surface_color, vec3(0.0), metallic) * irradiance;
+#ifdef LAMBERT
+                    return mix(surface_color, vec3(0.0), metallic) * irradiance * occlusion * ambientColor;
+#endif
+#if defined(REFLECTION_PROBE) || defined(REFLECTION_PLANE)
+                    // The specular reflections are added by the reflection probe or reflection plane of the material instead
+                    return diffuse * occlusion * ambientColor;
+#endif
                     
                     // sample both the pre-filter map and the BRDF lut and combine them together as per the Split-Sum approximation to get the IBL specular part.
                     const float MAX_REFLECTION_LOD = 4.0;
-                    vec3 prefilteredColor = textureLod(prefilterMap, R,  roughness * MAX_REFLECTION_LOD).rgb;    
+                    vec3 prefilteredColor = textureLod(prefilterMap, environmentRotation * R,  roughness * MAX_REFLECTION_LOD).rgb;    
+                    {}
                     vec2 brdf  = texture(brdfLUT, vec2(NdV, roughness)).rg;
                     vec3 specular = prefilteredColor * (specular_fresnel * brdf.x + brdf.y);
     
                     return (diffuse + specular) * occlusion * ambientColor;
                 }}
             
-            ", i)
+            ",
+            if crossfade {
+                "uniform samplerCube nextIrradianceMap;
+                uniform samplerCube nextPrefilterMap;
+                uniform float environmentCrossfade;"
+            } else {
+                ""
+            },
+            i,
+            if crossfade {
+                "irradiance = mix(irradiance, texture(nextIrradianceMap, environmentRotation * N).rgb, environmentCrossfade);"
+            } else {
+                ""
+            },
+            if crossfade {
+                "prefilteredColor = mix(prefilteredColor, textureLod(nextPrefilterMap, environmentRotation * R,  roughness * MAX_REFLECTION_LOD).rgb, environmentCrossfade);"
+            } else {
+                ""
+            })
         } else {
             format!(
                 "
@@ -90,9 +168,21 @@ impl Light for AmbientLight {
     }
     fn use_uniforms(&self, program: &Program, _i: u32) {
         if let Some(ref environment) = self.environment {
+            // The inverse rotation transforms a world direction to the direction in the environment map
+            program.use_uniform("environmentRotation", self.environment_rotation.transpose());
             program.use_texture_cube("irradianceMap", &environment.irradiance_map);
-            program.use_texture_cube("prefilterMap", &environment.prefilter_map);
-            program.use_texture("brdfLUT", &environment.brdf_map);
+            // The specular part is not used by a diffuse only shading model
+            if program.requires_uniform("prefilterMap") {
+                program.use_texture_cube("prefilterMap", &environment.prefilter_map);
+                program.use_texture("brdfLUT", &environment.brdf_map);
+            }
+            if let Some(ref next_environment) = self.next_environment {
+                program.use_uniform("environmentCrossfade", self.crossfade.clamp(0.0, 1.0));
+                program.use_texture_cube("nextIrradianceMap", &next_environment.irradiance_map);
+                if program.requires_uniform("nextPrefilterMap") {
+                    program.use_texture_cube("nextPrefilterMap", &next_environment.prefilter_map);
+                }
+            }
         }
         program.use_uniform(
             "ambientColor",
@@ -101,7 +191,9 @@ impl Light for AmbientLight {
     }
 
     fn id(&self) -> u8 {
-        if self.environment.is_some() {
+        if self.environment.is_some() && self.next_environment.is_some() {
+            0b1u8 << 7 | 0b1010u8
+        } else if self.environment.is_some() {
             0b1u8 << 7
         } else {
             0b1u8 << 7 | 0b1u8
@@ -115,6 +207,9 @@ impl Default for AmbientLight {
             color: Srgba::WHITE,
             intensity: 1.0,
             environment: None,
+            environment_rotation: Mat3::identity(),
+            next_environment: None,
+            crossfade: 0.0,
         }
     }
 }
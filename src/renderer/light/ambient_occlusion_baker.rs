@@ -0,0 +1,320 @@
+use crate::renderer::*;
+
+///
+/// Bakes ambient occlusion into textures in the uv space of a mesh (ambient occlusion maps) on the CPU,
+/// which can be used as the [PhysicalMaterial::occlusion_texture] or [DeferredPhysicalMaterial::occlusion_texture] of the mesh.
+///
+/// For each texel covered by a triangle in the uv layout, rays are cast from the surface into the hemisphere above it and tested for intersection
+/// with the occluders using a [TriangleBvh], where the occlusion is the fraction of the rays which hit an occluder closer than [AmbientOcclusionBaker::max_distance].
+/// Contrary to the ambient occlusion calculated by a [LightmapBaker], the occlusion is therefore local, so for example the inside of a room is not fully occluded.
+/// Afterwards, the noise is removed by blurring the occlusion between neighbouring texels on the same surface and the occlusion is extended beyond the edges
+/// of the triangles in the uv layout to avoid seams.
+///
+/// The uv layout has to be unwrapped so that each triangle has its own area of the texture (a uv atlas),
+/// or a separate set of uv coordinates has to be used, see [AmbientOcclusionBaker::bake_with_uvs].
+///
+/// ```no_run
+/// # use three_d::*;
+/// # let context: Context = unimplemented!();
+/// # let cpu_mesh: CpuMesh = unimplemented!();
+/// # let mut material: PhysicalMaterial = unimplemented!();
+/// let occluders = TriangleBvh::from_meshes([(&cpu_mesh, Mat4::identity())]);
+/// let occlusion = AmbientOcclusionBaker::default()
+///     .bake(&cpu_mesh, Mat4::identity(), &occluders)
+///     .unwrap();
+/// material.occlusion_texture = Some(Texture2DRef::from_cpu_texture(&context, &occlusion));
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct AmbientOcclusionBaker {
+    /// The width and height of the baked textures. The default is 512.
+    pub texture_size: u32,
+    /// The number of rays cast from each texel. The default is 64.
+    pub samples: u32,
+    /// The maximum distance, in world space units, at which an occluder is occluding the surface. The default is 1.0.
+    pub max_distance: f32,
+    /// The distance, in world space units, which the rays are offset along the normal of the surface, which avoids the surface occluding itself.
+    /// The default is 0.001.
+    pub bias: f32,
+    /// The radius in texels of the blur which removes the noise. Zero disables the denoising. The default is 2.
+    pub denoise_radius: u32,
+    /// The number of texels that the occlusion is extended beyond the edges of the triangles in the uv layout,
+    /// which avoids seams when the texture is sampled with linear interpolation and mip mapping. The default is 4.
+    pub padding: u32,
+}
+
+impl Default for AmbientOcclusionBaker {
+    fn default() -> Self {
+        Self {
+            texture_size: 512,
+            samples: 64,
+            max_distance: 1.0,
+            bias: 0.001,
+            denoise_radius: 2,
+            padding: 4,
+        }
+    }
+}
+
+// The surface at the center of a texel
+#[derive(Clone, Copy)]
+struct Texel {
+    position: Vec3,
+    normal: Vec3,
+    // The approximate size of the texel in world space units
+    size: f32,
+}
+
+impl AmbientOcclusionBaker {
+    ///
+    /// Bakes the ambient occlusion of the given mesh, placed in the world using the given transformation, into a texture
+    /// using the uv coordinates of the mesh.
+    /// The occluders would normally contain all static geometries in the scene including the mesh itself.
+    ///
+    /// The returned texture contains the occlusion in the red channel, where 1.0 means no occlusion and 0.0 means fully occluded,
+    /// as expected by the [PhysicalMaterial::occlusion_texture] with the default [ChannelPacking].
+    ///
+    pub fn bake(
+        &self,
+        cpu_mesh: &CpuMesh,
+        transformation: Mat4,
+        occluders: &TriangleBvh,
+    ) -> Result<CpuTexture, RendererError> {
+        let uvs = cpu_mesh
+            .uvs
+            .as_ref()
+            .ok_or(RendererError::MissingLightmapUvs)?;
+        Ok(self.bake_with_uvs(cpu_mesh, uvs, transformation, occluders))
+    }
+
+    ///
+    /// Same as [AmbientOcclusionBaker::bake] but the texture uses the given uv coordinates instead of the uv coordinates of the mesh,
+    /// for example a separate uv layout where each triangle has its own area of the texture while the uv coordinates of the mesh are used for tiling textures.
+    /// The same uv coordinates should be set as the secondary uv coordinates of the rendered mesh, see [Mesh::set_secondary_uvs],
+    /// and the texture should be sampled with these by setting [Texture2DRef::uv_channel] to 1.
+    ///
+    /// # Panic
+    /// Will panic if the number of uv coordinates does not match the number of vertices in the mesh.
+    ///
+    pub fn bake_with_uvs(
+        &self,
+        cpu_mesh: &CpuMesh,
+        uvs: &[Vec2],
+        transformation: Mat4,
+        occluders: &TriangleBvh,
+    ) -> CpuTexture {
+        assert_eq!(
+            uvs.len(),
+            cpu_mesh.vertex_count(),
+            "the number of ambient occlusion uv coordinates must match the number of vertices"
+        );
+        let size = self.texture_size.max(1) as usize;
+        let texels = self.rasterize(cpu_mesh, uvs, transformation, size);
+
+        // Cast cosine weighted rays into the hemisphere of each texel, rotating the sample pattern per texel so the error becomes noise instead of banding
+        let samples = self.samples.max(1);
+        let max_distance = self.max_distance.max(0.0);
+        let occlusion = texels
+            .iter()
+            .enumerate()
+            .map(|(i, texel)| {
+                texel.map(|texel| {
+                    let (tangent, bitangent) = orthonormal_basis(texel.normal);
+                    let origin = texel.position + texel.normal * self.bias;
+                    let rotation = vec2(hash(2 * i as u32), hash(2 * i as u32 + 1));
+                    let visible = (0..samples)
+                        .filter(|k| {
+                            let u = vec2((*k as f32 + 0.5) / samples as f32, radical_inverse(*k))
+                                + rotation;
+                            let u = vec2(u.x.fract(), u.y.fract());
+                            let radius = u.x.sqrt();
+                            let angle = 2.0 * std::f32::consts::PI * u.y;
+                            let direction = tangent * (radius * angle.cos())
+                                + bitangent * (radius * angle.sin())
+                                + texel.normal * (1.0 - u.x).max(0.0).sqrt();
+                            !occluders.is_occluded(origin, direction, max_distance)
+                        })
+                        .count();
+                    visible as f32 / samples as f32
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let occlusion = denoise(&texels, &occlusion, size, self.denoise_radius);
+
+        // Uncovered texels are unoccluded, unless they are reached by the dilation
+        let data = occlusion
+            .iter()
+            .map(|occlusion| match occlusion {
+                Some(o) => [*o, *o, *o, 1.0],
+                None => [1.0, 1.0, 1.0, 0.0],
+            })
+            .collect();
+        let data = super::lightmap_baker::dilate(data, size, self.padding);
+        CpuTexture {
+            name: "ambient occlusion".to_owned(),
+            data: TextureData::RU8(
+                data.iter()
+                    .map(|c| (c[0].clamp(0.0, 1.0) * 255.0).round() as u8)
+                    .collect(),
+            ),
+            width: size as u32,
+            height: size as u32,
+            wrap_s: Wrapping::ClampToEdge,
+            wrap_t: Wrapping::ClampToEdge,
+            ..Default::default()
+        }
+    }
+
+    // Finds the surface at the center of each texel covered by a triangle in the uv layout
+    fn rasterize(
+        &self,
+        cpu_mesh: &CpuMesh,
+        uvs: &[Vec2],
+        transformation: Mat4,
+        size: usize,
+    ) -> Vec<Option<Texel>> {
+        let computed_normals;
+        let normals = if let Some(normals) = &cpu_mesh.normals {
+            normals
+        } else {
+            let mut cpu_mesh = cpu_mesh.clone();
+            cpu_mesh.compute_normals();
+            computed_normals = cpu_mesh.normals.unwrap();
+            &computed_normals
+        };
+        let normal_matrix = transformation
+            .invert()
+            .unwrap_or(Mat4::identity())
+            .transpose();
+        let positions = cpu_mesh
+            .positions
+            .to_f32()
+            .into_iter()
+            .map(|p| (transformation * p.extend(1.0)).truncate())
+            .collect::<Vec<_>>();
+
+        let mut texels = vec![None; size * size];
+        cpu_mesh.for_each_triangle(|i0, i1, i2| {
+            let p = [i0, i1, i2].map(|i| uvs[i] * size as f32);
+            let area = (p[1] - p[0]).perp_dot(p[2] - p[0]);
+            if area.abs() < 1.0e-12 {
+                return;
+            }
+            let world_area = (positions[i1] - positions[i0])
+                .cross(positions[i2] - positions[i0])
+                .magnitude();
+            let texel_size = (world_area / area.abs()).sqrt();
+            let min_x = (p[0].x.min(p[1].x).min(p[2].x).floor().max(0.0)) as usize;
+            let min_y = (p[0].y.min(p[1].y).min(p[2].y).floor().max(0.0)) as usize;
+            let max_x = (p[0].x.max(p[1].x).max(p[2].x).ceil().max(0.0) as usize).min(size);
+            let max_y = (p[0].y.max(p[1].y).max(p[2].y).ceil().max(0.0) as usize).min(size);
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    if texels[y * size + x].is_some() {
+                        continue;
+                    }
+                    let c = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                    let w0 = (p[2] - p[1]).perp_dot(c - p[1]) / area;
+                    let w1 = (p[0] - p[2]).perp_dot(c - p[2]) / area;
+                    let w2 = 1.0 - w0 - w1;
+                    if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                        continue;
+                    }
+                    let normal = normals[i0] * w0 + normals[i1] * w1 + normals[i2] * w2;
+                    let normal = (normal_matrix * normal.extend(0.0)).truncate();
+                    if normal.magnitude2() < 1.0e-12 {
+                        continue;
+                    }
+                    texels[y * size + x] = Some(Texel {
+                        position: positions[i0] * w0 + positions[i1] * w1 + positions[i2] * w2,
+                        normal: normal.normalize(),
+                        size: texel_size,
+                    });
+                }
+            }
+        });
+        texels
+    }
+}
+
+///
+/// Blurs the occlusion of each texel with the neighbouring texels within the given radius that lie on the same surface,
+/// ie. which are close in world space and have a similar normal, so the blur does not cross edges or the borders between the islands of the uv layout.
+///
+fn denoise(
+    texels: &[Option<Texel>],
+    occlusion: &[Option<f32>],
+    size: usize,
+    radius: u32,
+) -> Vec<Option<f32>> {
+    if radius == 0 {
+        return occlusion.to_vec();
+    }
+    let radius = radius as i32;
+    let sigma2 = 2.0 * (0.5 * radius as f32).max(0.5).powi(2);
+    (0..size * size)
+        .map(|i| {
+            let texel = texels[i]?;
+            let (x, y) = ((i % size) as i32, (i / size) as i32);
+            let max_distance = 1.5 * (radius + 1) as f32 * texel.size;
+            let mut sum = 0.0;
+            let mut weight_sum = 0.0;
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= size as i32 || ny >= size as i32 {
+                        continue;
+                    }
+                    let j = ny as usize * size + nx as usize;
+                    let (Some(neighbour), Some(o)) = (texels[j], occlusion[j]) else {
+                        continue;
+                    };
+                    let similarity = texel.normal.dot(neighbour.normal);
+                    if similarity < 0.8
+                        || texel.position.distance(neighbour.position) > max_distance
+                    {
+                        continue;
+                    }
+                    let weight =
+                        (-((dx * dx + dy * dy) as f32) / sigma2).exp() * similarity.powi(8);
+                    sum += weight * o;
+                    weight_sum += weight;
+                }
+            }
+            Some(if weight_sum > 0.0 {
+                sum / weight_sum
+            } else {
+                occlusion[i]?
+            })
+        })
+        .collect()
+}
+
+// Returns two vectors which are orthogonal to each other and to the given unit vector
+// (Duff et al., "Building an Orthonormal Basis, Revisited", JCGT 2017)
+fn orthonormal_basis(n: Vec3) -> (Vec3, Vec3) {
+    let sign = 1.0f32.copysign(n.z);
+    let a = -1.0 / (sign + n.z);
+    let b = n.x * n.y * a;
+    (
+        vec3(1.0 + sign * n.x * n.x * a, sign * b, -sign * n.x),
+        vec3(b, sign + n.y * n.y * a, -n.y),
+    )
+}
+
+// The Van der Corput sequence in base 2
+fn radical_inverse(i: u32) -> f32 {
+    i.reverse_bits() as f32 * 2.328_306_4e-10
+}
+
+// A pseudo random number in the range [0..1) which is different for each input
+fn hash(i: u32) -> f32 {
+    let mut x = i.wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846C_A68B);
+    x ^= x >> 16;
+    (x >> 8) as f32 / (1u32 << 24) as f32
+}
@@ -0,0 +1,267 @@
+use crate::core::*;
+use crate::renderer::*;
+
+const TILES_X: usize = 16;
+const TILES_Y: usize = 9;
+const SLICES: usize = 24;
+const DATA_TEXTURE_WIDTH: usize = 1024;
+
+///
+/// A large number of [PointLight]s which are culled using a grid of clusters, ie. the view frustum of the camera is divided into tiles on the screen and slices in depth,
+/// so that the lighting calculation only iterates over the lights which affect the cluster the shaded point is inside.
+/// This makes it possible to have hundreds of point lights in a scene, which would otherwise be too expensive to render.
+///
+/// Use it as a single light in a render call, for example [RenderTarget::render], together with other lights.
+/// Call [ClusteredLights::update] each frame before rendering, or whenever the lights or the camera have changed.
+///
+/// Since clustering requires that each light only affects a limited region, the contribution from each light is faded to zero at the distance where
+/// the attenuated intensity is below [ClusteredLights::intensity_cutoff]. Lights without any attenuation affect the entire view frustum.
+///
+pub struct ClusteredLights {
+    context: Context,
+    /// The point lights. Call [ClusteredLights::update] after changing the lights.
+    pub lights: Vec<PointLight>,
+    /// The attenuated light intensity below which the contribution of a light is ignored.
+    /// A larger value limits the region affected by each light more, which improves performance but makes the lights affect a smaller area.
+    pub intensity_cutoff: f32,
+    light_count: usize,
+    light_texture: Texture2D,
+    grid_texture: Texture2D,
+    index_texture: Texture2D,
+    view: Mat4,
+    view_projection: Mat4,
+    depth_range: Vec2,
+}
+
+impl ClusteredLights {
+    ///
+    /// Creates a new set of clustered lights. Call [ClusteredLights::update] with the camera before rendering.
+    ///
+    pub fn new(context: &Context, lights: Vec<PointLight>) -> Self {
+        Self {
+            context: context.clone(),
+            lights,
+            intensity_cutoff: 0.01,
+            light_count: 0,
+            light_texture: data_texture::<[f32; 4]>(context, 0),
+            grid_texture: data_texture::<[f32; 2]>(context, TILES_X * TILES_Y * SLICES),
+            index_texture: data_texture::<f32>(context, 0),
+            view: Mat4::identity(),
+            view_projection: Mat4::identity(),
+            depth_range: vec2(1.0, 1.0),
+        }
+    }
+
+    ///
+    /// Assigns the lights to the clusters of the view frustum of the given camera, which should be the camera used for rendering.
+    ///
+    pub fn update(&mut self, camera: &Camera) {
+        let view = *camera.view();
+        let projection = camera.projection();
+        let projection_inverse = projection.invert().unwrap_or(Mat4::identity());
+        let near = camera.z_near().max(0.001 * camera.z_far());
+        let far = camera.z_far().max(near * 1.001);
+        let log_depth = (far / near).ln();
+        let slice_depth = |k: usize| near * (log_depth * k as f32 / SLICES as f32).exp();
+        let slice_of = |depth: f32| {
+            ((depth.max(near).ln() - near.ln()) / log_depth * SLICES as f32)
+                .clamp(0.0, SLICES as f32 - 1.0) as usize
+        };
+
+        // The view space bounding box of each cluster
+        let unproject = |x: f32, y: f32, z: f32| {
+            let p = projection_inverse * vec4(x, y, z, 1.0);
+            p.truncate() / p.w
+        };
+        let mut clusters = Vec::with_capacity(TILES_X * TILES_Y * SLICES);
+        for k in 0..SLICES {
+            let (depth0, depth1) = (slice_depth(k), slice_depth(k + 1));
+            for j in 0..TILES_Y {
+                for i in 0..TILES_X {
+                    let mut aabb = AxisAlignedBoundingBox::EMPTY;
+                    for (x, y) in [(i, j), (i + 1, j), (i, j + 1), (i + 1, j + 1)] {
+                        let x = 2.0 * x as f32 / TILES_X as f32 - 1.0;
+                        let y = 2.0 * y as f32 / TILES_Y as f32 - 1.0;
+                        let (p0, p1) = (unproject(x, y, -1.0), unproject(x, y, 1.0));
+                        let at_depth =
+                            |depth: f32| p0 + (p1 - p0) * ((depth + p0.z) / (p0.z - p1.z));
+                        aabb.expand(&[at_depth(depth0), at_depth(depth1)]);
+                    }
+                    clusters.push(aabb);
+                }
+            }
+        }
+
+        // The lights affecting each cluster
+        let mut cluster_lights = vec![Vec::new(); clusters.len()];
+        let mut light_data: Vec<[f32; 4]> = Vec::with_capacity(3 * self.lights.len());
+        for light in self.lights.iter() {
+            let color = light.color.to_linear_srgb().truncate() * light.intensity;
+            let radius = light_radius(
+                color.x.max(color.y).max(color.z),
+                &light.attenuation,
+                self.intensity_cutoff,
+            )
+            .min(light.attenuation.range.unwrap_or(f32::INFINITY));
+            let center = (view * light.position.extend(1.0)).truncate();
+            if radius <= 0.0 || -center.z + radius < near || -center.z - radius > far {
+                continue;
+            }
+            let index = (light_data.len() / 3) as f32;
+            for k in slice_of(-center.z - radius)..=slice_of(-center.z + radius) {
+                for c in k * TILES_X * TILES_Y..(k + 1) * TILES_X * TILES_Y {
+                    if clusters[c].distance(&center) <= radius {
+                        cluster_lights[c].push(index);
+                    }
+                }
+            }
+            light_data.push(light.position.extend(radius.min(f32::MAX)).into());
+            light_data.push(color.extend(0.0).into());
+            light_data.push(light.attenuation.uniform().into());
+        }
+        let mut grid = Vec::with_capacity(clusters.len());
+        let mut indices = Vec::new();
+        for lights in cluster_lights {
+            grid.push([indices.len() as f32, lights.len() as f32]);
+            indices.extend(lights);
+        }
+
+        self.light_count = light_data.len() / 3;
+        fill_data_texture(&self.context, &mut self.light_texture, &light_data);
+        fill_data_texture(&self.context, &mut self.index_texture, &indices);
+        fill_data_texture(&self.context, &mut self.grid_texture, &grid);
+        self.view = view;
+        self.view_projection = projection * view;
+        self.depth_range = vec2(near, log_depth);
+    }
+
+    ///
+    /// Returns the number of lights that affect the view frustum of the camera given to the last call to [ClusteredLights::update].
+    ///
+    pub fn visible_light_count(&self) -> usize {
+        self.light_count
+    }
+}
+
+impl Light for ClusteredLights {
+    fn shader_source(&self, i: u32) -> String {
+        format!(
+            "
+            uniform sampler2D clusterLights{i};
+            uniform sampler2D clusterGrid{i};
+            uniform sampler2D clusterIndices{i};
+            uniform mat4 clusterView{i};
+            uniform mat4 clusterViewProjection{i};
+            uniform vec2 clusterDepthRange{i};
+
+            vec3 calculate_lighting{i}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+            {{
+                vec4 clip = clusterViewProjection{i} * vec4(position, 1.0);
+                vec2 ndc = clamp(clip.xy / clip.w, -1.0, 0.9999);
+                float depth = -(clusterView{i} * vec4(position, 1.0)).z;
+                int slice = int(clamp((log(max(depth, clusterDepthRange{i}.x)) - log(clusterDepthRange{i}.x)) / clusterDepthRange{i}.y * {SLICES}.0, 0.0, {SLICES}.0 - 1.0));
+                ivec2 tile = ivec2((0.5 * ndc + 0.5) * vec2({TILES_X}.0, {TILES_Y}.0));
+                int c = tile.x + tile.y * {TILES_X} + slice * {TILES_X} * {TILES_Y};
+                vec2 cluster = texelFetch(clusterGrid{i}, ivec2(c % {DATA_TEXTURE_WIDTH}, c / {DATA_TEXTURE_WIDTH}), 0).rg;
+
+                vec3 color = vec3(0.0);
+                for (int j = 0; j < int(cluster.y); j++) {{
+                    int index = int(cluster.x) + j;
+                    int light = 3 * int(texelFetch(clusterIndices{i}, ivec2(index % {DATA_TEXTURE_WIDTH}, index / {DATA_TEXTURE_WIDTH}), 0).r);
+                    vec4 light_position = texelFetch(clusterLights{i}, ivec2(light % {DATA_TEXTURE_WIDTH}, light / {DATA_TEXTURE_WIDTH}), 0);
+                    vec3 light_direction = light_position.xyz - position;
+                    float distance = length(light_direction);
+                    if (distance >= light_position.w) {{
+                        continue;
+                    }}
+                    light_direction = light_direction / distance;
+                    vec3 light_color = texelFetch(clusterLights{i}, ivec2((light + 1) % {DATA_TEXTURE_WIDTH}, (light + 1) / {DATA_TEXTURE_WIDTH}), 0).rgb;
+                    vec4 attenuation = texelFetch(clusterLights{i}, ivec2((light + 2) % {DATA_TEXTURE_WIDTH}, (light + 2) / {DATA_TEXTURE_WIDTH}), 0);
+                    float fade = saturate(1.0 - pow(distance / light_position.w, 4.0));
+                    light_color = fade * fade * attenuate(light_color, attenuation, distance);
+                    color += calculate_light(light_color, light_direction, surface_color, view_direction, normal, metallic, roughness);
+                }}
+                return color;
+            }}
+            "
+        )
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        program.use_texture(&format!("clusterLights{}", i), &self.light_texture);
+        program.use_texture(&format!("clusterGrid{}", i), &self.grid_texture);
+        program.use_texture(&format!("clusterIndices{}", i), &self.index_texture);
+        program.use_uniform(&format!("clusterView{}", i), self.view);
+        program.use_uniform(&format!("clusterViewProjection{}", i), self.view_projection);
+        program.use_uniform(&format!("clusterDepthRange{}", i), self.depth_range);
+    }
+
+    fn id(&self) -> u8 {
+        0b1u8 << 7 | 0b111u8
+    }
+}
+
+///
+/// Returns the distance at which the attenuated intensity of a light is equal to the cutoff.
+///
+fn light_radius(intensity: f32, attenuation: &Attenuation, cutoff: f32) -> f32 {
+    let target = intensity / cutoff.max(f32::EPSILON);
+    if target <= 1.0 {
+        return 0.0;
+    }
+    let Attenuation {
+        constant,
+        linear,
+        quadratic,
+        ..
+    } = *attenuation;
+    if quadratic > 0.0 {
+        (-linear
+            + (linear * linear - 4.0 * quadratic * (constant - target))
+                .max(0.0)
+                .sqrt())
+            / (2.0 * quadratic)
+    } else if linear > 0.0 {
+        ((target - constant) / linear).max(0.0)
+    } else {
+        f32::INFINITY
+    }
+}
+
+///
+/// Creates a texture with the given number of texels laid out in rows of [DATA_TEXTURE_WIDTH] texels, which is read in the shader using `texelFetch`.
+///
+fn data_texture<T: TextureDataType>(context: &Context, len: usize) -> Texture2D {
+    Texture2D::new_empty::<T>(
+        context,
+        DATA_TEXTURE_WIDTH as u32,
+        len.div_ceil(DATA_TEXTURE_WIDTH).max(1) as u32,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
+///
+/// Fills the data texture with the given data, where the texture is only recreated if it is too small to contain the data.
+///
+fn fill_data_texture<T: TextureDataType>(context: &Context, texture: &mut Texture2D, data: &[T]) {
+    if data.is_empty() {
+        return;
+    }
+    let width = DATA_TEXTURE_WIDTH;
+    if (texture.height() as usize) * width < data.len() {
+        *texture = data_texture::<T>(context, data.len());
+    }
+    let mut data = data.to_vec();
+    data.resize(width * texture.height() as usize, data[0].clone());
+    // The rows are flipped when filling the texture, so they are flipped here as well to keep the order
+    let data = data
+        .chunks(width)
+        .rev()
+        .flat_map(|row| row.iter().cloned())
+        .collect::<Vec<_>>();
+    texture.fill(&data);
+}
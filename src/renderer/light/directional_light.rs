@@ -56,6 +56,7 @@ impl DirectionalLight {
         texture_size: u32,
         geometries: impl IntoIterator<Item = impl Geometry> + Clone,
     ) {
+        span!(INFO, "generate_shadow_map", texture_size);
         let up = compute_up_direction(self.direction);
 
         let viewport = Viewport::new_at_origo(texture_size, texture_size);
@@ -123,6 +124,14 @@ impl DirectionalLight {
     pub fn shadow_map(&self) -> Option<&DepthTexture2D> {
         self.shadow_texture.as_ref()
     }
+
+    ///
+    /// Returns the matrix which transforms a world position to the shadow map coordinates in the range `[0..1]` in all three dimensions.
+    /// Only valid if the shadow map has been generated.
+    ///
+    pub fn shadow_matrix(&self) -> Mat4 {
+        self.shadow_matrix
+    }
 }
 
 impl Light for DirectionalLight {
@@ -131,42 +140,42 @@ impl Light for DirectionalLight {
             format!(
                 "
                     uniform sampler2D shadowMap{};
-                    uniform mat4 shadowMVP{};
-
-                    uniform vec3 color{};
-                    uniform vec3 direction{};
+                    {}
 
                     vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
                     {{
-                        return calculate_light(color{}, -direction{}, surface_color, view_direction, normal, metallic, roughness)
-                            * calculate_shadow(-direction{}, normal, shadowMap{}, shadowMVP{}, position);
+                        return calculate_light(light{}_color, -light{}_direction, surface_color, view_direction, normal, metallic, roughness)
+                            * calculate_shadow(-light{}_direction, normal, shadowMap{}, light{}_shadowMVP, position);
                     }}
 
-                ", i, i, i, i, i, i, i, i, i, i)
+                ", i, light_uniform_block_source(i, &[("vec3", "color"), ("vec3", "direction"), ("mat4", "shadowMVP")]), i, i, i, i, i, i)
         } else {
             format!(
                 "
-                    uniform vec3 color{};
-                    uniform vec3 direction{};
+                    {}
 
                     vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
                     {{
-                        return calculate_light(color{}, -direction{}, surface_color, view_direction, normal, metallic, roughness);
+                        return calculate_light(light{}_color, -light{}_direction, surface_color, view_direction, normal, metallic, roughness);
                     }}
 
-                ", i, i, i, i, i)
+                ", light_uniform_block_source(i, &[("vec3", "color"), ("vec3", "direction")]), i, i, i)
         }
     }
     fn use_uniforms(&self, program: &Program, i: u32) {
+        let color = self.color.to_linear_srgb().truncate() * self.intensity;
+        let direction = self.direction.normalize();
         if let Some(ref tex) = self.shadow_texture {
             program.use_depth_texture(&format!("shadowMap{}", i), tex);
-            program.use_uniform(&format!("shadowMVP{}", i), self.shadow_matrix);
+            let m = self.shadow_matrix;
+            use_light_uniform_block(
+                program,
+                i,
+                &[color.extend(0.0), direction.extend(0.0), m.x, m.y, m.z, m.w],
+            );
+        } else {
+            use_light_uniform_block(program, i, &[color.extend(0.0), direction.extend(0.0)]);
         }
-        program.use_uniform(
-            &format!("color{}", i),
-            self.color.to_linear_srgb().truncate() * self.intensity,
-        );
-        program.use_uniform(&format!("direction{}", i), self.direction.normalize());
     }
 
     fn id(&self) -> u8 {
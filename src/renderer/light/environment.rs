@@ -39,6 +39,7 @@ impl Environment {
         environment_map: &TextureCubeMap,
         lighting_model: LightingModel,
     ) -> Self {
+        span!(INFO, "compute_environment");
         // Diffuse
         let irradiance_size = 32;
         let mut irradiance_map = TextureCubeMap::new_empty::<[f16; 4]>(
@@ -0,0 +1,396 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Bakes the static lighting of a scene into lightmaps, ie. textures which contain the lighting of each point on the surface of a mesh.
+/// Rendering with a lightmap, for example using a [LightmapMaterial], is a lot cheaper than calculating the lighting each frame
+/// and allows for more expensive lighting like ambient occlusion, but only works for objects and lights that do not move.
+///
+/// The lightmap contains the direct lighting from the given lights, including shadows if a shadow map has been generated for a light,
+/// and ambient occlusion which is applied to the lights that are affected by occlusion, for example an [AmbientLight].
+/// The ambient occlusion is calculated by rendering shadow maps of the occluders from a number of directions distributed evenly on the sphere.
+///
+/// The lightmap uses the uv coordinates of the mesh, which therefore has to be unwrapped so that each triangle has its own area of the texture,
+/// or a separate set of lightmap uv coordinates, see [LightmapBaker::bake_with_uvs].
+///
+pub struct LightmapBaker {
+    context: Context,
+    /// The width and height of the baked lightmaps.
+    pub texture_size: u32,
+    /// The number of directions used when calculating ambient occlusion. Zero disables ambient occlusion.
+    pub ambient_occlusion_samples: u32,
+    /// The width and height of the shadow maps used when calculating ambient occlusion.
+    pub shadow_map_size: u32,
+    /// The number of texels that the lightmap is extended beyond the edges of the triangles in the uv layout,
+    /// which avoids seams when the lightmap is sampled with linear interpolation and mip mapping.
+    pub padding: u32,
+    /// The lighting model used when calculating the direct lighting.
+    pub lighting_model: LightingModel,
+    /// The width and height of each of the six views rendered at each probe when baking a volumetric lightmap, see [LightmapBaker::bake_volumetric].
+    pub probe_size: u32,
+}
+
+impl LightmapBaker {
+    ///
+    /// Creates a new lightmap baker with a texture size of 512, 64 ambient occlusion samples, 4 texels padding and a probe size of 16.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            texture_size: 512,
+            ambient_occlusion_samples: 64,
+            shadow_map_size: 512,
+            padding: 4,
+            lighting_model: LightingModel::Blinn,
+            probe_size: 16,
+        }
+    }
+
+    ///
+    /// Bakes the lighting of the given mesh, placed in the scene using the given transformation, into a lightmap.
+    /// The occluders are the geometries that cast shadows when calculating ambient occlusion, which would normally be all
+    /// static geometries in the scene including the one that corresponds to the given mesh.
+    /// The lights should be static lights, where shadow maps should be generated before baking if the light should cast shadows.
+    ///
+    /// The lightmap contains the lighting of a white diffuse surface in linear HDR color space,
+    /// so the final color is found by multiplying with the albedo of the surface.
+    ///
+    pub fn bake(
+        &self,
+        cpu_mesh: &CpuMesh,
+        transformation: Mat4,
+        occluders: impl IntoIterator<Item = impl Geometry> + Clone,
+        lights: &[&dyn Light],
+    ) -> Result<CpuTexture, RendererError> {
+        let uvs = cpu_mesh
+            .uvs
+            .as_ref()
+            .ok_or(RendererError::MissingLightmapUvs)?;
+        self.bake_with_uvs(cpu_mesh, uvs, transformation, occluders, lights)
+    }
+
+    ///
+    /// Same as [LightmapBaker::bake] but the lightmap uses the given uv coordinates instead of the uv coordinates of the mesh,
+    /// for example a separate lightmap uv layout where each triangle has its own area of the texture while the uv coordinates of the mesh are used for tiling textures.
+    /// The same uv coordinates should be set as the secondary uv coordinates of the rendered mesh, see [Mesh::set_secondary_uvs],
+    /// and the lightmap should be sampled with these by setting [Texture2DRef::uv_channel] to 1.
+    ///
+    /// # Panic
+    /// Will panic if the number of uv coordinates does not match the number of vertices in the mesh.
+    ///
+    pub fn bake_with_uvs(
+        &self,
+        cpu_mesh: &CpuMesh,
+        uvs: &[Vec2],
+        transformation: Mat4,
+        occluders: impl IntoIterator<Item = impl Geometry> + Clone,
+        lights: &[&dyn Light],
+    ) -> Result<CpuTexture, RendererError> {
+        assert_eq!(
+            uvs.len(),
+            cpu_mesh.vertex_count(),
+            "the number of lightmap uv coordinates must match the number of vertices"
+        );
+        let computed_normals;
+        let normals = if let Some(normals) = &cpu_mesh.normals {
+            normals
+        } else {
+            let mut cpu_mesh = cpu_mesh.clone();
+            cpu_mesh.compute_normals();
+            computed_normals = cpu_mesh.normals.unwrap();
+            &computed_normals
+        };
+
+        // Each triangle has its own vertices, so the vertex buffers can be drawn without indices
+        let positions = cpu_mesh.positions.to_f32();
+        let mut vertices = Vec::with_capacity(3 * cpu_mesh.triangle_count());
+        cpu_mesh.for_each_triangle(|i0, i1, i2| vertices.extend([i0, i1, i2]));
+        let position_buffer = VertexBuffer::new_with_data(
+            &self.context,
+            &vertices.iter().map(|v| positions[*v]).collect::<Vec<_>>(),
+        );
+        let normal_buffer = VertexBuffer::new_with_data(
+            &self.context,
+            &vertices.iter().map(|v| normals[*v]).collect::<Vec<_>>(),
+        );
+        let uv_buffer = VertexBuffer::new_with_data(
+            &self.context,
+            &vertices.iter().map(|v| uvs[*v]).collect::<Vec<_>>(),
+        );
+        let normal_matrix = transformation
+            .invert()
+            .unwrap_or(Mat4::identity())
+            .transpose();
+        let size = self.texture_size.max(1);
+        let viewport = Viewport::new_at_origo(size, size);
+        let draw = |program: &Program, render_states: RenderStates| {
+            program.use_uniform("modelMatrix", transformation);
+            program.use_uniform("normalMatrix", normal_matrix);
+            program.use_vertex_attribute("position", &position_buffer);
+            program.use_vertex_attribute("normal", &normal_buffer);
+            program.use_vertex_attribute("uv_coordinates", &uv_buffer);
+            program.draw_arrays(render_states, viewport, vertices.len() as u32);
+        };
+        let new_texture = || {
+            Texture2D::new_empty::<[f32; 4]>(
+                &self.context,
+                size,
+                size,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        let vertex_shader_source = include_str!("shaders/lightmap_baker.vert");
+
+        // Accumulate the visibility in each direction weighted by the angle to the normal
+        let ambient_occlusion = if self.ambient_occlusion_samples > 0 {
+            let program = Program::from_source(
+                &self.context,
+                vertex_shader_source,
+                &format!(
+                    "#define AMBIENT_OCCLUSION_PASS\n{}{}{}{}",
+                    lighting_model_shader(self.lighting_model),
+                    include_str!("../../core/shared.frag"),
+                    include_str!("shaders/light_shared.frag"),
+                    include_str!("shaders/lightmap_baker.frag")
+                ),
+            )?;
+            let mut texture = new_texture();
+            texture
+                .as_color_target(None)
+                .clear(ClearState::color(0.0, 0.0, 0.0, 0.0));
+            let render_states = RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                blend: Blend::ADD,
+                cull: Cull::None,
+            };
+            let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+            let samples = self.ambient_occlusion_samples;
+            for k in 0..samples {
+                let y = 1.0 - 2.0 * (k as f32 + 0.5) / samples as f32;
+                let radius = (1.0 - y * y).max(0.0).sqrt();
+                let angle = golden_angle * k as f32;
+                let direction = -vec3(radius * angle.cos(), y, radius * angle.sin());
+                let mut light = DirectionalLight::new(&self.context, 1.0, Srgba::WHITE, &direction);
+                light.generate_shadow_map(self.shadow_map_size, occluders.clone());
+                let Some(shadow_map) = light.shadow_map() else {
+                    continue;
+                };
+                texture.as_color_target(None).write::<RendererError>(|| {
+                    program.use_depth_texture("shadowMap", shadow_map);
+                    program.use_uniform("shadowMVP", light.shadow_matrix());
+                    program.use_uniform("direction", direction);
+                    draw(&program, render_states);
+                    Ok(())
+                })?;
+            }
+            Some(texture)
+        } else {
+            None
+        };
+
+        // Calculate the direct lighting
+        let program = Program::from_source(
+            &self.context,
+            vertex_shader_source,
+            &format!(
+                "{}{}{}",
+                if ambient_occlusion.is_some() {
+                    "#define USE_AMBIENT_OCCLUSION\n"
+                } else {
+                    ""
+                },
+                lights_shader_source(lights, self.lighting_model),
+                include_str!("shaders/lightmap_baker.frag")
+            ),
+        )?;
+        let mut texture = new_texture();
+        let data = texture
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .write::<RendererError>(|| {
+                for (i, light) in lights.iter().enumerate() {
+                    light.use_uniforms(&program, i as u32);
+                }
+                if let Some(ambient_occlusion) = &ambient_occlusion {
+                    program.use_texture("ambientOcclusion", ambient_occlusion);
+                }
+                draw(
+                    &program,
+                    RenderStates {
+                        depth_test: DepthTest::Always,
+                        cull: Cull::None,
+                        ..Default::default()
+                    },
+                );
+                Ok(())
+            })?
+            .read::<[f32; 4]>();
+
+        Ok(CpuTexture {
+            name: "lightmap".to_owned(),
+            data: TextureData::RgbF32(dilate(data, size as usize, self.padding)),
+            width: size,
+            height: size,
+            wrap_s: Wrapping::ClampToEdge,
+            wrap_t: Wrapping::ClampToEdge,
+            ..Default::default()
+        })
+    }
+
+    ///
+    /// Bakes the light arriving from all directions at a grid of light probes with the given number of probes along each axis,
+    /// evenly spaced in the given box, into a volumetric lightmap which can light objects moving through the scene, see [VolumetricLightmap].
+    ///
+    /// The light arriving at each probe is found by rendering the given objects with the given lights in six directions from the probe
+    /// and is stored as spherical harmonics. The objects should therefore include the surroundings, for example a [Skybox],
+    /// and the lights can include a [VolumetricLightmap] from a previous bake to add more bounces of light.
+    /// Probes inside objects do not receive any light, so the box should be chosen so the probes are placed in the open space of the scene.
+    ///
+    pub fn bake_volumetric(
+        &self,
+        aabb: AxisAlignedBoundingBox,
+        resolution: [u32; 3],
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        lights: &[&dyn Light],
+    ) -> CpuVolumetricLightmap {
+        let resolution = resolution.map(|r| r.max(1));
+        let mut lightmap = CpuVolumetricLightmap {
+            min: aabb.min(),
+            max: aabb.max(),
+            resolution,
+            probes: vec![
+                [Vec3::zero(); 4];
+                (resolution[0] * resolution[1] * resolution[2]) as usize
+            ],
+        };
+        let size = self.probe_size.max(1);
+        let mut color_texture = Texture2D::new_empty::<[f32; 4]>(
+            &self.context,
+            size,
+            size,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &self.context,
+            size,
+            size,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let extent = aabb.size().magnitude().max(1.0);
+        let mut camera = Camera::new_perspective(
+            Viewport::new_at_origo(size, size),
+            Vec3::zero(),
+            vec3(0.0, 0.0, -1.0),
+            vec3(0.0, 1.0, 0.0),
+            degrees(90.0),
+            0.0001 * extent,
+            100.0 * extent,
+        );
+        camera.disable_tone_and_color_mapping();
+
+        // The direction and solid angle of each pixel in a view with a field of view of 90 degrees, relative to the view direction
+        let pixels = (0..size * size)
+            .map(|i| {
+                let x = 2.0 * ((i % size) as f32 + 0.5) / size as f32 - 1.0;
+                // The rows are read starting with the top row
+                let y = 1.0 - 2.0 * ((i / size) as f32 + 0.5) / size as f32;
+                let length2 = 1.0 + x * x + y * y;
+                (
+                    x,
+                    y,
+                    (2.0 / size as f32).powi(2) / (length2 * length2.sqrt()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let total_solid_angle = 6.0 * pixels.iter().map(|(_, _, w)| w).sum::<f32>();
+        let normalization = 4.0 * std::f32::consts::PI / total_solid_angle;
+        let views = [
+            (Vec3::unit_x(), Vec3::unit_y()),
+            (-Vec3::unit_x(), Vec3::unit_y()),
+            (Vec3::unit_y(), Vec3::unit_z()),
+            (-Vec3::unit_y(), -Vec3::unit_z()),
+            (Vec3::unit_z(), Vec3::unit_y()),
+            (-Vec3::unit_z(), Vec3::unit_y()),
+        ];
+
+        for z in 0..resolution[2] {
+            for y in 0..resolution[1] {
+                for x in 0..resolution[0] {
+                    let index = (x + resolution[0] * (y + resolution[1] * z)) as usize;
+                    let position = lightmap.probe_position(x, y, z);
+                    for (direction, up) in views {
+                        camera.set_view(position, position + direction, up);
+                        let right = direction.cross(up);
+                        let data = RenderTarget::new(
+                            color_texture.as_color_target(None),
+                            depth_texture.as_depth_target(),
+                        )
+                        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+                        .render(&camera, objects.clone(), lights)
+                        .read_color::<[f32; 4]>();
+                        for ((px, py, solid_angle), radiance) in pixels.iter().zip(data) {
+                            lightmap.add_sample(
+                                index,
+                                (direction + right * *px + up * *py).normalize(),
+                                vec3(radiance[0], radiance[1], radiance[2]),
+                                solid_angle * normalization,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        lightmap
+    }
+}
+
+///
+/// Extends the texels that are covered by a triangle, ie. have an alpha value above zero, into the uncovered texels the given number of times.
+///
+pub(super) fn dilate(mut data: Vec<[f32; 4]>, size: usize, padding: u32) -> Vec<[f32; 3]> {
+    for _ in 0..padding {
+        let source = data.clone();
+        for y in 0..size {
+            for x in 0..size {
+                if source[y * size + x][3] > 0.0 {
+                    continue;
+                }
+                let mut sum = [0.0; 4];
+                for (dx, dy) in [
+                    (-1, 0),
+                    (1, 0),
+                    (0, -1),
+                    (0, 1),
+                    (-1, -1),
+                    (1, -1),
+                    (-1, 1),
+                    (1, 1),
+                ] {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx < 0 || ny < 0 || nx >= size as i32 || ny >= size as i32 {
+                        continue;
+                    }
+                    let neighbour = source[ny as usize * size + nx as usize];
+                    if neighbour[3] > 0.0 {
+                        (0..3).for_each(|c| sum[c] += neighbour[c]);
+                        sum[3] += 1.0;
+                    }
+                }
+                if sum[3] > 0.0 {
+                    data[y * size + x] = [sum[0] / sum[3], sum[1] / sum[3], sum[2] / sum[3], 1.0];
+                }
+            }
+        }
+    }
+    data.into_iter().map(|c| [c[0], c[1], c[2]]).collect()
+}
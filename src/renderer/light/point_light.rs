@@ -37,36 +37,30 @@ impl Light for PointLight {
     fn shader_source(&self, i: u32) -> String {
         format!(
         "
-            uniform vec3 color{};
-            uniform vec3 attenuation{};
-            uniform vec3 position{};
+            {}
 
             vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
             {{
-                vec3 light_direction = position{} - position;
+                vec3 light_direction = light{}_position - position;
                 float distance = length(light_direction);
                 light_direction = light_direction / distance;
 
-                vec3 light_color = attenuate(color{}, attenuation{}, distance);
+                vec3 light_color = attenuate(light{}_color, light{}_attenuation, distance);
                 return calculate_light(light_color, light_direction, surface_color, view_direction, normal, metallic, roughness);
             }}
         
-        ", i, i, i, i, i, i, i)
+        ", light_uniform_block_source(i, &[("vec3", "color"), ("vec4", "attenuation"), ("vec3", "position")]), i, i, i, i)
     }
     fn use_uniforms(&self, program: &Program, i: u32) {
-        program.use_uniform(
-            &format!("color{}", i),
-            self.color.to_linear_srgb().truncate() * self.intensity,
+        use_light_uniform_block(
+            program,
+            i,
+            &[
+                (self.color.to_linear_srgb().truncate() * self.intensity).extend(0.0),
+                self.attenuation.uniform(),
+                self.position.extend(1.0),
+            ],
         );
-        program.use_uniform(
-            &format!("attenuation{}", i),
-            vec3(
-                self.attenuation.constant,
-                self.attenuation.linear,
-                self.attenuation.quadratic,
-            ),
-        );
-        program.use_uniform(&format!("position{}", i), self.position);
     }
 
     fn id(&self) -> u8 {
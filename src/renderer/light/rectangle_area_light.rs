@@ -0,0 +1,131 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A light which shines from one side of a rectangle, for example a window, a TV screen or a soft box in a photo studio.
+/// Compared to a [SpotLight], the highlights on glossy surfaces are the shape of the rectangle and are softer the larger the rectangle is.
+///
+/// The light is approximated using the representative point method, ie. the light is calculated as coming from the point on the rectangle
+/// closest to the reflection direction, while the specular lobe is widened to preserve the energy.
+/// The light does not cast shadows.
+///
+pub struct RectangleAreaLight {
+    /// The intensity of the light. This allows for higher intensity than 1 which can be used to simulate high intensity light sources like the sun.
+    pub intensity: f32,
+    /// The base color of the light.
+    pub color: Srgba,
+    /// The position of the center of the rectangle.
+    pub position: Vec3,
+    /// The direction the light shines, ie. the normal of the rectangle.
+    pub direction: Vec3,
+    /// The up direction of the rectangle, which together with the direction defines the orientation of the rectangle.
+    pub up: Vec3,
+    /// The width of the rectangle.
+    pub width: f32,
+    /// The height of the rectangle, ie. the size in the up direction.
+    pub height: f32,
+    /// The [Attenuation] of the light.
+    pub attenuation: Attenuation,
+}
+
+impl RectangleAreaLight {
+    /// Constructs a new rectangle area light.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        _context: &Context,
+        intensity: f32,
+        color: Srgba,
+        position: &Vec3,
+        direction: &Vec3,
+        up: &Vec3,
+        width: f32,
+        height: f32,
+        attenuation: Attenuation,
+    ) -> RectangleAreaLight {
+        RectangleAreaLight {
+            intensity,
+            color,
+            position: *position,
+            direction: *direction,
+            up: *up,
+            width,
+            height,
+            attenuation,
+        }
+    }
+}
+
+impl Light for RectangleAreaLight {
+    fn shader_source(&self, i: u32) -> String {
+        let uniforms = light_uniform_block_source(
+            i,
+            &[
+                ("vec3", "color"),
+                ("vec4", "attenuation"),
+                ("vec3", "position"),
+                ("vec3", "direction"),
+                ("vec3", "right"),
+                ("vec3", "up"),
+                ("vec2", "halfSize"),
+            ],
+        );
+        format!(
+            "
+            {uniforms}
+
+            vec3 calculate_lighting{i}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+            {{
+                vec3 to_light = light{i}_position - position;
+                if (dot(to_light, light{i}_direction) >= 0.0) {{
+                    return vec3(0.0);
+                }}
+
+                // The point where the reflection ray hits the plane of the rectangle, or the point closest to the plane if it does not hit
+                vec3 reflection = reflect(-view_direction, normal);
+                float denominator = dot(reflection, light{i}_direction);
+                float plane_distance = dot(to_light, light{i}_direction);
+                vec3 plane_point = denominator < -0.0001 ? reflection * (plane_distance / denominator) : plane_distance * light{i}_direction;
+                vec3 offset = plane_point - to_light;
+                vec3 closest_point = to_light
+                    + light{i}_right * clamp(dot(offset, light{i}_right), -light{i}_halfSize.x, light{i}_halfSize.x)
+                    + light{i}_up * clamp(dot(offset, light{i}_up), -light{i}_halfSize.y, light{i}_halfSize.y);
+                float distance = max(length(closest_point), 0.0001);
+                vec3 light_direction = closest_point / distance;
+
+                // Widen the specular lobe by the size of the rectangle to preserve the energy
+                float alpha = roughness * roughness;
+                float equivalent_radius = sqrt(4.0 * light{i}_halfSize.x * light{i}_halfSize.y / PI);
+                float area_roughness = sqrt(saturate(alpha + equivalent_radius / (2.0 * distance)));
+
+                // The light is emitted from a diffuse surface, so the intensity falls off with the angle to the rectangle normal
+                float emission = saturate(dot(light{i}_direction, -light_direction));
+                vec3 light_color = emission * attenuate(light{i}_color, light{i}_attenuation, distance);
+                return calculate_light(light_color, light_direction, surface_color, view_direction, normal, metallic, area_roughness);
+            }}
+            "
+        )
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        let direction = self.direction.normalize();
+        let right = self.up.cross(direction).normalize();
+        let up = direction.cross(right);
+        use_light_uniform_block(
+            program,
+            i,
+            &[
+                (self.color.to_linear_srgb().truncate() * self.intensity).extend(0.0),
+                self.attenuation.uniform(),
+                self.position.extend(1.0),
+                direction.extend(0.0),
+                right.extend(0.0),
+                up.extend(0.0),
+                vec4(self.width.max(0.0), self.height.max(0.0), 0.0, 0.0) * 0.5,
+            ],
+        );
+    }
+
+    fn id(&self) -> u8 {
+        0b1u8 << 7 | 0b1001u8
+    }
+}
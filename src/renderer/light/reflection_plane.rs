@@ -0,0 +1,147 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// Renders the scene mirrored about a plane into a texture which a [PhysicalMaterial] can use for reflections (see [PhysicalMaterial::reflection_plane]),
+/// for example for mirrors, polished floors and calm water surfaces.
+/// Contrary to a [ReflectionProbe], the reflections are correct everywhere on the plane, but the scene needs to be rendered an extra time each frame with [ReflectionPlane::render].
+///
+/// The mirrored scene is rendered with an oblique near plane (see [Camera::set_oblique_near_plane]) so everything behind the plane is clipped and does not show up in the reflection.
+/// Objects using the reflection should lie in the plane and be excluded when rendering the reflection.
+///
+/// A reflection plane is cheap to clone, since the rendered texture is shared, but a clone is not updated when the reflection is rendered again,
+/// so assign the reflection plane to the materials again after calling [ReflectionPlane::render].
+///
+#[derive(Clone)]
+pub struct ReflectionPlane {
+    context: Context,
+    /// A point in the plane.
+    pub point: Vec3,
+    /// The normal of the plane.
+    pub normal: Vec3,
+    /// The size of the reflection texture relative to the viewport of the camera, for example 0.5 renders the reflection in half resolution. The default is 1.0.
+    pub resolution_scale: f32,
+    /// How much the reflection is distorted by the normal of the surface deviating from the normal of the plane, for example due to a normal map. The default is 0.0.
+    pub distortion: f32,
+    /// Everything closer to the plane than this distance is clipped when rendering the reflection, which removes artifacts where objects touch the plane. The default is 0.0.
+    pub clip_offset: f32,
+    texture: Option<Arc<Texture2D>>,
+    view_projection: Mat4,
+}
+
+impl ReflectionPlane {
+    ///
+    /// Creates a new reflection plane through the given point with the given normal.
+    /// The reflection is not rendered before [ReflectionPlane::render] is called.
+    ///
+    pub fn new(context: &Context, point: Vec3, normal: Vec3) -> Self {
+        Self {
+            context: context.clone(),
+            point,
+            normal: normal.normalize(),
+            resolution_scale: 1.0,
+            distortion: 0.0,
+            clip_offset: 0.0,
+            texture: None,
+            view_projection: Mat4::identity(),
+        }
+    }
+
+    ///
+    /// Renders the given objects lit by the given lights as seen in the plane by the given camera, which should be the camera used for rendering the plane afterwards.
+    /// This should be called whenever the camera or the reflected objects change, usually once each frame, and the reflection is only rendered if the camera is in front of the plane.
+    /// The objects using this reflection can be excluded, since they otherwise occlude the reflection.
+    ///
+    pub fn render(
+        &mut self,
+        camera: &Camera,
+        objects: impl IntoIterator<Item = impl Object>,
+        lights: &[&dyn Light],
+    ) {
+        let normal = self.normal.normalize();
+        let side = normal.dot(camera.position() - self.point);
+        if side <= 0.0 {
+            self.texture = None;
+            return;
+        }
+        let mirror_point = |p: Vec3| p - normal * (2.0 * normal.dot(p - self.point));
+        let mirror_vector = |v: Vec3| v - normal * (2.0 * normal.dot(v));
+
+        let viewport = camera.viewport();
+        let scale = self.resolution_scale.max(0.01);
+        let width = ((viewport.width as f32 * scale).round() as u32).max(1);
+        let height = ((viewport.height as f32 * scale).round() as u32).max(1);
+
+        let mut mirrored_camera = camera.clone();
+        mirrored_camera.set_viewport(Viewport::new_at_origo(width, height));
+        mirrored_camera.set_view(
+            mirror_point(*camera.position()),
+            mirror_point(camera.position() + camera.view_direction()),
+            mirror_vector(*camera.up()),
+        );
+        mirrored_camera.set_jitter(vec2(0.0, 0.0));
+        mirrored_camera.disable_tone_and_color_mapping();
+        mirrored_camera.set_oblique_near_plane(Some(
+            normal.extend(-normal.dot(self.point) - self.clip_offset),
+        ));
+
+        let mut color_texture = Texture2D::new_empty::<[f16; 4]>(
+            &self.context,
+            width,
+            height,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            Some(Interpolation::Linear),
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &self.context,
+            width,
+            height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        RenderTarget::new(
+            color_texture.as_color_target(None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+        .render(&mirrored_camera, objects, lights);
+        self.view_projection = mirrored_camera.projection_without_jitter() * mirrored_camera.view();
+        self.texture = Some(Arc::new(color_texture));
+    }
+
+    ///
+    /// Returns whether or not the reflection has been rendered using [ReflectionPlane::render].
+    ///
+    pub fn is_rendered(&self) -> bool {
+        self.texture.is_some()
+    }
+
+    ///
+    /// Returns the texture containing the reflection if it has been rendered using [ReflectionPlane::render].
+    /// The alpha channel is zero where nothing is reflected.
+    ///
+    pub fn texture(&self) -> Option<&Texture2D> {
+        self.texture.as_deref()
+    }
+
+    pub(in crate::renderer) fn fragment_shader_source() -> &'static str {
+        include_str!("shaders/reflection_plane.frag")
+    }
+
+    pub(in crate::renderer) fn use_uniforms(&self, program: &Program) {
+        if let Some(ref texture) = self.texture {
+            program.use_texture("reflectionPlaneMap", texture);
+            program.use_uniform("reflectionPlaneViewProjection", self.view_projection);
+            program.use_uniform("reflectionPlaneNormal", self.normal.normalize());
+            program.use_uniform_if_required("reflectionPlaneDistortion", self.distortion);
+            program.use_uniform_if_required(
+                "reflectionPlaneMaxLod",
+                texture.number_of_mip_maps().saturating_sub(1) as f32,
+            );
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// Captures the surroundings at a position into a cube map which a [PhysicalMaterial] can use for glossy reflections (see [PhysicalMaterial::reflection_probe]),
+/// so shiny objects reflect the objects around them and not only the environment of an [AmbientLight].
+/// The surroundings are only captured when calling [ReflectionProbe::render], which is expensive, so it should be called when the surroundings change and not every frame.
+///
+/// Since the cube map is captured at a single position, the reflections are only correct close to that position.
+/// If a [ReflectionProbe::projection_box] is specified, the reflection direction is corrected by intersecting it with the box (known as box projection),
+/// which gives correct reflections of for example the walls of a room with the same size as the box.
+///
+/// A probe is cheap to clone, since the captured data is shared, but a clone is not updated when the probe is rendered again,
+/// so assign the probe to the materials again after calling [ReflectionProbe::render].
+///
+#[derive(Clone)]
+pub struct ReflectionProbe {
+    context: Context,
+    /// The position at which the surroundings are captured.
+    pub position: Vec3,
+    /// The box used for box projection, usually the bounds of the room the probe is placed in. If `None`, the surroundings are assumed to be infinitely far away.
+    pub projection_box: Option<AxisAlignedBoundingBox>,
+    /// The width and height in pixels of each side of the cube map the surroundings are captured into.
+    pub resolution: u32,
+    environment: Option<Arc<Environment>>,
+}
+
+impl ReflectionProbe {
+    ///
+    /// Creates a new reflection probe at the given position with the given box for box projection.
+    /// The surroundings are not captured before [ReflectionProbe::render] is called.
+    ///
+    pub fn new(
+        context: &Context,
+        position: Vec3,
+        projection_box: Option<AxisAlignedBoundingBox>,
+    ) -> Self {
+        Self {
+            context: context.clone(),
+            position,
+            projection_box,
+            resolution: 256,
+            environment: None,
+        }
+    }
+
+    ///
+    /// Captures the given objects lit by the given lights into a cube map at the position of the probe and prefilters it for the different surface roughnesses.
+    /// The near and far planes of the cameras used for capturing are given as input and should include all the surroundings, for example a [Skybox].
+    /// The objects which are shiny enough to reflect the surroundings can be excluded, since they otherwise reflect themselves.
+    ///
+    pub fn render(
+        &mut self,
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        lights: &[&dyn Light],
+        z_near: f32,
+        z_far: f32,
+    ) {
+        let resolution = self.resolution.max(1);
+        let mut color_texture = TextureCubeMap::new_empty::<[f16; 4]>(
+            &self.context,
+            resolution,
+            resolution,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            Some(Interpolation::Linear),
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTextureCubeMap::new::<f32>(
+            &self.context,
+            resolution,
+            resolution,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut camera = Camera::new_perspective(
+            Viewport::new_at_origo(resolution, resolution),
+            self.position,
+            self.position + CubeMapSide::Front.direction(),
+            CubeMapSide::Front.up(),
+            degrees(90.0),
+            z_near,
+            z_far,
+        );
+        camera.tone_mapping = ToneMapping::None;
+        camera.color_mapping = ColorMapping::None;
+        for side in CubeMapSide::iter() {
+            camera.set_view(self.position, self.position + side.direction(), side.up());
+            RenderTarget::new(
+                color_texture.as_color_target(&[side], None),
+                depth_texture.as_depth_target(side),
+            )
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+            .render(&camera, objects.clone(), lights);
+        }
+        self.environment = Some(Arc::new(Environment::new(&self.context, &color_texture)));
+    }
+
+    ///
+    /// Returns whether or not the surroundings have been captured using [ReflectionProbe::render].
+    ///
+    pub fn is_rendered(&self) -> bool {
+        self.environment.is_some()
+    }
+
+    ///
+    /// Returns the probe in the given list of probes which is closest to the given position, for example the center of the bounding box of an object,
+    /// where probes with a [ReflectionProbe::projection_box] containing the position are preferred.
+    ///
+    pub fn nearest(probes: &[ReflectionProbe], position: Vec3) -> Option<&ReflectionProbe> {
+        probes.iter().min_by(|a, b| {
+            let key = |probe: &ReflectionProbe| {
+                (
+                    !probe
+                        .projection_box
+                        .map(|b| b.distance(&position) <= 0.0)
+                        .unwrap_or(false),
+                    probe.position.distance2(position),
+                )
+            };
+            key(a)
+                .partial_cmp(&key(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    }
+
+    pub(in crate::renderer) fn fragment_shader_source() -> &'static str {
+        include_str!("shaders/reflection_probe.frag")
+    }
+
+    pub(in crate::renderer) fn use_uniforms(&self, program: &Program) {
+        if let Some(ref environment) = self.environment {
+            program.use_texture_cube("reflectionProbeMap", &environment.prefilter_map);
+            program.use_texture("reflectionProbeBrdf", &environment.brdf_map);
+            program.use_uniform("reflectionProbePosition", self.position);
+            let (min, max, use_box) = match self.projection_box {
+                Some(b) if !b.is_empty() && !b.is_infinite() => (b.min(), b.max(), 1),
+                _ => (Vec3::zero(), Vec3::zero(), 0),
+            };
+            program.use_uniform("reflectionProbeBoxMin", min);
+            program.use_uniform("reflectionProbeBoxMax", max);
+            program.use_uniform("reflectionProbeUseBox", use_box);
+        }
+    }
+}
@@ -16,6 +16,91 @@ vec3 fresnel_schlick_roughness(vec3 F0, float cosTheta, float roughness)
     return F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(saturate(1.0 - cosTheta), 5.0);
 }
 
+#ifdef IRIDESCENCE
+// The parameters of the thin-film layer, which are set by the material before calculating the lighting
+float iridescence_factor = 0.0;
+float iridescence_ior = 1.3;
+float iridescence_thickness = 0.0;
+
+vec3 ior_to_fresnel0(vec3 transmitted_ior, float incident_ior)
+{
+    vec3 r = (transmitted_ior - vec3(incident_ior)) / (transmitted_ior + vec3(incident_ior));
+    return r * r;
+}
+
+// Evaluates the XYZ sensitivity curves in Fourier space and converts the result to linear sRGB
+vec3 eval_sensitivity(float opd, vec3 shift)
+{
+    float phase = 2.0 * PI * opd * 1.0e-9;
+    vec3 val = vec3(5.4856e-13, 4.4201e-13, 5.2481e-13);
+    vec3 pos = vec3(1.6810e+06, 1.7953e+06, 2.2084e+06);
+    vec3 var = vec3(4.3278e+09, 9.3046e+09, 6.6121e+09);
+    vec3 xyz = val * sqrt(2.0 * PI * var) * cos(pos * phase + shift) * exp(-phase * phase * var);
+    xyz.x += 9.7470e-14 * sqrt(2.0 * PI * 4.5282e+09) * cos(2.2399e+06 * phase + shift.x) * exp(-4.5282e+09 * phase * phase);
+    xyz /= 1.0685e-7;
+    const mat3 XYZ_TO_REC709 = mat3(
+         3.2404542, -0.9692660,  0.0556434,
+        -1.5371385,  1.8760108, -0.2040259,
+        -0.4985314,  0.0415560,  1.0572252
+    );
+    return XYZ_TO_REC709 * xyz;
+}
+
+// Fresnel reflectance of a thin dielectric film on top of the base material,
+// see "A Practical Extension to Microfacet Theory for the Modeling of Varying Iridescence" by Belcour and Barla.
+vec3 eval_iridescence(float cos_theta1, vec3 F0)
+{
+    // The film disappears when the thickness goes to zero
+    float film_ior = mix(1.0, iridescence_ior, smoothstep(0.0, 0.03, iridescence_thickness));
+    float sin_theta2_sq = (1.0 - cos_theta1 * cos_theta1) / (film_ior * film_ior);
+    float cos_theta2_sq = 1.0 - sin_theta2_sq;
+    if (cos_theta2_sq < 0.0) {
+        // Total internal reflection
+        return vec3(1.0);
+    }
+    float cos_theta2 = sqrt(cos_theta2_sq);
+
+    // First interface, from air to the film
+    float R0 = ior_to_fresnel0(vec3(film_ior), 1.0).x;
+    float R12 = fresnel_schlick(vec3(R0), cos_theta1).x;
+    float T121 = 1.0 - R12;
+    float phi12 = film_ior < 1.0 ? PI : 0.0;
+    float phi21 = PI - phi12;
+
+    // Second interface, from the film to the base material
+    vec3 sqrt_F0 = sqrt(clamp(F0, 0.0, 0.9999));
+    vec3 base_ior = (1.0 + sqrt_F0) / (1.0 - sqrt_F0);
+    vec3 R23 = fresnel_schlick(ior_to_fresnel0(base_ior, film_ior), cos_theta2);
+    vec3 phi23 = mix(vec3(0.0), vec3(PI), lessThan(base_ior, vec3(film_ior)));
+
+    // Phase shift
+    float opd = 2.0 * film_ior * iridescence_thickness * cos_theta2;
+    vec3 phi = vec3(phi21) + phi23;
+
+    // Compound terms
+    vec3 R123 = clamp(R12 * R23, 1e-5, 0.9999);
+    vec3 r123 = sqrt(R123);
+    vec3 Rs = T121 * T121 * R23 / (vec3(1.0) - R123);
+    vec3 I = R12 + Rs;
+    vec3 Cm = Rs - T121;
+    for (int m = 1; m <= 2; m++) {
+        Cm *= r123;
+        I += Cm * 2.0 * eval_sensitivity(float(m) * opd, float(m) * phi);
+    }
+    return max(I, vec3(0.0));
+}
+#endif
+
+// Applies the thin-film iridescence of the material, if any, to the given specular fresnel factor
+vec3 iridescence_fresnel(vec3 specular_fresnel, vec3 F0, float cosTheta)
+{
+#ifdef IRIDESCENCE
+    return mix(specular_fresnel, eval_iridescence(cosTheta, F0), iridescence_factor);
+#else
+    return specular_fresnel;
+#endif
+}
+
 
 // following functions are copies of UE4
 // for computing cook-torrance specular lighting terms
@@ -104,19 +189,24 @@ vec3 calculate_light(vec3 light_color, vec3 L, vec3 surface_color, vec3 V, vec3
     float NdL = max(0.001, dot(N, L));
     float NdV = max(0.001, dot(N, V));
 
+#ifdef LAMBERT
+    // diffuse reflectance only
+    return mix(surface_color, vec3(0.0), metallic) / PI * light_color * NdL;
+#endif
+
     // mix between metal and non-metal material, for non-metal
     // constant base specular factor of 0.04 grey is used
     vec3 F0 = mix(vec3(0.04), surface_color, metallic);
 
 #ifdef PHONG
     // specular reflectance with PHONG
-    vec3 specular_fresnel = fresnel_schlick_roughness(F0, NdV, roughness);
+    vec3 specular_fresnel = iridescence_fresnel(fresnel_schlick_roughness(F0, NdV, roughness), F0, NdV);
     vec3 specular = phong_specular(V, L, N, specular_fresnel, roughness);
 #else
     vec3 H = normalize(L + V);
     float NdH = max(0.001, dot(N, H));
     float HdV = max(0.001, dot(H, V));
-    vec3 specular_fresnel = fresnel_schlick_roughness(F0, HdV, roughness);
+    vec3 specular_fresnel = iridescence_fresnel(fresnel_schlick_roughness(F0, HdV, roughness), F0, HdV);
 #endif
 
 #ifdef BLINN
@@ -146,6 +236,16 @@ vec3 attenuate(vec3 light_color, vec3 attenuation, float distance)
     return light_color / max(1.0, att);
 }
 
+vec3 attenuate(vec3 light_color, vec4 attenuation, float distance)
+{
+    vec3 color = attenuate(light_color, attenuation.xyz, distance);
+    if (attenuation.w > 0.0) {
+        float fade = saturate(1.0 - pow(distance / attenuation.w, 4.0));
+        color *= fade * fade;
+    }
+    return color;
+}
+
 float is_visible(vec3 lightDirection, vec3 normal, sampler2D shadowMap, vec4 shadow_coord, vec2 offset)
 {
     vec2 uv = (shadow_coord.xy + offset)/shadow_coord.w;
@@ -0,0 +1,40 @@
+
+in vec3 pos;
+in vec3 nor;
+
+layout (location = 0) out vec4 outColor;
+
+#ifdef AMBIENT_OCCLUSION_PASS
+
+uniform sampler2D shadowMap;
+uniform mat4 shadowMVP;
+uniform vec3 direction;
+
+void main()
+{
+    vec3 normal = normalize(nor);
+    float weight = max(dot(normal, -direction), 0.0);
+    float visibility = weight > 0.0 ? calculate_shadow(-direction, normal, shadowMap, shadowMVP, pos) : 0.0;
+    outColor = vec4(weight * visibility, weight, 0.0, 1.0);
+}
+
+#else
+
+#ifdef USE_AMBIENT_OCCLUSION
+uniform sampler2D ambientOcclusion;
+#endif
+
+void main()
+{
+    vec3 normal = normalize(nor);
+    float occlusion = 1.0;
+#ifdef USE_AMBIENT_OCCLUSION
+    vec2 ao = texelFetch(ambientOcclusion, ivec2(gl_FragCoord.xy), 0).rg;
+    occlusion = ao.y > 0.0 ? ao.x / ao.y : 1.0;
+#endif
+    // The lighting of a white diffuse surface seen from the direction of the normal
+    vec3 color = calculate_lighting(pos + normal, vec3(1.0), pos, normal, 0.0, 1.0, occlusion);
+    outColor = vec4(color, 1.0);
+}
+
+#endif
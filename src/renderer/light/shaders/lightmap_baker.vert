@@ -0,0 +1,20 @@
+
+uniform mat4 modelMatrix;
+uniform mat4 normalMatrix;
+
+in vec3 position;
+in vec3 normal;
+in vec2 uv_coordinates;
+
+out vec3 pos;
+out vec3 nor;
+
+void main()
+{
+    vec4 worldPosition = modelMatrix * vec4(position, 1.0);
+    pos = worldPosition.xyz / worldPosition.w;
+    nor = normalize(mat3(normalMatrix) * normal);
+
+    // Each triangle is rasterized at its location in the lightmap
+    gl_Position = vec4(2.0 * uv_coordinates.x - 1.0, 1.0 - 2.0 * uv_coordinates.y, 0.0, 1.0);
+}
@@ -0,0 +1,27 @@
+
+uniform sampler2D reflectionPlaneMap;
+uniform mat4 reflectionPlaneViewProjection;
+uniform vec3 reflectionPlaneNormal;
+uniform float reflectionPlaneDistortion;
+uniform float reflectionPlaneMaxLod;
+
+// Returns the specular reflection in rgb and how much of the surroundings are covered by the reflection in alpha
+vec4 reflection_plane_specular(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness)
+{
+    vec3 offset = (normal - reflectionPlaneNormal) * reflectionPlaneDistortion;
+    vec4 clip_position = reflectionPlaneViewProjection * vec4(position + offset, 1.0);
+    vec2 uv = 0.5 + 0.5 * clip_position.xy / clip_position.w;
+    vec4 reflection = textureLod(reflectionPlaneMap, uv, roughness * reflectionPlaneMaxLod);
+    vec2 inside = step(vec2(0.0), uv) * step(uv, vec2(1.0));
+    reflection *= inside.x * inside.y;
+
+    float NdV = max(0.001, dot(normal, view_direction));
+    vec3 F0 = mix(vec3(0.04), surface_color, metallic);
+    vec3 specular_fresnel = iridescence_fresnel(fresnel_schlick_roughness(F0, NdV, roughness), F0, NdV);
+
+    // Analytical approximation of the BRDF lookup table, see Karis, "Physically Based Shading on Mobile"
+    vec4 r = roughness * vec4(-1.0, -0.0275, -0.572, 0.022) + vec4(1.0, 0.0425, 1.04, -0.04);
+    float a004 = min(r.x * r.x, exp2(-9.28 * NdV)) * r.x + r.y;
+    vec2 brdf = vec2(-1.04, 1.04) * a004 + r.zw;
+    return vec4(reflection.rgb * (specular_fresnel * brdf.x + brdf.y), reflection.a);
+}
@@ -0,0 +1,35 @@
+
+uniform samplerCube reflectionProbeMap;
+uniform sampler2D reflectionProbeBrdf;
+uniform vec3 reflectionProbePosition;
+uniform vec3 reflectionProbeBoxMin;
+uniform vec3 reflectionProbeBoxMax;
+uniform int reflectionProbeUseBox;
+
+// Intersects the reflection ray with the box and returns the direction from the probe position to the intersection
+vec3 reflection_probe_direction(vec3 position, vec3 R)
+{
+    if (reflectionProbeUseBox == 1) {
+        vec3 first = (reflectionProbeBoxMax - position) / R;
+        vec3 second = (reflectionProbeBoxMin - position) / R;
+        vec3 furthest = max(first, second);
+        float distance = min(min(furthest.x, furthest.y), furthest.z);
+        if (distance > 0.0) {
+            return position + R * distance - reflectionProbePosition;
+        }
+    }
+    return R;
+}
+
+vec3 reflection_probe_specular(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness)
+{
+    vec3 R = reflect(-view_direction, normal);
+    float NdV = max(0.001, dot(normal, view_direction));
+    vec3 F0 = mix(vec3(0.04), surface_color, metallic);
+    vec3 specular_fresnel = iridescence_fresnel(fresnel_schlick_roughness(F0, NdV, roughness), F0, NdV);
+
+    const float MAX_REFLECTION_LOD = 4.0;
+    vec3 prefilteredColor = textureLod(reflectionProbeMap, reflection_probe_direction(position, R), roughness * MAX_REFLECTION_LOD).rgb;
+    vec2 brdf = texture(reflectionProbeBrdf, vec2(NdV, roughness)).rg;
+    return prefilteredColor * (specular_fresnel * brdf.x + brdf.y);
+}
@@ -0,0 +1,98 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A light which shines in all directions from the surface of a sphere with the given position and radius, for example a light bulb.
+/// Compared to a [PointLight], the highlights on glossy surfaces are the shape of the sphere and are softer the larger the sphere is.
+///
+/// The light is approximated using the representative point method, ie. the light is calculated as coming from the point on the sphere
+/// closest to the reflection direction, while the specular lobe is widened to preserve the energy.
+/// The light does not cast shadows.
+///
+pub struct SphereAreaLight {
+    /// The intensity of the light. This allows for higher intensity than 1 which can be used to simulate high intensity light sources like the sun.
+    pub intensity: f32,
+    /// The base color of the light.
+    pub color: Srgba,
+    /// The position of the center of the sphere.
+    pub position: Vec3,
+    /// The radius of the sphere.
+    pub radius: f32,
+    /// The [Attenuation] of the light.
+    pub attenuation: Attenuation,
+}
+
+impl SphereAreaLight {
+    /// Constructs a new sphere area light.
+    pub fn new(
+        _context: &Context,
+        intensity: f32,
+        color: Srgba,
+        position: &Vec3,
+        radius: f32,
+        attenuation: Attenuation,
+    ) -> SphereAreaLight {
+        SphereAreaLight {
+            intensity,
+            color,
+            position: *position,
+            radius,
+            attenuation,
+        }
+    }
+}
+
+impl Light for SphereAreaLight {
+    fn shader_source(&self, i: u32) -> String {
+        let uniforms = light_uniform_block_source(
+            i,
+            &[
+                ("vec3", "color"),
+                ("vec4", "attenuation"),
+                ("vec3", "position"),
+                ("float", "radius"),
+            ],
+        );
+        format!(
+            "
+            {uniforms}
+
+            vec3 calculate_lighting{i}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+            {{
+                vec3 to_light = light{i}_position - position;
+                float distance = max(length(to_light), 0.0001);
+
+                // The point on the sphere closest to the reflection ray
+                vec3 reflection = reflect(-view_direction, normal);
+                vec3 center_to_ray = dot(to_light, reflection) * reflection - to_light;
+                vec3 closest_point = to_light + center_to_ray * saturate(light{i}_radius / max(length(center_to_ray), 0.0001));
+                vec3 light_direction = normalize(closest_point);
+
+                // Widen the specular lobe by the solid angle of the sphere to preserve the energy
+                float alpha = roughness * roughness;
+                float area_roughness = sqrt(saturate(alpha + light{i}_radius / (2.0 * distance)));
+
+                vec3 light_color = attenuate(light{i}_color, light{i}_attenuation, distance);
+                return calculate_light(light_color, light_direction, surface_color, view_direction, normal, metallic, area_roughness);
+            }}
+            "
+        )
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        use_light_uniform_block(
+            program,
+            i,
+            &[
+                (self.color.to_linear_srgb().truncate() * self.intensity).extend(0.0),
+                self.attenuation.uniform(),
+                self.position.extend(1.0),
+                vec4(self.radius.max(0.0), 0.0, 0.0, 0.0),
+            ],
+        );
+    }
+
+    fn id(&self) -> u8 {
+        0b1u8 << 7 | 0b1000u8
+    }
+}
@@ -67,6 +67,7 @@ impl SpotLight {
         texture_size: u32,
         geometries: impl IntoIterator<Item = impl Geometry> + Clone,
     ) {
+        span!(INFO, "generate_shadow_map", texture_size);
         let position = self.position;
         let direction = self.direction;
         let up = compute_up_direction(self.direction);
@@ -136,90 +137,92 @@ impl SpotLight {
     pub fn shadow_map(&self) -> Option<&DepthTexture2D> {
         self.shadow_texture.as_ref()
     }
+
+    ///
+    /// Returns the matrix which transforms a world position to the shadow map coordinates in the range `[0..1]` in all three dimensions.
+    /// Only valid if the shadow map has been generated.
+    ///
+    pub fn shadow_matrix(&self) -> Mat4 {
+        self.shadow_matrix
+    }
 }
 
+/// The uniforms of a spot light in the `Lights` uniform block, where the last is only used when the light casts shadows.
+const SPOT_LIGHT_UNIFORMS: [(&str, &str); 6] = [
+    ("vec3", "color"),
+    ("vec4", "attenuation"),
+    ("vec3", "position"),
+    ("float", "cutoff"),
+    ("vec3", "direction"),
+    ("mat4", "shadowMVP"),
+];
+
 impl Light for SpotLight {
     fn shader_source(&self, i: u32) -> String {
         if self.shadow_texture.is_some() {
             format!(
                 "
                     uniform sampler2D shadowMap{};
-                    uniform mat4 shadowMVP{};
-
-                    uniform vec3 color{};
-                    uniform vec3 attenuation{};
-                    uniform vec3 position{};
-                    uniform float cutoff{};
-                    uniform vec3 direction{};
+                    {}
                     vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
                     {{
-                        vec3 light_direction = position{} - position;
+                        vec3 light_direction = light{}_position - position;
                         float distance = length(light_direction);
                         light_direction = light_direction / distance;
 
-                        float angle = acos(dot(-light_direction, normalize(direction{})));
-                        float cutoff = cutoff{};
+                        float angle = acos(dot(-light_direction, normalize(light{}_direction)));
+                        float cutoff = light{}_cutoff;
 
                         vec3 result = vec3(0.0);
                         if (angle < cutoff) {{
-                            vec3 light_color = attenuate(color{}, attenuation{}, distance);
+                            vec3 light_color = attenuate(light{}_color, light{}_attenuation, distance);
                             result = calculate_light(light_color, light_direction, surface_color, view_direction, normal,
                                 metallic, roughness) * (1.0 - smoothstep(0.75 * cutoff, cutoff, angle));
-                            result *= calculate_shadow(light_direction, normal, shadowMap{}, shadowMVP{}, position);
+                            result *= calculate_shadow(light_direction, normal, shadowMap{}, light{}_shadowMVP, position);
                         }}
                         return result;
                     }}
 
-                ", i, i, i, i, i, i, i, i, i, i, i, i, i, i, i)
+                ", i, light_uniform_block_source(i, &SPOT_LIGHT_UNIFORMS), i, i, i, i, i, i, i, i)
         } else {
             format!(
                 "
-                    uniform vec3 color{};
-                    uniform vec3 attenuation{};
-                    uniform vec3 position{};
-                    uniform float cutoff{};
-                    uniform vec3 direction{};
+                    {}
                     vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
                     {{
-                        vec3 light_direction = position{} - position;
+                        vec3 light_direction = light{}_position - position;
                         float distance = length(light_direction);
                         light_direction = light_direction / distance;
 
-                        float angle = acos(dot(-light_direction, normalize(direction{})));
-                        float cutoff = cutoff{};
+                        float angle = acos(dot(-light_direction, normalize(light{}_direction)));
+                        float cutoff = light{}_cutoff;
 
                         vec3 result = vec3(0.0);
                         if (angle < cutoff) {{
-                            vec3 light_color = attenuate(color{}, attenuation{}, distance);
+                            vec3 light_color = attenuate(light{}_color, light{}_attenuation, distance);
                             result = calculate_light(light_color, light_direction, surface_color, view_direction, normal,
                                 metallic, roughness) * (1.0 - smoothstep(0.75 * cutoff, cutoff, angle));
                         }}
                         return result;
                     }}
 
-                ", i, i, i, i, i, i, i, i, i, i, i)
+                ", light_uniform_block_source(i, &SPOT_LIGHT_UNIFORMS[..5]), i, i, i, i, i, i)
         }
     }
     fn use_uniforms(&self, program: &Program, i: u32) {
+        let mut data = vec![
+            (self.color.to_linear_srgb().truncate() * self.intensity).extend(0.0),
+            self.attenuation.uniform(),
+            self.position.extend(1.0),
+            vec4(self.cutoff.0, 0.0, 0.0, 0.0),
+            self.direction.normalize().extend(0.0),
+        ];
         if let Some(ref tex) = self.shadow_texture {
             program.use_depth_texture(&format!("shadowMap{}", i), tex);
-            program.use_uniform(&format!("shadowMVP{}", i), self.shadow_matrix);
+            let m = self.shadow_matrix;
+            data.extend([m.x, m.y, m.z, m.w]);
         }
-        program.use_uniform(
-            &format!("color{}", i),
-            self.color.to_linear_srgb().truncate() * self.intensity,
-        );
-        program.use_uniform(
-            &format!("attenuation{}", i),
-            vec3(
-                self.attenuation.constant,
-                self.attenuation.linear,
-                self.attenuation.quadratic,
-            ),
-        );
-        program.use_uniform(&format!("position{}", i), self.position);
-        program.use_uniform(&format!("direction{}", i), self.direction.normalize());
-        program.use_uniform(&format!("cutoff{}", i), self.cutoff.0);
+        use_light_uniform_block(program, i, &data);
     }
 
     fn id(&self) -> u8 {
@@ -0,0 +1,243 @@
+use crate::core::*;
+use crate::renderer::*;
+
+/// The constant spherical harmonics basis function.
+const SH_CONSTANT: f32 = 0.282_095;
+/// The linear spherical harmonics basis functions divided by the direction.
+const SH_LINEAR: f32 = 0.488_603;
+
+///
+/// A grid of light probes on the CPU, also known as a volumetric lightmap or an irradiance volume, which contains the light arriving
+/// at each probe from all directions, for example baked offline using [LightmapBaker::bake_volumetric].
+/// Use [VolumetricLightmap] to light objects with it, for example dynamic objects moving through a scene where the static objects use lightmaps,
+/// and [serialize_baked_lighting] to store it.
+///
+/// The probes are evenly spaced in the box from [CpuVolumetricLightmap::min] to [CpuVolumetricLightmap::max],
+/// so there is a probe in each corner of the box, and the light is interpolated between the probes.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct CpuVolumetricLightmap {
+    /// The position of the probe with the smallest coordinates.
+    pub min: Vec3,
+    /// The position of the probe with the largest coordinates.
+    pub max: Vec3,
+    /// The number of probes along the x, y and z axis.
+    pub resolution: [u32; 3],
+    /// The light arriving at each probe as the coefficients of the first two bands of spherical harmonics in linear HDR color space,
+    /// in the order constant, x, y and z. The probes are ordered by their x, then y and then z index,
+    /// so the probe with indices `(x, y, z)` is at index `x + resolution[0] * (y + resolution[1] * z)`.
+    pub probes: Vec<[Vec3; 4]>,
+}
+
+impl CpuVolumetricLightmap {
+    ///
+    /// Returns the position of the probe with the given indices along the x, y and z axis.
+    ///
+    pub fn probe_position(&self, x: u32, y: u32, z: u32) -> Vec3 {
+        let t = |i: u32, axis: usize| {
+            if self.resolution[axis] > 1 {
+                i as f32 / (self.resolution[axis] - 1) as f32
+            } else {
+                0.5
+            }
+        };
+        let size = self.max - self.min;
+        self.min + vec3(size.x * t(x, 0), size.y * t(y, 1), size.z * t(z, 2))
+    }
+
+    ///
+    /// Returns the light reflected by a white diffuse surface at the given position with the given normal,
+    /// interpolated between the nearest probes, which is the same as the lighting calculated by a [VolumetricLightmap].
+    /// Positions outside the grid use the nearest probes on the border of the grid.
+    ///
+    pub fn lighting(&self, position: Vec3, normal: Vec3) -> Vec3 {
+        let size = self.max - self.min;
+        let resolution = self.resolution.map(|r| r.max(1));
+        let mut base = [0u32; 3];
+        let mut fraction = [0.0f32; 3];
+        for axis in 0..3 {
+            let cells = resolution[axis] - 1;
+            let t = if size[axis] > 0.0 {
+                ((position[axis] - self.min[axis]) / size[axis]).clamp(0.0, 1.0) * cells as f32
+            } else {
+                0.0
+            };
+            base[axis] = (t.floor() as u32).min(cells.saturating_sub(1));
+            fraction[axis] = if cells > 0 {
+                t - base[axis] as f32
+            } else {
+                0.0
+            };
+        }
+        let mut coefficients = [Vec3::zero(); 4];
+        for corner in 0..8u32 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let mut weight = 1.0;
+            let mut index = [0u32; 3];
+            for axis in 0..3 {
+                index[axis] = (base[axis] + offset[axis]).min(resolution[axis] - 1);
+                weight *= if offset[axis] == 1 {
+                    fraction[axis]
+                } else {
+                    1.0 - fraction[axis]
+                };
+            }
+            if weight > 0.0 {
+                let probe = self.probes
+                    [(index[0] + resolution[0] * (index[1] + resolution[1] * index[2])) as usize];
+                for (c, p) in coefficients.iter_mut().zip(probe) {
+                    *c += p * weight;
+                }
+            }
+        }
+        let normal = normal.normalize();
+        let lighting = coefficients[0] * SH_CONSTANT
+            + (coefficients[1] * normal.x
+                + coefficients[2] * normal.y
+                + coefficients[3] * normal.z)
+                * (SH_LINEAR * 2.0 / 3.0);
+        vec3(
+            lighting.x.max(0.0),
+            lighting.y.max(0.0),
+            lighting.z.max(0.0),
+        )
+    }
+
+    ///
+    /// Adds the light arriving from the given direction, with the given radiance and covering the given solid angle, to the probe at the given index.
+    ///
+    pub(super) fn add_sample(
+        &mut self,
+        index: usize,
+        direction: Vec3,
+        radiance: Vec3,
+        solid_angle: f32,
+    ) {
+        let probe = &mut self.probes[index];
+        let radiance = radiance * solid_angle;
+        probe[0] += radiance * SH_CONSTANT;
+        probe[1] += radiance * (SH_LINEAR * direction.x);
+        probe[2] += radiance * (SH_LINEAR * direction.y);
+        probe[3] += radiance * (SH_LINEAR * direction.z);
+    }
+}
+
+///
+/// A light which lights objects using the light probes in a [CpuVolumetricLightmap], for example dynamic objects moving through a scene
+/// where the light is baked, instead of or in addition to an [AmbientLight].
+/// The light arriving at a surface is interpolated between the nearest probes and only the diffuse part of the lighting is calculated.
+///
+pub struct VolumetricLightmap {
+    /// The intensity of the light, which is multiplied with the baked light.
+    pub intensity: f32,
+    /// The color of the light, which is multiplied with the baked light.
+    pub color: Srgba,
+    textures: [Texture3D; 3],
+    min: Vec3,
+    max: Vec3,
+    resolution: [u32; 3],
+}
+
+impl VolumetricLightmap {
+    ///
+    /// Creates a new volumetric lightmap light from the probes in the given [CpuVolumetricLightmap].
+    ///
+    /// # Panic
+    /// Will panic if the number of probes does not match the resolution.
+    ///
+    pub fn new(context: &Context, cpu_lightmap: &CpuVolumetricLightmap) -> Self {
+        let resolution = cpu_lightmap.resolution.map(|r| r.max(1));
+        assert_eq!(
+            cpu_lightmap.probes.len(),
+            (resolution[0] * resolution[1] * resolution[2]) as usize,
+            "the number of probes must match the resolution of the volumetric lightmap"
+        );
+        // One texture for each color channel, containing the four coefficients of that channel
+        let textures = [0, 1, 2].map(|channel| {
+            let mut texture = Texture3D::new_empty::<[f16; 4]>(
+                context,
+                resolution[0],
+                resolution[1],
+                resolution[2],
+                Interpolation::Linear,
+                Interpolation::Linear,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            texture.fill(
+                &cpu_lightmap
+                    .probes
+                    .iter()
+                    .map(|probe| probe.map(|c| f16::from_f32(c[channel])))
+                    .collect::<Vec<_>>(),
+            );
+            texture
+        });
+        Self {
+            intensity: 1.0,
+            color: Srgba::WHITE,
+            textures,
+            min: cpu_lightmap.min,
+            max: cpu_lightmap.max,
+            resolution,
+        }
+    }
+}
+
+impl Light for VolumetricLightmap {
+    fn shader_source(&self, i: u32) -> String {
+        format!(
+            "
+                uniform sampler3D volumetricLightmapRed{i};
+                uniform sampler3D volumetricLightmapGreen{i};
+                uniform sampler3D volumetricLightmapBlue{i};
+                uniform vec3 volumetricLightmapMin{i};
+                uniform vec3 volumetricLightmapSize{i};
+                uniform vec3 volumetricLightmapResolution{i};
+                uniform vec3 volumetricLightmapColor{i};
+
+                vec3 calculate_lighting{i}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+                {{
+                    vec3 uvw = clamp((position - volumetricLightmapMin{i}) / max(volumetricLightmapSize{i}, vec3(0.0001)), 0.0, 1.0);
+                    uvw = (uvw * (volumetricLightmapResolution{i} - 1.0) + 0.5) / volumetricLightmapResolution{i};
+                    vec4 basis = vec4({constant}, {linear} * normal);
+                    vec3 irradiance = max(vec3(
+                        dot(texture(volumetricLightmapRed{i}, uvw), basis),
+                        dot(texture(volumetricLightmapGreen{i}, uvw), basis),
+                        dot(texture(volumetricLightmapBlue{i}, uvw), basis)
+                    ), vec3(0.0));
+                    return occlusion * volumetricLightmapColor{i} * irradiance * mix(surface_color, vec3(0.0), metallic);
+                }}
+            ",
+            i = i,
+            constant = SH_CONSTANT,
+            linear = SH_LINEAR * 2.0 / 3.0,
+        )
+    }
+
+    fn use_uniforms(&self, program: &Program, i: u32) {
+        program.use_texture_3d(&format!("volumetricLightmapRed{}", i), &self.textures[0]);
+        program.use_texture_3d(&format!("volumetricLightmapGreen{}", i), &self.textures[1]);
+        program.use_texture_3d(&format!("volumetricLightmapBlue{}", i), &self.textures[2]);
+        program.use_uniform(&format!("volumetricLightmapMin{}", i), self.min);
+        program.use_uniform(&format!("volumetricLightmapSize{}", i), self.max - self.min);
+        program.use_uniform(
+            &format!("volumetricLightmapResolution{}", i),
+            vec3(
+                self.resolution[0] as f32,
+                self.resolution[1] as f32,
+                self.resolution[2] as f32,
+            ),
+        );
+        program.use_uniform(
+            &format!("volumetricLightmapColor{}", i),
+            self.color.to_linear_srgb().truncate() * self.intensity,
+        );
+    }
+
+    fn id(&self) -> u8 {
+        0b1u8 << 7 | 0b1011u8
+    }
+}
@@ -25,6 +25,15 @@ macro_rules! impl_material_body {
         fn id(&self) -> u16 {
             self.$inner().id()
         }
+        fn extended_id(&self) -> Option<u64> {
+            self.$inner().extended_id()
+        }
+        fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+            self.$inner().depth_pre_pass_material()
+        }
+        fn vertex_displacement_source(&self) -> Option<String> {
+            self.$inner().vertex_displacement_source()
+        }
     };
 }
 
@@ -38,10 +47,21 @@ mod color_material;
 #[doc(inline)]
 pub use color_material::*;
 
+mod texture_array_material;
+#[doc(inline)]
+pub use texture_array_material::*;
+
 mod depth_material;
 #[doc(inline)]
 pub use depth_material::*;
 
+mod depth_pre_pass_material;
+#[doc(inline)]
+pub use depth_pre_pass_material::*;
+
+mod ray_hit_material;
+pub(crate) use ray_hit_material::*;
+
 mod normal_material;
 #[doc(inline)]
 pub use normal_material::*;
@@ -58,10 +78,41 @@ mod uv_material;
 #[doc(inline)]
 pub use uv_material::*;
 
+mod velocity_material;
+#[doc(inline)]
+pub use velocity_material::*;
+
+mod wireframe_material;
+#[doc(inline)]
+pub use wireframe_material::*;
+
+mod lightmap_material;
+#[doc(inline)]
+pub use lightmap_material::*;
+
+mod texture_painter;
+#[doc(inline)]
+pub use texture_painter::*;
+
 mod physical_material;
 #[doc(inline)]
 pub use physical_material::*;
 
+mod unlit_material;
+#[doc(inline)]
+pub use unlit_material::*;
+
+mod shader_material;
+#[doc(inline)]
+pub use shader_material::*;
+
+#[cfg(feature = "hot-reload")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hot-reload")))]
+mod shader_watcher;
+#[doc(inline)]
+#[cfg(feature = "hot-reload")]
+pub use shader_watcher::*;
+
 mod deferred_physical_material;
 #[doc(inline)]
 pub use deferred_physical_material::*;
@@ -70,14 +121,42 @@ mod skybox_material;
 #[doc(inline)]
 pub(in crate::renderer) use skybox_material::*;
 
+#[cfg(feature = "volume")]
+#[cfg_attr(docsrs, doc(cfg(feature = "volume")))]
 mod isosurface_material;
 #[doc(inline)]
+#[cfg(feature = "volume")]
 pub use isosurface_material::*;
 
-use std::{ops::Deref, sync::Arc};
+#[cfg(feature = "volume")]
+#[cfg_attr(docsrs, doc(cfg(feature = "volume")))]
+mod volume_material;
+#[doc(inline)]
+#[cfg(feature = "volume")]
+pub use volume_material::*;
+
+#[cfg(feature = "terrain")]
+#[cfg_attr(docsrs, doc(cfg(feature = "terrain")))]
+mod splat_material;
+#[doc(inline)]
+#[cfg(feature = "terrain")]
+pub use splat_material::*;
+
+mod blue_noise;
+#[doc(inline)]
+pub use blue_noise::*;
+
+mod noise_generator;
+#[doc(inline)]
+pub use noise_generator::*;
+
+use std::{
+    ops::Deref,
+    sync::{Arc, Weak},
+};
 
 ///
-/// A reference to a 2D texture and a texture transformation.
+/// A reference to a 2D texture, a texture transformation and the uv coordinates used when sampling the texture.
 ///
 #[derive(Clone)]
 pub struct Texture2DRef {
@@ -86,6 +165,10 @@ pub struct Texture2DRef {
     /// A transformation applied to the uv coordinates before reading a texel value at those uv coordinates.
     /// This is primarily used in relation to texture atlasing.
     pub transformation: Mat3,
+    /// The uv coordinates used when sampling the texture, where 0 is the uv coordinates of the [CpuMesh] and 1 is the secondary uv coordinates,
+    /// for example the uv coordinates of a lightmap, see [Mesh::set_secondary_uvs].
+    /// If the geometry does not have secondary uv coordinates, the uv coordinates of the [CpuMesh] are used instead.
+    pub uv_channel: u32,
 }
 
 impl Texture2DRef {
@@ -94,6 +177,24 @@ impl Texture2DRef {
         Self {
             texture: Arc::new(Texture2D::new(context, cpu_texture)),
             transformation: Mat3::identity(),
+            uv_channel: 0,
+        }
+    }
+
+    /// Creates a new [Texture2DRef] with an identity transformation from a [CpuTexture] in the given color space, see [Texture2D::new_with_color_space].
+    pub fn from_cpu_texture_with_color_space(
+        context: &Context,
+        cpu_texture: &CpuTexture,
+        color_space: ColorSpace,
+    ) -> Self {
+        Self {
+            texture: Arc::new(Texture2D::new_with_color_space(
+                context,
+                cpu_texture,
+                color_space,
+            )),
+            transformation: Mat3::identity(),
+            uv_channel: 0,
         }
     }
 
@@ -102,6 +203,16 @@ impl Texture2DRef {
         Self {
             texture: Arc::new(texture),
             transformation: Mat3::identity(),
+            uv_channel: 0,
+        }
+    }
+
+    /// Creates a [WeakTexture2DRef] to the same texture, which does not keep the texture alive.
+    pub fn downgrade(&self) -> WeakTexture2DRef {
+        WeakTexture2DRef {
+            texture: Arc::downgrade(&self.texture),
+            transformation: self.transformation,
+            uv_channel: self.uv_channel,
         }
     }
 }
@@ -124,10 +235,147 @@ impl std::convert::From<Arc<Texture2D>> for Texture2DRef {
         Self {
             texture,
             transformation: Mat3::identity(),
+            uv_channel: 0,
         }
     }
 }
 
+///
+/// A weak reference to a 2D texture, created by [Texture2DRef::downgrade], which does not keep the texture alive,
+/// so the texture is deleted on the GPU when all [Texture2DRef]s to it are dropped.
+/// This is for example useful for a cache of textures which should not prevent textures that are no longer used from being deleted.
+///
+#[derive(Clone)]
+pub struct WeakTexture2DRef {
+    texture: Weak<Texture2D>,
+    transformation: Mat3,
+    uv_channel: u32,
+}
+
+impl WeakTexture2DRef {
+    /// Returns a [Texture2DRef] to the texture or `None` if the texture has been deleted.
+    pub fn upgrade(&self) -> Option<Texture2DRef> {
+        self.texture.upgrade().map(|texture| Texture2DRef {
+            texture,
+            transformation: self.transformation,
+            uv_channel: self.uv_channel,
+        })
+    }
+}
+
+///
+/// A color channel of a texture.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureChannel {
+    /// The red channel.
+    Red,
+    /// The green channel.
+    Green,
+    /// The blue channel.
+    Blue,
+    /// The alpha channel.
+    Alpha,
+}
+
+impl TextureChannel {
+    fn mask(self) -> Vec4 {
+        match self {
+            Self::Red => vec4(1.0, 0.0, 0.0, 0.0),
+            Self::Green => vec4(0.0, 1.0, 0.0, 0.0),
+            Self::Blue => vec4(0.0, 0.0, 1.0, 0.0),
+            Self::Alpha => vec4(0.0, 0.0, 0.0, 1.0),
+        }
+    }
+}
+
+///
+/// Specifies which channels of the metallic roughness texture and the occlusion texture of a material contain the metallic, roughness and occlusion values.
+/// This makes it possible to use textures authored for the packing conventions of other engines without re-exporting them.
+/// The default is the glTF convention, see [ChannelPacking::GLTF].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChannelPacking {
+    /// The channel of the metallic roughness texture which contains the metallic values.
+    pub metallic: TextureChannel,
+    /// The channel of the metallic roughness texture which contains the roughness values.
+    pub roughness: TextureChannel,
+    /// Whether the [ChannelPacking::roughness] channel contains smoothness values, ie. one minus the roughness, instead of roughness values.
+    pub smoothness: bool,
+    /// The channel of the occlusion texture which contains the occlusion values.
+    pub occlusion: TextureChannel,
+}
+
+impl ChannelPacking {
+    ///
+    /// The glTF convention where occlusion is stored in the red channel, roughness in the green channel and metallic in the blue channel (ORM).
+    /// This is also used by for example Unreal Engine.
+    ///
+    pub const GLTF: Self = Self {
+        metallic: TextureChannel::Blue,
+        roughness: TextureChannel::Green,
+        smoothness: false,
+        occlusion: TextureChannel::Red,
+    };
+
+    ///
+    /// The convention of the mask map of the Unity HD render pipeline where metallic is stored in the red channel, occlusion in the green channel
+    /// and smoothness in the alpha channel.
+    ///
+    pub const UNITY_MASK_MAP: Self = Self {
+        metallic: TextureChannel::Red,
+        roughness: TextureChannel::Alpha,
+        smoothness: true,
+        occlusion: TextureChannel::Green,
+    };
+
+    ///
+    /// Sends the uniforms `metallicChannel`, `roughnessChannel`, `roughnessIsSmoothness` and `occlusionChannel` to the program if they are required.
+    /// The channels are sent as masks which select the channel using a dot product with the texel value.
+    ///
+    pub(crate) fn use_uniforms(&self, program: &Program) {
+        program.use_uniform_if_required("metallicChannel", self.metallic.mask());
+        program.use_uniform_if_required("roughnessChannel", self.roughness.mask());
+        program.use_uniform_if_required(
+            "roughnessIsSmoothness",
+            if self.smoothness { 1.0f32 } else { 0.0 },
+        );
+        program.use_uniform_if_required("occlusionChannel", self.occlusion.mask());
+    }
+}
+
+impl Default for ChannelPacking {
+    fn default() -> Self {
+        Self::GLTF
+    }
+}
+
+///
+/// Returns the fragment shader source which declares the uv coordinates and the function `vec2 texture_uvs(float uv_channel)`,
+/// which returns the uv coordinates to sample a texture with the given [Texture2DRef::uv_channel] at.
+/// The secondary uv coordinates `in vec2 uvs1;` are only declared if any texture of the material uses them.
+///
+pub(crate) fn uvs_shader_source<'a>(
+    textures: impl IntoIterator<Item = Option<&'a Texture2DRef>>,
+) -> &'static str {
+    if uses_secondary_uvs(textures) {
+        "in vec2 uvs;\nin vec2 uvs1;\nvec2 texture_uvs(float uv_channel) { return uv_channel > 0.5 ? uvs1 : uvs; }\n"
+    } else {
+        "in vec2 uvs;\nvec2 texture_uvs(float uv_channel) { return uvs; }\n"
+    }
+}
+
+///
+/// Returns whether any of the given textures are sampled using the secondary uv coordinates, see [Texture2DRef::uv_channel].
+///
+pub(crate) fn uses_secondary_uvs<'a>(
+    textures: impl IntoIterator<Item = Option<&'a Texture2DRef>>,
+) -> bool {
+    textures
+        .into_iter()
+        .any(|t| t.is_some_and(|t| t.uv_channel > 0))
+}
+
 ///
 /// Defines the material type which is needed to render the objects in the correct order.
 /// For example, transparent objects need to be rendered back to front, whereas opaque objects need to be rendered front to back.
@@ -154,7 +402,7 @@ pub struct FragmentAttributes {
     pub normal: bool,
     /// Tangent and bitangent: `in vec3 tang; in vec3 bitang;`
     pub tangents: bool,
-    /// UV coordinates: `in vec2 uvs;`
+    /// UV coordinates: `in vec2 uvs;`, the secondary uv coordinates: `in vec2 uvs1;` and the layer of a texture array: `flat in float texture_layer;`
     pub uv: bool,
     /// Color: `in vec4 col;`
     pub color: bool,
@@ -198,6 +446,15 @@ pub trait Material {
     ///
     fn id(&self) -> u16;
 
+    ///
+    /// Returns an additional id which together with [Material::id] identifies the variation of the shader source,
+    /// for materials with more variations than fit in [Material::id], for example because the shader source is given at runtime,
+    /// or None if the [Material::id] is enough, which is the default.
+    ///
+    fn extended_id(&self) -> Option<u64> {
+        None
+    }
+
     ///
     /// Returns a [FragmentAttributes] struct that describes which fragment attributes,
     /// ie. the input from the vertex shader, are required for rendering with this material.
@@ -218,6 +475,39 @@ pub trait Material {
     /// Returns the type of material.
     ///
     fn material_type(&self) -> MaterialType;
+
+    ///
+    /// Returns the material used for rendering only the depth of the fragments written by this material in a depth pre-pass,
+    /// see [RenderTarget::render_with_depth_pre_pass], or None if this material does not support a depth pre-pass, which is the default.
+    /// Materials that discard fragments, for example because of an alpha cutout, must discard the same fragments in the returned material.
+    ///
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        None
+    }
+
+    ///
+    /// Returns the source of a GLSL function which modifies the position of each vertex before it is transformed to world space,
+    /// for example to make grass sway in the wind, a flag wave or jelly wobble, or None if the vertices are not modified, which is the default.
+    /// The function must have the signature
+    ///
+    /// `vec3 displace_position(vec3 position, vec3 normal, mat4 model_matrix)`
+    ///
+    /// where `position` and `normal` are the position and normal of the vertex in model space, also when the positions of a [Mesh] are quantized
+    /// using a packed [VertexFormat], and `model_matrix` is the transformation
+    /// from model space to world space, including the transformation of the instance when rendering an [InstancedMesh],
+    /// and the function returns the displaced position in model space.
+    /// The normal is zero if the material does not require normals, see [Material::fragment_attributes].
+    /// The displacement is applied to the vertices of a [Mesh], [InstancedMesh] and [ParticleSystem], after any deformation on the CPU,
+    /// for example by a [MorphMesh], and is ignored by other geometries. The normals are not changed by the displacement.
+    ///
+    /// Uniforms used by the function have to be declared in the source and can be sent in [Material::use_uniforms],
+    /// preferably using [Program::use_uniform_if_required] since the same program is used for the vertex and fragment shader.
+    /// The displacement is not applied when rendering shadows or a depth pre-pass, so a material which displaces the vertices
+    /// should not return a [Material::depth_pre_pass_material].
+    ///
+    fn vertex_displacement_source(&self) -> Option<String> {
+        None
+    }
 }
 
 ///
@@ -233,6 +523,8 @@ pub trait FromCpuMaterial: std::marker::Sized {
 ///
 /// Implement this for a [Material] that can be created from a [CpuVoxelGrid].
 ///
+#[cfg(feature = "volume")]
+#[cfg_attr(docsrs, doc(cfg(feature = "volume")))]
 pub trait FromCpuVoxelGrid: std::marker::Sized {
     ///
     /// Creates a new material that can be used for rendering from a [CpuVoxelGrid].
@@ -283,6 +575,15 @@ impl<T: Material> Material for std::sync::RwLock<T> {
     fn id(&self) -> u16 {
         self.read().unwrap().id()
     }
+    fn extended_id(&self) -> Option<u64> {
+        self.read().unwrap().extended_id()
+    }
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        self.read().unwrap().depth_pre_pass_material()
+    }
+    fn vertex_displacement_source(&self) -> Option<String> {
+        self.read().unwrap().vertex_displacement_source()
+    }
 }
 
 fn is_transparent(cpu_material: &CpuMaterial) -> bool {
@@ -0,0 +1,136 @@
+use crate::renderer::*;
+
+///
+/// A tileable blue-noise texture which can be used in custom materials and effects, for example for dithering or for jittering samples.
+/// Blue noise has no low-frequency components which means that the noise is perceived as a fine uniform grain instead of visible clumps.
+///
+/// Use [BlueNoise::fragment_shader_source] to get access to the `blue_noise` and `blue_noise_at` functions in a fragment shader and
+/// [BlueNoise::use_uniforms] to send the texture to the shader program.
+///
+#[derive(Clone)]
+pub struct BlueNoise {
+    texture: Texture2DRef,
+}
+
+impl BlueNoise {
+    ///
+    /// Creates a new blue-noise texture with a resolution of 64x64 pixels.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self::new_with_size(context, 64)
+    }
+
+    ///
+    /// Creates a new blue-noise texture with a resolution of `size`x`size` pixels.
+    /// Note that the generation time grows with the fourth power of the size, so the size should be kept small (usually 16 to 128).
+    ///
+    pub fn new_with_size(context: &Context, size: u32) -> Self {
+        Self {
+            texture: Texture2DRef::from_cpu_texture(context, &Self::cpu_texture(size)),
+        }
+    }
+
+    ///
+    /// Generates a `size`x`size` tileable blue-noise texture on the CPU using the void-and-cluster method.
+    /// Each pixel contains a single `u8` value and all values are distributed uniformly in the range `[0, 255]`.
+    ///
+    pub fn cpu_texture(size: u32) -> CpuTexture {
+        let size = size.max(1) as usize;
+        let count = size * size;
+
+        // Gaussian energy of a pixel at a given toroidal offset.
+        const SIGMA: f32 = 1.5;
+        let mut kernel = vec![0.0f32; count];
+        for y in 0..size {
+            for x in 0..size {
+                let dx = x.min(size - x) as f32;
+                let dy = y.min(size - y) as f32;
+                kernel[y * size + x] = (-(dx * dx + dy * dy) / (2.0 * SIGMA * SIGMA)).exp();
+            }
+        }
+
+        // A tiny deterministic jitter to break ties between pixels with equal energy.
+        let mut seed = 0x9E37_79B9u32;
+        let mut energy: Vec<f32> = (0..count)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed as f32 * 1.0e-15
+            })
+            .collect();
+
+        // Repeatedly insert a pixel into the largest void, ie. the free pixel with the lowest energy.
+        let mut ranks = vec![usize::MAX; count];
+        for rank in 0..count {
+            let mut index = 0;
+            let mut min = f32::MAX;
+            for (i, e) in energy.iter().enumerate() {
+                if ranks[i] == usize::MAX && *e < min {
+                    min = *e;
+                    index = i;
+                }
+            }
+            ranks[index] = rank;
+            let (px, py) = (index % size, index / size);
+            for y in 0..size {
+                let ky = (y + size - py) % size;
+                for x in 0..size {
+                    let kx = (x + size - px) % size;
+                    energy[y * size + x] += kernel[ky * size + kx];
+                }
+            }
+        }
+
+        CpuTexture {
+            name: "blue noise".to_string(),
+            data: TextureData::RU8(
+                ranks
+                    .into_iter()
+                    .map(|rank| ((rank * 256) / count) as u8)
+                    .collect(),
+            ),
+            width: size as u32,
+            height: size as u32,
+            min_filter: Interpolation::Nearest,
+            mag_filter: Interpolation::Nearest,
+            mip_map_filter: None,
+            wrap_s: Wrapping::Repeat,
+            wrap_t: Wrapping::Repeat,
+        }
+    }
+
+    ///
+    /// Returns the blue-noise texture.
+    ///
+    pub fn texture(&self) -> &Texture2D {
+        &self.texture
+    }
+
+    ///
+    /// Returns the fragment shader source for sampling the blue-noise texture in a shader.
+    /// The source defines the functions `float blue_noise()`, which returns the blue-noise value in the range `[0, 1]` at the current fragment,
+    /// and `float blue_noise_at(ivec2 pixel)`, which returns the value at the given pixel (the texture is repeated).
+    ///
+    pub fn fragment_shader_source() -> &'static str {
+        "
+        uniform sampler2D blueNoiseTexture;
+
+        float blue_noise_at(ivec2 pixel) {
+            ivec2 size = textureSize(blueNoiseTexture, 0);
+            return texelFetch(blueNoiseTexture, ((pixel % size) + size) % size, 0).r;
+        }
+
+        float blue_noise() {
+            return blue_noise_at(ivec2(gl_FragCoord.xy));
+        }
+        "
+    }
+
+    ///
+    /// Sends the uniform data needed to sample the blue-noise texture to the fragment shader.
+    ///
+    pub fn use_uniforms(&self, program: &Program) {
+        program.use_texture("blueNoiseTexture", &self.texture);
+    }
+}
@@ -10,7 +10,8 @@ pub struct ColorMaterial {
     /// Base surface color.
     pub color: Srgba,
     /// An optional texture which is samples using uv coordinates (requires that the [Geometry] supports uv coordinates).
-    /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
+    /// The constructors taking a [CpuMaterial] create it from the albedo texture in [ColorSpace::Srgb], which the hardware converts to linear sRGB when sampling.
+    /// Other textures are assumed to be in linear sRGB or HDR color space.
     pub texture: Option<Texture2DRef>,
     /// Render states.
     pub render_states: RenderStates,
@@ -34,18 +35,9 @@ impl ColorMaterial {
 
     /// Constructs a new opaque color material from a [CpuMaterial].
     pub fn new_opaque(context: &Context, cpu_material: &CpuMaterial) -> Self {
-        let texture =
-            cpu_material
-                .albedo_texture
-                .as_ref()
-                .map(|cpu_texture| match &cpu_texture.data {
-                    TextureData::RgbU8(_) | TextureData::RgbaU8(_) => {
-                        let mut cpu_texture = cpu_texture.clone();
-                        cpu_texture.data.to_linear_srgb();
-                        Texture2DRef::from_cpu_texture(context, &cpu_texture)
-                    }
-                    _ => Texture2DRef::from_cpu_texture(context, cpu_texture),
-                });
+        let texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Texture2DRef::from_cpu_texture_with_color_space(context, cpu_texture, ColorSpace::Srgb)
+        });
         Self {
             color: cpu_material.albedo,
             texture,
@@ -56,18 +48,9 @@ impl ColorMaterial {
 
     /// Constructs a new transparent color material from a [CpuMaterial].
     pub fn new_transparent(context: &Context, cpu_material: &CpuMaterial) -> Self {
-        let texture =
-            cpu_material
-                .albedo_texture
-                .as_ref()
-                .map(|cpu_texture| match &cpu_texture.data {
-                    TextureData::RgbU8(_) | TextureData::RgbaU8(_) => {
-                        let mut cpu_texture = cpu_texture.clone();
-                        cpu_texture.data.to_linear_srgb();
-                        Texture2DRef::from_cpu_texture(context, &cpu_texture)
-                    }
-                    _ => Texture2DRef::from_cpu_texture(context, cpu_texture),
-                });
+        let texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Texture2DRef::from_cpu_texture_with_color_space(context, cpu_texture, ColorSpace::Srgb)
+        });
         Self {
             color: cpu_material.albedo,
             texture,
@@ -100,7 +83,11 @@ impl FromCpuMaterial for ColorMaterial {
 impl Material for ColorMaterial {
     fn id(&self) -> u16 {
         if self.texture.is_some() {
-            0b1u16 << 15
+            if uses_secondary_uvs([self.texture.as_ref()]) {
+                0b1u16 << 15 | 0b1u16 << 9
+            } else {
+                0b1u16 << 15
+            }
         } else {
             0b1u16 << 15 | 0b1u16
         }
@@ -109,7 +96,8 @@ impl Material for ColorMaterial {
     fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
         let mut shader = String::new();
         if self.texture.is_some() {
-            shader.push_str("#define USE_TEXTURE\nin vec2 uvs;\n");
+            shader.push_str("#define USE_TEXTURE\n");
+            shader.push_str(uvs_shader_source([self.texture.as_ref()]));
         }
         shader.push_str(include_str!("../../core/shared.frag"));
         shader.push_str(ColorMapping::fragment_shader_source());
@@ -126,10 +114,13 @@ impl Material for ColorMaterial {
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
-        camera.color_mapping.use_uniforms(program);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
         program.use_uniform("surfaceColor", self.color.to_linear_srgb());
         if let Some(ref tex) = self.texture {
             program.use_uniform("textureTransformation", tex.transformation);
+            program.use_uniform_if_required("textureUvChannel", tex.uv_channel as f32);
             program.use_texture("tex", tex);
         }
     }
@@ -143,4 +134,11 @@ impl Material for ColorMaterial {
             MaterialType::Opaque
         }
     }
+
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        Some(DepthPrePassMaterial {
+            cull: self.render_states.cull,
+            ..Default::default()
+        })
+    }
 }
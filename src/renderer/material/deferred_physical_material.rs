@@ -18,20 +18,23 @@ pub struct DeferredPhysicalMaterial {
     /// Albedo base color, also called diffuse color.
     pub albedo: Srgba,
     /// Texture with albedo base colors, also called diffuse color.
-    /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
+    /// [DeferredPhysicalMaterial::new] creates it in [ColorSpace::Srgb], so the hardware converts the colors to linear sRGB when sampling.
+    /// Other textures are assumed to be in linear sRGB or HDR color space.
     pub albedo_texture: Option<Texture2DRef>,
     /// A value in the range `[0..1]` specifying how metallic the material is.
     pub metallic: f32,
     /// A value in the range `[0..1]` specifying how rough the material surface is.
     pub roughness: f32,
     /// Texture containing the metallic and roughness parameters which are multiplied with the [Self::metallic] and [Self::roughness] values in the shader.
-    /// The metallic values are sampled from the blue channel and the roughness from the green channel.
+    /// By default, the metallic values are sampled from the blue channel and the roughness from the green channel, see [Self::channel_packing].
     pub metallic_roughness_texture: Option<Texture2DRef>,
     /// A scalar multiplier controlling the amount of occlusion applied from the [Self::occlusion_texture]. A value of 0.0 means no occlusion. A value of 1.0 means full occlusion.
     pub occlusion_strength: f32,
     /// An occlusion map. Higher values indicate areas that should receive full indirect lighting and lower values indicate no indirect lighting.
-    /// The occlusion values are sampled from the red channel.
+    /// By default, the occlusion values are sampled from the red channel, see [Self::channel_packing].
     pub occlusion_texture: Option<Texture2DRef>,
+    /// Which channels of the [Self::metallic_roughness_texture] and [Self::occlusion_texture] contain the metallic, roughness and occlusion values.
+    pub channel_packing: ChannelPacking,
     /// A scalar multiplier applied to each normal vector of the [Self::normal_texture].
     pub normal_scale: f32,
     /// A tangent space normal map, also known as bump map.
@@ -41,7 +44,7 @@ pub struct DeferredPhysicalMaterial {
     /// Color of light shining from an object.
     pub emissive: Srgba,
     /// Texture with color of light shining from an object.
-    /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
+    /// Like the [Self::albedo_texture], it is in [ColorSpace::Srgb] when created by [DeferredPhysicalMaterial::new] and otherwise assumed to be in linear sRGB or HDR color space.
     pub emissive_texture: Option<Texture2DRef>,
     /// A threshold on the alpha value of the color as a workaround for transparency.
     /// If the alpha value of a pixel touched by an object with this material is less than the threshold, then that object is not contributing to the color of that pixel.
@@ -56,18 +59,9 @@ impl DeferredPhysicalMaterial {
     /// [DeferredPhysicalMaterial::metallic_roughness_texture] and [DeferredPhysicalMaterial::occlusion_texture] while any [CpuMaterial::metallic_roughness_texture] or [CpuMaterial::occlusion_texture] are ignored.
     ///
     pub fn new(context: &Context, cpu_material: &CpuMaterial) -> Self {
-        let albedo_texture =
-            cpu_material
-                .albedo_texture
-                .as_ref()
-                .map(|cpu_texture| match &cpu_texture.data {
-                    TextureData::RgbU8(_) | TextureData::RgbaU8(_) => {
-                        let mut cpu_texture = cpu_texture.clone();
-                        cpu_texture.data.to_linear_srgb();
-                        Texture2DRef::from_cpu_texture(context, &cpu_texture)
-                    }
-                    _ => Texture2DRef::from_cpu_texture(context, cpu_texture),
-                });
+        let albedo_texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Texture2DRef::from_cpu_texture_with_color_space(context, cpu_texture, ColorSpace::Srgb)
+        });
         let metallic_roughness_texture =
             if let Some(ref cpu_texture) = cpu_material.occlusion_metallic_roughness_texture {
                 Some(Texture2DRef::from_cpu_texture(context, cpu_texture))
@@ -89,18 +83,9 @@ impl DeferredPhysicalMaterial {
             .normal_texture
             .as_ref()
             .map(|cpu_texture| Texture2DRef::from_cpu_texture(context, cpu_texture));
-        let emissive_texture =
-            cpu_material
-                .emissive_texture
-                .as_ref()
-                .map(|cpu_texture| match &cpu_texture.data {
-                    TextureData::RgbU8(_) | TextureData::RgbaU8(_) => {
-                        let mut cpu_texture = cpu_texture.clone();
-                        cpu_texture.data.to_linear_srgb();
-                        Texture2DRef::from_cpu_texture(context, &cpu_texture)
-                    }
-                    _ => Texture2DRef::from_cpu_texture(context, cpu_texture),
-                });
+        let emissive_texture = cpu_material.emissive_texture.as_ref().map(|cpu_texture| {
+            Texture2DRef::from_cpu_texture_with_color_space(context, cpu_texture, ColorSpace::Srgb)
+        });
         Self {
             name: cpu_material.name.clone(),
             albedo: cpu_material.albedo,
@@ -111,6 +96,7 @@ impl DeferredPhysicalMaterial {
             normal_texture,
             normal_scale: cpu_material.normal_scale,
             occlusion_texture,
+            channel_packing: ChannelPacking::default(),
             occlusion_strength: cpu_material.occlusion_strength,
             render_states: RenderStates::default(),
             alpha_cutout: cpu_material.alpha_cutout,
@@ -133,6 +119,7 @@ impl DeferredPhysicalMaterial {
             normal_texture: physical_material.normal_texture.clone(),
             normal_scale: physical_material.normal_scale,
             occlusion_texture: physical_material.occlusion_texture.clone(),
+            channel_packing: physical_material.channel_packing,
             occlusion_strength: physical_material.occlusion_strength,
             render_states: RenderStates {
                 write_mask: WriteMask::default(),
@@ -169,6 +156,16 @@ impl DeferredPhysicalMaterial {
             Some(geometry_pass_depth_texture),
         );
     }
+
+    fn textures(&self) -> [Option<&Texture2DRef>; 5] {
+        [
+            self.albedo_texture.as_ref(),
+            self.metallic_roughness_texture.as_ref(),
+            self.occlusion_texture.as_ref(),
+            self.normal_texture.as_ref(),
+            self.emissive_texture.as_ref(),
+        ]
+    }
 }
 
 impl FromCpuMaterial for DeferredPhysicalMaterial {
@@ -179,6 +176,8 @@ impl FromCpuMaterial for DeferredPhysicalMaterial {
 
 impl Material for DeferredPhysicalMaterial {
     fn id(&self) -> u16 {
+        // The bits are used as follows: 0-4: textures, 5: alpha cutout, 6: always set and 9: secondary uv coordinates.
+        // Bit 6 is never set in the id of a PhysicalMaterial which otherwise uses the same bits.
         let mut id = 0b1u16 << 15 | 0b1u16 << 6;
         if self.albedo_texture.is_some() {
             id |= 0b1u16;
@@ -195,6 +194,9 @@ impl Material for DeferredPhysicalMaterial {
         if self.emissive_texture.is_some() {
             id |= 0b1u16 << 4;
         }
+        if uses_secondary_uvs(self.textures()) {
+            id |= 0b1u16 << 9;
+        }
         if self.alpha_cutout.is_some() {
             id |= 0b1u16 << 5;
         }
@@ -210,7 +212,7 @@ impl Material for DeferredPhysicalMaterial {
             || self.emissive_texture.is_some()
             || self.alpha_cutout.is_some()
         {
-            output.push_str("in vec2 uvs;\n");
+            output.push_str(uvs_shader_source(self.textures()));
             if self.albedo_texture.is_some() {
                 output.push_str("#define USE_ALBEDO_TEXTURE;\n");
             }
@@ -263,24 +265,31 @@ impl Material for DeferredPhysicalMaterial {
         if let Some(ref texture) = self.albedo_texture {
             program.use_texture("albedoTexture", texture);
             program.use_uniform("albedoTexTransform", texture.transformation);
+            program.use_uniform_if_required("albedoUvChannel", texture.uv_channel as f32);
         }
         if let Some(ref texture) = self.metallic_roughness_texture {
             program.use_texture("metallicRoughnessTexture", texture);
             program.use_uniform("metallicRoughnessTexTransform", texture.transformation);
+            program
+                .use_uniform_if_required("metallicRoughnessUvChannel", texture.uv_channel as f32);
         }
         if let Some(ref texture) = self.occlusion_texture {
             program.use_uniform("occlusionStrength", self.occlusion_strength);
             program.use_uniform("occlusionTexTransform", texture.transformation);
+            program.use_uniform_if_required("occlusionUvChannel", texture.uv_channel as f32);
             program.use_texture("occlusionTexture", texture);
         }
+        self.channel_packing.use_uniforms(program);
         if let Some(ref texture) = self.normal_texture {
             program.use_uniform("normalScale", self.normal_scale);
             program.use_uniform("normalTexTransform", texture.transformation);
+            program.use_uniform_if_required("normalUvChannel", texture.uv_channel as f32);
             program.use_texture("normalTexture", texture);
         }
         if program.requires_uniform("emissiveTexture") {
             if let Some(ref texture) = self.emissive_texture {
                 program.use_uniform("emissiveTexTransform", texture.transformation);
+                program.use_uniform_if_required("emissiveUvChannel", texture.uv_channel as f32);
                 program.use_texture("emissiveTexture", texture);
             }
         }
@@ -293,6 +302,14 @@ impl Material for DeferredPhysicalMaterial {
     fn material_type(&self) -> MaterialType {
         MaterialType::Deferred
     }
+
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        Some(DepthPrePassMaterial {
+            alpha_cutout: self.alpha_cutout,
+            alpha_texture: self.albedo_texture.clone(),
+            cull: self.render_states.cull,
+        })
+    }
 }
 
 impl Default for DeferredPhysicalMaterial {
@@ -307,6 +324,7 @@ impl Default for DeferredPhysicalMaterial {
             normal_texture: None,
             normal_scale: 1.0,
             occlusion_texture: None,
+            channel_packing: ChannelPacking::default(),
             occlusion_strength: 1.0,
             render_states: RenderStates::default(),
             alpha_cutout: None,
@@ -56,4 +56,11 @@ impl Material for DepthMaterial {
     fn material_type(&self) -> MaterialType {
         MaterialType::Opaque
     }
+
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        Some(DepthPrePassMaterial {
+            cull: self.render_states.cull,
+            ..Default::default()
+        })
+    }
 }
@@ -0,0 +1,135 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A material which only writes the depth of the fragments that would be written by another material, which is used when rendering a depth pre-pass,
+/// see [RenderTarget::render_with_depth_pre_pass]. A material provides its depth-only variant using [Material::depth_pre_pass_material].
+///
+#[derive(Clone)]
+pub struct DepthPrePassMaterial {
+    /// Fragments where the alpha value of the [DepthPrePassMaterial::alpha_texture] is below this value are discarded.
+    /// If None, or if there is no alpha texture, no fragments are discarded.
+    pub alpha_cutout: Option<f32>,
+    /// The texture whose alpha channel is compared against the [DepthPrePassMaterial::alpha_cutout], usually the albedo texture of the original material.
+    pub alpha_texture: Option<Texture2DRef>,
+    /// The culling of the original material.
+    pub cull: Cull,
+}
+
+impl Default for DepthPrePassMaterial {
+    fn default() -> Self {
+        Self {
+            alpha_cutout: None,
+            alpha_texture: None,
+            cull: Cull::None,
+        }
+    }
+}
+
+impl DepthPrePassMaterial {
+    fn alpha_texture(&self) -> Option<&Texture2DRef> {
+        self.alpha_cutout.and(self.alpha_texture.as_ref())
+    }
+}
+
+impl Material for DepthPrePassMaterial {
+    fn id(&self) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 8 | 0b1u16 << 1;
+        if self.alpha_texture().is_some() {
+            id |= 0b1u16 << 2;
+        }
+        if uses_secondary_uvs([self.alpha_texture()]) {
+            id |= 0b1u16 << 9;
+        }
+        id
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        let mut output = String::new();
+        if self.alpha_texture().is_some() {
+            output.push_str("#define USE_ALPHA_TEXTURE\n");
+            output.push_str(uvs_shader_source([self.alpha_texture()]));
+        }
+        output.push_str(include_str!("shaders/depth_pre_pass_material.frag"));
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: self.alpha_texture().is_some(),
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
+        if let Some(texture) = self.alpha_texture() {
+            program.use_uniform("alphaCutout", self.alpha_cutout.unwrap());
+            program.use_uniform("alphaTexTransform", texture.transformation);
+            program.use_uniform_if_required("alphaUvChannel", texture.uv_channel as f32);
+            program.use_texture("alphaTexture", texture);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::DEPTH,
+            cull: self.cull,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
+
+///
+/// Renders with the given material, but only where the depth is equal to the depth written in a depth pre-pass and without writing the depth again.
+///
+pub(crate) struct DepthPrePassedMaterial<'a, M: Material + ?Sized>(pub &'a M);
+
+impl<M: Material + ?Sized> Material for DepthPrePassedMaterial<'_, M> {
+    fn id(&self) -> u16 {
+        self.0.id()
+    }
+
+    fn extended_id(&self) -> Option<u64> {
+        self.0.extended_id()
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        self.0.fragment_shader_source(lights)
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        self.0.fragment_attributes()
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        self.0.use_uniforms(program, camera, lights)
+    }
+
+    fn render_states(&self) -> RenderStates {
+        let render_states = self.0.render_states();
+        RenderStates {
+            write_mask: WriteMask {
+                depth: false,
+                ..render_states.write_mask
+            },
+            depth_test: DepthTest::LessOrEqual,
+            ..render_states
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        self.0.material_type()
+    }
+
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        self.0.depth_pre_pass_material()
+    }
+
+    fn vertex_displacement_source(&self) -> Option<String> {
+        self.0.vertex_displacement_source()
+    }
+}
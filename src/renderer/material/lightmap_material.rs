@@ -0,0 +1,114 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A material which renders a [Geometry] using lighting that has been baked into a lightmap, for example using a [LightmapBaker].
+/// The color is the albedo color multiplied with the baked lighting, which means this material is not affected by lights and is very cheap to render.
+///
+/// The lightmap is sampled using the uv coordinates of the geometry, the same uv coordinates that was used when baking the lightmap,
+/// or the secondary uv coordinates if the [Texture2DRef::uv_channel] of the lightmap is 1, see [LightmapBaker::bake_with_uvs].
+///
+#[derive(Clone)]
+pub struct LightmapMaterial {
+    /// Albedo base color, also called diffuse color.
+    pub albedo: Srgba,
+    /// Texture with albedo base colors, also called diffuse color.
+    /// [LightmapMaterial::new] creates it in [ColorSpace::Srgb], so the hardware converts the colors to linear sRGB when sampling.
+    /// Other textures are assumed to be in linear sRGB or HDR color space.
+    pub albedo_texture: Option<Texture2DRef>,
+    /// The lightmap containing the baked lighting in linear HDR color space.
+    pub lightmap: Texture2DRef,
+    /// A multiplier of the baked lighting, which can be used to adjust the brightness without baking the lightmap again.
+    pub intensity: f32,
+    /// Render states.
+    pub render_states: RenderStates,
+}
+
+impl LightmapMaterial {
+    ///
+    /// Constructs a new lightmap material from a [CpuMaterial], from which the albedo color and texture are used, and a lightmap.
+    ///
+    pub fn new(context: &Context, cpu_material: &CpuMaterial, lightmap: &CpuTexture) -> Self {
+        let albedo_texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Texture2DRef::from_cpu_texture_with_color_space(context, cpu_texture, ColorSpace::Srgb)
+        });
+        Self {
+            albedo: cpu_material.albedo,
+            albedo_texture,
+            lightmap: Texture2DRef::from_cpu_texture(context, lightmap),
+            intensity: 1.0,
+            render_states: RenderStates::default(),
+        }
+    }
+}
+
+impl Material for LightmapMaterial {
+    fn id(&self) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 8;
+        if self.albedo_texture.is_some() {
+            id |= 0b1u16;
+        }
+        if uses_secondary_uvs([Some(&self.lightmap), self.albedo_texture.as_ref()]) {
+            id |= 0b1u16 << 9;
+        }
+        id
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        let mut output = String::new();
+        if self.albedo_texture.is_some() {
+            output.push_str("#define USE_ALBEDO_TEXTURE\n");
+        }
+        output.push_str(uvs_shader_source([
+            Some(&self.lightmap),
+            self.albedo_texture.as_ref(),
+        ]));
+        output.push_str(include_str!("../../core/shared.frag"));
+        output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(include_str!("shaders/lightmap_material.frag"));
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            color: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        program.use_uniform("albedo", self.albedo.to_linear_srgb());
+        program.use_uniform("intensity", self.intensity);
+        program.use_uniform("lightmapTransformation", self.lightmap.transformation);
+        program.use_uniform_if_required("lightmapUvChannel", self.lightmap.uv_channel as f32);
+        program.use_texture("lightmap", &self.lightmap);
+        if let Some(ref texture) = self.albedo_texture {
+            program.use_uniform("albedoTexTransform", texture.transformation);
+            program.use_uniform_if_required("albedoUvChannel", texture.uv_channel as f32);
+            program.use_texture("albedoTexture", texture);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        Some(DepthPrePassMaterial {
+            cull: self.render_states.cull,
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,156 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// The type of noise generated by a [NoiseGenerator].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum NoiseType {
+    /// Smooth gradient noise on a square lattice, for example for terrain heights or material detail.
+    #[default]
+    Perlin,
+    /// Smooth gradient noise on a simplex lattice, which has fewer directional artifacts than Perlin noise but does not tile.
+    Simplex,
+    /// Cellular noise, ie. the distance to the closest of a set of randomly placed feature points, for example for stones, scales or water caustics.
+    Worley,
+}
+
+///
+/// Generates procedural noise into 2D or 3D textures on the GPU, for example to feed terrain heights, clouds, water or the detail of a material
+/// without shipping large noise textures with the application.
+/// Each texel contains a single value in the range `[0..1]` stored as a 16-bit float.
+///
+/// When [NoiseGenerator::octaves] is larger than one, several layers of noise with increasing frequency and decreasing amplitude are added together,
+/// ie. fractal Brownian motion (FBM).
+/// The generated Perlin and Worley noise tiles seamlessly if the frequency and lacunarity are whole numbers, simplex noise never tiles.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NoiseGenerator {
+    /// The type of noise.
+    pub noise_type: NoiseType,
+    /// The seed of the random values, different seeds give different noise with the same characteristics.
+    pub seed: u32,
+    /// The number of noise cells across the texture in each direction for the first octave.
+    pub frequency: f32,
+    /// The number of layers of noise that are added together.
+    pub octaves: u32,
+    /// The factor the frequency is multiplied by for each octave.
+    pub lacunarity: f32,
+    /// The factor the amplitude is multiplied by for each octave, a lower value gives a smoother result.
+    pub gain: f32,
+}
+
+impl Default for NoiseGenerator {
+    fn default() -> Self {
+        Self {
+            noise_type: NoiseType::default(),
+            seed: 0,
+            frequency: 4.0,
+            octaves: 1,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+}
+
+impl NoiseGenerator {
+    ///
+    /// Generates a new 2D texture with the given size containing the noise.
+    /// The texture uses linear interpolation, mip maps and repeat wrapping.
+    ///
+    pub fn texture_2d(&self, context: &Context, width: u32, height: u32) -> Texture2D {
+        let mut texture = Texture2D::new_empty::<f16>(
+            context,
+            width,
+            height,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            Some(Interpolation::Linear),
+            Wrapping::Repeat,
+            Wrapping::Repeat,
+        );
+        let program = self.program(context);
+        texture
+            .as_color_target(None)
+            .write::<RendererError>(|| {
+                self.draw(context, &program, width, height, 0.0);
+                Ok(())
+            })
+            .unwrap();
+        texture
+    }
+
+    ///
+    /// Generates a new 3D texture with the given size containing the noise, for example to be used as the density of clouds or fog.
+    /// The texture uses linear interpolation, mip maps and repeat wrapping.
+    ///
+    pub fn texture_3d(&self, context: &Context, width: u32, height: u32, depth: u32) -> Texture3D {
+        let mut texture = Texture3D::new_empty::<f16>(
+            context,
+            width,
+            height,
+            depth,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            Some(Interpolation::Linear),
+            Wrapping::Repeat,
+            Wrapping::Repeat,
+            Wrapping::Repeat,
+        );
+        let program = self.program(context);
+        for layer in 0..depth {
+            // Only generate the mip maps after the last layer has been written
+            let mip_level = if layer + 1 < depth { Some(0) } else { None };
+            texture
+                .as_color_target(&[layer], mip_level)
+                .write::<RendererError>(|| {
+                    let z = (layer as f32 + 0.5) / depth as f32;
+                    self.draw(context, &program, width, height, z);
+                    Ok(())
+                })
+                .unwrap();
+        }
+        texture
+    }
+
+    fn program(&self, context: &Context) -> Program {
+        Program::from_source(
+            context,
+            full_screen_vertex_shader_source(),
+            &format!(
+                "{}{}",
+                include_str!("shaders/noise_functions.frag"),
+                include_str!("shaders/noise.frag")
+            ),
+        )
+        .expect("Failed compiling shader")
+    }
+
+    fn draw(&self, context: &Context, program: &Program, width: u32, height: u32, layer: f32) {
+        program.use_uniform(
+            "noiseType",
+            match self.noise_type {
+                NoiseType::Perlin => 0u32,
+                NoiseType::Simplex => 1u32,
+                NoiseType::Worley => 2u32,
+            },
+        );
+        program.use_uniform("seed", self.seed);
+        program.use_uniform("frequency", self.frequency.max(0.0001));
+        program.use_uniform("octaves", self.octaves.max(1) as i32);
+        program.use_uniform("lacunarity", self.lacunarity);
+        program.use_uniform("gain", self.gain);
+        program.use_uniform("layer", layer);
+        full_screen_draw(
+            context,
+            program,
+            RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                blend: Blend::Disabled,
+                cull: Cull::None,
+            },
+            Viewport::new_at_origo(width, height),
+        );
+    }
+}
@@ -96,6 +96,13 @@ impl Material for NormalMaterial {
     fn material_type(&self) -> MaterialType {
         MaterialType::Opaque
     }
+
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        Some(DepthPrePassMaterial {
+            cull: self.render_states.cull,
+            ..Default::default()
+        })
+    }
 }
 
 impl Default for NormalMaterial {
@@ -12,13 +12,15 @@ pub struct ORMMaterial {
     /// A value in the range `[0..1]` specifying how rough the material surface is.
     pub roughness: f32,
     /// Texture containing the metallic and roughness parameters which are multiplied with the [Self::metallic] and [Self::roughness] values in the shader.
-    /// The metallic values are sampled from the blue channel and the roughness from the green channel.
+    /// By default, the metallic values are sampled from the blue channel and the roughness from the green channel, see [Self::channel_packing].
     pub metallic_roughness_texture: Option<Texture2DRef>,
     /// A scalar multiplier controlling the amount of occlusion applied from the [Self::occlusion_texture]. A value of 0.0 means no occlusion. A value of 1.0 means full occlusion.
     pub occlusion_strength: f32,
     /// An occlusion map. Higher values indicate areas that should receive full indirect lighting and lower values indicate no indirect lighting.
-    /// The occlusion values are sampled from the red channel.
+    /// By default, the occlusion values are sampled from the red channel, see [Self::channel_packing].
     pub occlusion_texture: Option<Texture2DRef>,
+    /// Which channels of the [Self::metallic_roughness_texture] and [Self::occlusion_texture] contain the metallic, roughness and occlusion values.
+    pub channel_packing: ChannelPacking,
     /// Render states.
     pub render_states: RenderStates,
 }
@@ -48,6 +50,7 @@ impl ORMMaterial {
             roughness: cpu_material.roughness,
             metallic_roughness_texture,
             occlusion_texture,
+            channel_packing: ChannelPacking::default(),
             occlusion_strength: cpu_material.occlusion_strength,
             render_states: RenderStates::default(),
         }
@@ -61,6 +64,7 @@ impl ORMMaterial {
             metallic_roughness_texture: physical_material.metallic_roughness_texture.clone(),
             occlusion_strength: physical_material.occlusion_strength,
             occlusion_texture: physical_material.occlusion_texture.clone(),
+            channel_packing: physical_material.channel_packing,
             render_states: RenderStates {
                 write_mask: WriteMask::default(),
                 blend: Blend::Disabled,
@@ -122,6 +126,7 @@ impl Material for ORMMaterial {
             program.use_texture("occlusionTexture", texture);
             program.use_uniform("occlusionTexTransform", texture.transformation);
         }
+        self.channel_packing.use_uniforms(program);
     }
 
     fn render_states(&self) -> RenderStates {
@@ -131,6 +136,13 @@ impl Material for ORMMaterial {
     fn material_type(&self) -> MaterialType {
         MaterialType::Opaque
     }
+
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        Some(DepthPrePassMaterial {
+            cull: self.render_states.cull,
+            ..Default::default()
+        })
+    }
 }
 
 impl Default for ORMMaterial {
@@ -140,6 +152,7 @@ impl Default for ORMMaterial {
             roughness: 1.0,
             metallic_roughness_texture: None,
             occlusion_texture: None,
+            channel_packing: ChannelPacking::default(),
             occlusion_strength: 1.0,
             render_states: RenderStates::default(),
         }
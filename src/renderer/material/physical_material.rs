@@ -1,6 +1,67 @@
 use crate::core::*;
 use crate::renderer::*;
 
+///
+/// The shading model used when rendering a [PhysicalMaterial], which makes it possible to skip the cost of physically based shading
+/// for materials that do not need it, for example user interface elements and stylized assets.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ShadingModel {
+    /// The material is not affected by lights and is rendered in the albedo color plus the emissive color,
+    /// as defined by the [KHR_materials_unlit](https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_materials_unlit/README.md) glTF extension.
+    Unlit,
+    /// The material is affected by lights, but only by diffuse (Lambertian) reflection, ie. without any specular highlights or reflections.
+    Lambert,
+    /// The material is affected by lights using the [PhysicalMaterial::lighting_model], for example Blinn-Phong or Cook-Torrance (PBR).
+    #[default]
+    Lit,
+}
+
+#[cfg(feature = "gltf-extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gltf-extensions")))]
+impl ShadingModel {
+    ///
+    /// Parses the shading models from the given raw assets, which must contain the glTF file at the given path, see [ShadingModel::from_gltf].
+    /// Call this before deserializing the [CpuModel], since deserializing removes the glTF file from the raw assets.
+    ///
+    /// ```no_run
+    /// # use three_d::*;
+    /// # let context: Context = unimplemented!();
+    /// let mut loaded = three_d_asset::io::load(&["stylized.glb"]).unwrap();
+    /// let shading_models = ShadingModel::deserialize(&loaded, "stylized.glb").unwrap();
+    /// let mut model =
+    ///     Model::<PhysicalMaterial>::new(&context, &loaded.deserialize("stylized.glb").unwrap()).unwrap();
+    /// model.set_shading_models(&shading_models);
+    /// ```
+    ///
+    pub fn deserialize(
+        raw_assets: &three_d_asset::io::RawAssets,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Vec<Self>, RendererError> {
+        Self::from_gltf(raw_assets.get(path)?)
+    }
+
+    ///
+    /// Parses the shading model of each material in the given bytes of a glTF (`.gltf`) or binary glTF (`.glb`) file,
+    /// in the same order as [CpuModel::materials]. The materials using the
+    /// [KHR_materials_unlit](https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_materials_unlit/README.md)
+    /// extension are [ShadingModel::Unlit] and all other materials are [ShadingModel::Lit].
+    ///
+    pub fn from_gltf(bytes: &[u8]) -> Result<Vec<Self>, RendererError> {
+        let document = gltf::Gltf::from_slice(bytes)?.document;
+        Ok(document
+            .materials()
+            .map(|material| {
+                if material.unlit() {
+                    Self::Unlit
+                } else {
+                    Self::Lit
+                }
+            })
+            .collect())
+    }
+}
+
 ///
 /// A physically-based material that renders a [Geometry] in an approximate correct physical manner based on Physically Based Rendering (PBR).
 /// This material is affected by lights.
@@ -12,20 +73,23 @@ pub struct PhysicalMaterial {
     /// Albedo base color, also called diffuse color.
     pub albedo: Srgba,
     /// Texture with albedo base colors, also called diffuse color.
-    /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
+    /// The constructors taking a [CpuMaterial] create it in [ColorSpace::Srgb], so the hardware converts the colors to linear sRGB when sampling.
+    /// Other textures are assumed to be in linear sRGB or HDR color space.
     pub albedo_texture: Option<Texture2DRef>,
     /// A value in the range `[0..1]` specifying how metallic the surface is.
     pub metallic: f32,
     /// A value in the range `[0..1]` specifying how rough the surface is.
     pub roughness: f32,
     /// Texture containing the metallic and roughness parameters which are multiplied with the [Self::metallic] and [Self::roughness] values in the shader.
-    /// The metallic values are sampled from the blue channel and the roughness from the green channel.
+    /// By default, the metallic values are sampled from the blue channel and the roughness from the green channel, see [Self::channel_packing].
     pub metallic_roughness_texture: Option<Texture2DRef>,
     /// A scalar multiplier controlling the amount of occlusion applied from the [Self::occlusion_texture]. A value of 0.0 means no occlusion. A value of 1.0 means full occlusion.
     pub occlusion_strength: f32,
     /// An occlusion map. Higher values indicate areas that should receive full indirect lighting and lower values indicate no indirect lighting.
-    /// The occlusion values are sampled from the red channel.
+    /// By default, the occlusion values are sampled from the red channel, see [Self::channel_packing].
     pub occlusion_texture: Option<Texture2DRef>,
+    /// Which channels of the [Self::metallic_roughness_texture] and [Self::occlusion_texture] contain the metallic, roughness and occlusion values.
+    pub channel_packing: ChannelPacking,
     /// A scalar multiplier applied to each normal vector of the [Self::normal_texture].
     pub normal_scale: f32,
     /// A tangent space normal map, also known as bump map.
@@ -37,10 +101,44 @@ pub struct PhysicalMaterial {
     /// Color of light shining from an object.
     pub emissive: Srgba,
     /// Texture with color of light shining from an object.
-    /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
+    /// Like the [Self::albedo_texture], it is in [ColorSpace::Srgb] when created from a [CpuMaterial] and otherwise assumed to be in linear sRGB or HDR color space.
     pub emissive_texture: Option<Texture2DRef>,
     /// The lighting model used when rendering this material
     pub lighting_model: LightingModel,
+    /// The shading model used when rendering this material, where the [PhysicalMaterial::lighting_model] is only used if it is [ShadingModel::Lit].
+    pub shading_model: ShadingModel,
+    /// Color of the rim light, ie. light emitted at the silhouette of an object, where the surface is seen at a grazing angle,
+    /// for example to highlight a selected object or for a stylized look. A black rim color disables the rim light.
+    pub rim_color: Srgba,
+    /// The exponent of the Fresnel falloff of the rim light, a higher value gives a thinner rim.
+    pub rim_power: f32,
+    /// A value in the range `[0..1]` specifying the strength of the thin-film iridescence,
+    /// ie. the angle dependent rainbow colors of for example soap bubbles, oil slicks and beetle shells,
+    /// as defined by the [KHR_materials_iridescence](https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_materials_iridescence/README.md) glTF extension.
+    /// A value of 0.0 disables the iridescence. Iridescence is only applied with the [ShadingModel::Lit] shading model.
+    pub iridescence: f32,
+    /// The index of refraction of the thin-film layer.
+    pub iridescence_ior: f32,
+    /// The minimum thickness of the thin-film layer in nanometers, which is used where the [Self::iridescence_texture] has a green value of 0.0.
+    pub iridescence_thickness_min: f32,
+    /// The maximum thickness of the thin-film layer in nanometers, which is used where the [Self::iridescence_texture] has a green value of 1.0
+    /// or if there is no texture.
+    pub iridescence_thickness_max: f32,
+    /// Texture where the [Self::iridescence] value is multiplied with the red channel and the thickness of the thin-film layer is interpolated
+    /// between [Self::iridescence_thickness_min] and [Self::iridescence_thickness_max] using the green channel.
+    pub iridescence_texture: Option<Texture2DRef>,
+    /// A probe which has captured the surroundings and which is used for the glossy reflections instead of the environment of an [AmbientLight],
+    /// see [ReflectionProbe::nearest] for choosing the probe for an object.
+    /// The probe is only used with the [ShadingModel::Lit] shading model and if it has been rendered.
+    pub reflection_probe: Option<ReflectionProbe>,
+    /// A mirrored rendering of the scene which is used for the glossy reflections instead of the environment of an [AmbientLight], for example for a mirror or a floor.
+    /// The object using this material should lie in the plane and where nothing is reflected, the [Self::reflection_probe] is used if there is one.
+    /// The reflection plane is only used with the [ShadingModel::Lit] shading model and if it has been rendered.
+    pub reflection_plane: Option<ReflectionPlane>,
+    /// Whether the [Camera::fog] is applied to this material. The default is true.
+    /// The fog is compiled into the shader whenever this is true, also when the camera has no fog, since the shader does not depend on the camera,
+    /// so set this to false if the material is never rendered with fog.
+    pub fog: bool,
 }
 
 impl PhysicalMaterial {
@@ -70,18 +168,9 @@ impl PhysicalMaterial {
     }
 
     fn new_internal(context: &Context, cpu_material: &CpuMaterial, is_transparent: bool) -> Self {
-        let albedo_texture =
-            cpu_material
-                .albedo_texture
-                .as_ref()
-                .map(|cpu_texture| match &cpu_texture.data {
-                    TextureData::RgbU8(_) | TextureData::RgbaU8(_) => {
-                        let mut cpu_texture = cpu_texture.clone();
-                        cpu_texture.data.to_linear_srgb();
-                        Texture2DRef::from_cpu_texture(context, &cpu_texture)
-                    }
-                    _ => Texture2DRef::from_cpu_texture(context, cpu_texture),
-                });
+        let albedo_texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Texture2DRef::from_cpu_texture_with_color_space(context, cpu_texture, ColorSpace::Srgb)
+        });
         let metallic_roughness_texture =
             if let Some(ref cpu_texture) = cpu_material.occlusion_metallic_roughness_texture {
                 Some(Texture2DRef::from_cpu_texture(context, cpu_texture))
@@ -103,18 +192,9 @@ impl PhysicalMaterial {
             .normal_texture
             .as_ref()
             .map(|cpu_texture| Texture2DRef::from_cpu_texture(context, cpu_texture));
-        let emissive_texture =
-            cpu_material
-                .emissive_texture
-                .as_ref()
-                .map(|cpu_texture| match &cpu_texture.data {
-                    TextureData::RgbU8(_) | TextureData::RgbaU8(_) => {
-                        let mut cpu_texture = cpu_texture.clone();
-                        cpu_texture.data.to_linear_srgb();
-                        Texture2DRef::from_cpu_texture(context, &cpu_texture)
-                    }
-                    _ => Texture2DRef::from_cpu_texture(context, cpu_texture),
-                });
+        let emissive_texture = cpu_material.emissive_texture.as_ref().map(|cpu_texture| {
+            Texture2DRef::from_cpu_texture_with_color_space(context, cpu_texture, ColorSpace::Srgb)
+        });
         Self {
             name: cpu_material.name.clone(),
             albedo: cpu_material.albedo,
@@ -125,6 +205,7 @@ impl PhysicalMaterial {
             normal_texture,
             normal_scale: cpu_material.normal_scale,
             occlusion_texture,
+            channel_packing: ChannelPacking::default(),
             occlusion_strength: cpu_material.occlusion_strength,
             render_states: if is_transparent {
                 RenderStates {
@@ -139,8 +220,56 @@ impl PhysicalMaterial {
             emissive: cpu_material.emissive,
             emissive_texture,
             lighting_model: cpu_material.lighting_model,
+            shading_model: ShadingModel::Lit,
+            rim_color: Srgba::BLACK,
+            rim_power: 5.0,
+            iridescence: 0.0,
+            iridescence_ior: 1.3,
+            iridescence_thickness_min: 100.0,
+            iridescence_thickness_max: 400.0,
+            iridescence_texture: None,
+            reflection_probe: None,
+            reflection_plane: None,
+            fog: true,
         }
     }
+
+    fn uses_rim(&self) -> bool {
+        self.rim_color.r > 0 || self.rim_color.g > 0 || self.rim_color.b > 0
+    }
+
+    fn uses_iridescence(&self) -> bool {
+        self.iridescence > 0.0 && self.shading_model == ShadingModel::Lit
+    }
+
+    fn uses_reflection_probe(&self) -> bool {
+        self.shading_model == ShadingModel::Lit
+            && self
+                .reflection_probe
+                .as_ref()
+                .map(|probe| probe.is_rendered())
+                .unwrap_or(false)
+    }
+
+    fn uses_reflection_plane(&self) -> bool {
+        self.shading_model == ShadingModel::Lit
+            && self
+                .reflection_plane
+                .as_ref()
+                .map(|plane| plane.is_rendered())
+                .unwrap_or(false)
+    }
+
+    fn textures(&self) -> [Option<&Texture2DRef>; 6] {
+        [
+            self.albedo_texture.as_ref(),
+            self.metallic_roughness_texture.as_ref(),
+            self.occlusion_texture.as_ref(),
+            self.normal_texture.as_ref(),
+            self.emissive_texture.as_ref(),
+            self.iridescence_texture.as_ref(),
+        ]
+    }
 }
 
 impl FromCpuMaterial for PhysicalMaterial {
@@ -151,6 +280,10 @@ impl FromCpuMaterial for PhysicalMaterial {
 
 impl Material for PhysicalMaterial {
     fn id(&self) -> u16 {
+        // The bits are used as follows: 0-4: textures, 5: always set, 7: iridescence, 8: iridescence texture, 9: secondary uv coordinates,
+        // 10-11: shading model, 12: reflection probe, 13: fog and 14: reflection plane.
+        // Bit 6 is never set, since it is always set in the id of a DeferredPhysicalMaterial which otherwise uses the same bits,
+        // so the rim light, for which there is no bit left, is part of the extended id instead.
         let mut id = 0b1u16 << 15 | 0b1u16 << 5;
         if self.albedo_texture.is_some() {
             id |= 0b1u16;
@@ -167,18 +300,74 @@ impl Material for PhysicalMaterial {
         if self.emissive_texture.is_some() {
             id |= 0b1u16 << 4;
         }
+        if uses_secondary_uvs(self.textures()) {
+            id |= 0b1u16 << 9;
+        }
+        if self.uses_iridescence() {
+            id |= 0b1u16 << 7;
+            if self.iridescence_texture.is_some() {
+                id |= 0b1u16 << 8;
+            }
+        }
+        match self.shading_model {
+            ShadingModel::Unlit => id |= 0b1u16 << 10,
+            ShadingModel::Lambert => id |= 0b1u16 << 11,
+            ShadingModel::Lit => {}
+        }
+        if self.uses_reflection_probe() {
+            id |= 0b1u16 << 12;
+        }
+        if self.uses_reflection_plane() {
+            id |= 0b1u16 << 14;
+        }
+        if self.fog {
+            id |= 0b1u16 << 13;
+        }
         id
     }
 
+    fn extended_id(&self) -> Option<u64> {
+        self.uses_rim().then_some(1)
+    }
+
     fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
-        let mut output = lights_shader_source(lights, self.lighting_model);
-        if self.albedo_texture.is_some()
-            || self.metallic_roughness_texture.is_some()
-            || self.normal_texture.is_some()
-            || self.occlusion_texture.is_some()
-            || self.emissive_texture.is_some()
-        {
-            output.push_str("in vec2 uvs;\n");
+        let mut output = match self.shading_model {
+            ShadingModel::Unlit => {
+                format!("#define UNLIT\n{}", include_str!("../../core/shared.frag"))
+            }
+            ShadingModel::Lambert => format!(
+                "#define LAMBERT\n{}",
+                lights_shader_source(lights, self.lighting_model)
+            ),
+            ShadingModel::Lit if self.uses_iridescence() => format!(
+                "#define IRIDESCENCE\n{}",
+                lights_shader_source(lights, self.lighting_model)
+            ),
+            ShadingModel::Lit => lights_shader_source(lights, self.lighting_model),
+        };
+        if self.uses_reflection_probe() {
+            output = format!(
+                "#define REFLECTION_PROBE\n{}{}",
+                output,
+                ReflectionProbe::fragment_shader_source()
+            );
+        }
+        if self.uses_reflection_plane() {
+            output = format!(
+                "#define REFLECTION_PLANE\n{}{}",
+                output,
+                ReflectionPlane::fragment_shader_source()
+            );
+        }
+        if self.uses_rim() {
+            output.push_str("#define USE_RIM\n");
+        }
+        if self.fog {
+            output.push_str("#define USE_FOG\n");
+            output.push_str(Fog::fragment_shader_source());
+        }
+        if self.textures().iter().any(|t| t.is_some()) {
+            output.push_str(uvs_shader_source(self.textures()));
             if self.albedo_texture.is_some() {
                 output.push_str("#define USE_ALBEDO_TEXTURE;\n");
             }
@@ -194,59 +383,107 @@ impl Material for PhysicalMaterial {
             if self.emissive_texture.is_some() {
                 output.push_str("#define USE_EMISSIVE_TEXTURE;\n");
             }
+            if self.iridescence_texture.is_some() && self.uses_iridescence() {
+                output.push_str("#define USE_IRIDESCENCE_TEXTURE;\n");
+            }
         }
         output.push_str(ToneMapping::fragment_shader_source());
         output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(CAMERA_UNIFORM_BLOCK_SOURCE);
         output.push_str(include_str!("shaders/physical_material.frag"));
         output
     }
 
     fn fragment_attributes(&self) -> FragmentAttributes {
+        let lit = self.shading_model != ShadingModel::Unlit;
         FragmentAttributes {
-            position: true,
-            normal: true,
+            position: lit || self.uses_rim() || self.fog,
+            normal: lit || self.uses_rim(),
             color: true,
-            uv: self.albedo_texture.is_some()
-                || self.metallic_roughness_texture.is_some()
-                || self.normal_texture.is_some()
-                || self.occlusion_texture.is_some()
-                || self.emissive_texture.is_some(),
-            tangents: self.normal_texture.is_some(),
+            uv: self.textures().iter().any(|t| t.is_some()),
+            tangents: lit && self.normal_texture.is_some(),
         }
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
-        camera.tone_mapping.use_uniforms(program);
-        camera.color_mapping.use_uniforms(program);
-        if !lights.is_empty() {
-            program.use_uniform_if_required("cameraPosition", camera.position());
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        if self.fog {
+            camera.fog.use_uniforms(program, *camera.position());
+        }
+        if program.requires_uniform("albedoTexture") {
+            if let Some(ref texture) = self.albedo_texture {
+                program.use_uniform("albedoTexTransform", texture.transformation);
+                program.use_uniform_if_required("albedoUvChannel", texture.uv_channel as f32);
+                program.use_texture("albedoTexture", texture);
+            }
+        }
+        camera.use_uniform_block_if_required(program);
+        if self.uses_rim() {
+            program.use_uniform("rimColor", self.rim_color.to_linear_srgb());
+            program.use_uniform("rimPower", self.rim_power);
+        }
+        if !lights.is_empty() && self.shading_model != ShadingModel::Unlit {
+            program.use_uniform_if_required("iridescenceFactor", self.iridescence);
+            program.use_uniform_if_required("iridescenceIor", self.iridescence_ior);
+            program.use_uniform_if_required(
+                "iridescenceThickness",
+                vec2(
+                    self.iridescence_thickness_min,
+                    self.iridescence_thickness_max,
+                ),
+            );
+            if program.requires_uniform("iridescenceTexture") {
+                if let Some(ref texture) = self.iridescence_texture {
+                    program.use_uniform("iridescenceTexTransform", texture.transformation);
+                    program
+                        .use_uniform_if_required("iridescenceUvChannel", texture.uv_channel as f32);
+                    program.use_texture("iridescenceTexture", texture);
+                }
+            }
             for (i, light) in lights.iter().enumerate() {
                 light.use_uniforms(program, i as u32);
             }
-            program.use_uniform("metallic", self.metallic);
-            program.use_uniform_if_required("roughness", self.roughness);
-            if program.requires_uniform("albedoTexture") {
-                if let Some(ref texture) = self.albedo_texture {
-                    program.use_uniform("albedoTexTransform", texture.transformation);
-                    program.use_texture("albedoTexture", texture);
+            if self.uses_reflection_probe() {
+                if let Some(ref probe) = self.reflection_probe {
+                    probe.use_uniforms(program);
+                }
+            }
+            if self.uses_reflection_plane() {
+                if let Some(ref plane) = self.reflection_plane {
+                    plane.use_uniforms(program);
                 }
             }
+            program.use_uniform("metallic", self.metallic);
+            program.use_uniform_if_required("roughness", self.roughness);
             if program.requires_uniform("metallicRoughnessTexture") {
                 if let Some(ref texture) = self.metallic_roughness_texture {
                     program.use_uniform("metallicRoughnessTexTransform", texture.transformation);
+                    program.use_uniform_if_required(
+                        "metallicRoughnessUvChannel",
+                        texture.uv_channel as f32,
+                    );
                     program.use_texture("metallicRoughnessTexture", texture);
                 }
             }
             if program.requires_uniform("occlusionTexture") {
                 if let Some(ref texture) = self.occlusion_texture {
                     program.use_uniform("occlusionTexTransform", texture.transformation);
+                    program
+                        .use_uniform_if_required("occlusionUvChannel", texture.uv_channel as f32);
                     program.use_uniform("occlusionStrength", self.occlusion_strength);
                     program.use_texture("occlusionTexture", texture);
                 }
             }
+            self.channel_packing.use_uniforms(program);
             if program.requires_uniform("normalTexture") {
                 if let Some(ref texture) = self.normal_texture {
                     program.use_uniform("normalTexTransform", texture.transformation);
+                    program.use_uniform_if_required("normalUvChannel", texture.uv_channel as f32);
                     program.use_uniform("normalScale", self.normal_scale);
                     program.use_texture("normalTexture", texture);
                 }
@@ -257,6 +494,7 @@ impl Material for PhysicalMaterial {
         if program.requires_uniform("emissiveTexture") {
             if let Some(ref texture) = self.emissive_texture {
                 program.use_uniform("emissiveTexTransform", texture.transformation);
+                program.use_uniform_if_required("emissiveUvChannel", texture.uv_channel as f32);
                 program.use_texture("emissiveTexture", texture);
             }
         }
@@ -272,6 +510,13 @@ impl Material for PhysicalMaterial {
             MaterialType::Opaque
         }
     }
+
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        Some(DepthPrePassMaterial {
+            cull: self.render_states.cull,
+            ..Default::default()
+        })
+    }
 }
 
 impl Default for PhysicalMaterial {
@@ -286,12 +531,24 @@ impl Default for PhysicalMaterial {
             normal_texture: None,
             normal_scale: 1.0,
             occlusion_texture: None,
+            channel_packing: ChannelPacking::default(),
             occlusion_strength: 1.0,
             render_states: RenderStates::default(),
             is_transparent: false,
             emissive: Srgba::BLACK,
             emissive_texture: None,
             lighting_model: LightingModel::Blinn,
+            shading_model: ShadingModel::Lit,
+            rim_color: Srgba::BLACK,
+            rim_power: 5.0,
+            iridescence: 0.0,
+            iridescence_ior: 1.3,
+            iridescence_thickness_min: 100.0,
+            iridescence_thickness_max: 400.0,
+            iridescence_texture: None,
+            reflection_probe: None,
+            reflection_plane: None,
+            fog: true,
         }
     }
 }
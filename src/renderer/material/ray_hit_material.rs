@@ -0,0 +1,73 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Renders the distance from the camera, the index of the object and, if the given material requires normals, the normal
+/// of the fragments that would be written by the given material, which is used for finding ray intersections on the GPU,
+/// see [ray_intersect_with_materials].
+///
+pub(crate) struct RayHitMaterial<'a, M: Material + ?Sized> {
+    pub material: &'a M,
+    pub index: usize,
+    pub max_distance: f32,
+}
+
+impl<M: Material + ?Sized> RayHitMaterial<'_, M> {
+    fn uses_normal(&self) -> bool {
+        self.material.fragment_attributes().normal
+    }
+}
+
+impl<M: Material + ?Sized> Material for RayHitMaterial<'_, M> {
+    fn id(&self) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 13;
+        if self.uses_normal() {
+            id |= 0b1u16;
+        }
+        id
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        let mut output = String::new();
+        if self.uses_normal() {
+            output.push_str("#define USE_NORMAL\n");
+        }
+        output.push_str(include_str!("shaders/ray_hit_material.frag"));
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: self.uses_normal(),
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        if self.material.vertex_displacement_source().is_some() {
+            // The uniforms used by the vertex displacement
+            self.material.use_uniforms(program, camera, lights);
+        }
+        program.use_uniform("minDistance", 0.0f32);
+        program.use_uniform("maxDistance", self.max_distance);
+        program.use_uniform("eye", camera.position());
+        program.use_uniform("objectIndex", self.index as f32);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR_AND_DEPTH,
+            cull: self.material.render_states().cull,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+
+    fn vertex_displacement_source(&self) -> Option<String> {
+        self.material.vertex_displacement_source()
+    }
+}
@@ -0,0 +1,375 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+///
+/// A value of a uniform variable of a [ShaderMaterial].
+///
+#[derive(Clone)]
+pub enum ShaderUniform {
+    /// `uniform int`
+    Int(i32),
+    /// `uniform uint`
+    UnsignedInt(u32),
+    /// `uniform float`
+    Float(f32),
+    /// `uniform vec2`
+    Vec2(Vec2),
+    /// `uniform vec3`
+    Vec3(Vec3),
+    /// `uniform vec4`
+    Vec4(Vec4),
+    /// `uniform mat2`
+    Mat2(Mat2),
+    /// `uniform mat3`
+    Mat3(Mat3),
+    /// `uniform mat4`
+    Mat4(Mat4),
+    /// `uniform sampler2D`
+    Texture2D(Texture2DRef),
+    /// `uniform sampler2DArray`
+    Texture2DArray(Arc<Texture2DArray>),
+    /// `uniform sampler3D`
+    Texture3D(Arc<Texture3D>),
+    /// `uniform samplerCube`
+    TextureCubeMap(Arc<TextureCubeMap>),
+}
+
+macro_rules! impl_shader_uniform_from {
+    ($variant:ident, $type:ty) => {
+        impl From<$type> for ShaderUniform {
+            fn from(value: $type) -> Self {
+                Self::$variant(value)
+            }
+        }
+    };
+}
+
+impl_shader_uniform_from!(Int, i32);
+impl_shader_uniform_from!(UnsignedInt, u32);
+impl_shader_uniform_from!(Float, f32);
+impl_shader_uniform_from!(Vec2, Vec2);
+impl_shader_uniform_from!(Vec3, Vec3);
+impl_shader_uniform_from!(Vec4, Vec4);
+impl_shader_uniform_from!(Mat2, Mat2);
+impl_shader_uniform_from!(Mat3, Mat3);
+impl_shader_uniform_from!(Mat4, Mat4);
+impl_shader_uniform_from!(Texture2D, Texture2DRef);
+impl_shader_uniform_from!(Texture2DArray, Arc<Texture2DArray>);
+impl_shader_uniform_from!(Texture3D, Arc<Texture3D>);
+impl_shader_uniform_from!(TextureCubeMap, Arc<TextureCubeMap>);
+
+impl From<Srgba> for ShaderUniform {
+    fn from(value: Srgba) -> Self {
+        Self::Vec4(value.to_linear_srgb())
+    }
+}
+
+impl From<Arc<Texture2D>> for ShaderUniform {
+    fn from(value: Arc<Texture2D>) -> Self {
+        Self::Texture2D(Texture2DRef {
+            texture: value,
+            transformation: Mat3::identity(),
+            uv_channel: 0,
+        })
+    }
+}
+
+///
+/// A material defined by a custom fragment shader, which makes it possible to write a custom material without implementing the [Material] trait.
+///
+/// The fragment shader source must contain a `void main()` function which writes the color to `outColor`.
+/// The following is available to the fragment shader source, so it should not be declared:
+/// - `layout (location = 0) out vec4 outColor;`
+/// - The fragment attributes given by [ShaderMaterial::fragment_attributes], see [FragmentAttributes], for example `in vec3 pos;` and `in vec3 nor;`.
+///   If the uv coordinates are used, they can also be accessed with `texture_uvs(0.0)`.
+/// - `uniform vec3 cameraPosition;`, the position of the camera in world space.
+/// - `vec3 calculate_lighting(vec3 camera_position, vec3 surface_color, vec3 position, vec3 normal, float metallic, float roughness, float occlusion)`,
+///   which calculates the lighting from the lights given when rendering using the [ShaderMaterial::lighting_model], see [lights_shader_source].
+///   The lights are only used if the fragment shader source calls this function.
+/// - `vec3 tone_mapping(vec3 color)` and `vec3 color_mapping(vec3 color)`, which apply the [ToneMapping] and [ColorMapping] of the camera
+///   and should be applied to the final color unless rendering to an intermediate render target.
+///
+/// Other shader snippets, for example the built-in `"noise"` snippet or snippets registered using [register_shader_snippet], can be included
+/// in the fragment shader source and the vertex displacement source using `#include "name"`, see [preprocess_shader_source].
+/// Including the snippets that are already available, for example `"tone_mapping"`, has no effect.
+/// The `"shared"` snippet cannot be included in the vertex displacement source.
+///
+/// The vertices can also be displaced before they are transformed to world space by a function given in [ShaderMaterial::vertex_displacement_source],
+/// see [Material::vertex_displacement_source].
+///
+/// The uniforms in [ShaderMaterial::uniforms] are sent to the shader each time it is used.
+/// They need to be declared in the fragment shader source or the vertex displacement source, for example `uniform float time;`.
+///
+/// ```no_run
+/// # use three_d::*;
+/// let mut material = ShaderMaterial::new(
+///     "
+///     uniform vec4 surfaceColor;
+///     uniform float time;
+///     void main() {
+///         float stripes = step(0.5, fract(pos.y * 4.0 + time));
+///         vec3 color = calculate_lighting(cameraPosition, surfaceColor.rgb * stripes, pos, normalize(nor), 0.0, 0.5, 1.0);
+///         outColor = vec4(color_mapping(tone_mapping(color)), 1.0);
+///     }
+///     ",
+///     FragmentAttributes {
+///         position: true,
+///         normal: true,
+///         ..FragmentAttributes::NONE
+///     },
+/// );
+/// material.set_uniform("surfaceColor", Srgba::RED);
+/// material.set_uniform("time", 0.5);
+/// ```
+///
+#[derive(Clone)]
+pub struct ShaderMaterial {
+    /// The source code of the fragment shader, see [ShaderMaterial] for what is available to the source code.
+    pub fragment_source: String,
+    /// The fragment attributes, ie. the input from the vertex shader, which are available to the fragment shader.
+    pub fragment_attributes: FragmentAttributes,
+    /// The source code of a function which displaces the vertices, see [Material::vertex_displacement_source], or None if the vertices are not displaced.
+    pub vertex_displacement_source: Option<String>,
+    /// The uniforms sent to the shader, where the key is the name of the uniform variable in the shader source.
+    pub uniforms: HashMap<String, ShaderUniform>,
+    /// The lighting model used by the `calculate_lighting` function.
+    pub lighting_model: LightingModel,
+    /// Render states.
+    pub render_states: RenderStates,
+    /// Whether this material should be treated as a transparent material (An object needs to be rendered differently depending on whether it is transparent or opaque).
+    pub is_transparent: bool,
+}
+
+impl ShaderMaterial {
+    ///
+    /// Constructs a new opaque shader material from the given fragment shader source which uses the given fragment attributes.
+    ///
+    pub fn new(
+        fragment_source: impl Into<String>,
+        fragment_attributes: FragmentAttributes,
+    ) -> Self {
+        Self {
+            fragment_source: fragment_source.into(),
+            fragment_attributes,
+            vertex_displacement_source: None,
+            uniforms: HashMap::new(),
+            lighting_model: LightingModel::Blinn,
+            render_states: RenderStates::default(),
+            is_transparent: false,
+        }
+    }
+
+    ///
+    /// Constructs a new transparent shader material from the given fragment shader source which uses the given fragment attributes.
+    ///
+    pub fn new_transparent(
+        fragment_source: impl Into<String>,
+        fragment_attributes: FragmentAttributes,
+    ) -> Self {
+        Self {
+            render_states: RenderStates {
+                write_mask: WriteMask::COLOR,
+                blend: Blend::TRANSPARENCY,
+                ..Default::default()
+            },
+            is_transparent: true,
+            ..Self::new(fragment_source, fragment_attributes)
+        }
+    }
+
+    ///
+    /// Sets the value of the uniform with the given name, for example `material.set_uniform("time", 0.5)`.
+    ///
+    pub fn set_uniform(&mut self, name: &str, value: impl Into<ShaderUniform>) {
+        self.uniforms.insert(name.to_string(), value.into());
+    }
+
+    ///
+    /// Replaces the fragment shader source with the given source if it compiles, otherwise the current source is kept and the compile error is returned.
+    /// This makes it possible to change the source at runtime, for example while iterating on a custom material, without panicking when rendering with an invalid source.
+    /// The source is compiled without any lights, so errors caused by a specific combination of lights are not detected.
+    ///
+    pub fn try_set_fragment_source(
+        &mut self,
+        context: &Context,
+        fragment_source: impl Into<String>,
+    ) -> Result<(), RendererError> {
+        let fragment_source = fragment_source.into();
+        if fragment_source == self.fragment_source {
+            return Ok(());
+        }
+        let old_source = std::mem::replace(&mut self.fragment_source, fragment_source);
+        let mut missing = Vec::new();
+        self.preprocessed_fragment_source(&mut missing);
+        if let Some(name) = missing.into_iter().next() {
+            self.fragment_source = old_source;
+            return Err(RendererError::MissingShaderSnippet(name));
+        }
+        if let Err(e) = Program::from_source(
+            context,
+            VALIDATION_VERTEX_SHADER_SOURCE,
+            &self.fragment_shader_source(&[]),
+        ) {
+            self.fragment_source = old_source;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn preprocessed_fragment_source(&self, missing: &mut Vec<String>) -> String {
+        // The snippets which are already part of the fragment shader source, see fragment_shader_source
+        let mut included = ["shared", "tone_mapping", "color_mapping"]
+            .map(String::from)
+            .into_iter()
+            .collect::<HashSet<_>>();
+        if self.uses_lights() {
+            included.insert("lighting".to_string());
+        }
+        preprocess(&self.fragment_source, &[], &mut included, missing)
+    }
+
+    fn uses_lights(&self) -> bool {
+        self.fragment_source.contains("calculate_lighting")
+    }
+}
+
+/// A vertex shader which outputs all the fragment attributes, used to check that a fragment shader source compiles.
+const VALIDATION_VERTEX_SHADER_SOURCE: &str = "
+    out vec3 pos;
+    out vec3 nor;
+    out vec3 tang;
+    out vec3 bitang;
+    out vec2 uvs;
+    out vec2 uvs1;
+    flat out float texture_layer;
+    out vec4 col;
+    void main()
+    {
+        pos = vec3(0.0);
+        nor = vec3(0.0, 0.0, 1.0);
+        tang = vec3(1.0, 0.0, 0.0);
+        bitang = vec3(0.0, 1.0, 0.0);
+        uvs = vec2(0.0);
+        uvs1 = vec2(0.0);
+        texture_layer = 0.0;
+        col = vec4(1.0);
+        gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+    }
+";
+
+impl Material for ShaderMaterial {
+    fn id(&self) -> u16 {
+        // The shader source is identified by the extended id, so this id is only used by shader materials
+        0b1u16 << 15 | 0b1u16 << 14 | 0b1u16 << 13 | 0b1u16 << 12 | 0b1u16 << 11
+    }
+
+    fn extended_id(&self) -> Option<u64> {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.fragment_source.hash(&mut hasher);
+        lighting_model_shader(self.lighting_model).hash(&mut hasher);
+        let attributes = self.fragment_attributes;
+        [
+            attributes.position,
+            attributes.normal,
+            attributes.tangents,
+            attributes.uv,
+            attributes.color,
+        ]
+        .hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        let mut output = if self.uses_lights() {
+            lights_shader_source(lights, self.lighting_model)
+        } else {
+            include_str!("../../core/shared.frag").to_string()
+        };
+        let attributes = self.fragment_attributes;
+        if attributes.position {
+            output.push_str("in vec3 pos;\n");
+        }
+        if attributes.normal {
+            output.push_str("in vec3 nor;\n");
+        }
+        if attributes.tangents {
+            output.push_str("in vec3 tang;\nin vec3 bitang;\n");
+        }
+        if attributes.uv {
+            output.push_str(uvs_shader_source([None]));
+        }
+        if attributes.color {
+            output.push_str("in vec4 col;\n");
+        }
+        output.push_str("uniform vec3 cameraPosition;\nlayout (location = 0) out vec4 outColor;\n");
+        output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(&self.preprocessed_fragment_source(&mut Vec::new()));
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        self.fragment_attributes
+    }
+
+    fn vertex_displacement_source(&self) -> Option<String> {
+        self.vertex_displacement_source.as_ref().map(|source| {
+            let mut included = HashSet::from(["shared".to_string()]);
+            preprocess(source, &[], &mut included, &mut Vec::new())
+        })
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        if program.requires_uniform("toneMappingType") {
+            camera
+                .tone_mapping
+                .use_uniforms_with_exposure(program, camera.exposure);
+        }
+        if program.requires_uniform("ColorMappingType") {
+            camera
+                .color_mapping
+                .use_uniforms_with_gamma(program, camera.gamma);
+        }
+        program.use_uniform_if_required("cameraPosition", camera.position());
+        if self.uses_lights() {
+            for (i, light) in lights.iter().enumerate() {
+                light.use_uniforms(program, i as u32);
+            }
+        }
+        for (name, value) in self.uniforms.iter() {
+            if !program.requires_uniform(name) {
+                continue;
+            }
+            match value {
+                ShaderUniform::Int(v) => program.use_uniform(name, *v),
+                ShaderUniform::UnsignedInt(v) => program.use_uniform(name, *v),
+                ShaderUniform::Float(v) => program.use_uniform(name, *v),
+                ShaderUniform::Vec2(v) => program.use_uniform(name, *v),
+                ShaderUniform::Vec3(v) => program.use_uniform(name, *v),
+                ShaderUniform::Vec4(v) => program.use_uniform(name, *v),
+                ShaderUniform::Mat2(v) => program.use_uniform(name, *v),
+                ShaderUniform::Mat3(v) => program.use_uniform(name, *v),
+                ShaderUniform::Mat4(v) => program.use_uniform(name, *v),
+                ShaderUniform::Texture2D(texture) => program.use_texture(name, texture),
+                ShaderUniform::Texture2DArray(texture) => program.use_texture_array(name, texture),
+                ShaderUniform::Texture3D(texture) => program.use_texture_3d(name, texture),
+                ShaderUniform::TextureCubeMap(texture) => program.use_texture_cube(name, texture),
+            }
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        if self.is_transparent {
+            MaterialType::Transparent
+        } else {
+            MaterialType::Opaque
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::renderer::*;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+///
+/// Watches a fragment shader source file and reloads the source of a [ShaderMaterial] when the file changes,
+/// which makes it possible to iterate on a custom material without restarting the application.
+///
+/// The file is checked each time [ShaderWatcher::update] is called, typically once every frame.
+/// If the file could not be read or the new source does not compile, the error is given to the error callback
+/// and the material keeps the last source that compiled, so the application continues running.
+/// An error reading the file is only reported once until the file can be read again.
+///
+/// This is only meant for development since it reads from the file system, which is not available on web.
+///
+/// ```no_run
+/// # use three_d::*;
+/// # let context: Context = unimplemented!();
+/// let mut material = ShaderMaterial::new(
+///     "void main() { outColor = vec4(1.0); }",
+///     FragmentAttributes::NONE,
+/// );
+/// let mut watcher = ShaderWatcher::new("shaders/custom.frag", |error| {
+///     eprintln!("{}", error);
+/// });
+/// // Each frame:
+/// watcher.update(&context, &mut material);
+/// ```
+///
+pub struct ShaderWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    file_error: bool,
+    on_error: Box<dyn FnMut(RendererError)>,
+}
+
+impl ShaderWatcher {
+    ///
+    /// Creates a new watcher of the fragment shader source file at the given path.
+    /// The given callback is called with the error each time the file could not be read or the source does not compile.
+    ///
+    pub fn new(path: impl AsRef<Path>, on_error: impl FnMut(RendererError) + 'static) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            modified: None,
+            file_error: false,
+            on_error: Box::new(on_error),
+        }
+    }
+
+    ///
+    /// Returns the path of the watched file.
+    ///
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    ///
+    /// Sets the source of the given material to the content of the watched file if the file has changed since the last call,
+    /// or if this is the first call, and the content compiles, see [ShaderMaterial::try_set_fragment_source].
+    /// Returns whether the source of the material was changed.
+    ///
+    pub fn update(&mut self, context: &Context, material: &mut ShaderMaterial) -> bool {
+        let modified = match std::fs::metadata(&self.path).and_then(|m| m.modified()) {
+            Ok(modified) => modified,
+            Err(e) => {
+                self.report(e);
+                return false;
+            }
+        };
+        if self.modified == Some(modified) {
+            return false;
+        }
+        let source = match std::fs::read_to_string(&self.path) {
+            Ok(source) => source,
+            Err(e) => {
+                self.report(e);
+                return false;
+            }
+        };
+        self.file_error = false;
+        // The file is not checked again until it changes, also if the source does not compile
+        self.modified = Some(modified);
+        if source == material.fragment_source {
+            return false;
+        }
+        match material.try_set_fragment_source(context, source) {
+            Ok(()) => true,
+            Err(e) => {
+                (self.on_error)(e);
+                false
+            }
+        }
+    }
+
+    fn report(&mut self, error: std::io::Error) {
+        if self.file_error {
+            return;
+        }
+        self.file_error = true;
+        (self.on_error)(RendererError::ShaderSourceFile(
+            self.path.display().to_string(),
+            error,
+        ));
+    }
+}
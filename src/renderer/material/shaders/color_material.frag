@@ -3,6 +3,7 @@ uniform vec4 surfaceColor;
 #ifdef USE_TEXTURE
 uniform sampler2D tex;
 uniform mat3 textureTransformation;
+uniform float textureUvChannel;
 #endif
 
 in vec4 col;
@@ -14,7 +15,7 @@ void main()
     outColor = surfaceColor * col;
     
     #ifdef USE_TEXTURE
-    outColor *= texture(tex, (textureTransformation * vec3(uvs, 1.0)).xy);
+    outColor *= texture(tex, (textureTransformation * vec3(texture_uvs(textureUvChannel), 1.0)).xy);
     #endif
 
     outColor.rgb = color_mapping(outColor.rgb);
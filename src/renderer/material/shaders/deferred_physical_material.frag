@@ -6,28 +6,37 @@ uniform vec4 albedo;
 #ifdef USE_ALBEDO_TEXTURE
 uniform sampler2D albedoTexture;
 uniform mat3 albedoTexTransform;
+uniform float albedoUvChannel;
 #endif
 
 uniform vec4 emissive;
 #ifdef USE_EMISSIVE_TEXTURE
 uniform sampler2D emissiveTexture;
 uniform mat3 emissiveTexTransform;
+uniform float emissiveUvChannel;
 #endif
 
 #ifdef USE_METALLIC_ROUGHNESS_TEXTURE
 uniform sampler2D metallicRoughnessTexture;
 uniform mat3 metallicRoughnessTexTransform;
+uniform vec4 metallicChannel;
+uniform vec4 roughnessChannel;
+uniform float roughnessIsSmoothness;
+uniform float metallicRoughnessUvChannel;
 #endif
 
 #ifdef USE_OCCLUSION_TEXTURE
 uniform sampler2D occlusionTexture;
 uniform mat3 occlusionTexTransform;
+uniform vec4 occlusionChannel;
+uniform float occlusionUvChannel;
 uniform float occlusionStrength;
 #endif
 
 #ifdef USE_NORMAL_TEXTURE
 uniform sampler2D normalTexture;
 uniform mat3 normalTexTransform;
+uniform float normalUvChannel;
 uniform float normalScale;
 #endif
 
@@ -43,7 +52,7 @@ void main()
 {
     vec4 surface_color = albedo * col;
 #ifdef USE_ALBEDO_TEXTURE
-    vec4 c = texture(albedoTexture, (albedoTexTransform * vec3(uvs, 1.0)).xy);
+    vec4 c = texture(albedoTexture, (albedoTexTransform * vec3(texture_uvs(albedoUvChannel), 1.0)).xy);
     #ifdef ALPHACUT
         if (c.a < acut) discard;
     #endif
@@ -53,14 +62,15 @@ void main()
     float metallic_factor = metallic;
     float roughness_factor = roughness;
 #ifdef USE_METALLIC_ROUGHNESS_TEXTURE
-    vec2 t = texture(metallicRoughnessTexture, (metallicRoughnessTexTransform * vec3(uvs, 1.0)).xy).gb;
-    roughness_factor *= t.x;
-    metallic_factor *= t.y;
+    vec4 t = texture(metallicRoughnessTexture, (metallicRoughnessTexTransform * vec3(texture_uvs(metallicRoughnessUvChannel), 1.0)).xy);
+    float r = dot(t, roughnessChannel);
+    roughness_factor *= mix(r, 1.0 - r, roughnessIsSmoothness);
+    metallic_factor *= dot(t, metallicChannel);
 #endif
 
     float occlusion = 1.0;
 #ifdef USE_OCCLUSION_TEXTURE
-    occlusion = mix(1.0, texture(occlusionTexture, (occlusionTexTransform * vec3(uvs, 1.0)).xy).r, occlusionStrength);
+    occlusion = mix(1.0, dot(texture(occlusionTexture, (occlusionTexTransform * vec3(texture_uvs(occlusionUvChannel), 1.0)).xy), occlusionChannel), occlusionStrength);
 #endif
 
     vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
@@ -68,12 +78,12 @@ void main()
     vec3 tangent = normalize(gl_FrontFacing ? tang : -tang);
     vec3 bitangent = normalize(gl_FrontFacing ? bitang : -bitang);
     mat3 tbn = mat3(tangent, bitangent, normal);
-    normal = tbn * ((2.0 * texture(normalTexture, (normalTexTransform * vec3(uvs, 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
+    normal = tbn * ((2.0 * texture(normalTexture, (normalTexTransform * vec3(texture_uvs(normalUvChannel), 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
 #endif
 
     vec3 total_emissive = emissive.rgb;
 #ifdef USE_EMISSIVE_TEXTURE
-    total_emissive *= texture(emissiveTexture, (emissiveTexTransform * vec3(uvs, 1.0)).xy).rgb;
+    total_emissive *= texture(emissiveTexture, (emissiveTexTransform * vec3(texture_uvs(emissiveUvChannel), 1.0)).xy).rgb;
 #endif
 
     outColor = vec4(surface_color.rgb, metallic_factor);
@@ -0,0 +1,14 @@
+
+#ifdef USE_ALPHA_TEXTURE
+uniform sampler2D alphaTexture;
+uniform mat3 alphaTexTransform;
+uniform float alphaUvChannel;
+uniform float alphaCutout;
+#endif
+
+void main()
+{
+#ifdef USE_ALPHA_TEXTURE
+    if (texture(alphaTexture, (alphaTexTransform * vec3(texture_uvs(alphaUvChannel), 1.0)).xy).a < alphaCutout) discard;
+#endif
+}
@@ -0,0 +1,29 @@
+uniform vec4 albedo;
+uniform sampler2D lightmap;
+uniform mat3 lightmapTransformation;
+uniform float lightmapUvChannel;
+uniform float intensity;
+
+#ifdef USE_ALBEDO_TEXTURE
+uniform sampler2D albedoTexture;
+uniform mat3 albedoTexTransform;
+uniform float albedoUvChannel;
+#endif
+
+in vec4 col;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 surface_color = albedo * col;
+#ifdef USE_ALBEDO_TEXTURE
+    vec4 c = texture(albedoTexture, (albedoTexTransform * vec3(texture_uvs(albedoUvChannel), 1.0)).xy);
+    surface_color *= c;
+#endif
+
+    vec3 light = intensity * texture(lightmap, (lightmapTransformation * vec3(texture_uvs(lightmapUvChannel), 1.0)).xy).rgb;
+    outColor.rgb = tone_mapping(surface_color.rgb * light);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = surface_color.a;
+}
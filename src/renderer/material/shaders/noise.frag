@@ -0,0 +1,38 @@
+
+uniform uint noiseType;
+uniform uint seed;
+uniform float frequency;
+uniform int octaves;
+uniform float lacunarity;
+uniform float gain;
+uniform float layer;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    noise_seed = seed;
+    vec3 position = vec3(uvs, layer);
+    float f = frequency;
+    float amplitude = 1.0;
+    float sum = 0.0;
+    float total = 0.0;
+    for (int octave = 0; octave < octaves; octave++) {
+        vec3 p = position * f;
+        float n;
+        if (noiseType == 0u) {
+            n = 0.5 + 0.5 * perlin(p, vec3(f));
+        } else if (noiseType == 1u) {
+            n = 0.5 + 0.5 * simplex(p + vec3(float(octave) * 17.0));
+        } else {
+            n = worley(p, vec3(f));
+        }
+        sum += amplitude * n;
+        total += amplitude;
+        f *= lacunarity;
+        amplitude *= gain;
+    }
+    outColor = vec4(clamp(sum / max(total, 0.0001), 0.0, 1.0));
+}
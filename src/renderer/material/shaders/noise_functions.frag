@@ -0,0 +1,90 @@
+
+// The seed of the random values used by the noise functions below.
+uint noise_seed = 0u;
+
+// Random values in the range [0, 1) for each integer cell, see "Hash Functions for GPU Rendering" by Jarzynski and Olano.
+vec3 noise_random(vec3 cell)
+{
+    uvec3 v = uvec3(ivec3(cell) + 1048576) + noise_seed * uvec3(0x9E3779B9u, 0x85EBCA6Bu, 0xC2B2AE35u);
+    v = v * 1664525u + 1013904223u;
+    v.x += v.y * v.z;
+    v.y += v.z * v.x;
+    v.z += v.x * v.y;
+    v ^= v >> 16u;
+    v.x += v.y * v.z;
+    v.y += v.z * v.x;
+    v.z += v.x * v.y;
+    return vec3(v >> 8u) * (1.0 / 16777216.0);
+}
+
+vec3 noise_gradient(vec3 cell)
+{
+    vec3 g = noise_random(cell) * 2.0 - 1.0;
+    return g / max(length(g), 0.0001);
+}
+
+// The cell coordinates are wrapped by the period so that the noise tiles.
+float perlin(vec3 p, vec3 period)
+{
+    vec3 i = floor(p);
+    vec3 f = p - i;
+    vec3 u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    float n000 = dot(noise_gradient(floor(mod(i, period))), f);
+    float n100 = dot(noise_gradient(floor(mod(i + vec3(1.0, 0.0, 0.0), period))), f - vec3(1.0, 0.0, 0.0));
+    float n010 = dot(noise_gradient(floor(mod(i + vec3(0.0, 1.0, 0.0), period))), f - vec3(0.0, 1.0, 0.0));
+    float n110 = dot(noise_gradient(floor(mod(i + vec3(1.0, 1.0, 0.0), period))), f - vec3(1.0, 1.0, 0.0));
+    float n001 = dot(noise_gradient(floor(mod(i + vec3(0.0, 0.0, 1.0), period))), f - vec3(0.0, 0.0, 1.0));
+    float n101 = dot(noise_gradient(floor(mod(i + vec3(1.0, 0.0, 1.0), period))), f - vec3(1.0, 0.0, 1.0));
+    float n011 = dot(noise_gradient(floor(mod(i + vec3(0.0, 1.0, 1.0), period))), f - vec3(0.0, 1.0, 1.0));
+    float n111 = dot(noise_gradient(floor(mod(i + vec3(1.0, 1.0, 1.0), period))), f - vec3(1.0, 1.0, 1.0));
+    float n = mix(
+        mix(mix(n000, n100, u.x), mix(n010, n110, u.x), u.y),
+        mix(mix(n001, n101, u.x), mix(n011, n111, u.x), u.y),
+        u.z
+    );
+    // Scale from the range [-sqrt(3)/2, sqrt(3)/2] to [-1, 1]
+    return n * 1.1547;
+}
+
+float simplex(vec3 p)
+{
+    const float F3 = 1.0 / 3.0;
+    const float G3 = 1.0 / 6.0;
+    vec3 i = floor(p + dot(p, vec3(F3)));
+    vec3 x0 = p - i + dot(i, vec3(G3));
+    vec3 g = step(x0.yzx, x0.xyz);
+    vec3 l = 1.0 - g;
+    vec3 i1 = min(g, l.zxy);
+    vec3 i2 = max(g, l.zxy);
+    vec3 x1 = x0 - i1 + G3;
+    vec3 x2 = x0 - i2 + 2.0 * G3;
+    vec3 x3 = x0 - 1.0 + 3.0 * G3;
+    vec4 m = max(0.6 - vec4(dot(x0, x0), dot(x1, x1), dot(x2, x2), dot(x3, x3)), 0.0);
+    m = m * m;
+    m = m * m;
+    vec4 n = vec4(
+        dot(noise_gradient(i), x0),
+        dot(noise_gradient(i + i1), x1),
+        dot(noise_gradient(i + i2), x2),
+        dot(noise_gradient(i + 1.0), x3)
+    );
+    return 32.0 * dot(m, n);
+}
+
+// The distance to the closest feature point, where each cell contains one feature point.
+float worley(vec3 p, vec3 period)
+{
+    vec3 i = floor(p);
+    vec3 f = p - i;
+    float d = 1.0;
+    for (int z = -1; z <= 1; z++) {
+        for (int y = -1; y <= 1; y++) {
+            for (int x = -1; x <= 1; x++) {
+                vec3 offset = vec3(float(x), float(y), float(z));
+                vec3 point = offset + noise_random(floor(mod(i + offset, period)));
+                d = min(d, length(point - f));
+            }
+        }
+    }
+    return d;
+}
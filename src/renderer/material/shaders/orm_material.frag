@@ -5,11 +5,15 @@ uniform float roughness;
 #ifdef USE_METALLIC_ROUGHNESS_TEXTURE
 uniform sampler2D metallicRoughnessTexture;
 uniform mat3 metallicRoughnessTexTransform;
+uniform vec4 metallicChannel;
+uniform vec4 roughnessChannel;
+uniform float roughnessIsSmoothness;
 #endif
 
 #ifdef USE_OCCLUSION_TEXTURE
 uniform sampler2D occlusionTexture;
 uniform mat3 occlusionTexTransform;
+uniform vec4 occlusionChannel;
 uniform float occlusionStrength;
 #endif
 
@@ -20,14 +24,15 @@ void main()
     float metallic_factor = metallic;
     float roughness_factor = roughness;
 #ifdef USE_METALLIC_ROUGHNESS_TEXTURE
-    vec2 t = texture(metallicRoughnessTexture, (metallicRoughnessTexTransform * vec3(uvs, 1.0)).xy).gb;
-    roughness_factor *= t.x;
-    metallic_factor *= t.y;
+    vec4 t = texture(metallicRoughnessTexture, (metallicRoughnessTexTransform * vec3(uvs, 1.0)).xy);
+    float r = dot(t, roughnessChannel);
+    roughness_factor *= mix(r, 1.0 - r, roughnessIsSmoothness);
+    metallic_factor *= dot(t, metallicChannel);
 #endif
 
     float occlusion = 1.0;
 #ifdef USE_OCCLUSION_TEXTURE
-    occlusion = mix(1.0, texture(occlusionTexture, (occlusionTexTransform * vec3(uvs, 1.0)).xy).r, occlusionStrength);
+    occlusion = mix(1.0, dot(texture(occlusionTexture, (occlusionTexTransform * vec3(uvs, 1.0)).xy), occlusionChannel), occlusionStrength);
 #endif
 
     outColor = vec4(occlusion, roughness_factor, metallic_factor, 1.0);
@@ -1,37 +1,61 @@
 
 uniform float metallic;
 uniform float roughness;
-uniform vec3 cameraPosition;
 
 uniform vec4 albedo;
 #ifdef USE_ALBEDO_TEXTURE
 uniform sampler2D albedoTexture;
 uniform mat3 albedoTexTransform;
+uniform float albedoUvChannel;
 #endif
 
 uniform vec4 emissive;
 #ifdef USE_EMISSIVE_TEXTURE
 uniform sampler2D emissiveTexture;
 uniform mat3 emissiveTexTransform;
+uniform float emissiveUvChannel;
 #endif
 
 #ifdef USE_METALLIC_ROUGHNESS_TEXTURE
 uniform sampler2D metallicRoughnessTexture;
 uniform mat3 metallicRoughnessTexTransform;
+uniform vec4 metallicChannel;
+uniform vec4 roughnessChannel;
+uniform float roughnessIsSmoothness;
+uniform float metallicRoughnessUvChannel;
 #endif
 
 #ifdef USE_OCCLUSION_TEXTURE
 uniform sampler2D occlusionTexture;
 uniform mat3 occlusionTexTransform;
+uniform vec4 occlusionChannel;
+uniform float occlusionUvChannel;
 uniform float occlusionStrength;
 #endif
 
 #ifdef USE_NORMAL_TEXTURE
 uniform sampler2D normalTexture;
 uniform mat3 normalTexTransform;
+uniform float normalUvChannel;
 uniform float normalScale;
 #endif
 
+#ifdef USE_RIM
+uniform vec4 rimColor;
+uniform float rimPower;
+#endif
+
+#ifdef IRIDESCENCE
+uniform float iridescenceFactor;
+uniform float iridescenceIor;
+uniform vec2 iridescenceThickness;
+#ifdef USE_IRIDESCENCE_TEXTURE
+uniform sampler2D iridescenceTexture;
+uniform mat3 iridescenceTexTransform;
+uniform float iridescenceUvChannel;
+#endif
+#endif
+
 in vec3 pos;
 in vec3 nor;
 in vec4 col;
@@ -42,7 +66,7 @@ void main()
 {
     vec4 surface_color = albedo * col;
 #ifdef USE_ALBEDO_TEXTURE
-    vec4 c = texture(albedoTexture, (albedoTexTransform * vec3(uvs, 1.0)).xy);
+    vec4 c = texture(albedoTexture, (albedoTexTransform * vec3(texture_uvs(albedoUvChannel), 1.0)).xy);
     #ifdef ALPHACUT
         if (c.a < acut) discard;
     #endif
@@ -52,30 +76,65 @@ void main()
     float metallic_factor = metallic;
     float roughness_factor = roughness;
 #ifdef USE_METALLIC_ROUGHNESS_TEXTURE
-    vec2 t = texture(metallicRoughnessTexture, (metallicRoughnessTexTransform * vec3(uvs, 1.0)).xy).gb;
-    roughness_factor *= t.x;
-    metallic_factor *= t.y;
+    vec4 t = texture(metallicRoughnessTexture, (metallicRoughnessTexTransform * vec3(texture_uvs(metallicRoughnessUvChannel), 1.0)).xy);
+    float r = dot(t, roughnessChannel);
+    roughness_factor *= mix(r, 1.0 - r, roughnessIsSmoothness);
+    metallic_factor *= dot(t, metallicChannel);
 #endif
 
     float occlusion = 1.0;
 #ifdef USE_OCCLUSION_TEXTURE
-    occlusion = mix(1.0, texture(occlusionTexture, (occlusionTexTransform * vec3(uvs, 1.0)).xy).r, occlusionStrength);
+    occlusion = mix(1.0, dot(texture(occlusionTexture, (occlusionTexTransform * vec3(texture_uvs(occlusionUvChannel), 1.0)).xy), occlusionChannel), occlusionStrength);
 #endif
 
+#if !defined(UNLIT) || defined(USE_RIM)
     vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
-#ifdef USE_NORMAL_TEXTURE
+#endif
+#if defined(USE_NORMAL_TEXTURE) && !defined(UNLIT)
     vec3 tangent = normalize(gl_FrontFacing ? tang : -tang);
     vec3 bitangent = normalize(gl_FrontFacing ? bitang : -bitang);
     mat3 tbn = mat3(tangent, bitangent, normal);
-    normal = tbn * ((2.0 * texture(normalTexture, (normalTexTransform * vec3(uvs, 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
+    normal = tbn * ((2.0 * texture(normalTexture, (normalTexTransform * vec3(texture_uvs(normalUvChannel), 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
 #endif
 
     vec3 total_emissive = emissive.rgb;
 #ifdef USE_EMISSIVE_TEXTURE
-    total_emissive *= texture(emissiveTexture, (emissiveTexTransform * vec3(uvs, 1.0)).xy).rgb;
+    total_emissive *= texture(emissiveTexture, (emissiveTexTransform * vec3(texture_uvs(emissiveUvChannel), 1.0)).xy).rgb;
+#endif
+
+#ifdef USE_RIM
+    float rim = pow(1.0 - saturate(dot(normal, normalize(camera.position - pos))), rimPower);
+    total_emissive += rimColor.rgb * rim;
+#endif
+
+#ifdef IRIDESCENCE
+    iridescence_factor = iridescenceFactor;
+    iridescence_ior = iridescenceIor;
+    iridescence_thickness = iridescenceThickness.y;
+#ifdef USE_IRIDESCENCE_TEXTURE
+    vec2 i = texture(iridescenceTexture, (iridescenceTexTransform * vec3(texture_uvs(iridescenceUvChannel), 1.0)).xy).rg;
+    iridescence_factor *= i.x;
+    iridescence_thickness = mix(iridescenceThickness.x, iridescenceThickness.y, i.y);
+#endif
 #endif
 
-    outColor.rgb = total_emissive + calculate_lighting(cameraPosition, surface_color.rgb, pos, normal, metallic_factor, roughness_factor, occlusion);
+#ifdef UNLIT
+    outColor.rgb = total_emissive + surface_color.rgb;
+#else
+    outColor.rgb = total_emissive + calculate_lighting(camera.position, surface_color.rgb, pos, normal, metallic_factor, roughness_factor, occlusion);
+#ifdef REFLECTION_PLANE
+    vec4 plane_specular = reflection_plane_specular(surface_color.rgb, pos, normal, normalize(camera.position - pos), metallic_factor, roughness_factor);
+    outColor.rgb += occlusion * plane_specular.rgb;
+#ifdef REFLECTION_PROBE
+    outColor.rgb += occlusion * (1.0 - plane_specular.a) * reflection_probe_specular(surface_color.rgb, pos, normal, normalize(camera.position - pos), metallic_factor, roughness_factor);
+#endif
+#elif defined(REFLECTION_PROBE)
+    outColor.rgb += occlusion * reflection_probe_specular(surface_color.rgb, pos, normal, normalize(camera.position - pos), metallic_factor, roughness_factor);
+#endif
+#endif
+#ifdef USE_FOG
+    outColor.rgb = apply_fog(outColor.rgb, pos);
+#endif
     outColor.rgb = tone_mapping(outColor.rgb);
     outColor.rgb = color_mapping(outColor.rgb);
     outColor.a = surface_color.a;
@@ -0,0 +1,26 @@
+uniform vec3 eye;
+uniform float minDistance;
+uniform float maxDistance;
+uniform float objectIndex;
+
+in vec3 pos;
+#ifdef USE_NORMAL
+in vec3 nor;
+#endif
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float dist = (distance(pos, eye) - minDistance) / (maxDistance - minDistance);
+#ifdef USE_NORMAL
+    // Octahedral encoding of the normal
+    vec3 n = normalize(nor);
+    n /= abs(n.x) + abs(n.y) + abs(n.z);
+    vec2 e = n.z >= 0.0 ? n.xy : (1.0 - abs(n.yx)) * vec2(n.x >= 0.0 ? 1.0 : -1.0, n.y >= 0.0 ? 1.0 : -1.0);
+    outColor = vec4(dist, objectIndex, e);
+#else
+    // Outside the range of the octahedral encoding to signal that there is no normal
+    outColor = vec4(dist, objectIndex, 2.0, 2.0);
+#endif
+}
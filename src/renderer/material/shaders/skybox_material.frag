@@ -1,4 +1,7 @@
 uniform samplerCube texture0;
+uniform samplerCube nextTexture;
+uniform float crossfade;
+uniform mat3 rotation;
 uniform int isHDR;
 
 in vec3 coords;
@@ -6,7 +9,8 @@ in vec3 coords;
 layout (location = 0) out vec4 outColor;
 
 void main() {
-    outColor = vec4(texture(texture0, coords).rgb, 1.0);
+    vec3 direction = rotation * coords;
+    outColor = vec4(mix(texture(texture0, direction).rgb, texture(nextTexture, direction).rgb, crossfade), 1.0);
     outColor.rgb = tone_mapping(outColor.rgb);
     outColor.rgb = color_mapping(outColor.rgb);
 }
@@ -0,0 +1,40 @@
+
+uniform vec3 cameraPosition;
+uniform vec2 controlSize;
+uniform sampler2D controlTexture;
+uniform sampler2D layer0;
+uniform sampler2D layer1;
+uniform sampler2D layer2;
+uniform sampler2D layer3;
+uniform vec4 layerMask;
+uniform vec4 layerTiling;
+uniform vec4 layerMetallic;
+uniform vec4 layerRoughness;
+
+in vec3 pos;
+in vec3 nor;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // The texture rows are flipped when uploaded, so the first row is at v = 1.
+    vec2 half_texel = 0.5 / vec2(textureSize(controlTexture, 0));
+    vec2 control_uv = clamp(vec2(pos.x / controlSize.x, 1.0 - pos.z / controlSize.y), half_texel, 1.0 - half_texel);
+    vec4 weights = texture(controlTexture, control_uv) * layerMask;
+    float total = dot(weights, vec4(1.0));
+    weights = total > 0.0001 ? weights / total : vec4(1.0, 0.0, 0.0, 0.0);
+
+    vec3 surface_color = weights.x * texture(layer0, pos.xz * layerTiling.x).rgb
+        + weights.y * texture(layer1, pos.xz * layerTiling.y).rgb
+        + weights.z * texture(layer2, pos.xz * layerTiling.z).rgb
+        + weights.w * texture(layer3, pos.xz * layerTiling.w).rgb;
+    float metallic_factor = dot(weights, layerMetallic);
+    float roughness_factor = dot(weights, layerRoughness);
+
+    vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+    outColor.rgb = calculate_lighting(cameraPosition, surface_color, pos, normal, metallic_factor, roughness_factor, 1.0);
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = 1.0;
+}
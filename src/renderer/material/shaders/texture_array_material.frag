@@ -0,0 +1,14 @@
+uniform vec4 surfaceColor;
+uniform sampler2DArray tex;
+
+in vec2 uvs;
+in vec4 col;
+flat in float texture_layer;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    outColor = surfaceColor * col * texture(tex, vec3(uvs, texture_layer));
+    outColor.rgb = color_mapping(outColor.rgb);
+}
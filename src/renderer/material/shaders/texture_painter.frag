@@ -0,0 +1,19 @@
+
+uniform vec4 brushColor;
+uniform vec3 brushPosition;
+uniform float brushRadius;
+uniform float brushHardness;
+
+in vec3 pos;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float distance = length(pos - brushPosition) / brushRadius;
+    float alpha = brushColor.a * (1.0 - smoothstep(brushHardness, 1.0, distance));
+    if (alpha <= 0.0) {
+        discard;
+    }
+    outColor = vec4(brushColor.rgb, alpha);
+}
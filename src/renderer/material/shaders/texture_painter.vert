@@ -0,0 +1,16 @@
+
+uniform mat4 modelMatrix;
+
+in vec3 position;
+in vec2 uv_coordinates;
+
+out vec3 pos;
+
+void main()
+{
+    vec4 worldPosition = modelMatrix * vec4(position, 1.0);
+    pos = worldPosition.xyz / worldPosition.w;
+
+    // Each triangle is rasterized at its location in the texture
+    gl_Position = vec4(2.0 * uv_coordinates.x - 1.0, 1.0 - 2.0 * uv_coordinates.y, 0.0, 1.0);
+}
@@ -0,0 +1,39 @@
+uniform vec4 albedo;
+
+#ifdef USE_ALBEDO_TEXTURE
+uniform sampler2D albedoTexture;
+uniform mat3 albedoTexTransform;
+uniform float albedoUvChannel;
+#endif
+
+#ifdef ALPHACUT
+uniform float alphaCutout;
+#endif
+
+#ifdef USE_FOG
+in vec3 pos;
+#endif
+
+in vec4 col;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    outColor = albedo * col;
+
+#ifdef USE_ALBEDO_TEXTURE
+    outColor *= texture(albedoTexture, (albedoTexTransform * vec3(texture_uvs(albedoUvChannel), 1.0)).xy);
+#endif
+
+#ifdef ALPHACUT
+    if (outColor.a < alphaCutout) discard;
+#endif
+
+#ifdef USE_FOG
+    outColor.rgb = apply_fog(outColor.rgb, pos);
+#endif
+
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+}
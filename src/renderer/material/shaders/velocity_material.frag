@@ -0,0 +1,19 @@
+uniform mat4 currentViewProjection;
+uniform mat4 previousViewProjection;
+uniform mat4 motion;
+uniform vec2 resolution;
+
+in vec3 pos;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 current = currentViewProjection * vec4(pos, 1.0);
+    vec4 previous = previousViewProjection * motion * vec4(pos, 1.0);
+    vec2 velocity = vec2(0.0);
+    if (previous.w > 0.0) { // Otherwise the point was behind the camera in the previous frame
+        velocity = 0.5 * (current.xy / current.w - previous.xy / previous.w) * resolution;
+    }
+    outColor = vec4(velocity, 0.0, 1.0);
+}
@@ -0,0 +1,91 @@
+uniform vec3 cameraPosition;
+uniform vec3 size;
+uniform vec3 h;
+uniform int sampleCount;
+uniform sampler3D voxels;
+uniform int slicingPlaneCount;
+uniform vec4 slicingPlanes[MAX_SLICING_PLANES];
+
+#ifdef ISOSURFACE
+uniform vec4 surfaceColor;
+uniform float metallic;
+uniform float roughness;
+uniform float threshold;
+
+vec3 estimate_normal(vec3 uvw) {
+    float x = texture(voxels, uvw + vec3(h.x, 0.0, 0.0)).r - texture(voxels, uvw - vec3(h.x, 0.0, 0.0)).r;
+    float y = texture(voxels, uvw + vec3(0.0, h.y, 0.0)).r - texture(voxels, uvw - vec3(0.0, h.y, 0.0)).r;
+    float z = texture(voxels, uvw + vec3(0.0, 0.0, h.z)).r - texture(voxels, uvw - vec3(0.0, 0.0, h.z)).r;
+    return -normalize(vec3(x, y, z) / (2.0 * h));
+}
+#else
+uniform sampler2D transferFunction;
+#endif
+
+in vec3 pos;
+
+layout (location = 0) out vec4 outColor;
+
+bool is_sliced(vec3 position) {
+    for (int i = 0; i < slicingPlaneCount; i++) {
+        if (dot(slicingPlanes[i].xyz, position) + slicingPlanes[i].w > 0.0) {
+            return true;
+        }
+    }
+    return false;
+}
+
+void main() {
+    vec3 rayDir = normalize(pos - cameraPosition);
+
+    // Find the part of the ray inside the volume, which starts at the camera position if the camera is inside the volume
+    vec3 t0 = (-0.5 * size - cameraPosition) / rayDir;
+    vec3 t1 = (0.5 * size - cameraPosition) / rayDir;
+    vec3 tMin = min(t0, t1);
+    vec3 tMax = max(t0, t1);
+    float tNear = max(max(max(tMin.x, tMin.y), tMin.z), 0.0);
+    float tFar = min(min(tMax.x, tMax.y), tMax.z);
+
+    float stepSize = length(size) / float(sampleCount);
+    vec4 color = vec4(0.0);
+    for (int i = 0; i < sampleCount; i++) {
+        float t = tNear + (float(i) + 0.5) * stepSize;
+        if (t > tFar) {
+            break;
+        }
+        vec3 rayPos = cameraPosition + t * rayDir;
+        if (is_sliced(rayPos)) {
+            continue;
+        }
+        vec3 uvw = (rayPos / size) + 0.5;
+        float density = texture(voxels, uvw).r;
+#ifdef ISOSURFACE
+        if (density >= threshold) { // We hit the surface
+            vec3 normal = estimate_normal(uvw);
+            outColor.rgb = calculate_lighting(cameraPosition, surfaceColor.rgb, rayPos, normal, metallic, roughness, 1.0);
+            outColor.rgb = tone_mapping(outColor.rgb);
+            outColor.rgb = color_mapping(outColor.rgb);
+            outColor.a = surfaceColor.a;
+            return;
+        }
+#else
+        vec4 value = texture(transferFunction, vec2(density, 0.5));
+        // The opacity in the transfer function is the opacity of a sample the length of a voxel
+        float voxelSize = min(min(size.x * h.x, size.y * h.y), size.z * h.z);
+        float alpha = 1.0 - pow(1.0 - clamp(value.a, 0.0, 1.0), stepSize / voxelSize);
+        color.rgb += (1.0 - color.a) * alpha * value.rgb;
+        color.a += (1.0 - color.a) * alpha;
+        if (color.a > 0.99) {
+            break;
+        }
+#endif
+    }
+#ifdef ISOSURFACE
+    outColor = vec4(0.0);
+#else
+    outColor.rgb = color.a > 0.0 ? color.rgb / color.a : vec3(0.0);
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = color.a;
+#endif
+}
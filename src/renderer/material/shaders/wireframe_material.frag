@@ -0,0 +1,39 @@
+
+uniform vec4 lineColor;
+uniform float lineWidth;
+
+#ifdef USE_SURFACE
+uniform vec4 surfaceColor;
+uniform float metallic;
+uniform float roughness;
+uniform vec3 cameraPosition;
+
+in vec3 pos;
+in vec3 nor;
+#endif
+
+in vec4 col;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // The vertex colors contain the barycentric coordinates, so the distance to the closest edge in pixels is found using the screen space derivatives
+    vec3 distance = col.rgb / max(fwidth(col.rgb), vec3(0.00001));
+    float closest = min(min(distance.x, distance.y), distance.z);
+    float line = 1.0 - smoothstep(0.5 * lineWidth - 0.5, 0.5 * lineWidth + 0.5, closest);
+
+#ifdef USE_SURFACE
+    vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+    vec3 surface = calculate_lighting(cameraPosition, surfaceColor.rgb, pos, normal, metallic, roughness, 1.0);
+    surface = tone_mapping(surface);
+    outColor.rgb = mix(surface, lineColor.rgb, line * lineColor.a);
+    outColor.a = surfaceColor.a;
+#else
+    if (line < 0.5) {
+        discard;
+    }
+    outColor = lineColor;
+#endif
+    outColor.rgb = color_mapping(outColor.rgb);
+}
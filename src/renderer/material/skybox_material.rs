@@ -4,6 +4,9 @@ use std::sync::Arc;
 
 pub struct SkyboxMaterial {
     pub texture: Arc<TextureCubeMap>,
+    pub rotation: Mat3,
+    pub next_texture: Option<Arc<TextureCubeMap>>,
+    pub crossfade: f32,
 }
 
 impl Material for SkyboxMaterial {
@@ -26,9 +29,27 @@ impl Material for SkyboxMaterial {
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
-        camera.tone_mapping.use_uniforms(program);
-        camera.color_mapping.use_uniforms(program);
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
         program.use_texture_cube("texture0", &self.texture);
+        program.use_texture_cube(
+            "nextTexture",
+            self.next_texture.as_ref().unwrap_or(&self.texture),
+        );
+        program.use_uniform(
+            "crossfade",
+            if self.next_texture.is_some() {
+                self.crossfade.clamp(0.0, 1.0)
+            } else {
+                0.0
+            },
+        );
+        // The inverse rotation transforms a world direction to the direction in the cube map
+        program.use_uniform("rotation", self.rotation.transpose());
     }
 
     fn render_states(&self) -> RenderStates {
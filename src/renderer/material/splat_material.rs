@@ -0,0 +1,140 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A texture layer used by a [SplatMaterial].
+///
+#[derive(Clone)]
+pub struct SplatLayer {
+    /// The albedo texture of this layer, repeated across the surface.
+    pub albedo_texture: Texture2DRef,
+    /// The size in world space of one repetition of the albedo texture.
+    pub tiling: f32,
+    /// A value in the range `[0..1]` specifying how metallic this layer is.
+    pub metallic: f32,
+    /// A value in the range `[0..1]` specifying how rough this layer is.
+    pub roughness: f32,
+}
+
+impl SplatLayer {
+    ///
+    /// Creates a new non-metallic and fully rough splat layer from the given albedo texture which is repeated every `tiling` world space units.
+    ///
+    pub fn new(context: &Context, albedo_texture: &CpuTexture, tiling: f32) -> Self {
+        let albedo_texture = Texture2DRef::from_cpu_texture_with_color_space(
+            context,
+            albedo_texture,
+            ColorSpace::Srgb,
+        );
+        Self {
+            albedo_texture,
+            tiling,
+            metallic: 0.0,
+            roughness: 1.0,
+        }
+    }
+}
+
+///
+/// A material that blends up to four texture layers based on the weights in a control texture, also called texture splatting.
+/// The red, green, blue and alpha channels of the control texture are the weights of the first, second, third and fourth layer respectively.
+/// The control texture covers the area from origo to [SplatMaterial::size] in the xz-plane, where the columns of the texture are along the x-axis and the rows along the z-axis,
+/// which is the same mapping as used by [Terrain::new_from_height_texture].
+/// This material is affected by lights and is intended to be used with a [Terrain] or any other geometry that is mostly aligned with the xz-plane.
+///
+#[derive(Clone)]
+pub struct SplatMaterial {
+    /// The control texture which contains the weights of the layers.
+    pub control_texture: Texture2DRef,
+    /// The texture layers, at most four layers are used.
+    pub layers: Vec<SplatLayer>,
+    /// The size in world space of the area covered by the control texture.
+    pub size: Vec2,
+    /// The lighting model used when rendering this material
+    pub lighting_model: LightingModel,
+}
+
+impl SplatMaterial {
+    ///
+    /// Creates a new splat material from a control texture and up to four layers, where the control texture covers the area from origo to `size` in the xz-plane.
+    ///
+    pub fn new(
+        context: &Context,
+        control_texture: &CpuTexture,
+        layers: Vec<SplatLayer>,
+        size: Vec2,
+    ) -> Self {
+        Self {
+            control_texture: Texture2DRef::from_cpu_texture(context, control_texture),
+            layers,
+            size,
+            lighting_model: LightingModel::Blinn,
+        }
+    }
+}
+
+impl Material for SplatMaterial {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1001u16
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        let mut source = lights_shader_source(lights, self.lighting_model);
+        source.push_str(ToneMapping::fragment_shader_source());
+        source.push_str(ColorMapping::fragment_shader_source());
+        source.push_str(include_str!("shaders/splat_material.frag"));
+        source
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            normal: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+        program.use_uniform_if_required("cameraPosition", camera.position());
+        program.use_uniform("controlSize", self.size);
+        program.use_texture("controlTexture", &self.control_texture);
+        let mut mask = [0.0; 4];
+        let mut tiling = [1.0; 4];
+        let mut metallic = [0.0; 4];
+        let mut roughness = [1.0; 4];
+        for i in 0..4 {
+            // Unused layers are bound to the first layer texture and masked out.
+            let layer = self.layers.get(i).or(self.layers.first());
+            if let Some(layer) = layer {
+                if i < self.layers.len() {
+                    mask[i] = 1.0;
+                }
+                tiling[i] = 1.0 / layer.tiling.max(f32::EPSILON);
+                metallic[i] = layer.metallic;
+                roughness[i] = layer.roughness;
+                program.use_texture(&format!("layer{}", i), &layer.albedo_texture);
+            }
+        }
+        program.use_uniform("layerMask", Vec4::from(mask));
+        program.use_uniform("layerTiling", Vec4::from(tiling));
+        program.use_uniform_if_required("layerMetallic", Vec4::from(metallic));
+        program.use_uniform_if_required("layerRoughness", Vec4::from(roughness));
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
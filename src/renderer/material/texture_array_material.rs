@@ -0,0 +1,116 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// A material that renders a [Geometry] in a color defined by multiplying a color with a layer of a texture array and optional per vertex colors,
+/// where the layer is given by the geometry, for example per instance using [Instances::texture_layers].
+/// This makes it possible to render many differently textured instances of an [InstancedMesh] in one draw call without building a texture atlas.
+/// This material is not affected by lights.
+///
+#[derive(Clone)]
+pub struct TextureArrayMaterial {
+    /// Base surface color.
+    pub color: Srgba,
+    /// The texture array which is sampled using uv coordinates and the texture layer given by the [Geometry].
+    /// The colors are assumed to be in linear sRGB (`RgbU8`), linear sRGB with an alpha channel (`RgbaU8`) or HDR color space.
+    pub texture: Arc<Texture2DArray>,
+    /// Render states.
+    pub render_states: RenderStates,
+    /// Whether this material should be treated as a transparent material (An object needs to be rendered differently depending on whether it is transparent or opaque).
+    pub is_transparent: bool,
+}
+
+impl TextureArrayMaterial {
+    ///
+    /// Constructs a new opaque texture array material where each of the given textures is a layer in the texture array.
+    /// The textures must have the same size and format and the colors of `RgbU8` and `RgbaU8` textures are converted from sRGB to linear sRGB.
+    ///
+    pub fn new_opaque(context: &Context, cpu_textures: &[&CpuTexture]) -> Self {
+        Self {
+            color: Srgba::WHITE,
+            texture: Arc::new(texture_array(context, cpu_textures)),
+            render_states: RenderStates::default(),
+            is_transparent: false,
+        }
+    }
+
+    ///
+    /// Constructs a new transparent texture array material where each of the given textures is a layer in the texture array.
+    /// The textures must have the same size and format and the colors of `RgbU8` and `RgbaU8` textures are converted from sRGB to linear sRGB.
+    ///
+    pub fn new_transparent(context: &Context, cpu_textures: &[&CpuTexture]) -> Self {
+        Self {
+            color: Srgba::WHITE,
+            texture: Arc::new(texture_array(context, cpu_textures)),
+            render_states: RenderStates {
+                write_mask: WriteMask::COLOR,
+                blend: Blend::TRANSPARENCY,
+                ..Default::default()
+            },
+            is_transparent: true,
+        }
+    }
+}
+
+fn texture_array(context: &Context, cpu_textures: &[&CpuTexture]) -> Texture2DArray {
+    let cpu_textures = cpu_textures
+        .iter()
+        .map(|cpu_texture| {
+            let mut cpu_texture = (*cpu_texture).clone();
+            if let TextureData::RgbU8(_) | TextureData::RgbaU8(_) = cpu_texture.data {
+                cpu_texture.data.to_linear_srgb();
+            }
+            cpu_texture
+        })
+        .collect::<Vec<_>>();
+    Texture2DArray::new(context, &cpu_textures.iter().collect::<Vec<_>>())
+}
+
+impl Material for TextureArrayMaterial {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 12 | 0b10u16
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        let mut shader = String::new();
+        shader.push_str(ColorMapping::fragment_shader_source());
+        shader.push_str(include_str!("shaders/texture_array_material.frag"));
+        shader
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            color: true,
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        program.use_uniform("surfaceColor", self.color.to_linear_srgb());
+        program.use_texture_array("tex", &self.texture);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        if self.is_transparent {
+            MaterialType::Transparent
+        } else {
+            MaterialType::Opaque
+        }
+    }
+
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        Some(DepthPrePassMaterial {
+            cull: self.render_states.cull,
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,311 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// The brush used when painting with a [TexturePainter].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Brush {
+    /// The color of the brush. The alpha value is the opacity of the brush.
+    pub color: Srgba,
+    /// The radius of the brush in world space.
+    pub radius: f32,
+    /// A value in the range `[0..1]` specifying the size of the fully opaque center of the brush relative to the radius.
+    /// A value of 0 gives a soft brush which fades out from the center, a value of 1 gives a hard brush with a sharp edge.
+    pub hardness: f32,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            color: Srgba::BLACK,
+            radius: 0.1,
+            hardness: 0.5,
+        }
+    }
+}
+
+///
+/// Paints brush strokes onto the surface of a mesh, for example an annotation or a simple texturing tool.
+/// Each dab of the brush colors the surface within the radius of the brush around a position in world space,
+/// which is projected into the uv space of the mesh and accumulated into a texture. The mesh therefore has to be unwrapped
+/// so that each triangle has its own area of the texture.
+///
+/// The painted texture, see [TexturePainter::texture], is updated in place and can be used directly as for example the albedo texture
+/// of a [PhysicalMaterial] or a mask texture. Strokes can be undone and redone, see [TexturePainter::begin_stroke].
+///
+pub struct TexturePainter {
+    context: Context,
+    texture: Texture2DRef,
+    program: Program,
+    positions: VertexBuffer,
+    uvs: VertexBuffer,
+    transformation: Mat4,
+    undo_stack: Vec<Texture2DRef>,
+    redo_stack: Vec<Texture2DRef>,
+    /// The maximum number of strokes that can be undone.
+    pub max_undo_steps: usize,
+}
+
+impl TexturePainter {
+    ///
+    /// Creates a new texture painter which paints onto the given mesh and a texture with the given size which is initially filled with the given color.
+    ///
+    /// # Panic
+    /// Will panic if the mesh does not have uv coordinates.
+    ///
+    pub fn new(
+        context: &Context,
+        cpu_mesh: &CpuMesh,
+        width: u32,
+        height: u32,
+        color: Srgba,
+    ) -> Self {
+        let data = vec![[color.r, color.g, color.b, color.a]; (width * height) as usize];
+        Self::new_with_data(context, cpu_mesh, width, height, &data)
+    }
+
+    ///
+    /// Creates a new texture painter which paints onto the given mesh and a copy of the given texture, for example the albedo texture of the mesh.
+    /// 8-bit colors are assumed to be in sRGB color space and are kept in sRGB, since the painted texture is in [ColorSpace::Srgb].
+    ///
+    /// # Panic
+    /// Will panic if the mesh does not have uv coordinates or if the texture does not contain 8-bit RGB or RGBA colors.
+    ///
+    pub fn from_cpu_texture(
+        context: &Context,
+        cpu_mesh: &CpuMesh,
+        cpu_texture: &CpuTexture,
+    ) -> Self {
+        let data = match cpu_texture.data.clone() {
+            TextureData::RgbaU8(data) => data,
+            TextureData::RgbU8(data) => data.into_iter().map(|c| [c[0], c[1], c[2], 255]).collect(),
+            _ => panic!("a texture painter only supports textures with 8-bit RGB or RGBA colors"),
+        };
+        Self::new_with_data(
+            context,
+            cpu_mesh,
+            cpu_texture.width,
+            cpu_texture.height,
+            &data,
+        )
+    }
+
+    fn new_with_data(
+        context: &Context,
+        cpu_mesh: &CpuMesh,
+        width: u32,
+        height: u32,
+        data: &[[u8; 4]],
+    ) -> Self {
+        let uvs = cpu_mesh
+            .uvs
+            .as_ref()
+            .expect("a texture painter requires a mesh with uv coordinates");
+        let positions = cpu_mesh.positions.to_f32();
+        let mut vertices = Vec::with_capacity(3 * cpu_mesh.triangle_count());
+        cpu_mesh.for_each_triangle(|i0, i1, i2| vertices.extend([i0, i1, i2]));
+        let mut texture = Texture2D::new_empty_srgb(
+            context,
+            width,
+            height,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            Some(Interpolation::Linear),
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        texture.fill(data);
+        Self {
+            context: context.clone(),
+            texture: texture.into(),
+            program: Program::from_source(
+                context,
+                include_str!("shaders/texture_painter.vert"),
+                include_str!("shaders/texture_painter.frag"),
+            )
+            .expect("Failed compiling shader"),
+            positions: VertexBuffer::new_with_data(
+                context,
+                &vertices.iter().map(|v| positions[*v]).collect::<Vec<_>>(),
+            ),
+            uvs: VertexBuffer::new_with_data(
+                context,
+                &vertices.iter().map(|v| uvs[*v]).collect::<Vec<_>>(),
+            ),
+            transformation: Mat4::identity(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            max_undo_steps: 32,
+        }
+    }
+
+    ///
+    /// Returns the painted texture. The texture is updated in place when painting, so there is no need to call this method again after painting.
+    /// The texture is in [ColorSpace::Srgb], so the colors are converted to linear sRGB by the hardware when the texture is sampled.
+    ///
+    pub fn texture(&self) -> Texture2DRef {
+        self.texture.clone()
+    }
+
+    ///
+    /// Returns the painted texture as a [CpuTexture], for example to save it to disk.
+    /// The colors are in sRGB color space.
+    ///
+    pub fn to_cpu_texture(&self) -> CpuTexture {
+        CpuTexture {
+            name: "painted".to_owned(),
+            data: TextureData::RgbaU8(self.texture.as_shared_color_target(None).read()),
+            width: self.texture.width(),
+            height: self.texture.height(),
+            ..Default::default()
+        }
+    }
+
+    ///
+    /// Returns the local to world transformation of the mesh, which should be the same as the transformation used when rendering the mesh.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Sets the local to world transformation of the mesh, which should be the same as the transformation used when rendering the mesh.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+    }
+
+    ///
+    /// Starts a new stroke, ie. the current state of the texture is stored so that the dabs painted after this call can be undone with [TexturePainter::undo].
+    /// Call this for example when the mouse button is pressed and then call [TexturePainter::paint] each time the mouse is moved.
+    ///
+    pub fn begin_stroke(&mut self) {
+        let snapshot = Texture2DRef::from(self.copy_texture(&self.texture));
+        self.undo_stack.push(snapshot);
+        if self.undo_stack.len() > self.max_undo_steps {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    ///
+    /// Reverts the texture to the state before the last stroke. Returns false if there is nothing to undo.
+    ///
+    pub fn undo(&mut self) -> bool {
+        if let Some(snapshot) = self.undo_stack.pop() {
+            let current = Texture2DRef::from(self.copy_texture(&self.texture));
+            self.redo_stack.push(current);
+            self.restore(&snapshot);
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Paints the last undone stroke again. Returns false if there is nothing to redo.
+    ///
+    pub fn redo(&mut self) -> bool {
+        if let Some(snapshot) = self.redo_stack.pop() {
+            let current = Texture2DRef::from(self.copy_texture(&self.texture));
+            self.undo_stack.push(current);
+            self.restore(&snapshot);
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Paints a single dab of the given brush onto the surface of the mesh around the given position in world space.
+    /// All of the surface within the brush radius is painted, including surfaces that are not visible from the camera.
+    ///
+    pub fn paint(&mut self, position: Vec3, brush: &Brush) {
+        let viewport = Viewport::new_at_origo(self.texture.width(), self.texture.height());
+        self.texture
+            .as_shared_color_target(None)
+            .write::<RendererError>(|| {
+                let program = &self.program;
+                program.use_uniform("modelMatrix", self.transformation);
+                program.use_uniform("brushColor", brush.color.to_linear_srgb());
+                program.use_uniform("brushPosition", position);
+                program.use_uniform("brushRadius", brush.radius.max(0.0001));
+                program.use_uniform("brushHardness", brush.hardness.clamp(0.0, 0.999));
+                program.use_vertex_attribute("position", &self.positions);
+                program.use_vertex_attribute("uv_coordinates", &self.uvs);
+                program.draw_arrays(
+                    RenderStates {
+                        write_mask: WriteMask::COLOR,
+                        depth_test: DepthTest::Always,
+                        blend: Blend::TRANSPARENCY,
+                        cull: Cull::None,
+                    },
+                    viewport,
+                    self.positions.vertex_count(),
+                );
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    ///
+    /// Paints a single dab of the given brush at the position on the given geometries that is visible at the given pixel, see [pick].
+    /// The geometries should include the geometry corresponding to the painted mesh, but can also contain other geometries that block the brush.
+    /// Returns the picked position or `None` if no geometry is visible at the pixel.
+    ///
+    pub fn paint_at_pixel(
+        &mut self,
+        camera: &Camera,
+        pixel: impl Into<PhysicalPoint> + Copy,
+        geometries: impl IntoIterator<Item = impl Geometry>,
+        brush: &Brush,
+    ) -> Option<Vec3> {
+        let position = pick(&self.context, camera, pixel, geometries)?;
+        self.paint(position, brush);
+        Some(position)
+    }
+
+    fn copy_texture(&self, source: &Texture2DRef) -> Texture2D {
+        let mut texture = Texture2D::new_empty_srgb(
+            &self.context,
+            source.width(),
+            source.height(),
+            Interpolation::Linear,
+            Interpolation::Linear,
+            Some(Interpolation::Linear),
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        copy(&self.context, source, texture.as_color_target(None));
+        texture
+    }
+
+    fn restore(&self, snapshot: &Texture2DRef) {
+        copy(
+            &self.context,
+            snapshot,
+            self.texture.as_shared_color_target(None),
+        );
+    }
+}
+
+fn copy(context: &Context, source: &Texture2DRef, target: ColorTarget<'_>) {
+    let mut camera = Camera::new_2d(Viewport::new_at_origo(source.width(), source.height()));
+    camera.color_mapping = ColorMapping::None;
+    target
+        .write::<RendererError>(|| {
+            apply_screen_material(
+                context,
+                ColorMaterial {
+                    texture: Some(source.clone()),
+                    color: Srgba::WHITE,
+                    ..Default::default()
+                },
+                &camera,
+                &[],
+            );
+            Ok(())
+        })
+        .unwrap();
+}
@@ -0,0 +1,201 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A material that renders a [Geometry] in a color defined by multiplying a color with an optional texture and optional per vertex colors,
+/// as defined by the [KHR_materials_unlit](https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_materials_unlit/README.md) glTF extension,
+/// for example for user interface elements, stylized assets or assets where the lighting is baked into the textures.
+/// This material is not affected by lights, but unlike [ColorMaterial], the color is tone mapped in the same way as the lit materials
+/// and it supports [alpha cutout](UnlitMaterial::alpha_cutout).
+///
+#[derive(Clone)]
+pub struct UnlitMaterial {
+    /// Name.
+    pub name: String,
+    /// Base surface color.
+    pub albedo: Srgba,
+    /// An optional texture which is multiplied with the [UnlitMaterial::albedo] color (requires that the [Geometry] supports uv coordinates).
+    /// The constructors taking a [CpuMaterial] create it in [ColorSpace::Srgb], so the hardware converts the colors to linear sRGB when sampling.
+    /// Other textures are assumed to be in linear sRGB or HDR color space.
+    pub albedo_texture: Option<Texture2DRef>,
+    /// A threshold on the alpha value below which the fragments are discarded, for example for foliage or fences.
+    pub alpha_cutout: Option<f32>,
+    /// Render states.
+    pub render_states: RenderStates,
+    /// Whether this material should be treated as a transparent material (An object needs to be rendered differently depending on whether it is transparent or opaque).
+    pub is_transparent: bool,
+    /// Whether the [Camera::fog] is applied to this material. The default is true.
+    /// The fog is compiled into the shader whenever this is true, also when the camera has no fog, since the shader does not depend on the camera,
+    /// so set this to false if the material is never rendered with fog.
+    pub fog: bool,
+}
+
+impl UnlitMaterial {
+    ///
+    /// Constructs a new unlit material from a [CpuMaterial], where only the albedo color, albedo texture and alpha cutout are used.
+    /// Tries to infer whether this material is transparent or opaque from the alpha value of the albedo color and the alpha values in the albedo texture.
+    /// Since this is not always correct, it is preferred to use [UnlitMaterial::new_opaque] or [UnlitMaterial::new_transparent].
+    ///
+    pub fn new(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        if cpu_material.alpha_cutout.is_none() && super::is_transparent(cpu_material) {
+            Self::new_transparent(context, cpu_material)
+        } else {
+            Self::new_opaque(context, cpu_material)
+        }
+    }
+
+    /// Constructs a new opaque unlit material from a [CpuMaterial].
+    pub fn new_opaque(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        Self::new_internal(context, cpu_material, false)
+    }
+
+    /// Constructs a new transparent unlit material from a [CpuMaterial].
+    pub fn new_transparent(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        Self::new_internal(context, cpu_material, true)
+    }
+
+    fn new_internal(context: &Context, cpu_material: &CpuMaterial, is_transparent: bool) -> Self {
+        let albedo_texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Texture2DRef::from_cpu_texture_with_color_space(context, cpu_texture, ColorSpace::Srgb)
+        });
+        Self {
+            name: cpu_material.name.clone(),
+            albedo: cpu_material.albedo,
+            albedo_texture,
+            alpha_cutout: cpu_material.alpha_cutout,
+            render_states: if is_transparent {
+                RenderStates {
+                    write_mask: WriteMask::COLOR,
+                    blend: Blend::TRANSPARENCY,
+                    ..Default::default()
+                }
+            } else {
+                RenderStates::default()
+            },
+            is_transparent,
+            fog: true,
+        }
+    }
+
+    /// Creates an unlit material from a [PhysicalMaterial].
+    pub fn from_physical_material(physical_material: &PhysicalMaterial) -> Self {
+        Self {
+            name: physical_material.name.clone(),
+            albedo: physical_material.albedo,
+            albedo_texture: physical_material.albedo_texture.clone(),
+            alpha_cutout: None,
+            render_states: physical_material.render_states,
+            is_transparent: physical_material.is_transparent,
+            fog: physical_material.fog,
+        }
+    }
+}
+
+impl Default for UnlitMaterial {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            albedo: Srgba::default(),
+            albedo_texture: None,
+            alpha_cutout: None,
+            render_states: RenderStates::default(),
+            is_transparent: false,
+            fog: true,
+        }
+    }
+}
+
+impl FromCpuMaterial for UnlitMaterial {
+    fn from_cpu_material(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        Self::new(context, cpu_material)
+    }
+}
+
+impl Material for UnlitMaterial {
+    fn id(&self) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 10;
+        if self.albedo_texture.is_some() {
+            id |= 0b1u16;
+        }
+        if self.alpha_cutout.is_some() {
+            id |= 0b1u16 << 1;
+        }
+        if uses_secondary_uvs([self.albedo_texture.as_ref()]) {
+            id |= 0b1u16 << 9;
+        }
+        if self.fog {
+            id |= 0b1u16 << 2;
+        }
+        id
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        let mut shader = String::new();
+        if self.albedo_texture.is_some() {
+            shader.push_str("#define USE_ALBEDO_TEXTURE\n");
+            shader.push_str(uvs_shader_source([self.albedo_texture.as_ref()]));
+        }
+        if self.alpha_cutout.is_some() {
+            shader.push_str("#define ALPHACUT\n");
+        }
+        if self.fog {
+            shader.push_str("#define USE_FOG\n");
+            shader.push_str(Fog::fragment_shader_source());
+        }
+        shader.push_str(include_str!("../../core/shared.frag"));
+        shader.push_str(ToneMapping::fragment_shader_source());
+        shader.push_str(ColorMapping::fragment_shader_source());
+        shader.push_str(include_str!("shaders/unlit_material.frag"));
+        shader
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: self.fog,
+            color: true,
+            uv: self.albedo_texture.is_some(),
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        if self.fog {
+            camera.fog.use_uniforms(program, *camera.position());
+        }
+        program.use_uniform("albedo", self.albedo.to_linear_srgb());
+        if let Some(ref texture) = self.albedo_texture {
+            program.use_uniform("albedoTexTransform", texture.transformation);
+            program.use_uniform_if_required("albedoUvChannel", texture.uv_channel as f32);
+            program.use_texture("albedoTexture", texture);
+        }
+        if let Some(alpha_cutout) = self.alpha_cutout {
+            program.use_uniform("alphaCutout", alpha_cutout);
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        if self.is_transparent {
+            MaterialType::Transparent
+        } else {
+            MaterialType::Opaque
+        }
+    }
+
+    fn depth_pre_pass_material(&self) -> Option<DepthPrePassMaterial> {
+        Some(DepthPrePassMaterial {
+            alpha_cutout: self.alpha_cutout,
+            alpha_texture: self.albedo_texture.clone(),
+            cull: self.render_states.cull,
+        })
+    }
+}
@@ -0,0 +1,71 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Render the object with colors that reflect the screen space motion of each pixel since the previous frame, also known as motion vectors,
+/// for example to use as optical flow ground truth. The red and green channels contain the motion in pixels along the x- and y-axis
+/// of the viewport, where the y-axis points up, ie. the current pixel position minus the pixel position of the same point in the previous frame.
+/// The motion is calculated without the sub-pixel jitter of the camera (see [Camera::set_jitter]).
+///
+/// The motion of the object is given by [VelocityMaterial::motion] which means that only rigid motion is taken into account
+/// and not for example skinning or morph target animations. Use [MotionVectors] to keep track of the previous transformations of a set of objects.
+///
+#[derive(Clone)]
+pub struct VelocityMaterial {
+    /// The view projection matrix (without jitter) of the camera in the previous frame.
+    pub previous_view_projection: Mat4,
+    /// Transforms a position in world space in this frame to the world space position of the same point on the object in the previous frame,
+    /// ie. the previous transformation of the object multiplied by the inverse of the current transformation.
+    pub motion: Mat4,
+    /// Render states.
+    pub render_states: RenderStates,
+}
+
+impl Default for VelocityMaterial {
+    fn default() -> Self {
+        Self {
+            previous_view_projection: Mat4::identity(),
+            motion: Mat4::identity(),
+            render_states: RenderStates::default(),
+        }
+    }
+}
+
+impl Material for VelocityMaterial {
+    fn id(&self) -> u16 {
+        0b1u16 << 15 | 0b1u16 << 12 | 0b11u16
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        include_str!("shaders/velocity_material.frag").to_string()
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_uniform(
+            "currentViewProjection",
+            camera.projection_without_jitter() * camera.view(),
+        );
+        program.use_uniform("previousViewProjection", self.previous_view_projection);
+        program.use_uniform("motion", self.motion);
+        let viewport = camera.viewport();
+        program.use_uniform(
+            "resolution",
+            vec2(viewport.width as f32, viewport.height as f32),
+        );
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
@@ -0,0 +1,224 @@
+use crate::core::*;
+use crate::renderer::*;
+
+/// The maximum number of [SlicingPlane]s of a [VolumeMaterial].
+pub const MAX_SLICING_PLANES: usize = 8;
+
+///
+/// Defines how a [VolumeMaterial] renders the voxel data.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VolumeRenderMode {
+    /// Accumulates the color and opacity given by the [VolumeMaterial::transfer_function] for the density of each sample along the view ray,
+    /// for example to show the different tissues in a medical scan at the same time. The volume is not affected by lights.
+    DirectVolumeRendering,
+    /// Renders the surface where the density is equal to the threshold (in the range `[0..1]`) with the given color, like [IsosurfaceMaterial].
+    /// The surface is lit by the lights using the [VolumeMaterial::metallic], [VolumeMaterial::roughness] and [VolumeMaterial::lighting_model].
+    Isosurface {
+        /// Threshold (in the range `[0..1]`) that defines the surface in the voxel data.
+        threshold: f32,
+        /// The color of the surface.
+        color: Srgba,
+    },
+}
+
+///
+/// A plane which cuts away the part of a volume on the side of the plane that the normal points to, for example to look at a cross section of a medical scan.
+/// The plane is defined in the same space as the volume, ie. a cube with center in origo and the size [VolumeMaterial::size].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SlicingPlane {
+    /// A point on the plane.
+    pub point: Vec3,
+    /// The normal of the plane, which points towards the part of the volume which is cut away.
+    pub normal: Vec3,
+}
+
+///
+/// A material that renders the density given by the red channel of the voxel data in [VolumeMaterial::voxels] by raymarching through the volume,
+/// either by accumulating the color and opacity given by a transfer function or as an isosurface, see [VolumeRenderMode].
+/// Parts of the volume can be cut away using [VolumeMaterial::slicing_planes].
+/// This material should be applied to a cube with center in origo and the size [VolumeMaterial::size], for example using a [VolumeObject].
+///
+#[derive(Clone)]
+pub struct VolumeMaterial {
+    /// The voxel data where the red channel is the density.
+    pub voxels: std::sync::Arc<Texture3D>,
+    /// A texture where the color at the texture coordinate `(density, 0.5)` is the linear color and opacity of a density, for example constructed
+    /// with [VolumeMaterial::transfer_function_from_control_points]. The opacity is the opacity of a sample the length of a voxel.
+    pub transfer_function: std::sync::Arc<Texture2D>,
+    /// How the voxel data is rendered.
+    pub mode: VolumeRenderMode,
+    /// The planes which cuts away parts of the volume. At most [MAX_SLICING_PLANES] planes are used.
+    pub slicing_planes: Vec<SlicingPlane>,
+    /// The number of samples along a ray through the diagonal of the volume. More samples gives a more accurate result but is slower.
+    pub sample_count: u32,
+    /// A value in the range `[0..1]` specifying how metallic the isosurface is.
+    pub metallic: f32,
+    /// A value in the range `[0..1]` specifying how rough the isosurface is.
+    pub roughness: f32,
+    /// The size of the cube that is used to render the voxel data. The texture is scaled to fill the entire cube.
+    pub size: Vec3,
+    /// The lighting model used when rendering the isosurface.
+    pub lighting_model: LightingModel,
+}
+
+impl VolumeMaterial {
+    ///
+    /// Constructs the [CpuTexture] of a transfer function, see [VolumeMaterial::transfer_function], from control points consisting of a density
+    /// in the range `[0..1]` and the color and opacity of that density. The colors and opacities are linearly interpolated between the control points
+    /// and the control points must be sorted by density.
+    ///
+    pub fn transfer_function_from_control_points(control_points: &[(f32, Srgba)]) -> CpuTexture {
+        const WIDTH: u32 = 256;
+        let data = (0..WIDTH)
+            .map(|i| {
+                let density = i as f32 / (WIDTH - 1) as f32;
+                let next = control_points
+                    .iter()
+                    .position(|(d, _)| *d > density)
+                    .unwrap_or(control_points.len());
+                let color = match (next.checked_sub(1), control_points.get(next)) {
+                    (Some(i), Some((d1, c1))) => {
+                        let (d0, c0) = control_points[i];
+                        let t = (density - d0) / (d1 - d0);
+                        c0.to_linear_srgb() * (1.0 - t) + c1.to_linear_srgb() * t
+                    }
+                    (Some(i), None) => control_points[i].1.to_linear_srgb(),
+                    (None, Some((_, c))) => c.to_linear_srgb(),
+                    (None, None) => Vec4::zero(),
+                };
+                [
+                    f16::from_f32(color.x),
+                    f16::from_f32(color.y),
+                    f16::from_f32(color.z),
+                    f16::from_f32(color.w),
+                ]
+            })
+            .collect();
+        CpuTexture {
+            name: "transfer function".to_string(),
+            data: TextureData::RgbaF16(data),
+            width: WIDTH,
+            height: 1,
+            mip_map_filter: None,
+            wrap_s: Wrapping::ClampToEdge,
+            wrap_t: Wrapping::ClampToEdge,
+            ..Default::default()
+        }
+    }
+}
+
+impl Material for VolumeMaterial {
+    fn id(&self) -> u16 {
+        match self.mode {
+            VolumeRenderMode::DirectVolumeRendering => 0b1u16 << 15 | 0b1u16 << 12,
+            VolumeRenderMode::Isosurface { .. } => 0b1u16 << 15 | 0b1u16 << 12 | 0b1u16,
+        }
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        let mut source = String::new();
+        if let VolumeRenderMode::Isosurface { .. } = self.mode {
+            source.push_str("#define ISOSURFACE\n");
+            source.push_str(&lights_shader_source(lights, self.lighting_model));
+        }
+        source.push_str(&format!(
+            "#define MAX_SLICING_PLANES {}\n",
+            MAX_SLICING_PLANES
+        ));
+        source.push_str(ToneMapping::fragment_shader_source());
+        source.push_str(ColorMapping::fragment_shader_source());
+        source.push_str(include_str!("shaders/volume_material.frag"));
+        source
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        program.use_uniform("cameraPosition", camera.position());
+        program.use_uniform("size", self.size);
+        program.use_uniform(
+            "h",
+            vec3(
+                1.0 / self.voxels.width() as f32,
+                1.0 / self.voxels.height() as f32,
+                1.0 / self.voxels.depth() as f32,
+            ),
+        );
+        program.use_uniform("sampleCount", self.sample_count.max(1) as i32);
+        program.use_texture_3d("voxels", &self.voxels);
+
+        let slicing_planes = self
+            .slicing_planes
+            .iter()
+            .take(MAX_SLICING_PLANES)
+            .map(|plane| {
+                let normal = plane.normal.normalize();
+                normal.extend(-normal.dot(plane.point))
+            })
+            .collect::<Vec<_>>();
+        program.use_uniform("slicingPlaneCount", slicing_planes.len() as i32);
+        if !slicing_planes.is_empty() {
+            program.use_uniform_array("slicingPlanes", &slicing_planes);
+        }
+
+        match self.mode {
+            VolumeRenderMode::DirectVolumeRendering => {
+                program.use_texture("transferFunction", &self.transfer_function);
+            }
+            VolumeRenderMode::Isosurface { threshold, color } => {
+                for (i, light) in lights.iter().enumerate() {
+                    light.use_uniforms(program, i as u32);
+                }
+                program.use_uniform("threshold", threshold);
+                program.use_uniform("surfaceColor", color.to_linear_srgb());
+                program.use_uniform("metallic", self.metallic);
+                program.use_uniform_if_required("roughness", self.roughness);
+            }
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::Front,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+impl FromCpuVoxelGrid for VolumeMaterial {
+    fn from_cpu_voxel_grid(context: &Context, cpu_voxel_grid: &CpuVoxelGrid) -> Self {
+        let transfer_function = Self::transfer_function_from_control_points(&[
+            (0.0, Srgba::new(0, 0, 0, 0)),
+            (1.0, Srgba::WHITE),
+        ]);
+        Self {
+            voxels: std::sync::Arc::new(Texture3D::new(context, &cpu_voxel_grid.voxels)),
+            transfer_function: std::sync::Arc::new(Texture2D::new(context, &transfer_function)),
+            mode: VolumeRenderMode::DirectVolumeRendering,
+            slicing_planes: Vec::new(),
+            sample_count: 256,
+            lighting_model: LightingModel::Blinn,
+            size: cpu_voxel_grid.size,
+            roughness: 1.0,
+            metallic: 0.0,
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Renders the edges of the triangles of a [Geometry] as lines in a single pass, either as wireframe only or on top of a shaded surface.
+/// This is a lot cheaper than building geometry for each edge because the lines are computed in the fragment shader from barycentric coordinates.
+///
+/// The barycentric coordinates are stored in the vertex colors, so the geometry has to be constructed from a [CpuMesh] returned by [WireframeMaterial::prepare_mesh].
+/// This also means that any vertex or instance colors of the geometry are not used by this material.
+///
+#[derive(Clone)]
+pub struct WireframeMaterial {
+    /// The color of the lines.
+    pub line_color: Srgba,
+    /// The width of the lines in physical pixels.
+    pub line_width: f32,
+    /// The color of the surface between the lines. If `None`, only the lines are rendered, otherwise the surface is shaded using the lights.
+    pub surface_color: Option<Srgba>,
+    /// A value in the range `[0..1]` specifying how metallic the surface is.
+    pub metallic: f32,
+    /// A value in the range `[0..1]` specifying how rough the surface is.
+    pub roughness: f32,
+    /// The lighting model used when rendering the surface.
+    pub lighting_model: LightingModel,
+    /// Render states. Defaults to rendering both sides of the triangles, so the lines on the back side are also visible when only the lines are rendered.
+    pub render_states: RenderStates,
+}
+
+impl WireframeMaterial {
+    ///
+    /// Returns a copy of the given mesh where each triangle has its own three vertices with the barycentric coordinates of the triangle corners stored in the vertex colors,
+    /// which is needed for rendering with a wireframe material.
+    /// Positions, normals, tangents and uv coordinates are kept, so the same mesh can also be used for shading the surface.
+    ///
+    pub fn prepare_mesh(cpu_mesh: &CpuMesh) -> CpuMesh {
+        let mut vertices = Vec::with_capacity(3 * cpu_mesh.triangle_count());
+        cpu_mesh.for_each_triangle(|i0, i1, i2| vertices.extend([i0, i1, i2]));
+        let positions = cpu_mesh.positions.to_f32();
+        CpuMesh {
+            positions: Positions::F32(vertices.iter().map(|v| positions[*v]).collect()),
+            indices: Indices::None,
+            normals: cpu_mesh
+                .normals
+                .as_ref()
+                .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+            tangents: cpu_mesh
+                .tangents
+                .as_ref()
+                .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+            uvs: cpu_mesh
+                .uvs
+                .as_ref()
+                .map(|d| vertices.iter().map(|v| d[*v]).collect()),
+            colors: Some(
+                (0..vertices.len())
+                    .map(|i| match i % 3 {
+                        0 => Srgba::new(255, 0, 0, 255),
+                        1 => Srgba::new(0, 255, 0, 255),
+                        _ => Srgba::new(0, 0, 255, 255),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Default for WireframeMaterial {
+    fn default() -> Self {
+        Self {
+            line_color: Srgba::BLACK,
+            line_width: 1.0,
+            surface_color: None,
+            metallic: 0.0,
+            roughness: 1.0,
+            lighting_model: LightingModel::Blinn,
+            render_states: RenderStates {
+                cull: Cull::None,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl FromCpuMaterial for WireframeMaterial {
+    fn from_cpu_material(_context: &Context, cpu_material: &CpuMaterial) -> Self {
+        Self {
+            surface_color: Some(cpu_material.albedo),
+            metallic: cpu_material.metallic,
+            roughness: cpu_material.roughness,
+            lighting_model: cpu_material.lighting_model,
+            ..Default::default()
+        }
+    }
+}
+
+impl Material for WireframeMaterial {
+    fn id(&self) -> u16 {
+        if self.surface_color.is_some() {
+            0b1u16 << 15 | 0b1011u16
+        } else {
+            0b1u16 << 15 | 0b1010u16
+        }
+    }
+
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        let mut output = String::new();
+        if self.surface_color.is_some() {
+            output.push_str("#define USE_SURFACE\n");
+            output.push_str(&lights_shader_source(lights, self.lighting_model));
+            output.push_str(ToneMapping::fragment_shader_source());
+        } else {
+            output.push_str(include_str!("../../core/shared.frag"));
+        }
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(include_str!("shaders/wireframe_material.frag"));
+        output
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: self.surface_color.is_some(),
+            normal: self.surface_color.is_some(),
+            color: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        program.use_uniform("lineColor", self.line_color.to_linear_srgb());
+        program.use_uniform("lineWidth", self.line_width);
+        if let Some(surface_color) = self.surface_color {
+            camera
+                .tone_mapping
+                .use_uniforms_with_exposure(program, camera.exposure);
+            program.use_uniform("surfaceColor", surface_color.to_linear_srgb());
+            if !lights.is_empty() {
+                program.use_uniform_if_required("cameraPosition", camera.position());
+                for (i, light) in lights.iter().enumerate() {
+                    light.use_uniforms(program, i as u32);
+                }
+                program.use_uniform_if_required("metallic", self.metallic);
+                program.use_uniform_if_required("roughness", self.roughness);
+            }
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
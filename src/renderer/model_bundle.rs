@@ -0,0 +1,664 @@
+use crate::renderer::*;
+use three_d_asset::{
+    GeometryFunction, LightingModel, NormalDistributionFunction, PbrMaterial, Primitive,
+};
+
+/// The bytes at the start of a `.3d` file.
+const MAGIC: &[u8; 8] = b"THREE-D\0";
+/// The version of the `.3d` format, which is increased each time the format changes.
+const VERSION: u32 = 1;
+
+///
+/// Serializes the given [CpuModel] into the `.3d` format, which is a binary format containing the data of the model as it is represented in memory,
+/// including the generated levels of detail and imposter texture, see [generate_lod_levels] and [generate_imposter_texture], and the decoded textures.
+/// Loading a model in this format is therefore fast, since nothing has to be parsed, decoded or generated, which makes it suitable for shipping the assets
+/// of an application, for example produced offline by the `three-d-assetc` binary. Use [deserialize_model] to get the model back.
+///
+/// ```no_run
+/// # use three_d::*;
+/// # let cpu_model: CpuModel = unimplemented!();
+/// std::fs::write("model.3d", serialize_model(&cpu_model)).unwrap();
+/// ```
+///
+pub fn serialize_model(cpu_model: &CpuModel) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    VERSION.encode(&mut bytes);
+    cpu_model.encode(&mut bytes);
+    bytes
+}
+
+///
+/// Deserializes a [CpuModel] from bytes in the `.3d` format, see [serialize_model].
+/// Returns an error if the bytes are not in the `.3d` format or if the format was written by a different version of three-d.
+///
+/// ```no_run
+/// # use three_d::*;
+/// # async fn load() -> Result<CpuModel, RendererError> {
+/// let loaded = three_d_asset::io::load_async(&["model.3d"]).await?;
+/// deserialize_model(loaded.get("model.3d")?)
+/// # }
+/// ```
+///
+pub fn deserialize_model(bytes: &[u8]) -> Result<CpuModel, RendererError> {
+    let mut reader = Reader(bytes);
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(RendererError::InvalidModelBundle(
+            "not a .3d file".to_string(),
+        ));
+    }
+    let version = u32::decode(&mut reader)?;
+    if version != VERSION {
+        return Err(RendererError::InvalidModelBundle(format!(
+            "unsupported version {}, expected version {}",
+            version, VERSION
+        )));
+    }
+    let cpu_model = CpuModel::decode(&mut reader)?;
+    if !reader.0.is_empty() {
+        return Err(RendererError::InvalidModelBundle(
+            "unexpected data after the model".to_string(),
+        ));
+    }
+    Ok(cpu_model)
+}
+
+pub(super) struct Reader<'a>(pub(super) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub(super) fn take(&mut self, count: usize) -> Result<&'a [u8], RendererError> {
+        if count > self.0.len() {
+            return Err(RendererError::InvalidModelBundle(
+                "unexpected end of data".to_string(),
+            ));
+        }
+        let (bytes, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    pub(super) fn tag(&mut self, name: &str, max: u8) -> Result<u8, RendererError> {
+        let tag = u8::decode(self)?;
+        if tag > max {
+            return Err(RendererError::InvalidModelBundle(format!(
+                "invalid {} {}",
+                name, tag
+            )));
+        }
+        Ok(tag)
+    }
+}
+
+///
+/// Implemented for the types that are part of a [CpuModel] to encode them in the `.3d` format and decode them again.
+///
+pub(super) trait Encode: Sized {
+    fn encode(&self, bytes: &mut Vec<u8>);
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError>;
+}
+
+macro_rules! impl_encode_number {
+    ($($t:ty),*) => {
+        $(impl Encode for $t {
+            fn encode(&self, bytes: &mut Vec<u8>) {
+                bytes.extend_from_slice(&self.to_le_bytes());
+            }
+            fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+                let bytes = reader.take(std::mem::size_of::<$t>())?;
+                Ok(<$t>::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        })*
+    };
+}
+impl_encode_number!(u8, u16, u32, i32, f32, f64);
+
+impl Encode for f16 {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.to_bits().encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(f16::from_bits(u16::decode(reader)?))
+    }
+}
+
+impl Encode for bool {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        (*self as u8).encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(reader.tag("boolean", 1)? == 1)
+    }
+}
+
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.iter().for_each(|v| v.encode(bytes));
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        let values = (0..N)
+            .map(|_| T::decode(reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(values.try_into().ok().unwrap())
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        (self.len() as u32).encode(bytes);
+        self.iter().for_each(|v| v.encode(bytes));
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        let len = u32::decode(reader)? as usize;
+        // Each value is at least one byte, so this avoids allocating a huge vector for invalid data
+        let mut values = Vec::with_capacity(len.min(reader.0.len()));
+        for _ in 0..len {
+            values.push(T::decode(reader)?);
+        }
+        Ok(values)
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.is_some().encode(bytes);
+        if let Some(value) = self {
+            value.encode(bytes);
+        }
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(if bool::decode(reader)? {
+            Some(T::decode(reader)?)
+        } else {
+            None
+        })
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.as_bytes().to_vec().encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        String::from_utf8(Vec::decode(reader)?)
+            .map_err(|_| RendererError::InvalidModelBundle("invalid string".to_string()))
+    }
+}
+
+macro_rules! impl_encode_vector {
+    ($($t:ident: $($c:ident),*);*) => {
+        $(impl<T: Encode> Encode for $t<T> {
+            fn encode(&self, bytes: &mut Vec<u8>) {
+                $(self.$c.encode(bytes);)*
+            }
+            fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+                Ok(Self {
+                    $($c: T::decode(reader)?,)*
+                })
+            }
+        })*
+    };
+}
+impl_encode_vector!(Vector2: x, y; Vector3: x, y, z; Vector4: x, y, z, w);
+
+impl Encode for Quat {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.s.encode(bytes);
+        self.v.encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(Quat::from_sv(f32::decode(reader)?, Vec3::decode(reader)?))
+    }
+}
+
+impl Encode for Mat4 {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.x.encode(bytes);
+        self.y.encode(bytes);
+        self.z.encode(bytes);
+        self.w.encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(Mat4::from_cols(
+            Vec4::decode(reader)?,
+            Vec4::decode(reader)?,
+            Vec4::decode(reader)?,
+            Vec4::decode(reader)?,
+        ))
+    }
+}
+
+impl Encode for Srgba {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        [self.r, self.g, self.b, self.a].encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        let [r, g, b, a] = <[u8; 4]>::decode(reader)?;
+        Ok(Srgba::new(r, g, b, a))
+    }
+}
+
+impl Encode for CpuModel {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.name.encode(bytes);
+        self.geometries.encode(bytes);
+        self.materials.encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(Self {
+            name: String::decode(reader)?,
+            geometries: Vec::decode(reader)?,
+            materials: Vec::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for Primitive {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.name.encode(bytes);
+        self.transformation.encode(bytes);
+        self.animations.encode(bytes);
+        self.geometry.encode(bytes);
+        self.material_index.map(|i| i as u32).encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(Self {
+            name: String::decode(reader)?,
+            transformation: Mat4::decode(reader)?,
+            animations: Vec::decode(reader)?,
+            geometry: CpuGeometry::decode(reader)?,
+            material_index: Option::<u32>::decode(reader)?.map(|i| i as usize),
+        })
+    }
+}
+
+impl Encode for KeyFrameAnimation {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.name.encode(bytes);
+        (self.key_frames.len() as u32).encode(bytes);
+        for (transformation, key_frames) in self.key_frames.iter() {
+            transformation.encode(bytes);
+            key_frames.loop_time.encode(bytes);
+            key_frames.interpolation.encode(bytes);
+            key_frames.times.encode(bytes);
+            key_frames.rotations.encode(bytes);
+            key_frames.translations.encode(bytes);
+            key_frames.scales.encode(bytes);
+            key_frames.weights.encode(bytes);
+        }
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        let name = Option::decode(reader)?;
+        let count = u32::decode(reader)?;
+        let mut key_frames = Vec::new();
+        for _ in 0..count {
+            let transformation = Mat4::decode(reader)?;
+            key_frames.push((
+                transformation,
+                std::sync::Arc::new(KeyFrames {
+                    loop_time: Option::decode(reader)?,
+                    interpolation: Interpolation::decode(reader)?,
+                    times: Vec::decode(reader)?,
+                    rotations: Option::decode(reader)?,
+                    translations: Option::decode(reader)?,
+                    scales: Option::decode(reader)?,
+                    weights: Option::decode(reader)?,
+                }),
+            ));
+        }
+        Ok(Self { name, key_frames })
+    }
+}
+
+impl Encode for CpuGeometry {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Self::Points(point_cloud) => {
+                0u8.encode(bytes);
+                point_cloud.positions.encode(bytes);
+                point_cloud.colors.encode(bytes);
+            }
+            Self::Triangles(cpu_mesh) => {
+                1u8.encode(bytes);
+                cpu_mesh.positions.encode(bytes);
+                cpu_mesh.indices.encode(bytes);
+                cpu_mesh.normals.encode(bytes);
+                cpu_mesh.tangents.encode(bytes);
+                cpu_mesh.uvs.encode(bytes);
+                cpu_mesh.colors.encode(bytes);
+            }
+        }
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(match reader.tag("geometry", 1)? {
+            0 => Self::Points(PointCloud {
+                positions: Positions::decode(reader)?,
+                colors: Option::decode(reader)?,
+            }),
+            _ => Self::Triangles(CpuMesh {
+                positions: Positions::decode(reader)?,
+                indices: Indices::decode(reader)?,
+                normals: Option::decode(reader)?,
+                tangents: Option::decode(reader)?,
+                uvs: Option::decode(reader)?,
+                colors: Option::decode(reader)?,
+            }),
+        })
+    }
+}
+
+impl Encode for Positions {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Self::F32(positions) => {
+                0u8.encode(bytes);
+                positions.encode(bytes);
+            }
+            Self::F64(positions) => {
+                1u8.encode(bytes);
+                positions.encode(bytes);
+            }
+        }
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(match reader.tag("positions", 1)? {
+            0 => Self::F32(Vec::decode(reader)?),
+            _ => Self::F64(Vec::decode(reader)?),
+        })
+    }
+}
+
+impl Encode for Indices {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Self::None => 0u8.encode(bytes),
+            Self::U8(indices) => {
+                1u8.encode(bytes);
+                indices.encode(bytes);
+            }
+            Self::U16(indices) => {
+                2u8.encode(bytes);
+                indices.encode(bytes);
+            }
+            Self::U32(indices) => {
+                3u8.encode(bytes);
+                indices.encode(bytes);
+            }
+        }
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(match reader.tag("indices", 3)? {
+            0 => Self::None,
+            1 => Self::U8(Vec::decode(reader)?),
+            2 => Self::U16(Vec::decode(reader)?),
+            _ => Self::U32(Vec::decode(reader)?),
+        })
+    }
+}
+
+impl Encode for PbrMaterial {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.name.encode(bytes);
+        self.albedo.encode(bytes);
+        self.albedo_texture.encode(bytes);
+        self.metallic.encode(bytes);
+        self.roughness.encode(bytes);
+        self.occlusion_metallic_roughness_texture.encode(bytes);
+        self.metallic_roughness_texture.encode(bytes);
+        self.occlusion_strength.encode(bytes);
+        self.occlusion_texture.encode(bytes);
+        self.normal_scale.encode(bytes);
+        self.normal_texture.encode(bytes);
+        self.emissive.encode(bytes);
+        self.emissive_texture.encode(bytes);
+        self.alpha_cutout.encode(bytes);
+        self.lighting_model.encode(bytes);
+        self.index_of_refraction.encode(bytes);
+        self.transmission.encode(bytes);
+        self.transmission_texture.encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(Self {
+            name: String::decode(reader)?,
+            albedo: Srgba::decode(reader)?,
+            albedo_texture: Option::decode(reader)?,
+            metallic: f32::decode(reader)?,
+            roughness: f32::decode(reader)?,
+            occlusion_metallic_roughness_texture: Option::decode(reader)?,
+            metallic_roughness_texture: Option::decode(reader)?,
+            occlusion_strength: f32::decode(reader)?,
+            occlusion_texture: Option::decode(reader)?,
+            normal_scale: f32::decode(reader)?,
+            normal_texture: Option::decode(reader)?,
+            emissive: Srgba::decode(reader)?,
+            emissive_texture: Option::decode(reader)?,
+            alpha_cutout: Option::decode(reader)?,
+            lighting_model: LightingModel::decode(reader)?,
+            index_of_refraction: f32::decode(reader)?,
+            transmission: f32::decode(reader)?,
+            transmission_texture: Option::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for LightingModel {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Self::Phong => 0u8.encode(bytes),
+            Self::Blinn => 1u8.encode(bytes),
+            Self::Cook(normal_distribution, GeometryFunction::SmithSchlickGGX) => {
+                2u8.encode(bytes);
+                match normal_distribution {
+                    NormalDistributionFunction::Blinn => 0u8,
+                    NormalDistributionFunction::Beckmann => 1u8,
+                    NormalDistributionFunction::TrowbridgeReitzGGX => 2u8,
+                }
+                .encode(bytes);
+            }
+        }
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(match reader.tag("lighting model", 2)? {
+            0 => Self::Phong,
+            1 => Self::Blinn,
+            _ => Self::Cook(
+                match reader.tag("normal distribution function", 2)? {
+                    0 => NormalDistributionFunction::Blinn,
+                    1 => NormalDistributionFunction::Beckmann,
+                    _ => NormalDistributionFunction::TrowbridgeReitzGGX,
+                },
+                GeometryFunction::SmithSchlickGGX,
+            ),
+        })
+    }
+}
+
+impl Encode for CpuTexture {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        self.name.encode(bytes);
+        self.data.encode(bytes);
+        self.width.encode(bytes);
+        self.height.encode(bytes);
+        self.min_filter.encode(bytes);
+        self.mag_filter.encode(bytes);
+        self.mip_map_filter.encode(bytes);
+        self.wrap_s.encode(bytes);
+        self.wrap_t.encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(Self {
+            name: String::decode(reader)?,
+            data: TextureData::decode(reader)?,
+            width: u32::decode(reader)?,
+            height: u32::decode(reader)?,
+            min_filter: Interpolation::decode(reader)?,
+            mag_filter: Interpolation::decode(reader)?,
+            mip_map_filter: Option::decode(reader)?,
+            wrap_s: Wrapping::decode(reader)?,
+            wrap_t: Wrapping::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for TextureData {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Self::RU8(data) => {
+                0u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RgU8(data) => {
+                1u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RgbU8(data) => {
+                2u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RgbaU8(data) => {
+                3u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RF16(data) => {
+                4u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RgF16(data) => {
+                5u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RgbF16(data) => {
+                6u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RgbaF16(data) => {
+                7u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RF32(data) => {
+                8u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RgF32(data) => {
+                9u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RgbF32(data) => {
+                10u8.encode(bytes);
+                data.encode(bytes);
+            }
+            Self::RgbaF32(data) => {
+                11u8.encode(bytes);
+                data.encode(bytes);
+            }
+        }
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(match reader.tag("texture data", 11)? {
+            0 => Self::RU8(Vec::decode(reader)?),
+            1 => Self::RgU8(Vec::decode(reader)?),
+            2 => Self::RgbU8(Vec::decode(reader)?),
+            3 => Self::RgbaU8(Vec::decode(reader)?),
+            4 => Self::RF16(Vec::decode(reader)?),
+            5 => Self::RgF16(Vec::decode(reader)?),
+            6 => Self::RgbF16(Vec::decode(reader)?),
+            7 => Self::RgbaF16(Vec::decode(reader)?),
+            8 => Self::RF32(Vec::decode(reader)?),
+            9 => Self::RgF32(Vec::decode(reader)?),
+            10 => Self::RgbF32(Vec::decode(reader)?),
+            _ => Self::RgbaF32(Vec::decode(reader)?),
+        })
+    }
+}
+
+impl Encode for Interpolation {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Self::Nearest => 0u8,
+            Self::Linear => 1u8,
+            Self::CubicSpline => 2u8,
+        }
+        .encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(match reader.tag("interpolation", 2)? {
+            0 => Self::Nearest,
+            1 => Self::Linear,
+            _ => Self::CubicSpline,
+        })
+    }
+}
+
+impl Encode for Wrapping {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Self::Repeat => 0u8,
+            Self::MirroredRepeat => 1u8,
+            Self::ClampToEdge => 2u8,
+        }
+        .encode(bytes);
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(match reader.tag("wrapping", 2)? {
+            0 => Self::Repeat,
+            1 => Self::MirroredRepeat,
+            _ => Self::ClampToEdge,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    pub(in crate::renderer) fn model() -> CpuModel {
+        let mut mesh = CpuMesh::cube();
+        mesh.colors = Some(vec![Srgba::new(10, 20, 30, 40); mesh.vertex_count()]);
+        CpuModel {
+            name: "model".to_string(),
+            geometries: vec![Primitive {
+                name: "cube".to_string(),
+                transformation: Mat4::from_translation(vec3(1.0, 2.0, 3.0)),
+                animations: Vec::new(),
+                geometry: CpuGeometry::Triangles(mesh),
+                material_index: Some(0),
+            }],
+            materials: vec![PbrMaterial {
+                name: "material".to_string(),
+                albedo: Srgba::new(200, 100, 50, 255),
+                albedo_texture: Some(CpuTexture {
+                    name: "albedo".to_string(),
+                    data: TextureData::RgbaU8(vec![[1, 2, 3, 4], [5, 6, 7, 8]]),
+                    width: 2,
+                    height: 1,
+                    ..Default::default()
+                }),
+                roughness: 0.25,
+                alpha_cutout: Some(0.5),
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[test]
+    fn model_round_trip() {
+        let model = model();
+        let bytes = serialize_model(&model);
+        let decoded = deserialize_model(&bytes).unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", model));
+        assert_eq!(serialize_model(&decoded), bytes);
+    }
+
+    #[test]
+    fn invalid_model_bytes() {
+        let bytes = serialize_model(&model());
+        assert!(deserialize_model(b"not a model").is_err());
+        assert!(deserialize_model(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(deserialize_model(&trailing).is_err());
+
+        let mut version = bytes.clone();
+        version[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(deserialize_model(&version).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::renderer::*;
+
+const FIELD_OF_VIEW: f32 = 45.0;
+
+///
+/// Everything needed to show one or more models in an existing application, ie. the "just show this model" solution.
+/// It consists of a camera which is automatically positioned to show all the models, an [OrbitControl], an ambient light, a directional light with shadows
+/// and a background which is either a color or, if an environment map is set using [ModelViewer::set_environment], a skybox which is also used for image based lighting.
+///
+/// Each frame, call [ModelViewer::handle_events] with the events and the viewport to render into, [ModelViewer::animate] if the models are animated
+/// and finally [ModelViewer::render] with the render target, for example the screen.
+/// To get a window with a model viewer, use the [Viewer](crate::Viewer) instead.
+///
+pub struct ModelViewer {
+    context: Context,
+    camera: Camera,
+    control: OrbitControl,
+    ambient: AmbientLight,
+    directional: DirectionalLight,
+    skybox: Option<Skybox>,
+    models: Vec<Model<PhysicalMaterial>>,
+    objects: Vec<Box<dyn Object>>,
+    background: Srgba,
+}
+
+impl ModelViewer {
+    ///
+    /// Creates a new model viewer with default settings but nothing to show, which renders into the given viewport.
+    ///
+    pub fn new(context: &Context, viewport: Viewport) -> Self {
+        Self {
+            context: context.clone(),
+            camera: Camera::new_perspective(
+                viewport,
+                vec3(3.0, 2.0, 3.0),
+                vec3(0.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                degrees(FIELD_OF_VIEW),
+                0.1,
+                1000.0,
+            ),
+            control: OrbitControl::new(vec3(0.0, 0.0, 0.0), 0.1, 100.0),
+            ambient: AmbientLight::new(context, 0.4, Srgba::WHITE),
+            directional: DirectionalLight::new(context, 2.0, Srgba::WHITE, &vec3(-1.0, -1.0, -0.5)),
+            skybox: None,
+            models: Vec::new(),
+            objects: Vec::new(),
+            background: Srgba::new(77, 77, 77, 255),
+        }
+    }
+
+    ///
+    /// Returns the graphics context, for example to construct objects to add with [ModelViewer::add_object].
+    ///
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    ///
+    /// Returns the camera.
+    ///
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    ///
+    /// Returns a mutable reference to the camera.
+    ///
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    ///
+    /// Returns a mutable reference to the control which handles the camera.
+    ///
+    pub fn control_mut(&mut self) -> &mut OrbitControl {
+        &mut self.control
+    }
+
+    ///
+    /// Returns a mutable reference to the ambient light.
+    ///
+    pub fn ambient_light_mut(&mut self) -> &mut AmbientLight {
+        &mut self.ambient
+    }
+
+    ///
+    /// Returns a mutable reference to the directional light.
+    /// Call [ModelViewer::frame_all] to update the shadows after changing the direction of the light.
+    ///
+    pub fn directional_light_mut(&mut self) -> &mut DirectionalLight {
+        &mut self.directional
+    }
+
+    ///
+    /// Sets the background color which is used when no environment is set.
+    ///
+    pub fn set_background(&mut self, color: Srgba) {
+        self.background = color;
+    }
+
+    ///
+    /// Adds a model, positions the camera to show all the models and objects and updates the shadows.
+    ///
+    pub fn add_model(&mut self, cpu_model: &CpuModel) -> Result<(), RendererError> {
+        self.models.push(Model::new(&self.context, cpu_model)?);
+        self.frame_all();
+        Ok(())
+    }
+
+    ///
+    /// Loads the model at the given path, for example a glTF or obj file, and adds it using [ModelViewer::add_model].
+    /// The corresponding feature of the [three-d-asset](https://crates.io/crates/three-d-asset) crate, for example `"gltf"` or `"obj"`, has to be enabled.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(&mut self, path: impl AsRef<std::path::Path>) -> Result<(), RendererError> {
+        let cpu_model: CpuModel = three_d_asset::io::load_and_deserialize(path)?;
+        self.add_model(&cpu_model)
+    }
+
+    ///
+    /// Loads a model from the bytes of a file with the given name, for example a `.glb` file, and adds it using [ModelViewer::add_model].
+    /// Only file formats where the model is contained in one file are supported, so for example not glTF files with external buffers or textures.
+    /// The corresponding feature of the [three-d-asset](https://crates.io/crates/three-d-asset) crate, for example `"gltf"`, has to be enabled.
+    ///
+    pub fn load_from_bytes(&mut self, name: &str, bytes: Vec<u8>) -> Result<(), RendererError> {
+        let cpu_model: CpuModel = three_d_asset::io::RawAssets::new()
+            .insert(name, bytes)
+            .deserialize(name)?;
+        self.add_model(&cpu_model)
+    }
+
+    ///
+    /// Adds an object, positions the camera to show all the models and objects and updates the shadows.
+    ///
+    pub fn add_object(&mut self, object: impl Object + 'static) {
+        self.objects.push(Box::new(object));
+        self.frame_all();
+    }
+
+    ///
+    /// Removes all models and objects.
+    ///
+    pub fn clear(&mut self) {
+        self.models.clear();
+        self.objects.clear();
+        self.directional.clear_shadow_map();
+    }
+
+    ///
+    /// Uses the given equirectangular environment map, for example loaded from a hdr file, as the background and for image based lighting.
+    ///
+    pub fn set_environment(&mut self, cpu_texture: &CpuTexture) {
+        let skybox = Skybox::new_from_equirectangular(&self.context, cpu_texture);
+        self.ambient = AmbientLight::new_with_environment(
+            &self.context,
+            self.ambient.intensity,
+            self.ambient.color,
+            skybox.texture(),
+        );
+        self.skybox = Some(skybox);
+    }
+
+    ///
+    /// Positions the camera and the target of the control so that all the models and objects are visible, keeping the current view direction,
+    /// and updates the shadows of the directional light.
+    ///
+    pub fn frame_all(&mut self) {
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        objects(&self.models, &self.objects).for_each(|o| aabb.expand_with_aabb(&o.aabb()));
+        if aabb.is_empty() {
+            return;
+        }
+        let target = aabb.center();
+        let radius = (0.5 * aabb.size().magnitude()).max(0.001);
+        let distance = radius / (0.5 * FIELD_OF_VIEW).to_radians().sin();
+        let direction = (self.camera.position() - self.camera.target()).normalize();
+        let up = *self.camera.up();
+        self.camera
+            .set_view(target + direction * distance, target, up);
+        self.camera.set_perspective_projection(
+            degrees(FIELD_OF_VIEW),
+            0.01 * radius,
+            20.0 * distance,
+        );
+        let inertia = self.control.inertia;
+        self.control =
+            OrbitControl::new_with_mode(target, 0.1 * radius, 10.0 * distance, self.control.mode());
+        self.control.inertia = inertia;
+        self.directional
+            .generate_shadow_map(2048, objects(&self.models, &self.objects));
+    }
+
+    ///
+    /// Updates the viewport of the camera and moves the camera according to the given events using the control.
+    /// Returns whether the camera has changed.
+    ///
+    pub fn handle_events(&mut self, viewport: Viewport, events: &mut [Event]) -> bool {
+        let mut change = self.camera.set_viewport(viewport);
+        change |= self.control.handle_events(&mut self.camera, events);
+        change
+    }
+
+    ///
+    /// Updates the animations of the models to the given time in seconds.
+    ///
+    pub fn animate(&mut self, time: f32) {
+        self.models.iter_mut().for_each(|m| m.animate(time));
+    }
+
+    ///
+    /// Renders the background, models and objects into the given render target, within the viewport of the camera.
+    ///
+    pub fn render(&self, target: &RenderTarget) {
+        let scissor_box = self.camera.viewport().into();
+        target.clear_partially(
+            scissor_box,
+            ClearState::color_and_depth(
+                self.background.r as f32 / 255.0,
+                self.background.g as f32 / 255.0,
+                self.background.b as f32 / 255.0,
+                self.background.a as f32 / 255.0,
+                1.0,
+            ),
+        );
+        if let Some(skybox) = &self.skybox {
+            target.render(&self.camera, skybox, &[]);
+        }
+        target.render(
+            &self.camera,
+            objects(&self.models, &self.objects),
+            &[&self.ambient, &self.directional],
+        );
+    }
+}
+
+fn objects<'a>(
+    models: &'a [Model<PhysicalMaterial>],
+    objects: &'a [Box<dyn Object>],
+) -> impl Iterator<Item = &'a dyn Object> + Clone {
+    models
+        .iter()
+        .flat_map(|m| m.into_iter())
+        .chain(objects.iter().map(|o| o.as_ref()))
+}
@@ -0,0 +1,181 @@
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// Renders the screen space motion of each pixel since the previous frame, also known as motion vectors or a velocity buffer,
+/// for example to use as optical flow ground truth when generating synthetic datasets, see [VelocityMaterial] for a description of the output.
+///
+/// Each frame, call [MotionVectors::render] with the camera and the geometries together with their current transformation
+/// in the same order as the previous frame. The previous transformation of a geometry is the one given at the same index in the previous frame,
+/// so if the number of geometries changes or in the first frame, the motion of the geometries is only given by the camera motion.
+/// Alternatively, call [MotionVectors::render_meshes] which uses the previous transformation tracked by each [Mesh].
+///
+/// The rendered texture can be shared with a [MotionBlurEffect] using [MotionVectors::texture].
+///
+pub struct MotionVectors {
+    context: Context,
+    targets: Option<(Arc<Texture2D>, DepthTexture2D)>,
+    previous_view_projection: Option<Mat4>,
+    previous_transformations: Vec<Mat4>,
+}
+
+impl MotionVectors {
+    ///
+    /// Creates a new [MotionVectors] without any history.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            targets: None,
+            previous_view_projection: None,
+            previous_transformations: Vec::new(),
+        }
+    }
+
+    ///
+    /// Renders the motion vectors of the given geometries, each with its current transformation, as seen from the given camera
+    /// and returns a texture with the size of the camera viewport where the red and green channels contain the motion in pixels.
+    /// Pixels that are not covered by any of the geometries have zero motion.
+    ///
+    pub fn render<'a>(
+        &mut self,
+        camera: &Camera,
+        geometries: impl IntoIterator<Item = (&'a dyn Geometry, Mat4)>,
+    ) -> &Texture2D {
+        let geometries = geometries.into_iter().collect::<Vec<_>>();
+        let history_valid = geometries.len() == self.previous_transformations.len();
+        let motions = geometries
+            .iter()
+            .enumerate()
+            .map(|(i, (geometry, transformation))| {
+                let previous_transformation = if history_valid {
+                    self.previous_transformations[i]
+                } else {
+                    *transformation
+                };
+                (
+                    *geometry,
+                    previous_transformation * transformation.invert().unwrap_or(Mat4::identity()),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.render_motions(camera, motions);
+        self.previous_transformations = geometries.iter().map(|(_, t)| *t).collect();
+        &self.targets.as_ref().unwrap().0
+    }
+
+    ///
+    /// Renders the motion vectors of the given meshes as seen from the given camera, where the motion of each mesh is given by
+    /// the difference between its current transformation and its [Mesh::previous_transformation],
+    /// and afterwards updates the previous transformation of the meshes (see [Mesh::update_previous_transformation]).
+    /// Otherwise the same as [MotionVectors::render].
+    ///
+    pub fn render_meshes<'a>(
+        &mut self,
+        camera: &Camera,
+        meshes: impl IntoIterator<Item = &'a mut Mesh>,
+    ) -> &Texture2D {
+        let mut meshes = meshes.into_iter().collect::<Vec<_>>();
+        let motions = meshes
+            .iter()
+            .map(|mesh| (&**mesh as &dyn Geometry, mesh.motion()))
+            .collect::<Vec<_>>();
+        self.render_motions(camera, motions);
+        for mesh in meshes.iter_mut() {
+            mesh.update_previous_transformation();
+        }
+        self.previous_transformations.clear();
+        &self.targets.as_ref().unwrap().0
+    }
+
+    ///
+    /// Returns the texture rendered in the last call to [MotionVectors::render] or [MotionVectors::render_meshes],
+    /// for example to use as the [MotionBlurEffect::velocity_texture].
+    /// The texture is shared, so a new texture is allocated in the next frame if the returned texture is still in use at that time.
+    ///
+    pub fn texture(&self) -> Option<Arc<Texture2D>> {
+        self.targets.as_ref().map(|(texture, _)| texture.clone())
+    }
+
+    fn render_motions(&mut self, camera: &Camera, motions: Vec<(&dyn Geometry, Mat4)>) {
+        let viewport = camera.viewport();
+        let reuse = self
+            .targets
+            .as_mut()
+            .map(|(t, _)| {
+                t.width() == viewport.width
+                    && t.height() == viewport.height
+                    && Arc::get_mut(t).is_some()
+            })
+            .unwrap_or(false);
+        if !reuse {
+            self.targets = Some((
+                Arc::new(Texture2D::new_empty::<[f32; 4]>(
+                    &self.context,
+                    viewport.width,
+                    viewport.height,
+                    Interpolation::Nearest,
+                    Interpolation::Nearest,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                )),
+                DepthTexture2D::new::<f32>(
+                    &self.context,
+                    viewport.width,
+                    viewport.height,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ),
+            ));
+        }
+        let mut camera = camera.clone();
+        camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        let view_projection = camera.projection_without_jitter() * camera.view();
+        let previous_view_projection = self.previous_view_projection.unwrap_or(view_projection);
+        {
+            let (color_texture, depth_texture) = self.targets.as_mut().unwrap();
+            let target = RenderTarget::new(
+                Arc::get_mut(color_texture).unwrap().as_color_target(None),
+                depth_texture.as_depth_target(),
+            );
+            target.clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0));
+            for (geometry, motion) in motions {
+                let material = VelocityMaterial {
+                    previous_view_projection,
+                    motion,
+                    ..Default::default()
+                };
+                target.render_with_material(&material, &camera, [geometry], &[]);
+            }
+        }
+        self.previous_view_projection = Some(view_projection);
+    }
+
+    ///
+    /// Reads the motion vectors rendered in the last call to [MotionVectors::render] or [MotionVectors::render_meshes], in pixels.
+    /// The motion vectors are returned row by row starting with the top row of the viewport, similar to [ColorTarget::read].
+    ///
+    pub fn read(&self) -> Vec<Vec2> {
+        self.targets
+            .as_ref()
+            .map(|(texture, _)| {
+                texture
+                    .as_shared_color_target(None)
+                    .read::<[f32; 4]>()
+                    .into_iter()
+                    .map(|v| vec2(v[0], v[1]))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Discards the previous camera and transformations, for example when the camera is moved to a completely different location,
+    /// so the motion in the next frame is zero.
+    ///
+    pub fn reset(&mut self) {
+        self.previous_view_projection = None;
+        self.previous_transformations.clear();
+    }
+}
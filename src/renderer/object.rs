@@ -15,6 +15,14 @@ macro_rules! impl_object_body {
         fn material_type(&self) -> MaterialType {
             self.$inner().material_type()
         }
+
+        fn render_depth_pre_pass(&self, camera: &Camera) -> bool {
+            self.$inner().render_depth_pre_pass(camera)
+        }
+
+        fn render_after_depth_pre_pass(&self, camera: &Camera, lights: &[&dyn Light]) {
+            self.$inner().render_after_depth_pre_pass(camera, lights)
+        }
     };
 }
 
@@ -26,35 +34,76 @@ mod model;
 #[doc(inline)]
 pub use model::*;
 
+mod model_lod;
+#[doc(inline)]
+pub use model_lod::*;
+
 mod instanced_model;
 #[doc(inline)]
 pub use instanced_model::*;
 
+#[cfg(feature = "volume")]
+#[cfg_attr(docsrs, doc(cfg(feature = "volume")))]
 mod voxel_grid;
 #[doc(inline)]
+#[cfg(feature = "volume")]
 pub use voxel_grid::*;
 
+#[cfg(feature = "volume")]
+#[cfg_attr(docsrs, doc(cfg(feature = "volume")))]
+mod volume_object;
+#[doc(inline)]
+#[cfg(feature = "volume")]
+pub use volume_object::*;
+
 mod skybox;
 #[doc(inline)]
 pub use skybox::*;
 
+#[cfg(feature = "imposters")]
+#[cfg_attr(docsrs, doc(cfg(feature = "imposters")))]
 mod imposters;
 #[doc(inline)]
+#[cfg(feature = "imposters")]
 pub use imposters::*;
 
+#[cfg(feature = "terrain")]
+#[cfg_attr(docsrs, doc(cfg(feature = "terrain")))]
 mod terrain;
 #[doc(inline)]
+#[cfg(feature = "terrain")]
 pub use terrain::*;
 
+#[cfg(feature = "terrain")]
+#[cfg_attr(docsrs, doc(cfg(feature = "terrain")))]
 mod water;
 #[doc(inline)]
+#[cfg(feature = "terrain")]
 pub use water::*;
 
 mod axes;
 #[doc(inline)]
 pub use axes::*;
 
-use crate::core::*;
+mod light_gizmo;
+#[doc(inline)]
+pub use light_gizmo::*;
+
+mod decal;
+#[doc(inline)]
+pub use decal::*;
+
+mod lines;
+#[doc(inline)]
+pub use lines::*;
+
+#[cfg(feature = "text")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+mod text;
+#[doc(inline)]
+#[cfg(feature = "text")]
+pub use text::*;
+
 use crate::renderer::*;
 
 ///
@@ -72,6 +121,25 @@ pub trait Object: Geometry {
     /// Returns the type of material applied to this object.
     ///
     fn material_type(&self) -> MaterialType;
+
+    ///
+    /// Renders only the depth of this object as part of a depth pre-pass, see [RenderTarget::render_with_depth_pre_pass], and returns true.
+    /// Returns false without rendering anything if this object does not support a depth pre-pass, which is the default,
+    /// in which case the object is rendered using [Object::render] after the depth pre-pass.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    fn render_depth_pre_pass(&self, _camera: &Camera) -> bool {
+        false
+    }
+
+    ///
+    /// Renders this object after its depth has been rendered using [Object::render_depth_pre_pass],
+    /// ie. only the fragments with the depth written in the depth pre-pass are shaded.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    fn render_after_depth_pre_pass(&self, camera: &Camera, lights: &[&dyn Light]) {
+        self.render(camera, lights)
+    }
 }
 
 use std::ops::Deref;
@@ -107,4 +175,14 @@ impl<T: Object> Object for std::sync::RwLock<T> {
     fn material_type(&self) -> MaterialType {
         self.read().unwrap().material_type()
     }
+
+    fn render_depth_pre_pass(&self, camera: &Camera) -> bool {
+        self.read().unwrap().render_depth_pre_pass(camera)
+    }
+
+    fn render_after_depth_pre_pass(&self, camera: &Camera, lights: &[&dyn Light]) {
+        self.read()
+            .unwrap()
+            .render_after_depth_pre_pass(camera, lights)
+    }
 }
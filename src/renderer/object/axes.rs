@@ -28,6 +28,7 @@ impl Axes {
                     ],
                     texture_transformations: None,
                     colors: Some(vec![Srgba::RED, Srgba::GREEN, Srgba::BLUE]),
+                    texture_layers: None,
                 },
                 &cpu_mesh,
             ),
@@ -0,0 +1,273 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A decal which projects a texture onto the already rendered geometry inside an oriented box, for example bullet holes, stickers or road markings,
+/// without having to create geometry that follows the surface.
+///
+/// The box is a cube with center in origo and side length 1 placed in the world using [Decal::set_transformation],
+/// and the texture is projected along the negative z-axis of the box, so it is seen without distortion when looking at the box along the negative z-axis.
+/// The position of the surface inside the box is reconstructed from the depth texture of the rendered scene,
+/// so first render the scene into a color and depth texture and then call [Decal::render] with the color texture as the render target
+/// and the depth texture as input. This works both for forward rendering and after the [DeferredPhysicalMaterial::lighting_pass].
+///
+/// The decal is lit with its own color, metallic, roughness and normal on top of the normal of the surface,
+/// so for example a normal map can add the dent of a bullet hole or the bumps of a road marking.
+///
+pub struct Decal {
+    context: Context,
+    mesh: Mesh,
+    /// The color of the decal, which is multiplied with the [Decal::albedo_texture].
+    pub albedo: Srgba,
+    /// The texture projected onto the surface, where the alpha channel is the opacity of the decal.
+    /// [Decal::new] creates it in [ColorSpace::Srgb], so the hardware converts the colors to linear sRGB when sampling.
+    /// Other textures are assumed to be in linear sRGB or HDR color space.
+    pub albedo_texture: Option<Texture2DRef>,
+    /// A value in the range `[0..1]` specifying how metallic the decal is.
+    pub metallic: f32,
+    /// A value in the range `[0..1]` specifying how rough the decal is.
+    pub roughness: f32,
+    /// Texture containing the metallic values in the blue channel and the roughness values in the green channel,
+    /// which are multiplied with the [Decal::metallic] and [Decal::roughness] values.
+    pub metallic_roughness_texture: Option<Texture2DRef>,
+    /// A tangent space normal map, where the tangent is the x-axis of the box projected onto the surface.
+    pub normal_texture: Option<Texture2DRef>,
+    /// A scalar multiplier applied to each normal vector of the [Decal::normal_texture].
+    pub normal_scale: f32,
+    /// The decal is only applied to surfaces where the cosine of the angle between the normal and the z-axis of the box is above this threshold,
+    /// which avoids the texture being stretched across surfaces parallel to the projection direction. The default is 0.1.
+    pub normal_threshold: f32,
+    /// The lighting model used when rendering the decal.
+    pub lighting_model: LightingModel,
+}
+
+impl Decal {
+    ///
+    /// Creates a new decal with the colors, metallic, roughness and normal from the given [CpuMaterial].
+    /// The box of the decal is a cube with center in origo and side length 1 until the transformation is set using [Decal::set_transformation].
+    ///
+    pub fn new(context: &Context, cpu_material: &CpuMaterial) -> Self {
+        let mut cpu_mesh = CpuMesh::cube();
+        cpu_mesh
+            .transform(&Mat4::from_scale(0.5))
+            .expect("the scale is invertible");
+        let albedo_texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Texture2DRef::from_cpu_texture_with_color_space(context, cpu_texture, ColorSpace::Srgb)
+        });
+        let metallic_roughness_texture = cpu_material
+            .occlusion_metallic_roughness_texture
+            .as_ref()
+            .or(cpu_material.metallic_roughness_texture.as_ref())
+            .map(|cpu_texture| Texture2DRef::from_cpu_texture(context, cpu_texture));
+        let normal_texture = cpu_material
+            .normal_texture
+            .as_ref()
+            .map(|cpu_texture| Texture2DRef::from_cpu_texture(context, cpu_texture));
+        Self {
+            context: context.clone(),
+            mesh: Mesh::new(context, &cpu_mesh),
+            albedo: cpu_material.albedo,
+            albedo_texture,
+            metallic: cpu_material.metallic,
+            roughness: cpu_material.roughness,
+            metallic_roughness_texture,
+            normal_texture,
+            normal_scale: cpu_material.normal_scale,
+            normal_threshold: 0.1,
+            lighting_model: cpu_material.lighting_model,
+        }
+    }
+
+    ///
+    /// Returns the transformation of the box of the decal, see [Decal::set_transformation].
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.mesh.transformation()
+    }
+
+    ///
+    /// Sets the transformation of the box of the decal, which is a cube with center in origo and side length 1 before the transformation.
+    /// The texture is projected along the negative z-axis and the x- and y-axis of the box are the horizontal and vertical axis of the texture.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.mesh.set_transformation(transformation);
+    }
+
+    ///
+    /// Sets the transformation of the decal so that it is projected onto the given position on a surface with the given normal,
+    /// where the tangent specifies the direction of the horizontal axis of the texture, the size is the width and height of the decal
+    /// and the depth is how far in front of and behind the position the surface is affected.
+    ///
+    pub fn place(&mut self, position: Vec3, normal: Vec3, tangent: Vec3, size: Vec2, depth: f32) {
+        let z = normal.normalize();
+        let x = (tangent - z * tangent.dot(z)).normalize();
+        let y = z.cross(x);
+        self.set_transformation(
+            Mat4::from_cols(
+                x.extend(0.0),
+                y.extend(0.0),
+                z.extend(0.0),
+                position.extend(1.0),
+            ) * Mat4::from_nonuniform_scale(size.x, size.y, depth),
+        );
+    }
+
+    ///
+    /// Returns the axis aligned bounding box of the box of the decal.
+    ///
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.mesh.aabb()
+    }
+
+    ///
+    /// Renders the decal onto the surfaces given by the depth texture, which must be the depth of the scene rendered with the same camera.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method,
+    /// where the target must not contain the given depth texture.
+    ///
+    pub fn render(&self, camera: &Camera, depth_texture: DepthTexture, lights: &[&dyn Light]) {
+        render_with_effect(
+            &self.context,
+            camera,
+            &self.mesh,
+            DecalEffect { decal: self },
+            lights,
+            None,
+            Some(depth_texture),
+        );
+    }
+
+    fn textures(&self) -> [Option<&Texture2DRef>; 3] {
+        [
+            self.albedo_texture.as_ref(),
+            self.metallic_roughness_texture.as_ref(),
+            self.normal_texture.as_ref(),
+        ]
+    }
+}
+
+struct DecalEffect<'a> {
+    decal: &'a Decal,
+}
+
+impl Effect for DecalEffect<'_> {
+    fn fragment_shader_source(
+        &self,
+        lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        let mut output = String::new();
+        for (texture, define) in self.decal.textures().iter().zip([
+            "USE_ALBEDO_TEXTURE",
+            "USE_METALLIC_ROUGHNESS_TEXTURE",
+            "USE_NORMAL_TEXTURE",
+        ]) {
+            if texture.is_some() {
+                output.push_str(&format!("#define {}\n", define));
+            }
+        }
+        output.push_str(&lights_shader_source(lights, self.decal.lighting_model));
+        output.push_str(
+            &depth_texture
+                .expect("Must supply a depth texture to render a decal")
+                .fragment_shader_source(),
+        );
+        output.push_str(ToneMapping::fragment_shader_source());
+        output.push_str(ColorMapping::fragment_shader_source());
+        output.push_str(Fog::fragment_shader_source());
+        output.push_str(include_str!("shaders/decal.frag"));
+        output
+    }
+
+    fn id(&self, _color_texture: Option<ColorTexture>, depth_texture: Option<DepthTexture>) -> u16 {
+        let mut id = 0b1u16 << 14
+            | 0b1u16 << 13
+            | 0b1u16 << 12
+            | 0b1u16 << 10
+            | depth_texture
+                .expect("Must supply a depth texture to render a decal")
+                .id();
+        for (i, texture) in self.decal.textures().iter().enumerate() {
+            if texture.is_some() {
+                id |= 0b1u16 << (7 + i);
+            }
+        }
+        id
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            position: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let decal = self.decal;
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        camera.fog.use_uniforms(program, *camera.position());
+        depth_texture
+            .expect("Must supply a depth texture to render a decal")
+            .use_uniforms(program);
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+        let view_projection = camera.projection() * camera.view();
+        program.use_uniform("viewProjection", view_projection);
+        program.use_uniform(
+            "viewProjectionInverse",
+            view_projection.invert().unwrap_or(Mat4::identity()),
+        );
+        program.use_uniform("cameraPosition", camera.position());
+        let transformation = decal.transformation();
+        program.use_uniform(
+            "decalInverse",
+            transformation.invert().unwrap_or(Mat4::identity()),
+        );
+        program.use_uniform("decalX", transformation.x.truncate().normalize());
+        program.use_uniform("decalZ", transformation.z.truncate().normalize());
+        program.use_uniform("normalThreshold", decal.normal_threshold);
+        program.use_uniform("albedo", decal.albedo.to_linear_srgb());
+        program.use_uniform_if_required("metallic", decal.metallic);
+        program.use_uniform_if_required("roughness", decal.roughness);
+        if let Some(ref texture) = decal.albedo_texture {
+            program.use_uniform("albedoTexTransform", texture.transformation);
+            program.use_texture("albedoTexture", texture);
+        }
+        if program.requires_uniform("metallicRoughnessTexture") {
+            if let Some(ref texture) = decal.metallic_roughness_texture {
+                program.use_uniform("metallicRoughnessTexTransform", texture.transformation);
+                program.use_texture("metallicRoughnessTexture", texture);
+            }
+        }
+        if program.requires_uniform("normalTexture") {
+            if let Some(ref texture) = decal.normal_texture {
+                program.use_uniform("normalTexTransform", texture.transformation);
+                program.use_uniform_if_required("normalScale", decal.normal_scale);
+                program.use_texture("normalTexture", texture);
+            }
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        // The back faces are rendered without depth test, so the decal is also rendered when the camera is inside the box
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            depth_test: DepthTest::Always,
+            cull: Cull::Front,
+        }
+    }
+}
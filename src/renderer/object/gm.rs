@@ -69,4 +69,26 @@ impl<G: Geometry, M: Material> Object for Gm<G, M> {
     fn material_type(&self) -> MaterialType {
         self.material.material_type()
     }
+
+    fn render_depth_pre_pass(&self, camera: &Camera) -> bool {
+        let render_states = self.material.render_states();
+        if self.material.material_type() == MaterialType::Transparent
+            || !render_states.write_mask.depth
+            || (render_states.depth_test != DepthTest::Less
+                && render_states.depth_test != DepthTest::LessOrEqual)
+            || render_states.blend != Blend::Disabled
+        {
+            return false;
+        }
+        if let Some(material) = self.material.depth_pre_pass_material() {
+            self.render_with_material(&material, camera, &[]);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn render_after_depth_pre_pass(&self, camera: &Camera, lights: &[&dyn Light]) {
+        self.render_with_material(&DepthPrePassedMaterial(&self.material), camera, lights)
+    }
 }
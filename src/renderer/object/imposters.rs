@@ -4,16 +4,57 @@ use std::f32::consts::PI;
 
 const NO_VIEW_ANGLES: u32 = 8;
 
+/// The suffix added to the name of a [CpuModel] to get the name of the material containing the imposter texture, see [generate_imposter_texture].
+const IMPOSTER_MATERIAL_SUFFIX: &str = "_IMPOSTER";
+
+///
+/// Renders the imposter texture of the given [CpuModel] with the given lights, as in [Imposters::new], and stores it in the model,
+/// for example as a step in an asset pipeline when a model is loaded or before it is saved, so that the texture does not have to be rendered each time the imposters are used.
+/// Only the first level of the primitives with levels of detail is rendered, see [generate_lod_levels].
+///
+/// The texture is stored as the albedo texture of a material named after the model followed by `_IMPOSTER`, which is not used by any of the primitives,
+/// and contains the views of the model from the different angles side by side. Any previously stored imposter texture is replaced.
+/// Use [Imposters::from_cpu_model] to construct imposters from the stored texture.
+///
+pub fn generate_imposter_texture(
+    context: &Context,
+    cpu_model: &mut CpuModel,
+    lights: &[&dyn Light],
+    max_texture_size: u32,
+) -> Result<(), RendererError> {
+    let model = Model::<PhysicalMaterial>::new(context, cpu_model)?;
+    let aabb = model_aabb(cpu_model);
+    let mut material = ImpostersMaterial::new(context, aabb, &model, lights, max_texture_size);
+    let name = format!("{}{}", cpu_model.name, IMPOSTER_MATERIAL_SUFFIX);
+    let imposter_material = CpuMaterial {
+        name: name.clone(),
+        albedo_texture: Some(material.read_cpu_texture(&name)),
+        ..Default::default()
+    };
+    if let Some(m) = cpu_model.materials.iter_mut().find(|m| m.name == name) {
+        *m = imposter_material;
+    } else {
+        cpu_model.materials.push(imposter_material);
+    }
+    Ok(())
+}
+
 ///
 /// A level-of-detail technique to replace rendering of high-poly meshes.
 /// Should only be used where details cannot be seen, for example when the objects are far away.
 /// A set of objects are rendered from different angles into a set of textures and the textures are then
 /// rendered continuously instead of the expensive objects.
 ///
+/// Use [Imposters::set_lod_distance] and [Imposters::update_lod] to only render the imposters that are far away from the camera
+/// and render the objects at [Imposters::near_instances] instead, for example using an [InstancedMesh].
+///
 pub struct Imposters {
     context: Context,
     sprites: Sprites,
     material: ImpostersMaterial,
+    positions: Vec<Vec3>,
+    near_positions: Vec<Vec3>,
+    lod_distance: Option<f32>,
 }
 
 impl Imposters {
@@ -39,16 +80,109 @@ impl Imposters {
             context: context.clone(),
             sprites,
             material: ImpostersMaterial::new(context, aabb, objects, lights, max_texture_size),
+            positions: positions.to_vec(),
+            near_positions: Vec::new(),
+            lod_distance: None,
         }
     }
 
+    ///
+    /// Constructs a new [Imposters] from the imposter texture stored in the given [CpuModel], see [generate_imposter_texture].
+    /// The imposters are placed at the given positions.
+    ///
+    pub fn from_cpu_model(
+        context: &Context,
+        positions: &[Vec3],
+        cpu_model: &CpuModel,
+    ) -> Result<Self, RendererError> {
+        let name = format!("{}{}", cpu_model.name, IMPOSTER_MATERIAL_SUFFIX);
+        let texture = cpu_model
+            .materials
+            .iter()
+            .find(|m| m.name == name)
+            .and_then(|m| m.albedo_texture.as_ref())
+            .ok_or_else(|| RendererError::MissingImposterTexture(cpu_model.name.clone()))?;
+        let aabb = model_aabb(cpu_model);
+        let mut sprites = Sprites::new(context, positions, Some(vec3(0.0, 1.0, 0.0)));
+        sprites.set_transformation(get_sprite_transform(aabb));
+        Ok(Imposters {
+            context: context.clone(),
+            sprites,
+            material: ImpostersMaterial::from_cpu_texture(context, texture)?,
+            positions: positions.to_vec(),
+            near_positions: Vec::new(),
+            lod_distance: None,
+        })
+    }
+
     ///
     /// Set the positions of the imposters.
+    /// All imposters are rendered until the next call to [Imposters::update_lod].
     ///
     pub fn set_positions(&mut self, positions: &[Vec3]) {
+        self.positions = positions.to_vec();
+        self.near_positions.clear();
         self.sprites.set_centers(positions);
     }
 
+    ///
+    /// Set the distance from the camera within which the imposters are not rendered, because the objects should be rendered with full detail instead.
+    /// If `None`, all imposters are rendered regardless of the distance to the camera, which is the default.
+    /// The imposters to render are updated in the next call to [Imposters::update_lod].
+    ///
+    pub fn set_lod_distance(&mut self, distance: Option<f32>) {
+        self.lod_distance = distance;
+    }
+
+    ///
+    /// Updates which of the imposters are rendered based on the distance from the given camera to each imposter position, see [Imposters::set_lod_distance].
+    /// Should be called each frame, or at least each time the camera has moved.
+    ///
+    pub fn update_lod(&mut self, camera: &Camera) {
+        let near_positions = if let Some(distance) = self.lod_distance {
+            let camera_position = camera.position();
+            self.positions
+                .iter()
+                .filter(|p| p.distance2(*camera_position) < distance * distance)
+                .copied()
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if near_positions != self.near_positions {
+            let far_positions = self
+                .positions
+                .iter()
+                .filter(|p| !near_positions.contains(p))
+                .copied()
+                .collect::<Vec<_>>();
+            self.sprites.set_centers(&far_positions);
+            self.near_positions = near_positions;
+        }
+    }
+
+    ///
+    /// Returns the positions of the imposters which are not rendered because they are closer to the camera than the distance specified in [Imposters::set_lod_distance].
+    /// The objects should be rendered at these positions with full detail instead.
+    ///
+    pub fn near_positions(&self) -> &[Vec3] {
+        &self.near_positions
+    }
+
+    ///
+    /// Returns [Instances] with a translation to each of the [Imposters::near_positions], for example to use with [InstancedMesh::set_instances].
+    ///
+    pub fn near_instances(&self) -> Instances {
+        Instances {
+            transformations: self
+                .near_positions
+                .iter()
+                .map(|p| Mat4::from_translation(*p))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
     ///
     /// Render the imposter texture from the given objects with the given lights.
     /// Use this if you want to update the look of the imposters.
@@ -70,6 +204,23 @@ impl Imposters {
     }
 }
 
+///
+/// Returns the bounding box of the primitives in the given model which are rendered by a [Model].
+///
+fn model_aabb(cpu_model: &CpuModel) -> AxisAlignedBoundingBox {
+    let mut aabb = AxisAlignedBoundingBox::EMPTY;
+    for primitive in cpu_model.geometries.iter() {
+        if let CpuGeometry::Triangles(cpu_mesh) = &primitive.geometry {
+            if !is_lod_level(&primitive.name) {
+                let mut primitive_aabb = cpu_mesh.compute_aabb();
+                primitive_aabb.transform(&primitive.transformation);
+                aabb.expand_with_aabb(&primitive_aabb);
+            }
+        }
+    }
+    aabb
+}
+
 fn get_sprite_transform(aabb: AxisAlignedBoundingBox) -> Mat4 {
     if aabb.is_empty() {
         Mat4::identity()
@@ -149,6 +300,86 @@ impl ImpostersMaterial {
         m.update(aabb, objects, lights, max_texture_size);
         m
     }
+
+    ///
+    /// Constructs the material from a texture with the views side by side, see [ImpostersMaterial::read_cpu_texture].
+    /// Returns an error if the texture does not have RGBA u8 or f16 data or if the width is not a multiple of the number of views.
+    ///
+    pub fn from_cpu_texture(
+        context: &Context,
+        cpu_texture: &CpuTexture,
+    ) -> Result<Self, RendererError> {
+        if cpu_texture.width == 0 || !cpu_texture.width.is_multiple_of(NO_VIEW_ANGLES) {
+            Err(RendererError::InvalidImposterTexture(
+                cpu_texture.name.clone(),
+                format!(
+                    "the width {} is not a multiple of the {} views",
+                    cpu_texture.width, NO_VIEW_ANGLES
+                ),
+            ))?;
+        }
+        let width = cpu_texture.width / NO_VIEW_ANGLES;
+        let views = (0..NO_VIEW_ANGLES as usize)
+            .map(|i| {
+                let data = match &cpu_texture.data {
+                    TextureData::RgbaF16(data) => {
+                        TextureData::RgbaF16(view_data(data, cpu_texture.width, width, i))
+                    }
+                    TextureData::RgbaU8(data) => {
+                        TextureData::RgbaU8(view_data(data, cpu_texture.width, width, i))
+                    }
+                    _ => Err(RendererError::InvalidImposterTexture(
+                        cpu_texture.name.clone(),
+                        "the data must be RGBA u8 or f16".to_string(),
+                    ))?,
+                };
+                Ok(CpuTexture {
+                    data,
+                    width,
+                    mip_map_filter: None,
+                    wrap_s: Wrapping::ClampToEdge,
+                    wrap_t: Wrapping::ClampToEdge,
+                    ..cpu_texture.clone()
+                })
+            })
+            .collect::<Result<Vec<_>, RendererError>>()?;
+        Ok(Self {
+            context: context.clone(),
+            texture: Texture2DArray::new(context, &views.iter().collect::<Vec<_>>()),
+        })
+    }
+
+    ///
+    /// Returns the texture with the views side by side.
+    ///
+    pub fn read_cpu_texture(&mut self, name: &str) -> CpuTexture {
+        let (width, height) = (self.texture.width(), self.texture.height());
+        let mut data = vec![[f16::ZERO; 4]; (width * height * NO_VIEW_ANGLES) as usize];
+        for i in 0..NO_VIEW_ANGLES {
+            let layers = [i];
+            let view = self
+                .texture
+                .as_color_target(&layers, None)
+                .read::<[f32; 4]>();
+            for (j, color) in view.into_iter().enumerate() {
+                let (x, y) = (j as u32 % width, j as u32 / width);
+                data[(y * width * NO_VIEW_ANGLES + i * width + x) as usize] =
+                    color.map(f16::from_f32);
+            }
+        }
+        CpuTexture {
+            name: name.to_string(),
+            data: TextureData::RgbaF16(data),
+            width: width * NO_VIEW_ANGLES,
+            height,
+            min_filter: Interpolation::Linear,
+            mag_filter: Interpolation::Linear,
+            mip_map_filter: None,
+            wrap_s: Wrapping::ClampToEdge,
+            wrap_t: Wrapping::ClampToEdge,
+        }
+    }
+
     pub fn update(
         &mut self,
         aabb: AxisAlignedBoundingBox,
@@ -211,6 +442,16 @@ impl ImpostersMaterial {
     }
 }
 
+///
+/// Returns the data of the view with the given index in the data of a texture with the views side by side.
+///
+fn view_data<T: Clone>(data: &[T], texture_width: u32, view_width: u32, index: usize) -> Vec<T> {
+    let view_width = view_width as usize;
+    data.chunks(texture_width as usize)
+        .flat_map(|row| row[index * view_width..(index + 1) * view_width].to_vec())
+        .collect()
+}
+
 impl Material for ImpostersMaterial {
     fn id(&self) -> u16 {
         0b1u16 << 15 | 0b1101u16
@@ -234,8 +475,12 @@ impl Material for ImpostersMaterial {
     }
 
     fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
-        camera.tone_mapping.use_uniforms(program);
-        camera.color_mapping.use_uniforms(program);
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
         program.use_uniform("no_views", NO_VIEW_ANGLES as i32);
         program.use_uniform("view", camera.view());
         program.use_texture_array("tex", &self.texture);
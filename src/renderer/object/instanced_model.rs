@@ -19,6 +19,20 @@ impl<M: Material> InstancedModelPart<M> {
             .collect()
     }
 
+    ///
+    /// Returns the duration in seconds of the animation with the given name or `None` if this model part does not have an animation with that name.
+    ///
+    pub fn animation_duration(&self, animation_name: Option<&str>) -> Option<f32> {
+        super::model::animation_duration(&self.animations, animation_name)
+    }
+
+    ///
+    /// Returns the sorted times in seconds of all the key frames in the animation with the given name.
+    ///
+    pub fn key_frame_times(&self, animation_name: Option<&str>) -> Vec<f32> {
+        super::model::key_frame_times(&self.animations, animation_name)
+    }
+
     ///
     /// Specifies the animation to use when [Geometry::animate] is called. Use the [Self::animations] method to get a list of possible animations.
     ///
@@ -96,6 +110,12 @@ impl<M: Material + FromCpuMaterial + Clone + Default> InstancedModel<M> {
         instances: &Instances,
         cpu_model: &CpuModel,
     ) -> Result<Self, RendererError> {
+        span!(
+            INFO,
+            "load_instanced_model",
+            name = cpu_model.name.as_str(),
+            instances = instances.count()
+        );
         let materials = cpu_model
             .materials
             .iter()
@@ -104,6 +124,12 @@ impl<M: Material + FromCpuMaterial + Clone + Default> InstancedModel<M> {
         let mut gms = Vec::new();
         for primitive in cpu_model.geometries.iter() {
             if let CpuGeometry::Triangles(geometry) = &primitive.geometry {
+                let geometry = &with_missing_attributes(
+                    geometry,
+                    primitive
+                        .material_index
+                        .and_then(|i| cpu_model.materials.get(i)),
+                );
                 let material = if let Some(material_index) = primitive.material_index {
                     materials
                         .get(material_index)
@@ -161,6 +187,39 @@ impl<M: Material + FromCpuMaterial + Clone + Default> InstancedModel<M> {
     pub fn animate(&mut self, time: f32) {
         self.iter_mut().for_each(|m| m.animate(time));
     }
+
+    ///
+    /// Returns the duration in seconds of the animation with the given name, ie. the longest duration of the animation in any of the model parts,
+    /// or `None` if none of the model parts have an animation with that name.
+    ///
+    pub fn animation_duration(&self, animation_name: Option<&str>) -> Option<f32> {
+        self.iter()
+            .filter_map(|part| part.animation_duration(animation_name))
+            .reduce(f32::max)
+    }
+
+    ///
+    /// Returns the sorted times in seconds of all the key frames in the animation with the given name in any of the model parts,
+    /// for example to show the key frames in a timeline.
+    ///
+    pub fn key_frame_times(&self, animation_name: Option<&str>) -> Vec<f32> {
+        let mut times = self
+            .iter()
+            .flat_map(|part| part.key_frame_times(animation_name))
+            .collect::<Vec<_>>();
+        times.sort_by(|a, b| a.total_cmp(b));
+        times.dedup();
+        times
+    }
+
+    ///
+    /// Chooses the animation with the given name and evaluates it at the given time in seconds,
+    /// which makes it possible to scrub through an animation, for example using a timeline, independently of the time since start.
+    ///
+    pub fn set_time(&mut self, animation_name: Option<&str>, time: f32) {
+        self.choose_animation(animation_name);
+        self.animate(time);
+    }
 }
 
 impl<M: Material> std::ops::Deref for InstancedModel<M> {
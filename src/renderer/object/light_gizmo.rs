@@ -0,0 +1,413 @@
+use crate::renderer::*;
+
+///
+/// A light which can be visualized and moved by a [LightGizmo].
+/// Implemented for [PointLight], [SpotLight] and [DirectionalLight].
+///
+pub trait GizmoLight: Light {
+    ///
+    /// Returns the color of the lines visualizing the light.
+    ///
+    fn gizmo_color(&self) -> Srgba;
+
+    ///
+    /// Returns the line segments which visualize the extent of the light.
+    /// The anchor is where lights without a position, like a directional light, are visualized and the size is the size of the gizmo.
+    ///
+    fn gizmo_lines(&self, anchor: Vec3, size: f32) -> Vec<(Vec3, Vec3)>;
+
+    ///
+    /// Returns the positions of the handles which can be dragged to move the light.
+    ///
+    fn gizmo_handles(&self, anchor: Vec3, size: f32) -> Vec<Vec3>;
+
+    ///
+    /// Moves the handle with the given index, as returned by [GizmoLight::gizmo_handles], to the given position.
+    ///
+    fn move_gizmo_handle(&mut self, anchor: &mut Vec3, handle: usize, position: Vec3);
+}
+
+impl GizmoLight for PointLight {
+    fn gizmo_color(&self) -> Srgba {
+        self.color
+    }
+
+    fn gizmo_lines(&self, _anchor: Vec3, size: f32) -> Vec<(Vec3, Vec3)> {
+        let range = light_range(self.intensity, &self.attenuation).unwrap_or(size);
+        let mut lines = circle(self.position, vec3(range, 0.0, 0.0), vec3(0.0, range, 0.0));
+        lines.extend(circle(
+            self.position,
+            vec3(range, 0.0, 0.0),
+            vec3(0.0, 0.0, range),
+        ));
+        lines.extend(circle(
+            self.position,
+            vec3(0.0, range, 0.0),
+            vec3(0.0, 0.0, range),
+        ));
+        lines
+    }
+
+    fn gizmo_handles(&self, _anchor: Vec3, _size: f32) -> Vec<Vec3> {
+        vec![self.position]
+    }
+
+    fn move_gizmo_handle(&mut self, _anchor: &mut Vec3, _handle: usize, position: Vec3) {
+        self.position = position;
+    }
+}
+
+impl GizmoLight for SpotLight {
+    fn gizmo_color(&self) -> Srgba {
+        self.color
+    }
+
+    fn gizmo_lines(&self, _anchor: Vec3, size: f32) -> Vec<(Vec3, Vec3)> {
+        let range = light_range(self.intensity, &self.attenuation).unwrap_or(size);
+        let direction = self.direction.normalize();
+        let (right, up) = orthogonal_directions(direction);
+        let radius = range * self.cutoff.0.min(89f32.to_radians()).tan();
+        let center = self.position + direction * range;
+        let mut lines = circle(center, right * radius, up * radius);
+        for side in [right, up, -right, -up] {
+            lines.push((self.position, center + side * radius));
+        }
+        if self.shadow_map().is_some() {
+            lines.extend(frustum(self.shadow_matrix()));
+        }
+        lines
+    }
+
+    fn gizmo_handles(&self, _anchor: Vec3, size: f32) -> Vec<Vec3> {
+        vec![
+            self.position,
+            self.position + self.direction.normalize() * size,
+        ]
+    }
+
+    fn move_gizmo_handle(&mut self, _anchor: &mut Vec3, handle: usize, position: Vec3) {
+        if handle == 0 {
+            self.position = position;
+        } else if position != self.position {
+            self.direction = (position - self.position).normalize();
+        }
+    }
+}
+
+impl GizmoLight for DirectionalLight {
+    fn gizmo_color(&self) -> Srgba {
+        self.color
+    }
+
+    fn gizmo_lines(&self, anchor: Vec3, size: f32) -> Vec<(Vec3, Vec3)> {
+        let direction = self.direction.normalize();
+        let (right, up) = orthogonal_directions(direction);
+        let tip = anchor + direction * size;
+        let mut lines = vec![(anchor, tip)];
+        for side in [right, up, -right, -up] {
+            lines.push((tip, tip - direction * 0.2 * size + side * 0.1 * size));
+        }
+        if self.shadow_map().is_some() {
+            lines.extend(frustum(self.shadow_matrix()));
+        }
+        lines
+    }
+
+    fn gizmo_handles(&self, anchor: Vec3, size: f32) -> Vec<Vec3> {
+        vec![anchor, anchor + self.direction.normalize() * size]
+    }
+
+    fn move_gizmo_handle(&mut self, anchor: &mut Vec3, handle: usize, position: Vec3) {
+        if handle == 0 {
+            *anchor = position;
+        } else if position != *anchor {
+            self.direction = (position - *anchor).normalize();
+        }
+    }
+}
+
+///
+/// A debug/editor object which visualizes the extent of a light, ie. the range of a [PointLight], the cone of a [SpotLight],
+/// the direction of a [DirectionalLight] and the frustum of the shadow map if one has been generated.
+/// The light can be moved and aimed interactively by dragging the handles with the left mouse button, see [LightGizmo::handle_events].
+///
+/// The gizmo owns the light, use [LightGizmo::light] when rendering the scene and call [LightGizmo::update] after changing the light directly.
+///
+pub struct LightGizmo<L: GizmoLight> {
+    /// The light which is visualized.
+    pub light: L,
+    /// Where lights without a position, like a directional light, are visualized.
+    pub anchor: Vec3,
+    /// The size of the gizmo which is used as the length of the direction arrows and the size of the handles.
+    pub size: f32,
+    lines: Gm<InstancedMesh, ColorMaterial>,
+    handles: Gm<InstancedMesh, ColorMaterial>,
+    drag: Option<Drag>,
+}
+
+#[derive(Clone, Copy)]
+struct Drag {
+    handle: usize,
+    offset: Vec3,
+    plane_point: Vec3,
+    plane_normal: Vec3,
+}
+
+impl<L: GizmoLight> LightGizmo<L> {
+    ///
+    /// Creates a new gizmo for the given light, where directional lights are visualized at the given anchor.
+    ///
+    pub fn new(context: &Context, light: L, anchor: Vec3, size: f32) -> Self {
+        let mut gizmo = Self {
+            light,
+            anchor,
+            size,
+            lines: Gm::new(
+                InstancedMesh::new(context, &Instances::default(), &CpuMesh::cylinder(8)),
+                ColorMaterial::default(),
+            ),
+            handles: Gm::new(
+                InstancedMesh::new(context, &Instances::default(), &CpuMesh::sphere(8)),
+                ColorMaterial::default(),
+            ),
+            drag: None,
+        };
+        gizmo.update();
+        gizmo
+    }
+
+    ///
+    /// Updates the visualization, which is needed after changing the light, anchor or size directly.
+    ///
+    pub fn update(&mut self) {
+        let color = self.light.gizmo_color();
+        let radius = 0.01 * self.size;
+        let transformations = self
+            .light
+            .gizmo_lines(self.anchor, self.size)
+            .into_iter()
+            .map(|(a, b)| segment_transformation(a, b, radius))
+            .collect::<Vec<_>>();
+        self.lines.set_instances(&Instances {
+            colors: Some(vec![color; transformations.len()]),
+            transformations,
+            ..Default::default()
+        });
+
+        let handles = self.light.gizmo_handles(self.anchor, self.size);
+        let handle_radius = self.handle_radius();
+        let dragged = self.drag.map(|d| d.handle);
+        self.handles.set_instances(&Instances {
+            transformations: handles
+                .iter()
+                .map(|p| Mat4::from_translation(*p) * Mat4::from_scale(handle_radius))
+                .collect(),
+            colors: Some(
+                (0..handles.len())
+                    .map(|i| {
+                        if dragged == Some(i) {
+                            Srgba::new_opaque(255, 200, 0)
+                        } else {
+                            Srgba::WHITE
+                        }
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        });
+    }
+
+    ///
+    /// Returns whether or not a handle is currently being dragged.
+    ///
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    ///
+    /// Handles the events by dragging a handle, if one is pressed with the left mouse button, in the plane orthogonal to the view direction.
+    /// Dragging the handle at the position of the light moves the light (or the anchor of a directional light)
+    /// and dragging the handle in front of the light changes the direction of the light.
+    /// The events used by the gizmo are marked as handled, so call this before handling the events with for example a camera control.
+    /// Returns whether or not the light has changed.
+    ///
+    pub fn handle_events(&mut self, camera: &Camera, events: &mut [Event]) -> bool {
+        let mut change = false;
+        for event in events.iter_mut() {
+            match event {
+                Event::MousePress {
+                    button: MouseButton::Left,
+                    position,
+                    handled,
+                    ..
+                } if !*handled => {
+                    if let Some(drag) = self.pick(camera, *position) {
+                        self.drag = Some(drag);
+                        *handled = true;
+                        self.update();
+                    }
+                }
+                Event::MouseMotion {
+                    position, handled, ..
+                } if !*handled => {
+                    if let Some(drag) = self.drag {
+                        let origin = camera.position_at_pixel(*position);
+                        let direction = camera.view_direction_at_pixel(*position);
+                        if let Some(point) =
+                            ray_plane(origin, direction, drag.plane_point, drag.plane_normal)
+                        {
+                            self.light.move_gizmo_handle(
+                                &mut self.anchor,
+                                drag.handle,
+                                point + drag.offset,
+                            );
+                            self.update();
+                            change = true;
+                        }
+                        *handled = true;
+                    }
+                }
+                Event::MouseRelease {
+                    button: MouseButton::Left,
+                    handled,
+                    ..
+                } if self.drag.is_some() => {
+                    self.drag = None;
+                    *handled = true;
+                    self.update();
+                }
+                _ => {}
+            }
+        }
+        change
+    }
+
+    fn pick(&self, camera: &Camera, pixel: PhysicalPoint) -> Option<Drag> {
+        let origin = camera.position_at_pixel(pixel);
+        let direction = camera.view_direction_at_pixel(pixel);
+        // The handles are a bit easier to hit than they look
+        let radius = 1.5 * self.handle_radius();
+        let (handle, center) = self
+            .light
+            .gizmo_handles(self.anchor, self.size)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, center)| {
+                let to_center = center - origin;
+                let t = to_center.dot(direction);
+                let distance2 = to_center.magnitude2() - t * t;
+                (t >= 0.0 && distance2 <= radius * radius).then_some((t, i, center))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, i, center)| (i, center))?;
+        let plane_normal = camera.view_direction();
+        let point = ray_plane(origin, direction, center, plane_normal)?;
+        Some(Drag {
+            handle,
+            offset: center - point,
+            plane_point: center,
+            plane_normal,
+        })
+    }
+
+    fn handle_radius(&self) -> f32 {
+        0.05 * self.size
+    }
+}
+
+impl<'a, L: GizmoLight> IntoIterator for &'a LightGizmo<L> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::array::IntoIter<&'a dyn Object, 2>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        [&self.lines as &dyn Object, &self.handles as &dyn Object].into_iter()
+    }
+}
+
+///
+/// Returns the distance at which the attenuated intensity falls below 1/256, which is the smallest visible change for 8-bit colors,
+/// or `None` if the light is never attenuated.
+///
+fn light_range(intensity: f32, attenuation: &Attenuation) -> Option<f32> {
+    let c = attenuation.constant - 256.0 * intensity;
+    let l = attenuation.linear;
+    let q = attenuation.quadratic;
+    let distance = if q > 0.0 {
+        Some((-l + (l * l - 4.0 * q * c).max(0.0).sqrt()) / (2.0 * q))
+    } else if l > 0.0 {
+        Some(-c / l)
+    } else {
+        None
+    }
+    .map(|d| d.max(0.0));
+    match (distance, attenuation.range) {
+        (Some(d), Some(r)) => Some(d.min(r)),
+        (d, r) => d.or(r),
+    }
+}
+
+fn orthogonal_directions(direction: Vec3) -> (Vec3, Vec3) {
+    let other = if direction.x.abs() > 0.9 {
+        vec3(0.0, 1.0, 0.0)
+    } else {
+        vec3(1.0, 0.0, 0.0)
+    };
+    let right = direction.cross(other).normalize();
+    (right, right.cross(direction))
+}
+
+fn circle(center: Vec3, a: Vec3, b: Vec3) -> Vec<(Vec3, Vec3)> {
+    const SEGMENTS: u32 = 32;
+    let point = |i: u32| {
+        let angle = 2.0 * std::f32::consts::PI * i as f32 / SEGMENTS as f32;
+        center + a * angle.cos() + b * angle.sin()
+    };
+    (0..SEGMENTS).map(|i| (point(i), point(i + 1))).collect()
+}
+
+fn frustum(shadow_matrix: Mat4) -> Vec<(Vec3, Vec3)> {
+    let inverse = shadow_matrix.invert().unwrap_or(Mat4::identity());
+    let corner = |i: usize| {
+        let p = inverse
+            * vec4(
+                (i & 1) as f32,
+                ((i >> 1) & 1) as f32,
+                ((i >> 2) & 1) as f32,
+                1.0,
+            );
+        p.truncate() / p.w
+    };
+    let mut lines = Vec::new();
+    for i in 0..8 {
+        for bit in [1, 2, 4] {
+            if i & bit == 0 {
+                lines.push((corner(i), corner(i | bit)));
+            }
+        }
+    }
+    lines
+}
+
+fn segment_transformation(a: Vec3, b: Vec3, radius: f32) -> Mat4 {
+    let d = b - a;
+    let length = d.magnitude();
+    let rotation = if length > 0.0 {
+        Mat4::from(Quat::from_arc(
+            vec3(1.0, 0.0, 0.0),
+            d / length,
+            Some(vec3(0.0, 1.0, 0.0)),
+        ))
+    } else {
+        Mat4::identity()
+    };
+    Mat4::from_translation(a) * rotation * Mat4::from_nonuniform_scale(length, radius, radius)
+}
+
+fn ray_plane(origin: Vec3, direction: Vec3, point: Vec3, normal: Vec3) -> Option<Vec3> {
+    let denominator = direction.dot(normal);
+    if denominator.abs() < 1e-6 {
+        return None;
+    }
+    let t = (point - origin).dot(normal) / denominator;
+    (t >= 0.0).then(|| origin + direction * t)
+}
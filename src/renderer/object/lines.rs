@@ -0,0 +1,401 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// The dash pattern of [Lines], where the lengths are in world space units along the lines, so the dashes stay in place when the camera moves.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineDashes {
+    /// The length of each dash.
+    pub dash_length: f32,
+    /// The length of the gap between two dashes.
+    pub gap_length: f32,
+    /// The distance along the lines where the first dash starts, which can be animated to make the dashes move along the lines.
+    pub offset: f32,
+}
+
+///
+/// A set of lines in world space with a constant width in pixels, for example debug paths, graphs and edges,
+/// which consists of independent line segments (see [Lines::new]) and connected polylines (see [Lines::new_polyline]).
+///
+/// The lines are rendered as quads that are expanded in screen space, with round caps and joins and antialiased edges.
+/// The color of a line is the [Lines::color] multiplied with the colors of the vertices, see [Lines::set_colors],
+/// and the lines can be dashed, see [Lines::dashes].
+/// The lines can also be rendered with a material that only requires positions and colors, for example [ColorMaterial] in a [Gm],
+/// in which case the lines are rendered as quads without round caps, antialiasing and dashes.
+///
+pub struct Lines {
+    context: Context,
+    /// The width of the lines in pixels. The default is 2.0.
+    pub width: f32,
+    /// The color of the lines, which is multiplied with the colors of the vertices. The default is white.
+    pub color: Srgba,
+    /// The dash pattern of the lines, or solid lines if `None`. The default is `None`.
+    pub dashes: Option<LineDashes>,
+    /// Whether the lines are hidden behind other objects. If false, the lines are rendered on top of everything else. The default is true.
+    pub depth_test: bool,
+    position_buffer: VertexBuffer,
+    start_buffer: InstanceBuffer,
+    end_buffer: InstanceBuffer,
+    distance_buffer: InstanceBuffer,
+    color_buffers: Option<(InstanceBuffer, InstanceBuffer)>,
+    segments: Vec<(usize, usize)>,
+    positions: Vec<Vec3>,
+    transformation: Mat4,
+    aabb: AxisAlignedBoundingBox,
+}
+
+impl Lines {
+    ///
+    /// Creates new lines consisting of independent line segments between the given pairs of positions.
+    ///
+    pub fn new(context: &Context, segments: &[(Vec3, Vec3)]) -> Self {
+        let positions = segments.iter().flat_map(|(a, b)| [*a, *b]).collect();
+        Self::new_internal(
+            context,
+            positions,
+            (0..segments.len()).map(|i| (2 * i, 2 * i + 1)).collect(),
+        )
+    }
+
+    ///
+    /// Creates new lines consisting of one connected polyline through the given positions.
+    /// The dashes continue across the joins between the segments of the polyline.
+    ///
+    pub fn new_polyline(context: &Context, positions: &[Vec3]) -> Self {
+        Self::new_internal(
+            context,
+            positions.to_vec(),
+            (1..positions.len()).map(|i| (i - 1, i)).collect(),
+        )
+    }
+
+    fn new_internal(
+        context: &Context,
+        positions: Vec<Vec3>,
+        segments: Vec<(usize, usize)>,
+    ) -> Self {
+        // Each segment is a quad where x is the position along the segment and y is the side of the segment
+        let position_buffer = VertexBuffer::new_with_data(
+            context,
+            &[
+                vec2(0.0, -1.0),
+                vec2(1.0, -1.0),
+                vec2(1.0, 1.0),
+                vec2(1.0, 1.0),
+                vec2(0.0, 1.0),
+                vec2(0.0, -1.0),
+            ],
+        );
+        let mut lines = Self {
+            context: context.clone(),
+            width: 2.0,
+            color: Srgba::WHITE,
+            dashes: None,
+            depth_test: true,
+            position_buffer,
+            start_buffer: InstanceBuffer::new(context),
+            end_buffer: InstanceBuffer::new(context),
+            distance_buffer: InstanceBuffer::new(context),
+            color_buffers: None,
+            segments,
+            positions: Vec::new(),
+            transformation: Mat4::identity(),
+            aabb: AxisAlignedBoundingBox::EMPTY,
+        };
+        lines.set_positions(&positions);
+        lines
+    }
+
+    ///
+    /// Returns the number of line segments.
+    ///
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    ///
+    /// Returns the local to world transformation applied to the lines.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to the lines.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+        self.update_aabb();
+    }
+
+    ///
+    /// Moves the vertices of the lines to the given positions, for example to animate a graph, while keeping which vertices are connected.
+    /// The positions are given in the same order as when the lines were created, ie. two positions per segment for lines created by [Lines::new]
+    /// and one position per vertex for a polyline.
+    ///
+    /// # Panic
+    /// Will panic if the number of positions does not match the number of vertices.
+    ///
+    pub fn set_positions(&mut self, positions: &[Vec3]) {
+        assert!(
+            self.positions.is_empty() || positions.len() == self.positions.len(),
+            "the number of positions must match the number of vertices of the lines"
+        );
+        self.positions = positions.to_vec();
+        // The distance along the lines is used for the dashes, which continue through connected segments
+        let mut distances = Vec::with_capacity(self.segments.len());
+        let mut previous: Option<(usize, f32)> = None;
+        for (a, b) in self.segments.iter() {
+            let start = match previous {
+                Some((end, distance)) if end == *a => distance,
+                _ => 0.0,
+            };
+            let end = start + positions[*a].distance(positions[*b]);
+            distances.push(vec2(start, end));
+            previous = Some((*b, end));
+        }
+        self.start_buffer.fill(
+            &self
+                .segments
+                .iter()
+                .map(|(a, _)| positions[*a])
+                .collect::<Vec<_>>(),
+        );
+        self.end_buffer.fill(
+            &self
+                .segments
+                .iter()
+                .map(|(_, b)| positions[*b])
+                .collect::<Vec<_>>(),
+        );
+        self.distance_buffer.fill(&distances);
+        self.update_aabb();
+    }
+
+    ///
+    /// Set a color for each vertex, in the same order as the positions, which is multiplied with the [Lines::color]
+    /// and interpolated along each segment. Use an empty slice to remove the colors.
+    ///
+    /// # Panic
+    /// Will panic if the number of colors does not match the number of vertices.
+    ///
+    pub fn set_colors(&mut self, colors: &[Srgba]) {
+        self.color_buffers = if colors.is_empty() {
+            None
+        } else {
+            assert_eq!(
+                colors.len(),
+                self.positions.len(),
+                "the number of colors must match the number of vertices of the lines"
+            );
+            let colors = colors
+                .iter()
+                .map(|c| c.to_linear_srgb())
+                .collect::<Vec<_>>();
+            let segment_colors = |end: fn(&(usize, usize)) -> usize| {
+                InstanceBuffer::new_with_data(
+                    &self.context,
+                    &self
+                        .segments
+                        .iter()
+                        .map(|s| colors[end(s)])
+                        .collect::<Vec<_>>(),
+                )
+            };
+            Some((segment_colors(|s| s.0), segment_colors(|s| s.1)))
+        };
+    }
+
+    fn update_aabb(&mut self) {
+        let mut aabb = AxisAlignedBoundingBox::new_with_positions(&self.positions);
+        aabb.transform(&self.transformation);
+        self.aabb = aabb;
+    }
+}
+
+impl<'a> IntoIterator for &'a Lines {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Lines {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        attributes: FragmentAttributes,
+    ) {
+        if self.segments.is_empty() {
+            return;
+        }
+        let viewport = camera.viewport();
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("transformation", self.transformation);
+        program.use_uniform(
+            "viewport",
+            vec4(
+                viewport.x as f32,
+                viewport.y as f32,
+                viewport.width as f32,
+                viewport.height as f32,
+            ),
+        );
+        program.use_uniform("halfWidth", 0.5 * self.width.max(0.0));
+        program.use_vertex_attribute("position", &self.position_buffer);
+        program.use_instance_attribute("start", &self.start_buffer);
+        program.use_instance_attribute("end", &self.end_buffer);
+        if program.requires_attribute("distance") {
+            program.use_instance_attribute("distance", &self.distance_buffer);
+        }
+        if attributes.color {
+            if let Some((start_colors, end_colors)) = &self.color_buffers {
+                program.use_instance_attribute("start_color", start_colors);
+                program.use_instance_attribute("end_color", end_colors);
+            }
+        }
+        program.draw_arrays_instanced(render_states, viewport, 6, self.segments.len() as u32)
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        format!(
+            "{}{}",
+            if required_attributes.color && self.color_buffers.is_some() {
+                "#define USE_VERTEX_COLORS\n"
+            } else {
+                ""
+            },
+            include_str!("shaders/lines.vert")
+        )
+    }
+
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 11;
+        if required_attributes.color && self.color_buffers.is_some() {
+            id |= 0b1u16;
+        }
+        id
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.aabb
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
+
+impl Object for Lines {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        render_with_material(
+            &self.context,
+            camera,
+            self,
+            LinesMaterial { lines: self },
+            lights,
+        );
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct LinesMaterial<'a> {
+    lines: &'a Lines,
+}
+
+impl Material for LinesMaterial<'_> {
+    fn id(&self) -> u16 {
+        let mut id = 0b1u16 << 15 | 0b1u16 << 12 | 0b100u16;
+        if self.lines.dashes.is_some() {
+            id |= 0b1u16;
+        }
+        id
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}{}{}",
+            if self.lines.dashes.is_some() {
+                "#define USE_DASHES\n"
+            } else {
+                ""
+            },
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/lines.frag")
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            color: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        program.use_uniform("lineColor", self.lines.color.to_linear_srgb());
+        if let Some(dashes) = self.lines.dashes {
+            program.use_uniform(
+                "dashes",
+                vec3(
+                    dashes.dash_length.max(0.0),
+                    dashes.gap_length.max(0.0),
+                    dashes.offset,
+                ),
+            );
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        // The depth is not written, since the antialiased edges would otherwise hide the overlapping segments of a polyline
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: if self.lines.depth_test {
+                DepthTest::LessOrEqual
+            } else {
+                DepthTest::Always
+            },
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::None,
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
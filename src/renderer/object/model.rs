@@ -6,10 +6,24 @@ pub use three_d_asset::Model as CpuModel;
 ///
 pub struct ModelPart<M: Material> {
     gm: Gm<Mesh, M>,
+    name: String,
     animations: Vec<KeyFrameAnimation>,
+    animation: Option<KeyFrameAnimation>,
+    root_motion: bool,
+    primitive_index: usize,
+    material_index: Option<usize>,
+    default_material: Option<M>,
+    variant_materials: Vec<(usize, usize, M)>,
 }
 
 impl<M: Material> ModelPart<M> {
+    ///
+    /// Returns the name of this model part, for example the name of the node in a glTF file.
+    ///
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     ///
     /// Returns a list of unique names for the animations for this model part. Use these names as input to [Self::choose_animation].
     ///
@@ -20,6 +34,20 @@ impl<M: Material> ModelPart<M> {
             .collect()
     }
 
+    ///
+    /// Returns the duration in seconds of the animation with the given name or `None` if this model part does not have an animation with that name.
+    ///
+    pub fn animation_duration(&self, animation_name: Option<&str>) -> Option<f32> {
+        animation_duration(&self.animations, animation_name)
+    }
+
+    ///
+    /// Returns the sorted times in seconds of all the key frames in the animation with the given name.
+    ///
+    pub fn key_frame_times(&self, animation_name: Option<&str>) -> Vec<f32> {
+        key_frame_times(&self.animations, animation_name)
+    }
+
     ///
     /// Specifies the animation to use when [Geometry::animate] is called. Use the [Self::animations] method to get a list of possible animations.
     ///
@@ -30,7 +58,64 @@ impl<M: Material> ModelPart<M> {
             .find(|a| animation_name == a.name.as_deref())
             .cloned()
         {
-            self.set_animation(move |time| animation.transformation(time));
+            let root_motion = self.root_motion;
+            self.animation = Some(animation.clone());
+            self.set_animation(move |time| animation_transformation(&animation, time, root_motion));
+        }
+    }
+
+    ///
+    /// Specifies whether the translation of the root node is removed from the animation, so that the model part is animated in place,
+    /// and instead is available as a per-frame movement using [Self::root_motion].
+    /// This makes it possible for a character controller to move the model according to an authored animation, for example a walk cycle.
+    /// The root node is the first node from the root of the scene that has translation key frames.
+    ///
+    pub fn set_root_motion(&mut self, enabled: bool) {
+        self.root_motion = enabled;
+        if let Some(animation) = self.animation.take() {
+            self.choose_animation(animation.name.as_deref());
+        }
+    }
+
+    ///
+    /// Returns the movement of the root node of the current animation from the first to the second time in seconds in the space of the model,
+    /// for example the time of the previous frame and the time of the current frame. Looping animations continue the movement for each loop.
+    /// Returns a zero vector if the animation does not translate any node.
+    /// See [Self::set_root_motion] for how to remove the movement from the animation itself.
+    ///
+    pub fn root_motion(&self, from_time: f32, to_time: f32) -> Vec3 {
+        let Some(animation) = &self.animation else {
+            return vec3(0.0, 0.0, 0.0);
+        };
+        let Some(root) = root_index(animation) else {
+            return vec3(0.0, 0.0, 0.0);
+        };
+        let mut parent = self.gm.transformation();
+        for (transformation, key_frames) in animation.key_frames[..root].iter() {
+            parent = parent * transformation * key_frames.transformation(to_time);
+        }
+        let (transformation, key_frames) = &animation.key_frames[root];
+        (parent * transformation).transform_vector(
+            root_translation(key_frames, to_time) - root_translation(key_frames, from_time),
+        )
+    }
+
+    ///
+    /// Attaches the mesh to this model part, so that the mesh follows the transformation and the current animation of this model part,
+    /// for example a sword in the hand of a character. The offset is the transformation of the mesh relative to this model part.
+    ///
+    /// The mesh has to be animated with the same time as this model part, for example by calling [Geometry::animate] on both each frame.
+    /// Call this method again after choosing another animation using [Self::choose_animation].
+    ///
+    pub fn attach(&self, mesh: &mut Mesh, offset: Mat4) {
+        mesh.set_transformation(self.gm.transformation());
+        if let Some(animation) = self.animation.clone() {
+            let root_motion = self.root_motion;
+            mesh.set_animation(move |time| {
+                animation_transformation(&animation, time, root_motion) * offset
+            });
+        } else {
+            mesh.set_animation(move |_| offset);
         }
     }
 }
@@ -73,7 +158,11 @@ impl<'a, M: Material> IntoIterator for &'a ModelPart<M> {
 ///
 /// A 3D model consisting of a set of [Gm]s with [Mesh]es as the geometries and a [material] type specified by the generic parameter.
 ///
-pub struct Model<M: Material>(Vec<ModelPart<M>>);
+pub struct Model<M: Material> {
+    parts: Vec<ModelPart<M>>,
+    variants: Vec<String>,
+    variant: Option<usize>,
+}
 
 impl<'a, M: Material> IntoIterator for &'a Model<M> {
     type Item = &'a dyn Object;
@@ -93,39 +182,94 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
     /// a [material] type specified by the generic parameter which implement [FromCpuMaterial] (constructed from the [CpuMaterial]s in the [CpuModel]).
     ///
     pub fn new(context: &Context, cpu_model: &CpuModel) -> Result<Self, RendererError> {
+        Self::new_with_variants(context, cpu_model, &MaterialVariants::default())
+    }
+
+    ///
+    /// Constructs a [Model] from a [CpuModel] in the same way as [Model::new] together with the given material variants,
+    /// for example the color options of a product, which can then be chosen using [Model::set_variant].
+    ///
+    pub fn new_with_variants(
+        context: &Context,
+        cpu_model: &CpuModel,
+        variants: &MaterialVariants,
+    ) -> Result<Self, RendererError> {
+        span!(
+            INFO,
+            "load_model",
+            name = cpu_model.name.as_str(),
+            meshes = cpu_model.geometries.len()
+        );
         let materials = cpu_model
             .materials
             .iter()
             .map(|m| M::from_cpu_material(context, m))
             .collect::<Vec<_>>();
+        let material = |material_index: usize, primitive_name: &str| {
+            materials.get(material_index).cloned().ok_or_else(|| {
+                RendererError::MissingMaterial(
+                    material_index.to_string(),
+                    primitive_name.to_string(),
+                )
+            })
+        };
         let mut gms = Vec::new();
-        for primitive in cpu_model.geometries.iter() {
+        for (i, primitive) in cpu_model.geometries.iter().enumerate() {
+            if is_lod_level(&primitive.name) {
+                continue;
+            }
             if let CpuGeometry::Triangles(geometry) = &primitive.geometry {
-                let material = if let Some(material_index) = primitive.material_index {
-                    materials
-                        .get(material_index)
-                        .ok_or_else(|| {
-                            RendererError::MissingMaterial(
-                                material_index.to_string(),
-                                primitive.name.clone(),
-                            )
-                        })?
-                        .clone()
+                let geometry = &with_missing_attributes(
+                    geometry,
+                    primitive
+                        .material_index
+                        .and_then(|i| cpu_model.materials.get(i)),
+                );
+                let default_material = if let Some(material_index) = primitive.material_index {
+                    material(material_index, &primitive.name)?
                 } else {
                     M::default()
                 };
+                let variant_materials = variants
+                    .mappings
+                    .get(i)
+                    .map(|mappings| {
+                        mappings
+                            .iter()
+                            .map(|(variant, material_index)| {
+                                Ok((
+                                    *variant,
+                                    *material_index,
+                                    material(*material_index, &primitive.name)?,
+                                ))
+                            })
+                            .collect::<Result<Vec<_>, RendererError>>()
+                    })
+                    .transpose()?
+                    .unwrap_or_default();
                 let mut gm = Gm {
                     geometry: Mesh::new(context, geometry),
-                    material,
+                    material: default_material.clone(),
                 };
                 gm.set_transformation(primitive.transformation);
                 gms.push(ModelPart {
                     gm,
+                    name: primitive.name.clone(),
                     animations: primitive.animations.clone(),
+                    animation: None,
+                    root_motion: false,
+                    primitive_index: i,
+                    material_index: primitive.material_index,
+                    default_material: (!variant_materials.is_empty()).then_some(default_material),
+                    variant_materials,
                 });
             }
         }
-        let mut model = Self(gms);
+        let mut model = Self {
+            parts: gms,
+            variants: variants.names.clone(),
+            variant: None,
+        };
         if let Some(animation_name) = model.animations().first().cloned() {
             model.choose_animation(animation_name.as_deref());
         }
@@ -137,7 +281,7 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
     ///
     pub fn animations(&self) -> Vec<Option<String>> {
         let mut set = std::collections::HashSet::new();
-        for model_part in self.0.iter() {
+        for model_part in self.parts.iter() {
             set.extend(model_part.animations());
         }
         set.into_iter().collect()
@@ -147,7 +291,7 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
     /// Specifies the animation to use when [Geometry::animate] is called. Use the [Self::animations] method to get a list of possible animations.
     ///
     pub fn choose_animation(&mut self, animation_name: Option<&str>) {
-        for part in self.0.iter_mut() {
+        for part in self.parts.iter_mut() {
             part.choose_animation(animation_name);
         }
     }
@@ -158,17 +302,471 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
     pub fn animate(&mut self, time: f32) {
         self.iter_mut().for_each(|m| m.animate(time));
     }
+
+    ///
+    /// Returns the duration in seconds of the animation with the given name, ie. the longest duration of the animation in any of the model parts,
+    /// or `None` if none of the model parts have an animation with that name.
+    ///
+    pub fn animation_duration(&self, animation_name: Option<&str>) -> Option<f32> {
+        self.iter()
+            .filter_map(|part| part.animation_duration(animation_name))
+            .reduce(f32::max)
+    }
+
+    ///
+    /// Returns the sorted times in seconds of all the key frames in the animation with the given name in any of the model parts,
+    /// for example to show the key frames in a timeline.
+    ///
+    pub fn key_frame_times(&self, animation_name: Option<&str>) -> Vec<f32> {
+        let mut times = self
+            .iter()
+            .flat_map(|part| part.key_frame_times(animation_name))
+            .collect::<Vec<_>>();
+        times.sort_by(|a, b| a.total_cmp(b));
+        times.dedup();
+        times
+    }
+
+    ///
+    /// Chooses the animation with the given name and evaluates it at the given time in seconds,
+    /// which makes it possible to scrub through an animation, for example using a timeline, independently of the time since start.
+    ///
+    pub fn set_time(&mut self, animation_name: Option<&str>, time: f32) {
+        self.choose_animation(animation_name);
+        self.animate(time);
+    }
+
+    ///
+    /// Specifies whether the translation of the root node is removed from the animations of all the model parts, see [ModelPart::set_root_motion].
+    ///
+    pub fn set_root_motion(&mut self, enabled: bool) {
+        self.iter_mut()
+            .for_each(|part| part.set_root_motion(enabled));
+    }
+
+    ///
+    /// Returns the movement of the root node of the current animation from the first to the second time in seconds, see [ModelPart::root_motion].
+    /// All the model parts usually share the same root node, so the movement of the first model part which has a moving root node is returned.
+    ///
+    pub fn root_motion(&self, from_time: f32, to_time: f32) -> Vec3 {
+        self.iter()
+            .map(|part| part.root_motion(from_time, to_time))
+            .find(|motion| *motion != vec3(0.0, 0.0, 0.0))
+            .unwrap_or(vec3(0.0, 0.0, 0.0))
+    }
+
+    ///
+    /// Attaches the mesh to the model part with the given name using [ModelPart::attach], for example to put a hat on the head of a character.
+    /// Returns an error if the model does not contain a part with the given name.
+    ///
+    pub fn attach(
+        &self,
+        part_name: &str,
+        mesh: &mut Mesh,
+        offset: Mat4,
+    ) -> Result<(), RendererError> {
+        self.iter()
+            .find(|part| part.name() == part_name)
+            .ok_or_else(|| RendererError::MissingModelPart(part_name.to_string()))?
+            .attach(mesh, offset);
+        Ok(())
+    }
+
+    ///
+    /// Returns the names of the material variants of this model, see [Model::new_with_variants]. Use these names as input to [Self::set_variant].
+    ///
+    pub fn variants(&self) -> &[String] {
+        &self.variants
+    }
+
+    ///
+    /// Returns the name of the currently chosen material variant or `None` if the default materials are used.
+    ///
+    pub fn variant(&self) -> Option<&str> {
+        self.variant.map(|i| self.variants[i].as_str())
+    }
+
+    ///
+    /// Applies the materials of the material variant with the given name to the model parts, for example `model.set_variant("red")`.
+    /// The model parts which do not have a material for the variant use their default material.
+    /// Returns an error if the model does not have a material variant with the given name.
+    ///
+    pub fn set_variant(&mut self, variant_name: &str) -> Result<(), RendererError> {
+        let variant = self
+            .variants
+            .iter()
+            .position(|name| name == variant_name)
+            .ok_or_else(|| RendererError::MissingMaterialVariant(variant_name.to_string()))?;
+        self.variant = Some(variant);
+        self.apply_variant();
+        Ok(())
+    }
+
+    ///
+    /// Applies the default materials to the model parts, ie. the materials used before a material variant was chosen using [Self::set_variant].
+    ///
+    pub fn clear_variant(&mut self) {
+        self.variant = None;
+        self.apply_variant();
+    }
+
+    ///
+    /// Calls the callback for each material of the model parts with the index into [CpuModel::materials] of the material, or `None` if the
+    /// model part does not have a material and uses the default material, for example to change the materials based on information
+    /// which is not available in the [CpuMaterial]s. This includes the materials of the material variants which are not currently applied.
+    ///
+    pub fn update_materials(&mut self, mut callback: impl FnMut(Option<usize>, &mut M)) {
+        for part in self.parts.iter_mut() {
+            callback(part.material_index, &mut part.gm.material);
+            if let Some(default_material) = &mut part.default_material {
+                callback(part.material_index, default_material);
+            }
+            for (_, material_index, material) in part.variant_materials.iter_mut() {
+                callback(Some(*material_index), material);
+            }
+        }
+    }
+
+    ///
+    /// Sets the secondary uv coordinates of the meshes of the model parts, see [Mesh::set_secondary_uvs], for example the lightmap or ambient occlusion
+    /// uv coordinates parsed from the `TEXCOORD_1` attribute of a glTF file using [SecondaryUvs::from_gltf].
+    /// The model parts which do not have secondary uv coordinates in the list are not changed.
+    /// Returns an error if the number of secondary uv coordinates does not match the number of vertices of a model part.
+    ///
+    pub fn set_secondary_uvs(&mut self, secondary_uvs: &SecondaryUvs) -> Result<(), RendererError> {
+        for part in self.parts.iter_mut() {
+            if let Some(uvs) = secondary_uvs
+                .uvs
+                .get(part.primitive_index)
+                .and_then(|uvs| uvs.as_ref())
+            {
+                if uvs.len() as u32 != part.gm.geometry.vertex_count() {
+                    Err(RendererError::InvalidSecondaryUvs(part.name.clone()))?;
+                }
+                part.gm.geometry.set_secondary_uvs(uvs);
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_variant(&mut self) {
+        let variant = self.variant;
+        for part in self.parts.iter_mut() {
+            if let Some(default_material) = &part.default_material {
+                part.gm.material = variant
+                    .and_then(|variant| {
+                        part.variant_materials
+                            .iter()
+                            .find(|(v, _, _)| *v == variant)
+                            .map(|(_, _, material)| material)
+                    })
+                    .unwrap_or(default_material)
+                    .clone();
+            }
+        }
+    }
+}
+
+impl Model<PhysicalMaterial> {
+    ///
+    /// Sets the [PhysicalMaterial::shading_model] of each of the materials of the model parts to the shading model at the index into [CpuModel::materials]
+    /// of the material in the given list, for example the shading models parsed from a glTF file using [ShadingModel::from_gltf],
+    /// which makes the materials using the [KHR_materials_unlit](https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_materials_unlit/README.md)
+    /// extension unlit. The materials that are not in the list are not changed.
+    ///
+    pub fn set_shading_models(&mut self, shading_models: &[ShadingModel]) {
+        self.update_materials(|material_index, material| {
+            if let Some(shading_model) = material_index.and_then(|i| shading_models.get(i)) {
+                material.shading_model = *shading_model;
+            }
+        });
+    }
+}
+
+///
+/// The material variants of a model, for example the color options of a product, as defined by the
+/// [KHR_materials_variants](https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_materials_variants/README.md) glTF extension.
+/// Use [Model::new_with_variants] to construct a model with material variants and [Model::set_variant] to choose a variant.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MaterialVariants {
+    /// The names of the variants.
+    pub names: Vec<String>,
+    /// For each primitive in [CpuModel::geometries], a list of pairs of an index into [MaterialVariants::names] and
+    /// the index into [CpuModel::materials] of the material that should be used for that variant.
+    pub mappings: Vec<Vec<(usize, usize)>>,
+}
+
+#[cfg(feature = "gltf-extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gltf-extensions")))]
+impl MaterialVariants {
+    ///
+    /// Parses the material variants from the given raw assets, which must contain the glTF file at the given path,
+    /// in the same way as when deserializing a [CpuModel] from the raw assets. Call this before deserializing the [CpuModel],
+    /// since deserializing removes the glTF file from the raw assets.
+    ///
+    /// ```no_run
+    /// # use three_d::*;
+    /// # let context: Context = unimplemented!();
+    /// let mut loaded = three_d_asset::io::load(&["product.glb"]).unwrap();
+    /// let variants = MaterialVariants::deserialize(&loaded, "product.glb").unwrap();
+    /// let mut model = Model::<PhysicalMaterial>::new_with_variants(
+    ///     &context,
+    ///     &loaded.deserialize("product.glb").unwrap(),
+    ///     &variants,
+    /// )
+    /// .unwrap();
+    /// model.set_variant("red").unwrap();
+    /// ```
+    ///
+    pub fn deserialize(
+        raw_assets: &three_d_asset::io::RawAssets,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, RendererError> {
+        Self::from_gltf(raw_assets.get(path)?)
+    }
+
+    ///
+    /// Parses the material variants from the given bytes of a glTF (`.gltf`) or binary glTF (`.glb`) file.
+    /// Returns empty material variants if the file does not use the extension.
+    ///
+    pub fn from_gltf(bytes: &[u8]) -> Result<Self, RendererError> {
+        let document = gltf::Gltf::from_slice(bytes)?.document;
+        let names = document
+            .variants()
+            .map(|variants| variants.map(|v| v.name().to_string()).collect())
+            .unwrap_or_default();
+        let mut mappings = Vec::new();
+        // Visit the primitives in the same order as they are added to the geometries of a CpuModel
+        fn visit(node: gltf::Node, mappings: &mut Vec<Vec<(usize, usize)>>) {
+            let transformation = Mat4::from(node.transform().matrix());
+            if transformation.determinant() == 0.0 {
+                return;
+            }
+            if let Some(mesh) = node.mesh() {
+                for primitive in mesh.primitives() {
+                    if primitive.get(&gltf::Semantic::Positions).is_some() {
+                        mappings.push(
+                            primitive
+                                .mappings()
+                                .filter_map(|mapping| {
+                                    mapping.material().index().map(|material| {
+                                        mapping
+                                            .variants()
+                                            .iter()
+                                            .map(move |variant| (*variant as usize, material))
+                                    })
+                                })
+                                .flatten()
+                                .collect(),
+                        );
+                    }
+                }
+            }
+            for child in node.children() {
+                visit(child, mappings);
+            }
+        }
+        if let Some(scene) = document.scenes().next() {
+            for node in scene.nodes() {
+                visit(node, &mut mappings);
+            }
+        }
+        Ok(Self { names, mappings })
+    }
+}
+
+///
+/// The secondary uv coordinates of the primitives of a model, ie. the `TEXCOORD_1` attribute of a glTF file, for example the uv coordinates of a lightmap
+/// or an ambient occlusion texture, which are not part of a [CpuMesh]. Use [Model::set_secondary_uvs] to apply them to a model.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SecondaryUvs {
+    /// For each primitive in [CpuModel::geometries], the secondary uv coordinates or `None` if the primitive does not have secondary uv coordinates.
+    pub uvs: Vec<Option<Vec<Vec2>>>,
+}
+
+#[cfg(feature = "gltf-extensions")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gltf-extensions")))]
+impl SecondaryUvs {
+    ///
+    /// Parses the secondary uv coordinates from the given raw assets, which must contain the glTF file at the given path and the buffers it refers to,
+    /// in the same way as when deserializing a [CpuModel] from the raw assets. Call this before deserializing the [CpuModel],
+    /// since deserializing removes the glTF file and the buffers from the raw assets.
+    ///
+    /// ```no_run
+    /// # use three_d::*;
+    /// # let context: Context = unimplemented!();
+    /// let mut loaded = three_d_asset::io::load(&["level.gltf"]).unwrap();
+    /// let secondary_uvs = SecondaryUvs::deserialize(&loaded, "level.gltf").unwrap();
+    /// let mut model =
+    ///     Model::<PhysicalMaterial>::new(&context, &loaded.deserialize("level.gltf").unwrap())
+    ///         .unwrap();
+    /// model.set_secondary_uvs(&secondary_uvs).unwrap();
+    /// ```
+    ///
+    pub fn deserialize(
+        raw_assets: &three_d_asset::io::RawAssets,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<Self, RendererError> {
+        let path = path.as_ref();
+        let base_path = path.parent().unwrap_or(std::path::Path::new(""));
+        Self::parse(raw_assets.get(path)?, |uri| {
+            Ok(if uri.starts_with("data:") {
+                raw_assets.get(uri)?
+            } else {
+                raw_assets.get(base_path.join(uri))?
+            })
+        })
+    }
+
+    ///
+    /// Parses the secondary uv coordinates from the given bytes of a binary glTF (`.glb`) file.
+    /// Use [SecondaryUvs::deserialize] for a glTF file which refers to external buffers.
+    ///
+    pub fn from_gltf(bytes: &[u8]) -> Result<Self, RendererError> {
+        Self::parse(bytes, |uri| {
+            Err(RendererError::MissingGltfBuffer(uri.to_string()))
+        })
+    }
+
+    fn parse<'a>(
+        bytes: &'a [u8],
+        buffer: impl Fn(&str) -> Result<&'a [u8], RendererError>,
+    ) -> Result<Self, RendererError> {
+        let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(bytes)?;
+        let buffers = document
+            .buffers()
+            .map(|b| match b.source() {
+                gltf::buffer::Source::Uri(uri) => buffer(uri),
+                gltf::buffer::Source::Bin => blob
+                    .as_deref()
+                    .ok_or_else(|| RendererError::MissingGltfBuffer("bin".to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut uvs = Vec::new();
+        // Visit the primitives in the same order as they are added to the geometries of a CpuModel
+        fn visit(node: gltf::Node, buffers: &[&[u8]], uvs: &mut Vec<Option<Vec<Vec2>>>) {
+            let transformation = Mat4::from(node.transform().matrix());
+            if transformation.determinant() == 0.0 {
+                return;
+            }
+            if let Some(mesh) = node.mesh() {
+                for primitive in mesh.primitives() {
+                    let reader = primitive.reader(|b| buffers.get(b.index()).copied());
+                    if reader.read_positions().is_some() {
+                        uvs.push(
+                            reader
+                                .read_tex_coords(1)
+                                .map(|values| values.into_f32().map(|uv| uv.into()).collect()),
+                        );
+                    }
+                }
+            }
+            for child in node.children() {
+                visit(child, buffers, uvs);
+            }
+        }
+        if let Some(scene) = document.scenes().next() {
+            for node in scene.nodes() {
+                visit(node, &buffers, &mut uvs);
+            }
+        }
+        Ok(Self { uvs })
+    }
 }
 
 impl<M: Material> std::ops::Deref for Model<M> {
     type Target = Vec<ModelPart<M>>;
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.parts
     }
 }
 
 impl<M: Material> std::ops::DerefMut for Model<M> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.parts
+    }
+}
+
+pub(super) fn animation_duration(
+    animations: &[KeyFrameAnimation],
+    animation_name: Option<&str>,
+) -> Option<f32> {
+    animations
+        .iter()
+        .find(|a| animation_name == a.name.as_deref())
+        .map(|animation| {
+            animation
+                .key_frames
+                .iter()
+                .map(|(_, key_frames)| {
+                    key_frames
+                        .loop_time
+                        .unwrap_or_else(|| key_frames.times.last().copied().unwrap_or(0.0))
+                })
+                .fold(0.0, f32::max)
+        })
+}
+
+pub(super) fn key_frame_times(
+    animations: &[KeyFrameAnimation],
+    animation_name: Option<&str>,
+) -> Vec<f32> {
+    let mut times = animations
+        .iter()
+        .filter(|a| animation_name == a.name.as_deref())
+        .flat_map(|animation| animation.key_frames.iter())
+        .flat_map(|(_, key_frames)| key_frames.times.iter().copied())
+        .collect::<Vec<_>>();
+    times.sort_by(|a, b| a.total_cmp(b));
+    times.dedup();
+    times
+}
+
+///
+/// Returns the index of the root node of the animation, ie. the first key frames with translations.
+///
+fn root_index(animation: &KeyFrameAnimation) -> Option<usize> {
+    animation
+        .key_frames
+        .iter()
+        .position(|(_, key_frames)| key_frames.translations.is_some())
+}
+
+///
+/// Returns the translation of the root node at the given time, where the translation accumulates for each loop of a looping animation.
+///
+fn root_translation(key_frames: &KeyFrames, time: f32) -> Vec3 {
+    let translation = key_frames.translation(time).unwrap_or(vec3(0.0, 0.0, 0.0));
+    match (key_frames.loop_time, key_frames.translations.as_ref()) {
+        (Some(loop_time), Some(translations)) if loop_time > 0.0 && !translations.is_empty() => {
+            let loops = (time / loop_time).floor();
+            translation + (translations[translations.len() - 1] - translations[0]) * loops
+        }
+        _ => translation,
+    }
+}
+
+fn animation_transformation(animation: &KeyFrameAnimation, time: f32, root_motion: bool) -> Mat4 {
+    if !root_motion {
+        return animation.transformation(time);
+    }
+    let root = root_index(animation);
+    let mut result = Mat4::identity();
+    for (i, (transformation, key_frames)) in animation.key_frames.iter().enumerate() {
+        let mut key_frame_transformation = key_frames.transformation(time);
+        if Some(i) == root {
+            // Keep the root node at the translation of the first key frame
+            if let (Some(start), Some(current)) = (
+                key_frames.translations.as_ref().and_then(|t| t.first()),
+                key_frames.translation(time),
+            ) {
+                key_frame_transformation =
+                    Mat4::from_translation(start - current) * key_frame_transformation;
+            }
+        }
+        result = result * transformation * key_frame_transformation;
     }
+    result
 }
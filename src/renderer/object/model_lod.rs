@@ -0,0 +1,167 @@
+use crate::renderer::*;
+use three_d_asset::Primitive;
+
+/// The suffix added to the name of a primitive to mark it as a level of detail of the preceding primitive, followed by the level, for example `rock_LOD1`.
+/// This is the naming convention used by most tools to export levels of detail in a glTF file.
+const LOD_SUFFIX: &str = "_LOD";
+
+///
+/// Settings for generating the levels of detail of a [CpuModel], see [generate_lod_levels].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LodGenerationSettings {
+    /// The number of levels including the original primitive, which is the first level.
+    pub level_count: usize,
+    /// The number of triangles in each level relative to the number of triangles in the previous level.
+    pub reduction: f32,
+    /// No more levels are generated for a primitive when the number of triangles in a level is below this number.
+    pub min_triangle_count: usize,
+}
+
+impl Default for LodGenerationSettings {
+    fn default() -> Self {
+        Self {
+            level_count: 3,
+            reduction: 0.5,
+            min_triangle_count: 32,
+        }
+    }
+}
+
+///
+/// Generates the levels of detail of each triangle mesh primitive in the given [CpuModel] using [CpuMeshProcessing::simplify], for example as a step
+/// in an asset pipeline when a model is loaded or before it is saved, so that the levels do not have to be generated each time the model is used.
+///
+/// The levels are added to the model as primitives directly after the primitive they are generated from, with the same transformation, animations and material,
+/// and are named after that primitive followed by `_LOD` and the level, for example `rock_LOD1` and `rock_LOD2`, which is the naming convention used by most tools
+/// that export levels of detail. The levels can therefore be saved together with the model and are recognized when loading a model with levels of detail
+/// exported from another tool. Primitives which already have levels of detail are left as they are.
+///
+/// Use [lod_objects] to construct objects which render the appropriate level of detail, while a [Model] only renders the first level.
+///
+pub fn generate_lod_levels(cpu_model: &mut CpuModel, settings: &LodGenerationSettings) {
+    let mut geometries = Vec::with_capacity(cpu_model.geometries.len() * settings.level_count);
+    let mut primitives = std::mem::take(&mut cpu_model.geometries)
+        .into_iter()
+        .peekable();
+    while let Some(primitive) = primitives.next() {
+        let has_levels = primitives
+            .peek()
+            .is_some_and(|next| lod_level(&next.name) > 0);
+        let mut levels = Vec::new();
+        if let CpuGeometry::Triangles(cpu_mesh) = &primitive.geometry {
+            if lod_level(&primitive.name) == 0 && !has_levels {
+                let mut cpu_mesh = cpu_mesh.clone();
+                for level in 1..settings.level_count {
+                    let target_triangle_count =
+                        (cpu_mesh.triangle_count() as f32 * settings.reduction) as usize;
+                    if target_triangle_count < settings.min_triangle_count {
+                        break;
+                    }
+                    let simplified = cpu_mesh.simplify(target_triangle_count);
+                    if simplified.triangle_count() >= cpu_mesh.triangle_count() {
+                        break;
+                    }
+                    cpu_mesh = simplified;
+                    levels.push(Primitive {
+                        name: format!("{}{}{}", primitive.name, LOD_SUFFIX, level),
+                        geometry: CpuGeometry::Triangles(cpu_mesh.clone()),
+                        ..primitive.clone()
+                    });
+                }
+            }
+        }
+        geometries.push(primitive);
+        geometries.extend(levels);
+    }
+    cpu_model.geometries = geometries;
+}
+
+///
+/// Constructs a [Gm] with a [LodGeometry] as geometry for each triangle mesh primitive in the given [CpuModel] which is not a level of detail of another primitive,
+/// where the levels of detail are the primitive and the primitives following it which are named as a level of detail of that primitive, see [generate_lod_levels].
+/// The material is constructed from the material of the primitive as in [Model::new].
+///
+/// The first level is rendered up to the first of the given maximum distances from the camera, the second level up to the second distance and so on,
+/// see [LodGeometry::new]. Levels without a maximum distance are not used and the last level is used up to the last maximum distance if there are fewer levels than distances.
+/// Use [f32::INFINITY] as the last maximum distance to always render something.
+/// The animations of the primitives are not applied.
+///
+/// # Panic
+/// Will panic if no maximum distances are given.
+///
+pub fn lod_objects<M: Material + FromCpuMaterial + Clone + Default>(
+    context: &Context,
+    cpu_model: &CpuModel,
+    max_distances: &[f32],
+) -> Result<Vec<Gm<LodGeometry<Mesh>, M>>, RendererError> {
+    assert!(
+        !max_distances.is_empty(),
+        "at least one maximum distance is required to construct level-of-detail objects"
+    );
+    let mut groups: Vec<Vec<&Primitive>> = Vec::new();
+    for primitive in cpu_model.geometries.iter() {
+        if !matches!(primitive.geometry, CpuGeometry::Triangles(_)) {
+            continue;
+        }
+        match groups.last_mut() {
+            Some(group) if lod_level(&primitive.name) > 0 => group.push(primitive),
+            _ => groups.push(vec![primitive]),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|group| {
+            let primitive = group[0];
+            let material = primitive
+                .material_index
+                .map(|material_index| {
+                    cpu_model
+                        .materials
+                        .get(material_index)
+                        .map(|m| M::from_cpu_material(context, m))
+                        .ok_or_else(|| {
+                            RendererError::MissingMaterial(
+                                material_index.to_string(),
+                                primitive.name.clone(),
+                            )
+                        })
+                })
+                .transpose()?
+                .unwrap_or_default();
+            let mut levels = Vec::new();
+            for (i, max_distance) in max_distances.iter().enumerate() {
+                let level = group[i.min(group.len() - 1)];
+                if let CpuGeometry::Triangles(cpu_mesh) = &level.geometry {
+                    let cpu_mesh = with_missing_attributes(
+                        cpu_mesh,
+                        level
+                            .material_index
+                            .and_then(|i| cpu_model.materials.get(i)),
+                    );
+                    let mut mesh = Mesh::new(context, &cpu_mesh);
+                    mesh.set_transformation(level.transformation);
+                    levels.push((mesh, *max_distance));
+                }
+            }
+            Ok(Gm::new(LodGeometry::new(levels), material))
+        })
+        .collect()
+}
+
+///
+/// Returns whether the primitive with the given name is a level of detail of another primitive, see [generate_lod_levels].
+///
+pub(crate) fn is_lod_level(name: &str) -> bool {
+    lod_level(name) > 0
+}
+
+///
+/// Returns the level of detail given by the name of a primitive, which is zero if the name does not end with `_LOD` followed by the level.
+///
+fn lod_level(name: &str) -> usize {
+    name.rsplit_once(LOD_SUFFIX)
+        .filter(|(_, level)| level.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|(_, level)| level.parse().ok())
+        .unwrap_or(0)
+}
@@ -0,0 +1,83 @@
+
+uniform mat4 viewProjection;
+uniform mat4 viewProjectionInverse;
+uniform vec3 cameraPosition;
+uniform mat4 decalInverse;
+uniform vec3 decalX;
+uniform vec3 decalZ;
+uniform float normalThreshold;
+
+uniform vec4 albedo;
+uniform float metallic;
+uniform float roughness;
+
+#ifdef USE_ALBEDO_TEXTURE
+uniform sampler2D albedoTexture;
+uniform mat3 albedoTexTransform;
+#endif
+
+#ifdef USE_METALLIC_ROUGHNESS_TEXTURE
+uniform sampler2D metallicRoughnessTexture;
+uniform mat3 metallicRoughnessTexTransform;
+#endif
+
+#ifdef USE_NORMAL_TEXTURE
+uniform sampler2D normalTexture;
+uniform mat3 normalTexTransform;
+uniform float normalScale;
+#endif
+
+in vec3 pos;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 p = viewProjection * vec4(pos, 1.0);
+    vec2 screen_uv = 0.5 + 0.5 * p.xy / p.w;
+    vec3 position = world_pos_from_depth(viewProjectionInverse, sample_depth(screen_uv), screen_uv);
+
+    // The normal of the surface is reconstructed from the depth, which must happen before any fragments are discarded
+    vec3 normal = normalize(cross(dFdx(position), dFdy(position)));
+    if (dot(normal, cameraPosition - position) < 0.0) {
+        normal = -normal;
+    }
+
+    vec3 local = (decalInverse * vec4(position, 1.0)).xyz;
+    if (any(greaterThan(abs(local), vec3(0.5)))) {
+        discard;
+    }
+    float facing = dot(normal, decalZ);
+    if (facing < normalThreshold) {
+        discard;
+    }
+    vec2 uv = vec2(local.x + 0.5, 0.5 - local.y);
+
+    vec4 surface_color = albedo;
+#ifdef USE_ALBEDO_TEXTURE
+    surface_color *= texture(albedoTexture, (albedoTexTransform * vec3(uv, 1.0)).xy);
+#endif
+    // Fade out towards surfaces parallel to the projection direction
+    surface_color.a *= smoothstep(normalThreshold, min(normalThreshold + 0.1, 1.0), facing);
+
+    float metallic_factor = metallic;
+    float roughness_factor = roughness;
+#ifdef USE_METALLIC_ROUGHNESS_TEXTURE
+    vec4 t = texture(metallicRoughnessTexture, (metallicRoughnessTexTransform * vec3(uv, 1.0)).xy);
+    roughness_factor *= t.g;
+    metallic_factor *= t.b;
+#endif
+
+#ifdef USE_NORMAL_TEXTURE
+    vec3 tangent = normalize(decalX - normal * dot(normal, decalX));
+    vec3 bitangent = cross(normal, tangent);
+    mat3 tbn = mat3(tangent, bitangent, normal);
+    normal = normalize(tbn * ((2.0 * texture(normalTexture, (normalTexTransform * vec3(uv, 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0)));
+#endif
+
+    outColor.rgb = calculate_lighting(cameraPosition, surface_color.rgb, position, normal, metallic_factor, roughness_factor, 1.0);
+    outColor.rgb = apply_fog(outColor.rgb, position);
+    outColor.rgb = tone_mapping(outColor.rgb);
+    outColor.rgb = color_mapping(outColor.rgb);
+    outColor.a = surface_color.a;
+}
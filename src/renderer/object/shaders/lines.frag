@@ -0,0 +1,35 @@
+uniform vec4 lineColor;
+uniform float halfWidth;
+
+#ifdef USE_DASHES
+uniform vec3 dashes;
+#endif
+
+in vec4 col;
+flat in vec4 segment;
+flat in vec4 segmentDepth;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // The distance in pixels to the segment on the screen gives round caps and antialiased edges
+    vec2 line = segment.zw - segment.xy;
+    float length_squared = dot(line, line);
+    float s = length_squared > 0.0 ? clamp(dot(gl_FragCoord.xy - segment.xy, line) / length_squared, 0.0, 1.0) : 0.0;
+    float coverage = clamp(halfWidth + 0.5 - distance(gl_FragCoord.xy, segment.xy + s * line), 0.0, 1.0);
+
+#ifdef USE_DASHES
+    // The position on the screen is converted into a perspective correct distance along the line in world space
+    float t = s * segmentDepth.x / max(s * segmentDepth.x + (1.0 - s) * segmentDepth.y, 0.000001);
+    float d = mix(segmentDepth.z, segmentDepth.w, t) - dashes.z;
+    float m = mod(d, max(dashes.x + dashes.y, 0.000001));
+    coverage *= clamp(min(m, dashes.x - m) / max(fwidth(d), 0.000001) + 0.5, 0.0, 1.0);
+#endif
+
+    if (coverage < 0.004) {
+        discard;
+    }
+    vec4 color = lineColor * col;
+    outColor = vec4(color_mapping(color.rgb), color.a * coverage);
+}
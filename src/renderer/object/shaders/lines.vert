@@ -0,0 +1,78 @@
+uniform mat4 viewProjection;
+uniform mat4 transformation;
+uniform vec4 viewport;
+uniform float halfWidth;
+
+in vec2 position;
+in vec3 start;
+in vec3 end;
+in vec2 distance;
+
+#ifdef USE_VERTEX_COLORS
+in vec4 start_color;
+in vec4 end_color;
+#endif
+
+out vec3 pos;
+out vec4 col;
+flat out vec4 segment;
+flat out vec4 segmentDepth;
+
+vec2 to_window(vec4 p)
+{
+    return viewport.xy + (0.5 + 0.5 * p.xy / p.w) * viewport.zw;
+}
+
+void main()
+{
+    vec3 world_start = (transformation * vec4(start, 1.0)).xyz;
+    vec3 world_end = (transformation * vec4(end, 1.0)).xyz;
+    vec4 clip_start = viewProjection * vec4(world_start, 1.0);
+    vec4 clip_end = viewProjection * vec4(world_end, 1.0);
+    vec2 d = distance;
+    vec4 color_start = vec4(1.0);
+    vec4 color_end = vec4(1.0);
+#ifdef USE_VERTEX_COLORS
+    color_start = start_color;
+    color_end = end_color;
+#endif
+
+    // Clip the segment against the near plane, so the screen space expansion is well defined
+    float near_start = clip_start.z + clip_start.w;
+    float near_end = clip_end.z + clip_end.w;
+    if (near_start < 0.0 && near_end < 0.0) {
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+        return;
+    }
+    if (near_start < 0.0) {
+        float t = near_start / (near_start - near_end);
+        clip_start = mix(clip_start, clip_end, t);
+        world_start = mix(world_start, world_end, t);
+        color_start = mix(color_start, color_end, t);
+        d.x = mix(d.x, d.y, t);
+    } else if (near_end < 0.0) {
+        float t = near_end / (near_end - near_start);
+        clip_end = mix(clip_end, clip_start, t);
+        world_end = mix(world_end, world_start, t);
+        color_end = mix(color_end, color_start, t);
+        d.y = mix(d.y, d.x, t);
+    }
+
+    vec2 window_start = to_window(clip_start);
+    vec2 window_end = to_window(clip_end);
+    segment = vec4(window_start, window_end);
+    segmentDepth = vec4(clip_start.w, clip_end.w, d);
+
+    // The quad is expanded by an extra pixel for the antialiasing and along the segment for the round caps
+    vec2 direction = window_end - window_start;
+    direction = dot(direction, direction) > 0.0 ? normalize(direction) : vec2(1.0, 0.0);
+    vec2 normal = vec2(-direction.y, direction.x);
+    float extent = halfWidth + 1.0;
+    vec2 offset = (normal * position.y + direction * (2.0 * position.x - 1.0)) * extent;
+
+    vec4 clip = position.x < 0.5 ? clip_start : clip_end;
+    gl_Position = clip;
+    gl_Position.xy += 2.0 * offset / viewport.zw * clip.w;
+    pos = position.x < 0.5 ? world_start : world_end;
+    col = position.x < 0.5 ? color_start : color_end;
+}
@@ -4,6 +4,8 @@ in vec3 position;
 
 out vec3 pos;
 out vec2 uvs;
+out vec2 uvs1;
+flat out float texture_layer;
 out vec4 col;
 
 #ifdef USE_NORMALS
@@ -20,6 +22,8 @@ void main()
     vec4 worldPos = vec4(position, 1.);
     pos = worldPos.xyz;
     uvs = worldPos.xz;
+    uvs1 = uvs;
+    texture_layer = 0.0;
     col = vec4(1.0);
 #ifdef USE_NORMALS
     nor = normalize(normal);
@@ -0,0 +1,30 @@
+uniform sampler2D glyphAtlas;
+uniform vec4 textColor;
+
+in vec2 uvs;
+in vec4 col;
+
+#ifdef USE_COLOR_GLYPHS
+in float tinted;
+#endif
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+#ifdef USE_COLOR_GLYPHS
+    // Color glyphs keep their own color, while the other glyphs are white with the coverage in the alpha channel
+    vec4 glyph = texture(glyphAtlas, uvs);
+    if (glyph.a < 0.004) {
+        discard;
+    }
+    vec3 color = mix(glyph.rgb, glyph.rgb * textColor.rgb * col.rgb, tinted);
+    outColor = vec4(color_mapping(color), textColor.a * col.a * glyph.a);
+#else
+    float coverage = texture(glyphAtlas, uvs).r;
+    if (coverage < 0.004) {
+        discard;
+    }
+    outColor = vec4(color_mapping(textColor.rgb * col.rgb), textColor.a * col.a * coverage);
+#endif
+}
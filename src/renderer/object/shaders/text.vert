@@ -0,0 +1,63 @@
+uniform mat4 view;
+uniform mat4 projection;
+uniform vec2 viewportSize;
+uniform int screenSpace;
+uniform vec3 anchorPosition;
+uniform vec2 offset;
+uniform float size;
+uniform vec3 eyePosition;
+uniform float depthOffset;
+uniform int constantScreenSize;
+uniform int clampDepth;
+
+in vec2 position;
+in vec2 uv_coordinate;
+in vec4 color;
+in float tint;
+
+out vec2 uvs;
+out vec2 uvs1;
+out vec4 col;
+out float tinted;
+out vec3 pos;
+
+void main()
+{
+    uvs = uv_coordinate;
+    uvs1 = uvs;
+    col = color;
+    tinted = tint;
+
+    vec2 p = (position + offset) * size;
+    if (screenSpace == 1) {
+        vec2 screen_pos = anchorPosition.xy + p;
+        pos = vec3(screen_pos, 0.0);
+        gl_Position = vec4(2.0 * screen_pos / viewportSize - 1.0, 0.0, 1.0);
+    } else {
+        if (constantScreenSize == 1) {
+            // The size is in pixels, so the glyphs are offset from the anchor in clip space
+            pos = anchorPosition;
+            gl_Position = projection * view * vec4(pos, 1.0);
+            gl_Position.xy += 2.0 * p / viewportSize * gl_Position.w;
+        } else {
+            vec3 right = vec3(view[0][0], view[1][0], view[2][0]);
+            vec3 up = vec3(view[0][1], view[1][1], view[2][1]);
+            pos = anchorPosition + right * p.x + up * p.y;
+            gl_Position = projection * view * vec4(pos, 1.0);
+        }
+        if (depthOffset > 0.0) {
+            // The offset position is kept in front of the camera and the depth in front of the near plane
+            float dist = distance(eyePosition, pos);
+            vec4 offset_position = projection * view * vec4(pos + (eyePosition - pos) * min(depthOffset, 0.99 * dist) / max(dist, 0.0001), 1.0);
+            gl_Position.z = max(offset_position.z / offset_position.w, -0.9999) * gl_Position.w;
+        }
+        if (clampDepth == 1) {
+            if ((view * vec4(anchorPosition, 1.0)).z >= 0.0) {
+                // The anchor is behind the camera
+                gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+            } else {
+                gl_Position.z = clamp(gl_Position.z, -gl_Position.w, gl_Position.w);
+            }
+        }
+    }
+}
@@ -11,10 +11,16 @@ const float pi = 3.14159;
 in vec3 position;
 
 out vec2 uvs;
+out vec2 uvs1;
 out vec3 nor;
 out vec3 pos;
 out vec4 col;
 
+#ifdef USE_TANGENTS
+out vec3 tang;
+out vec3 bitang;
+#endif
+
 void main()
 {
     pos = position + offset;
@@ -51,7 +57,13 @@ void main()
         }
     }
     
+    nor = normalize(nor);
+#ifdef USE_TANGENTS
+    tang = cross(vec3(1.0, 0.0, 0.0), nor);
+    bitang = cross(nor, tang);
+#endif
     gl_Position = viewProjection * vec4(pos, 1.);
     uvs = pos.xz;
+    uvs1 = uvs;
     col = vec4(1.0);
 }
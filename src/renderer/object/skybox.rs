@@ -51,6 +51,7 @@ impl Skybox {
     /// Creates a new skybox with a cube texture generated from the equirectangular texture given as input.
     ///
     pub fn new_from_equirectangular(context: &Context, cpu_texture: &CpuTexture) -> Self {
+        span!(INFO, "load_skybox");
         let texture = match cpu_texture.data {
             TextureData::RgbaU8(_) | TextureData::RgbU8(_) => {
                 let mut cpu_texture = cpu_texture.clone();
@@ -127,7 +128,12 @@ impl Skybox {
         Skybox {
             context: context.clone(),
             vertex_buffer,
-            material: SkyboxMaterial { texture },
+            material: SkyboxMaterial {
+                texture,
+                rotation: Mat3::identity(),
+                next_texture: None,
+                crossfade: 0.0,
+            },
         }
     }
 
@@ -137,6 +143,48 @@ impl Skybox {
     pub fn texture(&self) -> &Arc<TextureCubeMap> {
         &self.material.texture
     }
+
+    ///
+    /// Sets the rotation of the skybox, which should be the same as [AmbientLight::environment_rotation] if the ambient light is computed from the same environment map.
+    ///
+    pub fn set_rotation(&mut self, rotation: Mat3) {
+        self.material.rotation = rotation;
+    }
+
+    ///
+    /// Returns the rotation of the skybox.
+    ///
+    pub fn rotation(&self) -> Mat3 {
+        self.material.rotation
+    }
+
+    ///
+    /// Starts a crossfade from the current cube map texture to the given texture, which is advanced using [Skybox::advance_crossfade].
+    /// Use this together with [AmbientLight::crossfade_to] to also crossfade the light from the environment.
+    ///
+    pub fn crossfade_to(&mut self, texture: Arc<TextureCubeMap>) {
+        self.material.next_texture = Some(texture);
+        self.material.crossfade = 0.0;
+    }
+
+    ///
+    /// Advances the crossfade by the given amount, for example the elapsed time divided by the duration of the crossfade.
+    /// When the crossfade is done, the texture is replaced by the texture which is crossfaded to.
+    /// Returns whether or not a crossfade is still in progress.
+    ///
+    pub fn advance_crossfade(&mut self, amount: f32) -> bool {
+        if self.material.next_texture.is_none() {
+            return false;
+        }
+        self.material.crossfade += amount;
+        if self.material.crossfade >= 1.0 {
+            self.material.texture = self.material.next_texture.take().unwrap();
+            self.material.crossfade = 0.0;
+            false
+        } else {
+            true
+        }
+    }
 }
 
 impl<'a> IntoIterator for &'a Skybox {
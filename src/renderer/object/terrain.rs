@@ -74,6 +74,34 @@ impl<M: Material + Clone> Terrain<M> {
         }
     }
 
+    ///
+    /// Creates a new [Terrain] from a height map texture where the first channel of each texel is the height.
+    /// The columns of the texture are along the x-axis and the rows along the z-axis and the texture covers the area from origo to `(size.x, size.z)` in the xz-plane.
+    /// The height values are assumed to be in the range `[0..1]`, or `[0..255]` for `u8` textures, and are scaled by `size.y`.
+    /// The heights between texels are bilinearly interpolated and the heights outside the texture are those at the closest edge.
+    /// An empty texture, or a texture with less data than its size, results in a flat terrain at height zero.
+    ///
+    /// Only the patches within `side_length` of the center are created, see [Terrain::set_center] for how to stream in new patches when the center moves.
+    ///
+    pub fn new_from_height_texture(
+        context: &Context,
+        material: M,
+        height_texture: &CpuTexture,
+        size: Vec3,
+        side_length: f32,
+        vertex_distance: f32,
+        center: Vec2,
+    ) -> Self {
+        Self::new(
+            context,
+            material,
+            height_map_from_texture(height_texture, size),
+            side_length,
+            vertex_distance,
+            center,
+        )
+    }
+
     ///
     /// Returns the height at the given position.
     ///
@@ -214,6 +242,42 @@ impl<'a, M: Material> IntoIterator for &'a Terrain<M> {
     }
 }
 
+fn height_map_from_texture(
+    texture: &CpuTexture,
+    size: Vec3,
+) -> Arc<dyn Fn(f32, f32) -> f32 + Send + Sync> {
+    let heights: Vec<f32> = match &texture.data {
+        TextureData::RU8(data) => data.iter().map(|v| *v as f32 / 255.0).collect(),
+        TextureData::RgU8(data) => data.iter().map(|v| v[0] as f32 / 255.0).collect(),
+        TextureData::RgbU8(data) => data.iter().map(|v| v[0] as f32 / 255.0).collect(),
+        TextureData::RgbaU8(data) => data.iter().map(|v| v[0] as f32 / 255.0).collect(),
+        TextureData::RF16(data) => data.iter().map(|v| v.to_f32()).collect(),
+        TextureData::RgF16(data) => data.iter().map(|v| v[0].to_f32()).collect(),
+        TextureData::RgbF16(data) => data.iter().map(|v| v[0].to_f32()).collect(),
+        TextureData::RgbaF16(data) => data.iter().map(|v| v[0].to_f32()).collect(),
+        TextureData::RF32(data) => data.clone(),
+        TextureData::RgF32(data) => data.iter().map(|v| v[0]).collect(),
+        TextureData::RgbF32(data) => data.iter().map(|v| v[0]).collect(),
+        TextureData::RgbaF32(data) => data.iter().map(|v| v[0]).collect(),
+    };
+    let width = texture.width as usize;
+    let height = texture.height as usize;
+    if width == 0 || height == 0 || heights.len() < width * height {
+        return Arc::new(|_, _| 0.0);
+    }
+    Arc::new(move |x, z| {
+        let u = (x / size.x).clamp(0.0, 1.0) * (width - 1) as f32;
+        let v = (z / size.z).clamp(0.0, 1.0) * (height - 1) as f32;
+        let (c0, r0) = (u.floor() as usize, v.floor() as usize);
+        let (c1, r1) = ((c0 + 1).min(width - 1), (r0 + 1).min(height - 1));
+        let (tu, tv) = (u - c0 as f32, v - r0 as f32);
+        let h = |r: usize, c: usize| heights[r * width + c];
+        let h0 = h(r0, c0) * (1.0 - tu) + h(r0, c1) * tu;
+        let h1 = h(r1, c0) * (1.0 - tu) + h(r1, c1) * tu;
+        (h0 * (1.0 - tv) + h1 * tv) * size.y
+    })
+}
+
 fn patch_size(vertex_distance: f32) -> f32 {
     vertex_distance * (VERTICES_PER_SIDE - 1) as f32
 }
@@ -0,0 +1,1139 @@
+use crate::core::*;
+use crate::renderer::*;
+use ab_glyph::{Font as _, FontArc, GlyphId, PxScale, ScaleFont};
+use std::collections::HashMap;
+
+mod color_glyph;
+
+///
+/// A TrueType or OpenType font which can be used to render [Text].
+/// Color glyphs, like emoji, are supported in fonts with layers of colored shapes (`COLR` version 0 and 1) and in fonts with PNG images (`CBDT` and `sbix`).
+/// Use [Font::add_fallback] to render the characters that are not in the font, for example emoji, with another font.
+///
+#[derive(Clone)]
+pub struct Font {
+    font: FontArc,
+    fallbacks: Vec<FontArc>,
+    raster_size: f32,
+}
+
+impl Font {
+    ///
+    /// Creates a new font from the content of a `.ttf` or `.otf` file.
+    /// The glyphs are rasterized with a height of 48 pixels, see [Font::new_with_raster_size].
+    ///
+    pub fn new(data: Vec<u8>) -> Result<Self, RendererError> {
+        Self::new_with_raster_size(data, 48.0)
+    }
+
+    ///
+    /// Creates a new font from the content of a `.ttf` or `.otf` file where the glyphs are rasterized with the given height in pixels.
+    /// Text rendered much larger than the raster size looks blurry while text rendered much smaller wastes memory.
+    ///
+    pub fn new_with_raster_size(data: Vec<u8>, raster_size: f32) -> Result<Self, RendererError> {
+        Ok(Self {
+            font: FontArc::try_from_vec(data)?,
+            fallbacks: Vec::new(),
+            raster_size: raster_size.max(1.0),
+        })
+    }
+
+    ///
+    /// Adds a font which is used for the characters that are not in this font or in the previously added fallback fonts,
+    /// for example an emoji font, so text containing emoji is rendered instead of showing missing glyph boxes.
+    /// The glyphs of the fallback fonts are rasterized with the raster size of this font.
+    ///
+    pub fn add_fallback(&mut self, font: &Font) {
+        self.fallbacks.push(font.font.clone());
+        self.fallbacks.extend(font.fallbacks.iter().cloned());
+    }
+
+    fn fonts(&self) -> impl Iterator<Item = &FontArc> {
+        std::iter::once(&self.font).chain(self.fallbacks.iter())
+    }
+}
+
+///
+/// Defines which point of the text is placed at the position given by the [TextPlacement].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum TextAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl TextAnchor {
+    fn fraction(&self) -> Vec2 {
+        match self {
+            Self::TopLeft => vec2(0.0, 1.0),
+            Self::Top => vec2(0.5, 1.0),
+            Self::TopRight => vec2(1.0, 1.0),
+            Self::Left => vec2(0.0, 0.5),
+            Self::Center => vec2(0.5, 0.5),
+            Self::Right => vec2(1.0, 0.5),
+            Self::BottomLeft => vec2(0.0, 0.0),
+            Self::Bottom => vec2(0.5, 0.0),
+            Self::BottomRight => vec2(1.0, 0.0),
+        }
+    }
+}
+
+///
+/// Defines where a [Text] is rendered.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextPlacement {
+    /// A 2D label at the given position in pixels, measured from the bottom left corner of the viewport.
+    /// The text is always rendered on top of everything else and the size of the text is given in pixels.
+    Screen(Vec2),
+    /// A 3D label at the given position in world space which always faces the camera.
+    /// How the text is affected by other objects is given by the [Text::depth_mode]
+    /// and the size of the text is given in world space units, unless [Text::constant_screen_size] is enabled.
+    World(Vec3),
+}
+
+///
+/// Defines how a [TextPlacement::World] text is affected by the objects in front of it.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TextDepthMode {
+    /// The text is hidden behind other objects.
+    #[default]
+    DepthTested,
+    /// The text is always rendered on top of everything else, for example for labels that should be visible everywhere in a dense scene.
+    AlwaysOnTop,
+    /// The text is rendered on top of everything else, but the parts of the text that are behind other objects are faded,
+    /// which shows that the labelled point is hidden while keeping the label readable.
+    OcclusionFaded {
+        /// The opacity of the parts of the text that are behind other objects in the range `[0..1]`.
+        opacity: f32,
+    },
+}
+
+///
+/// A part of a [Text] with its own style, see [Text::new_with_spans].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextSpan {
+    /// The text of the span, which may contain `\n` to start a new line.
+    pub text: String,
+    /// The color of the span, which is multiplied with the [Text::color]. The default is white.
+    pub color: Srgba,
+    /// The height of the font of the span relative to the [Text::size]. The default is 1.0.
+    pub scale: f32,
+    /// Whether the span is bold. Since a [Font] only contains one font face, the glyphs are made bold by widening them. The default is false.
+    pub bold: bool,
+}
+
+impl TextSpan {
+    ///
+    /// Creates a new span with the given text and the default style.
+    ///
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for TextSpan {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            color: Srgba::WHITE,
+            scale: 1.0,
+            bold: false,
+        }
+    }
+}
+
+///
+/// Defines how the lines of a [Text] are aligned relative to each other.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlignment {
+    /// The lines are aligned to the left if the line is part of a left-to-right paragraph and to the right if it is part of a right-to-left paragraph.
+    #[default]
+    Start,
+    /// The lines are centered.
+    Center,
+    /// The lines are aligned to the right if the line is part of a left-to-right paragraph and to the left if it is part of a right-to-left paragraph.
+    End,
+}
+
+///
+/// Defines the base direction of the paragraphs of a [Text], which determines the order of runs of left-to-right and right-to-left text,
+/// for example Latin and Hebrew, within a line.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextDirection {
+    /// The direction of each paragraph is given by the first character with a strong direction, ie. a letter, and is left-to-right if there is none.
+    #[default]
+    Auto,
+    /// All paragraphs are left-to-right.
+    LeftToRight,
+    /// All paragraphs are right-to-left.
+    RightToLeft,
+}
+
+///
+/// A text rendered with a [Font], either as a 2D label in screen space or as a 3D label in the scene, see [TextPlacement].
+/// The lines of the text are separated by `\n` and can also be wrapped to a maximum width, see [Text::set_wrap_width].
+/// The text can consist of several [TextSpan]s with different colors, sizes and boldness, see [Text::new_with_spans].
+///
+/// Right-to-left scripts, like Hebrew and Arabic, and text mixing left-to-right and right-to-left scripts are laid out using
+/// a simplified version of the Unicode Bidirectional Algorithm without explicit embeddings.
+/// The glyphs are not shaped, so scripts which need contextual glyph forms, like Arabic, require a font with presentation forms.
+/// Color glyphs, like emoji, are rendered with their own colors, ie. they are not tinted by the color of the text or span except for the opacity.
+/// Since the glyphs are not shaped, emoji sequences, like flags and emoji joined with zero width joiners, are rendered as the individual emoji.
+///
+/// The glyphs used in the text are rasterized into a glyph atlas when the text is created or changed, so changing the color, size, anchor or placement is cheap
+/// whereas changing the text itself is more expensive.
+///
+pub struct Text {
+    context: Context,
+    font: Font,
+    text: String,
+    spans: Vec<TextSpan>,
+    wrap_width: Option<f32>,
+    alignment: TextAlignment,
+    direction: TextDirection,
+    /// The color of the text, which is multiplied with the color of each [TextSpan].
+    pub color: Srgba,
+    /// The height of the font, in pixels for [TextPlacement::Screen] and in world space units for [TextPlacement::World],
+    /// unless [Text::constant_screen_size] is enabled.
+    pub size: f32,
+    /// Which point of the text is placed at the position given by the placement.
+    pub anchor: TextAnchor,
+    /// Where the text is rendered.
+    pub placement: TextPlacement,
+    /// How a [TextPlacement::World] text is affected by the objects in front of it. The default is [TextDepthMode::DepthTested].
+    pub depth_mode: TextDepthMode,
+    /// The distance, in world space units, that a [TextPlacement::World] text is moved towards the camera when comparing it to the depth of other objects,
+    /// so a label placed on or slightly inside a surface is not cut by the surface. The default is 0.0.
+    pub depth_offset: f32,
+    /// Whether the [Text::size] of a [TextPlacement::World] text is given in pixels, so the text has the same size on the screen
+    /// regardless of the distance to the camera. The default is false.
+    pub constant_screen_size: bool,
+    atlas: Texture2D,
+    color_glyphs: bool,
+    position_buffer: VertexBuffer,
+    uv_buffer: VertexBuffer,
+    color_buffer: VertexBuffer,
+    tint_buffer: VertexBuffer,
+    vertex_count: u32,
+    bounds: (Vec2, Vec2),
+}
+
+impl Text {
+    ///
+    /// Creates a new white text with the given size and placement.
+    ///
+    pub fn new(
+        context: &Context,
+        font: &Font,
+        text: &str,
+        size: f32,
+        placement: TextPlacement,
+    ) -> Self {
+        Self::new_with_spans(context, font, &[TextSpan::new(text)], size, placement)
+    }
+
+    ///
+    /// Creates a new white text consisting of the given spans with the given size and placement.
+    ///
+    pub fn new_with_spans(
+        context: &Context,
+        font: &Font,
+        spans: &[TextSpan],
+        size: f32,
+        placement: TextPlacement,
+    ) -> Self {
+        let text = spans.iter().map(|s| s.text.as_str()).collect::<String>();
+        span!(DEBUG, "layout_text", length = text.len());
+        let layout = TextLayout::new(
+            font,
+            spans,
+            None,
+            TextAlignment::default(),
+            TextDirection::default(),
+        );
+        Self {
+            context: context.clone(),
+            font: font.clone(),
+            text,
+            spans: spans.to_vec(),
+            wrap_width: None,
+            alignment: TextAlignment::default(),
+            direction: TextDirection::default(),
+            color: Srgba::WHITE,
+            size,
+            anchor: TextAnchor::default(),
+            placement,
+            depth_mode: TextDepthMode::default(),
+            depth_offset: 0.0,
+            constant_screen_size: false,
+            atlas: Texture2D::new_with_color_space(context, &layout.atlas, ColorSpace::Srgb),
+            color_glyphs: layout.color_glyphs,
+            position_buffer: VertexBuffer::new_with_data(context, &layout.positions),
+            uv_buffer: VertexBuffer::new_with_data(context, &layout.uvs),
+            color_buffer: VertexBuffer::new_with_data(context, &layout.colors),
+            tint_buffer: VertexBuffer::new_with_data(context, &layout.tints),
+            vertex_count: layout.positions.len() as u32,
+            bounds: layout.bounds,
+        }
+    }
+
+    ///
+    /// Returns the text, which is the text of all the spans.
+    ///
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    ///
+    /// Changes the text to a single span with the default style, which also rasterizes the glyphs of the new text.
+    ///
+    pub fn set_text(&mut self, text: &str) {
+        let spans = [TextSpan::new(text)];
+        if self.spans != spans {
+            self.set_spans(&spans);
+        }
+    }
+
+    ///
+    /// Returns the spans of the text.
+    ///
+    pub fn spans(&self) -> &[TextSpan] {
+        &self.spans
+    }
+
+    ///
+    /// Changes the spans of the text, which also rasterizes the glyphs of the new text.
+    ///
+    pub fn set_spans(&mut self, spans: &[TextSpan]) {
+        self.spans = spans.to_vec();
+        self.text = spans.iter().map(|s| s.text.as_str()).collect();
+        self.update();
+    }
+
+    ///
+    /// Returns the maximum width of a line, see [Text::set_wrap_width].
+    ///
+    pub fn wrap_width(&self) -> Option<f32> {
+        self.wrap_width
+    }
+
+    ///
+    /// Sets the maximum width of a line relative to the [Text::size], for example 20.0 wraps the lines to a width of 20 times the height of the font.
+    /// The lines are wrapped at whitespace, or inside words that are wider than a line. If the width is `None`, the lines are only separated by `\n`.
+    ///
+    pub fn set_wrap_width(&mut self, wrap_width: Option<f32>) {
+        if self.wrap_width != wrap_width {
+            self.wrap_width = wrap_width;
+            self.update();
+        }
+    }
+
+    ///
+    /// Returns how the lines of the text are aligned relative to each other.
+    ///
+    pub fn alignment(&self) -> TextAlignment {
+        self.alignment
+    }
+
+    ///
+    /// Sets how the lines of the text are aligned relative to each other.
+    ///
+    pub fn set_alignment(&mut self, alignment: TextAlignment) {
+        if self.alignment != alignment {
+            self.alignment = alignment;
+            self.update();
+        }
+    }
+
+    ///
+    /// Returns the base direction of the paragraphs of the text.
+    ///
+    pub fn direction(&self) -> TextDirection {
+        self.direction
+    }
+
+    ///
+    /// Sets the base direction of the paragraphs of the text.
+    ///
+    pub fn set_direction(&mut self, direction: TextDirection) {
+        if self.direction != direction {
+            self.direction = direction;
+            self.update();
+        }
+    }
+
+    ///
+    /// Changes the font, which also rasterizes the glyphs of the text with the new font.
+    ///
+    pub fn set_font(&mut self, font: &Font) {
+        self.font = font.clone();
+        self.update();
+    }
+
+    ///
+    /// Returns the width and height of the text, in the same unit as the [Text::size].
+    ///
+    pub fn dimensions(&self) -> Vec2 {
+        (self.bounds.1 - self.bounds.0) * self.size
+    }
+
+    fn update(&mut self) {
+        span!(DEBUG, "layout_text", length = self.text.len());
+        let layout = TextLayout::new(
+            &self.font,
+            &self.spans,
+            self.wrap_width,
+            self.alignment,
+            self.direction,
+        );
+        self.atlas =
+            Texture2D::new_with_color_space(&self.context, &layout.atlas, ColorSpace::Srgb);
+        self.color_glyphs = layout.color_glyphs;
+        self.position_buffer = VertexBuffer::new_with_data(&self.context, &layout.positions);
+        self.uv_buffer = VertexBuffer::new_with_data(&self.context, &layout.uvs);
+        self.color_buffer = VertexBuffer::new_with_data(&self.context, &layout.colors);
+        self.tint_buffer = VertexBuffer::new_with_data(&self.context, &layout.tints);
+        self.vertex_count = layout.positions.len() as u32;
+        self.bounds = layout.bounds;
+    }
+}
+
+impl<'a> IntoIterator for &'a Text {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Text {
+    fn draw(
+        &self,
+        camera: &Camera,
+        program: &Program,
+        render_states: RenderStates,
+        _attributes: FragmentAttributes,
+    ) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        let (min, max) = self.bounds;
+        let fraction = self.anchor.fraction();
+        let viewport = camera.viewport();
+        let (screen_space, position) = match self.placement {
+            TextPlacement::Screen(position) => (1, position.extend(0.0)),
+            TextPlacement::World(position) => (0, position),
+        };
+        program.use_uniform("view", camera.view());
+        program.use_uniform("projection", camera.projection());
+        program.use_uniform(
+            "viewportSize",
+            vec2(viewport.width as f32, viewport.height as f32),
+        );
+        program.use_uniform("screenSpace", screen_space);
+        program.use_uniform("anchorPosition", position);
+        program.use_uniform(
+            "offset",
+            -vec2(
+                min.x + (max.x - min.x) * fraction.x,
+                min.y + (max.y - min.y) * fraction.y,
+            ),
+        );
+        program.use_uniform("size", self.size);
+        program.use_uniform("eyePosition", camera.position());
+        program.use_uniform("depthOffset", self.depth_offset.max(0.0));
+        program.use_uniform("constantScreenSize", self.constant_screen_size as i32);
+        // Text rendered on top of everything is not clipped by the near and far planes
+        program.use_uniform(
+            "clampDepth",
+            (self.depth_mode != TextDepthMode::DepthTested) as i32,
+        );
+        program.use_vertex_attribute("position", &self.position_buffer);
+        program.use_vertex_attribute("uv_coordinate", &self.uv_buffer);
+        program.use_vertex_attribute("color", &self.color_buffer);
+        if program.requires_attribute("tint") {
+            program.use_vertex_attribute("tint", &self.tint_buffer);
+        }
+        program.draw_arrays(render_states, viewport, self.vertex_count);
+    }
+
+    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
+        include_str!("shaders/text.vert").to_owned()
+    }
+
+    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
+        0b1u16 << 15 | 0b110u16
+    }
+
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::INFINITE
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        render_with_material(&self.context, camera, self, material, lights)
+    }
+
+    fn render_with_effect(
+        &self,
+        material: &dyn Effect,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        render_with_effect(
+            &self.context,
+            camera,
+            self,
+            material,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
+
+impl Object for Text {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        let material = |depth_test, opacity| TextMaterial {
+            atlas: &self.atlas,
+            color_glyphs: self.color_glyphs,
+            color: self.color,
+            opacity,
+            depth_test,
+        };
+        match (self.placement, self.depth_mode) {
+            (TextPlacement::Screen(_), _) | (_, TextDepthMode::AlwaysOnTop) => {
+                render_with_material(
+                    &self.context,
+                    camera,
+                    self,
+                    material(DepthTest::Always, 1.0),
+                    lights,
+                );
+            }
+            (_, TextDepthMode::DepthTested) => {
+                render_with_material(
+                    &self.context,
+                    camera,
+                    self,
+                    material(DepthTest::Less, 1.0),
+                    lights,
+                );
+            }
+            (_, TextDepthMode::OcclusionFaded { opacity }) => {
+                render_with_material(
+                    &self.context,
+                    camera,
+                    self,
+                    material(DepthTest::GreaterOrEqual, opacity.clamp(0.0, 1.0)),
+                    lights,
+                );
+                render_with_material(
+                    &self.context,
+                    camera,
+                    self,
+                    material(DepthTest::Less, 1.0),
+                    lights,
+                );
+            }
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+struct TextMaterial<'a> {
+    atlas: &'a Texture2D,
+    color_glyphs: bool,
+    color: Srgba,
+    opacity: f32,
+    depth_test: DepthTest,
+}
+
+impl Material for TextMaterial<'_> {
+    fn id(&self) -> u16 {
+        if self.color_glyphs {
+            0b1u16 << 15 | 0b1u16 << 12 | 0b110u16
+        } else {
+            0b1u16 << 15 | 0b1000u16
+        }
+    }
+
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        format!(
+            "{}{}{}",
+            if self.color_glyphs {
+                "#define USE_COLOR_GLYPHS\n"
+            } else {
+                ""
+            },
+            ColorMapping::fragment_shader_source(),
+            include_str!("shaders/text.frag")
+        )
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        program.use_texture("glyphAtlas", self.atlas);
+        let mut color = self.color.to_linear_srgb();
+        color.w *= self.opacity;
+        program.use_uniform("textColor", color);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: self.depth_test,
+            blend: Blend::TRANSPARENCY,
+            cull: Cull::None,
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+///
+/// The glyph quads and glyph atlas of a text, where the positions are in units of the font height with the y-axis pointing up.
+///
+struct TextLayout {
+    positions: Vec<Vec2>,
+    uvs: Vec<Vec2>,
+    colors: Vec<Vec4>,
+    // Zero for the vertices of color glyphs, which are not tinted by the color of the text, and one otherwise
+    tints: Vec<f32>,
+    // Whether the atlas contains color glyphs, in which case it is in sRGB with an alpha channel, otherwise it only contains the coverage in the red channel
+    color_glyphs: bool,
+    atlas: CpuTexture,
+    bounds: (Vec2, Vec2),
+}
+
+impl TextLayout {
+    fn new(
+        font: &Font,
+        spans: &[TextSpan],
+        wrap_width: Option<f32>,
+        alignment: TextAlignment,
+        direction: TextDirection,
+    ) -> Self {
+        const PADDING: u32 = 2;
+        const ATLAS_WIDTH: u32 = 512;
+        let scale = PxScale::from(font.raster_size);
+        let fonts = font.fonts().map(|f| f.as_scaled(scale)).collect::<Vec<_>>();
+        let faces = font
+            .fonts()
+            .map(|f| ttf_parser::Face::parse(f.font_data(), 0).ok())
+            .collect::<Vec<_>>();
+        let scaled = &fonts[0];
+        let height = scaled.height();
+        let line_height = height + scaled.line_gap();
+        // The index of the font with a glyph for the character, or the missing glyph of the first font,
+        // except for invisible formatting characters like variation selectors which are skipped if they are not in any of the fonts
+        let glyph_of = |c: char| {
+            fonts
+                .iter()
+                .map(|f| f.glyph_id(c))
+                .enumerate()
+                .find(|(_, id)| id.0 != 0)
+                .or_else(|| (!is_default_ignorable(c)).then(|| (0, scaled.glyph_id(c))))
+        };
+        // Bold glyphs are widened by this number of pixels
+        let bold_width = (font.raster_size / 24.0).ceil() as u32;
+        let advance = |c: char, span: &TextSpan| {
+            let bold = if span.bold { bold_width as f32 } else { 0.0 };
+            glyph_of(c).map_or(0.0, |(f, id)| (fonts[f].h_advance(id) + bold) * span.scale)
+        };
+
+        // The characters of each paragraph in logical order together with the index of their span
+        let mut paragraphs: Vec<Vec<(char, usize)>> = vec![Vec::new()];
+        for (index, span) in spans.iter().enumerate() {
+            for c in span.text.chars() {
+                if c == '\n' {
+                    paragraphs.push(Vec::new());
+                } else {
+                    paragraphs.last_mut().unwrap().push((c, index));
+                }
+            }
+        }
+
+        // Rasterize each glyph used in the text once and place it in the atlas using shelf packing
+        enum GlyphPixels {
+            Coverage(Vec<u8>),
+            Color(Vec<[u8; 4]>),
+        }
+        struct AtlasGlyph {
+            offset: Vec2,
+            size: Vec2,
+            atlas_position: (u32, u32),
+            pixels: GlyphPixels,
+        }
+        struct Line {
+            instances: std::ops::Range<usize>,
+            width: f32,
+            right_to_left: bool,
+        }
+        let mut glyphs: HashMap<(usize, GlyphId, bool), Option<AtlasGlyph>> = HashMap::new();
+        let mut instances = Vec::new();
+        let mut lines = Vec::new();
+        let mut top = 0.0;
+        let mut last_line_scale = 1.0;
+        let (mut x, mut y, mut shelf_height) = (PADDING, PADDING, 0);
+        for paragraph in paragraphs.iter() {
+            let right_to_left = match direction {
+                TextDirection::Auto => {
+                    paragraph
+                        .iter()
+                        .map(|(c, _)| bidi_class(*c))
+                        .find(|class| *class == BidiClass::Left || *class == BidiClass::Right)
+                        == Some(BidiClass::Right)
+                }
+                TextDirection::LeftToRight => false,
+                TextDirection::RightToLeft => true,
+            };
+            let levels = bidi_levels(paragraph, right_to_left);
+
+            // Break the paragraph into lines after whitespace, or inside a word which is wider than a line
+            let mut ranges = Vec::new();
+            let mut start = 0;
+            let mut width = 0.0;
+            let mut break_at = None;
+            for (i, (c, span)) in paragraph.iter().enumerate() {
+                let a = advance(*c, &spans[*span]);
+                if let Some(wrap_width) = wrap_width {
+                    if i > start && !c.is_whitespace() && width + a > wrap_width * height {
+                        let end = break_at.filter(|b| *b > start).unwrap_or(i);
+                        ranges.push(start..end);
+                        start = end;
+                        width = paragraph[start..i]
+                            .iter()
+                            .map(|(c, span)| advance(*c, &spans[*span]))
+                            .sum();
+                        break_at = None;
+                    }
+                }
+                width += a;
+                if c.is_whitespace() {
+                    break_at = Some(i + 1);
+                }
+            }
+            ranges.push(start..paragraph.len());
+
+            for mut range in ranges {
+                // Whitespace at the end of a line is not rendered and not part of the width
+                while range.end > range.start && paragraph[range.end - 1].0.is_whitespace() {
+                    range.end -= 1;
+                }
+                let line_scale = paragraph[range.clone()]
+                    .iter()
+                    .map(|(_, span)| spans[*span].scale)
+                    .fold(None, |max: Option<f32>, s| {
+                        Some(max.map_or(s, |m| m.max(s)))
+                    })
+                    .unwrap_or(1.0);
+                let baseline = top + scaled.ascent() * line_scale;
+                let first_instance = instances.len();
+                let mut caret = 0.0;
+                let mut previous = None;
+                for i in visual_order(&levels[range.clone()]) {
+                    let (c, span_index) = paragraph[range.start + i];
+                    let span = &spans[span_index];
+                    let c = if levels[range.start + i] % 2 == 1 {
+                        mirror(c)
+                    } else {
+                        c
+                    };
+                    let Some((font_index, id)) = glyph_of(c) else {
+                        continue;
+                    };
+                    if let Some((previous, previous_font, previous_span)) = previous {
+                        if previous_span == span_index && previous_font == font_index {
+                            caret += fonts[font_index].kern(previous, id) * span.scale;
+                        }
+                    }
+                    let extra_width = if span.bold { bold_width } else { 0 };
+                    let key = (font_index, id, span.bold);
+                    let glyph = glyphs.entry(key).or_insert_with(|| {
+                        let scaled = &fonts[font_index];
+                        let color_glyph = faces[font_index].as_ref().and_then(|face| {
+                            color_glyph::rasterize(face, id.0, scaled.h_scale_factor())
+                        });
+                        let (offset, w, h, pixels) = if let Some(color_glyph) = color_glyph {
+                            (
+                                color_glyph.offset,
+                                color_glyph.width,
+                                color_glyph.height,
+                                GlyphPixels::Color(color_glyph.pixels),
+                            )
+                        } else {
+                            let outlined = scaled.outline_glyph(id.with_scale(scale))?;
+                            let bounds = outlined.px_bounds();
+                            let (w, h) =
+                                (bounds.width() as u32 + extra_width, bounds.height() as u32);
+                            let mut coverage = vec![0u8; (w * h) as usize];
+                            outlined.draw(|px, py, c| {
+                                if px < w && py < h {
+                                    coverage[(py * w + px) as usize] =
+                                        (c.clamp(0.0, 1.0) * 255.0) as u8;
+                                }
+                            });
+                            if extra_width > 0 {
+                                // Synthetic bold by widening the glyph to the right
+                                for row in coverage.chunks_mut(w.max(1) as usize) {
+                                    for px in (0..row.len()).rev() {
+                                        let from = px.saturating_sub(extra_width as usize);
+                                        row[px] = row[from..=px].iter().copied().max().unwrap_or(0);
+                                    }
+                                }
+                            }
+                            (
+                                vec2(bounds.min.x, bounds.min.y),
+                                w,
+                                h,
+                                GlyphPixels::Coverage(coverage),
+                            )
+                        };
+                        if x + w + PADDING > ATLAS_WIDTH.max(w + 2 * PADDING) {
+                            x = PADDING;
+                            y += shelf_height + PADDING;
+                            shelf_height = 0;
+                        }
+                        let atlas_position = (x, y);
+                        x += w + PADDING;
+                        shelf_height = shelf_height.max(h);
+                        Some(AtlasGlyph {
+                            offset,
+                            size: vec2(w as f32, h as f32),
+                            atlas_position,
+                            pixels,
+                        })
+                    });
+                    if glyph.is_some() {
+                        instances.push((
+                            key,
+                            vec2(caret, baseline),
+                            span.scale,
+                            span.color.to_linear_srgb(),
+                        ));
+                    }
+                    caret += advance(c, span);
+                    previous = Some((id, font_index, span_index));
+                }
+                lines.push(Line {
+                    instances: first_instance..instances.len(),
+                    width: caret,
+                    right_to_left,
+                });
+                top += line_height * line_scale;
+                last_line_scale = line_scale;
+            }
+        }
+
+        // Align the lines within the widest line
+        let width = lines.iter().map(|l| l.width).fold(0.0, f32::max);
+        for line in lines.iter() {
+            let start = if line.right_to_left {
+                width - line.width
+            } else {
+                0.0
+            };
+            let offset = match alignment {
+                TextAlignment::Start => start,
+                TextAlignment::Center => 0.5 * (width - line.width),
+                TextAlignment::End => width - line.width - start,
+            };
+            for instance in instances[line.instances.clone()].iter_mut() {
+                instance.1.x += offset;
+            }
+        }
+
+        let atlas_width = glyphs
+            .values()
+            .flatten()
+            .map(|g| g.size.x as u32 + 2 * PADDING)
+            .fold(ATLAS_WIDTH, u32::max);
+        let atlas_height = y + shelf_height + PADDING;
+        let color_glyphs = glyphs
+            .values()
+            .flatten()
+            .any(|g| matches!(g.pixels, GlyphPixels::Color(_)));
+        let mut coverage_data = Vec::new();
+        let mut color_data = Vec::new();
+        if color_glyphs {
+            // The glyphs without color are white with the coverage in the alpha channel
+            color_data = vec![[255, 255, 255, 0]; (atlas_width * atlas_height) as usize];
+        } else {
+            coverage_data = vec![0u8; (atlas_width * atlas_height) as usize];
+        }
+        for glyph in glyphs.values().flatten() {
+            let (gx, gy) = glyph.atlas_position;
+            let w = glyph.size.x as usize;
+            if w == 0 {
+                continue;
+            }
+            for row in 0..glyph.size.y as usize {
+                let start = (gy as usize + row) * atlas_width as usize + gx as usize;
+                let range = row * w..(row + 1) * w;
+                match &glyph.pixels {
+                    GlyphPixels::Coverage(coverage) if color_glyphs => {
+                        for (pixel, c) in color_data[start..start + w]
+                            .iter_mut()
+                            .zip(&coverage[range])
+                        {
+                            pixel[3] = *c;
+                        }
+                    }
+                    GlyphPixels::Coverage(coverage) => {
+                        coverage_data[start..start + w].copy_from_slice(&coverage[range]);
+                    }
+                    GlyphPixels::Color(colors) => {
+                        color_data[start..start + w].copy_from_slice(&colors[range]);
+                    }
+                }
+            }
+        }
+        let data = if color_glyphs {
+            color_glyph::bleed(&mut color_data, atlas_width);
+            TextureData::RgbaU8(color_data)
+        } else {
+            TextureData::RU8(coverage_data)
+        };
+
+        let mut positions = Vec::with_capacity(instances.len() * 6);
+        let mut uvs = Vec::with_capacity(instances.len() * 6);
+        let mut colors = Vec::with_capacity(instances.len() * 6);
+        let mut tints = Vec::with_capacity(instances.len() * 6);
+        for (key, origin, glyph_scale, color) in instances {
+            let glyph = glyphs[&key].as_ref().unwrap();
+            let top_left = origin + glyph.offset * glyph_scale;
+            let bottom_right = top_left + glyph.size * glyph_scale;
+            let x0 = top_left.x / height;
+            let x1 = bottom_right.x / height;
+            let y0 = -bottom_right.y / height;
+            let y1 = -top_left.y / height;
+            let u0 = glyph.atlas_position.0 as f32 / atlas_width as f32;
+            let u1 = (glyph.atlas_position.0 as f32 + glyph.size.x) / atlas_width as f32;
+            // The rows of the atlas are flipped when uploaded to the texture
+            let v0 = 1.0 - (glyph.atlas_position.1 as f32 + glyph.size.y) / atlas_height as f32;
+            let v1 = 1.0 - glyph.atlas_position.1 as f32 / atlas_height as f32;
+            positions.extend([
+                vec2(x0, y0),
+                vec2(x1, y0),
+                vec2(x1, y1),
+                vec2(x1, y1),
+                vec2(x0, y1),
+                vec2(x0, y0),
+            ]);
+            uvs.extend([
+                vec2(u0, v0),
+                vec2(u1, v0),
+                vec2(u1, v1),
+                vec2(u1, v1),
+                vec2(u0, v1),
+                vec2(u0, v0),
+            ]);
+            colors.extend([color; 6]);
+            let tint = match glyph.pixels {
+                GlyphPixels::Coverage(_) => 1.0,
+                GlyphPixels::Color(_) => 0.0,
+            };
+            tints.extend([tint; 6]);
+        }
+
+        Self {
+            positions,
+            uvs,
+            colors,
+            tints,
+            color_glyphs,
+            atlas: CpuTexture {
+                name: "glyph atlas".to_string(),
+                data,
+                width: atlas_width,
+                height: atlas_height,
+                min_filter: Interpolation::Linear,
+                mag_filter: Interpolation::Linear,
+                mip_map_filter: Some(Interpolation::Linear),
+                wrap_s: Wrapping::ClampToEdge,
+                wrap_t: Wrapping::ClampToEdge,
+            },
+            bounds: (
+                vec2(
+                    0.0,
+                    -(top - line_height * last_line_scale + height * last_line_scale) / height,
+                ),
+                vec2(width / height, 0.0),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BidiClass {
+    Left,
+    Right,
+    Number,
+    Neutral,
+}
+
+// A simplified version of the bidirectional character types of the Unicode Bidirectional Algorithm (UAX #9)
+fn bidi_class(c: char) -> BidiClass {
+    if c.is_numeric() {
+        BidiClass::Number
+    } else if matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF | 0x10800..=0x10FFF | 0x1E800..=0x1EFFF)
+    {
+        BidiClass::Right
+    } else if c.is_alphabetic() {
+        BidiClass::Left
+    } else {
+        BidiClass::Neutral
+    }
+}
+
+// Resolves the embedding level of each character of a paragraph without explicit embeddings, following the weak, neutral and implicit rules of UAX #9
+fn bidi_levels(paragraph: &[(char, usize)], right_to_left: bool) -> Vec<u8> {
+    let mut classes = paragraph
+        .iter()
+        .map(|(c, _)| bidi_class(*c))
+        .collect::<Vec<_>>();
+    // Numbers after a left-to-right character, or at the start of a left-to-right paragraph, are left-to-right (W7)
+    let mut previous_strong = if right_to_left {
+        BidiClass::Right
+    } else {
+        BidiClass::Left
+    };
+    for class in classes.iter_mut() {
+        match *class {
+            BidiClass::Left | BidiClass::Right => previous_strong = *class,
+            BidiClass::Number if previous_strong == BidiClass::Left => *class = BidiClass::Left,
+            _ => {}
+        }
+    }
+    // Neutrals between characters with the same direction get that direction, where numbers count as right-to-left,
+    // and otherwise the direction of the paragraph (N1 and N2)
+    let is_right_to_left = |class: BidiClass| class != BidiClass::Left;
+    let mut i = 0;
+    while i < classes.len() {
+        if classes[i] != BidiClass::Neutral {
+            i += 1;
+            continue;
+        }
+        let mut j = i;
+        while j < classes.len() && classes[j] == BidiClass::Neutral {
+            j += 1;
+        }
+        let before = if i == 0 {
+            right_to_left
+        } else {
+            is_right_to_left(classes[i - 1])
+        };
+        let after = if j == classes.len() {
+            right_to_left
+        } else {
+            is_right_to_left(classes[j])
+        };
+        let resolved = if before == after {
+            before
+        } else {
+            right_to_left
+        };
+        for class in classes[i..j].iter_mut() {
+            *class = if resolved {
+                BidiClass::Right
+            } else {
+                BidiClass::Left
+            };
+        }
+        i = j;
+    }
+    // I1 and I2
+    classes
+        .into_iter()
+        .map(|class| match (class, right_to_left) {
+            (BidiClass::Right, _) => 1,
+            (BidiClass::Left, false) => 0,
+            _ => 2,
+        })
+        .collect()
+}
+
+// Returns the logical indices of the characters of a line in visual order from left to right (L2)
+fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order = (0..levels.len()).collect::<Vec<_>>();
+    let highest = levels.iter().copied().max().unwrap_or(0);
+    let lowest_odd = levels.iter().copied().min().unwrap_or(0) | 1;
+    for level in (lowest_odd..=highest).rev() {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] < level {
+                i += 1;
+                continue;
+            }
+            let mut j = i;
+            while j < order.len() && levels[order[j]] >= level {
+                j += 1;
+            }
+            order[i..j].reverse();
+            i = j;
+        }
+    }
+    order
+}
+
+// Invisible formatting characters, like zero width joiners and the variation selectors used after emoji,
+// which are skipped when they are not in the font instead of being rendered as missing glyphs
+fn is_default_ignorable(c: char) -> bool {
+    matches!(c as u32, 0x200B..=0x200F | 0x2060..=0x2064 | 0xFE00..=0xFE0F | 0xE0000..=0xE0FFF)
+}
+
+// Mirrors the characters that are mirrored in right-to-left text (L4)
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        _ => c,
+    }
+}
@@ -0,0 +1,640 @@
+//!
+//! Rasterization of color glyphs, ie. glyphs defined by layers of colored shapes (`COLR`) or by images (`CBDT` and `sbix`),
+//! which is mostly used for emoji.
+//!
+
+use crate::core::*;
+use ab_glyph::point;
+use ab_glyph_rasterizer::Rasterizer;
+use ttf_parser::colr::{CompositeMode, GradientExtend, Paint, Painter};
+use ttf_parser::{Face, OutlineBuilder, RasterImageFormat, RgbaColor, Transform};
+
+///
+/// A rasterized color glyph in straight alpha sRGB, where the offset is from the glyph origin to the top left corner
+/// in pixels with the y-axis pointing down.
+///
+pub(super) struct ColorGlyph {
+    pub offset: Vec2,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+///
+/// Rasterizes the glyph with the given scale in pixels per font unit if it is a color glyph, otherwise returns `None`.
+///
+pub(super) fn rasterize(face: &Face, glyph_id: u16, scale: f32) -> Option<ColorGlyph> {
+    let glyph_id = ttf_parser::GlyphId(glyph_id);
+    if face.is_color_glyph(glyph_id) {
+        paint_layers(face, glyph_id, scale)
+    } else {
+        raster_image(face, glyph_id, scale * face.units_per_em() as f32)
+    }
+}
+
+fn paint_layers(face: &Face, glyph_id: ttf_parser::GlyphId, scale: f32) -> Option<ColorGlyph> {
+    // The layers use white for the text color, since the glyph is not tinted by the color of the text
+    let foreground = RgbaColor::new(255, 255, 255, 255);
+    let mut bounds = BoundsPainter {
+        face,
+        transforms: vec![Transform::default()],
+        min: vec2(f32::INFINITY, f32::INFINITY),
+        max: vec2(f32::NEG_INFINITY, f32::NEG_INFINITY),
+    };
+    face.paint_color_glyph(glyph_id, 0, foreground, &mut bounds)?;
+    if bounds.min.x > bounds.max.x || bounds.min.y > bounds.max.y {
+        return None;
+    }
+    let left = (bounds.min.x * scale).floor() - 1.0;
+    let top = (-bounds.max.y * scale).floor() - 1.0;
+    let width = ((bounds.max.x * scale).ceil() + 1.0 - left) as u32;
+    let height = ((-bounds.min.y * scale).ceil() + 1.0 - top) as u32;
+    let size = (width * height) as usize;
+    let mut canvas = Canvas {
+        face,
+        width,
+        height,
+        to_pixels: Transform::new(scale, 0.0, 0.0, -scale, -left, -top),
+        transforms: vec![Transform::default()],
+        outline: None,
+        clips: Vec::new(),
+        layers: vec![(vec![[0.0; 4]; size], CompositeMode::SourceOver)],
+    };
+    face.paint_color_glyph(glyph_id, 0, foreground, &mut canvas)?;
+    let pixels = canvas
+        .layers
+        .swap_remove(0)
+        .0
+        .into_iter()
+        .map(|c| {
+            if c[3] > 0.0 {
+                let a = c[3].min(1.0);
+                [
+                    (255.0 * (c[0] / a).min(1.0)) as u8,
+                    (255.0 * (c[1] / a).min(1.0)) as u8,
+                    (255.0 * (c[2] / a).min(1.0)) as u8,
+                    (255.0 * a).round() as u8,
+                ]
+            } else {
+                [0; 4]
+            }
+        })
+        .collect::<Vec<_>>();
+    crop(vec2(left, top), width, height, pixels)
+}
+
+fn raster_image(
+    face: &Face,
+    glyph_id: ttf_parser::GlyphId,
+    pixels_per_em: f32,
+) -> Option<ColorGlyph> {
+    let image = face.glyph_raster_image(
+        glyph_id,
+        pixels_per_em.round().clamp(1.0, u16::MAX as f32) as u16,
+    )?;
+    let (width, height, pixels) = match image.format {
+        RasterImageFormat::PNG => {
+            let texture: CpuTexture = three_d_asset::io::deserialize(image.data.to_vec()).ok()?;
+            let pixels = match texture.data {
+                TextureData::RgbaU8(data) => data,
+                TextureData::RgbU8(data) => {
+                    data.into_iter().map(|c| [c[0], c[1], c[2], 255]).collect()
+                }
+                TextureData::RgU8(data) => {
+                    data.into_iter().map(|c| [c[0], c[0], c[0], c[1]]).collect()
+                }
+                TextureData::RU8(data) => data.into_iter().map(|c| [c, c, c, 255]).collect(),
+                _ => return None,
+            };
+            (texture.width, texture.height, pixels)
+        }
+        RasterImageFormat::BitmapPremulBgra32 => {
+            let pixels = image
+                .data
+                .chunks_exact(4)
+                .map(|c| {
+                    let a = c[3].max(1) as f32 / 255.0;
+                    let straight = |v: u8| (v as f32 / a).min(255.0) as u8;
+                    [straight(c[2]), straight(c[1]), straight(c[0]), c[3]]
+                })
+                .collect::<Vec<_>>();
+            (image.width as u32, image.height as u32, pixels)
+        }
+        _ => return None,
+    };
+    if width == 0 || height == 0 || pixels.len() < (width * height) as usize {
+        return None;
+    }
+
+    // The image is scaled from the pixels per em of the image to the pixels per em of the raster size
+    let scale = pixels_per_em / image.pixels_per_em.max(1) as f32;
+    let new_width = ((width as f32 * scale).round() as u32).max(1);
+    let new_height = ((height as f32 * scale).round() as u32).max(1);
+    let pixels = resize(&pixels, width, height, new_width, new_height);
+    crop(
+        vec2(
+            image.x as f32 * scale,
+            -(image.y as f32 + image.height as f32) * scale,
+        ),
+        new_width,
+        new_height,
+        pixels,
+    )
+}
+
+// Resizes the image using a box filter on the premultiplied colors
+fn resize(
+    pixels: &[[u8; 4]],
+    width: u32,
+    height: u32,
+    new_width: u32,
+    new_height: u32,
+) -> Vec<[u8; 4]> {
+    let rx = width as f32 / new_width as f32;
+    let ry = height as f32 / new_height as f32;
+    let overlap = |a0: f32, a1: f32, b: u32| (a1.min(b as f32 + 1.0) - a0.max(b as f32)).max(0.0);
+    let mut result = Vec::with_capacity((new_width * new_height) as usize);
+    for y in 0..new_height {
+        let (y0, y1) = (y as f32 * ry, (y + 1) as f32 * ry);
+        for x in 0..new_width {
+            let (x0, x1) = (x as f32 * rx, (x + 1) as f32 * rx);
+            let mut sum = [0.0f32; 4];
+            let mut total = 0.0;
+            for sy in (y0.floor() as u32)..(y1.ceil() as u32).min(height) {
+                let wy = overlap(y0, y1, sy);
+                for sx in (x0.floor() as u32)..(x1.ceil() as u32).min(width) {
+                    let w = wy * overlap(x0, x1, sx);
+                    let c = pixels[(sy * width + sx) as usize];
+                    let a = c[3] as f32 * w;
+                    for i in 0..3 {
+                        sum[i] += c[i] as f32 * a;
+                    }
+                    sum[3] += a;
+                    total += w;
+                }
+            }
+            result.push(if sum[3] > 0.0 {
+                [
+                    (sum[0] / sum[3]).min(255.0) as u8,
+                    (sum[1] / sum[3]).min(255.0) as u8,
+                    (sum[2] / sum[3]).min(255.0) as u8,
+                    (sum[3] / total.max(f32::EPSILON)).round().min(255.0) as u8,
+                ]
+            } else {
+                [0; 4]
+            });
+        }
+    }
+    result
+}
+
+// Removes the transparent rows and columns around the glyph
+fn crop(offset: Vec2, width: u32, height: u32, pixels: Vec<[u8; 4]>) -> Option<ColorGlyph> {
+    let visible = |x: u32, y: u32| pixels[(y * width + x) as usize][3] > 0;
+    let x0 = (0..width).find(|x| (0..height).any(|y| visible(*x, y)))?;
+    let x1 = (0..width)
+        .rev()
+        .find(|x| (0..height).any(|y| visible(*x, y)))?
+        + 1;
+    let y0 = (0..height).find(|y| (0..width).any(|x| visible(x, *y)))?;
+    let y1 = (0..height)
+        .rev()
+        .find(|y| (0..width).any(|x| visible(x, *y)))?
+        + 1;
+    Some(ColorGlyph {
+        offset: offset + vec2(x0 as f32, y0 as f32),
+        width: x1 - x0,
+        height: y1 - y0,
+        pixels: (y0..y1)
+            .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+            .map(|(x, y)| pixels[(y * width + x) as usize])
+            .collect(),
+    })
+}
+
+///
+/// Replaces the color of the fully transparent pixels next to visible pixels with the average color of the visible pixels,
+/// so the transparent pixels do not darken the edges of the glyphs when the atlas is sampled with linear interpolation.
+///
+pub(super) fn bleed(pixels: &mut [[u8; 4]], width: u32) {
+    let width = width as usize;
+    let height = pixels.len() / width.max(1);
+    let source = pixels.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            if source[y * width + x][3] > 0 {
+                continue;
+            }
+            let mut sum = [0u32; 3];
+            let mut count = 0;
+            for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    let c = source[ny * width + nx];
+                    if c[3] > 0 {
+                        for i in 0..3 {
+                            sum[i] += c[i] as u32;
+                        }
+                        count += 1;
+                    }
+                }
+            }
+            let c = &mut pixels[y * width + x];
+            for i in 0..3 {
+                if let Some(average) = sum[i].checked_div(count) {
+                    c[i] = average as u8;
+                }
+            }
+        }
+    }
+}
+
+fn apply(transform: &Transform, x: f32, y: f32) -> Vec2 {
+    vec2(
+        transform.a * x + transform.c * y + transform.e,
+        transform.b * x + transform.d * y + transform.f,
+    )
+}
+
+fn invert(transform: &Transform) -> Transform {
+    let det = transform.a * transform.d - transform.b * transform.c;
+    if det.abs() < f32::EPSILON {
+        return Transform::default();
+    }
+    let (a, b, c, d) = (
+        transform.d / det,
+        -transform.b / det,
+        -transform.c / det,
+        transform.a / det,
+    );
+    Transform::new(
+        a,
+        b,
+        c,
+        d,
+        -(a * transform.e + c * transform.f),
+        -(b * transform.e + d * transform.f),
+    )
+}
+
+// Finds the bounds in font units of the outlines used by a color glyph
+struct BoundsPainter<'a, 'b> {
+    face: &'b Face<'a>,
+    transforms: Vec<Transform>,
+    min: Vec2,
+    max: Vec2,
+}
+
+impl<'a> Painter<'a> for BoundsPainter<'a, '_> {
+    fn outline_glyph(&mut self, glyph_id: ttf_parser::GlyphId) {
+        if let Some(rect) = self.face.glyph_bounding_box(glyph_id) {
+            let transform = self.transforms.last().unwrap();
+            for (x, y) in [
+                (rect.x_min, rect.y_min),
+                (rect.x_max, rect.y_min),
+                (rect.x_min, rect.y_max),
+                (rect.x_max, rect.y_max),
+            ] {
+                let p = apply(transform, x as f32, y as f32);
+                self.min = vec2(self.min.x.min(p.x), self.min.y.min(p.y));
+                self.max = vec2(self.max.x.max(p.x), self.max.y.max(p.y));
+            }
+        }
+    }
+    fn paint(&mut self, _paint: Paint<'a>) {}
+    fn push_clip(&mut self) {}
+    fn push_clip_box(&mut self, _clipbox: ttf_parser::colr::ClipBox) {}
+    fn pop_clip(&mut self) {}
+    fn push_layer(&mut self, _mode: CompositeMode) {}
+    fn pop_layer(&mut self) {}
+    fn push_transform(&mut self, transform: Transform) {
+        let current = *self.transforms.last().unwrap();
+        self.transforms.push(Transform::combine(current, transform));
+    }
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+}
+
+// Paints the layers of a color glyph into premultiplied colors
+struct Canvas<'a, 'b> {
+    face: &'b Face<'a>,
+    width: u32,
+    height: u32,
+    to_pixels: Transform,
+    transforms: Vec<Transform>,
+    outline: Option<Vec<f32>>,
+    clips: Vec<Vec<f32>>,
+    layers: Vec<(Vec<[f32; 4]>, CompositeMode)>,
+}
+
+impl Canvas<'_, '_> {
+    fn transform(&self) -> Transform {
+        Transform::combine(self.to_pixels, *self.transforms.last().unwrap())
+    }
+
+    fn mask(&self, draw: impl FnOnce(&mut MaskBuilder)) -> Vec<f32> {
+        let mut builder = MaskBuilder {
+            rasterizer: Rasterizer::new(self.width as usize, self.height as usize),
+            transform: self.transform(),
+            size: vec2(self.width as f32, self.height as f32),
+            start: vec2(0.0, 0.0),
+            last: vec2(0.0, 0.0),
+        };
+        draw(&mut builder);
+        let mut mask = vec![0.0; (self.width * self.height) as usize];
+        builder
+            .rasterizer
+            .for_each_pixel(|i, coverage| mask[i] = coverage.min(1.0));
+        mask
+    }
+
+    fn push_mask(&mut self, mask: Vec<f32>) {
+        let mask = match self.clips.last() {
+            Some(clip) => clip.iter().zip(mask).map(|(a, b)| a * b).collect(),
+            None => mask,
+        };
+        self.clips.push(mask);
+    }
+
+    fn color(&self, paint: &Paint, position: Vec2) -> [f32; 4] {
+        let coords = self.face.variation_coordinates();
+        let (t, extend, stops) = match paint {
+            Paint::Solid(color) => return premultiply(color),
+            Paint::LinearGradient(gradient) => {
+                let p0 = vec2(gradient.x0, gradient.y0);
+                let p1 = vec2(gradient.x1, gradient.y1);
+                let d = vec2(gradient.x2, gradient.y2) - p0;
+                // The gradient is perpendicular to the line from p0 to p2
+                let normal = vec2(d.y, -d.x);
+                let p3 = if normal.magnitude2() > 0.0 {
+                    p0 + normal * (p1 - p0).dot(normal) / normal.magnitude2()
+                } else {
+                    p1
+                };
+                let axis = p3 - p0;
+                let t = if axis.magnitude2() > 0.0 {
+                    (position - p0).dot(axis) / axis.magnitude2()
+                } else {
+                    0.0
+                };
+                (t, gradient.extend, gradient.stops(0, coords).collect())
+            }
+            Paint::RadialGradient(gradient) => {
+                let c0 = vec2(gradient.x0, gradient.y0);
+                let dc = vec2(gradient.x1, gradient.y1) - c0;
+                let dr = gradient.r1 - gradient.r0;
+                let p = position - c0;
+                // The largest t where the position is on the circle interpolated between the two circles
+                let a = dc.magnitude2() - dr * dr;
+                let b = p.dot(dc) + gradient.r0 * dr;
+                let c = p.magnitude2() - gradient.r0 * gradient.r0;
+                let t = if a.abs() < 1.0e-6 {
+                    if b.abs() < 1.0e-6 {
+                        return [0.0; 4];
+                    }
+                    0.5 * c / b
+                } else {
+                    let discriminant = b * b - a * c;
+                    if discriminant < 0.0 {
+                        return [0.0; 4];
+                    }
+                    let t0 = (b + discriminant.sqrt()) / a;
+                    let t1 = (b - discriminant.sqrt()) / a;
+                    let radius = |t: f32| gradient.r0 + t * dr;
+                    if radius(t0.max(t1)) >= 0.0 {
+                        t0.max(t1)
+                    } else if radius(t0.min(t1)) >= 0.0 {
+                        t0.min(t1)
+                    } else {
+                        return [0.0; 4];
+                    }
+                };
+                (t, gradient.extend, gradient.stops(0, coords).collect())
+            }
+            Paint::SweepGradient(gradient) => {
+                // The angles are counter-clockwise in units of 180 degrees
+                let angle = (position.y - gradient.center_y)
+                    .atan2(position.x - gradient.center_x)
+                    .to_degrees()
+                    .rem_euclid(360.0);
+                let (start, end) = (gradient.start_angle * 180.0, gradient.end_angle * 180.0);
+                let t = if (end - start).abs() > f32::EPSILON {
+                    (angle - start) / (end - start)
+                } else {
+                    0.0
+                };
+                (t, gradient.extend, gradient.stops(0, coords).collect())
+            }
+        };
+        gradient_color(t, extend, stops)
+    }
+}
+
+fn premultiply(color: &RgbaColor) -> [f32; 4] {
+    let a = color.alpha as f32 / 255.0;
+    [
+        color.red as f32 / 255.0 * a,
+        color.green as f32 / 255.0 * a,
+        color.blue as f32 / 255.0 * a,
+        a,
+    ]
+}
+
+fn gradient_color(
+    t: f32,
+    extend: GradientExtend,
+    mut stops: Vec<ttf_parser::colr::ColorStop>,
+) -> [f32; 4] {
+    if stops.is_empty() {
+        return [0.0; 4];
+    }
+    stops.sort_by(|a, b| a.stop_offset.total_cmp(&b.stop_offset));
+    let first = stops[0].stop_offset;
+    let last = stops[stops.len() - 1].stop_offset;
+    let t = if last - first > f32::EPSILON {
+        let u = (t - first) / (last - first);
+        let u = match extend {
+            GradientExtend::Pad => u.clamp(0.0, 1.0),
+            GradientExtend::Repeat => u.rem_euclid(1.0),
+            GradientExtend::Reflect => 1.0 - ((u.rem_euclid(2.0)) - 1.0).abs(),
+        };
+        first + u * (last - first)
+    } else {
+        first
+    };
+    let next = stops
+        .iter()
+        .position(|s| s.stop_offset > t)
+        .unwrap_or(stops.len());
+    if next == 0 {
+        return premultiply(&stops[0].color);
+    }
+    if next == stops.len() {
+        return premultiply(&stops[stops.len() - 1].color);
+    }
+    let (a, b) = (&stops[next - 1], &stops[next]);
+    let f = (t - a.stop_offset) / (b.stop_offset - a.stop_offset).max(f32::EPSILON);
+    let (ca, cb) = (premultiply(&a.color), premultiply(&b.color));
+    [0, 1, 2, 3].map(|i| ca[i] + (cb[i] - ca[i]) * f)
+}
+
+fn composite(source: [f32; 4], destination: [f32; 4], mode: CompositeMode) -> [f32; 4] {
+    let (sa, da) = (source[3], destination[3]);
+    let blend = |fs: f32, fd: f32| [0, 1, 2, 3].map(|i| source[i] * fs + destination[i] * fd);
+    match mode {
+        CompositeMode::Clear => [0.0; 4],
+        CompositeMode::Source => source,
+        CompositeMode::Destination => destination,
+        CompositeMode::DestinationOver => blend(1.0 - da, 1.0),
+        CompositeMode::SourceIn => blend(da, 0.0),
+        CompositeMode::DestinationIn => blend(0.0, sa),
+        CompositeMode::SourceOut => blend(1.0 - da, 0.0),
+        CompositeMode::DestinationOut => blend(0.0, 1.0 - sa),
+        CompositeMode::SourceAtop => blend(da, 1.0 - sa),
+        CompositeMode::DestinationAtop => blend(1.0 - da, sa),
+        CompositeMode::Xor => blend(1.0 - da, 1.0 - sa),
+        CompositeMode::Plus => blend(1.0, 1.0).map(|c| c.min(1.0)),
+        CompositeMode::Screen => {
+            [0, 1, 2, 3].map(|i| source[i] + destination[i] - source[i] * destination[i])
+        }
+        CompositeMode::Multiply => [0, 1, 2, 3].map(|i| {
+            source[i] * (1.0 - da) + destination[i] * (1.0 - sa) + source[i] * destination[i]
+        }),
+        // The remaining blend modes are approximated by drawing the source over the destination
+        _ => blend(1.0, 1.0 - sa),
+    }
+}
+
+impl<'a> Painter<'a> for Canvas<'a, '_> {
+    fn outline_glyph(&mut self, glyph_id: ttf_parser::GlyphId) {
+        let face = self.face;
+        self.outline = Some(self.mask(|builder| {
+            face.outline_glyph(glyph_id, builder);
+        }));
+    }
+
+    fn paint(&mut self, paint: Paint<'a>) {
+        let inverse = invert(&self.transform());
+        let width = self.width as usize;
+        let mut mask = self.outline.clone();
+        if let Some(clip) = self.clips.last() {
+            mask = Some(match mask {
+                Some(mask) => mask.iter().zip(clip).map(|(a, b)| a * b).collect(),
+                None => clip.clone(),
+            });
+        }
+        let mut target = std::mem::take(&mut self.layers.last_mut().unwrap().0);
+        for (i, pixel) in target.iter_mut().enumerate() {
+            let coverage = mask.as_ref().map_or(1.0, |mask| mask[i]);
+            if coverage <= 0.0 {
+                continue;
+            }
+            let position = apply(&inverse, (i % width) as f32 + 0.5, (i / width) as f32 + 0.5);
+            let color = self.color(&paint, position).map(|c| c * coverage);
+            *pixel = composite(color, *pixel, CompositeMode::SourceOver);
+        }
+        self.layers.last_mut().unwrap().0 = target;
+    }
+
+    fn push_clip(&mut self) {
+        let outline = self
+            .outline
+            .take()
+            .unwrap_or_else(|| vec![0.0; (self.width * self.height) as usize]);
+        self.push_mask(outline);
+    }
+
+    fn push_clip_box(&mut self, clipbox: ttf_parser::colr::ClipBox) {
+        let mask = self.mask(|builder| {
+            builder.move_to(clipbox.x_min, clipbox.y_min);
+            builder.line_to(clipbox.x_max, clipbox.y_min);
+            builder.line_to(clipbox.x_max, clipbox.y_max);
+            builder.line_to(clipbox.x_min, clipbox.y_max);
+            builder.close();
+        });
+        self.push_mask(mask);
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn push_layer(&mut self, mode: CompositeMode) {
+        self.layers
+            .push((vec![[0.0; 4]; (self.width * self.height) as usize], mode));
+    }
+
+    fn pop_layer(&mut self) {
+        if self.layers.len() > 1 {
+            let (source, mode) = self.layers.pop().unwrap();
+            for (destination, source) in self.layers.last_mut().unwrap().0.iter_mut().zip(source) {
+                *destination = composite(source, *destination, mode);
+            }
+        }
+    }
+
+    fn push_transform(&mut self, transform: Transform) {
+        let current = *self.transforms.last().unwrap();
+        self.transforms.push(Transform::combine(current, transform));
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+}
+
+// Rasterizes outlines in font units into a coverage mask
+struct MaskBuilder {
+    rasterizer: Rasterizer,
+    transform: Transform,
+    size: Vec2,
+    start: Vec2,
+    last: Vec2,
+}
+
+impl MaskBuilder {
+    fn to_pixels(&self, x: f32, y: f32) -> ab_glyph::Point {
+        let p = apply(&self.transform, x, y);
+        point(p.x.clamp(0.0, self.size.x), p.y.clamp(0.0, self.size.y))
+    }
+}
+
+impl OutlineBuilder for MaskBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = vec2(x, y);
+        self.last = vec2(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (p0, p1) = (
+            self.to_pixels(self.last.x, self.last.y),
+            self.to_pixels(x, y),
+        );
+        self.rasterizer.draw_line(p0, p1);
+        self.last = vec2(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let p0 = self.to_pixels(self.last.x, self.last.y);
+        let (p1, p2) = (self.to_pixels(x1, y1), self.to_pixels(x, y));
+        self.rasterizer.draw_quad(p0, p1, p2);
+        self.last = vec2(x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let p0 = self.to_pixels(self.last.x, self.last.y);
+        let (p1, p2, p3) = (
+            self.to_pixels(x1, y1),
+            self.to_pixels(x2, y2),
+            self.to_pixels(x, y),
+        );
+        self.rasterizer.draw_cubic(p0, p1, p2, p3);
+        self.last = vec2(x, y);
+    }
+
+    fn close(&mut self) {
+        if self.last != self.start {
+            self.line_to(self.start.x, self.start.y);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use super::*;
+
+///
+/// A density volume, for example a medical scan or the result of a scientific simulation, inside a cube which is rendered by raymarching using a [VolumeMaterial].
+/// The volume is rendered using a transfer function which maps the density to a color and opacity by default, see [VolumeRenderMode] for other ways to render the volume,
+/// and parts of the volume can be cut away using [SlicingPlane]s, both of which are set on the [VolumeMaterial] which is available as [Gm::material].
+///
+pub struct VolumeObject(Gm<Mesh, VolumeMaterial>);
+
+impl VolumeObject {
+    ///
+    /// Constructs a [VolumeObject] from a [CpuVoxelGrid] where the red channel of the voxel data is the density.
+    /// The default transfer function goes from transparent black at zero density to opaque white at full density, use [VolumeObject::set_transfer_function] to change it.
+    ///
+    pub fn new(context: &Context, cpu_voxel_grid: &CpuVoxelGrid) -> Self {
+        let mut cube = CpuMesh::cube();
+        cube.transform(&Mat4::from_nonuniform_scale(
+            0.5 * cpu_voxel_grid.size.x,
+            0.5 * cpu_voxel_grid.size.y,
+            0.5 * cpu_voxel_grid.size.z,
+        ))
+        .expect("Invalid size for VolumeObject");
+        Self(Gm::new(
+            Mesh::new(context, &cube),
+            VolumeMaterial::from_cpu_voxel_grid(context, cpu_voxel_grid),
+        ))
+    }
+
+    ///
+    /// Sets the transfer function to the one given by the control points, see [VolumeMaterial::transfer_function_from_control_points].
+    ///
+    pub fn set_transfer_function(&mut self, context: &Context, control_points: &[(f32, Srgba)]) {
+        self.0.material.transfer_function = std::sync::Arc::new(Texture2D::new(
+            context,
+            &VolumeMaterial::transfer_function_from_control_points(control_points),
+        ));
+    }
+}
+
+impl<'a> IntoIterator for &'a VolumeObject {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+use std::ops::Deref;
+impl Deref for VolumeObject {
+    type Target = Gm<Mesh, VolumeMaterial>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for VolumeObject {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Geometry for VolumeObject {
+    impl_geometry_body!(deref);
+
+    fn animate(&mut self, time: f32) {
+        self.0.animate(time)
+    }
+}
+
+impl Object for VolumeObject {
+    impl_object_body!(deref);
+}
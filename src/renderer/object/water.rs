@@ -217,11 +217,8 @@ impl Geometry for WaterPatch {
         camera: &Camera,
         program: &Program,
         render_states: RenderStates,
-        attributes: FragmentAttributes,
+        _attributes: FragmentAttributes,
     ) {
-        if attributes.tangents {
-            todo!() // Water should be able to provide tangents
-        }
         program.use_uniform(
             "offset",
             self.center + vec3(self.offset.x, 0.0, self.offset.y),
@@ -249,12 +246,23 @@ impl Geometry for WaterPatch {
         program.draw_elements(render_states, camera.viewport(), &self.index_buffer);
     }
 
-    fn vertex_shader_source(&self, _required_attributes: FragmentAttributes) -> String {
-        include_str!("shaders/water.vert").to_owned()
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        if required_attributes.tangents {
+            format!(
+                "#define USE_TANGENTS\n{}",
+                include_str!("shaders/water.vert")
+            )
+        } else {
+            include_str!("shaders/water.vert").to_owned()
+        }
     }
 
-    fn id(&self, _required_attributes: FragmentAttributes) -> u16 {
-        0b1u16 << 15 | 0b101u16
+    fn id(&self, required_attributes: FragmentAttributes) -> u16 {
+        if required_attributes.tangents {
+            0b1u16 << 15 | 0b111u16
+        } else {
+            0b1u16 << 15 | 0b101u16
+        }
     }
 
     fn render_with_material(
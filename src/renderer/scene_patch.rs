@@ -0,0 +1,344 @@
+use super::model_bundle::{Encode, Reader};
+use crate::renderer::*;
+use std::collections::BTreeMap;
+
+/// The bytes at the start of serialized scene patches.
+const MAGIC: &[u8; 8] = b"3DPATCH\0";
+/// The version of the scene patch format, which is increased each time the format changes.
+const VERSION: u32 = 1;
+
+///
+/// An incremental change to a scene consisting of models identified by an id chosen by the application, for example to keep the scene
+/// of several viewers in a collaborative review session in sync by sending the changes made in one viewer over a network and applying them
+/// in the other viewers using a [SceneReplica]. Use [serialize_scene_patches] to get the patches as compact bytes, where the models are encoded
+/// in the same way as in the `.3d` format (see [serialize_model]), and [deserialize_scene_patches] to get them back.
+///
+#[derive(Clone, Debug)]
+pub enum ScenePatch {
+    /// Adds the model with the given id, or replaces the model if a model with the id already exists.
+    AddModel {
+        /// The id of the model.
+        id: u32,
+        /// The model.
+        model: CpuModel,
+    },
+    /// Removes the model with the given id.
+    RemoveModel {
+        /// The id of the model.
+        id: u32,
+    },
+    /// Moves the model with the given id, ie. sets the transformation which is applied to all the parts of the model.
+    SetTransformation {
+        /// The id of the model.
+        id: u32,
+        /// The transformation of the model.
+        transformation: Mat4,
+    },
+    /// Changes the material at the given index into [CpuModel::materials] of the model with the given id.
+    SetMaterial {
+        /// The id of the model.
+        id: u32,
+        /// The index into [CpuModel::materials] of the material.
+        material_index: u32,
+        /// The new material.
+        material: Box<CpuMaterial>,
+    },
+}
+
+///
+/// Serializes the given scene patches into compact bytes, for example to send them over a network, see [ScenePatch].
+///
+pub fn serialize_scene_patches(patches: &[ScenePatch]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    VERSION.encode(&mut bytes);
+    (patches.len() as u32).encode(&mut bytes);
+    for patch in patches {
+        patch.encode(&mut bytes);
+    }
+    bytes
+}
+
+///
+/// Deserializes scene patches from bytes produced by [serialize_scene_patches].
+/// Returns an error if the bytes are not scene patches or if they were serialized by a different version of three-d.
+///
+pub fn deserialize_scene_patches(bytes: &[u8]) -> Result<Vec<ScenePatch>, RendererError> {
+    let invalid = |e| match e {
+        RendererError::InvalidModelBundle(message) => RendererError::InvalidScenePatch(message),
+        e => e,
+    };
+    let mut reader = Reader(bytes);
+    if reader.take(MAGIC.len()).map_err(invalid)? != MAGIC {
+        return Err(RendererError::InvalidScenePatch(
+            "not scene patches".to_string(),
+        ));
+    }
+    let version = u32::decode(&mut reader).map_err(invalid)?;
+    if version != VERSION {
+        return Err(RendererError::InvalidScenePatch(format!(
+            "unsupported version {}, expected version {}",
+            version, VERSION
+        )));
+    }
+    let count = u32::decode(&mut reader).map_err(invalid)?;
+    let mut patches = Vec::new();
+    for _ in 0..count {
+        patches.push(ScenePatch::decode(&mut reader).map_err(invalid)?);
+    }
+    if !reader.0.is_empty() {
+        return Err(RendererError::InvalidScenePatch(
+            "unexpected data after the patches".to_string(),
+        ));
+    }
+    Ok(patches)
+}
+
+impl Encode for ScenePatch {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Self::AddModel { id, model } => {
+                0u8.encode(bytes);
+                id.encode(bytes);
+                model.encode(bytes);
+            }
+            Self::RemoveModel { id } => {
+                1u8.encode(bytes);
+                id.encode(bytes);
+            }
+            Self::SetTransformation { id, transformation } => {
+                2u8.encode(bytes);
+                id.encode(bytes);
+                transformation.encode(bytes);
+            }
+            Self::SetMaterial {
+                id,
+                material_index,
+                material,
+            } => {
+                3u8.encode(bytes);
+                id.encode(bytes);
+                material_index.encode(bytes);
+                material.encode(bytes);
+            }
+        }
+    }
+    fn decode(reader: &mut Reader) -> Result<Self, RendererError> {
+        Ok(match reader.tag("scene patch", 3)? {
+            0 => Self::AddModel {
+                id: u32::decode(reader)?,
+                model: CpuModel::decode(reader)?,
+            },
+            1 => Self::RemoveModel {
+                id: u32::decode(reader)?,
+            },
+            2 => Self::SetTransformation {
+                id: u32::decode(reader)?,
+                transformation: Mat4::decode(reader)?,
+            },
+            _ => Self::SetMaterial {
+                id: u32::decode(reader)?,
+                material_index: u32::decode(reader)?,
+                material: Box::new(CpuMaterial::decode(reader)?),
+            },
+        })
+    }
+}
+
+struct ReplicaModel<M: Material> {
+    cpu_model: CpuModel,
+    transformation: Mat4,
+    part_transformations: Vec<Mat4>,
+    model: Model<M>,
+}
+
+///
+/// A scene consisting of [Model]s which is kept up to date by applying [ScenePatch]es, for example received from another viewer in a collaborative review session.
+/// The scene keeps the [CpuModel]s, so it can produce the patches which brings a new viewer up to date, see [SceneReplica::snapshot].
+///
+pub struct SceneReplica<M: Material> {
+    models: BTreeMap<u32, ReplicaModel<M>>,
+}
+
+impl<M: Material + FromCpuMaterial + Clone + Default> SceneReplica<M> {
+    ///
+    /// Creates a new empty scene.
+    ///
+    pub fn new() -> Self {
+        Self {
+            models: BTreeMap::new(),
+        }
+    }
+
+    ///
+    /// Applies the given patch to the scene.
+    /// Returns an error if the patch changes a model or a material which does not exist or if the model could not be constructed.
+    ///
+    pub fn apply(&mut self, context: &Context, patch: &ScenePatch) -> Result<(), RendererError> {
+        match patch {
+            ScenePatch::AddModel { id, model } => {
+                let replica_model = Model::new(context, model)?;
+                let part_transformations = replica_model
+                    .iter()
+                    .map(|part| part.transformation())
+                    .collect();
+                self.models.insert(
+                    *id,
+                    ReplicaModel {
+                        cpu_model: model.clone(),
+                        transformation: Mat4::identity(),
+                        part_transformations,
+                        model: replica_model,
+                    },
+                );
+            }
+            ScenePatch::RemoveModel { id } => {
+                self.models
+                    .remove(id)
+                    .ok_or(RendererError::MissingSceneModel(*id))?;
+            }
+            ScenePatch::SetTransformation { id, transformation } => {
+                let replica_model = self
+                    .models
+                    .get_mut(id)
+                    .ok_or(RendererError::MissingSceneModel(*id))?;
+                replica_model.transformation = *transformation;
+                for (part, part_transformation) in replica_model
+                    .model
+                    .iter_mut()
+                    .zip(replica_model.part_transformations.iter())
+                {
+                    part.set_transformation(transformation * part_transformation);
+                }
+            }
+            ScenePatch::SetMaterial {
+                id,
+                material_index,
+                material,
+            } => {
+                let replica_model = self
+                    .models
+                    .get_mut(id)
+                    .ok_or(RendererError::MissingSceneModel(*id))?;
+                let index = *material_index as usize;
+                let cpu_material = replica_model
+                    .cpu_model
+                    .materials
+                    .get_mut(index)
+                    .ok_or_else(|| {
+                        RendererError::MissingMaterial(
+                            index.to_string(),
+                            replica_model.cpu_model.name.clone(),
+                        )
+                    })?;
+                *cpu_material = material.as_ref().clone();
+                let new_material = M::from_cpu_material(context, material);
+                replica_model.model.update_materials(|i, m| {
+                    if i == Some(index) {
+                        *m = new_material.clone();
+                    }
+                });
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Deserializes the patches in the given bytes, see [deserialize_scene_patches], and applies them to the scene in order.
+    ///
+    pub fn apply_bytes(&mut self, context: &Context, bytes: &[u8]) -> Result<(), RendererError> {
+        for patch in deserialize_scene_patches(bytes)? {
+            self.apply(context, &patch)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns the patches which recreates the current state of this scene when applied to an empty scene, for example to bring a viewer
+    /// which joins a collaborative review session up to date.
+    ///
+    pub fn snapshot(&self) -> Vec<ScenePatch> {
+        let mut patches = Vec::new();
+        for (id, replica_model) in self.models.iter() {
+            patches.push(ScenePatch::AddModel {
+                id: *id,
+                model: replica_model.cpu_model.clone(),
+            });
+            if replica_model.transformation != Mat4::identity() {
+                patches.push(ScenePatch::SetTransformation {
+                    id: *id,
+                    transformation: replica_model.transformation,
+                });
+            }
+        }
+        patches
+    }
+
+    ///
+    /// Returns the model with the given id.
+    ///
+    pub fn model(&self, id: u32) -> Option<&Model<M>> {
+        self.models.get(&id).map(|m| &m.model)
+    }
+
+    ///
+    /// Returns the ids of the models in the scene in increasing order.
+    ///
+    pub fn ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.models.keys().copied()
+    }
+
+    ///
+    /// Returns the parts of all the models in the scene, for example to render the scene.
+    ///
+    pub fn objects(&self) -> impl Iterator<Item = &dyn Object> + Clone {
+        self.models
+            .values()
+            .flat_map(|m| m.model.iter().map(|part| part as &dyn Object))
+    }
+}
+
+impl<M: Material + FromCpuMaterial + Clone + Default> Default for SceneReplica<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scene_patches_round_trip() {
+        let patches = vec![
+            ScenePatch::AddModel {
+                id: 1,
+                model: super::super::model_bundle::tests::model(),
+            },
+            ScenePatch::SetTransformation {
+                id: 1,
+                transformation: Mat4::from_scale(2.0),
+            },
+            ScenePatch::SetMaterial {
+                id: 1,
+                material_index: 0,
+                material: Box::new(CpuMaterial {
+                    name: "red".to_string(),
+                    albedo: Srgba::RED,
+                    ..Default::default()
+                }),
+            },
+            ScenePatch::RemoveModel { id: 1 },
+        ];
+        let bytes = serialize_scene_patches(&patches);
+        let decoded = deserialize_scene_patches(&bytes).unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", patches));
+        assert!(deserialize_scene_patches(&[]).is_err());
+        assert!(deserialize_scene_patches(&bytes[..bytes.len() - 1]).is_err());
+        assert!(deserialize_scene_patches(&serialize_model(&CpuModel {
+            name: String::new(),
+            geometries: Vec::new(),
+            materials: Vec::new(),
+        }))
+        .is_err());
+    }
+}
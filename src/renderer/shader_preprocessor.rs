@@ -0,0 +1,123 @@
+use crate::renderer::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// The shader snippets registered using [register_shader_snippet].
+static SHADER_SNIPPETS: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+///
+/// Registers a shader snippet with the given name, which can then be included in shader sources using `#include "name"`, see [preprocess_shader_source].
+/// A registered snippet replaces any snippet previously registered with the same name, but the built-in snippets cannot be replaced.
+///
+pub fn register_shader_snippet(name: impl Into<String>, source: impl Into<String>) {
+    SHADER_SNIPPETS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(name.into(), source.into());
+}
+
+///
+/// Returns the source of the shader snippet with the given name, either one of the built-in snippets or a snippet registered using [register_shader_snippet].
+/// The built-in snippets are
+/// - `"shared"`: Common functions and constants, for example `PI` and `saturate`.
+/// - `"camera"`: The `Camera` uniform block containing the matrices and the position of the camera, for example `camera.viewProjection`,
+///   see [Camera::use_uniform_block] for the layout.
+/// - `"lighting"`: The functions used to calculate lighting, for example `calculate_light` and `fresnel_schlick`, using the [LightingModel::Blinn] lighting model.
+///   To calculate the lighting from a set of lights, use [lights_shader_source] instead.
+/// - `"tone_mapping"`: The function `vec3 tone_mapping(vec3 color)` which applies the [ToneMapping] given by the uniforms `toneMappingType` and `toneMappingExposure`,
+///   see [ToneMapping::use_uniforms].
+/// - `"color_mapping"`: The function `vec3 color_mapping(vec3 color)` which applies the [ColorMapping] given by the uniform `ColorMappingType`, see [ColorMapping::use_uniforms].
+/// - `"fog"`: The functions `float fog_factor(vec3 position)` and `vec3 apply_fog(vec3 color, vec3 position)` which apply the [Fog], see [Fog::use_uniforms].
+/// - `"noise"`: The noise functions `float perlin(vec3 p, vec3 period)`, `float simplex(vec3 p)` and `float worley(vec3 p, vec3 period)` used by the [NoiseGenerator].
+///   The seed of the noise is given by the global variable `uint noise_seed`.
+///
+pub fn shader_snippet(name: &str) -> Option<String> {
+    match name {
+        "shared" => Some(include_str!("../core/shared.frag").to_string()),
+        "camera" => Some(CAMERA_UNIFORM_BLOCK_SOURCE.to_string()),
+        "lighting" => Some(format!(
+            "#include \"shared\"\n{}\n{}",
+            lighting_model_shader(LightingModel::Blinn),
+            include_str!("light/shaders/light_shared.frag")
+        )),
+        "tone_mapping" => Some(ToneMapping::fragment_shader_source().to_string()),
+        "color_mapping" => Some(ColorMapping::fragment_shader_source().to_string()),
+        "fog" => Some(Fog::fragment_shader_source().to_string()),
+        "noise" => Some(include_str!("material/shaders/noise_functions.frag").to_string()),
+        _ => SHADER_SNIPPETS
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|snippets| snippets.get(name).cloned()),
+    }
+}
+
+///
+/// Preprocesses the given shader source by replacing each line of the form `#include "name"` (or `#include <name>`) with the source of the
+/// shader snippet with that name, see [shader_snippet], and adding a `#define` for each of the given defines, where the value can be empty.
+/// Snippets can include other snippets and each snippet is only included once, so it is safe to include the same snippet in several snippets.
+///
+/// Returns an error if a snippet could not be found.
+///
+/// ```
+/// # use three_d::*;
+/// register_shader_snippet("wave", "float wave(float x) { return sin(x * WAVE_FREQUENCY); }");
+/// let source = preprocess_shader_source(
+///     "#include \"shared\"\n#include \"wave\"\nfloat f(float x) { return saturate(wave(x)); }",
+///     &[("WAVE_FREQUENCY", "2.0")],
+/// )
+/// .unwrap();
+/// assert!(source.starts_with("#define WAVE_FREQUENCY 2.0\n"));
+/// ```
+///
+pub fn preprocess_shader_source(
+    source: &str,
+    defines: &[(&str, &str)],
+) -> Result<String, RendererError> {
+    let mut missing = Vec::new();
+    let output = preprocess(source, defines, &mut HashSet::new(), &mut missing);
+    match missing.into_iter().next() {
+        Some(name) => Err(RendererError::MissingShaderSnippet(name)),
+        None => Ok(output),
+    }
+}
+
+///
+/// Preprocesses the given shader source, see [preprocess_shader_source], where the snippets with the names in `included` are treated as already included.
+/// The lines including a snippet which could not be found are kept and the names of those snippets are added to `missing`.
+///
+pub(crate) fn preprocess(
+    source: &str,
+    defines: &[(&str, &str)],
+    included: &mut HashSet<String>,
+    missing: &mut Vec<String>,
+) -> String {
+    let mut output = String::new();
+    for (name, value) in defines {
+        output.push_str(&format!("#define {} {}\n", name, value));
+    }
+    for line in source.lines() {
+        if let Some(name) = include_name(line) {
+            if included.contains(name) {
+                continue;
+            }
+            if let Some(snippet) = shader_snippet(name) {
+                included.insert(name.to_string());
+                output.push_str(&preprocess(&snippet, &[], included, missing));
+                continue;
+            }
+            missing.push(name.to_string());
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+fn include_name(line: &str) -> Option<&str> {
+    let name = line.trim().strip_prefix("#include")?.trim();
+    name.strip_prefix('"')
+        .and_then(|n| n.strip_suffix('"'))
+        .or_else(|| name.strip_prefix('<').and_then(|n| n.strip_suffix('>')))
+}
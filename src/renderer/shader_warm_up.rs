@@ -0,0 +1,200 @@
+use crate::renderer::*;
+
+///
+/// Compiles the shader programs needed to render a set of geometries with a set of materials or effects before they are used,
+/// to avoid that the compilation happens when the objects are first rendered which usually results in a noticeable stall.
+///
+/// Add the combinations that will be rendered, for example while loading the scene, and then call [ShaderWarmUp::update] once every frame
+/// until [ShaderWarmUp::is_done] returns true, for example while showing a loading screen with the progress.
+/// The compiled programs are stored in the same cache as the programs compiled when rendering, so nothing else needs to change.
+///
+/// **Note:** Compilation happens in parallel with rendering if the graphics driver supports the `KHR_parallel_shader_compile` extension,
+/// which is widely supported on web, or if the programs are compiled on a separate thread with its own graphics context, see [Context::enable_shader_compile_thread],
+/// which is done automatically on desktop when the platform supports it.
+/// Otherwise [ShaderWarmUp::update] compiles at most one program each call, so it is still possible to spread the compilation over multiple frames.
+///
+pub struct ShaderWarmUp {
+    context: Context,
+    pending: Vec<(Vec<u8>, PendingProgram)>,
+    total: usize,
+    finished: usize,
+}
+
+impl ShaderWarmUp {
+    ///
+    /// Creates a new empty shader warm-up.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            pending: Vec::new(),
+            total: 0,
+            finished: 0,
+        }
+    }
+
+    ///
+    /// Starts compiling the program needed to render the given [Geometry] with the given [Material] and lights, see [render_with_material].
+    /// Nothing happens if the program is already compiled or compiling.
+    ///
+    /// Returns an error if the program could not be created.
+    ///
+    pub fn add_material(
+        &mut self,
+        geometry: impl Geometry,
+        material: impl Material,
+        lights: &[&dyn Light],
+    ) -> Result<(), CoreError> {
+        let id = material_program_id(&geometry, &material, lights);
+        self.add(id, || {
+            (
+                material_vertex_shader_source(&geometry, &material),
+                material.fragment_shader_source(lights),
+            )
+        })
+    }
+
+    ///
+    /// Starts compiling the program needed to render the given [Geometry] with the given [Effect], lights and textures, see [render_with_effect].
+    /// Nothing happens if the program is already compiled or compiling.
+    ///
+    /// Returns an error if the program could not be created.
+    ///
+    pub fn add_effect(
+        &mut self,
+        geometry: impl Geometry,
+        effect: impl Effect,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> Result<(), CoreError> {
+        let id = effect_program_id(
+            Some(&geometry),
+            &effect,
+            lights,
+            color_texture,
+            depth_texture,
+        );
+        self.add(id, || {
+            (
+                geometry.vertex_shader_source(effect.fragment_attributes()),
+                effect.fragment_shader_source(lights, color_texture, depth_texture),
+            )
+        })
+    }
+
+    ///
+    /// Starts compiling the program needed to apply the given [Effect] to the entire screen, see [apply_screen_effect].
+    /// Nothing happens if the program is already compiled or compiling.
+    ///
+    /// Returns an error if the program could not be created.
+    ///
+    pub fn add_screen_effect(
+        &mut self,
+        effect: impl Effect,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> Result<(), CoreError> {
+        let id = effect_program_id(None, &effect, lights, color_texture, depth_texture);
+        self.add(id, || {
+            (
+                full_screen_vertex_shader_source().to_string(),
+                effect.fragment_shader_source(lights, color_texture, depth_texture),
+            )
+        })
+    }
+
+    fn add(
+        &mut self,
+        id: Vec<u8>,
+        sources: impl FnOnce() -> (String, String),
+    ) -> Result<(), CoreError> {
+        if self.context.programs.read().unwrap().contains_key(&id)
+            || self.pending.iter().any(|(i, _)| *i == id)
+        {
+            return Ok(());
+        }
+        let (vertex_shader_source, fragment_shader_source) = sources();
+        self.pending.push((
+            id,
+            PendingProgram::new(
+                &self.context,
+                &vertex_shader_source,
+                &fragment_shader_source,
+            )?,
+        ));
+        self.total += 1;
+        Ok(())
+    }
+
+    ///
+    /// Moves the programs that are done compiling into the program cache without blocking
+    /// or, if the programs are not compiled in parallel, finishes the compilation of one program.
+    /// The `progress` callback is called with the number of finished programs and the total number of programs each time a program is finished.
+    ///
+    /// Returns an error if the compilation of a program failed.
+    ///
+    pub fn update(&mut self, mut progress: impl FnMut(usize, usize)) -> Result<(), CoreError> {
+        let mut i = 0;
+        while i < self.pending.len() {
+            if self.pending[i].1.is_ready() {
+                let (id, program) = self.pending.remove(i);
+                self.finish(id, program, &mut progress)?;
+                if !self.context.supports_parallel_shader_compile()
+                    && !self.context.has_shader_compile_thread()
+                {
+                    break;
+                }
+            } else {
+                i += 1;
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Blocks until all programs are compiled and moved into the program cache.
+    /// The `progress` callback is called with the number of finished programs and the total number of programs each time a program is finished.
+    ///
+    /// Returns an error if the compilation of a program failed.
+    ///
+    pub fn finish_all(&mut self, mut progress: impl FnMut(usize, usize)) -> Result<(), CoreError> {
+        while !self.pending.is_empty() {
+            let (id, program) = self.pending.remove(0);
+            self.finish(id, program, &mut progress)?;
+        }
+        Ok(())
+    }
+
+    fn finish(
+        &mut self,
+        id: Vec<u8>,
+        program: PendingProgram,
+        progress: &mut impl FnMut(usize, usize),
+    ) -> Result<(), CoreError> {
+        let program = program.finish()?;
+        self.context.programs.write().unwrap().insert(id, program);
+        self.finished += 1;
+        progress(self.finished, self.total);
+        Ok(())
+    }
+
+    ///
+    /// Returns the fraction of the added programs that are compiled, in the range `[0, 1]`.
+    ///
+    pub fn progress(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.finished as f32 / self.total as f32
+        }
+    }
+
+    ///
+    /// Returns whether all added programs are compiled.
+    ///
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
@@ -0,0 +1,414 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// The shading used by a [SoftwareMaterial].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SoftwareShading {
+    /// The color is not affected by lights.
+    Unlit,
+    /// Each triangle is lit by a directional light using the normal of the triangle (flat shading).
+    Flat {
+        /// The direction the light shines in.
+        light_direction: Vec3,
+        /// The fraction of the light which also reaches the triangles facing away from the light.
+        ambient: f32,
+    },
+}
+
+///
+/// A material used when rendering with a [SoftwareRasterizer], which is the color multiplied with the optional per vertex colors and texture.
+///
+#[derive(Clone, Debug)]
+pub struct SoftwareMaterial {
+    /// Base surface color.
+    pub color: Srgba,
+    /// An optional texture which is sampled using the uv coordinates of the mesh with nearest interpolation and repeat wrapping.
+    /// Only textures with the `RgbU8`, `RgbaU8`, `RgbF32` and `RgbaF32` formats are supported, where the colors of `RgbU8` and `RgbaU8` textures are assumed to be in sRGB.
+    pub texture: Option<CpuTexture>,
+    /// The shading applied to the color.
+    pub shading: SoftwareShading,
+    /// Defines which triangles are culled.
+    pub cull: Cull,
+}
+
+impl Default for SoftwareMaterial {
+    fn default() -> Self {
+        Self {
+            color: Srgba::WHITE,
+            texture: None,
+            shading: SoftwareShading::Unlit,
+            cull: Cull::None,
+        }
+    }
+}
+
+impl SoftwareMaterial {
+    ///
+    /// Constructs a new material from the albedo color and albedo texture of a [CpuMaterial] and the given shading.
+    ///
+    pub fn from_cpu_material(cpu_material: &CpuMaterial, shading: SoftwareShading) -> Self {
+        Self {
+            color: cpu_material.albedo,
+            texture: cpu_material.albedo_texture.clone(),
+            shading,
+            cull: Cull::None,
+        }
+    }
+
+    fn sample(&self, uv: Vec2) -> Vec4 {
+        let Some(texture) = self.texture.as_ref() else {
+            return vec4(1.0, 1.0, 1.0, 1.0);
+        };
+        let (width, height) = (texture.width.max(1), texture.height.max(1));
+        let x = ((uv.x.rem_euclid(1.0) * width as f32) as u32).min(width - 1);
+        let y = ((uv.y.rem_euclid(1.0) * height as f32) as u32).min(height - 1);
+        let index = (y * width + x) as usize;
+        let srgb = |c: u8| Srgba::new(c, 0, 0, 255).to_linear_srgb().x;
+        match &texture.data {
+            TextureData::RgbU8(data) => data
+                .get(index)
+                .map(|c| vec4(srgb(c[0]), srgb(c[1]), srgb(c[2]), 1.0)),
+            TextureData::RgbaU8(data) => data
+                .get(index)
+                .map(|c| vec4(srgb(c[0]), srgb(c[1]), srgb(c[2]), c[3] as f32 / 255.0)),
+            TextureData::RgbF32(data) => data.get(index).map(|c| vec4(c[0], c[1], c[2], 1.0)),
+            TextureData::RgbaF32(data) => data.get(index).map(|c| vec4(c[0], c[1], c[2], c[3])),
+            _ => None,
+        }
+        .unwrap_or(vec4(1.0, 1.0, 1.0, 1.0))
+    }
+}
+
+///
+/// A rasterizer which renders meshes entirely on the CPU, so it can be used where no GPU is available, for example for generating previews and thumbnails
+/// or for running tests in a continuous integration environment.
+///
+/// Only a small subset of the renderer is supported, namely triangle meshes with a [SoftwareMaterial], which is either unlit or flat shaded, and a depth test.
+/// The [Camera::color_mapping] is applied to the output, but the tone mapping is not, similar to a [ColorMaterial].
+///
+pub struct SoftwareRasterizer {
+    width: u32,
+    height: u32,
+    color: Vec<Vec4>,
+    depth: Vec<f32>,
+}
+
+impl SoftwareRasterizer {
+    ///
+    /// Creates a new rasterizer which renders into a color and depth buffer with the given size.
+    /// The color buffer is initially transparent black and the depth buffer is initially 1.0, ie. the far plane.
+    ///
+    pub fn new(width: u32, height: u32) -> Self {
+        let count = (width * height) as usize;
+        Self {
+            width,
+            height,
+            color: vec![vec4(0.0, 0.0, 0.0, 0.0); count],
+            depth: vec![1.0; count],
+        }
+    }
+
+    ///
+    /// Returns the width of the color and depth buffer.
+    ///
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    ///
+    /// Returns the height of the color and depth buffer.
+    ///
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    ///
+    /// Clears the color and depth buffer as defined by the given clear state, where the color is written as is, like [RenderTarget::clear].
+    ///
+    pub fn clear(&mut self, clear_state: ClearState) -> &mut Self {
+        for color in self.color.iter_mut() {
+            color.x = clear_state.red.unwrap_or(color.x);
+            color.y = clear_state.green.unwrap_or(color.y);
+            color.z = clear_state.blue.unwrap_or(color.z);
+            color.w = clear_state.alpha.unwrap_or(color.w);
+        }
+        if let Some(depth) = clear_state.depth {
+            self.depth.iter_mut().for_each(|d| *d = depth);
+        }
+        self
+    }
+
+    ///
+    /// Renders the given mesh, transformed by the given transformation, with the given material as seen by the given camera.
+    /// The viewport of the camera is ignored and the whole buffer is rendered into, so the aspect ratio of the camera should match the size of the buffer.
+    ///
+    pub fn render(
+        &mut self,
+        camera: &Camera,
+        mesh: &CpuMesh,
+        transformation: Mat4,
+        material: &SoftwareMaterial,
+    ) -> &mut Self {
+        let view_projection = camera.projection() * camera.view();
+        let positions = mesh.positions.to_f32();
+        let world_positions = positions
+            .iter()
+            .map(|p| (transformation * p.extend(1.0)).truncate())
+            .collect::<Vec<_>>();
+        let base_color = material.color.to_linear_srgb();
+        let color_mapping = camera.color_mapping;
+        let gamma = camera.gamma;
+        mesh.for_each_triangle(|i0, i1, i2| {
+            let indices = [i0, i1, i2];
+            let shade = match material.shading {
+                SoftwareShading::Unlit => 1.0,
+                SoftwareShading::Flat {
+                    light_direction,
+                    ambient,
+                } => {
+                    let normal = (world_positions[i1] - world_positions[i0])
+                        .cross(world_positions[i2] - world_positions[i0]);
+                    let light = if normal.magnitude2() > 0.0 && light_direction.magnitude2() > 0.0 {
+                        normal
+                            .normalize()
+                            .dot(-light_direction.normalize())
+                            .max(0.0)
+                    } else {
+                        0.0
+                    };
+                    ambient + (1.0 - ambient) * light
+                }
+            };
+            let vertices = indices.map(|i| Vertex {
+                clip_position: view_projection * world_positions[i].extend(1.0),
+                color: mesh
+                    .colors
+                    .as_ref()
+                    .map(|colors| colors[i].to_linear_srgb())
+                    .unwrap_or(vec4(1.0, 1.0, 1.0, 1.0)),
+                uv: mesh
+                    .uvs
+                    .as_ref()
+                    .map(|uvs| uvs[i])
+                    .unwrap_or(vec2(0.0, 0.0)),
+            });
+            for triangle in clip_near(vertices) {
+                self.rasterize(&triangle, material.cull, |vertex| {
+                    let texture_color = material.sample(vertex.uv);
+                    let color = vec4(
+                        vertex.color.x * base_color.x * texture_color.x,
+                        vertex.color.y * base_color.y * texture_color.y,
+                        vertex.color.z * base_color.z * texture_color.z,
+                        vertex.color.w * base_color.w * texture_color.w,
+                    );
+                    vec4(
+                        map_color(color_mapping, gamma, color.x * shade),
+                        map_color(color_mapping, gamma, color.y * shade),
+                        map_color(color_mapping, gamma, color.z * shade),
+                        color.w,
+                    )
+                });
+            }
+        });
+        self
+    }
+
+    ///
+    /// Renders all triangle meshes of the given model as seen by the given camera, using the albedo color and albedo texture of their materials with the given shading.
+    ///
+    pub fn render_model(
+        &mut self,
+        camera: &Camera,
+        model: &CpuModel,
+        shading: SoftwareShading,
+    ) -> &mut Self {
+        for primitive in model.geometries.iter() {
+            if let three_d_asset::Geometry::Triangles(mesh) = &primitive.geometry {
+                let material = primitive
+                    .material_index
+                    .and_then(|index| model.materials.get(index))
+                    .map(|cpu_material| SoftwareMaterial::from_cpu_material(cpu_material, shading))
+                    .unwrap_or(SoftwareMaterial {
+                        shading,
+                        ..Default::default()
+                    });
+                self.render(camera, mesh, primitive.transformation, &material);
+            }
+        }
+        self
+    }
+
+    ///
+    /// Returns the colors in the color buffer quantized to 8 bits per channel, starting with the top row, like [RenderTarget::read_color].
+    ///
+    pub fn read_color(&self) -> Vec<[u8; 4]> {
+        let quantize = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        (0..self.height)
+            .rev()
+            .flat_map(|y| {
+                let row = (y * self.width) as usize;
+                self.color[row..row + self.width as usize].iter()
+            })
+            .map(|c| [quantize(c.x), quantize(c.y), quantize(c.z), quantize(c.w)])
+            .collect()
+    }
+
+    ///
+    /// Returns the depths in the depth buffer, in the range `[0, 1]` where 0 is the near plane and 1 is the far plane.
+    /// Like [RenderTarget::read_depth], the depths start with the bottom row.
+    ///
+    pub fn read_depth(&self) -> Vec<f32> {
+        self.depth.clone()
+    }
+
+    ///
+    /// Returns the color buffer as a [CpuTexture], for example to save it to disk.
+    ///
+    pub fn to_cpu_texture(&self) -> CpuTexture {
+        CpuTexture {
+            name: "software rasterizer".to_owned(),
+            data: TextureData::RgbaU8(self.read_color()),
+            width: self.width,
+            height: self.height,
+            ..Default::default()
+        }
+    }
+
+    fn rasterize(&mut self, triangle: &[Vertex; 3], cull: Cull, shader: impl Fn(&Vertex) -> Vec4) {
+        let (width, height) = (self.width as f32, self.height as f32);
+        // Screen space positions in pixels, where the origin is the bottom left corner, the depth in [0, 1] and the reciprocal of w
+        let screen = triangle.map(|v| {
+            let w = 1.0 / v.clip_position.w;
+            let ndc = v.clip_position.truncate() * w;
+            vec4(
+                (ndc.x * 0.5 + 0.5) * width,
+                (ndc.y * 0.5 + 0.5) * height,
+                ndc.z * 0.5 + 0.5,
+                w,
+            )
+        });
+        let edge =
+            |a: Vec4, b: Vec4, x: f32, y: f32| (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x);
+        let area = edge(screen[0], screen[1], screen[2].x, screen[2].y);
+        let culled = match cull {
+            Cull::None => false,
+            Cull::Back => area <= 0.0,
+            Cull::Front => area >= 0.0,
+            Cull::FrontAndBack => true,
+        };
+        if culled || area == 0.0 || !area.is_finite() {
+            return;
+        }
+        let min_x = screen
+            .iter()
+            .map(|s| s.x)
+            .fold(f32::MAX, f32::min)
+            .floor()
+            .max(0.0) as u32;
+        let max_x = screen
+            .iter()
+            .map(|s| s.x)
+            .fold(f32::MIN, f32::max)
+            .ceil()
+            .min(width) as u32;
+        let min_y = screen
+            .iter()
+            .map(|s| s.y)
+            .fold(f32::MAX, f32::min)
+            .floor()
+            .max(0.0) as u32;
+        let max_y = screen
+            .iter()
+            .map(|s| s.y)
+            .fold(f32::MIN, f32::max)
+            .ceil()
+            .min(height) as u32;
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let b0 = edge(screen[1], screen[2], px, py) / area;
+                let b1 = edge(screen[2], screen[0], px, py) / area;
+                let b2 = edge(screen[0], screen[1], px, py) / area;
+                if b0 < 0.0 || b1 < 0.0 || b2 < 0.0 {
+                    continue;
+                }
+                let depth = b0 * screen[0].z + b1 * screen[1].z + b2 * screen[2].z;
+                let index = (y * self.width + x) as usize;
+                if !(0.0..=1.0).contains(&depth) || depth >= self.depth[index] {
+                    continue;
+                }
+                // Perspective correct interpolation of the vertex attributes
+                let (w0, w1, w2) = (b0 * screen[0].w, b1 * screen[1].w, b2 * screen[2].w);
+                let w = w0 + w1 + w2;
+                let (w0, w1, w2) = (w0 / w, w1 / w, w2 / w);
+                let vertex = Vertex {
+                    clip_position: vec4(0.0, 0.0, 0.0, 1.0),
+                    color: triangle[0].color * w0 + triangle[1].color * w1 + triangle[2].color * w2,
+                    uv: triangle[0].uv * w0 + triangle[1].uv * w1 + triangle[2].uv * w2,
+                };
+                self.depth[index] = depth;
+                self.color[index] = shader(&vertex);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Vertex {
+    clip_position: Vec4,
+    color: Vec4,
+    uv: Vec2,
+}
+
+impl Vertex {
+    fn lerp(&self, other: &Vertex, t: f32) -> Vertex {
+        Vertex {
+            clip_position: self.clip_position.lerp(other.clip_position, t),
+            color: self.color.lerp(other.color, t),
+            uv: self.uv.lerp(other.uv, t),
+        }
+    }
+}
+
+/// Clips the triangle against the near plane, which results in zero, one or two triangles.
+fn clip_near(vertices: [Vertex; 3]) -> Vec<[Vertex; 3]> {
+    let distance = |v: &Vertex| v.clip_position.z + v.clip_position.w;
+    let mut polygon = Vec::with_capacity(4);
+    for i in 0..3 {
+        let (current, next) = (&vertices[i], &vertices[(i + 1) % 3]);
+        let (d0, d1) = (distance(current), distance(next));
+        if d0 >= 0.0 {
+            polygon.push(*current);
+        }
+        if (d0 >= 0.0) != (d1 >= 0.0) {
+            polygon.push(current.lerp(next, d0 / (d0 - d1)));
+        }
+    }
+    (1..polygon.len().saturating_sub(1))
+        .map(|i| [polygon[0], polygon[i], polygon[i + 1]])
+        .collect()
+}
+
+/// Applies the given color mapping to a single color channel, see the `color_mapping` shader function.
+fn map_color(color_mapping: ColorMapping, gamma: f32, color: f32) -> f32 {
+    match color_mapping {
+        ColorMapping::None => color,
+        ColorMapping::ComputeToSrgb | ColorMapping::ComputeToSrgbDithered => {
+            if color < 0.0031308 {
+                color * 12.92
+            } else {
+                1.055 * color.powf(1.0 / 2.4) - 0.055
+            }
+        }
+        ColorMapping::ComputeToRec709 => {
+            if color < 0.018 {
+                color * 4.5
+            } else {
+                1.099 * color.powf(0.45) - 0.099
+            }
+        }
+        ColorMapping::ComputeToGamma => color.max(0.0).powf(1.0 / gamma.max(0.001)),
+    }
+}
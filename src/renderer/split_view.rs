@@ -0,0 +1,265 @@
+use crate::renderer::*;
+
+///
+/// Renders the same scene from several cameras into a grid of tiled views of a viewport, for example the top, front, side and
+/// perspective views of a modelling application, see [SplitView::new_quad].
+/// The viewport of each camera is updated to cover the tile of the view and each view is rendered unaffected by the other views,
+/// see [SplitView::render].
+///
+/// The input events can be handled for each view separately using [SplitView::handle_events], which for example makes it possible
+/// to control each camera with its own [OrbitControl].
+///
+pub struct SplitView {
+    viewport: Viewport,
+    columns: u32,
+    rows: u32,
+    /// The cameras of the views in row-major order starting with the view in the top left corner.
+    /// The viewports of the cameras are updated by [SplitView::set_viewport].
+    pub cameras: Vec<Camera>,
+    dragged: Option<usize>,
+    hovered: Option<usize>,
+}
+
+impl SplitView {
+    ///
+    /// Creates a new split view which divides the given viewport into the given number of columns and rows of equally sized views,
+    /// one for each of the given cameras in row-major order starting with the view in the top left corner.
+    ///
+    /// # Panic
+    /// Will panic if the number of cameras is not equal to the number of columns times the number of rows.
+    ///
+    pub fn new(viewport: Viewport, columns: u32, rows: u32, cameras: Vec<Camera>) -> Self {
+        assert_eq!(
+            cameras.len(),
+            (columns * rows) as usize,
+            "the number of cameras must be equal to the number of views in a split view"
+        );
+        let mut split_view = Self {
+            viewport,
+            columns,
+            rows,
+            cameras,
+            dragged: None,
+            hovered: None,
+        };
+        split_view.set_viewport(viewport);
+        split_view
+    }
+
+    ///
+    /// Creates a new split view with a two by two grid of views of the given target, with an orthographic top view in the top left corner,
+    /// a perspective view in the top right corner, an orthographic front view in the bottom left corner and an orthographic side view
+    /// in the bottom right corner. The radius is the radius of the area around the target which is visible in each view.
+    ///
+    pub fn new_quad(viewport: Viewport, target: Vec3, radius: f32) -> Self {
+        let orthographic = |direction: Vec3, up: Vec3| {
+            Camera::new_orthographic(
+                viewport,
+                target + direction * 2.0 * radius,
+                target,
+                up,
+                2.2 * radius,
+                0.0,
+                4.0 * radius,
+            )
+        };
+        let perspective = Camera::new_perspective(
+            viewport,
+            target + vec3(1.0, 0.8, 1.0).normalize() * 3.0 * radius,
+            target,
+            vec3(0.0, 1.0, 0.0),
+            degrees(45.0),
+            0.01 * radius,
+            10.0 * radius,
+        );
+        Self::new(
+            viewport,
+            2,
+            2,
+            vec![
+                orthographic(vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, -1.0)),
+                perspective,
+                orthographic(vec3(0.0, 0.0, 1.0), vec3(0.0, 1.0, 0.0)),
+                orthographic(vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)),
+            ],
+        )
+    }
+
+    ///
+    /// Returns the viewport which is divided into the views.
+    ///
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    ///
+    /// Sets the viewport which is divided into the views and updates the viewports of the cameras accordingly.
+    /// Call this for example when the window is resized.
+    ///
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
+        for index in 0..self.cameras.len() {
+            let view_viewport = self.view_viewport(index);
+            self.cameras[index].set_viewport(view_viewport);
+        }
+    }
+
+    ///
+    /// Returns the number of views.
+    ///
+    pub fn view_count(&self) -> usize {
+        self.cameras.len()
+    }
+
+    ///
+    /// Returns the viewport of the view with the given index, where the views are in row-major order starting with the view in the top left corner.
+    ///
+    pub fn view_viewport(&self, index: usize) -> Viewport {
+        let (column, row) = (index as u32 % self.columns, index as u32 / self.columns);
+        let x0 = self.viewport.width * column / self.columns;
+        let x1 = self.viewport.width * (column + 1) / self.columns;
+        let y0 = self.viewport.height - self.viewport.height * (row + 1) / self.rows;
+        let y1 = self.viewport.height - self.viewport.height * row / self.rows;
+        Viewport {
+            x: self.viewport.x + x0 as i32,
+            y: self.viewport.y + y0 as i32,
+            width: x1 - x0,
+            height: y1 - y0,
+        }
+    }
+
+    ///
+    /// Returns the index of the view which contains the given pixel or `None` if the pixel is outside the viewport.
+    ///
+    pub fn view_at(&self, pixel: impl Into<PhysicalPoint>) -> Option<usize> {
+        let pixel = pixel.into();
+        (0..self.cameras.len()).find(|index| {
+            let viewport = self.view_viewport(*index);
+            pixel.x >= viewport.x as f32
+                && pixel.x < (viewport.x + viewport.width as i32) as f32
+                && pixel.y >= viewport.y as f32
+                && pixel.y < (viewport.y + viewport.height as i32) as f32
+        })
+    }
+
+    ///
+    /// Handles the events for each view separately by calling the given callback with the index of the view, the camera of the view
+    /// and the events that belong to the view. A mouse event belongs to the view under the mouse, except while dragging, where all the events
+    /// belong to the view where the drag started, and keyboard events belong to the view that the mouse was last moved over.
+    /// The events which are handled in the callback are also marked as handled in the given list of events.
+    /// Returns true if the callback returned true for any of the views, for example if a camera has changed.
+    ///
+    /// ```no_run
+    /// # use three_d::*;
+    /// # let viewport = Viewport::new_at_origo(1, 1);
+    /// # let mut events: Vec<Event> = Vec::new();
+    /// let mut split_view = SplitView::new_quad(viewport, vec3(0.0, 0.0, 0.0), 5.0);
+    /// let mut controls: Vec<_> = (0..split_view.view_count())
+    ///     .map(|_| OrbitControl::new(vec3(0.0, 0.0, 0.0), 1.0, 100.0))
+    ///     .collect();
+    /// split_view.handle_events(&mut events, |index, camera, events| {
+    ///     controls[index].handle_events(camera, events)
+    /// });
+    /// ```
+    ///
+    pub fn handle_events(
+        &mut self,
+        events: &mut [Event],
+        mut callback: impl FnMut(usize, &mut Camera, &mut [Event]) -> bool,
+    ) -> bool {
+        // The view that each event belongs to, where `None` means that the event belongs to all the views
+        let mut targets = Vec::with_capacity(events.len());
+        for event in events.iter() {
+            let target = match event {
+                Event::MousePress { position, .. } => {
+                    self.hovered = self.view_at(*position);
+                    self.dragged = self.dragged.or(self.hovered);
+                    Some(self.dragged)
+                }
+                Event::MouseRelease { position, .. } => {
+                    self.hovered = self.view_at(*position);
+                    Some(self.dragged.take().or(self.hovered))
+                }
+                Event::MouseMotion { position, .. } => {
+                    self.hovered = self.view_at(*position);
+                    Some(self.dragged.or(self.hovered))
+                }
+                Event::MouseWheel { position, .. } => {
+                    self.hovered = self.view_at(*position);
+                    Some(self.hovered)
+                }
+                Event::KeyPress { .. } | Event::KeyRelease { .. } | Event::Text(..) => {
+                    Some(self.hovered)
+                }
+                Event::MouseLeave => {
+                    self.hovered = None;
+                    None
+                }
+                Event::MouseEnter | Event::ModifiersChange { .. } => None,
+            };
+            targets.push(target);
+        }
+
+        let mut change = false;
+        for index in 0..self.cameras.len() {
+            let indices = targets
+                .iter()
+                .enumerate()
+                .filter(|(_, target)| target.map(|t| t == Some(index)).unwrap_or(true))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            if indices.is_empty() {
+                continue;
+            }
+            let mut view_events = indices
+                .iter()
+                .map(|i| events[*i].clone())
+                .collect::<Vec<_>>();
+            change |= callback(index, &mut self.cameras[index], &mut view_events);
+            for (i, view_event) in indices.into_iter().zip(view_events.iter_mut()) {
+                if let (Some(handled), Some(view_handled)) =
+                    (handled_mut(&mut events[i]), handled_mut(view_event))
+                {
+                    *handled |= *view_handled;
+                }
+            }
+        }
+        change
+    }
+
+    ///
+    /// Clears each view in the given render target as defined by the given clear state.
+    ///
+    pub fn clear(&self, target: &RenderTarget, clear_state: ClearState) {
+        for camera in self.cameras.iter() {
+            target.clear_partially(camera.viewport().into(), clear_state);
+        }
+    }
+
+    ///
+    /// Renders the objects using the given lights into each view of the given render target using the camera of the view,
+    /// see [RenderTarget::render]. Nothing is rendered outside the viewport of each view.
+    ///
+    pub fn render(
+        &self,
+        target: &RenderTarget,
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        lights: &[&dyn Light],
+    ) {
+        for camera in self.cameras.iter() {
+            target.render_partially(camera.viewport().into(), camera, objects.clone(), lights);
+        }
+    }
+}
+
+fn handled_mut(event: &mut Event) -> Option<&mut bool> {
+    match event {
+        Event::MousePress { handled, .. }
+        | Event::MouseRelease { handled, .. }
+        | Event::MouseMotion { handled, .. }
+        | Event::MouseWheel { handled, .. }
+        | Event::KeyPress { handled, .. }
+        | Event::KeyRelease { handled, .. } => Some(handled),
+        _ => None,
+    }
+}
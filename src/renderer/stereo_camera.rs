@@ -0,0 +1,131 @@
+use crate::renderer::*;
+
+///
+/// The view of one eye of a headset, see [StereoCamera::set_eye_views].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EyeView {
+    /// The pose of the eye, ie. the transformation from the space of the eye to world space.
+    /// The eye looks along the negative z-axis with the y-axis as up direction in eye space.
+    pub pose: Mat4,
+    /// The projection matrix of the eye, which is usually asymmetric, see [Camera::set_projection_matrix].
+    pub projection: Mat4,
+}
+
+///
+/// A pair of cameras, one for each eye, for rendering a scene in stereo, for example side-by-side on a 3D display or in a VR headset.
+///
+/// The eye cameras are either derived from a single camera, see [StereoCamera::update], where the eyes are placed on each side of the camera
+/// separated by the interpupillary distance (IPD) and looking in the same direction, or given directly by the pose and projection of each eye of a headset,
+/// see [StereoCamera::set_eye_views].
+/// When rendering side-by-side, see [StereoCamera::render], the left eye is rendered into the left half of the viewport and the right eye into the right half.
+///
+#[derive(Clone, Debug)]
+pub struct StereoCamera {
+    /// The camera of the left eye.
+    pub left: Camera,
+    /// The camera of the right eye.
+    pub right: Camera,
+    /// The distance between the eyes in world units, for example 0.063 if the world units are meters.
+    pub ipd: f32,
+}
+
+impl StereoCamera {
+    ///
+    /// Creates a new stereo camera with eyes placed on each side of the given camera separated by the given interpupillary distance,
+    /// where each eye is rendered into one half of the viewport of the given camera.
+    ///
+    pub fn new(camera: &Camera, ipd: f32) -> Self {
+        let mut stereo_camera = Self {
+            left: camera.clone(),
+            right: camera.clone(),
+            ipd,
+        };
+        stereo_camera.update(camera);
+        stereo_camera
+    }
+
+    ///
+    /// Updates the eye cameras from the given camera, so that the eyes are placed on each side of the camera separated by [StereoCamera::ipd]
+    /// and each eye is rendered into one half of the viewport of the camera.
+    /// The projection, tone mapping and other properties of the camera are copied to both eyes.
+    /// Call this each frame after the camera has changed, for example by a camera control.
+    ///
+    pub fn update(&mut self, camera: &Camera) {
+        let viewport = camera.viewport();
+        let half_width = viewport.width / 2;
+        let offset = camera.right_direction().normalize() * (0.5 * self.ipd);
+        for (eye, x, width, offset) in [
+            (&mut self.left, viewport.x, half_width, -offset),
+            (
+                &mut self.right,
+                viewport.x + half_width as i32,
+                viewport.width - half_width,
+                offset,
+            ),
+        ] {
+            *eye = camera.clone();
+            eye.set_viewport(Viewport {
+                x,
+                y: viewport.y,
+                width,
+                height: viewport.height,
+            });
+            eye.set_view(
+                camera.position() + offset,
+                camera.target() + offset,
+                *camera.up(),
+            );
+        }
+    }
+
+    ///
+    /// Sets the view and projection of the eye cameras from the given views, for example as reported by a VR headset.
+    /// The viewports of the eye cameras are not changed and [StereoCamera::ipd] is set to the distance between the eyes.
+    /// On web, the views are for example the `transform.matrix` and `projectionMatrix` of each of the views of a WebXR viewer pose,
+    /// which is done by [WebXrFrame::update_camera](crate::window::WebXrFrame::update_camera) when using the [window](crate::window) module.
+    ///
+    pub fn set_eye_views(&mut self, left: EyeView, right: EyeView) {
+        for (eye, view) in [(&mut self.left, left), (&mut self.right, right)] {
+            let position = view
+                .pose
+                .transform_point(Point3::new(0.0, 0.0, 0.0))
+                .to_vec();
+            let direction = view.pose.transform_vector(vec3(0.0, 0.0, -1.0));
+            let up = view.pose.transform_vector(vec3(0.0, 1.0, 0.0));
+            eye.set_view(position, position + direction, up);
+            eye.set_projection_matrix(Some(view.projection));
+        }
+        self.ipd = self.left.position().distance(*self.right.position());
+    }
+
+    ///
+    /// Returns the cameras of the left and right eye.
+    ///
+    pub fn eyes(&self) -> [&Camera; 2] {
+        [&self.left, &self.right]
+    }
+
+    ///
+    /// Clears the viewport of each eye in the given render target as defined by the given clear state.
+    ///
+    pub fn clear(&self, target: &RenderTarget, clear_state: ClearState) {
+        for eye in self.eyes() {
+            target.clear_partially(eye.viewport().into(), clear_state);
+        }
+    }
+
+    ///
+    /// Renders the objects using the given lights into the viewport of each eye in the given render target, see [RenderTarget::render].
+    ///
+    pub fn render(
+        &self,
+        target: &RenderTarget,
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        lights: &[&dyn Light],
+    ) {
+        for eye in self.eyes() {
+            target.render_partially(eye.viewport().into(), eye, objects.clone(), lights);
+        }
+    }
+}
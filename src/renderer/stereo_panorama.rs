@@ -0,0 +1,246 @@
+use crate::renderer::*;
+
+///
+/// Renders omni-directional stereo (ODS) panoramas, ie. a 360° equirectangular image for each eye, for example to export a scene as a 360° VR video.
+///
+/// The panorama is rendered in slits, where each slit is a range of longitudes. For each slit and each eye, the scene is rendered into the needed
+/// parts of a cube map from the eye position, which is on a circle with the interpupillary distance as diameter and offset perpendicular to the
+/// direction of the slit, and the cube map is then reprojected into the columns of the equirectangular image that the slit covers.
+/// More slits gives a more correct stereo effect but is slower.
+///
+/// As for all omni-directional stereo panoramas, the stereo effect is incorrect when looking straight up or down.
+///
+pub struct StereoPanorama {
+    context: Context,
+    /// The distance between the eyes in world units, for example 0.063 if the world units are meters.
+    pub ipd: f32,
+    /// The number of slits the 360° around the viewer is divided into, at least 4 slits are used.
+    pub slit_count: u32,
+    faces: Option<(TextureCubeMap, DepthTextureCubeMap)>,
+}
+
+impl StereoPanorama {
+    ///
+    /// Creates a new stereo panorama renderer with the given interpupillary distance and 64 slits.
+    ///
+    pub fn new(context: &Context, ipd: f32) -> Self {
+        Self {
+            context: context.clone(),
+            ipd,
+            slit_count: 64,
+            faces: None,
+        }
+    }
+
+    ///
+    /// Renders the objects using the given lights as an over-under stereo panorama into the viewport of the given camera in the given render target,
+    /// ie. the equirectangular image of the left eye is rendered into the top half of the viewport and the right eye into the bottom half.
+    /// The viewport should therefore have the same width and height, for example 4096x4096 pixels.
+    ///
+    /// The center between the eyes is the position of the camera and the center of the panorama is in the view direction of the camera projected
+    /// onto the plane orthogonal to the up direction of the camera, which is the up direction of the panorama.
+    /// The near and far planes, tone mapping, color mapping and exposure of the camera are used, whereas the projection and jitter are not.
+    ///
+    /// The parts of the panorama not covered by any of the objects are transparent black, so either clear the render target first
+    /// and blend the result or for example add a [Skybox] to the objects.
+    ///
+    pub fn render(
+        &mut self,
+        target: &RenderTarget,
+        camera: &Camera,
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        lights: &[&dyn Light],
+    ) {
+        let viewport = camera.viewport();
+        let face_size = (viewport.width / 4).max(1);
+        if self
+            .faces
+            .as_ref()
+            .map(|(f, _)| f.width() != face_size)
+            .unwrap_or(true)
+        {
+            self.faces = Some((
+                TextureCubeMap::new_empty::<[f16; 4]>(
+                    &self.context,
+                    face_size,
+                    face_size,
+                    Interpolation::Linear,
+                    Interpolation::Linear,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ),
+                DepthTextureCubeMap::new::<f32>(
+                    &self.context,
+                    face_size,
+                    face_size,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                ),
+            ));
+        }
+        let (color_texture, depth_texture) = self.faces.as_mut().unwrap();
+
+        // The basis of the panorama, where the center of the panorama is in the forward direction
+        let up = camera.up().normalize();
+        let view_direction = camera.view_direction();
+        let mut forward = view_direction - up * up.dot(view_direction);
+        if forward.magnitude2() < 0.000001 {
+            forward = if up.x.abs() < 0.9 {
+                vec3(1.0, 0.0, 0.0)
+            } else {
+                vec3(0.0, 0.0, 1.0)
+            };
+            forward -= up * up.dot(forward);
+        }
+        let forward = forward.normalize();
+        let right = forward.cross(up);
+        let basis = Mat3::from_cols(right, up, forward);
+
+        let slit_count = self.slit_count.max(4);
+        // Only the columns around the center of the front, top and bottom sides of the cube map are needed for a slit
+        let half_width = (0.5 * face_size as f32 * (std::f32::consts::PI / slit_count as f32).tan())
+            .ceil() as u32
+            + 2;
+        let x = (face_size / 2).saturating_sub(half_width);
+        let face_scissor_box = ScissorBox {
+            x: x as i32,
+            y: 0,
+            width: (2 * half_width).min(face_size - x),
+            height: face_size,
+        };
+
+        let mut face_camera = camera.clone();
+        face_camera.set_viewport(Viewport::new_at_origo(face_size, face_size));
+        face_camera.set_perspective_projection(degrees(90.0), camera.z_near(), camera.z_far());
+        face_camera.set_jitter(vec2(0.0, 0.0));
+        face_camera.tone_mapping = ToneMapping::None;
+        face_camera.color_mapping = ColorMapping::None;
+
+        let top_height = viewport.height / 2;
+        for (eye_offset, y, height) in [
+            (-0.5 * self.ipd, viewport.y + top_height as i32, top_height),
+            (0.5 * self.ipd, viewport.y, viewport.height - top_height),
+        ] {
+            let mut eye_camera = camera.clone();
+            eye_camera.set_viewport(Viewport {
+                x: viewport.x,
+                y,
+                width: viewport.width,
+                height,
+            });
+            for slit in 0..slit_count {
+                let longitude =
+                    ((slit as f32 + 0.5) / slit_count as f32 * 2.0 - 1.0) * std::f32::consts::PI;
+                let direction = forward * longitude.cos() + right * longitude.sin();
+                let position = camera.position() + direction.cross(up) * eye_offset;
+                let rotation = Mat3::from_cols(up.cross(direction), up, direction);
+                for side in [CubeMapSide::Front, CubeMapSide::Top, CubeMapSide::Bottom] {
+                    face_camera.set_view(
+                        position,
+                        position + rotation * side.direction(),
+                        rotation * side.up(),
+                    );
+                    RenderTarget::new(
+                        color_texture.as_color_target(&[side], None),
+                        depth_texture.as_depth_target(side),
+                    )
+                    .clear_partially(
+                        face_scissor_box,
+                        ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0),
+                    )
+                    .render_partially(
+                        face_scissor_box,
+                        &face_camera,
+                        objects.clone(),
+                        lights,
+                    );
+                }
+
+                let x0 = viewport.width * slit / slit_count;
+                let x1 = viewport.width * (slit + 1) / slit_count;
+                target.apply_screen_effect_partially(
+                    ScissorBox {
+                        x: viewport.x + x0 as i32,
+                        y,
+                        width: x1 - x0,
+                        height,
+                    },
+                    &PanoramaEffect {
+                        cube_map: color_texture,
+                        to_cube_map: rotation.transpose() * basis,
+                    },
+                    &eye_camera,
+                    &[],
+                    None,
+                    None,
+                );
+            }
+        }
+    }
+}
+
+struct PanoramaEffect<'a> {
+    cube_map: &'a TextureCubeMap,
+    to_cube_map: Mat3,
+}
+
+impl<'a> Effect for PanoramaEffect<'a> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}{}{}",
+            ToneMapping::fragment_shader_source(),
+            ColorMapping::fragment_shader_source(),
+            include_str!("effect/shaders/panorama_effect.frag")
+        )
+    }
+
+    fn id(
+        &self,
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> u16 {
+        0b1u16 << 14 | 0b1u16 << 11 | 0b110u16 << 7
+    }
+
+    fn fragment_attributes(&self) -> FragmentAttributes {
+        FragmentAttributes {
+            uv: true,
+            ..FragmentAttributes::NONE
+        }
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        camera
+            .tone_mapping
+            .use_uniforms_with_exposure(program, camera.exposure);
+        camera
+            .color_mapping
+            .use_uniforms_with_gamma(program, camera.gamma);
+        program.use_texture_cube("cubeMap", self.cube_map);
+        program.use_uniform("toCubeMap", self.to_cube_map);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::Always,
+            cull: Cull::Back,
+            ..Default::default()
+        }
+    }
+}
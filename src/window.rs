@@ -4,6 +4,7 @@
 //! from a [glow](https://crates.io/crates/glow) OpenGL/WebGL context.
 //! * If full control over the window and event handling, but not the context creation, is desired, use a [WindowedContext] or [HeadlessContext].
 //! * Finally, for an easy setup, use [Window::new] or [Window::from_winit_window], the latter will provide full control over the creation of the window.
+//! * To just show some models, use a [Viewer] which also sets up the camera, control and lights.
 //!
 //!
 
@@ -13,8 +14,14 @@ mod winit_window;
 #[cfg(feature = "window")]
 pub use winit_window::*;
 
-#[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+#[cfg(feature = "headless")]
 #[cfg_attr(docsrs, doc(feature = "headless"))]
 mod headless;
-#[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+#[cfg(feature = "headless")]
 pub use headless::*;
+
+#[cfg(all(feature = "golden-image", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(feature = "golden-image"))]
+mod golden_image;
+#[cfg(all(feature = "golden-image", not(target_arch = "wasm32")))]
+pub use golden_image::*;
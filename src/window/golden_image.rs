@@ -0,0 +1,291 @@
+use crate::core::*;
+use crate::window::{HeadlessContext, HeadlessError};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+///
+/// The environment variable which, when set, makes [check_golden_image] overwrite the golden images with the rendered images instead of comparing them.
+///
+pub const UPDATE_GOLDEN_IMAGES: &str = "THREE_D_UPDATE_GOLDEN_IMAGES";
+
+///
+/// Error associated with golden image testing.
+///
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+pub enum GoldenImageError {
+    #[error("the images have different sizes, expected {0}x{1} but got {2}x{3}")]
+    SizeMismatch(u32, u32, u32, u32),
+    #[error("only images with u8 channels can be compared, but got {0}")]
+    UnsupportedFormat(String),
+    #[error("{different_pixels} of {total_pixels} pixels differ from the golden image {path}, see {actual} and {diff}")]
+    Mismatch {
+        path: PathBuf,
+        actual: PathBuf,
+        diff: PathBuf,
+        different_pixels: usize,
+        total_pixels: usize,
+    },
+    #[error("failed loading or saving an image")]
+    Asset(#[from] three_d_asset::Error),
+    #[error("failed creating a headless context")]
+    Headless(#[from] HeadlessError),
+}
+
+///
+/// Specifies how much a rendered image may differ from a golden image, see [compare_images] and [check_golden_image].
+/// Some tolerance is usually needed to compare images rendered on different platforms and graphics drivers.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageTolerance {
+    /// The maximum difference in any of the color channels before a pixel is counted as different.
+    pub channel: u8,
+    /// The fraction of the pixels, in the range `[0, 1]`, that are allowed to be different.
+    pub pixel_fraction: f32,
+}
+
+impl Default for ImageTolerance {
+    fn default() -> Self {
+        Self {
+            channel: 2,
+            pixel_fraction: 0.001,
+        }
+    }
+}
+
+///
+/// The result of comparing two images using [compare_images].
+///
+#[derive(Clone, Debug)]
+pub struct ImageDifference {
+    /// The number of pixels where the difference in at least one channel is larger than the channel tolerance.
+    pub different_pixels: usize,
+    /// The total number of pixels in each of the images.
+    pub total_pixels: usize,
+    /// The largest difference in any channel of any pixel.
+    pub max_difference: u8,
+    /// An image which shows the different pixels in red on top of a faded version of the expected image.
+    pub diff_image: CpuTexture,
+}
+
+impl ImageDifference {
+    ///
+    /// Returns whether the difference is within the given tolerance.
+    ///
+    pub fn is_within(&self, tolerance: &ImageTolerance) -> bool {
+        self.different_pixels as f32 <= tolerance.pixel_fraction * self.total_pixels as f32
+    }
+}
+
+///
+/// Compares the pixels of two images with u8 channels, for example a golden image and an image rendered by [GoldenImageRenderer::render].
+///
+/// Returns an error if the images do not have the same size or if one of the images does not have u8 channels.
+///
+pub fn compare_images(
+    expected: &CpuTexture,
+    actual: &CpuTexture,
+    tolerance: &ImageTolerance,
+) -> Result<ImageDifference, GoldenImageError> {
+    if expected.width != actual.width || expected.height != actual.height {
+        Err(GoldenImageError::SizeMismatch(
+            expected.width,
+            expected.height,
+            actual.width,
+            actual.height,
+        ))?;
+    }
+    let expected_pixels = rgba_pixels(&expected.data)?;
+    let actual_pixels = rgba_pixels(&actual.data)?;
+    let mut different_pixels = 0;
+    let mut max_difference = 0;
+    let diff_pixels = expected_pixels
+        .iter()
+        .zip(actual_pixels.iter())
+        .map(|(e, a)| {
+            let difference = (0..4).map(|i| e[i].abs_diff(a[i])).max().unwrap();
+            max_difference = max_difference.max(difference);
+            if difference > tolerance.channel {
+                different_pixels += 1;
+                [255, 0, 0, 255]
+            } else {
+                let faded = |c: u8| 128 + c / 2;
+                [faded(e[0]), faded(e[1]), faded(e[2]), 255]
+            }
+        })
+        .collect();
+    Ok(ImageDifference {
+        different_pixels,
+        total_pixels: expected_pixels.len(),
+        max_difference,
+        diff_image: CpuTexture {
+            data: TextureData::RgbaU8(diff_pixels),
+            width: expected.width,
+            height: expected.height,
+            ..Default::default()
+        },
+    })
+}
+
+fn rgba_pixels(data: &TextureData) -> Result<Vec<[u8; 4]>, GoldenImageError> {
+    Ok(match data {
+        TextureData::RU8(data) => data.iter().map(|r| [*r, *r, *r, 255]).collect(),
+        TextureData::RgU8(data) => data.iter().map(|c| [c[0], c[1], 0, 255]).collect(),
+        TextureData::RgbU8(data) => data.iter().map(|c| [c[0], c[1], c[2], 255]).collect(),
+        TextureData::RgbaU8(data) => data.clone(),
+        _ => Err(GoldenImageError::UnsupportedFormat(format!("{:?}", data)))?,
+    })
+}
+
+///
+/// Compares the image with the golden image stored at the given path, which must be a png file, and returns an error if they differ more than the given tolerance.
+/// If they differ, the image and the difference, see [ImageDifference::diff_image], are saved next to the golden image
+/// with the extension replaced by `.actual.png` and `.diff.png`, so the cause of a failing test is easy to inspect.
+///
+/// If the golden image does not exist or if the [UPDATE_GOLDEN_IMAGES] environment variable is set,
+/// the image is saved as the new golden image instead, which should then be reviewed and committed together with the test.
+///
+pub fn check_golden_image(
+    path: impl AsRef<Path>,
+    image: &CpuTexture,
+    tolerance: &ImageTolerance,
+) -> Result<(), GoldenImageError> {
+    use three_d_asset::io::Serialize;
+    let path = path.as_ref();
+    if !path.exists() || std::env::var_os(UPDATE_GOLDEN_IMAGES).is_some() {
+        three_d_asset::io::save(&image.serialize(path)?)?;
+        return Ok(());
+    }
+    let golden: CpuTexture = three_d_asset::io::load_and_deserialize(path)?;
+    let difference = compare_images(&golden, image, tolerance)?;
+    if !difference.is_within(tolerance) {
+        let with_suffix = |suffix: &str| {
+            let mut name = path.file_stem().unwrap_or_default().to_os_string();
+            name.push(suffix);
+            path.with_file_name(name)
+        };
+        let actual = with_suffix(".actual.png");
+        let diff = with_suffix(".diff.png");
+        three_d_asset::io::save(&image.serialize(&actual)?)?;
+        three_d_asset::io::save(&difference.diff_image.serialize(&diff)?)?;
+        Err(GoldenImageError::Mismatch {
+            path: path.to_path_buf(),
+            actual,
+            diff,
+            different_pixels: difference.different_pixels,
+            total_pixels: difference.total_pixels,
+        })?;
+    }
+    Ok(())
+}
+
+///
+/// Renders into an offscreen color and depth texture using a [HeadlessContext] and reads back the result,
+/// which is useful for writing regression tests that compare the rendered images to golden images using [check_golden_image].
+///
+/// ```no_run
+/// # use three_d::*;
+/// let mut renderer = GoldenImageRenderer::new(128, 128).unwrap();
+/// let camera = Camera::new_perspective(
+///     renderer.viewport(),
+///     vec3(0.0, 0.0, 3.0),
+///     vec3(0.0, 0.0, 0.0),
+///     vec3(0.0, 1.0, 0.0),
+///     degrees(45.0),
+///     0.1,
+///     10.0,
+/// );
+/// let cube = Gm::new(
+///     Mesh::new(renderer.context(), &CpuMesh::cube()),
+///     NormalMaterial::default(),
+/// );
+/// let image = renderer.render(|target| {
+///     target
+///         .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+///         .render(&camera, &cube, &[]);
+/// });
+/// check_golden_image("tests/golden/cube.png", &image, &ImageTolerance::default()).unwrap();
+/// ```
+///
+pub struct GoldenImageRenderer {
+    context: Context,
+    _headless_context: Option<HeadlessContext>,
+    color_texture: Texture2D,
+    depth_texture: DepthTexture2D,
+}
+
+impl GoldenImageRenderer {
+    ///
+    /// Creates a new headless context and a color and depth texture with the given size to render into.
+    ///
+    pub fn new(width: u32, height: u32) -> Result<Self, GoldenImageError> {
+        let headless_context = HeadlessContext::new()?;
+        let mut renderer = Self::from_context(&headless_context, width, height);
+        renderer._headless_context = Some(headless_context);
+        Ok(renderer)
+    }
+
+    ///
+    /// Creates a color and depth texture with the given size to render into using an existing graphics context,
+    /// for example if a [HeadlessContext] cannot be created on the test machine and the context is created in another way.
+    ///
+    pub fn from_context(context: &Context, width: u32, height: u32) -> Self {
+        let color_texture = Texture2D::new_empty::<[u8; 4]>(
+            context,
+            width,
+            height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let depth_texture = DepthTexture2D::new::<f32>(
+            context,
+            width,
+            height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        Self {
+            context: context.clone(),
+            _headless_context: None,
+            color_texture,
+            depth_texture,
+        }
+    }
+
+    ///
+    /// Returns the graphics context used for rendering, which should be used to create the objects to render.
+    ///
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    ///
+    /// Returns the viewport covering the entire image.
+    ///
+    pub fn viewport(&self) -> Viewport {
+        Viewport::new_at_origo(self.color_texture.width(), self.color_texture.height())
+    }
+
+    ///
+    /// Calls the callback with a render target to clear and render into and returns the resulting image.
+    /// The content of the previous image is kept, so clear the render target to avoid that.
+    ///
+    pub fn render(&mut self, callback: impl FnOnce(&RenderTarget)) -> CpuTexture {
+        let width = self.color_texture.width();
+        let height = self.color_texture.height();
+        let target = RenderTarget::new(
+            self.color_texture.as_color_target(None),
+            self.depth_texture.as_depth_target(),
+        );
+        callback(&target);
+        CpuTexture {
+            data: TextureData::RgbaU8(target.read_color()),
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+}
@@ -1,9 +1,4 @@
 use crate::{Context, CoreError};
-use glutin_029::{
-    dpi::PhysicalSize, event_loop::EventLoop, ContextBuilder, ContextCurrentState, CreationError,
-    NotCurrent, PossiblyCurrent,
-};
-use std::rc::Rc;
 use thiserror::Error;
 
 ///
@@ -12,116 +7,263 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 #[allow(missing_docs)]
 pub enum HeadlessError {
+    #[cfg(not(target_arch = "wasm32"))]
     #[error("glutin error")]
     GlutinCreationError(#[from] glutin_029::CreationError),
+    #[cfg(not(target_arch = "wasm32"))]
     #[error("glutin error")]
     GlutinContextError(#[from] glutin_029::ContextError),
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("glutin error")]
+    GlutinError(#[from] glutin::error::Error),
+    #[cfg(target_arch = "wasm32")]
+    #[error("failed creating a new offscreen canvas{0}")]
+    OffscreenCanvasNotSupported(String),
+    #[cfg(target_arch = "wasm32")]
+    #[error("WebGL2 is not supported by the offscreen canvas{0}")]
+    WebGL2NotSupported(String),
     #[error("error in three-d")]
     ThreeDError(#[from] CoreError),
 }
 
-///
-/// A headless graphics context, ie. a graphics context that is not associated with any window.
-/// For a graphics context associated with a window, see [WindowedContext](crate::WindowedContext).
-/// Can only be created on native, not on web.
-///
-#[derive(Clone)]
-pub struct HeadlessContext {
-    context: Context,
-    _glutin_context: Rc<glutin_029::Context<PossiblyCurrent>>,
-}
+#[cfg(target_arch = "wasm32")]
+mod inner {
+    use super::*;
+    use std::sync::Arc;
+    use wasm_bindgen::JsCast;
 
-impl HeadlessContext {
     ///
-    /// Creates a new headless graphics context.
+    /// A headless graphics context, ie. a graphics context that is not associated with any window.
+    /// For a graphics context associated with a window, see [WindowedContext](crate::WindowedContext).
+    /// On web, the context is created from an `OffscreenCanvas`, so it can also be created in a web worker.
     ///
-    #[allow(unsafe_code)]
-    pub fn new() -> Result<Self, HeadlessError> {
-        let cb = ContextBuilder::new();
-        let glutin_context = build_context(cb)?;
-        let glutin_context = unsafe { glutin_context.make_current().map_err(|(_, e)| e)? };
-        let context = Context::from_gl_context(std::sync::Arc::new(unsafe {
-            crate::context::Context::from_loader_function(|s| {
-                glutin_context.get_proc_address(s) as *const _
+    #[derive(Clone)]
+    pub struct HeadlessContext {
+        context: Context,
+    }
+
+    impl HeadlessContext {
+        ///
+        /// Creates a new headless graphics context.
+        ///
+        pub fn new() -> Result<Self, HeadlessError> {
+            let canvas = web_sys::OffscreenCanvas::new(1, 1)
+                .map_err(|e| HeadlessError::OffscreenCanvasNotSupported(format!(": {:?}", e)))?;
+            let webgl_context = canvas
+                .get_context("webgl2")
+                .map_err(|e| HeadlessError::WebGL2NotSupported(format!(": {:?}", e)))?
+                .ok_or(HeadlessError::WebGL2NotSupported("".to_string()))?
+                .dyn_into::<web_sys::WebGl2RenderingContext>()
+                .map_err(|e| HeadlessError::WebGL2NotSupported(format!(": {:?}", e)))?;
+            for extension in [
+                "EXT_color_buffer_float",
+                "OES_texture_float_linear",
+                "OES_texture_half_float_linear",
+            ] {
+                webgl_context.get_extension(extension).map_err(|e| {
+                    HeadlessError::WebGL2NotSupported(format!(
+                        ", the {} extension is missing: {:?}",
+                        extension, e
+                    ))
+                })?;
+            }
+            Ok(Self {
+                context: Context::from_gl_context(Arc::new(
+                    crate::context::Context::from_webgl2_context(webgl_context),
+                ))?,
             })
-        }))?;
-        Ok(Self {
-            context,
-            _glutin_context: Rc::new(glutin_context),
-        })
+        }
     }
-}
 
-impl std::ops::Deref for HeadlessContext {
-    type Target = Context;
-    fn deref(&self) -> &Self::Target {
-        &self.context
+    impl std::ops::Deref for HeadlessContext {
+        type Target = Context;
+        fn deref(&self) -> &Self::Target {
+            &self.context
+        }
     }
 }
 
-/*#[cfg(target_os = "linux")]
-fn build_context_surfaceless<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-    el: &EventLoop<()>,
-) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
-    use glutin_029::platform::unix::HeadlessContextExt;
-    cb.build_surfaceless(&el)
-}*/
-
-fn build_context_headless<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-    el: &EventLoop<()>,
-) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
-    let size_one = PhysicalSize::new(1, 1);
-    cb.build_headless(&el, size_one)
-}
+#[cfg(not(target_arch = "wasm32"))]
+mod inner {
+    use super::*;
+    use glutin_029::{
+        dpi::PhysicalSize, event_loop::EventLoop, ContextBuilder, ContextCurrentState,
+        CreationError, NotCurrent, PossiblyCurrent,
+    };
+    use std::rc::Rc;
 
-#[cfg(target_os = "linux")]
-fn build_context_osmesa<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
-    use glutin_029::platform::unix::HeadlessContextExt;
-    let size_one = PhysicalSize::new(1, 1);
-    cb.build_osmesa(size_one)
-}
+    ///
+    /// The underlying graphics context, which must be kept alive as long as the [HeadlessContext] is used.
+    ///
+    #[allow(dead_code)]
+    enum GlutinContext {
+        #[cfg(target_os = "linux")]
+        Surfaceless(glutin::api::egl::context::PossiblyCurrentContext),
+        Headless(glutin_029::Context<PossiblyCurrent>),
+    }
 
-#[cfg(target_os = "linux")]
-fn build_context<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
-    // On unix operating systems, you should always try for surfaceless first,
-    // and if that does not work, headless (pbuffers), and if that too fails,
-    // finally osmesa.
-    //
-    // If willing, you could attempt to use hidden windows instead of os mesa,
-    // but note that you must handle events for the window that come on the
-    // events loop.
-
-    /*
-    let err1 = match build_context_surfaceless(cb.clone(), &el) {
-        Ok(ctx) => return Ok((ctx, el)),
-        Err(err) => err,
-    };*/
-
-    let _err3 = match build_context_osmesa(cb.clone()) {
-        Ok(ctx) => return Ok(ctx),
-        Err(err) => err,
-    };
+    ///
+    /// A headless graphics context, ie. a graphics context that is not associated with any window.
+    /// For a graphics context associated with a window, see [WindowedContext](crate::WindowedContext).
+    /// On Linux, a surfaceless EGL context is created if possible, which does not require a display server,
+    /// otherwise an OSMesa context or finally a pbuffer context.
+    ///
+    #[derive(Clone)]
+    pub struct HeadlessContext {
+        context: Context,
+        _glutin_context: Rc<GlutinContext>,
+    }
 
-    let el = EventLoop::new();
+    impl HeadlessContext {
+        ///
+        /// Creates a new headless graphics context.
+        ///
+        #[allow(unsafe_code)]
+        pub fn new() -> Result<Self, HeadlessError> {
+            #[cfg(target_os = "linux")]
+            if let Ok(context) = Self::new_surfaceless() {
+                return Ok(context);
+            }
 
-    let err2 = match build_context_headless(cb, &el) {
-        Ok(ctx) => return Ok(ctx),
-        Err(err) => err,
-    };
+            let cb = ContextBuilder::new();
+            let glutin_context = build_context(cb)?;
+            let glutin_context = unsafe { glutin_context.make_current().map_err(|(_, e)| e)? };
+            let loader = |s: &str| glutin_context.get_proc_address(s) as *const _;
+            let mut context = Context::from_gl_context(std::sync::Arc::new(unsafe {
+                crate::context::Context::from_loader_function(loader)
+            }))?;
+            unsafe { context.enable_program_binaries(loader) };
+            Ok(Self {
+                context,
+                _glutin_context: Rc::new(GlutinContext::Headless(glutin_context)),
+            })
+        }
 
-    Err(err2)
-}
+        ///
+        /// Creates a surfaceless EGL context on the first hardware device or, if there is none, on a software device.
+        ///
+        #[cfg(target_os = "linux")]
+        #[allow(unsafe_code)]
+        fn new_surfaceless() -> Result<Self, HeadlessError> {
+            use glutin::api::egl::{device::Device, display::Display};
+            use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+            use glutin::context::{ContextApi, ContextAttributesBuilder, Version};
+            use glutin::prelude::*;
+            use std::ffi::CString;
+
+            let mut devices = Device::query_devices()?.collect::<Vec<_>>();
+            devices.sort_by_key(|d| d.extensions().contains("EGL_MESA_device_software"));
+            let no_device =
+                || HeadlessError::GlutinError(glutin::error::ErrorKind::NotFound.into());
+            let device = devices.first().ok_or_else(no_device)?;
+            let display = unsafe { Display::with_device(device, None)? };
+            let template = ConfigTemplateBuilder::new()
+                .with_surface_type(ConfigSurfaceTypes::empty())
+                .build();
+            let config = unsafe { display.find_configs(template)? }
+                .next()
+                .ok_or_else(no_device)?;
+            let attributes = ContextAttributesBuilder::new()
+                .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+                .build(None);
+            let glutin_context = unsafe { display.create_context(&config, &attributes)? }
+                .make_current_surfaceless()?;
+            let loader = |s: &str| {
+                CString::new(s)
+                    .map(|s| display.get_proc_address(&s))
+                    .unwrap_or(std::ptr::null())
+            };
+            let mut context = Context::from_gl_context(std::sync::Arc::new(unsafe {
+                crate::context::Context::from_loader_function(loader)
+            }))?;
+            unsafe { context.enable_program_binaries(loader) };
+
+            // The shader programs are compiled on a thread with a context which shares resources with this context
+            let shared_attributes = ContextAttributesBuilder::new()
+                .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+                .with_sharing(&glutin_context)
+                .build(None);
+            if let Ok(compile_context) =
+                unsafe { display.create_context(&config, &shared_attributes) }
+            {
+                let display = display.clone();
+                unsafe {
+                    context.enable_shader_compile_thread(move || {
+                        let compile_context = compile_context.make_current_surfaceless().ok()?;
+                        let context = crate::context::Context::from_loader_function(|s| {
+                            CString::new(s)
+                                .map(|s| display.get_proc_address(&s))
+                                .unwrap_or(std::ptr::null())
+                        });
+                        Some((context, Box::new(compile_context) as Box<dyn std::any::Any>))
+                    });
+                }
+            }
+            Ok(Self {
+                context,
+                _glutin_context: Rc::new(GlutinContext::Surfaceless(glutin_context)),
+            })
+        }
+    }
 
-#[cfg(not(target_os = "linux"))]
-fn build_context<T1: ContextCurrentState>(
-    cb: ContextBuilder<T1>,
-) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
-    let el = EventLoop::new();
-    build_context_headless(cb.clone(), &el)
+    impl std::ops::Deref for HeadlessContext {
+        type Target = Context;
+        fn deref(&self) -> &Self::Target {
+            &self.context
+        }
+    }
+
+    fn build_context_headless<T1: ContextCurrentState>(
+        cb: ContextBuilder<T1>,
+        el: &EventLoop<()>,
+    ) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
+        let size_one = PhysicalSize::new(1, 1);
+        cb.build_headless(&el, size_one)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn build_context_osmesa<T1: ContextCurrentState>(
+        cb: ContextBuilder<T1>,
+    ) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
+        use glutin_029::platform::unix::HeadlessContextExt;
+        let size_one = PhysicalSize::new(1, 1);
+        cb.build_osmesa(size_one)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn build_context<T1: ContextCurrentState>(
+        cb: ContextBuilder<T1>,
+    ) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
+        // The surfaceless EGL context is tried first (see HeadlessContext::new_surfaceless),
+        // if that does not work, osmesa, and if that too fails, finally headless (pbuffers).
+        //
+        // If willing, you could attempt to use hidden windows instead of os mesa,
+        // but note that you must handle events for the window that come on the
+        // events loop.
+
+        let _err3 = match build_context_osmesa(cb.clone()) {
+            Ok(ctx) => return Ok(ctx),
+            Err(err) => err,
+        };
+
+        let el = EventLoop::new();
+
+        let err2 = match build_context_headless(cb, &el) {
+            Ok(ctx) => return Ok(ctx),
+            Err(err) => err,
+        };
+
+        Err(err2)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn build_context<T1: ContextCurrentState>(
+        cb: ContextBuilder<T1>,
+    ) -> Result<glutin_029::Context<NotCurrent>, CreationError> {
+        let el = EventLoop::new();
+        build_context_headless(cb.clone(), &el)
+    }
 }
+
+#[doc(inline)]
+pub use inner::*;
@@ -17,6 +17,19 @@ pub use frame_input_generator::*;
 mod windowed_context;
 pub use windowed_context::*;
 
+mod viewer;
+pub use viewer::*;
+
+#[cfg(target_arch = "wasm32")]
+mod web_xr;
+#[cfg(target_arch = "wasm32")]
+pub use web_xr::*;
+
+#[cfg(all(feature = "image", not(target_arch = "wasm32")))]
+mod frame_capture;
+#[cfg(all(feature = "image", not(target_arch = "wasm32")))]
+pub use frame_capture::*;
+
 use thiserror::Error;
 ///
 /// Error associated with a window.
@@ -50,6 +63,10 @@ pub enum WindowError {
     WindowCreation,
     #[error("unable to get document from canvas")]
     DocumentMissing,
+    #[error("settings doesn't contain canvas and DOM doesn't have a canvas element either")]
+    CanvasMissing,
+    #[error("unable to listen to canvas events: {0}")]
+    EventListener(String),
     #[error("unable to convert canvas to html canvas: {0}")]
     CanvasConvertFailed(String),
     #[error("unable to get webgl2 context for the given canvas, maybe the browser doesn't support WebGL2{0}")]
@@ -58,6 +75,10 @@ pub enum WindowError {
     ColorBufferFloatNotSupported(String),
     #[error("unable to get OES_texture_float extension for the given canvas, maybe the browser doesn't support OES_texture_float: {0}")]
     OESTextureFloatNotSupported(String),
+    #[error("sharing resources between graphics contexts is not supported on web")]
+    ContextSharingNotSupported,
+    #[error("unable to start an immersive WebXR session, maybe the browser or device doesn't support WebXR{0}")]
+    WebXrNotSupported(String),
     #[error("error in three-d")]
     ThreeDError(#[from] CoreError),
 }
@@ -77,6 +98,7 @@ pub struct Window {
     gl: WindowedContext,
     #[allow(dead_code)]
     maximized: bool,
+    fixed_frame_rate: Option<u32>,
 }
 
 impl Window {
@@ -95,6 +117,7 @@ impl Window {
         window_settings: WindowSettings,
         event_loop: EventLoop<()>,
     ) -> Result<Self, WindowError> {
+        let fixed_frame_rate = window_settings.fixed_frame_rate;
         #[cfg(not(target_arch = "wasm32"))]
         let window_builder = {
             let window_builder = WindowBuilder::new()
@@ -129,33 +152,38 @@ impl Window {
                 canvas
             } else {
                 web_sys::window()
-                .ok_or(WindowError::WindowCreation)?
-                .document()
-                .ok_or(WindowError::DocumentMissing)?
-                .get_elements_by_tag_name("canvas")
-                .item(0)
-                .expect(
-                    "settings doesn't contain canvas and DOM doesn't have a canvas element either",
-                )
-                .dyn_into::<web_sys::HtmlCanvasElement>()
-                .map_err(|e| WindowError::CanvasConvertFailed(format!("{:?}", e)))?
+                    .ok_or(WindowError::WindowCreation)?
+                    .document()
+                    .ok_or(WindowError::DocumentMissing)?
+                    .get_elements_by_tag_name("canvas")
+                    .item(0)
+                    .ok_or(WindowError::CanvasMissing)?
+                    .dyn_into::<web_sys::HtmlCanvasElement>()
+                    .map_err(|e| WindowError::CanvasConvertFailed(format!("{:?}", e)))?
             };
 
-            let inner_size = window_settings
-                .initial_size
-                .or(window_settings.max_size)
-                .map(|(width, height)| LogicalSize::new(width as f64, height as f64))
-                .unwrap_or_else(|| {
+            let inner_size = match window_settings.initial_size.or(window_settings.max_size) {
+                Some((width, height)) => LogicalSize::new(width as f64, height as f64),
+                None => {
                     let browser_window = canvas
                         .owner_document()
                         .and_then(|doc| doc.default_view())
                         .or_else(web_sys::window)
-                        .unwrap();
+                        .ok_or(WindowError::WindowCreation)?;
                     LogicalSize::new(
-                        browser_window.inner_width().unwrap().as_f64().unwrap(),
-                        browser_window.inner_height().unwrap().as_f64().unwrap(),
+                        browser_window
+                            .inner_width()
+                            .ok()
+                            .and_then(|w| w.as_f64())
+                            .ok_or(WindowError::WindowCreation)?,
+                        browser_window
+                            .inner_height()
+                            .ok()
+                            .and_then(|h| h.as_f64())
+                            .ok_or(WindowError::WindowCreation)?,
                     )
-                });
+                }
+            };
 
             WindowBuilder::new()
                 .with_title(window_settings.title)
@@ -166,12 +194,14 @@ impl Window {
 
         let winit_window = window_builder.build(&event_loop)?;
         winit_window.focus_window();
-        Self::from_winit_window(
+        let mut window = Self::from_winit_window(
             winit_window,
             event_loop,
             window_settings.surface_settings,
             window_settings.max_size.is_none() && window_settings.initial_size.is_none(),
-        )
+        )?;
+        window.set_fixed_frame_rate(fixed_frame_rate);
+        Ok(window)
     }
 
     ///
@@ -202,7 +232,7 @@ impl Window {
             winit_window
                 .canvas()
                 .add_event_listener_with_callback("contextmenu", closure.as_ref().unchecked_ref())
-                .expect("failed to listen to canvas context menu");
+                .map_err(|e| WindowError::EventListener(format!("{:?}", e)))?;
             closure
         };
 
@@ -213,14 +243,23 @@ impl Window {
             #[cfg(target_arch = "wasm32")]
             closure,
             maximized,
+            fixed_frame_rate: None,
         })
     }
 
+    ///
+    /// Sets the fixed frame rate, see [WindowSettings::fixed_frame_rate].
+    ///
+    pub fn set_fixed_frame_rate(&mut self, fixed_frame_rate: Option<u32>) {
+        self.fixed_frame_rate = fixed_frame_rate;
+    }
+
     ///
     /// Start the main render loop which calls the `callback` closure each frame.
     ///
     pub fn render_loop<F: 'static + FnMut(FrameInput) -> FrameOutput>(self, mut callback: F) {
         let mut frame_input_generator = FrameInputGenerator::from_winit_window(&self.window);
+        frame_input_generator.set_fixed_frame_rate(self.fixed_frame_rate);
         self.event_loop
             .run(move |event, _, control_flow| match event {
                 Event::LoopDestroyed => {
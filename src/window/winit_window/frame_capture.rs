@@ -0,0 +1,218 @@
+use crate::core::{Context, PixelReadback, RenderTarget, Viewport};
+use crate::window::{FrameInput, Window};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use thiserror::Error;
+
+///
+/// Error associated with capturing screenshots or recording frames, see [Window::capture_screenshot] and [FrameRecorder].
+///
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+pub enum CaptureError {
+    #[error("failed saving the image")]
+    Image(#[from] image::ImageError),
+    #[error("failed writing the frame")]
+    Io(#[from] std::io::Error),
+    #[error("the size of the recorded frames changed from {0}x{1} to {2}x{3}, which is not supported when piping the frames to an encoder")]
+    SizeChanged(u32, u32, u32, u32),
+}
+
+impl Window {
+    ///
+    /// Saves the current content of the screen of this window as an image at the given path, where the image format is determined by the extension, for example `.png`.
+    /// This should be called after rendering but before the buffers are swapped, so to capture a frame inside [Window::render_loop], use [FrameInput::capture_screenshot] instead.
+    ///
+    pub fn capture_screenshot(&self, path: impl AsRef<Path>) -> Result<(), CaptureError> {
+        capture_screenshot(&self.gl(), self.viewport(), path)
+    }
+}
+
+impl FrameInput {
+    ///
+    /// Saves the content of the screen as an image at the given path, where the image format is determined by the extension, for example `.png`.
+    /// Call this at the end of the render loop callback after everything has been rendered to the screen.
+    /// To capture every frame, use a [FrameRecorder].
+    ///
+    pub fn capture_screenshot(&self, path: impl AsRef<Path>) -> Result<(), CaptureError> {
+        capture_screenshot(&self.context, self.viewport, path)
+    }
+}
+
+fn capture_screenshot(
+    context: &Context,
+    viewport: Viewport,
+    path: impl AsRef<Path>,
+) -> Result<(), CaptureError> {
+    let pixels =
+        RenderTarget::screen(context, viewport.width, viewport.height).read_color::<[u8; 4]>();
+    save_image(path.as_ref(), viewport.width, viewport.height, pixels)
+}
+
+fn save_image(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 4]>,
+) -> Result<(), CaptureError> {
+    if let Some(directory) = path.parent() {
+        if !directory.as_os_str().is_empty() {
+            std::fs::create_dir_all(directory)?;
+        }
+    }
+    image::RgbaImage::from_raw(width, height, pixels.into_iter().flatten().collect())
+        .expect("the number of pixels matches the size of the image")
+        .save(path)?;
+    Ok(())
+}
+
+enum FrameOutputTarget {
+    Images {
+        directory: PathBuf,
+        extension: String,
+    },
+    Encoder(Child),
+}
+
+///
+/// Records every rendered frame, either as a sequence of images or by piping the raw frames to an external encoder, for example [ffmpeg](https://ffmpeg.org),
+/// which makes it easy to produce documentation images and demo videos of any application that uses [Window::render_loop].
+/// Call [FrameRecorder::record] at the end of the render loop callback each frame and [FrameRecorder::finish] when done recording.
+///
+/// The frames are read back asynchronously using a [PixelReadback], so recording does not wait for the GPU to finish rendering each frame.
+///
+/// ```no_run
+/// # use three_d::*;
+/// let window = Window::new(WindowSettings::default()).unwrap();
+/// let mut recorder = Some(FrameRecorder::new("frames"));
+/// window.render_loop(move |frame_input| {
+///     frame_input.screen().clear(ClearState::color(0.8, 0.8, 0.8, 1.0));
+///     if frame_input.accumulated_time < 5000.0 {
+///         recorder.as_mut().unwrap().record(&frame_input).unwrap();
+///     } else if let Some(recorder) = recorder.take() {
+///         recorder.finish().unwrap();
+///     }
+///     FrameOutput::default()
+/// });
+/// ```
+///
+pub struct FrameRecorder {
+    output: FrameOutputTarget,
+    pending: VecDeque<PixelReadback<[u8; 4]>>,
+    size: Option<(u32, u32)>,
+    frame_count: usize,
+}
+
+impl FrameRecorder {
+    ///
+    /// Creates a new recorder which saves each frame as a png image in the given directory, which is created if it does not exist.
+    /// The images are named `frame_00000.png`, `frame_00001.png` and so on.
+    ///
+    pub fn new(directory: impl AsRef<Path>) -> Self {
+        Self::new_with_extension(directory, "png")
+    }
+
+    ///
+    /// Creates a new recorder which saves each frame as an image in the given directory, where the image format is determined by the given extension, for example `"jpg"`.
+    ///
+    pub fn new_with_extension(directory: impl AsRef<Path>, extension: &str) -> Self {
+        Self::new_with_output(FrameOutputTarget::Images {
+            directory: directory.as_ref().to_path_buf(),
+            extension: extension.to_string(),
+        })
+    }
+
+    ///
+    /// Creates a new recorder which writes each frame as raw 8-bit RGBA pixels, from the top row to the bottom row, to the standard input of the given command.
+    /// All frames must have the same size, which must be specified in the command. For example, to encode a 1280x720 video at 60 frames per second using ffmpeg:
+    ///
+    /// ```no_run
+    /// # use three_d::*;
+    /// let mut command = std::process::Command::new("ffmpeg");
+    /// command.args(["-y", "-f", "rawvideo", "-pix_fmt", "rgba", "-s", "1280x720", "-r", "60", "-i", "-", "video.mp4"]);
+    /// let recorder = FrameRecorder::new_with_encoder(command).unwrap();
+    /// ```
+    ///
+    pub fn new_with_encoder(mut command: Command) -> Result<Self, CaptureError> {
+        let child = command.stdin(Stdio::piped()).spawn()?;
+        Ok(Self::new_with_output(FrameOutputTarget::Encoder(child)))
+    }
+
+    fn new_with_output(output: FrameOutputTarget) -> Self {
+        Self {
+            output,
+            pending: VecDeque::new(),
+            size: None,
+            frame_count: 0,
+        }
+    }
+
+    ///
+    /// Records the content of the screen of the current frame. Call this at the end of the render loop callback after everything has been rendered to the screen.
+    /// The frames that have been read back since the last call are written to the output.
+    ///
+    pub fn record(&mut self, frame_input: &FrameInput) -> Result<(), CaptureError> {
+        let viewport = frame_input.viewport;
+        self.pending.push_back(
+            RenderTarget::screen(&frame_input.context, viewport.width, viewport.height)
+                .read_color_async(),
+        );
+        while self.pending.front().map(|p| p.is_ready()).unwrap_or(false) {
+            let readback = self.pending.pop_front().unwrap();
+            self.write(readback)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns the number of frames written to the output so far.
+    ///
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    ///
+    /// Waits for the remaining frames to be read back and written to the output and, if the frames are piped to an encoder, waits for the encoder to finish.
+    /// Returns the total number of recorded frames.
+    ///
+    pub fn finish(mut self) -> Result<usize, CaptureError> {
+        while let Some(readback) = self.pending.pop_front() {
+            self.write(readback)?;
+        }
+        if let FrameOutputTarget::Encoder(child) = &mut self.output {
+            // Closing the standard input signals the end of the stream to the encoder
+            drop(child.stdin.take());
+            child.wait()?;
+        }
+        Ok(self.frame_count)
+    }
+
+    fn write(&mut self, readback: PixelReadback<[u8; 4]>) -> Result<(), CaptureError> {
+        let (width, height) = (readback.width(), readback.height());
+        let pixels = readback.read();
+        match &mut self.output {
+            FrameOutputTarget::Images {
+                directory,
+                extension,
+            } => {
+                let path = directory.join(format!("frame_{:05}.{}", self.frame_count, extension));
+                save_image(&path, width, height, pixels)?;
+            }
+            FrameOutputTarget::Encoder(child) => {
+                if let Some((w, h)) = self.size {
+                    if (w, h) != (width, height) {
+                        Err(CaptureError::SizeChanged(w, h, width, height))?;
+                    }
+                }
+                self.size = Some((width, height));
+                if let Some(stdin) = child.stdin.as_mut() {
+                    stdin.write_all(&pixels.into_iter().flatten().collect::<Vec<_>>())?;
+                }
+            }
+        }
+        self.frame_count += 1;
+        Ok(())
+    }
+}
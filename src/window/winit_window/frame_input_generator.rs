@@ -28,6 +28,7 @@ pub struct FrameInputGenerator {
     secondary_finger_id: Option<u64>,
     modifiers: Modifiers,
     mouse_pressed: Option<MouseButton>,
+    fixed_frame_rate: Option<u32>,
 }
 
 impl FrameInputGenerator {
@@ -52,6 +53,7 @@ impl FrameInputGenerator {
             secondary_finger_id: None,
             modifiers: Modifiers::default(),
             mouse_pressed: None,
+            fixed_frame_rate: None,
         }
     }
 
@@ -62,14 +64,25 @@ impl FrameInputGenerator {
         Self::new(window.inner_size(), window.scale_factor())
     }
 
+    ///
+    /// Sets the fixed frame rate, ie. the time in the generated [FrameInput] advances by exactly one divided by the given number of seconds each frame
+    /// instead of the measured time, see [WindowSettings::fixed_frame_rate](crate::window::WindowSettings::fixed_frame_rate).
+    /// Use `None` to use the measured time, which is the default.
+    ///
+    pub fn set_fixed_frame_rate(&mut self, fixed_frame_rate: Option<u32>) {
+        self.fixed_frame_rate = fixed_frame_rate;
+    }
+
     ///
     /// Generates [FrameInput] for a new frame. This should be called each frame and the generated data should only be used for one frame.
     ///
     pub fn generate(&mut self, context: &Context) -> FrameInput {
         let now = Instant::now();
         let duration = now.duration_since(self.last_time);
-        let elapsed_time =
-            duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 * 1e-6;
+        let elapsed_time = match self.fixed_frame_rate {
+            Some(frame_rate) => 1000.0 / frame_rate.max(1) as f64,
+            None => duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 * 1e-6,
+        };
         self.accumulated_time += elapsed_time;
         self.last_time = now;
 
@@ -85,6 +85,10 @@ pub struct WindowSettings {
     /// If this is `None`, the DOM (`index.html`) must contain a canvas element
     #[cfg(target_arch = "wasm32")]
     pub canvas: Option<web_sys::HtmlCanvasElement>,
+    /// If specified, the time in the [FrameInput](super::FrameInput) advances by exactly one divided by this number of seconds each frame
+    /// instead of the measured time, so that time-based animations produce exactly the same frames each time the application is run,
+    /// for example for image-regression tests. This does not change the rate at which frames are rendered.
+    pub fixed_frame_rate: Option<u32>,
 
     /// Settings related to the surface on where to draw.
     pub surface_settings: SurfaceSettings,
@@ -99,6 +103,7 @@ impl Default for WindowSettings {
             borderless: false,
             #[cfg(target_arch = "wasm32")]
             canvas: None,
+            fixed_frame_rate: None,
             surface_settings: SurfaceSettings::default(),
         }
     }
@@ -0,0 +1,65 @@
+use crate::renderer::*;
+use crate::window::*;
+
+///
+/// A window with a [ModelViewer], so that a simple model viewer can be created in a few lines:
+///
+/// ```no_run
+/// # use three_d::*;
+/// let mut viewer = Viewer::new("Viewer").unwrap();
+/// viewer.load("examples/assets/suzanne.obj").unwrap();
+/// viewer.run();
+/// ```
+///
+/// The viewer sets up a camera which is automatically positioned to show all the models, an [OrbitControl], an ambient light and a directional light with shadows.
+/// Use [ModelViewer::set_environment] to add a skybox and image based lighting from an environment map.
+/// The model viewer can be changed before calling [Viewer::run], which starts the render loop.
+///
+pub struct Viewer {
+    window: Window,
+    model_viewer: ModelViewer,
+}
+
+impl Viewer {
+    ///
+    /// Creates a new window with the given title and a viewer with default settings but nothing to show.
+    ///
+    pub fn new(title: &str) -> Result<Self, WindowError> {
+        let window = Window::new(WindowSettings {
+            title: title.to_string(),
+            max_size: Some((1280, 720)),
+            ..Default::default()
+        })?;
+        let model_viewer = ModelViewer::new(&window.gl(), window.viewport());
+        Ok(Self {
+            window,
+            model_viewer,
+        })
+    }
+
+    ///
+    /// Starts the render loop which shows the models and objects until the window is closed.
+    ///
+    pub fn run(self) {
+        let mut model_viewer = self.model_viewer;
+        self.window.render_loop(move |mut frame_input| {
+            model_viewer.handle_events(frame_input.viewport, &mut frame_input.events);
+            model_viewer.animate(0.001 * frame_input.accumulated_time as f32);
+            model_viewer.render(&frame_input.screen());
+            FrameOutput::default()
+        });
+    }
+}
+
+impl std::ops::Deref for Viewer {
+    type Target = ModelViewer;
+    fn deref(&self) -> &Self::Target {
+        &self.model_viewer
+    }
+}
+
+impl std::ops::DerefMut for Viewer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.model_viewer
+    }
+}
@@ -0,0 +1,247 @@
+use crate::core::{Context, RenderTarget, Viewport};
+use crate::renderer::{EyeView, Mat4, StereoCamera};
+use crate::window::{Window, WindowError, WindowedContext};
+use js_sys::{Array, Float32Array, Function, Promise, Reflect};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+///
+/// An immersive WebXR session, which renders to a VR headset on web, see [WebXrSession::render_loop].
+///
+/// The session is started by [WebXrSession::new] or [Window::web_xr_session], which must be called from the handler of a user gesture,
+/// for example a click on an "Enter VR" button, since the browser otherwise rejects the request.
+///
+pub struct WebXrSession {
+    context: Context,
+    webgl_context: web_sys::WebGl2RenderingContext,
+    session: JsValue,
+    layer: JsValue,
+    reference_space: JsValue,
+}
+
+impl WebXrSession {
+    ///
+    /// Requests an immersive WebXR session for the given context and sets up a WebXR layer, which the context renders to,
+    /// and a local reference space, in which the poses of the eyes are given.
+    ///
+    pub async fn new(context: &WindowedContext) -> Result<Self, WindowError> {
+        let webgl_context: &JsValue = context.webgl_context.as_ref();
+        let xr = get(&get(&js_sys::global(), "navigator")?, "xr")?;
+        if xr.is_undefined() {
+            return Err(WindowError::WebXrNotSupported("".to_string()));
+        }
+        call_async(webgl_context, "makeXRCompatible", &[]).await?;
+        let session = call_async(&xr, "requestSession", &["immersive-vr".into()]).await?;
+
+        let layer = Reflect::construct(
+            get(&js_sys::global(), "XRWebGLLayer")?.unchecked_ref(),
+            &Array::of2(&session, webgl_context),
+        )
+        .map_err(error)?;
+        let render_state = js_sys::Object::new();
+        Reflect::set(&render_state, &"baseLayer".into(), &layer).map_err(error)?;
+        call(&session, "updateRenderState", &[render_state.into()])?;
+        let reference_space =
+            call_async(&session, "requestReferenceSpace", &["local".into()]).await?;
+
+        Ok(Self {
+            context: Context::clone(context),
+            webgl_context: context.webgl_context.clone(),
+            session,
+            layer,
+            reference_space,
+        })
+    }
+
+    ///
+    /// Starts the render loop of the session which calls the `callback` closure each time the headset requests a frame.
+    /// The loop stops when the session is ended, either by the user or by [WebXrSession::end].
+    ///
+    pub fn render_loop<F: 'static + FnMut(WebXrFrame)>(self, mut callback: F) {
+        let request: Rc<RefCell<Option<Closure<dyn FnMut(f64, JsValue)>>>> =
+            Rc::new(RefCell::new(None));
+        let next_request = request.clone();
+        let session = self.session.clone();
+        *request.borrow_mut() = Some(Closure::wrap(Box::new(move |time: f64, frame: JsValue| {
+            if let Ok(Some(frame)) = self.frame(time, &frame) {
+                self.context
+                    .set_screen_framebuffer(frame.framebuffer.clone().map(|framebuffer| {
+                        let webgl_context = self.webgl_context.clone();
+                        Box::new(move |_: &crate::context::Context, target: u32| {
+                            webgl_context.bind_framebuffer(target, Some(&framebuffer));
+                        }) as Box<dyn Fn(&crate::context::Context, u32)>
+                    }));
+                callback(frame);
+                self.context.set_screen_framebuffer(None);
+            }
+            if let Some(request) = next_request.borrow().as_ref() {
+                call(
+                    &self.session,
+                    "requestAnimationFrame",
+                    &[request.as_ref().clone()],
+                )
+                .ok();
+            }
+        }) as Box<dyn FnMut(f64, JsValue)>));
+
+        // The closure is dropped when the session ends, which breaks the reference cycle between the closure and the request
+        let ended = request.clone();
+        let end = Closure::once(move || {
+            ended.borrow_mut().take();
+        });
+        call(
+            &session,
+            "addEventListener",
+            &["end".into(), end.into_js_value()],
+        )
+        .ok();
+        if let Some(request) = request.borrow().as_ref() {
+            call(
+                &session,
+                "requestAnimationFrame",
+                &[request.as_ref().clone()],
+            )
+            .ok();
+        }
+    }
+
+    ///
+    /// Ends the session, which stops the render loop and returns the rendering to the canvas.
+    ///
+    pub fn end(&self) {
+        call(&self.session, "end", &[]).ok();
+    }
+
+    fn frame(&self, time: f64, frame: &JsValue) -> Result<Option<WebXrFrame>, WindowError> {
+        let pose = call(frame, "getViewerPose", &[self.reference_space.clone()])?;
+        if pose.is_null() || pose.is_undefined() {
+            return Ok(None);
+        }
+        let mut views = [None, None];
+        for view in Array::from(&get(&pose, "views")?).iter() {
+            let index = match get(&view, "eye")?.as_string().as_deref() {
+                Some("left") | Some("none") => 0,
+                Some("right") => 1,
+                _ => continue,
+            };
+            let viewport = call(&self.layer, "getViewport", &[view.clone()])?;
+            let viewport = Viewport {
+                x: number(&viewport, "x")? as i32,
+                y: number(&viewport, "y")? as i32,
+                width: number(&viewport, "width")? as u32,
+                height: number(&viewport, "height")? as u32,
+            };
+            let eye_view = EyeView {
+                pose: matrix(&get(&get(&view, "transform")?, "matrix")?),
+                projection: matrix(&get(&view, "projectionMatrix")?),
+            };
+            views[index] = Some((eye_view, viewport));
+        }
+        // A single view, for example on a handheld device, is used for both eyes
+        let Some(left) = views[0] else {
+            return Ok(None);
+        };
+        let right = views[1].unwrap_or(left);
+
+        Ok(Some(WebXrFrame {
+            context: self.context.clone(),
+            framebuffer: get(&self.layer, "framebuffer")?.dyn_into().ok(),
+            width: number(&self.layer, "framebufferWidth")? as u32,
+            height: number(&self.layer, "framebufferHeight")? as u32,
+            views: [left.0, right.0],
+            viewports: [left.1, right.1],
+            time,
+        }))
+    }
+}
+
+///
+/// A frame requested by the headset in the render loop of a [WebXrSession], which contains the views of the eyes and the screen to render to.
+///
+pub struct WebXrFrame {
+    context: Context,
+    framebuffer: Option<web_sys::WebGlFramebuffer>,
+    width: u32,
+    height: u32,
+    views: [EyeView; 2],
+    viewports: [Viewport; 2],
+    /// The time of the frame in milliseconds.
+    pub time: f64,
+}
+
+impl WebXrFrame {
+    ///
+    /// Sets the views of the eye cameras of the given stereo camera to the current views of the headset, see [StereoCamera::set_eye_views],
+    /// and the viewports of the eye cameras to the viewports of the eyes in [WebXrFrame::screen].
+    ///
+    pub fn update_camera(&self, camera: &mut StereoCamera) {
+        camera.set_eye_views(self.views[0], self.views[1]);
+        camera.left.set_viewport(self.viewports[0]);
+        camera.right.set_viewport(self.viewports[1]);
+    }
+
+    ///
+    /// Returns the screen render target of the headset, which contains the viewport of each eye, see [StereoCamera::render].
+    ///
+    pub fn screen(&self) -> RenderTarget<'_> {
+        RenderTarget::screen(&self.context, self.width, self.height)
+    }
+
+    ///
+    /// Returns the graphics context.
+    ///
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+}
+
+impl Window {
+    ///
+    /// Requests an immersive WebXR session for the context of this window, see [WebXrSession::new].
+    ///
+    pub async fn web_xr_session(&self) -> Result<WebXrSession, WindowError> {
+        WebXrSession::new(&self.gl).await
+    }
+}
+
+fn error(e: JsValue) -> WindowError {
+    WindowError::WebXrNotSupported(format!(": {:?}", e))
+}
+
+fn get(value: &JsValue, key: &str) -> Result<JsValue, WindowError> {
+    Reflect::get(value, &key.into()).map_err(error)
+}
+
+fn number(value: &JsValue, key: &str) -> Result<f64, WindowError> {
+    get(value, key)?
+        .as_f64()
+        .ok_or_else(|| WindowError::WebXrNotSupported(format!(": {} is not a number", key)))
+}
+
+fn matrix(value: &JsValue) -> Mat4 {
+    let m = Float32Array::new(value).to_vec();
+    Mat4::new(
+        m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7], m[8], m[9], m[10], m[11], m[12], m[13],
+        m[14], m[15],
+    )
+}
+
+fn call(value: &JsValue, method: &str, args: &[JsValue]) -> Result<JsValue, WindowError> {
+    get(value, method)?
+        .unchecked_into::<Function>()
+        .apply(value, &args.iter().collect::<Array>())
+        .map_err(error)
+}
+
+async fn call_async(
+    value: &JsValue,
+    method: &str,
+    args: &[JsValue],
+) -> Result<JsValue, WindowError> {
+    JsFuture::from(call(value, method, args)?.unchecked_into::<Promise>())
+        .await
+        .map_err(error)
+}
@@ -25,6 +25,7 @@ mod inner {
     /// A context used for rendering
     pub struct WindowedContext {
         pub(super) context: Context,
+        pub(in crate::window) webgl_context: web_sys::WebGl2RenderingContext,
     }
 
     impl WindowedContext {
@@ -50,7 +51,7 @@ mod inner {
                         },
                         alpha: false,
                     })
-                    .unwrap(),
+                    .map_err(|e| WindowError::WebGL2NotSupported(format!(": {:?}", e)))?,
                 )
                 .map_err(|e| WindowError::WebGL2NotSupported(format!(": {:?}", e)))?
                 .ok_or(WindowError::WebGL2NotSupported("".to_string()))?
@@ -68,11 +69,24 @@ mod inner {
 
             Ok(Self {
                 context: Context::from_gl_context(Arc::new(
-                    crate::context::Context::from_webgl2_context(webgl_context),
+                    crate::context::Context::from_webgl2_context(webgl_context.clone()),
                 ))?,
+                webgl_context,
             })
         }
 
+        ///
+        /// Sharing resources between contexts is not supported by WebGL, so this always returns [WindowError::ContextSharingNotSupported].
+        /// Create the resources for each context instead.
+        ///
+        pub fn from_winit_window_with_shared_context(
+            _window: &Window,
+            _settings: SurfaceSettings,
+            _shared_context: &WindowedContext,
+        ) -> Result<Self, WindowError> {
+            Err(WindowError::ContextSharingNotSupported)
+        }
+
         /// Resizes the context
         pub fn resize(&self, _physical_size: winit::dpi::PhysicalSize<u32>) {}
 
@@ -105,14 +119,42 @@ mod inner {
 
     impl WindowedContext {
         /// Creates a new windowed context from a [winit](https://crates.io/crates/winit) window.
-        #[allow(unsafe_code)]
         pub fn from_winit_window(
             window: &Window,
             settings: SurfaceSettings,
+        ) -> Result<Self, WindowError> {
+            Self::new(window, settings, None)
+        }
+
+        ///
+        /// Creates a new windowed context from a [winit](https://crates.io/crates/winit) window which shares resources with the given context,
+        /// so textures and buffers, for example the ones used by a [Mesh](crate::Mesh), created with one of the contexts can be used when rendering with the other,
+        /// which is useful when showing the same scene in several windows. Vertex array objects and framebuffers are not shared by the graphics drivers,
+        /// so each context still has its own [Program] cache, which is initialized with the program binaries of the given context if supported (see [Context::save_program_cache]).
+        ///
+        /// Remember to call [WindowedContext::make_current] before rendering with a context.
+        ///
+        /// [Program]: crate::Program
+        /// [Context::save_program_cache]: crate::Context::save_program_cache
+        ///
+        pub fn from_winit_window_with_shared_context(
+            window: &Window,
+            settings: SurfaceSettings,
+            shared_context: &WindowedContext,
+        ) -> Result<Self, WindowError> {
+            Self::new(window, settings, Some(shared_context))
+        }
+
+        #[allow(unsafe_code)]
+        fn new(
+            window: &Window,
+            settings: SurfaceSettings,
+            shared_context: Option<&WindowedContext>,
         ) -> Result<Self, WindowError> {
             if settings.multisamples > 0 && !settings.multisamples.is_power_of_two() {
                 Err(WindowError::InvalidNumberOfMSAASamples)?;
             }
+            use glutin::display::GetGlDisplay;
             use glutin::prelude::*;
             use raw_window_handle::*;
             let raw_display_handle = window.raw_display_handle();
@@ -138,8 +180,11 @@ mod inner {
             #[cfg(target_os = "android")]
             let preference = glutin::display::DisplayApiPreference::Egl;
 
-            let gl_display =
-                unsafe { glutin::display::Display::new(raw_display_handle, preference)? };
+            // A shared context must be created on the same display as the context it shares resources with
+            let gl_display = match shared_context {
+                Some(shared_context) => shared_context.glutin_context.display(),
+                None => unsafe { glutin::display::Display::new(raw_display_handle, preference)? },
+            };
             let swap_interval = if settings.vsync {
                 glutin::surface::SwapInterval::Wait(std::num::NonZeroU32::new(1).unwrap())
             } else {
@@ -175,8 +220,14 @@ mod inner {
                     .ok_or(WindowError::SurfaceCreationError)?
             };
 
-            let context_attributes =
-                glutin::context::ContextAttributesBuilder::new().build(Some(raw_window_handle));
+            let context_attributes = glutin::context::ContextAttributesBuilder::new();
+            let context_attributes = match shared_context {
+                Some(shared_context) => {
+                    context_attributes.with_sharing(&shared_context.glutin_context)
+                }
+                None => context_attributes,
+            }
+            .build(Some(raw_window_handle));
             // for surface creation.
             let (width, height): (u32, u32) = window.inner_size().into();
             let width = std::num::NonZeroU32::new(width.max(1)).unwrap();
@@ -192,15 +243,26 @@ mod inner {
             let gl_context = gl_context.make_current(&gl_surface)?;
             gl_surface.set_swap_interval(&gl_context, swap_interval)?;
 
-            Ok(Self {
-                context: Context::from_gl_context(Arc::new(unsafe {
-                    crate::context::Context::from_loader_function(|s| {
-                        let s = std::ffi::CString::new(s)
-                            .expect("failed to construct C string from string for gl proc address");
+            let loader = |s: &str| {
+                let s = std::ffi::CString::new(s)
+                    .expect("failed to construct C string from string for gl proc address");
 
-                        gl_display.get_proc_address(&s)
-                    })
-                }))?,
+                gl_display.get_proc_address(&s)
+            };
+            let mut context = Context::from_gl_context(Arc::new(unsafe {
+                crate::context::Context::from_loader_function(loader)
+            }))?;
+            unsafe { context.enable_program_binaries(loader) };
+            #[cfg(all(any(windows, unix), not(target_os = "macos"), not(target_os = "ios")))]
+            enable_shader_compile_thread(&mut context, &gl_display, &config, &gl_context);
+            if let Some(shared_context) = shared_context {
+                if context.supports_program_binaries() {
+                    // The cache is only used to avoid compiling the programs again, so it does not matter if it is rejected
+                    let _ = context.load_program_cache(&shared_context.save_program_cache());
+                }
+            }
+            Ok(Self {
+                context,
                 glutin_context: gl_context,
                 surface: gl_surface,
             })
@@ -223,6 +285,44 @@ mod inner {
             Ok(self.surface.swap_buffers(&self.glutin_context)?)
         }
     }
+
+    ///
+    /// Starts a thread which compiles shader programs using a graphics context which shares resources with the given context, see [Context::enable_shader_compile_thread].
+    /// Only EGL supports making a context current without a surface, so no thread is started if the context is not created with EGL.
+    ///
+    #[cfg(all(any(windows, unix), not(target_os = "macos"), not(target_os = "ios")))]
+    #[allow(unsafe_code, irrefutable_let_patterns)]
+    fn enable_shader_compile_thread(
+        context: &mut Context,
+        gl_display: &glutin::display::Display,
+        config: &glutin::config::Config,
+        gl_context: &glutin::context::PossiblyCurrentContext,
+    ) {
+        use glutin::prelude::*;
+        let (glutin::display::Display::Egl(display), glutin::config::Config::Egl(config)) =
+            (gl_display, config)
+        else {
+            return;
+        };
+        let attributes = glutin::context::ContextAttributesBuilder::new()
+            .with_sharing(gl_context)
+            .build(None);
+        let Ok(compile_context) = (unsafe { display.create_context(config, &attributes) }) else {
+            return;
+        };
+        let display = display.clone();
+        unsafe {
+            context.enable_shader_compile_thread(move || {
+                let compile_context = compile_context.make_current_surfaceless().ok()?;
+                let context = crate::context::Context::from_loader_function(|s| {
+                    std::ffi::CString::new(s)
+                        .map(|s| display.get_proc_address(&s))
+                        .unwrap_or(std::ptr::null())
+                });
+                Some((context, Box::new(compile_context) as Box<dyn std::any::Any>))
+            });
+        }
+    }
 }
 
 pub use inner::*;